@@ -1,14 +1,22 @@
 use crate::expression_analyzer;
 use crate::file_analyzer::FileAnalyzer;
 use crate::functionlike_analyzer::FunctionLikeAnalyzer;
+use crate::property_initialization_analyzer;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::typed_ast::TastInfo;
 use hakana_reflection_info::analysis_result::AnalysisResult;
+use hakana_reflection_info::classlike_info::{ClassLikeInfo, Variance};
 use hakana_reflection_info::codebase_info::symbols::SymbolKind;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
 use hakana_reflection_info::data_flow::graph::{DataFlowGraph, GraphKind};
-use hakana_reflection_info::function_context::FunctionContext;
+use hakana_reflection_info::function_context::{FunctionContext, FunctionLikeIdentifier};
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::member_visibility::MemberVisibility;
+use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
+use hakana_reflection_info::StrId;
 use oxidized::aast;
 
 pub(crate) struct ClassLikeAnalyzer<'a> {
@@ -24,6 +32,7 @@ impl<'a> ClassLikeAnalyzer<'a> {
         &mut self,
         stmt: &aast::Class_<(), ()>,
         statements_analyzer: &StatementsAnalyzer,
+        file_tast_info: &mut TastInfo,
         analysis_result: &mut AnalysisResult,
     ) {
         let resolved_names = self.file_analyzer.resolved_names.clone();
@@ -62,6 +71,10 @@ impl<'a> ClassLikeAnalyzer<'a> {
                 .add_symbol_reference_to_symbol(name.clone(), trait_name.clone(), true);
         }
 
+        if !classlike_storage.template_types.is_empty() {
+            check_variance(stmt, classlike_storage, statements_analyzer, file_tast_info);
+        }
+
         let mut function_context = FunctionContext::new();
         function_context.calling_class = Some(name.clone());
 
@@ -76,6 +89,8 @@ impl<'a> ClassLikeAnalyzer<'a> {
             None,
         );
 
+        tast_info.in_generated_code |= classlike_storage.generated;
+
         for constant in &stmt.consts {
             match &constant.kind {
                 aast::ClassConstKind::CCAbstract(Some(expr))
@@ -108,6 +123,17 @@ impl<'a> ClassLikeAnalyzer<'a> {
             .symbol_references
             .extend(tast_info.symbol_references);
 
+        let config = statements_analyzer.get_config();
+
+        let using_classes = if matches!(classlike_storage.kind, SymbolKind::Trait)
+            && config.analyze_traits_in_using_classes
+            && analysis_result.program_dataflow_graph.kind == GraphKind::FunctionBody
+        {
+            get_using_classes(codebase, &name, config.max_trait_using_classes)
+        } else {
+            vec![]
+        };
+
         for method in &stmt.methods {
             if method.abstract_ || matches!(classlike_storage.kind, SymbolKind::Interface) {
                 continue;
@@ -115,6 +141,402 @@ impl<'a> ClassLikeAnalyzer<'a> {
 
             let mut method_analyzer = FunctionLikeAnalyzer::new(self.file_analyzer);
             method_analyzer.analyze_method(method, classlike_storage, analysis_result);
+
+            for using_class_storage in &using_classes {
+                let mut method_analyzer = FunctionLikeAnalyzer::new(self.file_analyzer);
+                method_analyzer.analyze_trait_method_in_class(
+                    method,
+                    classlike_storage,
+                    using_class_storage,
+                    analysis_result,
+                );
+            }
+        }
+
+        if matches!(classlike_storage.kind, SymbolKind::Class)
+            && analysis_result.program_dataflow_graph.kind == GraphKind::FunctionBody
+        {
+            property_initialization_analyzer::check_constructor(
+                statements_analyzer,
+                stmt,
+                classlike_storage,
+                file_tast_info,
+            );
+        }
+    }
+}
+
+// The classes that use a trait, in a stable order so that the ones analyzed don't change
+// between runs when there are more than max_count
+fn get_using_classes<'a>(
+    codebase: &'a CodebaseInfo,
+    trait_name: &StrId,
+    max_count: usize,
+) -> Vec<&'a ClassLikeInfo> {
+    let mut using_classes = codebase
+        .classlike_infos
+        .values()
+        .filter(|classlike_info| {
+            !matches!(classlike_info.kind, SymbolKind::Trait)
+                && classlike_info.used_traits.contains(trait_name)
+        })
+        .collect::<Vec<_>>();
+
+    using_classes.sort_by(|a, b| {
+        codebase
+            .interner
+            .lookup(&a.name)
+            .cmp(codebase.interner.lookup(&b.name))
+    });
+    using_classes.truncate(max_count);
+
+    using_classes
+}
+
+// Covariant type params (+T) can only be used where values come out of an instance, e.g.
+// return types, and contravariant ones (-T) only where values go in, e.g. parameter types
+fn check_variance(
+    stmt: &aast::Class_<(), ()>,
+    classlike_storage: &ClassLikeInfo,
+    statements_analyzer: &StatementsAnalyzer,
+    tast_info: &mut TastInfo,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    for method in &stmt.methods {
+        // constructors can take covariant params, private methods are only called on
+        // $this, and static methods can't use class params
+        if method.static_
+            || method.name.1 == "__construct"
+            || matches!(method.visibility, aast::Visibility::Private)
+        {
+            continue;
+        }
+
+        let method_name = codebase.interner.get(&method.name.1).unwrap();
+
+        let functionlike_storage =
+            if let Some(functionlike_storage) = classlike_storage.methods.get(&method_name) {
+                functionlike_storage
+            } else {
+                continue;
+            };
+
+        let functionlike_id = Some(FunctionLikeIdentifier::Method(
+            classlike_storage.name,
+            method_name,
+        ));
+
+        for param in &functionlike_storage.params {
+            if let Some(signature_type) = &param.signature_type {
+                let mut invalid_params = vec![];
+
+                find_invalid_variance(
+                    signature_type,
+                    false,
+                    classlike_storage,
+                    codebase,
+                    &mut invalid_params,
+                );
+
+                // inout params are also written to the caller's variable
+                if param.is_inout {
+                    find_invalid_variance(
+                        signature_type,
+                        true,
+                        classlike_storage,
+                        codebase,
+                        &mut invalid_params,
+                    );
+                }
+
+                for (param_name, variance_name) in invalid_params {
+                    tast_info.maybe_add_issue(
+                        Issue::new(
+                            IssueKind::InvalidVariance,
+                            format!(
+                                "{} type parameter {} cannot be used in the type of parameter {}",
+                                variance_name,
+                                codebase.interner.lookup(&param_name),
+                                param.name
+                            ),
+                            param
+                                .signature_type_location
+                                .clone()
+                                .unwrap_or(param.location.clone()),
+                            &functionlike_id,
+                        ),
+                        statements_analyzer.get_config(),
+                        statements_analyzer.get_file_path_actual(),
+                    );
+                }
+            }
+        }
+
+        if let Some(return_type) = &functionlike_storage.return_type {
+            let mut invalid_params = vec![];
+
+            find_invalid_variance(
+                return_type,
+                true,
+                classlike_storage,
+                codebase,
+                &mut invalid_params,
+            );
+
+            for (param_name, variance_name) in invalid_params {
+                tast_info.maybe_add_issue(
+                    Issue::new(
+                        IssueKind::InvalidVariance,
+                        format!(
+                            "{} type parameter {} cannot be used in the return type",
+                            variance_name,
+                            codebase.interner.lookup(&param_name),
+                        ),
+                        functionlike_storage
+                            .return_type_location
+                            .clone()
+                            .unwrap_or(functionlike_storage.def_location.clone()),
+                        &functionlike_id,
+                    ),
+                    statements_analyzer.get_config(),
+                    statements_analyzer.get_file_path_actual(),
+                );
+            }
+        }
+    }
+
+    // properties that can be accessed from outside the class can be both read and written,
+    // so only invariant type params can be used in their types
+    let mut properties = classlike_storage
+        .properties
+        .iter()
+        .filter(|(_, property_storage)| {
+            !property_storage.is_static
+                && !matches!(property_storage.visibility, MemberVisibility::Private)
+        })
+        .collect::<Vec<_>>();
+
+    properties.sort_by_key(|(_, property_storage)| {
+        property_storage
+            .stmt_pos
+            .as_ref()
+            .map(|stmt_pos| stmt_pos.start_offset)
+    });
+
+    for (property_name, property_storage) in properties {
+        let mut invalid_params = vec![];
+
+        for is_output in [true, false] {
+            find_invalid_variance(
+                &property_storage.type_,
+                is_output,
+                classlike_storage,
+                codebase,
+                &mut invalid_params,
+            );
+        }
+
+        let property_pos = if let Some(property_pos) = property_storage
+            .type_pos
+            .as_ref()
+            .or(property_storage.pos.as_ref())
+        {
+            property_pos
+        } else {
+            continue;
+        };
+
+        for (param_name, variance_name) in invalid_params {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::InvalidVariance,
+                    format!(
+                        "{} type parameter {} cannot be used in the type of property ${}",
+                        variance_name,
+                        codebase.interner.lookup(&param_name),
+                        codebase
+                            .interner
+                            .lookup(property_name)
+                            .trim_start_matches('$')
+                    ),
+                    property_pos.clone(),
+                    &None,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+    }
+}
+
+// Finds the class's type params that appear in the given type where their variance doesn't
+// allow. Positions flip for closure params and contravariant type params of other classes.
+fn find_invalid_variance(
+    union: &TUnion,
+    is_output: bool,
+    classlike_storage: &ClassLikeInfo,
+    codebase: &CodebaseInfo,
+    invalid_params: &mut Vec<(StrId, &'static str)>,
+) {
+    for atomic in &union.types {
+        match atomic {
+            TAtomic::TGenericParam {
+                param_name,
+                defining_entity,
+                ..
+            } if *defining_entity == classlike_storage.name => {
+                let variance = classlike_storage
+                    .template_types
+                    .get_index_of(param_name)
+                    .and_then(|index| classlike_storage.generic_variance.get(&index));
+
+                let invalid_param = match (variance, is_output) {
+                    (Some(Variance::Covariant), false) => (*param_name, "Covariant"),
+                    (Some(Variance::Contravariant), true) => (*param_name, "Contravariant"),
+                    _ => continue,
+                };
+
+                if !invalid_params.contains(&invalid_param) {
+                    invalid_params.push(invalid_param);
+                }
+            }
+            TAtomic::TNamedObject {
+                name,
+                type_params: Some(type_params),
+                ..
+            }
+            | TAtomic::TReference {
+                name,
+                type_params: Some(type_params),
+            } => {
+                // type aliases are skipped, as their params have no declared variance
+                let type_classlike_storage =
+                    if let Some(type_classlike_storage) = codebase.classlike_infos.get(name) {
+                        type_classlike_storage
+                    } else {
+                        continue;
+                    };
+
+                for (i, type_param) in type_params.iter().enumerate() {
+                    match type_classlike_storage.generic_variance.get(&i) {
+                        Some(Variance::Covariant) => find_invalid_variance(
+                            type_param,
+                            is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        ),
+                        Some(Variance::Contravariant) => find_invalid_variance(
+                            type_param,
+                            !is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        ),
+                        _ => {
+                            for is_output in [true, false] {
+                                find_invalid_variance(
+                                    type_param,
+                                    is_output,
+                                    classlike_storage,
+                                    codebase,
+                                    invalid_params,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            TAtomic::TVec {
+                known_items,
+                type_param,
+                ..
+            } => {
+                find_invalid_variance(
+                    type_param,
+                    is_output,
+                    classlike_storage,
+                    codebase,
+                    invalid_params,
+                );
+
+                if let Some(known_items) = known_items {
+                    for (_, item_type) in known_items.values() {
+                        find_invalid_variance(
+                            item_type,
+                            is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        );
+                    }
+                }
+            }
+            TAtomic::TDict {
+                known_items,
+                params,
+                ..
+            } => {
+                if let Some((key_param, value_param)) = params {
+                    for type_param in [key_param, value_param] {
+                        find_invalid_variance(
+                            type_param,
+                            is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        );
+                    }
+                }
+
+                if let Some(known_items) = known_items {
+                    for (_, item_type) in known_items.values() {
+                        find_invalid_variance(
+                            item_type,
+                            is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        );
+                    }
+                }
+            }
+            TAtomic::TKeyset { type_param } => find_invalid_variance(
+                type_param,
+                is_output,
+                classlike_storage,
+                codebase,
+                invalid_params,
+            ),
+            TAtomic::TClosure {
+                params,
+                return_type,
+                ..
+            } => {
+                for param in params {
+                    if let Some(signature_type) = &param.signature_type {
+                        find_invalid_variance(
+                            signature_type,
+                            !is_output,
+                            classlike_storage,
+                            codebase,
+                            invalid_params,
+                        );
+                    }
+                }
+
+                if let Some(return_type) = return_type {
+                    find_invalid_variance(
+                        return_type,
+                        is_output,
+                        classlike_storage,
+                        codebase,
+                        invalid_params,
+                    );
+                }
+            }
+            _ => {}
         }
     }
 }
@@ -1,8 +1,10 @@
-use std::{error::Error, fs::File, io::BufReader, path::Path};
+use std::{error::Error, fmt, fs, path::Path};
 
 use rustc_hash::FxHashMap;
 use serde::Deserialize;
 
+use super::GeneratedIssuePolicy;
+
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct JsonConfig {
@@ -16,18 +18,245 @@ pub struct JsonConfig {
     pub allowed_issues: Vec<String>,
     #[serde(default)]
     pub test_files: Vec<String>,
+    #[serde(default)]
+    pub function_aliases: FxHashMap<String, String>,
+    #[serde(default)]
+    pub max_issue_count: Option<usize>,
+    // e.g. {"dict": 1000}, with unset thresholds left at their defaults
+    #[serde(default)]
+    pub literal_collection_thresholds: JsonLiteralCollectionThresholds,
+    #[serde(default)]
+    pub hhi_dir: Option<String>,
+    #[serde(default)]
+    pub hsl_dir: Option<String>,
+    #[serde(default)]
+    pub remote_cache_url: Option<String>,
+    #[serde(default)]
+    pub report_generated_issues_at_origin: bool,
+    #[serde(default)]
+    pub issue_help_uri: Option<String>,
+    #[serde(default)]
+    pub generated_code_issue_policy: FxHashMap<String, GeneratedIssuePolicy>,
+    // regexes matched against header comments, replacing the default of @generated
+    #[serde(default)]
+    pub generated_file_header_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub generated_file_paths: Vec<String>,
+    #[serde(default)]
+    pub type_predicates: FxHashMap<String, String>,
+    #[serde(default)]
+    pub must_handle_null_functions: Vec<String>,
+    #[serde(default)]
+    pub safe_fix_issues: Option<Vec<String>>,
+    #[serde(default)]
+    pub refine_nullable_returns: bool,
+    #[serde(default)]
+    pub find_redundant_computations: bool,
+    // can also be enabled with --topological-order
+    #[serde(default)]
+    pub topological_analysis_order: bool,
+    #[serde(default)]
+    pub analyze_traits_in_using_classes: bool,
+    #[serde(default)]
+    pub max_trait_using_classes: Option<usize>,
+    // profiles defined here replace built-in profiles with the same name
+    #[serde(default)]
+    pub issue_profiles: FxHashMap<String, JsonIssueProfile>,
+    // the path globs each profile applies to
+    #[serde(default)]
+    pub profile_files: FxHashMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct JsonIssueProfile {
+    #[serde(default)]
+    pub disabled_issues: Vec<String>,
+    #[serde(default)]
+    pub disable_mixed_issues: bool,
+    // e.g. {"UnusedParameter": "warning"}
+    #[serde(default)]
+    pub issue_severities: FxHashMap<String, String>,
+    #[serde(default)]
+    pub mixed_issue_severity: Option<String>,
+    #[serde(default)]
+    pub error_severity: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct JsonLiteralCollectionThresholds {
+    #[serde(default)]
+    pub vec: Option<usize>,
+    #[serde(default)]
+    pub dict: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
 pub struct JsonSecurityConfig {
     pub ignore_files: Vec<String>,
     pub ignore_sink_files: FxHashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub categories: FxHashMap<String, JsonTaintCategory>,
+    #[serde(default)]
+    pub sanitizer_predicates: FxHashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub static_properties_as_globals: bool,
+    // severities for issues whose sink is of the given type, e.g. {"Logging": "warning"}
+    #[serde(default)]
+    pub sink_severities: FxHashMap<String, String>,
+    // sinks whose issues are reported as info
+    #[serde(default)]
+    pub informational_sinks: Vec<String>,
+    // e.g. wrappers of AsyncMysqlConnection::queryf, which is always included
+    #[serde(default)]
+    pub query_format_functions: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct JsonTaintCategory {
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub sinks: Vec<String>,
 }
 
-pub(crate) fn read_from_file(path: &Path) -> Result<JsonConfig, Box<dyn Error>> {
-    // Open the file in read-only mode with buffer.
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String, std::io::Error),
+    Parse {
+        path: String,
+        message: String,
+        line: usize,
+        column: usize,
+        suggestion: Option<String>,
+    },
+    InvalidIssueKind(String, String),
+    InvalidTypePredicate(String, String),
+    InvalidSinkSeverity(String, String),
+    UnknownSink(String, String),
+    InvalidGeneratedFilePattern(String, String),
+    InvalidIssueSeverity(String, String),
+    UnknownIssueProfile(String, String),
+    InvalidProfileFilePattern(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, error) => write!(f, "Could not read {}: {}", path, error),
+            ConfigError::Parse {
+                path,
+                message,
+                line,
+                column,
+                suggestion,
+            } => {
+                write!(f, "{}:{}:{}: {}", path, line, column, message)?;
+
+                if let Some(suggestion) = suggestion {
+                    write!(f, " (did you mean `{}`?)", suggestion)?;
+                }
+
+                Ok(())
+            }
+            ConfigError::InvalidIssueKind(path, issue_name) => {
+                write!(f, "{}: unknown issue type {}", path, issue_name)
+            }
+            ConfigError::InvalidTypePredicate(path, type_name) => {
+                write!(f, "{}: unsupported predicate type {}", path, type_name)
+            }
+            ConfigError::InvalidSinkSeverity(path, severity) => {
+                write!(
+                    f,
+                    "{}: unknown sink severity {}, expected error, warning or info",
+                    path, severity
+                )
+            }
+            ConfigError::UnknownSink(path, sink_name) => {
+                write!(f, "{}: unknown sink {}", path, sink_name)
+            }
+            ConfigError::InvalidGeneratedFilePattern(path, pattern) => {
+                write!(f, "{}: invalid generated file pattern {}", path, pattern)
+            }
+            ConfigError::InvalidIssueSeverity(path, severity) => {
+                write!(
+                    f,
+                    "{}: unknown issue severity {}, expected error, warning or info",
+                    path, severity
+                )
+            }
+            ConfigError::UnknownIssueProfile(path, profile_name) => {
+                write!(f, "{}: unknown issue profile {}", path, profile_name)
+            }
+            ConfigError::InvalidProfileFilePattern(path, pattern) => {
+                write!(f, "{}: invalid profile file pattern {}", path, pattern)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+pub(crate) fn read_from_file(path: &Path) -> Result<JsonConfig, ConfigError> {
+    let path_str = path.to_string_lossy().to_string();
+
+    let contents = fs::read_to_string(path).map_err(|e| ConfigError::Io(path_str.clone(), e))?;
+
+    serde_json::from_str(&contents).map_err(|e| {
+        let message = e.to_string();
+
+        // serde_json appends the location, which we report separately
+        let message = if let Some((message, _)) = message.rsplit_once(" at line ") {
+            message.to_string()
+        } else {
+            message
+        };
+
+        ConfigError::Parse {
+            path: path_str,
+            suggestion: get_unknown_field_suggestion(&message),
+            message,
+            line: e.line(),
+            column: e.column(),
+        }
+    })
+}
+
+// serde reports unknown keys as "unknown field `foo`, expected one of `bar`, `baz`",
+// so we can suggest whichever expected key is closest to the one that was given
+fn get_unknown_field_suggestion(message: &str) -> Option<String> {
+    let rest = message.strip_prefix("unknown field ")?;
+
+    let mut names = rest.split('`').skip(1).step_by(2);
+
+    let unknown_field = names.next()?;
+
+    names
+        .map(|name| (get_edit_distance(unknown_field, name), name))
+        .filter(|(distance, name)| *distance <= std::cmp::max(2, name.len() / 3))
+        .min()
+        .map(|(_, name)| name.to_string())
+}
+
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut previous_row = (0..=b_chars.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+
+        for (j, b_char) in b_chars.iter().enumerate() {
+            current_row.push(std::cmp::min(
+                std::cmp::min(current_row[j] + 1, previous_row[j + 1] + 1),
+                previous_row[j] + if a_char == *b_char { 0 } else { 1 },
+            ));
+        }
+
+        previous_row = current_row;
+    }
 
-    Ok(serde_json::from_reader(reader)?)
+    previous_row[b_chars.len()]
 }
@@ -1,13 +1,24 @@
-use std::{path::Path, process::exit};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
 use hakana_reflection_info::{
     data_flow::graph::GraphKind,
-    issue::{Issue, IssueKind},
-    taint::SinkType,
+    issue::{Issue, IssueKind, IssueSeverity},
+    taint::{get_sinks_for_sources, string_to_sink_types, SinkType, SourceType},
+    Interner, LiteralCollectionThresholds, StrId,
 };
+use oxidized::{ast_defs::Pos, prim_defs::Comment};
+use regex::Regex;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
 
 use crate::custom_hook::CustomHook;
+use crate::expr::assertion_finder::get_predicate_type;
+use crate::program_visitor::ProgramVisitor;
+use crate::progress_reporter::ProgressReporter;
+
+use self::json_config::ConfigError;
 
 pub mod json_config;
 
@@ -23,6 +34,19 @@ pub struct Config {
     pub migration_symbols: FxHashSet<(String, String)>,
     pub find_unused_expressions: bool,
     pub find_unused_definitions: bool,
+    pub list_unused_symbols: bool,
+    // delete files where every definition is unused, and `use` statements importing them
+    pub remove_unused_files: bool,
+    // a function, class or method (as Foo::bar) to explain the liveness of
+    pub explain_liveness_of: Option<String>,
+    // a file (relative to root_dir) to keep the inferred types of expressions in
+    pub typed_ast_file: Option<String>,
+    // count how many of the expression types inferred in each file are mixed
+    pub collect_type_coverage: bool,
+    // record parameters typed as shapes, for the shape migration report
+    pub collect_shape_params: bool,
+    // count which functions mixed data comes from, for --show-mixed-function-counts
+    pub collect_mixed_source_counts: bool,
     pub allowed_issues: Option<FxHashSet<IssueKind>>,
     pub allowable_issues: Option<FxHashSet<IssueKind>>,
     pub issues_to_fix: FxHashSet<IssueKind>,
@@ -39,6 +63,144 @@ pub struct Config {
     pub remove_fixmes: bool,
     pub all_custom_issues: FxHashSet<String>,
     pub ast_diff: bool,
+    pub function_aliases: FxHashMap<String, String>,
+    pub compute_dependency_digests: bool,
+    // where to save the dataflow graph and issues before whole-program analysis starts,
+    // so that a run which fails during that phase can be resumed
+    pub whole_program_checkpoint_path: Option<String>,
+    // where to write the whole-program dataflow graph in DOT format, optionally limited
+    // to what's reachable from sources matching dataflow_graph_sources
+    pub dataflow_graph_path: Option<String>,
+    pub dataflow_graph_sources: Vec<String>,
+    pub topological_analysis_order: bool,
+    pub max_issue_count: usize,
+    // the sizes above which literal vecs and dicts get summarized types, which the
+    // scanner and analyzer both use
+    pub literal_collection_thresholds: LiteralCollectionThresholds,
+    pub hhi_dir: Option<String>,
+    pub hsl_dir: Option<String>,
+    // where codebase caches are shared between machines, keyed by build checksum
+    pub remote_cache_url: Option<String>,
+    pub publish_remote_cache: bool,
+    pub report_generated_issues_at_origin: bool,
+    // e.g. https://example.com/hakana/{kind}, where {kind} is replaced with the issue kind
+    pub issue_help_uri: Option<String>,
+    // what to do with issues found in generated functions, classes and files
+    pub generated_issue_policies: FxHashMap<IssueKind, GeneratedIssuePolicy>,
+    pub default_generated_issue_policy: Option<GeneratedIssuePolicy>,
+    // files whose header comments match one of these, or whose path matches one of the
+    // generated_file_paths globs, are generated, along with everything they define
+    pub generated_file_header_patterns: Vec<Regex>,
+    pub generated_file_paths: Vec<glob::Pattern>,
+    // functions that behave like is_int etc., mapped to the type they check for
+    pub type_predicates: FxHashMap<String, String>,
+    // functions and methods whose nullable results must be checked before they're used
+    pub must_handle_null_functions: FxHashSet<String>,
+    // the issues `analyze --fix` fixes, whose fixes can be applied without review
+    pub safe_fix_issues: FxHashSet<IssueKind>,
+    // when None, the CLI progress bar is shown for simple verbosity
+    pub progress_reporter: Option<Arc<dyn ProgressReporter>>,
+    // passes run over the program by downstream tools, during and after analysis
+    pub program_visitors: Vec<Box<dyn ProgramVisitor>>,
+    // analyze twice, so that calls to functions and methods declared to return a nullable
+    // type, but found never to return null, have a non-null type
+    pub refine_nullable_returns: bool,
+    // suggest reusing the results of pure function and static method calls that are
+    // repeated with the same arguments
+    pub find_redundant_computations: bool,
+    // also analyze trait methods as though they were declared in each class using the
+    // trait, for at most max_trait_using_classes classes per trait
+    pub analyze_traits_in_using_classes: bool,
+    pub max_trait_using_classes: usize,
+    // named bundles of issue settings, including the built-in strict, default, legacy and test
+    pub issue_profiles: FxHashMap<String, IssueProfile>,
+    // path globs and the profile their files use. When several match a file, the longest
+    // (and so most specific) one wins
+    pub issue_profile_files: Vec<(glob::Pattern, String)>,
+    // record which profile each analyzed file uses
+    pub collect_issue_profile_files: bool,
+}
+
+// Which issues are reported in a profile's files, and with what severity
+#[derive(Clone, Debug, Default)]
+pub struct IssueProfile {
+    pub disabled_issues: FxHashSet<IssueKind>,
+    pub disable_mixed_issues: bool,
+    pub issue_severities: FxHashMap<IssueKind, IssueSeverity>,
+    pub mixed_issue_severity: Option<IssueSeverity>,
+    // what issues without a severity of their own are reported as, instead of errors
+    pub error_severity: Option<IssueSeverity>,
+}
+
+impl IssueProfile {
+    fn get_builtin_profiles() -> FxHashMap<String, IssueProfile> {
+        FxHashMap::from_iter([
+            // everything is reported as usual
+            ("strict".to_string(), IssueProfile::default()),
+            (
+                "default".to_string(),
+                IssueProfile {
+                    mixed_issue_severity: Some(IssueSeverity::Warning),
+                    ..Default::default()
+                },
+            ),
+            // nothing fails the run
+            (
+                "legacy".to_string(),
+                IssueProfile {
+                    disable_mixed_issues: true,
+                    error_severity: Some(IssueSeverity::Warning),
+                    ..Default::default()
+                },
+            ),
+            // for test code, where comparing floats exactly is usually deliberate
+            (
+                "test".to_string(),
+                IssueProfile {
+                    issue_severities: FxHashMap::from_iter([(
+                        IssueKind::FloatEquality,
+                        IssueSeverity::Info,
+                    )]),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    // None when the issue isn't reported
+    pub fn get_issue_severity(&self, issue: &Issue) -> Option<IssueSeverity> {
+        if self.disabled_issues.contains(&issue.kind)
+            || (self.disable_mixed_issues && issue.kind.is_mixed_issue())
+        {
+            return None;
+        }
+
+        if let Some(severity) = self.issue_severities.get(&issue.kind) {
+            return Some(*severity);
+        }
+
+        if issue.kind.is_mixed_issue() {
+            if let Some(severity) = self.mixed_issue_severity {
+                return Some(severity);
+            }
+        }
+
+        if issue.severity == IssueSeverity::Error {
+            if let Some(severity) = self.error_severity {
+                return Some(severity);
+            }
+        }
+
+        Some(issue.severity)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeneratedIssuePolicy {
+    // report the issue as info, so it doesn't fail the run
+    Downgrade,
+    Drop,
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +208,26 @@ pub struct SecurityConfig {
     ignore_files: Vec<String>,
     ignore_sink_files: FxHashMap<String, Vec<String>>,
     pub max_depth: u8,
+    pub taint_categories: FxHashMap<String, TaintCategory>,
+    pub sanitizer_predicates: FxHashMap<String, FxHashSet<SinkType>>,
+    // Whether array writes to static properties, and accesses via subclasses, flow
+    // through a single per-property node. Off by default as it can add false positives.
+    pub static_properties_as_globals: bool,
+    // keyed by sink config name, e.g. Logging or privacy:Log. Issues for sinks
+    // without an entry are errors
+    pub sink_severities: FxHashMap<String, IssueSeverity>,
+    // functions and methods that take a queryf-style format string as their first
+    // argument, escaping the arguments that its placeholders interpolate
+    pub query_format_functions: FxHashSet<String>,
+}
+
+// A policy domain (e.g. privacy) with its own sources and sinks, checked alongside
+// the built-in security taints. Any of its sources flowing into any of its sinks
+// is an issue.
+#[derive(Clone, Debug, Default)]
+pub struct TaintCategory {
+    pub sources: FxHashSet<String>,
+    pub sinks: FxHashSet<String>,
 }
 
 impl SecurityConfig {
@@ -54,6 +236,67 @@ impl SecurityConfig {
             ignore_files: Vec::new(),
             ignore_sink_files: FxHashMap::default(),
             max_depth: 40,
+            taint_categories: FxHashMap::default(),
+            sanitizer_predicates: FxHashMap::default(),
+            static_properties_as_globals: false,
+            sink_severities: FxHashMap::default(),
+            query_format_functions: FxHashSet::from_iter([
+                "AsyncMysqlConnection::queryf".to_string()
+            ]),
+        }
+    }
+
+    pub fn get_sink_severity(&self, sink_type: &SinkType) -> IssueSeverity {
+        self.sink_severities
+            .get(&sink_type.get_config_name())
+            .copied()
+            .unwrap_or(IssueSeverity::Error)
+    }
+
+    // Whether a name from config refers to a built-in sink, or to a sink of one of
+    // the configured taint categories (written as category:Name)
+    fn is_known_sink(&self, sink_name: &str) -> bool {
+        if let Some((category, name)) = sink_name.split_once(':') {
+            self.taint_categories
+                .get(category)
+                .map_or(false, |taint_category| taint_category.sinks.contains(name))
+        } else {
+            SinkType::from_str(sink_name).is_ok()
+        }
+    }
+
+    // Functions configured to behave as if their first param had a
+    // RemoveTaintsWhenReturningTrue attribute. An empty set of taints
+    // means all user-controllable taints.
+    pub fn get_predicate_removed_taints(
+        &self,
+        functionlike_name: &str,
+        argument_offset: usize,
+    ) -> Option<&FxHashSet<SinkType>> {
+        if argument_offset != 0 {
+            return None;
+        }
+
+        self.sanitizer_predicates.get(functionlike_name)
+    }
+
+    pub fn get_sinks_for_source(&self, source_type: &SourceType) -> FxHashSet<SinkType> {
+        if let SourceType::Category(category_name, source_name) = source_type {
+            if let Some(category) = self.taint_categories.get(category_name) {
+                if category.sources.contains(source_name) {
+                    return category
+                        .sinks
+                        .iter()
+                        .map(|sink_name| {
+                            SinkType::Category(category_name.clone(), sink_name.clone())
+                        })
+                        .collect();
+                }
+            }
+
+            FxHashSet::default()
+        } else {
+            get_sinks_for_sources(source_type)
         }
     }
 }
@@ -64,6 +307,13 @@ impl Config {
             root_dir,
             find_unused_expressions: false,
             find_unused_definitions: false,
+            list_unused_symbols: false,
+            remove_unused_files: false,
+            explain_liveness_of: None,
+            typed_ast_file: None,
+            collect_type_coverage: false,
+            collect_shape_params: false,
+            collect_mixed_source_counts: false,
             ignore_mixed_issues: false,
             allowed_issues: None,
             migration_symbols: FxHashSet::default(),
@@ -80,15 +330,69 @@ impl Config {
             all_custom_issues,
             allowable_issues: None,
             ast_diff: false,
+            function_aliases: FxHashMap::default(),
+            compute_dependency_digests: false,
+            whole_program_checkpoint_path: None,
+            dataflow_graph_path: None,
+            dataflow_graph_sources: vec![],
+            topological_analysis_order: false,
+            max_issue_count: 500_000,
+            literal_collection_thresholds: LiteralCollectionThresholds::default(),
+            hhi_dir: None,
+            hsl_dir: None,
+            remote_cache_url: None,
+            publish_remote_cache: false,
+            report_generated_issues_at_origin: false,
+            issue_help_uri: None,
+            generated_issue_policies: FxHashMap::default(),
+            default_generated_issue_policy: None,
+            generated_file_header_patterns: vec![Regex::new("@generated").unwrap()],
+            generated_file_paths: vec![],
+            type_predicates: FxHashMap::default(),
+            must_handle_null_functions: FxHashSet::default(),
+            safe_fix_issues: FxHashSet::from_iter([
+                IssueKind::EmptyBlock,
+                IssueKind::UnnecessaryShapesIdx,
+                IssueKind::UnusedAssignment,
+            ]),
+            progress_reporter: None,
+            program_visitors: vec![],
+            refine_nullable_returns: false,
+            find_redundant_computations: false,
+            analyze_traits_in_using_classes: false,
+            max_trait_using_classes: 10,
+            issue_profiles: IssueProfile::get_builtin_profiles(),
+            issue_profile_files: vec![],
+            collect_issue_profile_files: false,
         }
     }
 
-    pub fn update_from_file(&mut self, cwd: &String, config_path: &Path) {
-        println!("Loading config from {:?}", config_path);
-        let json_config = json_config::read_from_file(config_path).unwrap_or_else(|e| {
-            println!("{}", e.to_string());
-            exit(1)
-        });
+    // Builds a config from a hakana.json file, with relative paths resolved against root_dir
+    pub fn from_file(
+        root_dir: String,
+        all_custom_issues: FxHashSet<String>,
+        config_path: &Path,
+    ) -> Result<Self, ConfigError> {
+        let mut config = Self::new(root_dir.clone(), all_custom_issues);
+        config.update_from_file(&root_dir, config_path)?;
+        Ok(config)
+    }
+
+    pub fn update_from_file(
+        &mut self,
+        cwd: &String,
+        config_path: &Path,
+    ) -> Result<(), ConfigError> {
+        let json_config = json_config::read_from_file(config_path)?;
+
+        let get_issue_kind = |issue_name: &String| {
+            IssueKind::from_str_custom(issue_name.as_str(), &self.all_custom_issues).map_err(|_| {
+                ConfigError::InvalidIssueKind(
+                    config_path.to_string_lossy().to_string(),
+                    issue_name.clone(),
+                )
+            })
+        };
 
         self.ignore_files = json_config
             .ignore_files
@@ -107,12 +411,12 @@ impl Config {
             .iter()
             .filter(|(k, _)| *k != "*")
             .map(|(k, v)| {
-                (
-                    IssueKind::from_str_custom(k.as_str(), &self.all_custom_issues).unwrap(),
+                Ok((
+                    get_issue_kind(k)?,
                     v.into_iter().map(|v| format!("{}/{}", cwd, v)).collect(),
-                )
+                ))
             })
-            .collect();
+            .collect::<Result<_, ConfigError>>()?;
 
         if let Some(v) = json_config.ignore_issue_files.get("*") {
             self.ignore_all_issues_in_files =
@@ -125,14 +429,180 @@ impl Config {
             Some(
                 json_config
                     .allowed_issues
-                    .into_iter()
-                    .map(|s| {
-                        IssueKind::from_str_custom(s.as_str(), &self.all_custom_issues).unwrap()
-                    })
-                    .collect::<FxHashSet<_>>(),
+                    .iter()
+                    .map(get_issue_kind)
+                    .collect::<Result<FxHashSet<_>, _>>()?,
             )
         };
 
+        // these replace the HHI and HSL files embedded at build time,
+        // e.g. to match a different HHVM release
+        let get_dir_path = |v: String| {
+            if v.starts_with('/') {
+                v
+            } else {
+                format!("{}/{}", cwd, v)
+            }
+        };
+
+        self.hhi_dir = json_config.hhi_dir.map(get_dir_path);
+        self.hsl_dir = json_config.hsl_dir.map(get_dir_path);
+        self.remote_cache_url = json_config.remote_cache_url;
+
+        self.report_generated_issues_at_origin = json_config.report_generated_issues_at_origin;
+        self.issue_help_uri = json_config.issue_help_uri;
+        self.refine_nullable_returns = json_config.refine_nullable_returns;
+        self.find_redundant_computations = json_config.find_redundant_computations;
+        self.topological_analysis_order |= json_config.topological_analysis_order;
+        self.analyze_traits_in_using_classes = json_config.analyze_traits_in_using_classes;
+
+        if let Some(max_trait_using_classes) = json_config.max_trait_using_classes {
+            self.max_trait_using_classes = max_trait_using_classes;
+        }
+
+        for (profile_name, json_profile) in json_config.issue_profiles {
+            let get_severity = |severity: String| {
+                get_issue_severity(&severity).ok_or_else(|| {
+                    ConfigError::InvalidIssueSeverity(
+                        config_path.to_string_lossy().to_string(),
+                        severity,
+                    )
+                })
+            };
+
+            let profile = IssueProfile {
+                disabled_issues: json_profile
+                    .disabled_issues
+                    .iter()
+                    .map(get_issue_kind)
+                    .collect::<Result<_, _>>()?,
+                disable_mixed_issues: json_profile.disable_mixed_issues,
+                issue_severities: json_profile
+                    .issue_severities
+                    .into_iter()
+                    .map(|(k, v)| Ok((get_issue_kind(&k)?, get_severity(v)?)))
+                    .collect::<Result<_, ConfigError>>()?,
+                mixed_issue_severity: json_profile
+                    .mixed_issue_severity
+                    .map(get_severity)
+                    .transpose()?,
+                error_severity: json_profile.error_severity.map(get_severity).transpose()?,
+            };
+
+            self.issue_profiles.insert(profile_name, profile);
+        }
+
+        self.issue_profile_files = vec![];
+
+        for (profile_name, paths) in json_config.profile_files {
+            if !self.issue_profiles.contains_key(&profile_name) {
+                return Err(ConfigError::UnknownIssueProfile(
+                    config_path.to_string_lossy().to_string(),
+                    profile_name,
+                ));
+            }
+
+            for path in paths {
+                let pattern = glob::Pattern::new(&format!("{}/{}", cwd, path)).map_err(|_| {
+                    ConfigError::InvalidProfileFilePattern(
+                        config_path.to_string_lossy().to_string(),
+                        path,
+                    )
+                })?;
+
+                self.issue_profile_files
+                    .push((pattern, profile_name.clone()));
+            }
+        }
+
+        self.generated_issue_policies = json_config
+            .generated_code_issue_policy
+            .iter()
+            .filter(|(k, _)| *k != "*")
+            .map(|(k, v)| Ok((get_issue_kind(k)?, *v)))
+            .collect::<Result<_, ConfigError>>()?;
+
+        self.default_generated_issue_policy =
+            json_config.generated_code_issue_policy.get("*").copied();
+
+        if let Some(header_patterns) = json_config.generated_file_header_patterns {
+            self.generated_file_header_patterns = header_patterns
+                .into_iter()
+                .map(|pattern| {
+                    Regex::new(&pattern).map_err(|_| {
+                        ConfigError::InvalidGeneratedFilePattern(
+                            config_path.to_string_lossy().to_string(),
+                            pattern,
+                        )
+                    })
+                })
+                .collect::<Result<_, ConfigError>>()?;
+        }
+
+        self.generated_file_paths = json_config
+            .generated_file_paths
+            .into_iter()
+            .map(|path| {
+                glob::Pattern::new(&format!("{}/{}", cwd, path)).map_err(|_| {
+                    ConfigError::InvalidGeneratedFilePattern(
+                        config_path.to_string_lossy().to_string(),
+                        path,
+                    )
+                })
+            })
+            .collect::<Result<_, ConfigError>>()?;
+
+        if let Some(max_issue_count) = json_config.max_issue_count {
+            self.max_issue_count = max_issue_count;
+        }
+
+        if let Some(vec_threshold) = json_config.literal_collection_thresholds.vec {
+            self.literal_collection_thresholds.vec = vec_threshold;
+        }
+
+        if let Some(dict_threshold) = json_config.literal_collection_thresholds.dict {
+            self.literal_collection_thresholds.dict = dict_threshold;
+        }
+
+        self.function_aliases = json_config
+            .function_aliases
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k.trim_start_matches('\\').to_string(),
+                    v.trim_start_matches('\\').to_string(),
+                )
+            })
+            .collect();
+
+        self.type_predicates = json_config
+            .type_predicates
+            .into_iter()
+            .map(|(k, v)| {
+                if get_predicate_type(&v).is_none() {
+                    return Err(ConfigError::InvalidTypePredicate(
+                        config_path.to_string_lossy().to_string(),
+                        v,
+                    ));
+                }
+
+                Ok((k.trim_start_matches('\\').to_string(), v))
+            })
+            .collect::<Result<_, ConfigError>>()?;
+
+        self.must_handle_null_functions = json_config
+            .must_handle_null_functions
+            .into_iter()
+            .map(|v| v.trim_start_matches('\\').to_string())
+            .collect();
+
+        if let Some(safe_fix_issues) = json_config.safe_fix_issues {
+            self.safe_fix_issues = safe_fix_issues
+                .iter()
+                .map(get_issue_kind)
+                .collect::<Result<_, _>>()?;
+        }
+
         self.security_config.ignore_files = json_config
             .security_analysis
             .ignore_files
@@ -145,6 +615,126 @@ impl Config {
             .into_iter()
             .map(|(k, v)| (k, v.into_iter().map(|v| format!("{}/{}", cwd, v)).collect()))
             .collect();
+        self.security_config.taint_categories = json_config
+            .security_analysis
+            .categories
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k,
+                    TaintCategory {
+                        sources: v.sources.into_iter().collect(),
+                        sinks: v.sinks.into_iter().collect(),
+                    },
+                )
+            })
+            .collect();
+        self.security_config.sanitizer_predicates = json_config
+            .security_analysis
+            .sanitizer_predicates
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k.trim_start_matches('\\').to_string(),
+                    v.into_iter().flat_map(string_to_sink_types).collect(),
+                )
+            })
+            .collect();
+        self.security_config.static_properties_as_globals =
+            json_config.security_analysis.static_properties_as_globals;
+        self.security_config.query_format_functions.extend(
+            json_config
+                .security_analysis
+                .query_format_functions
+                .into_iter()
+                .map(|v| v.trim_start_matches('\\').to_string()),
+        );
+
+        self.security_config.sink_severities = FxHashMap::default();
+
+        for sink_name in json_config.security_analysis.informational_sinks {
+            if !self.security_config.is_known_sink(&sink_name) {
+                return Err(ConfigError::UnknownSink(
+                    config_path.to_string_lossy().to_string(),
+                    sink_name,
+                ));
+            }
+
+            self.security_config
+                .sink_severities
+                .insert(sink_name, IssueSeverity::Info);
+        }
+
+        for (sink_name, severity) in json_config.security_analysis.sink_severities {
+            if !self.security_config.is_known_sink(&sink_name) {
+                return Err(ConfigError::UnknownSink(
+                    config_path.to_string_lossy().to_string(),
+                    sink_name,
+                ));
+            }
+
+            let severity = if let Some(severity) = get_issue_severity(&severity) {
+                severity
+            } else {
+                return Err(ConfigError::InvalidSinkSeverity(
+                    config_path.to_string_lossy().to_string(),
+                    severity,
+                ));
+            };
+
+            self.security_config
+                .sink_severities
+                .insert(sink_name, severity);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_issue_help_uri(&self, issue_kind: &IssueKind) -> Option<String> {
+        self.issue_help_uri
+            .as_ref()
+            .map(|template| template.replace("{kind}", &issue_kind.to_string()))
+    }
+
+    pub fn get_generated_issue_policy(
+        &self,
+        issue_kind: &IssueKind,
+    ) -> Option<GeneratedIssuePolicy> {
+        if let Some(policy) = self.generated_issue_policies.get(issue_kind) {
+            return Some(*policy);
+        }
+
+        self.default_generated_issue_policy
+    }
+
+    // With an allow-list of issue kinds, analysis that can only find other kinds is skipped
+    fn can_report_any_of(&self, issue_kind_filter: impl Fn(&IssueKind) -> bool) -> bool {
+        if let Some(allowed_issues) = &self.allowed_issues {
+            allowed_issues.iter().any(issue_kind_filter)
+        } else {
+            true
+        }
+    }
+
+    // Whether anything will read the dataflow graphs built for each function body
+    pub fn needs_function_body_graph(&self) -> bool {
+        self.graph_kind != GraphKind::FunctionBody
+            || self.collect_mixed_source_counts
+            // hooks can read the graph, e.g. to find where data comes from for custom issues
+            || !self.hooks.is_empty()
+            || self.can_report_any_of(|issue_kind| matches!(issue_kind, IssueKind::CustomIssue(_)))
+            || (self.find_unused_expressions
+                && self.can_report_any_of(|issue_kind| issue_kind.is_unused_expression()))
+            || self.can_report_any_of(|issue_kind| issue_kind == &IssueKind::ForLoopInvalidation)
+    }
+
+    pub fn needs_unused_definitions(&self) -> bool {
+        self.find_unused_definitions
+            && (self.list_unused_symbols
+                || self.remove_unused_files
+                || self.explain_liveness_of.is_some()
+                || !self.migration_symbols.is_empty()
+                || self.can_report_any_of(|issue_kind| issue_kind.is_unused_definition()))
     }
 
     pub fn can_add_issue(&self, issue: &Issue) -> bool {
@@ -157,6 +747,62 @@ impl Config {
         true
     }
 
+    // Forwarding wrappers can be configured to take on the special-cased semantics
+    // of the function they forward to
+    pub fn resolve_function_alias(&self, function_name: &StrId, interner: &Interner) -> StrId {
+        if let Some(target_name) = self.function_aliases.get(interner.lookup(function_name)) {
+            if let Some(target_id) = interner.get(target_name) {
+                return target_id;
+            }
+        }
+
+        *function_name
+    }
+
+    // Whether an issue found with every issue filter disabled would be reported under this
+    // config. Fixmes and generated code policies are applied during analysis, so they
+    // aren't considered.
+    pub fn reports_issue(&self, issue: &Issue, file: &str) -> bool {
+        self.get_reported_issue_severity(issue, file).is_some()
+    }
+
+    // The severity an issue is reported with once the file's issue profile is applied,
+    // or None if it isn't reported at all. Issues found outside of file analysis (unused
+    // symbols, whole-program taints) go through this too.
+    pub fn get_reported_issue_severity(&self, issue: &Issue, file: &str) -> Option<IssueSeverity> {
+        if self.ignore_mixed_issues && issue.kind.is_mixed_issue() {
+            return None;
+        }
+
+        if !self.can_add_issue(issue)
+            || !self.allow_issue_kind_in_file(&issue.kind, file)
+            || !self.allow_issues_in_file(file)
+        {
+            return None;
+        }
+
+        let severity = if let Some(issue_profile) = self
+            .get_issue_profile_name(file)
+            .and_then(|profile_name| self.issue_profiles.get(profile_name))
+        {
+            issue_profile.get_issue_severity(issue)?
+        } else {
+            issue.severity
+        };
+
+        let file = format!("{}/{}", self.root_dir, file);
+
+        if self
+            .ignore_files
+            .iter()
+            .any(|ignore_file_path| glob::Pattern::new(ignore_file_path).unwrap().matches(&file))
+        {
+            return None;
+        }
+
+        Some(severity)
+    }
+
     pub fn allow_issues_in_file(&self, file: &str) -> bool {
         let file = format!("{}/{}", self.root_dir, file);
 
@@ -169,6 +815,40 @@ impl Config {
         true
     }
 
+    // Only comments in the first ten lines count as header comments
+    pub fn is_generated_file(&self, file: &str, comments: &Vec<(Pos, Comment)>) -> bool {
+        let file = format!("{}/{}", self.root_dir, file);
+
+        if self
+            .generated_file_paths
+            .iter()
+            .any(|generated_file_path| generated_file_path.matches(&file))
+        {
+            return true;
+        }
+
+        comments
+            .iter()
+            .take_while(|(pos, _)| pos.line() <= 10)
+            .any(|(_, comment)| match comment {
+                Comment::CmtLine(text) | Comment::CmtBlock(text) => self
+                    .generated_file_header_patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(text)),
+            })
+    }
+
+    // The name of the issue profile used for a file (relative to root_dir), if any
+    pub fn get_issue_profile_name(&self, file: &str) -> Option<&String> {
+        let file = format!("{}/{}", self.root_dir, file);
+
+        self.issue_profile_files
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(&file))
+            .max_by_key(|(pattern, _)| pattern.as_str().len())
+            .map(|(_, profile_name)| profile_name)
+    }
+
     pub fn allow_issue_kind_in_file(&self, issue_kind: &IssueKind, file: &str) -> bool {
         let file = format!("{}/{}", self.root_dir, file);
 
@@ -194,7 +874,7 @@ impl Config {
     }
 
     pub fn allow_sink_in_file(&self, taint_type: &SinkType, file: &str) -> bool {
-        let str_type = taint_type.to_string();
+        let str_type = taint_type.get_config_name();
         let file = format!("{}/{}", self.root_dir, file);
 
         if let Some(issue_entries) = self.security_config.ignore_sink_files.get(&str_type) {
@@ -208,3 +888,15 @@ impl Config {
         true
     }
 }
+
+fn get_issue_severity(severity: &str) -> Option<IssueSeverity> {
+    match severity {
+        "error" => Some(IssueSeverity::Error),
+        "warning" => Some(IssueSeverity::Warning),
+        "info" => Some(IssueSeverity::Info),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
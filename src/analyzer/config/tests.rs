@@ -0,0 +1,124 @@
+use std::fs;
+
+use super::*;
+
+fn get_config(name: &str, json: &str) -> Result<Config, ConfigError> {
+    let root_dir = std::env::temp_dir()
+        .join(format!("hakana-config-{}-{}", name, std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    fs::create_dir_all(&root_dir).unwrap();
+
+    let config_path = format!("{}/hakana.json", root_dir);
+    fs::write(&config_path, json).unwrap();
+
+    let mut config = Config::new(root_dir.clone(), FxHashSet::default());
+    let result = config
+        .update_from_file(&root_dir, Path::new(&config_path))
+        .map(|_| config);
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    result
+}
+
+#[test]
+fn matches_generated_file_paths() {
+    let config = get_config(
+        "generated-paths",
+        "{\"generated_file_paths\": [\"gen/*.hack\"]}",
+    )
+    .unwrap();
+
+    assert!(config.is_generated_file("gen/a.hack", &vec![]));
+    assert!(!config.is_generated_file("src/a.hack", &vec![]));
+}
+
+#[test]
+fn rejects_invalid_generated_file_paths() {
+    let config = get_config(
+        "invalid-generated-paths",
+        "{\"generated_file_paths\": [\"gen/[.hack\"]}",
+    );
+
+    assert!(matches!(
+        config,
+        Err(ConfigError::InvalidGeneratedFilePattern(_, pattern)) if pattern == "gen/[.hack"
+    ));
+}
+
+#[test]
+fn rejects_invalid_generated_file_header_patterns() {
+    let config = get_config(
+        "invalid-generated-headers",
+        "{\"generated_file_header_patterns\": [\"@generated(\"]}",
+    );
+
+    assert!(matches!(
+        config,
+        Err(ConfigError::InvalidGeneratedFilePattern(_, pattern)) if pattern == "@generated("
+    ));
+}
+
+#[test]
+fn accepts_known_sink_severities() {
+    let config = get_config(
+        "sink-severities",
+        r#"{"security_analysis": {
+            "ignore_files": [],
+            "ignore_sink_files": {},
+            "categories": {"privacy": {"sinks": ["Analytics"]}},
+            "sink_severities": {"Logging": "warning", "privacy:Analytics": "info"}
+        }}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        IssueSeverity::Warning,
+        config.security_config.get_sink_severity(&SinkType::Logging)
+    );
+    assert_eq!(
+        IssueSeverity::Info,
+        config
+            .security_config
+            .get_sink_severity(&SinkType::Category(
+                "privacy".to_string(),
+                "Analytics".to_string()
+            ))
+    );
+}
+
+#[test]
+fn rejects_unknown_sink_severities() {
+    let config = get_config(
+        "unknown-sink-severities",
+        r#"{"security_analysis": {
+            "ignore_files": [],
+            "ignore_sink_files": {},
+            "sink_severities": {"Loging": "warning"}
+        }}"#,
+    );
+
+    assert!(matches!(
+        config,
+        Err(ConfigError::UnknownSink(_, sink_name)) if sink_name == "Loging"
+    ));
+}
+
+#[test]
+fn rejects_unknown_category_sinks() {
+    let config = get_config(
+        "unknown-category-sinks",
+        r#"{"security_analysis": {
+            "ignore_files": [],
+            "ignore_sink_files": {},
+            "categories": {"privacy": {"sinks": ["Analytics"]}},
+            "informational_sinks": ["privacy:Log"]
+        }}"#,
+    );
+
+    assert!(matches!(
+        config,
+        Err(ConfigError::UnknownSink(_, sink_name)) if sink_name == "privacy:Log"
+    ));
+}
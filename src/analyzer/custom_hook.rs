@@ -43,6 +43,14 @@ pub struct AfterArgAnalysisData<'a> {
     pub statements_analyzer: &'a StatementsAnalyzer<'a>,
 }
 
+pub struct AfterFunctionCallAnalysisData<'a> {
+    pub args: &'a Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    pub context: &'a ScopeContext,
+    pub function_call_pos: &'a Pos,
+    pub functionlike_id: &'a FunctionLikeIdentifier,
+    pub statements_analyzer: &'a StatementsAnalyzer<'a>,
+}
+
 pub trait InternalHook {
     fn get_migration_name(&self) -> Option<&str> {
         None
@@ -85,6 +93,22 @@ pub trait InternalHook {
     ) {
     }
 
+    // This hook is run after analysing a call to a function (not a method), once Hakana's
+    // own special-cased functions have been handled, so that knowledge about functions
+    // can be added without changing Hakana. Hooks can add assertions about the arguments
+    // to tast_info.if_true_assertions and if_false_assertions (keyed by the call's
+    // offsets), remove taints via Assertion::RemoveTaints under the "hakana taints" key,
+    // and refine the call's return type. The refined type is the one recorded for the
+    // call, so null tracking and the check for calls that never return both see it.
+    #[allow(unused_variables)]
+    fn after_function_call_analysis(
+        &self,
+        tast_info: &mut TastInfo,
+        after_function_call_analysis_data: AfterFunctionCallAnalysisData,
+        return_type: &mut TUnion,
+    ) {
+    }
+
     #[allow(unused_variables)]
     fn after_functionlike_analysis(
         &self,
@@ -1,3 +1,4 @@
+use hakana_reflection_info::code_location::HPos;
 use hakana_reflection_info::data_flow::node::DataFlowNodeKind;
 use hakana_reflection_info::Interner;
 use hakana_reflection_info::StrId;
@@ -20,13 +21,24 @@ pub fn find_tainted_data(
     config: &Config,
     verbosity: Verbosity,
     interner: &Interner,
+    threads: u8,
 ) -> Vec<Issue> {
     let mut new_issues = vec![];
 
     let sources = graph
         .sources
         .iter()
-        .map(|(_, v)| Arc::new(TaintedNode::from(v)))
+        .map(|(_, v)| {
+            let mut source = TaintedNode::from(v);
+
+            for source_type in &source.taint_sources {
+                source
+                    .taint_sinks
+                    .extend(config.security_config.get_sinks_for_source(source_type));
+            }
+
+            Arc::new(source)
+        })
         .collect::<Vec<_>>();
 
     if !matches!(verbosity, Verbosity::Quiet) {
@@ -50,6 +62,7 @@ pub fn find_tainted_data(
         &mut new_issues,
         true,
         interner,
+        threads,
     );
 
     new_issues
@@ -60,6 +73,7 @@ pub fn find_connections(
     config: &Config,
     verbosity: Verbosity,
     interner: &Interner,
+    threads: u8,
 ) -> Vec<Issue> {
     let mut new_issues = vec![];
 
@@ -88,6 +102,7 @@ pub fn find_connections(
         &mut new_issues,
         false,
         interner,
+        threads,
     );
 
     new_issues
@@ -102,6 +117,7 @@ fn find_paths_to_sinks(
     new_issues: &mut Vec<Issue>,
     match_sinks: bool,
     interner: &Interner,
+    threads: u8,
 ) {
     let mut seen_sources = FxHashSet::default();
 
@@ -117,39 +133,69 @@ fn find_paths_to_sinks(
                 } else {
                     None
                 };
+
+                let is_last = i == config.security_config.max_depth - 1;
+
+                let mut group_size = threads.max(1) as usize;
+
+                if (sources.len() / group_size) < 4 {
+                    group_size = 1;
+                }
+
+                let chunk_size = (sources.len() + group_size - 1) / group_size;
+
+                // each thread only sees the nodes visited in previous steps, so nodes
+                // that more than one thread reached in this step are dropped afterwards
+                let chunk_results = if group_size == 1 {
+                    vec![get_child_nodes_for_sources(
+                        &sources,
+                        graph,
+                        config,
+                        verbosity,
+                        &seen_sources,
+                        is_last,
+                        match_sinks,
+                        interner,
+                    )]
+                } else {
+                    std::thread::scope(|scope| {
+                        let handles = sources
+                            .chunks(chunk_size)
+                            .map(|chunk| {
+                                let seen_sources = &seen_sources;
+
+                                scope.spawn(move || {
+                                    get_child_nodes_for_sources(
+                                        chunk,
+                                        graph,
+                                        config,
+                                        verbosity,
+                                        seen_sources,
+                                        is_last,
+                                        match_sinks,
+                                        interner,
+                                    )
+                                })
+                            })
+                            .collect::<Vec<_>>();
+
+                        handles
+                            .into_iter()
+                            .map(|handle| handle.join().unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                };
+
                 let mut actual_source_count = 0;
                 let mut new_sources = Vec::new();
 
-                for source in sources {
-                    let inow = if matches!(verbosity, Verbosity::Debugging) {
-                        Some(Instant::now())
-                    } else {
-                        None
-                    };
-                    let source_taints = source.taint_sinks.clone();
-                    let source_id = source.id.clone();
-
-                    let generated_sources = get_specialized_sources(graph, source);
-                    actual_source_count += generated_sources.len();
-
-                    for generated_source in generated_sources {
-                        new_sources.extend(get_child_nodes(
-                            graph,
-                            config,
-                            &generated_source,
-                            &source_taints,
-                            &mut seen_sources,
-                            new_issues,
-                            i == config.security_config.max_depth - 1,
-                            match_sinks,
-                            interner,
-                        ))
-                    }
+                for (child_nodes, chunk_issues, chunk_source_count) in chunk_results {
+                    actual_source_count += chunk_source_count;
+                    new_issues.extend(chunk_issues);
 
-                    if let Some(inow) = inow {
-                        let ielapsed = inow.elapsed();
-                        if ielapsed.as_millis() > 100 {
-                            println!("    - took {:.2?} to generate from {}", ielapsed, source_id);
+                    for child_node in child_nodes {
+                        if seen_sources.insert(child_node.get_unique_source_id()) {
+                            new_sources.push(child_node);
                         }
                     }
                 }
@@ -173,6 +219,61 @@ fn find_paths_to_sinks(
     }
 }
 
+// Returns the nodes reached from the given sources, the issues found on the way, and
+// the number of (specialized) sources that were followed
+fn get_child_nodes_for_sources(
+    sources: &[Arc<TaintedNode>],
+    graph: &DataFlowGraph,
+    config: &Config,
+    verbosity: Verbosity,
+    seen_sources: &FxHashSet<String>,
+    is_last: bool,
+    match_sinks: bool,
+    interner: &Interner,
+) -> (Vec<Arc<TaintedNode>>, Vec<Issue>, usize) {
+    let mut new_seen_sources = FxHashSet::default();
+    let mut new_issues = vec![];
+    let mut actual_source_count = 0;
+    let mut new_sources = Vec::new();
+
+    for source in sources {
+        let inow = if matches!(verbosity, Verbosity::Debugging) {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        let source_taints = source.taint_sinks.clone();
+        let source_id = source.id.clone();
+
+        let generated_sources = get_specialized_sources(graph, source.clone());
+        actual_source_count += generated_sources.len();
+
+        for generated_source in generated_sources {
+            new_sources.extend(get_child_nodes(
+                graph,
+                config,
+                &generated_source,
+                &source_taints,
+                seen_sources,
+                &mut new_seen_sources,
+                &mut new_issues,
+                is_last,
+                match_sinks,
+                interner,
+            ))
+        }
+
+        if let Some(inow) = inow {
+            let ielapsed = inow.elapsed();
+            if ielapsed.as_millis() > 100 {
+                println!("    - took {:.2?} to generate from {}", ielapsed, source_id);
+            }
+        }
+    }
+
+    (new_sources, new_issues, actual_source_count)
+}
+
 fn get_specialized_sources(
     graph: &DataFlowGraph,
     source: Arc<TaintedNode>,
@@ -242,7 +343,8 @@ fn get_child_nodes(
     config: &Config,
     generated_source: &Arc<TaintedNode>,
     source_taints: &FxHashSet<SinkType>,
-    seen_sources: &mut FxHashSet<String>,
+    seen_sources: &FxHashSet<String>,
+    new_seen_sources: &mut FxHashSet<String>,
     new_issues: &mut Vec<Issue>,
     is_last: bool,
     match_sinks: bool,
@@ -260,12 +362,15 @@ fn get_child_nodes(
                             target_id,
                             generated_source.get_trace(interner)
                         );
-                        new_issues.push(Issue::new(
+                        let mut issue = Issue::new(
                             IssueKind::TaintedData(t.clone()),
                             message,
                             (**generated_source.pos.as_ref().unwrap()).clone(),
                             &None,
-                        ));
+                        );
+                        issue.severity = config.security_config.get_sink_severity(t);
+                        issue.taint_trace = Some(generated_source.get_trace_steps(interner));
+                        new_issues.push(issue);
                     }
                 }
             }
@@ -329,12 +434,15 @@ fn get_child_nodes(
                                 target_id,
                                 generated_source.get_trace(interner)
                             );
-                            new_issues.push(Issue::new(
+                            let mut issue = Issue::new(
                                 IssueKind::TaintedData(t.clone()),
                                 message,
                                 (**generated_source.pos.as_ref().unwrap()).clone(),
                                 &None,
-                            ));
+                            );
+                            issue.severity = config.security_config.get_sink_severity(t);
+                            issue.taint_trace = Some(generated_source.get_trace_steps(interner));
+                            new_issues.push(issue);
                         }
                     }
                 }
@@ -349,6 +457,24 @@ fn get_child_nodes(
                 new_taints.retain(|t| !removed_taints.contains(t));
             }
 
+            match &path.kind {
+                PathKind::Serialize => {
+                    let html_taints = SinkType::html_taints();
+                    new_taints.retain(|t| !html_taints.contains(t));
+                }
+                PathKind::Unserialize => {
+                    if let Some(serialized_taints) = get_serialized_taints(generated_source) {
+                        let html_taints = SinkType::html_taints();
+                        new_taints.extend(
+                            serialized_taints
+                                .into_iter()
+                                .filter(|t| html_taints.contains(t)),
+                        );
+                    }
+                }
+                _ => {}
+            }
+
             let mut new_destination = TaintedNode::from(destination_node);
 
             new_destination.previous = Some(generated_source.clone());
@@ -393,12 +519,44 @@ fn get_child_nodes(
                                                 matching_taint.get_error_message(),
                                                 new_destination.get_trace(interner)
                                             );
-                                            new_issues.push(Issue::new(
+                                            let mut issue = Issue::new(
                                                 IssueKind::TaintedData(matching_taint.clone()),
                                                 message,
                                                 (**issue_pos).clone(),
                                                 &None,
+                                            );
+
+                                            // lower-priority sinks (e.g. logging) can be
+                                            // configured to not fail the run
+                                            issue.severity = config
+                                                .security_config
+                                                .get_sink_severity(matching_taint);
+
+                                            // positions are left out so the id survives
+                                            // unrelated edits between revisions, but where
+                                            // the flow starts and ends is kept so that a new
+                                            // flow between the same kinds of source and sink
+                                            // isn't mistaken for an existing one
+                                            issue.taint_flow_id = Some(format!(
+                                                "{} in {} -> {} in {} ({})",
+                                                generated_source.get_source_label(),
+                                                get_flow_location_name(
+                                                    graph,
+                                                    new_destination.get_source_pos(),
+                                                    interner
+                                                ),
+                                                new_destination.label,
+                                                get_flow_location_name(
+                                                    graph,
+                                                    Some(&**issue_pos),
+                                                    interner
+                                                ),
+                                                matching_taint.get_config_name()
                                             ));
+                                            issue.taint_trace =
+                                                Some(new_destination.get_trace_steps(interner));
+
+                                            new_issues.push(issue);
                                         }
                                     }
                                 }
@@ -411,11 +569,11 @@ fn get_child_nodes(
 
             let source_id = new_destination.get_unique_source_id();
 
-            if seen_sources.contains(&source_id) {
+            if seen_sources.contains(&source_id) || new_seen_sources.contains(&source_id) {
                 continue;
             }
 
-            seen_sources.insert(source_id);
+            new_seen_sources.insert(source_id);
 
             if !is_last {
                 new_child_nodes.push(Arc::new(new_destination));
@@ -426,6 +584,50 @@ fn get_child_nodes(
     new_child_nodes
 }
 
+// The function or method containing a position, or its file for code outside of one
+fn get_flow_location_name(
+    graph: &DataFlowGraph,
+    pos: Option<&HPos>,
+    interner: &Interner,
+) -> String {
+    if let Some(pos) = pos {
+        if let Some(functionlike_name) = graph.get_enclosing_functionlike(pos) {
+            functionlike_name.clone()
+        } else {
+            interner.lookup(&pos.file_path).to_string()
+        }
+    } else {
+        "unknown".to_string()
+    }
+}
+
+// Data that's unserialized after being serialized in the same path is exactly as
+// dangerous as it was before, so this finds the taints it had at that point
+fn get_serialized_taints(tainted_node: &TaintedNode) -> Option<FxHashSet<SinkType>> {
+    let mut nesting = 0;
+    let mut current_node = tainted_node;
+
+    while let Some(previous_node) = &current_node.previous {
+        match current_node.path_types.last() {
+            Some(PathKind::Unserialize) => {
+                nesting += 1;
+            }
+            Some(PathKind::Serialize) => {
+                if nesting == 0 {
+                    return Some(previous_node.taint_sinks.clone());
+                }
+
+                nesting -= 1;
+            }
+            _ => {}
+        }
+
+        current_node = previous_node;
+    }
+
+    None
+}
+
 fn has_recent_assignment(generated_path_types: &Vec<PathKind>) -> bool {
     let filtered_paths = generated_path_types
         .iter()
@@ -32,6 +32,7 @@ pub(crate) fn analyze(
             class_analyzer.analyze(
                 def.as_class().unwrap(),
                 statements_analyzer,
+                tast_info,
                 analysis_result,
             );
         }
@@ -2,14 +2,19 @@ use super::expression_identifier::{get_dim_id, get_var_id};
 use crate::{formula_generator::AssertionContext, typed_ast::TastInfo};
 use hakana_reflection_info::function_context::FunctionLikeIdentifier;
 use hakana_reflection_info::t_atomic::DictKey;
+use hakana_reflection_info::StrId;
 use hakana_reflection_info::{
     assertion::Assertion,
     data_flow::graph::{DataFlowGraph, GraphKind},
     t_atomic::TAtomic,
-    t_union::populate_union_type,
+    t_union::{populate_union_type, TUnion},
 };
 use hakana_reflector::typehint_resolver::get_type_from_hint;
 use hakana_type::type_expander::{self, TypeExpansionOptions};
+use hakana_type::{
+    get_arraykey, get_bool, get_float, get_int, get_keyset, get_mixed_dict, get_mixed_vec,
+    get_null, get_num, get_scalar, get_string,
+};
 use oxidized::{
     aast,
     aast_defs::Hint,
@@ -130,7 +135,34 @@ pub(crate) fn scrape_assertions(
                     }
                 }
             }
-            ast_defs::Bop::Gt | ast_defs::Bop::Gte => {
+            ast_defs::Bop::Gt | ast_defs::Bop::Gte | ast_defs::Bop::Lt | ast_defs::Bop::Lte => {
+                // matches C\count($foo) > 0 and C\count($foo) >= 1, and the same comparisons
+                // written with the literal first (0 < C\count($foo) and 1 <= C\count($foo))
+                if let Some((var_name, count, literal_position)) =
+                    get_count_comparison(&binop.1, &binop.2, assertion_context)
+                {
+                    let count_is_greater = matches!(
+                        (&binop.0, literal_position),
+                        (
+                            ast_defs::Bop::Gt | ast_defs::Bop::Gte,
+                            OtherValuePosition::Right
+                        ) | (
+                            ast_defs::Bop::Lt | ast_defs::Bop::Lte,
+                            OtherValuePosition::Left
+                        )
+                    );
+
+                    let is_non_empty_check = count_is_greater
+                        && match binop.0 {
+                            ast_defs::Bop::Gt | ast_defs::Bop::Lt => count == 0,
+                            _ => count == 1,
+                        };
+
+                    if is_non_empty_check {
+                        if_types.insert(var_name, vec![vec![Assertion::NonEmptyCountable(true)]]);
+                    }
+                }
+
                 // return scrape_greater_assertions(
                 //     &binop.1,
                 //     &binop.2,
@@ -163,6 +195,17 @@ fn process_custom_assertions(
         .cloned()
         .unwrap_or(FxHashMap::default());
 
+    // keyed by the symbol rather than a variable, so each symbol gets its own clauses
+    if let Some(symbol_name) = tast_info
+        .symbol_existence_checks
+        .get(&(conditional_pos.start_offset(), conditional_pos.end_offset()))
+    {
+        if_true_assertions.insert(
+            symbol_name.clone(),
+            vec![Assertion::SymbolExists(symbol_name.clone())],
+        );
+    }
+
     if if_true_assertions.is_empty() && if_false_assertions.is_empty() {
         return FxHashMap::default();
     }
@@ -327,11 +370,13 @@ fn get_functionlike_id_from_call(
                 } else if boxed_id.1 == "\\in_array" {
                     codebase.interner.get("in_array").unwrap()
                 } else {
-                    assertion_context
-                        .resolved_names
-                        .get(&boxed_id.0.start_offset())
-                        .unwrap()
-                        .clone()
+                    assertion_context.config.resolve_function_alias(
+                        assertion_context
+                            .resolved_names
+                            .get(&boxed_id.0.start_offset())
+                            .unwrap(),
+                        &codebase.interner,
+                    )
                 };
 
                 Some(FunctionLikeIdentifier::Function(name))
@@ -385,6 +430,19 @@ fn scrape_equality_assertions(
         return get_null_equality_assertions(bop, left, right, assertion_context, null_position);
     }
 
+    if let Some((var_name, count, _)) = get_count_comparison(left, right, assertion_context) {
+        let mut if_types = FxHashMap::default();
+        if_types.insert(
+            var_name,
+            vec![vec![if count == 0 {
+                Assertion::EmptyCountable
+            } else {
+                Assertion::HasExactCount(count)
+            }]],
+        );
+        return vec![if_types];
+    }
+
     let true_position = has_true_variable(bop, left, right);
 
     if let Some(true_position) = true_position {
@@ -397,6 +455,12 @@ fn scrape_equality_assertions(
         return get_false_equality_assertions(bop, left, right, assertion_context, false_position);
     }
 
+    if let Some(if_types) =
+        get_class_equality_assertions(left, right, tast_info, assertion_context, false)
+    {
+        return if_types;
+    }
+
     if let Some(typed_value_position) =
         has_typed_value_comparison(left, right, tast_info, assertion_context)
     {
@@ -427,6 +491,19 @@ fn scrape_inequality_assertions(
         return get_null_inequality_assertions(bop, left, right, assertion_context, null_position);
     }
 
+    if let Some((var_name, count, _)) = get_count_comparison(left, right, assertion_context) {
+        let mut if_types = FxHashMap::default();
+        if_types.insert(
+            var_name,
+            vec![vec![if count == 0 {
+                Assertion::NonEmptyCountable(true)
+            } else {
+                Assertion::DoesNotHaveExactCount(count)
+            }]],
+        );
+        return vec![if_types];
+    }
+
     // let true_position = has_true_variable(bop, left, right, source);
 
     // if let Some(true_position) = true_position {}
@@ -435,6 +512,12 @@ fn scrape_inequality_assertions(
 
     // if let Some(false_position) = false_position {}
 
+    if let Some(if_types) =
+        get_class_equality_assertions(left, right, tast_info, assertion_context, true)
+    {
+        return if_types;
+    }
+
     if let Some(typed_value_position) =
         has_typed_value_comparison(left, right, tast_info, assertion_context)
     {
@@ -450,6 +533,164 @@ fn scrape_inequality_assertions(
     Vec::new()
 }
 
+// matches get_class($foo) === Foo::class, $foo::class === $bar::class and the like.
+// Subclasses of Foo have a different class name, so unless Foo is final the assertion
+// is an equality one, whose negation doesn't remove Foo from the type of $foo
+fn get_class_equality_assertions(
+    left: &aast::Expr<(), ()>,
+    right: &aast::Expr<(), ()>,
+    tast_info: &TastInfo,
+    assertion_context: &AssertionContext,
+    negated: bool,
+) -> Option<Vec<FxHashMap<String, Vec<Vec<Assertion>>>>> {
+    let left_object_expr = get_class_of_object_expr(left, assertion_context);
+    let right_object_expr = get_class_of_object_expr(right, assertion_context);
+
+    if left_object_expr.is_none() && right_object_expr.is_none() {
+        return None;
+    }
+
+    let mut if_types = FxHashMap::default();
+
+    for (object_expr, other_expr, other_object_expr) in [
+        (left_object_expr, right, right_object_expr),
+        (right_object_expr, left, left_object_expr),
+    ] {
+        let object_expr = if let Some(object_expr) = object_expr {
+            object_expr
+        } else {
+            continue;
+        };
+
+        let var_name = if let Some(var_name) = get_var_id(
+            object_expr,
+            assertion_context.this_class_name,
+            assertion_context.file_source,
+            assertion_context.resolved_names,
+            assertion_context.codebase,
+        ) {
+            var_name
+        } else {
+            continue;
+        };
+
+        let asserted_class_names = if let Some(other_object_expr) = other_object_expr {
+            // the other object's class is only known up to its subclasses
+            get_object_class_names(tast_info.get_expr_type(other_object_expr.pos()))
+                .map(|class_names| (class_names, false))
+        } else if let Some(TAtomic::TLiteralClassname { name }) = tast_info
+            .get_expr_type(other_expr.pos())
+            .filter(|other_type| other_type.is_single())
+            .map(|other_type| other_type.get_single())
+        {
+            let is_final = if let Some(codebase) = assertion_context.codebase {
+                codebase
+                    .classlike_infos
+                    .get(name)
+                    .map_or(false, |classlike_info| classlike_info.is_final)
+            } else {
+                false
+            };
+
+            Some((vec![*name], is_final))
+        } else {
+            None
+        };
+
+        if let Some((class_names, is_exact)) = asserted_class_names {
+            let orred_types = class_names
+                .into_iter()
+                .map(|name| {
+                    let object_type = TAtomic::TNamedObject {
+                        name,
+                        type_params: None,
+                        is_this: false,
+                        extra_types: None,
+                        remapped_params: false,
+                    };
+
+                    match (is_exact, negated) {
+                        (true, false) => Assertion::IsType(object_type),
+                        (true, true) => Assertion::IsNotType(object_type),
+                        (false, false) => Assertion::IsEqual(object_type),
+                        (false, true) => Assertion::IsNotEqual(object_type),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // a negated assertion is only valid when there's a single class to compare against
+            if !negated || orred_types.len() == 1 {
+                if_types.insert(var_name, vec![orred_types]);
+            }
+        }
+    }
+
+    Some(if if_types.is_empty() {
+        vec![]
+    } else {
+        vec![if_types]
+    })
+}
+
+// Gets $foo from get_class($foo) or $foo::class
+fn get_class_of_object_expr<'a>(
+    expr: &'a aast::Expr<(), ()>,
+    assertion_context: &AssertionContext,
+) -> Option<&'a aast::Expr<(), ()>> {
+    match &expr.2 {
+        aast::Expr_::Call(call) => {
+            if call.2.len() != 1 {
+                return None;
+            }
+
+            if let (Some(FunctionLikeIdentifier::Function(name)), Some(codebase)) = (
+                get_functionlike_id_from_call(call, assertion_context),
+                assertion_context.codebase,
+            ) {
+                if codebase.interner.lookup(&name) == "get_class" {
+                    return Some(&call.2[0].1);
+                }
+            }
+
+            None
+        }
+        aast::Expr_::ClassConst(boxed) => {
+            if boxed.1 .1 != "class" {
+                return None;
+            }
+
+            match &boxed.0 .2 {
+                aast::ClassId_::CIexpr(lhs_expr) => match &lhs_expr.2 {
+                    aast::Expr_::Id(_) => None,
+                    _ => Some(lhs_expr),
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn get_object_class_names(object_type: Option<&TUnion>) -> Option<Vec<StrId>> {
+    let object_type = object_type?;
+
+    let mut class_names = vec![];
+
+    for atomic in &object_type.types {
+        if let TAtomic::TNamedObject { name, .. } = atomic {
+            class_names.push(*name);
+        } else {
+            return None;
+        }
+    }
+
+    if class_names.is_empty() {
+        None
+    } else {
+        Some(class_names)
+    }
+}
+
 // fn has_literal_int_comparison(
 //     left: &aast::Expr<(), ()>,
 //     right: &aast::Expr<(), ()>,
@@ -512,12 +753,29 @@ fn scrape_function_assertions(
                 }
             }
         }
-    } else if function_name == "is_null" {
-        if let Some(first_var_name) = firsts.unwrap().1 {
-            if_types.insert(
-                first_var_name.clone(),
-                vec![vec![Assertion::IsType(TAtomic::TNull)]],
-            );
+    } else if function_name == "HH\\Lib\\C\\is_empty" {
+        if let Some((_, Some(first_var_name), _)) = firsts {
+            if_types.insert(first_var_name, vec![vec![Assertion::EmptyCountable]]);
+        }
+    } else if let Some(predicate_type) = get_builtin_predicate_type(function_name).or_else(|| {
+        assertion_context
+            .config
+            .type_predicates
+            .get(function_name)
+            .map(|v| v.as_str())
+    }) {
+        // is_int($x) narrows $x exactly like $x is int, in both branches
+        if let Some((_, Some(first_var_name), _)) = firsts {
+            if let Some(predicate_type) = get_predicate_type(predicate_type) {
+                if_types.insert(
+                    first_var_name,
+                    vec![predicate_type
+                        .types
+                        .into_iter()
+                        .map(Assertion::IsType)
+                        .collect()],
+                );
+            }
         }
     }
 
@@ -528,6 +786,90 @@ fn scrape_function_assertions(
     vec![if_types]
 }
 
+// matches C\count($foo) compared against a literal int, returning where the literal is
+fn get_count_comparison(
+    left: &aast::Expr<(), ()>,
+    right: &aast::Expr<(), ()>,
+    assertion_context: &AssertionContext,
+) -> Option<(String, usize, OtherValuePosition)> {
+    let codebase = assertion_context.codebase?;
+
+    for (count_expr, literal_expr, literal_position) in [
+        (left, right, OtherValuePosition::Right),
+        (right, left, OtherValuePosition::Left),
+    ] {
+        let count = if let aast::Expr_::Int(value) = &literal_expr.2 {
+            if let Ok(count) = value.parse::<usize>() {
+                count
+            } else {
+                continue;
+            }
+        } else {
+            continue;
+        };
+
+        if let aast::Expr_::Call(call) = &count_expr.2 {
+            if let Some(FunctionLikeIdentifier::Function(function_name)) =
+                get_functionlike_id_from_call(call, assertion_context)
+            {
+                if !matches!(
+                    codebase.interner.lookup(&function_name),
+                    "HH\\Lib\\C\\count" | "count"
+                ) {
+                    continue;
+                }
+
+                if let Some(first_arg) = call.2.first() {
+                    if let Some(var_name) = get_var_id(
+                        &first_arg.1,
+                        assertion_context.this_class_name,
+                        assertion_context.file_source,
+                        assertion_context.resolved_names,
+                        assertion_context.codebase,
+                    ) {
+                        return Some((var_name, count, literal_position));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn get_builtin_predicate_type(function_name: &str) -> Option<&'static str> {
+    Some(match function_name {
+        "is_int" | "is_integer" | "is_long" => "int",
+        "is_string" => "string",
+        "is_float" | "is_double" | "is_real" => "float",
+        "is_bool" => "bool",
+        "is_null" => "null",
+        "is_scalar" => "scalar",
+        "HH\\is_vec" | "is_vec" => "vec",
+        "HH\\is_dict" | "is_dict" => "dict",
+        "HH\\is_keyset" | "is_keyset" => "keyset",
+        _ => return None,
+    })
+}
+
+// The types that a predicate function can be configured to check for
+pub(crate) fn get_predicate_type(type_name: &str) -> Option<TUnion> {
+    Some(match type_name {
+        "int" => get_int(),
+        "string" => get_string(),
+        "float" => get_float(),
+        "bool" => get_bool(),
+        "null" => get_null(),
+        "num" => get_num(),
+        "arraykey" => get_arraykey(false),
+        "scalar" => get_scalar(),
+        "vec" => get_mixed_vec(),
+        "dict" => get_mixed_dict(),
+        "keyset" => get_keyset(get_arraykey(true)),
+        _ => return None,
+    })
+}
+
 fn has_null_variable(
     _bop: &ast_defs::Bop,
     left: &aast::Expr<(), ()>,
@@ -24,6 +24,8 @@ use crate::{
     expr::{expression_identifier, fetch::array_fetch_analyzer},
     typed_ast::TastInfo,
 };
+
+use super::static_property_assignment_analyzer;
 use crate::{expression_analyzer, scope_analyzer::ScopeAnalyzer};
 use crate::{scope_context::ScopeContext, statements_analyzer::StatementsAnalyzer};
 
@@ -97,6 +99,24 @@ pub(crate) fn analyze(
         &mut current_type,
     );
 
+    if let aast::Expr_::ClassGet(boxed) = &root_array_expr.2 {
+        if matches!(tast_info.data_flow_graph.kind, GraphKind::WholeProgram(_))
+            && statements_analyzer
+                .get_config()
+                .security_config
+                .static_properties_as_globals
+        {
+            static_property_assignment_analyzer::add_array_assignment_dataflow(
+                statements_analyzer,
+                (&boxed.0, &boxed.1),
+                pos,
+                &root_type,
+                tast_info,
+                context,
+            );
+        }
+    }
+
     if tast_info.data_flow_graph.kind == GraphKind::FunctionBody {
         if let Some(root_var_id) = &root_var_id {
             if let aast::Expr_::Lvar(_) = &root_array_expr.2 {
@@ -308,6 +328,7 @@ fn update_atomic_given_key(
                 ref mut known_items,
                 ref mut type_param,
                 ref mut known_count,
+                ref mut is_tuple,
                 ..
             } => {
                 *type_param = hakana_type::add_union_type(
@@ -319,6 +340,7 @@ fn update_atomic_given_key(
 
                 *known_items = None;
                 *known_count = None;
+                *is_tuple = false;
             }
             TAtomic::TKeyset {
                 ref mut type_param, ..
@@ -484,6 +506,7 @@ fn update_array_assignment_child_type(
                     },
                     known_count: None,
                     non_empty: true,
+                    is_tuple: false,
                 }),
                 TAtomic::TDict { known_items, .. } => collection_types.push(TAtomic::TDict {
                     params: Some((key_type.clone(), value_type.clone())),
@@ -527,6 +550,7 @@ fn update_array_assignment_child_type(
                         )])),
                         known_count: None,
                         non_empty: true,
+                        is_tuple: false,
                     }
                 } else {
                     TAtomic::TVec {
@@ -534,6 +558,7 @@ fn update_array_assignment_child_type(
                         known_items: None,
                         known_count: None,
                         non_empty: true,
+                        is_tuple: false,
                     }
                 }),
                 TAtomic::TDict { .. } => {
@@ -22,7 +22,7 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use crate::{
     expr::{
         call::argument_analyzer::get_removed_taints_in_comments, expression_identifier,
-        fetch::atomic_property_fetch_analyzer::localize_property_type,
+        fetch::atomic_property_fetch_analyzer::localize_property_type, readonly_analyzer,
     },
     typed_ast::TastInfo,
 };
@@ -276,6 +276,19 @@ pub(crate) fn analyze_regular_assignment(
             );
         }
 
+        if lhs_type.readonly {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::ReadonlyMismatch,
+                    "Properties of a readonly object can't be modified".to_string(),
+                    statements_analyzer.get_hpos(&expr.1 .1),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+
         for lhs_type_part in &lhs_type.types {
             if let TAtomic::TNull { .. } = lhs_type_part {
                 continue;
@@ -423,6 +436,25 @@ pub(crate) fn analyze_atomic_assignment(
             .get(declaring_property_class)
             .unwrap();
 
+        if !declaring_classlike_storage
+            .properties
+            .get(&prop_name)
+            .map_or(false, |property_storage| property_storage.is_readonly)
+        {
+            readonly_analyzer::check_mutable_use(
+                statements_analyzer,
+                assign_value_type,
+                format!(
+                    "Property {}::${} isn't readonly, so it can't be assigned a readonly value",
+                    codebase.interner.lookup(&property_id.0),
+                    codebase.interner.lookup(&property_id.1),
+                ),
+                expr.1.pos(),
+                tast_info,
+                context,
+            );
+        }
+
         // TODO trackPropertyImpurity and mutatable/immtable states
         let mut class_property_type =
             if let Some(prop_type) = codebase.get_property_type(&fq_class_name, &prop_name) {
@@ -277,3 +277,62 @@ pub(crate) fn analyze(
 
     true
 }
+
+// Array writes to a static property (e.g. `self::$cache[$k] = $v`) change the value
+// every later reader sees, so they get the same dataflow as a direct assignment
+pub(crate) fn add_array_assignment_dataflow(
+    statements_analyzer: &StatementsAnalyzer,
+    expr: (&ClassId<(), ()>, &ClassGetExpr<(), ()>),
+    assign_value_pos: &Pos,
+    assign_value_type: &TUnion,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let classlike_name = if let aast::ClassId_::CIexpr(lhs_expr) = &expr.0 .2 {
+        if let aast::Expr_::Id(id) = &lhs_expr.2 {
+            get_id_name(
+                id,
+                &context.function_context.calling_class,
+                codebase,
+                &mut false,
+                statements_analyzer.get_file_analyzer().resolved_names,
+            )
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let (classlike_name, prop_name, stmt_name_pos) =
+        if let (Some(classlike_name), aast::ClassGetExpr::CGstring(str)) = (classlike_name, expr.1)
+        {
+            if let Some(prop_name) = codebase.interner.get(&str.1[1..]) {
+                (classlike_name, prop_name, &str.0)
+            } else {
+                return;
+            }
+        } else {
+            return;
+        };
+
+    // writes via a subclass reach reads via the declaring class, and vice versa
+    let classlike_name = codebase
+        .get_declaring_class_for_property(&classlike_name, &prop_name)
+        .copied()
+        .unwrap_or(classlike_name);
+
+    add_unspecialized_property_assignment_dataflow(
+        statements_analyzer,
+        &(classlike_name, prop_name),
+        stmt_name_pos,
+        Some(assign_value_pos),
+        tast_info,
+        assign_value_type,
+        codebase,
+        &classlike_name,
+        prop_name,
+    );
+}
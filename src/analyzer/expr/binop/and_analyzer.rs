@@ -152,6 +152,7 @@ pub(crate) fn analyze<'expr, 'map, 'new_expr, 'tast>(
         &changed_var_ids,
     );
     right_context.clauses = partitioned_clauses.0;
+    right_context.inside_conditional_operand = true;
 
     if !expression_analyzer::analyze(
         statements_analyzer,
@@ -13,6 +13,7 @@ use crate::expr::expression_identifier;
 use crate::expr::expression_identifier::get_root_var_id;
 use crate::expr::expression_identifier::get_var_id;
 use crate::expr::fetch::array_fetch_analyzer;
+use crate::expr::nullable_return_analyzer;
 use crate::expression_analyzer;
 use crate::formula_generator;
 use crate::scope_analyzer::ScopeAnalyzer;
@@ -27,7 +28,7 @@ use hakana_reflection_info::data_flow::node::DataFlowNode;
 use hakana_reflection_info::data_flow::path::PathKind;
 use hakana_reflection_info::issue::Issue;
 use hakana_reflection_info::issue::IssueKind;
-use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_atomic::{DictKey, TAtomic};
 use hakana_reflection_info::t_union::TUnion;
 use hakana_reflection_info::taint::SinkType;
 use hakana_type::add_union_type;
@@ -61,6 +62,14 @@ pub(crate) fn analyze(
 
     //let removed_taints = Vec::new();
 
+    if let Some(root_var_id) = get_root_var_id(
+        assign_var,
+        context.function_context.calling_class.as_ref(),
+        Some(statements_analyzer.get_file_analyzer().get_file_source()),
+    ) {
+        context.remove_var_alias(&root_var_id);
+    }
+
     let mut existing_var_type = None;
 
     if let Some(var_id) = &var_id {
@@ -69,6 +78,7 @@ pub(crate) fn analyze(
             .assigned_var_ids
             .insert(var_id.clone(), assign_var.pos().start_offset());
         context.possibly_assigned_var_ids.insert(var_id.clone());
+        context.reassigned_foreach_value_vars.remove(var_id);
 
         existing_var_type = context.vars_in_scope.get(var_id).cloned();
     }
@@ -358,6 +368,17 @@ pub(crate) fn analyze(
         }
     };
 
+    // $b = $a makes each an alias of the other until either is written to
+    if let (Bop::Eq(None), Some(var_id), Some(assign_value)) = (binop, &var_id, assign_value) {
+        if let (aast::Expr_::Lvar(_), aast::Expr_::Lvar(source_var)) =
+            (&assign_var.2, &assign_value.2)
+        {
+            if context.vars_in_scope.contains_key(&source_var.1 .1) {
+                context.add_var_alias(var_id, &source_var.1 .1);
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -441,15 +462,16 @@ fn analyze_list_assignment(
                 if let Some(known_items) = known_items {
                     if let Some((possibly_undefined, value_type)) = known_items.get(&offset) {
                         if *possibly_undefined {
-                            tast_info.maybe_add_issue(
-                                Issue::new(
-                                    IssueKind::PossiblyUndefinedIntArrayOffset,
-                                    "Possibly undefined offset in list assignment".to_string(),
-                                    statements_analyzer.get_hpos(&assign_var_item.1),
-                                    &context.function_context.calling_functionlike_id,
-                                ),
-                                statements_analyzer.get_config(),
-                                statements_analyzer.get_file_path_actual(),
+                            array_fetch_analyzer::handle_possibly_undefined_offset(
+                                statements_analyzer,
+                                &assign_var_item.1,
+                                tast_info,
+                                context,
+                                assign_value_atomic_type,
+                                &DictKey::Int(offset as u32),
+                                false,
+                                false,
+                                &mut false,
                             );
                         }
 
@@ -591,6 +613,8 @@ fn analyze_assignment_to_variable(
         }
     }
 
+    nullable_return_analyzer::handle_assignment(var_id, source_expr, tast_info);
+
     let assign_value_type = check_variable_or_property_assignment(
         statements_analyzer,
         assign_value_type,
@@ -62,6 +62,7 @@ pub(crate) fn analyze<'expr, 'map, 'new_expr, 'tast>(
     let mut replacement_left = None;
 
     if has_arrayget_key
+        || has_nullsafe_fetch(left)
         || matches!(
             root_expr.2,
             aast::Expr_::Call(..)
@@ -147,6 +148,20 @@ pub(crate) fn analyze<'expr, 'map, 'new_expr, 'tast>(
     true
 }
 
+// In chains like $a?->b?->c each hop's type depends on the hop before, which isset
+// assertions on the whole chain can't express precisely, so the chain is analyzed once
+// and null is removed from its result
+fn has_nullsafe_fetch(expr: &aast::Expr<(), ()>) -> bool {
+    match &expr.2 {
+        aast::Expr_::ObjGet(boxed) => {
+            matches!(boxed.2, oxidized::ast_defs::OgNullFlavor::OGNullsafe)
+                || has_nullsafe_fetch(&boxed.0)
+        }
+        aast::Expr_::ArrayGet(boxed) => has_nullsafe_fetch(&boxed.0),
+        _ => false,
+    }
+}
+
 fn get_left_expr(
     context: &mut ScopeContext,
     statements_analyzer: &StatementsAnalyzer,
@@ -53,9 +53,8 @@ pub(crate) fn analyze<'expr, 'map, 'new_expr, 'tast>(
         if let Some(expr_type) = expr_type {
             all_literals = all_literals && expr_type.all_literals();
 
-            if let Some(str) = expr_type
-                .get_single_literal_string_value(&statements_analyzer.get_codebase().interner)
-            {
+            // a literal, or a string known to start with a literal prefix
+            if let Some(str) = expr_type.get_string_prefix() {
                 if str.contains("/") {
                     has_slash = true;
                 }
@@ -0,0 +1,115 @@
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::t_union::TUnion;
+use oxidized::ast_defs::{self, Bop};
+use oxidized::{aast, pos::Pos};
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// Checks comparisons against NAN, which are always false (or always true for != and !==),
+// and equality checks between floats, which rounding errors make unreliable
+pub(crate) fn analyze(
+    statements_analyzer: &StatementsAnalyzer,
+    bop: &ast_defs::Bop,
+    left_expr: &aast::Expr<(), ()>,
+    right_expr: &aast::Expr<(), ()>,
+    left_type: &TUnion,
+    right_type: &TUnion,
+    pos: &Pos,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+) {
+    if is_nan(statements_analyzer, left_expr) || is_nan(statements_analyzer, right_expr) {
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::NanComparison,
+                format!(
+                    "Comparisons with NAN are always {}, use is_nan() instead",
+                    if matches!(bop, Bop::Diff | Bop::Diff2) {
+                        "true"
+                    } else {
+                        "false"
+                    }
+                ),
+                statements_analyzer.get_hpos(pos),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+
+        return;
+    }
+
+    if !matches!(bop, Bop::Eqeq | Bop::Eqeqeq | Bop::Diff | Bop::Diff2) {
+        return;
+    }
+
+    // === never holds between a float and an int, but == compares them numerically
+    let is_float_comparison = if matches!(bop, Bop::Eqeqeq | Bop::Diff2) {
+        left_type.has_float() && right_type.has_float()
+    } else {
+        (left_type.has_float() && (right_type.has_float() || right_type.has_int()))
+            || (right_type.has_float() && left_type.has_int())
+    };
+
+    if !is_float_comparison {
+        return;
+    }
+
+    // zero is exact, so checks against it (e.g. before dividing) are fine, as are
+    // comparisons between two literals
+    if is_zero_literal(left_expr)
+        || is_zero_literal(right_expr)
+        || (is_float_literal(left_expr) && is_float_literal(right_expr))
+    {
+        return;
+    }
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            IssueKind::FloatEquality,
+            "Floats should not be compared for equality because of rounding errors, \
+            compare their difference with a tolerance instead"
+                .to_string(),
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
+fn is_nan(statements_analyzer: &StatementsAnalyzer, expr: &aast::Expr<(), ()>) -> bool {
+    if let aast::Expr_::Id(id) = &expr.2 {
+        if let Some(name) = statements_analyzer
+            .get_file_analyzer()
+            .resolved_names
+            .get(&id.0.start_offset())
+        {
+            return statements_analyzer.get_codebase().interner.lookup(name) == "NAN";
+        }
+    }
+
+    false
+}
+
+fn is_zero_literal(expr: &aast::Expr<(), ()>) -> bool {
+    match &expr.2 {
+        aast::Expr_::Float(value) => value.parse::<f64>().map_or(false, |value| value == 0.0),
+        aast::Expr_::Int(value) => value == "0",
+        _ => false,
+    }
+}
+
+fn is_float_literal(expr: &aast::Expr<(), ()>) -> bool {
+    match &expr.2 {
+        aast::Expr_::Float(_) => true,
+        aast::Expr_::Unop(boxed) => {
+            matches!(boxed.0, ast_defs::Uop::Uminus) && is_float_literal(&boxed.1)
+        }
+        _ => false,
+    }
+}
@@ -3,4 +3,5 @@ pub(crate) mod arithmetic_analyzer;
 pub(crate) mod assignment_analyzer;
 pub(crate) mod coalesce_analyzer;
 pub(crate) mod concat_analyzer;
+pub(crate) mod float_comparison_analyzer;
 pub(crate) mod or_analyzer;
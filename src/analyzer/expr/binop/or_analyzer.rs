@@ -230,6 +230,7 @@ pub(crate) fn analyze<'expr, 'map, 'new_expr, 'tast>(
 
     let pre_assigned_var_ids = right_context.assigned_var_ids.clone();
     right_context.assigned_var_ids = FxHashMap::default();
+    right_context.inside_conditional_operand = true;
 
     if !expression_analyzer::analyze(
         statements_analyzer,
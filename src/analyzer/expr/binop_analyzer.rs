@@ -1,3 +1,5 @@
+use crate::expr::binop::float_comparison_analyzer;
+use crate::expr::missing_await_analyzer;
 use crate::expression_analyzer::{self, add_decision_dataflow};
 use crate::scope_context::ScopeContext;
 use crate::statements_analyzer::StatementsAnalyzer;
@@ -112,6 +114,39 @@ pub(crate) fn analyze(
                 return false;
             }
 
+            if let (Some(left_type), Some(right_type)) = (
+                tast_info.get_expr_type(expr.1.pos()).cloned(),
+                tast_info.get_expr_type(expr.2.pos()).cloned(),
+            ) {
+                missing_await_analyzer::check_comparison_operand(
+                    statements_analyzer,
+                    expr.1,
+                    &left_type,
+                    &right_type,
+                    context,
+                    tast_info,
+                );
+                missing_await_analyzer::check_comparison_operand(
+                    statements_analyzer,
+                    expr.2,
+                    &right_type,
+                    &left_type,
+                    context,
+                    tast_info,
+                );
+                float_comparison_analyzer::analyze(
+                    statements_analyzer,
+                    expr.0,
+                    expr.1,
+                    expr.2,
+                    &left_type,
+                    &right_type,
+                    pos,
+                    context,
+                    tast_info,
+                );
+            }
+
             add_decision_dataflow(
                 statements_analyzer,
                 tast_info,
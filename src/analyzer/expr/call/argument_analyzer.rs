@@ -1,7 +1,10 @@
+use crate::config::SecurityConfig;
 use crate::custom_hook::AfterArgAnalysisData;
 use crate::expr::fetch::array_fetch_analyzer::{
     handle_array_access_on_dict, handle_array_access_on_vec,
 };
+use crate::expr::missing_await_analyzer;
+use crate::expr::readonly_analyzer;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
 use crate::statements_analyzer::StatementsAnalyzer;
@@ -71,6 +74,21 @@ pub(crate) fn check_argument_matches(
         );
     }
 
+    if !function_param.is_readonly {
+        readonly_analyzer::check_mutable_use(
+            statements_analyzer,
+            &arg_value_type,
+            format!(
+                "Argument {} of {} isn't readonly, so it can't be passed a readonly value",
+                argument_offset + 1,
+                functionlike_id.to_string(&statements_analyzer.get_codebase().interner)
+            ),
+            arg.1.pos(),
+            tast_info,
+            context,
+        );
+    }
+
     self::verify_type(
         statements_analyzer,
         &arg_value_type,
@@ -110,7 +128,7 @@ fn get_unpacked_type(
                 &get_arraykey(false),
                 false,
                 &mut has_valid_expected_offset,
-                context.inside_isset,
+                context.inside_isset || context.inside_unset,
                 &mut false,
                 &mut false,
             ),
@@ -123,6 +141,8 @@ fn get_unpacked_type(
                 get_int(),
                 false,
                 &mut has_valid_expected_offset,
+                context.inside_isset || context.inside_unset,
+                &mut false,
             ),
             TAtomic::TKeyset { type_param } => {
                 has_valid_expected_offset = true;
@@ -425,6 +445,19 @@ pub(crate) fn verify_type(
     }
 
     if !type_match_found && !union_comparison_result.type_coerced.unwrap_or(false) {
+        if missing_await_analyzer::check_argument(
+            statements_analyzer,
+            input_expr,
+            &input_type,
+            &param_type,
+            context,
+            tast_info,
+        )
+        .is_some()
+        {
+            return true;
+        }
+
         let types_can_be_identical = union_type_comparator::can_expression_types_be_identical(
             codebase,
             &input_type,
@@ -715,7 +748,14 @@ fn add_dataflow(
     let removed_taints = if data_flow_graph.kind == GraphKind::FunctionBody {
         FxHashSet::default()
     } else {
-        get_removed_taints_in_comments(statements_analyzer, input_expr.pos())
+        let mut removed_taints =
+            get_removed_taints_in_comments(statements_analyzer, input_expr.pos());
+
+        if let Some(inferred_removed_taints) = &function_param.inferred_removed_taints {
+            removed_taints.extend(inferred_removed_taints.iter().cloned());
+        }
+
+        removed_taints
     };
     // TODO add plugin hooks for adding/removing taints
 
@@ -750,7 +790,12 @@ fn add_dataflow(
     if data_flow_graph.kind == GraphKind::FunctionBody {
         data_flow_graph.add_node(argument_value_node);
     } else {
-        let mut taints = get_argument_taints(functionlike_id, argument_offset, &codebase.interner);
+        let mut taints = get_argument_taints(
+            functionlike_id,
+            argument_offset,
+            &codebase.interner,
+            &statements_analyzer.get_config().security_config,
+        );
 
         if let Some(sinks) = &function_param.taint_sinks {
             taints.extend(sinks.clone());
@@ -827,7 +872,17 @@ fn get_argument_taints(
     function_id: &FunctionLikeIdentifier,
     arg_offset: usize,
     interner: &Interner,
+    security_config: &SecurityConfig,
 ) -> Vec<SinkType> {
+    // every argument can end up in the query, but most are escaped first
+    // (see get_sanitized_argument_offsets)
+    if security_config
+        .query_format_functions
+        .contains(&function_id.to_string(interner))
+    {
+        return vec![SinkType::Sql];
+    }
+
     match function_id {
         FunctionLikeIdentifier::Function(id) => match interner.lookup(id) {
             "echo" | "print" | "var_dump" => {
@@ -889,7 +944,8 @@ fn get_argument_taints(
         },
         FunctionLikeIdentifier::Method(fq_class, method_name) => {
             match (interner.lookup(fq_class), interner.lookup(method_name)) {
-                ("AsyncMysqlConnection", "query") => {
+                ("AsyncMysqlConnection", "query" | "multiQuery")
+                | ("PDO", "query" | "exec" | "prepare") => {
                     if arg_offset == 0 {
                         return vec![SinkType::Sql];
                     }
@@ -901,3 +957,57 @@ fn get_argument_taints(
 
     return vec![];
 }
+
+// Arguments that a built-in function escapes before using them in a way that
+// would otherwise make them sinks. Prepared statement params (e.g. those passed
+// to PDOStatement::execute) aren't sinks in the first place.
+pub(crate) fn get_sanitized_argument_offsets(
+    functionlike_id: &FunctionLikeIdentifier,
+    args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    interner: &Interner,
+    security_config: &SecurityConfig,
+) -> FxHashSet<usize> {
+    if security_config
+        .query_format_functions
+        .contains(&functionlike_id.to_string(interner))
+    {
+        if let Some((_, aast::Expr(_, _, aast::Expr_::String(format_string)))) = args.first() {
+            return get_escaped_query_placeholder_offsets(&format_string.to_string());
+        }
+    }
+
+    FxHashSet::default()
+}
+
+// The offsets of the arguments that a queryf format string interpolates using
+// escaping placeholders like %s, %d and %=s. %Q inserts raw SQL, so isn't one.
+fn get_escaped_query_placeholder_offsets(format_string: &str) -> FxHashSet<usize> {
+    let mut escaped_offsets = FxHashSet::default();
+
+    // the format string is the first argument
+    let mut arg_offset = 1;
+
+    let mut chars = format_string.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        let is_escaped = match chars.next() {
+            Some('%') => continue,
+            Some('s' | 'd' | 'f' | 'm' | 'C' | 'T') => true,
+            Some('=') => matches!(chars.next(), Some('s' | 'd' | 'f' | 'm')),
+            Some('L') => matches!(chars.next(), Some('s' | 'd' | 'f' | 'm' | 'C')),
+            _ => false,
+        };
+
+        if is_escaped {
+            escaped_offsets.insert(arg_offset);
+        }
+
+        arg_offset += 1;
+    }
+
+    escaped_offsets
+}
@@ -24,7 +24,7 @@ use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_reflection_info::functionlike_info::FunctionLikeInfo;
 use hakana_reflection_info::functionlike_parameter::{DefaultType, FunctionLikeParameter};
 use hakana_reflection_info::t_atomic::TAtomic;
-use hakana_reflection_info::t_union::{populate_union_type, TUnion};
+use hakana_reflection_info::t_union::{populate_union_type, TUnion, TypeNode};
 use hakana_reflector::typehint_resolver::get_type_from_hint;
 use hakana_type::template::{
     self, inferred_type_replacer, standin_type_replacer, TemplateBound, TemplateResult,
@@ -81,6 +81,21 @@ pub(crate) fn check_arguments_match(
                 &mut tast_info.symbol_references,
             );
 
+            // reified type arguments can be instantiated or otherwise used by the callee
+            // (e.g. via T::class), so every class they name counts as used by the caller
+            for type_node in param_type.get_all_child_nodes() {
+                if let TypeNode::Atomic(
+                    TAtomic::TNamedObject { name, .. } | TAtomic::TEnum { name, .. },
+                ) = type_node
+                {
+                    tast_info.symbol_references.add_reference_to_symbol(
+                        &context.function_context,
+                        *name,
+                        false,
+                    );
+                }
+            }
+
             if let Some((template_name, map)) = template_result.template_types.get_index(i) {
                 template_result.lower_bounds.insert(
                     template_name.clone(),
@@ -228,6 +243,18 @@ pub(crate) fn check_arguments_match(
             .cloned()
             .unwrap_or(get_mixed_any());
 
+        if let aast::Expr_::EnumClassLabel(_) = arg_expr.2 {
+            if let Some(resolved_label_type) = resolve_enum_class_label_arg(
+                &param_type,
+                &arg_value_type,
+                template_result,
+                codebase,
+            ) {
+                tast_info.set_expr_type(arg_expr.pos(), resolved_label_type.clone());
+                arg_value_type = resolved_label_type;
+            }
+        }
+
         if let aast::Expr_::Lfun(_) | aast::Expr_::Efun(_) = arg_expr.2 {
             handle_closure_arg(
                 statements_analyzer,
@@ -352,6 +379,18 @@ pub(crate) fn check_arguments_match(
         }
     }
 
+    let sanitized_argument_offsets =
+        if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
+            argument_analyzer::get_sanitized_argument_offsets(
+                functionlike_id,
+                args,
+                &codebase.interner,
+                &statements_analyzer.get_config().security_config,
+            )
+        } else {
+            FxHashSet::default()
+        };
+
     for (argument_offset, (param_kind, arg_expr)) in reordered_args {
         let function_param = if let Some(function_param) = function_params.get(argument_offset) {
             function_param
@@ -415,7 +454,8 @@ pub(crate) fn check_arguments_match(
             arg_value_type,
             context,
             tast_info,
-            functionlike_info.ignore_taint_path,
+            functionlike_info.ignore_taint_path
+                || sanitized_argument_offsets.contains(&argument_offset),
             functionlike_info.specialize_call,
             function_call_pos,
         ) {
@@ -423,7 +463,21 @@ pub(crate) fn check_arguments_match(
         }
 
         if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
-            if let Some(removed_taints) = &function_param.removed_taints_when_returning_true {
+            let removed_taints = if let Some(removed_taints) =
+                &function_param.removed_taints_when_returning_true
+            {
+                Some(removed_taints)
+            } else {
+                statements_analyzer
+                    .get_config()
+                    .security_config
+                    .get_predicate_removed_taints(
+                        &functionlike_id.to_string(&codebase.interner),
+                        argument_offset,
+                    )
+            };
+
+            if let Some(removed_taints) = removed_taints {
                 if let Some(expr_var_id) = expression_identifier::get_var_id(
                     arg_expr,
                     None,
@@ -492,6 +546,83 @@ pub(crate) fn check_arguments_match(
     true
 }
 
+// A label without a class name (e.g. #A) gets its enum from the param it's passed to,
+// either directly or via a template bound earlier args have already inferred
+fn resolve_enum_class_label_arg(
+    param_type: &TUnion,
+    arg_value_type: &TUnion,
+    template_result: &TemplateResult,
+    codebase: &CodebaseInfo,
+) -> Option<TUnion> {
+    let member_name = if let TAtomic::TEnumClassLabel {
+        class_name: None,
+        member_name,
+    } = arg_value_type.get_single()
+    {
+        member_name
+    } else {
+        return None;
+    };
+
+    let label_name = codebase.interner.get("HH\\EnumClass\\Label")?;
+
+    for param_atomic in &param_type.types {
+        if let TAtomic::TTypeAlias {
+            name,
+            type_params: Some(type_params),
+            ..
+        } = param_atomic
+        {
+            if name != &label_name {
+                continue;
+            }
+
+            let enum_type = match type_params.get(0)?.get_single() {
+                TAtomic::TGenericParam {
+                    param_name,
+                    defining_entity,
+                    as_type,
+                    ..
+                } => {
+                    if let Some(bounds) = template_result
+                        .lower_bounds
+                        .get(param_name)
+                        .and_then(|bounds_by_entity| bounds_by_entity.get(defining_entity))
+                    {
+                        standin_type_replacer::get_most_specific_type_from_bounds(bounds, codebase)
+                    } else {
+                        as_type.clone()
+                    }
+                }
+                enum_type => wrap_atomic(enum_type.clone()),
+            };
+
+            if !enum_type.is_single() {
+                continue;
+            }
+
+            if let TAtomic::TNamedObject {
+                name: enum_name, ..
+            } = enum_type.get_single()
+            {
+                if codebase
+                    .classlike_infos
+                    .get(enum_name)
+                    .map(|classlike_info| classlike_info.constants.contains_key(member_name))
+                    .unwrap_or(false)
+                {
+                    return Some(wrap_atomic(TAtomic::TEnumClassLabel {
+                        class_name: Some(*enum_name),
+                        member_name: *member_name,
+                    }));
+                }
+            }
+        }
+    }
+
+    None
+}
+
 fn adjust_param_type(
     class_generic_params: &IndexMap<StrId, FxHashMap<StrId, Arc<TUnion>>>,
     param_type: &mut TUnion,
@@ -134,20 +134,26 @@ pub(crate) fn analyze(
     let method_name = codebase.interner.get(&expr.1 .1);
 
     if method_name.is_none() || !codebase.method_exists(&classlike_name, &method_name.unwrap()) {
-        tast_info.maybe_add_issue(
-            Issue::new(
-                IssueKind::NonExistentMethod,
-                format!(
-                    "Method {}::{} does not exist",
-                    codebase.interner.lookup(&classlike_name),
-                    &expr.1 .1
+        if !context
+            .asserted_existing_symbols
+            .contains(codebase.interner.lookup(&classlike_name))
+            || codebase.classlike_infos.contains_key(&classlike_name)
+        {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::NonExistentMethod,
+                    format!(
+                        "Method {}::{} does not exist",
+                        codebase.interner.lookup(&classlike_name),
+                        &expr.1 .1
+                    ),
+                    statements_analyzer.get_hpos(&pos),
+                    &context.function_context.calling_functionlike_id,
                 ),
-                statements_analyzer.get_hpos(&pos),
-                &context.function_context.calling_functionlike_id,
-            ),
-            statements_analyzer.get_config(),
-            statements_analyzer.get_file_path_actual(),
-        );
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
 
         tast_info.expr_effects.insert(
             (pos.start_offset(), pos.end_offset()),
@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use hakana_reflection_info::analysis_result::Replacement;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::method_identifier::MethodIdentifier;
 use hakana_reflection_info::StrId;
@@ -24,8 +25,10 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
     expr::{
-        call_analyzer::check_method_args, expression_identifier,
-        fetch::array_fetch_analyzer::handle_array_access_on_dict,
+        call_analyzer::{apply_functionlike_assertions, check_method_args},
+        expression_identifier,
+        fetch::array_fetch_analyzer::{add_array_fetch_dataflow, handle_array_access_on_dict},
+        nullable_return_analyzer, redundant_computation_analyzer,
     },
     scope_analyzer::ScopeAnalyzer,
     scope_context::ScopeContext,
@@ -35,7 +38,7 @@ use crate::{
 
 use super::{
     atomic_method_call_analyzer::AtomicMethodCallAnalysisResult, class_template_param_collector,
-    method_call_return_type_fetcher,
+    function_call_analyzer, method_call_return_type_fetcher,
 };
 
 pub(crate) fn analyze(
@@ -180,6 +183,19 @@ pub(crate) fn analyze(
         return get_mixed_any();
     }
 
+    // calling a method whose contexts make it impure is already a typechecker error, but a
+    // method declared pure can still call impure functions
+    if functionlike_storage.inferred_impure && !functionlike_storage.effects.is_impure() {
+        function_call_analyzer::check_impure_call(
+            statements_analyzer,
+            &FunctionLikeIdentifier::Method(declaring_method_id.0, declaring_method_id.1),
+            functionlike_storage,
+            pos,
+            tast_info,
+            context,
+        );
+    }
+
     if functionlike_storage.ignore_taints_if_true {
         tast_info.if_true_assertions.insert(
             (pos.start_offset(), pos.end_offset()),
@@ -187,6 +203,15 @@ pub(crate) fn analyze(
         );
     }
 
+    apply_functionlike_assertions(
+        statements_analyzer,
+        tast_info,
+        functionlike_storage,
+        call_expr.1,
+        context,
+        pos,
+    );
+
     if codebase.interner.lookup(&method_id.0) == "HH\\Shapes" {
         if let Some(value) = handle_shapes_static_method(
             &method_id,
@@ -216,6 +241,36 @@ pub(crate) fn analyze(
         pos,
     );
 
+    nullable_return_analyzer::track_call(
+        statements_analyzer,
+        &[
+            FunctionLikeIdentifier::Method(method_id.0, method_id.1),
+            FunctionLikeIdentifier::Method(declaring_method_id.0, declaring_method_id.1),
+        ],
+        &return_type_candidate,
+        pos,
+        tast_info,
+        context,
+    );
+
+    // instance methods can depend on the object's state
+    if functionlike_storage
+        .method_info
+        .as_ref()
+        .map_or(false, |method_info| method_info.is_static)
+    {
+        redundant_computation_analyzer::check_call(
+            statements_analyzer,
+            &FunctionLikeIdentifier::Method(declaring_method_id.0, declaring_method_id.1),
+            functionlike_storage,
+            call_expr.1,
+            call_expr.2,
+            pos,
+            tast_info,
+            context,
+        );
+    }
+
     // todo check method visibility
 
     // todo support if_this_is type
@@ -408,6 +463,19 @@ fn handle_shapes_static_method(
                         }
                     }
 
+                    // the value flows from the dict through the fetched key only, so
+                    // taints on its other fields aren't inherited
+                    if let Some(expr_type) = &mut expr_type {
+                        add_array_fetch_dataflow(
+                            statements_analyzer,
+                            &call_expr.1[0].1,
+                            tast_info,
+                            None,
+                            expr_type,
+                            &mut (*dim_type).clone(),
+                        );
+                    }
+
                     if (is_nullable || has_possibly_undefined) && call_expr.1.len() > 2 {
                         let default_type = tast_info.get_expr_type(call_expr.1[2].1.pos());
                         expr_type = if let Some(expr_type) = expr_type {
@@ -7,9 +7,13 @@ use hakana_type::{get_arrayish_params, get_void};
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::rc::Rc;
 
+use crate::custom_hook::AfterFunctionCallAnalysisData;
 use crate::expr::call::arguments_analyzer;
-use crate::expr::call_analyzer::check_template_result;
-use crate::expr::{echo_analyzer, exit_analyzer, expression_identifier, isset_analyzer};
+use crate::expr::call_analyzer::{apply_functionlike_assertions, check_template_result};
+use crate::expr::{
+    echo_analyzer, exit_analyzer, expression_identifier, isset_analyzer, nullable_return_analyzer,
+    redundant_computation_analyzer,
+};
 use crate::reconciler::reconciler;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
@@ -104,24 +108,43 @@ pub(crate) fn analyze(
         panic!()
     };
 
+    let alias_name = name;
+
+    let name = statements_analyzer
+        .get_config()
+        .resolve_function_alias(&alias_name, &codebase.interner);
+
+    if name != alias_name {
+        tast_info.symbol_references.add_reference_to_symbol(
+            &context.function_context,
+            alias_name,
+            false,
+        );
+    }
+
     let function_storage = if let Some(function_storage) =
         get_named_function_info(statements_analyzer, &name, expr.0 .0)
     {
         function_storage
     } else {
-        tast_info.maybe_add_issue(
-            Issue::new(
-                IssueKind::NonExistentFunction,
-                format!(
-                    "Function {} is not defined",
-                    codebase.interner.lookup(&name)
+        if !context
+            .asserted_existing_symbols
+            .contains(codebase.interner.lookup(&name))
+        {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::NonExistentFunction,
+                    format!(
+                        "Function {} is not defined",
+                        codebase.interner.lookup(&name)
+                    ),
+                    statements_analyzer.get_hpos(&expr.0 .0),
+                    &context.function_context.calling_functionlike_id,
                 ),
-                statements_analyzer.get_hpos(&expr.0 .0),
-                &context.function_context.calling_functionlike_id,
-            ),
-            statements_analyzer.get_config(),
-            statements_analyzer.get_file_path_actual(),
-        );
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
 
         return false;
     };
@@ -193,6 +216,27 @@ pub(crate) fn analyze(
         _ => (),
     }
 
+    if function_storage.is_non_deterministic {
+        check_non_deterministic_call(
+            statements_analyzer,
+            function_storage,
+            pos,
+            tast_info,
+            context,
+        );
+    }
+
+    if function_storage.inferred_impure {
+        check_impure_call(
+            statements_analyzer,
+            &functionlike_id,
+            function_storage,
+            pos,
+            tast_info,
+            context,
+        );
+    }
+
     for arg in expr.2 {
         tast_info.combine_effects(arg.1.pos(), pos, pos);
     }
@@ -213,28 +257,21 @@ pub(crate) fn analyze(
         );
     }
 
-    check_template_result(
+    apply_functionlike_assertions(
         statements_analyzer,
-        &mut template_result,
-        pos,
-        &functionlike_id,
-    );
-
-    let stmt_type = function_call_return_type_fetcher::fetch(
-        statements_analyzer,
-        expr,
-        pos,
-        &functionlike_id,
-        function_storage,
-        template_result,
         tast_info,
+        function_storage,
+        expr.2,
         context,
+        pos,
     );
 
-    tast_info.set_expr_type(&pos, stmt_type.clone());
-
-    if stmt_type.is_nothing() && !context.inside_loop {
-        context.has_returned = true;
+    if let Some(symbol_name) =
+        get_existence_check_symbol(statements_analyzer, &name, expr.2, tast_info)
+    {
+        tast_info
+            .symbol_existence_checks
+            .insert((pos.start_offset(), pos.end_offset()), symbol_name);
     }
 
     let real_name = codebase.interner.lookup(&name);
@@ -354,67 +391,89 @@ pub(crate) fn analyze(
         }
         "HH\\Lib\\Str\\starts_with" => {
             if expr.2.len() == 2 {
-                if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
-                    let expr_var_id = expression_identifier::get_var_id(
-                        &expr.2[0].1,
-                        context.function_context.calling_class.as_ref(),
-                        statements_analyzer.get_file_analyzer().get_file_source(),
-                        resolved_names,
-                        Some(statements_analyzer.get_codebase()),
-                    );
+                let expr_var_id = expression_identifier::get_var_id(
+                    &expr.2[0].1,
+                    context.function_context.calling_class.as_ref(),
+                    statements_analyzer.get_file_analyzer().get_file_source(),
+                    resolved_names,
+                    Some(statements_analyzer.get_codebase()),
+                );
 
-                    let second_arg_type = tast_info.get_expr_type(expr.2[1].1.pos());
+                let second_arg_type = tast_info.get_expr_type(expr.2[1].1.pos());
 
-                    // if we have a HH\Lib\Str\starts_with($foo, "/something") check
-                    // we can remove url-specific taints
-                    if let (Some(expr_var_id), Some(second_arg_type)) =
-                        (expr_var_id, second_arg_type)
-                    {
-                        if let Some(str) = second_arg_type.get_single_literal_string_value(
-                            &statements_analyzer.get_codebase().interner,
-                        ) {
+                if let (Some(expr_var_id), Some(second_arg_type)) = (expr_var_id, second_arg_type) {
+                    if let Some(str) = second_arg_type.get_single_literal_string_value(
+                        &statements_analyzer.get_codebase().interner,
+                    ) {
+                        let mut assertions = FxHashMap::default();
+
+                        // the string keeps its literal prefix, so e.g. later concatenations
+                        // can't change the host of a URI
+                        if !str.is_empty() {
+                            assertions.insert(
+                                expr_var_id.clone(),
+                                vec![Assertion::IsType(TAtomic::TStringWithPrefix {
+                                    prefix: str.clone(),
+                                })],
+                            );
+                        }
+
+                        // if we have a HH\Lib\Str\starts_with($foo, "/something") check
+                        // we can remove url-specific taints
+                        if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
                             if str.len() > 1 && str != "http://" && str != "https://" {
-                                tast_info.if_true_assertions.insert(
-                                    (pos.start_offset(), pos.end_offset()),
-                                    FxHashMap::from_iter([(
-                                        "hakana taints".to_string(),
-                                        vec![Assertion::RemoveTaints(
-                                            expr_var_id.clone(),
-                                            FxHashSet::from_iter([
-                                                SinkType::HtmlAttributeUri,
-                                                SinkType::CurlUri,
-                                                SinkType::RedirectUri,
-                                            ]),
-                                        )],
-                                    )]),
+                                assertions.insert(
+                                    "hakana taints".to_string(),
+                                    vec![Assertion::RemoveTaints(
+                                        expr_var_id.clone(),
+                                        FxHashSet::from_iter([
+                                            SinkType::HtmlAttributeUri,
+                                            SinkType::CurlUri,
+                                            SinkType::RedirectUri,
+                                        ]),
+                                    )],
                                 );
                             }
                         }
+
+                        if !assertions.is_empty() {
+                            tast_info
+                                .if_true_assertions
+                                .insert((pos.start_offset(), pos.end_offset()), assertions);
+                        }
                     }
                 }
             }
         }
-        "HH\\Lib\\Regex\\matches" => {
+        "HH\\Lib\\Regex\\matches" | "HH\\Lib\\Regex\\first_match" => {
             if expr.2.len() == 2 {
-                if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
-                    let expr_var_id = expression_identifier::get_var_id(
-                        &expr.2[0].1,
-                        context.function_context.calling_class.as_ref(),
-                        statements_analyzer.get_file_analyzer().get_file_source(),
-                        resolved_names,
-                        Some(statements_analyzer.get_codebase()),
-                    );
+                let expr_var_id = expression_identifier::get_var_id(
+                    &expr.2[0].1,
+                    context.function_context.calling_class.as_ref(),
+                    statements_analyzer.get_file_analyzer().get_file_source(),
+                    resolved_names,
+                    Some(statements_analyzer.get_codebase()),
+                );
 
-                    let second_arg_type = tast_info.get_expr_type(expr.2[1].1.pos());
+                let second_arg_type = tast_info.get_expr_type(expr.2[1].1.pos());
 
-                    // if we have a HH\Lib\Str\starts_with($foo, "/something") check
-                    // we can remove url-specific taints
-                    if let (Some(expr_var_id), Some(second_arg_type)) =
-                        (expr_var_id, second_arg_type)
-                    {
-                        if let Some(str) = second_arg_type.get_single_literal_string_value(
-                            &statements_analyzer.get_codebase().interner,
-                        ) {
+                if let (Some(expr_var_id), Some(second_arg_type)) = (expr_var_id, second_arg_type) {
+                    if let Some(str) = second_arg_type.get_single_literal_string_value(
+                        &statements_analyzer.get_codebase().interner,
+                    ) {
+                        let mut assertions = FxHashMap::default();
+
+                        // the string is known to match the pattern from here on
+                        assertions.insert(
+                            expr_var_id.clone(),
+                            vec![Assertion::IsType(TAtomic::TStringWithPattern {
+                                pattern: str.clone(),
+                            })],
+                        );
+
+                        // if we have a HH\Lib\Regex\matches($foo, re"/^something/") check
+                        // we can remove url-specific taints
+                        if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
                             let mut hashes_to_remove = FxHashSet::default();
 
                             if str.starts_with("^") {
@@ -443,18 +502,19 @@ pub(crate) fn analyze(
                             }
 
                             if !hashes_to_remove.is_empty() {
-                                tast_info.if_true_assertions.insert(
-                                    (pos.start_offset(), pos.end_offset()),
-                                    FxHashMap::from_iter([(
-                                        "hakana taints".to_string(),
-                                        vec![Assertion::RemoveTaints(
-                                            expr_var_id.clone(),
-                                            hashes_to_remove,
-                                        )],
-                                    )]),
+                                assertions.insert(
+                                    "hakana taints".to_string(),
+                                    vec![Assertion::RemoveTaints(
+                                        expr_var_id.clone(),
+                                        hashes_to_remove,
+                                    )],
                                 );
                             }
                         }
+
+                        tast_info
+                            .if_true_assertions
+                            .insert((pos.start_offset(), pos.end_offset()), assertions);
                     }
                 }
             }
@@ -462,6 +522,67 @@ pub(crate) fn analyze(
         _ => {}
     }
 
+    check_template_result(
+        statements_analyzer,
+        &mut template_result,
+        pos,
+        &functionlike_id,
+        function_storage,
+        tast_info,
+        context,
+    );
+
+    let mut stmt_type = function_call_return_type_fetcher::fetch(
+        statements_analyzer,
+        expr,
+        pos,
+        &functionlike_id,
+        function_storage,
+        template_result,
+        tast_info,
+        context,
+    );
+
+    for hook in &statements_analyzer.get_config().hooks {
+        hook.after_function_call_analysis(
+            tast_info,
+            AfterFunctionCallAnalysisData {
+                args: expr.2,
+                context,
+                function_call_pos: pos,
+                functionlike_id: &functionlike_id,
+                statements_analyzer,
+            },
+            &mut stmt_type,
+        );
+    }
+
+    tast_info.set_expr_type(&pos, stmt_type.clone());
+
+    nullable_return_analyzer::track_call(
+        statements_analyzer,
+        &[functionlike_id.clone()],
+        &stmt_type,
+        pos,
+        tast_info,
+        context,
+    );
+
+    redundant_computation_analyzer::check_call(
+        statements_analyzer,
+        &functionlike_id,
+        function_storage,
+        expr.2,
+        expr.3,
+        pos,
+        tast_info,
+        context,
+    );
+
+    if stmt_type.is_nothing() && !context.inside_loop {
+        context.has_returned = true;
+    }
+
     true
 }
 
@@ -532,6 +653,39 @@ fn process_function_effects(
     }
 }
 
+// function_exists('foo') and friends tell us that a symbol exists in the branch
+// where they're true, even if Hakana doesn't know about it
+fn get_existence_check_symbol(
+    statements_analyzer: &StatementsAnalyzer,
+    function_name: &StrId,
+    args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    tast_info: &TastInfo,
+) -> Option<String> {
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let checks_classlike = match interner.lookup(function_name) {
+        "function_exists" | "defined" => false,
+        "class_exists" | "interface_exists" | "trait_exists" | "enum_exists" => true,
+        _ => return None,
+    };
+
+    let first_arg_type = tast_info.get_expr_type(args.first()?.1.pos())?;
+
+    if !first_arg_type.is_single() {
+        return None;
+    }
+
+    let symbol_name = match first_arg_type.get_single() {
+        TAtomic::TLiteralString { value } => value.clone(),
+        TAtomic::TLiteralClassname { name } if checks_classlike => {
+            interner.lookup(name).to_string()
+        }
+        _ => return None,
+    };
+
+    Some(symbol_name.trim_start_matches('\\').to_string())
+}
+
 fn get_named_function_info<'a>(
     statements_analyzer: &'a StatementsAnalyzer,
     name: &StrId,
@@ -601,3 +755,109 @@ fn check_array_key_or_value_type(
         }
     }
 }
+
+// Memoized and pure functions should return the same result every time they're called
+// with the same arguments, which isn't possible if they rely on the clock, randomness or IO
+fn check_non_deterministic_call(
+    statements_analyzer: &StatementsAnalyzer,
+    function_storage: &FunctionLikeInfo,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let calling_functionlike_info =
+        if let Some(functionlike_info) = statements_analyzer.get_functionlike_info() {
+            functionlike_info
+        } else {
+            return;
+        };
+
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let calling_function_name =
+        if let Some(calling_functionlike_id) = &context.function_context.calling_functionlike_id {
+            calling_functionlike_id.to_string(interner)
+        } else {
+            return;
+        };
+
+    let (issue_kind, function_description) = if calling_functionlike_info.is_memoized {
+        (
+            IssueKind::NondeterministicCallInMemoizedFunction,
+            "Memoized function",
+        )
+    } else if let FnEffect::None = calling_functionlike_info.effects {
+        (
+            IssueKind::NondeterministicCallInPureFunction,
+            "Pure function",
+        )
+    } else {
+        return;
+    };
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            issue_kind,
+            format!(
+                "{} {} calls {}, which can return a different result each time",
+                function_description,
+                calling_function_name,
+                interner.lookup(&function_storage.name)
+            ),
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
+// Pure functions can't have side effects, either directly or through the functions they call
+pub(crate) fn check_impure_call(
+    statements_analyzer: &StatementsAnalyzer,
+    functionlike_id: &FunctionLikeIdentifier,
+    function_storage: &FunctionLikeInfo,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let calling_functionlike_info =
+        if let Some(functionlike_info) = statements_analyzer.get_functionlike_info() {
+            functionlike_info
+        } else {
+            return;
+        };
+
+    if !matches!(calling_functionlike_info.effects, FnEffect::None) {
+        return;
+    }
+
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let calling_function_name =
+        if let Some(calling_functionlike_id) = &context.function_context.calling_functionlike_id {
+            calling_functionlike_id.to_string(interner)
+        } else {
+            return;
+        };
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            IssueKind::ImpureFunctionInPureContext,
+            format!(
+                "Pure function {} calls {}, which {}",
+                calling_function_name,
+                functionlike_id.to_string(interner),
+                if function_storage.effects.is_impure() {
+                    "is impure"
+                } else {
+                    "calls impure functions"
+                }
+            ),
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
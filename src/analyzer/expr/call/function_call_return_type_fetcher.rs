@@ -6,15 +6,16 @@ use hakana_reflection_info::function_context::FunctionLikeIdentifier;
 use hakana_reflection_info::functionlike_info::FunctionLikeInfo;
 use hakana_reflection_info::t_atomic::{DictKey, TAtomic};
 use hakana_reflection_info::t_union::TUnion;
-use hakana_reflection_info::taint::SinkType;
+use hakana_reflection_info::taint::{get_builtin_sanitizer_removed_taints, SinkType};
 use hakana_reflection_info::Interner;
+use hakana_type::type_combiner::combine;
 use hakana_type::type_comparator::type_comparison_result::TypeComparisonResult;
 use hakana_type::type_comparator::union_type_comparator;
 use hakana_type::type_expander::TypeExpansionOptions;
 use hakana_type::{
-    add_union_type, get_arrayish_params, get_bool, get_float, get_int, get_mixed, get_mixed_any,
-    get_mixed_vec, get_nothing, get_null, get_object, get_string, get_vec, template, type_expander,
-    wrap_atomic,
+    add_union_type, get_arrayish_params, get_bool, get_float, get_int, get_literal_string,
+    get_mixed, get_mixed_any, get_mixed_vec, get_nothing, get_null, get_object, get_string,
+    get_value_param, get_vec, template, type_expander, wrap_atomic,
 };
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::collections::BTreeMap;
@@ -68,7 +69,7 @@ pub(crate) fn fetch(
 
     // todo support custom return type providers for functions
 
-    let stmt_type = if let Some(stmt_type) = stmt_type {
+    let mut stmt_type = if let Some(stmt_type) = stmt_type {
         stmt_type
     } else {
         if let Some(function_return_type) = &function_storage.return_type {
@@ -97,6 +98,10 @@ pub(crate) fn fetch(
 
             let mut function_return_type = function_return_type.clone();
 
+            if function_storage.never_returns_null {
+                function_return_type.remove_type(&TAtomic::TNull);
+            }
+
             if !template_result.lower_bounds.is_empty()
                 && !function_storage.template_types.is_empty()
             {
@@ -142,6 +147,10 @@ pub(crate) fn fetch(
         }
     };
 
+    if function_storage.readonly_return {
+        stmt_type.readonly = true;
+    }
+
     return add_dataflow(
         statements_analyzer,
         expr,
@@ -164,6 +173,10 @@ fn handle_special_functions(
     tast_info: &mut TastInfo,
     context: &mut ScopeContext,
 ) -> Option<TUnion> {
+    if let Some(folded_type) = get_folded_string_call_type(name, args, tast_info, codebase) {
+        return Some(folded_type);
+    }
+
     match name {
         "HH\\global_get" => {
             if let Some((_, arg_expr)) = args.get(0) {
@@ -229,6 +242,7 @@ fn handle_special_functions(
                                         type_param: get_string(),
                                         known_count: None,
                                         non_empty: true,
+                                        is_tuple: false,
                                     },
                                     TAtomic::TFalse,
                                 ]);
@@ -242,6 +256,7 @@ fn handle_special_functions(
                                         type_param: get_string(),
                                         known_count: None,
                                         non_empty: false,
+                                        is_tuple: false,
                                     },
                                     TAtomic::TFalse,
                                 ]);
@@ -260,9 +275,11 @@ fn handle_special_functions(
                                             type_param: get_nothing(),
                                             known_count: None,
                                             non_empty: true,
+                                            is_tuple: false,
                                         }),
                                         known_count: None,
                                         non_empty: false,
+                                        is_tuple: false,
                                     },
                                     TAtomic::TFalse,
                                 ]);
@@ -277,6 +294,7 @@ fn handle_special_functions(
                                 type_param: get_mixed(),
                                 known_count: None,
                                 non_empty: true,
+                                is_tuple: false,
                             },
                             TAtomic::TFalse,
                         ]);
@@ -291,6 +309,7 @@ fn handle_special_functions(
                         type_param: get_string(),
                         known_count: None,
                         non_empty: true,
+                        is_tuple: false,
                     },
                     TAtomic::TFalse,
                 ]);
@@ -339,6 +358,7 @@ fn handle_special_functions(
             }),
             known_count: None,
             non_empty: true,
+            is_tuple: false,
         })),
         "str_replace" => {
             // returns string if the second arg is a string
@@ -500,6 +520,15 @@ fn handle_special_functions(
                 None
             }
         }
+        "HH\\vec" | "HH\\dict" | "HH\\keyset" => {
+            if let Some((_, arg_expr)) = args.get(0) {
+                if let Some(arg_type) = tast_info.get_expr_type(arg_expr.pos()) {
+                    return get_converted_collection_type(name, arg_type, codebase);
+                }
+            }
+
+            None
+        }
         "HH\\idx" => {
             if args.len() >= 2 {
                 let dict_type = tast_info.get_rc_expr_type(args[0].1.pos()).cloned();
@@ -556,6 +585,228 @@ fn handle_special_functions(
     }
 }
 
+// Longer literals aren't worth carrying around in types
+const MAX_FOLDED_STRING_LENGTH: usize = 200;
+
+// Pure HSL string functions given literal arguments return literals, so that their
+// results can be used in equality checks, switch cases and shape keys
+fn get_folded_string_call_type(
+    name: &str,
+    args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    tast_info: &TastInfo,
+    codebase: &CodebaseInfo,
+) -> Option<TUnion> {
+    if !matches!(
+        name,
+        "HH\\Lib\\Str\\uppercase"
+            | "HH\\Lib\\Str\\lowercase"
+            | "HH\\Lib\\Str\\capitalize"
+            | "HH\\Lib\\Str\\slice"
+    ) {
+        return None;
+    }
+
+    let get_arg_type = |offset: usize| {
+        args.get(offset)
+            .and_then(|(_, arg_expr)| tast_info.get_expr_type(arg_expr.pos()))
+    };
+
+    let value = get_arg_type(0)?.get_single_literal_string_value(&codebase.interner)?;
+
+    if value.len() > MAX_FOLDED_STRING_LENGTH {
+        return None;
+    }
+
+    // these are byte-based, and only change the case of ASCII letters
+    let folded_value = match name {
+        "HH\\Lib\\Str\\uppercase" => value.to_ascii_uppercase(),
+        "HH\\Lib\\Str\\lowercase" => value.to_ascii_lowercase(),
+        "HH\\Lib\\Str\\capitalize" => {
+            let mut value = value;
+
+            if let Some(first_char) = value.get_mut(0..1) {
+                first_char.make_ascii_uppercase();
+            }
+
+            value
+        }
+        _ => {
+            let offset = get_arg_type(1)?.get_single_literal_int_value()?;
+
+            let length = if let Some(length_type) = get_arg_type(2) {
+                if length_type.is_null() {
+                    None
+                } else {
+                    Some(length_type.get_single_literal_int_value()?)
+                }
+            } else {
+                None
+            };
+
+            // negative offsets and lengths aren't folded
+            if offset < 0 || offset as usize > value.len() || length.map_or(false, |l| l < 0) {
+                return None;
+            }
+
+            let start = offset as usize;
+            let end = length.map_or(value.len(), |length| {
+                (start + length as usize).min(value.len())
+            });
+
+            value.get(start..end)?.to_string()
+        }
+    };
+
+    Some(get_literal_string(folded_value))
+}
+
+// vec(), dict() and keyset() keep what's known about the collection they're given, like its
+// known items and whether it's non-empty, rather than just its key and value types
+fn get_converted_collection_type(
+    function_name: &str,
+    arg_type: &TUnion,
+    codebase: &CodebaseInfo,
+) -> Option<TUnion> {
+    let mut converted_types = vec![];
+
+    for atomic in &arg_type.types {
+        converted_types.push(match function_name {
+            "HH\\vec" => convert_to_vec(atomic, codebase)?,
+            "HH\\dict" => convert_to_dict(atomic, codebase)?,
+            _ => convert_to_keyset(atomic, codebase)?,
+        });
+    }
+
+    Some(TUnion::new(combine(converted_types, codebase, false)))
+}
+
+fn convert_to_vec(atomic: &TAtomic, codebase: &CodebaseInfo) -> Option<TAtomic> {
+    match atomic {
+        TAtomic::TVec { .. } => Some(atomic.clone()),
+        TAtomic::TDict {
+            known_items,
+            non_empty,
+            ..
+        } => Some(TAtomic::TVec {
+            known_items: None,
+            type_param: get_value_param(atomic, codebase)?,
+            known_count: None,
+            non_empty: *non_empty
+                || if let Some(known_items) = known_items {
+                    known_items.values().any(|(optional, _)| !optional)
+                } else {
+                    false
+                },
+            is_tuple: false,
+        }),
+        TAtomic::TKeyset { type_param } => Some(TAtomic::TVec {
+            known_items: None,
+            type_param: type_param.clone(),
+            known_count: None,
+            non_empty: false,
+            is_tuple: false,
+        }),
+        _ => Some(TAtomic::TVec {
+            known_items: None,
+            type_param: get_collection_object_params(atomic, codebase)?.1,
+            known_count: None,
+            non_empty: false,
+            is_tuple: false,
+        }),
+    }
+}
+
+fn convert_to_dict(atomic: &TAtomic, codebase: &CodebaseInfo) -> Option<TAtomic> {
+    match atomic {
+        TAtomic::TDict {
+            known_items,
+            params,
+            non_empty,
+            ..
+        } => Some(TAtomic::TDict {
+            known_items: known_items.clone(),
+            params: params.clone(),
+            non_empty: *non_empty,
+            shape_name: None,
+        }),
+        TAtomic::TVec {
+            known_items,
+            type_param,
+            non_empty,
+            ..
+        } => Some(TAtomic::TDict {
+            known_items: known_items.as_ref().map(|known_items| {
+                known_items
+                    .iter()
+                    .map(|(offset, (optional, item_type))| {
+                        (
+                            DictKey::Int(*offset as u32),
+                            (*optional, Arc::new(item_type.clone())),
+                        )
+                    })
+                    .collect()
+            }),
+            params: if type_param.is_nothing() {
+                None
+            } else {
+                Some((get_int(), type_param.clone()))
+            },
+            non_empty: *non_empty,
+            shape_name: None,
+        }),
+        TAtomic::TKeyset { type_param } => Some(TAtomic::TDict {
+            known_items: None,
+            params: Some((type_param.clone(), type_param.clone())),
+            non_empty: false,
+            shape_name: None,
+        }),
+        _ => Some(TAtomic::TDict {
+            known_items: None,
+            params: Some(get_collection_object_params(atomic, codebase)?),
+            non_empty: false,
+            shape_name: None,
+        }),
+    }
+}
+
+fn convert_to_keyset(atomic: &TAtomic, codebase: &CodebaseInfo) -> Option<TAtomic> {
+    match atomic {
+        TAtomic::TKeyset { .. } => Some(atomic.clone()),
+        TAtomic::TVec { .. } | TAtomic::TDict { .. } => Some(TAtomic::TKeyset {
+            type_param: get_value_param(atomic, codebase)?,
+        }),
+        _ => Some(TAtomic::TKeyset {
+            type_param: get_collection_object_params(atomic, codebase)?.1,
+        }),
+    }
+}
+
+// The key and value types of Hack's mutable and immutable collection classes
+fn get_collection_object_params(
+    atomic: &TAtomic,
+    codebase: &CodebaseInfo,
+) -> Option<(TUnion, TUnion)> {
+    if let TAtomic::TNamedObject {
+        name,
+        type_params: Some(type_params),
+        ..
+    } = atomic
+    {
+        match codebase.interner.lookup(name) {
+            "HH\\Vector" | "HH\\ImmVector" => Some((get_int(), type_params.get(0)?.clone())),
+            "HH\\Map" | "HH\\ImmMap" => {
+                Some((type_params.get(0)?.clone(), type_params.get(1)?.clone()))
+            }
+            "HH\\Set" | "HH\\ImmSet" => {
+                Some((type_params.get(0)?.clone(), type_params.get(0)?.clone()))
+            }
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
 fn add_dataflow(
     statements_analyzer: &StatementsAnalyzer,
     expr: (
@@ -736,8 +987,6 @@ fn get_special_argument_nodes(
             | "chop"
             | "convert_uudecode"
             | "convert_uuencode"
-            | "json_encode"
-            | "json_decode"
             | "base64_encode"
             | "base64_decode"
             | "HH\\Lib\\Dict\\filter"
@@ -753,6 +1002,8 @@ fn get_special_argument_nodes(
             | "HH\\Lib\\Keyset\\filter_async"
             | "HH\\Lib\\Vec\\slice"
             | "HH\\Lib\\Str\\slice" => (vec![(0, PathKind::Default)], None),
+            "json_encode" | "json_encode_with_error" => (vec![(0, PathKind::Serialize)], None),
+            "json_decode" | "json_decode_with_error" => (vec![(0, PathKind::Unserialize)], None),
             "var_dump" | "printf" => (vec![(0, PathKind::Default)], Some(PathKind::Default)),
             "sscanf" | "substr_replace" => {
                 (vec![(0, PathKind::Default), (1, PathKind::Default)], None)
@@ -886,14 +1137,13 @@ fn get_special_added_removed_taints(
                     FxHashSet::default(),
                 ),
             )]),
-            "htmlentities" | "htmlspecialchars" | "strip_tags" => FxHashMap::from_iter([(
-                0,
-                (
-                    FxHashSet::default(),
-                    FxHashSet::from_iter([SinkType::HtmlTag]),
-                ),
-            )]),
-            _ => FxHashMap::default(),
+            function_name => {
+                if let Some(removed_taints) = get_builtin_sanitizer_removed_taints(function_name) {
+                    FxHashMap::from_iter([(0, (FxHashSet::default(), removed_taints))])
+                } else {
+                    FxHashMap::default()
+                }
+            }
         },
         FunctionLikeIdentifier::Method(_, _) => panic!(),
     }
@@ -1,4 +1,5 @@
 use crate::expr::expression_identifier;
+use crate::expr::nullable_return_analyzer;
 use crate::expression_analyzer;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
@@ -42,6 +43,10 @@ pub(crate) fn analyze(
         return false;
     }
 
+    if nullsafe {
+        nullable_return_analyzer::mark_checked(expr.0, tast_info);
+    }
+
     if let aast::Expr_::Id(_) = &expr.1 .2 {
         // do nothing
     } else {
@@ -71,8 +76,15 @@ pub(crate) fn analyze(
         .unwrap_or(get_mixed_any());
 
     let mut analysis_result = AtomicMethodCallAnalysisResult::new();
+    let mut has_nullsafe_null = false;
+
+    if class_type.is_null() && nullsafe {
+        // the call never happens, so the result is just null
+        let mut null_type = get_null();
+        null_type.parent_nodes = class_type.parent_nodes.clone();
 
-    if class_type.is_null() || class_type.is_void() {
+        analysis_result.return_type = Some(null_type);
+    } else if class_type.is_null() || class_type.is_void() {
         tast_info.maybe_add_issue(
             Issue::new(
                 IssueKind::MethodCallOnNull,
@@ -108,6 +120,7 @@ pub(crate) fn analyze(
         while let Some(lhs_atomic_type) = class_types.pop() {
             match lhs_atomic_type {
                 TAtomic::TNull => {
+                    has_nullsafe_null = nullsafe;
                     continue; // handled above
                 }
                 TAtomic::TFalse => {
@@ -153,7 +166,8 @@ pub(crate) fn analyze(
     }
 
     if let Some(mut stmt_type) = analysis_result.return_type {
-        if nullsafe && !stmt_type.is_mixed() {
+        // only a receiver that can be null makes the result nullable
+        if has_nullsafe_null && !stmt_type.is_mixed() {
             stmt_type = add_union_type(
                 stmt_type,
                 &get_null(),
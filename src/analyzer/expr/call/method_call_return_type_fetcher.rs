@@ -44,13 +44,19 @@ pub(crate) fn fetch(
     {
         return_type
     } else {
-        functionlike_storage.return_type.clone().unwrap_or(
+        let mut return_type = functionlike_storage.return_type.clone().unwrap_or(
             if method_id.1 == codebase.interner.get("__toString").unwrap() {
                 get_string()
             } else {
                 get_mixed_any()
             },
-        )
+        );
+
+        if functionlike_storage.never_returns_null {
+            return_type.remove_type(&TAtomic::TNull);
+        }
+
+        return_type
     };
 
     let method_storage = &functionlike_storage.method_info.as_ref().unwrap();
@@ -120,6 +126,10 @@ pub(crate) fn fetch(
         &mut tast_info.data_flow_graph,
     );
 
+    if functionlike_storage.readonly_return {
+        return_type_candidate.readonly = true;
+    }
+
     add_dataflow(
         statements_analyzer,
         return_type_candidate,
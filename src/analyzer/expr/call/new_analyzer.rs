@@ -152,6 +152,8 @@ fn analyze_atomic(
 ) {
     let mut from_static = false;
     let mut from_classname = false;
+    let mut classname_type_params = None;
+    let mut generic_classname = None;
 
     let classlike_name = match &lhs_type_part {
         TAtomic::TNamedObject { name, is_this, .. } => {
@@ -160,9 +162,22 @@ fn analyze_atomic(
             name.clone()
         }
         TAtomic::TClassname { as_type, .. } | TAtomic::TGenericClassname { as_type, .. } => {
+            if let TAtomic::TGenericClassname {
+                param_name,
+                defining_entity,
+                ..
+            } = lhs_type_part
+            {
+                generic_classname = Some((*param_name, *defining_entity));
+            }
+
             let as_type = *as_type.clone();
-            if let TAtomic::TNamedObject { name, .. } = as_type {
+            if let TAtomic::TNamedObject {
+                name, type_params, ..
+            } = as_type
+            {
                 from_classname = true;
+                classname_type_params = type_params;
 
                 name
             } else {
@@ -228,6 +243,8 @@ fn analyze_atomic(
         classlike_name,
         from_static,
         from_classname,
+        classname_type_params,
+        generic_classname,
         can_extend,
         result,
     )
@@ -248,6 +265,8 @@ fn analyze_named_constructor(
     classlike_name: StrId,
     from_static: bool,
     from_classname: bool,
+    classname_type_params: Option<Vec<TUnion>>,
+    generic_classname: Option<(StrId, StrId)>,
     can_extend: bool,
     result: &mut AtomicMethodCallAnalysisResult,
 ) {
@@ -255,19 +274,24 @@ fn analyze_named_constructor(
     let storage = if let Some(storage) = codebase.classlike_infos.get(&classlike_name) {
         storage
     } else {
-        tast_info.maybe_add_issue(
-            Issue::new(
-                IssueKind::NonExistentClass,
-                format!(
-                    "Cannot call new on undefined class {}",
-                    codebase.interner.lookup(&classlike_name)
+        if !context
+            .asserted_existing_symbols
+            .contains(codebase.interner.lookup(&classlike_name))
+        {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::NonExistentClass,
+                    format!(
+                        "Cannot call new on undefined class {}",
+                        codebase.interner.lookup(&classlike_name)
+                    ),
+                    statements_analyzer.get_hpos(&pos),
+                    &context.function_context.calling_functionlike_id,
                 ),
-                statements_analyzer.get_hpos(&pos),
-                &context.function_context.calling_functionlike_id,
-            ),
-            statements_analyzer.get_config(),
-            statements_analyzer.get_file_path_actual(),
-        );
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
 
         return;
     };
@@ -498,6 +522,16 @@ fn analyze_named_constructor(
         };
     }
 
+    // classname<Foo<int>> carries the type params the instance should have, unless
+    // they've been given explicitly
+    if expr.1.is_empty() && !storage.template_types.is_empty() {
+        if let Some(classname_type_params) = classname_type_params {
+            if classname_type_params.len() == storage.template_types.len() {
+                generic_type_params = Some(classname_type_params);
+            }
+        }
+    }
+
     let mut result_type = wrap_atomic(TAtomic::TNamedObject {
         name: classlike_name,
         type_params: generic_type_params,
@@ -506,6 +540,17 @@ fn analyze_named_constructor(
         remapped_params: false,
     });
 
+    // new $cls() where $cls is classname<T> creates a T, not just its upper bound
+    if let Some((param_name, defining_entity)) = generic_classname {
+        result_type = wrap_atomic(TAtomic::TGenericParam {
+            param_name,
+            as_type: result_type,
+            defining_entity,
+            from_class: false,
+            extra_types: None,
+        });
+    }
+
     if from_classname {
         let descendants = codebase.get_all_descendants(&classlike_name);
 
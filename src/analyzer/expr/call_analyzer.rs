@@ -3,17 +3,22 @@ use std::sync::Arc;
 use hakana_reflection_info::StrId;
 use rustc_hash::FxHashMap;
 
+use crate::expr::expression_identifier;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::typed_ast::TastInfo;
 use hakana_reflection_info::function_context::FunctionLikeIdentifier;
 use hakana_reflection_info::functionlike_info::{FnEffect, FunctionLikeInfo};
+use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::method_identifier::MethodIdentifier;
 use hakana_reflection_info::t_atomic::TAtomic;
 use hakana_reflection_info::t_union::TUnion;
 use hakana_type::get_mixed_any;
-use hakana_type::template::TemplateResult;
+use hakana_type::template::standin_type_replacer::get_most_specific_type_from_bounds;
+use hakana_type::template::{inferred_type_replacer, TemplateResult};
+use hakana_type::type_comparator::type_comparison_result::TypeComparisonResult;
+use hakana_type::type_comparator::union_type_comparator;
 use indexmap::IndexMap;
 use oxidized::pos::Pos;
 use oxidized::{aast, ast_defs};
@@ -127,13 +132,91 @@ pub(crate) fn analyze(
 
   T <: int|string, T >: string|float --- implies T <: int and T >: float, which is impossible
   T = int, T = string --- implies T is a string _and_ and int, which is impossible
+
+  It also checks the inferred types against user-declared constraints that can only
+  be checked once every template is inferred: `where T as Foo` constraints, and
+  `as` constraints that reference other templates (e.g. `<Tk, Tv as Tk>`).
 */
 pub(crate) fn check_template_result(
-    _statements_analyzer: &StatementsAnalyzer,
-    _template_result: &mut TemplateResult,
-    _pos: &Pos,
-    _functionlike_id: &FunctionLikeIdentifier,
+    statements_analyzer: &StatementsAnalyzer,
+    template_result: &mut TemplateResult,
+    pos: &Pos,
+    functionlike_id: &FunctionLikeIdentifier,
+    functionlike_storage: &FunctionLikeInfo,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
 ) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let mut constraints = vec![];
+
+    for (template_name, type_map) in &functionlike_storage.template_types {
+        for (defining_entity, upper_bound) in type_map {
+            if upper_bound.has_template_types() {
+                constraints.push((template_name, defining_entity, &**upper_bound, false));
+            }
+        }
+    }
+
+    for (template_name, defining_entity, upper_bound) in &functionlike_storage.where_as_constraints
+    {
+        constraints.push((template_name, defining_entity, upper_bound, true));
+    }
+
+    for (template_name, defining_entity, upper_bound, is_where_constraint) in constraints {
+        let inferred_type = if let Some(lower_bounds) = template_result
+            .lower_bounds
+            .get(template_name)
+            .and_then(|type_map| type_map.get(defining_entity))
+        {
+            get_most_specific_type_from_bounds(lower_bounds, codebase)
+        } else {
+            continue;
+        };
+
+        // other issues already cover arguments we know nothing about
+        if inferred_type.is_mixed() || inferred_type.is_nothing() {
+            continue;
+        }
+
+        let upper_bound = inferred_type_replacer::replace(upper_bound, template_result, codebase);
+
+        // the constraint references a template we couldn't infer
+        if upper_bound.has_template_types() {
+            continue;
+        }
+
+        if union_type_comparator::is_contained_by(
+            codebase,
+            &inferred_type,
+            &upper_bound,
+            false,
+            false,
+            false,
+            &mut TypeComparisonResult::new(),
+        ) {
+            continue;
+        }
+
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::TemplateConstraintViolation,
+                format!(
+                    "{} is inferred as {}, which does not satisfy the {}constraint {} as {} of {}",
+                    codebase.interner.lookup(template_name),
+                    inferred_type.get_id(Some(&codebase.interner)),
+                    if is_where_constraint { "where " } else { "" },
+                    codebase.interner.lookup(template_name),
+                    upper_bound.get_id(Some(&codebase.interner)),
+                    functionlike_id.to_string(&codebase.interner),
+                ),
+                statements_analyzer.get_hpos(pos),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
 }
 
 pub(crate) fn get_generic_param_for_offset(
@@ -236,9 +319,60 @@ pub(crate) fn check_method_args(
         tast_info.combine_effects(&arg.1, pos, pos);
     }
 
-    if !template_result.template_types.is_empty() {
-        check_template_result(statements_analyzer, template_result, pos, &functionlike_id);
+    if !template_result.template_types.is_empty()
+        || !functionlike_storage.where_as_constraints.is_empty()
+    {
+        check_template_result(
+            statements_analyzer,
+            template_result,
+            pos,
+            &functionlike_id,
+            functionlike_storage,
+            tast_info,
+            context,
+        );
     }
 
     return true;
 }
+
+/// Records the AssertIfTrue/AssertIfFalse assertions of a function or method against the
+/// variables passed to the corresponding parameters
+pub(crate) fn apply_functionlike_assertions(
+    statements_analyzer: &StatementsAnalyzer,
+    tast_info: &mut TastInfo,
+    functionlike_storage: &FunctionLikeInfo,
+    args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    context: &ScopeContext,
+    pos: &Pos,
+) {
+    for (functionlike_assertions, call_assertions) in [
+        (
+            &functionlike_storage.if_true_assertions,
+            &mut tast_info.if_true_assertions,
+        ),
+        (
+            &functionlike_storage.if_false_assertions,
+            &mut tast_info.if_false_assertions,
+        ),
+    ] {
+        if let Some(functionlike_assertions) = functionlike_assertions {
+            for (param_offset, assertion) in functionlike_assertions {
+                if let Some(arg_var_id) = args.get(*param_offset).and_then(|(_, arg_expr)| {
+                    expression_identifier::get_var_id(
+                        arg_expr,
+                        context.function_context.calling_class.as_ref(),
+                        statements_analyzer.get_file_analyzer().get_file_source(),
+                        statements_analyzer.get_file_analyzer().resolved_names,
+                        Some(statements_analyzer.get_codebase()),
+                    )
+                }) {
+                    call_assertions
+                        .entry((pos.start_offset(), pos.end_offset()))
+                        .or_insert_with(FxHashMap::default)
+                        .insert(arg_var_id, vec![assertion.clone()]);
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
 use hakana_reflection_info::{
+    code_location::HPos,
+    codebase_info::CodebaseInfo,
     data_flow::{
         graph::{GraphKind, WholeProgramKind},
         node::DataFlowNode,
         path::{ArrayDataKind, PathKind},
     },
+    issue::{Issue, IssueKind},
     t_atomic::{DictKey, TAtomic},
     t_union::TUnion,
 };
@@ -18,7 +21,7 @@ use oxidized::{
     ast_defs::Pos,
     tast::{KvcKind, VcKind},
 };
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::typed_ast::TastInfo;
 use crate::{expression_analyzer, scope_analyzer::ScopeAnalyzer};
@@ -31,6 +34,7 @@ pub(crate) struct ArrayCreationInfo {
     known_items: Vec<(TAtomic, TUnion)>,
     parent_nodes: FxHashSet<DataFlowNode>,
     effects: u8,
+    seen_keys: FxHashMap<DictKey, HPos>,
 }
 
 impl ArrayCreationInfo {
@@ -41,6 +45,7 @@ impl ArrayCreationInfo {
             parent_nodes: FxHashSet::default(),
             known_items: Vec::new(),
             effects: 0,
+            seen_keys: FxHashMap::default(),
         }
     }
 }
@@ -86,6 +91,7 @@ pub(crate) fn analyze_vals(
                         type_param: get_nothing(),
                         known_count: Some(0),
                         non_empty: false,
+                        is_tuple: false,
                     }),
                 );
             }
@@ -108,6 +114,26 @@ pub(crate) fn analyze_vals(
                     }),
                 );
             }
+            VcKind::ImmVector => {
+                tast_info.set_expr_type(
+                    &pos,
+                    get_immutable_collection_type(
+                        "HH\\ImmVector",
+                        vec![get_nothing()],
+                        statements_analyzer.get_codebase(),
+                    ),
+                );
+            }
+            VcKind::ImmSet => {
+                tast_info.set_expr_type(
+                    &pos,
+                    get_immutable_collection_type(
+                        "HH\\ImmSet",
+                        vec![get_nothing()],
+                        statements_analyzer.get_codebase(),
+                    ),
+                );
+            }
             _ => {}
         }
 
@@ -131,6 +157,28 @@ pub(crate) fn analyze_vals(
         );
     }
 
+    let mut summarized_count = None;
+
+    // big literal vecs get a summary type, in the same way as in simple_type_inferer
+    if array_creation_info.known_items.len()
+        > statements_analyzer
+            .get_file_analyzer()
+            .get_file_source()
+            .literal_collection_thresholds
+            .vec
+    {
+        // spread items make the count unknowable
+        if array_creation_info.known_items.len() == items.len() {
+            summarized_count = Some(items.len());
+        }
+
+        for (_, value_type) in std::mem::take(&mut array_creation_info.known_items) {
+            array_creation_info
+                .item_value_atomic_types
+                .extend(value_type.types);
+        }
+    }
+
     match vc_kind {
         VcKind::Vec => {
             let types = array_creation_info.item_value_atomic_types.clone();
@@ -159,6 +207,7 @@ pub(crate) fn analyze_vals(
                     type_param: get_nothing(),
                     known_count: Some(types.len()),
                     non_empty: true,
+                    is_tuple: false,
                 }
             } else {
                 TAtomic::TVec {
@@ -168,8 +217,9 @@ pub(crate) fn analyze_vals(
                         codebase,
                         false,
                     )),
-                    known_count: None,
+                    known_count: summarized_count,
                     non_empty: true,
+                    is_tuple: false,
                 }
             });
 
@@ -203,6 +253,27 @@ pub(crate) fn analyze_vals(
 
             tast_info.set_expr_type(&pos, new_vec);
         }
+        VcKind::ImmVector | VcKind::ImmSet => {
+            let item_value_type = TUnion::new(type_combiner::combine(
+                array_creation_info.item_value_atomic_types.clone(),
+                codebase,
+                false,
+            ));
+
+            let mut collection = get_immutable_collection_type(
+                if matches!(vc_kind, VcKind::ImmVector) {
+                    "HH\\ImmVector"
+                } else {
+                    "HH\\ImmSet"
+                },
+                vec![item_value_type],
+                codebase,
+            );
+
+            collection.parent_nodes = array_creation_info.parent_nodes;
+
+            tast_info.set_expr_type(&pos, collection);
+        }
         _ => {}
     }
 
@@ -226,12 +297,20 @@ pub(crate) fn analyze_keyvals(
     if items.is_empty() {
         tast_info.set_expr_type(
             &pos,
-            wrap_atomic(TAtomic::TDict {
-                known_items: None,
-                params: None,
-                non_empty: false,
-                shape_name: None,
-            }),
+            if let KvcKind::ImmMap = kvc_kind {
+                get_immutable_collection_type(
+                    "HH\\ImmMap",
+                    vec![get_nothing(), get_nothing()],
+                    statements_analyzer.get_codebase(),
+                )
+            } else {
+                wrap_atomic(TAtomic::TDict {
+                    known_items: None,
+                    params: None,
+                    non_empty: false,
+                    shape_name: None,
+                })
+            },
         );
         return true;
     }
@@ -252,6 +331,52 @@ pub(crate) fn analyze_keyvals(
         );
     }
 
+    // big literal dicts get a summary type, in the same way as in simple_type_inferer
+    if array_creation_info.known_items.len()
+        > statements_analyzer
+            .get_file_analyzer()
+            .get_file_source()
+            .literal_collection_thresholds
+            .dict
+    {
+        for (key_type, value_type) in std::mem::take(&mut array_creation_info.known_items) {
+            array_creation_info.item_key_atomic_types.push(key_type);
+            array_creation_info
+                .item_value_atomic_types
+                .extend(value_type.types);
+        }
+    }
+
+    if let KvcKind::ImmMap = kvc_kind {
+        let mut imm_map = get_immutable_collection_type(
+            "HH\\ImmMap",
+            vec![
+                TUnion::new(type_combiner::combine(
+                    array_creation_info.item_key_atomic_types.clone(),
+                    codebase,
+                    false,
+                )),
+                TUnion::new(type_combiner::combine(
+                    array_creation_info.item_value_atomic_types.clone(),
+                    codebase,
+                    false,
+                )),
+            ],
+            codebase,
+        );
+
+        imm_map.parent_nodes = array_creation_info.parent_nodes;
+
+        tast_info.set_expr_type(&pos, imm_map);
+
+        tast_info.expr_effects.insert(
+            (pos.start_offset(), pos.end_offset()),
+            array_creation_info.effects,
+        );
+
+        return true;
+    }
+
     let mut known_items = BTreeMap::new();
 
     if array_creation_info.item_key_atomic_types.len() < 20 {
@@ -307,6 +432,27 @@ pub(crate) fn analyze_keyvals(
     true
 }
 
+// Immutable collections are covariant, so unlike their mutable counterparts they can
+// keep the types of the items they're created with. Without the HHIs that define them
+// (e.g. with a replacement hhi_dir) nothing is known about them.
+fn get_immutable_collection_type(
+    name: &str,
+    type_params: Vec<TUnion>,
+    codebase: &CodebaseInfo,
+) -> TUnion {
+    if let Some(name) = codebase.interner.get(name) {
+        wrap_atomic(TAtomic::TNamedObject {
+            name,
+            type_params: Some(type_params),
+            is_this: false,
+            extra_types: None,
+            remapped_params: false,
+        })
+    } else {
+        get_mixed_any()
+    }
+}
+
 fn analyze_vals_item(
     statements_analyzer: &StatementsAnalyzer,
     context: &mut ScopeContext,
@@ -398,6 +544,30 @@ fn analyze_keyvals_item(
         array_creation_info,
     );
 
+    if key_item_type.is_single() {
+        let dict_key = match key_item_type.get_single() {
+            TAtomic::TLiteralString { value } => Some(DictKey::String(value.clone())),
+            TAtomic::TLiteralInt { value } => u32::try_from(*value).ok().map(DictKey::Int),
+            TAtomic::TEnumLiteralCase {
+                enum_name,
+                member_name,
+                ..
+            } => Some(DictKey::Enum(*enum_name, *member_name)),
+            _ => None,
+        };
+
+        if let Some(dict_key) = dict_key {
+            check_duplicate_key(
+                statements_analyzer,
+                dict_key,
+                item.0.pos(),
+                &mut array_creation_info.seen_keys,
+                tast_info,
+                context,
+            );
+        }
+    }
+
     // Now check types of the values
     expression_analyzer::analyze(statements_analyzer, &item.1, tast_info, context, &mut None);
 
@@ -442,6 +612,38 @@ fn analyze_keyvals_item(
     true
 }
 
+// A repeated key silently replaces the earlier value, which is almost never intended
+pub(crate) fn check_duplicate_key(
+    statements_analyzer: &StatementsAnalyzer,
+    key: DictKey,
+    key_pos: &Pos,
+    seen_keys: &mut FxHashMap<DictKey, HPos>,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let key_hpos = statements_analyzer.get_hpos(key_pos);
+
+    if let Some(first_pos) = seen_keys.get(&key) {
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::DuplicateArrayKey,
+                format!(
+                    "Key {} is already set on line {}, column {}",
+                    key.to_string(Some(&statements_analyzer.get_codebase().interner)),
+                    first_pos.start_line,
+                    first_pos.start_column
+                ),
+                key_hpos,
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    } else {
+        seen_keys.insert(key, key_hpos);
+    }
+}
+
 fn add_array_value_dataflow(
     statements_analyzer: &StatementsAnalyzer,
     value_type: &TUnion,
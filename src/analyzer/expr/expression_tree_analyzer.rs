@@ -0,0 +1,140 @@
+use crate::expression_analyzer;
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::stmt_analyzer;
+use crate::typed_ast::TastInfo;
+use crate::{scope_context::ScopeContext, statements_analyzer::StatementsAnalyzer};
+use hakana_reflection_info::method_identifier::MethodIdentifier;
+use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
+use hakana_reflector::typehint_resolver::get_type_from_hint;
+use hakana_type::get_mixed_any;
+use hakana_type::template::{inferred_type_replacer, TemplateResult};
+use hakana_type::type_expander::{self, StaticClassType, TypeExpansionOptions};
+use indexmap::IndexMap;
+use oxidized::{aast, ast_defs::Pos};
+use rustc_hash::FxHashMap;
+
+// Expression trees (e.g. SomeDsl`1 + $x`) are checked through their virtualized form, in
+// which every literal and operator is a call to a method on the visitor class, so uses the
+// DSL doesn't support are reported as ordinary method call issues. Splices are evaluated
+// first, as regular Hack, into the variables the virtualized expression refers to.
+pub(crate) fn analyze(
+    statements_analyzer: &StatementsAnalyzer,
+    expression_tree: &aast::ExpressionTree<(), ()>,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &mut ScopeContext,
+) -> bool {
+    for stmt in expression_tree
+        .splices
+        .iter()
+        .chain(expression_tree.function_pointers.iter())
+    {
+        if !stmt_analyzer::analyze(statements_analyzer, stmt, tast_info, context, &mut None) {
+            return false;
+        }
+    }
+
+    let was_inside_general_use = context.inside_general_use;
+    context.inside_general_use = true;
+
+    let analyzed = expression_analyzer::analyze(
+        statements_analyzer,
+        &expression_tree.virtualized_expr,
+        tast_info,
+        context,
+        &mut None,
+    );
+
+    context.inside_general_use = was_inside_general_use;
+
+    if !analyzed {
+        return false;
+    }
+
+    let virtualized_type = tast_info
+        .get_expr_type(expression_tree.virtualized_expr.pos())
+        .cloned()
+        .unwrap_or(get_mixed_any());
+
+    let tree_type = get_tree_type(
+        statements_analyzer,
+        expression_tree,
+        context,
+        tast_info,
+        virtualized_type,
+    )
+    .unwrap_or(get_mixed_any());
+
+    tast_info.set_expr_type(&pos, tree_type);
+
+    true
+}
+
+// At runtime the tree is whatever the visitor's makeTree method returns, with its template
+// param (TInfer in the standard visitor signature) bound to the type of the virtualized
+// expression
+fn get_tree_type(
+    statements_analyzer: &StatementsAnalyzer,
+    expression_tree: &aast::ExpressionTree<(), ()>,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+    virtualized_type: TUnion,
+) -> Option<TUnion> {
+    let codebase = statements_analyzer.get_codebase();
+
+    let visitor_type = get_type_from_hint(
+        &expression_tree.hint.1,
+        context.function_context.calling_class.as_ref(),
+        &statements_analyzer.get_type_resolution_context(),
+        statements_analyzer.get_file_analyzer().resolved_names,
+    )?;
+
+    let visitor_name = if let TAtomic::TNamedObject { name, .. } = visitor_type.get_single() {
+        *name
+    } else {
+        return None;
+    };
+
+    let declaring_method_id = codebase.get_declaring_method_id(&MethodIdentifier(
+        visitor_name,
+        codebase.interner.get("makeTree")?,
+    ));
+
+    let method_storage = codebase.get_method(&declaring_method_id)?;
+
+    let mut tree_type = method_storage.return_type.clone()?;
+
+    if !method_storage.template_types.is_empty() {
+        let template_result = TemplateResult::new(
+            IndexMap::new(),
+            method_storage
+                .template_types
+                .iter()
+                .map(|(template_name, map)| {
+                    (
+                        *template_name,
+                        map.iter()
+                            .map(|(entity, _)| (*entity, virtualized_type.clone()))
+                            .collect::<FxHashMap<_, _>>(),
+                    )
+                })
+                .collect(),
+        );
+
+        tree_type = inferred_type_replacer::replace(&tree_type, &template_result, codebase);
+    }
+
+    type_expander::expand_union(
+        codebase,
+        &mut tree_type,
+        &TypeExpansionOptions {
+            self_class: Some(&declaring_method_id.0),
+            static_class_type: StaticClassType::Name(&visitor_name),
+            ..Default::default()
+        },
+        &mut tast_info.data_flow_graph,
+    );
+
+    Some(tree_type)
+}
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
 use hakana_reflection_info::{
     data_flow::{
@@ -18,6 +18,7 @@ use hakana_type::{
 use oxidized::{aast, ast_defs::Pos};
 use rustc_hash::FxHashSet;
 
+use crate::expr::missing_await_analyzer;
 use crate::{expr::expression_identifier, typed_ast::TastInfo};
 use crate::{expression_analyzer, scope_analyzer::ScopeAnalyzer};
 use crate::{scope_context::ScopeContext, statements_analyzer::StatementsAnalyzer};
@@ -94,7 +95,17 @@ pub(crate) fn analyze(
 
     let stmt_var_type = tast_info.get_expr_type(expr.0.pos()).cloned();
 
-    if let Some(stmt_var_type) = stmt_var_type {
+    if let Some(mut stmt_var_type) = stmt_var_type {
+        if let Some(awaited_type) = missing_await_analyzer::check_array_access(
+            statements_analyzer,
+            expr.0,
+            &stmt_var_type,
+            context,
+            tast_info,
+        ) {
+            stmt_var_type = awaited_type;
+        }
+
         // maybe todo handle access on null
 
         let stmt_type = Some(get_array_access_type_given_offset(
@@ -109,6 +120,11 @@ pub(crate) fn analyze(
         ));
 
         if let Some(mut stmt_type) = stmt_type.clone() {
+            // the elements of a readonly collection are readonly too
+            if stmt_var_type.readonly && !context.inside_assignment {
+                stmt_type.readonly = true;
+            }
+
             if let Some(keyed_array_var_id) = &keyed_array_var_id {
                 let can_store_result = context.inside_assignment || !stmt_var_type.is_mixed();
 
@@ -203,12 +219,17 @@ pub(crate) fn add_array_fetch_dataflow(
             };
 
             let dim_value = if let Some(key_type_single) = key_type_single {
-                if let TAtomic::TLiteralString { value, .. } = key_type_single {
-                    Some(value.clone())
-                } else if let TAtomic::TLiteralInt { value, .. } = key_type_single {
-                    Some(value.to_string())
-                } else {
-                    None
+                match key_type_single {
+                    TAtomic::TLiteralString { value, .. } => Some(value.clone()),
+                    TAtomic::TLiteralInt { value, .. } => Some(value.to_string()),
+                    TAtomic::TEnumLiteralCase {
+                        enum_name,
+                        member_name,
+                        ..
+                    } => statements_analyzer
+                        .get_codebase()
+                        .get_enum_case_key_value(enum_name, member_name),
+                    _ => None,
                 }
             } else {
                 None
@@ -333,6 +354,24 @@ pub(crate) fn get_array_access_type_given_offset(
 
         match atomic_var_type {
             TAtomic::TKeyset { .. } | TAtomic::TVec { .. } => {
+                if let TAtomic::TVec {
+                    is_tuple: true,
+                    known_items,
+                    ..
+                } = atomic_var_type
+                {
+                    check_tuple_access(
+                        statements_analyzer,
+                        tast_info,
+                        context,
+                        stmt,
+                        atomic_var_type,
+                        known_items.as_ref(),
+                        offset_type,
+                        in_assignment,
+                    );
+                }
+
                 let new_type = handle_array_access_on_vec(
                     statements_analyzer,
                     stmt.2,
@@ -342,6 +381,8 @@ pub(crate) fn get_array_access_type_given_offset(
                     offset_type.clone(),
                     in_assignment,
                     &mut has_valid_expected_offset,
+                    context.inside_isset || context.inside_unset,
+                    &mut false,
                 );
 
                 if let Some(existing_type) = stmt_type {
@@ -371,7 +412,11 @@ pub(crate) fn get_array_access_type_given_offset(
                     stmt_type = Some(new_type);
                 }
             }
-            TAtomic::TString | TAtomic::TStringWithFlags(..) | TAtomic::TLiteralString { .. } => {
+            TAtomic::TString
+            | TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. }
+            | TAtomic::TLiteralString { .. } => {
                 let new_type = handle_array_access_on_string(
                     statements_analyzer,
                     atomic_var_type.clone(),
@@ -556,6 +601,79 @@ pub(crate) fn get_array_access_type_given_offset(
     }
 }
 
+// Tuples have a fixed arity, so can only be accessed with literal offsets, and
+// can't be appended to
+fn check_tuple_access(
+    statements_analyzer: &StatementsAnalyzer,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+    stmt: (&aast::Expr<(), ()>, Option<&aast::Expr<(), ()>>, &Pos),
+    tuple: &TAtomic,
+    known_items: Option<&BTreeMap<usize, (bool, TUnion)>>,
+    offset_type: &TUnion,
+    in_assignment: bool,
+) {
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let (issue_kind, description) = if stmt.1.is_none() {
+        (
+            IssueKind::InvalidTupleAppend,
+            format!("Cannot append to tuple {}", tuple.get_id(Some(interner))),
+        )
+    } else if offset_type.is_mixed() {
+        return;
+    } else if !offset_type
+        .types
+        .iter()
+        .all(|t| matches!(t, TAtomic::TLiteralInt { .. }))
+    {
+        (
+            IssueKind::InvalidTupleOffset,
+            format!(
+                "Tuple {} can only be accessed with a literal int offset, {} given",
+                tuple.get_id(Some(interner)),
+                offset_type.get_id(Some(interner))
+            ),
+        )
+    } else if in_assignment {
+        // out-of-bounds fetches are already reported as UndefinedIntArrayOffset
+        if let Some(offset) = offset_type.types.iter().find_map(|t| match t {
+            TAtomic::TLiteralInt { value }
+                if !known_items.map_or(false, |known_items| {
+                    known_items.contains_key(&(*value as usize))
+                }) =>
+            {
+                Some(*value)
+            }
+            _ => None,
+        }) {
+            (
+                IssueKind::InvalidTupleOffset,
+                format!(
+                    "Offset {} is out of bounds for tuple {}",
+                    offset,
+                    tuple.get_id(Some(interner))
+                ),
+            )
+        } else {
+            return;
+        }
+    } else {
+        return;
+    };
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            issue_kind,
+            description,
+            statements_analyzer.get_hpos(&stmt.2),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
 // Handle array access on vec-list collections
 pub(crate) fn handle_array_access_on_vec(
     statements_analyzer: &StatementsAnalyzer,
@@ -566,6 +684,8 @@ pub(crate) fn handle_array_access_on_vec(
     dim_type: TUnion,
     in_assignment: bool,
     has_valid_expected_offset: &mut bool,
+    allow_possibly_undefined: bool,
+    has_possibly_undefined: &mut bool,
 ) -> TUnion {
     let codebase = statements_analyzer.get_codebase();
 
@@ -593,29 +713,23 @@ pub(crate) fn handle_array_access_on_vec(
         if let Some(val) = dim_type.get_single_literal_int_value() {
             let index = val as usize;
 
+            let dict_key = DictKey::Int(index as u32);
+
             if let Some((actual_possibly_undefined, actual_value)) = known_items.get(&index) {
                 *has_valid_expected_offset = true;
                 // we know exactly which item we are fetching
 
-                if *actual_possibly_undefined
-                    && !context.inside_isset
-                    && !context.inside_unset
-                    && !in_assignment
-                {
-                    // oh no!
-                    tast_info.maybe_add_issue(
-                        Issue::new(
-                            IssueKind::PossiblyUndefinedIntArrayOffset,
-                            format!(
-                                "Fetch on {} using possibly-undefined key {}",
-                                vec.get_id(Some(&codebase.interner)),
-                                val
-                            ),
-                            statements_analyzer.get_hpos(&pos),
-                            &context.function_context.calling_functionlike_id,
-                        ),
-                        statements_analyzer.get_config(),
-                        statements_analyzer.get_file_path_actual(),
+                if *actual_possibly_undefined {
+                    handle_possibly_undefined_offset(
+                        statements_analyzer,
+                        pos,
+                        tast_info,
+                        context,
+                        &vec,
+                        &dict_key,
+                        in_assignment,
+                        allow_possibly_undefined,
+                        has_possibly_undefined,
                     );
                 }
 
@@ -624,19 +738,13 @@ pub(crate) fn handle_array_access_on_vec(
 
             if !in_assignment {
                 if type_param.is_nothing() {
-                    tast_info.maybe_add_issue(
-                        Issue::new(
-                            IssueKind::UndefinedIntArrayOffset,
-                            format!(
-                                "Invalid vec fetch on {} using offset {}",
-                                vec.get_id(Some(&codebase.interner)),
-                                index.to_string()
-                            ),
-                            statements_analyzer.get_hpos(&pos),
-                            &context.function_context.calling_functionlike_id,
-                        ),
-                        statements_analyzer.get_config(),
-                        statements_analyzer.get_file_path_actual(),
+                    report_undefined_offset(
+                        statements_analyzer,
+                        pos,
+                        tast_info,
+                        context,
+                        &vec,
+                        &dict_key,
                     );
                 }
 
@@ -720,29 +828,18 @@ pub(crate) fn handle_array_access_on_dict(
 
                 let expr_type = (*actual_value).clone();
 
-                if actual_possibly_undefined && !in_assignment {
-                    if !allow_possibly_undefined {
-                        // oh no!
-                        tast_info.maybe_add_issue(
-                            Issue::new(
-                                match &dict_key {
-                                    DictKey::Int(_) => IssueKind::PossiblyUndefinedIntArrayOffset,
-                                    _ => IssueKind::PossiblyUndefinedStringArrayOffset,
-                                },
-                                format!(
-                                    "Fetch on {} using possibly-undefined key {}",
-                                    dict.get_id(Some(&codebase.interner)),
-                                    dict_key.to_string(Some(&codebase.interner))
-                                ),
-                                statements_analyzer.get_hpos(&pos),
-                                &context.function_context.calling_functionlike_id,
-                            ),
-                            statements_analyzer.get_config(),
-                            statements_analyzer.get_file_path_actual(),
-                        );
-                    } else {
-                        *has_possibly_undefined = true;
-                    }
+                if actual_possibly_undefined {
+                    handle_possibly_undefined_offset(
+                        statements_analyzer,
+                        pos,
+                        tast_info,
+                        context,
+                        dict,
+                        &dict_key,
+                        in_assignment,
+                        allow_possibly_undefined,
+                        has_possibly_undefined,
+                    );
                 }
 
                 return expr_type;
@@ -753,38 +850,14 @@ pub(crate) fn handle_array_access_on_dict(
                     return params.1.clone();
                 }
 
-                if !context.inside_isset {
-                    // oh no!
-                    tast_info.maybe_add_issue(
-                        Issue::new(
-                            IssueKind::UndefinedStringArrayOffset,
-                            format!(
-                                "Invalid dict fetch on {} using key {}",
-                                dict.get_id(Some(&codebase.interner)),
-                                dict_key.to_string(Some(&codebase.interner))
-                            ),
-                            statements_analyzer.get_hpos(&pos),
-                            &context.function_context.calling_functionlike_id,
-                        ),
-                        statements_analyzer.get_config(),
-                        statements_analyzer.get_file_path_actual(),
-                    );
-                } else {
-                    tast_info.maybe_add_issue(
-                        Issue::new(
-                            IssueKind::ImpossibleNonnullEntryCheck,
-                            format!(
-                                "Type {} does not have a nonnull entry for {}",
-                                dict.get_id(Some(&codebase.interner)),
-                                dict_key.to_string(Some(&codebase.interner))
-                            ),
-                            statements_analyzer.get_hpos(&pos),
-                            &context.function_context.calling_functionlike_id,
-                        ),
-                        statements_analyzer.get_config(),
-                        statements_analyzer.get_file_path_actual(),
-                    );
-                }
+                report_undefined_offset(
+                    statements_analyzer,
+                    pos,
+                    tast_info,
+                    context,
+                    dict,
+                    &dict_key,
+                );
 
                 // since we're emitting a very specific error
                 // we don't want to emit another error afterwards
@@ -834,30 +907,17 @@ pub(crate) fn handle_array_access_on_dict(
         // }
         return if let Some(params) = params {
             if let Some(dict_key) = dim_type.get_single_dict_key() {
-                if !in_assignment {
-                    if !allow_possibly_undefined {
-                        // oh no!
-                        tast_info.maybe_add_issue(
-                            Issue::new(
-                                match &dict_key {
-                                    DictKey::Int(_) => IssueKind::PossiblyUndefinedIntArrayOffset,
-                                    _ => IssueKind::PossiblyUndefinedStringArrayOffset,
-                                },
-                                format!(
-                                    "Fetch on {} using possibly-undefined key {}",
-                                    dict.get_id(Some(&codebase.interner)),
-                                    dict_key.to_string(Some(&codebase.interner))
-                                ),
-                                statements_analyzer.get_hpos(&pos),
-                                &context.function_context.calling_functionlike_id,
-                            ),
-                            statements_analyzer.get_config(),
-                            statements_analyzer.get_file_path_actual(),
-                        );
-                    } else {
-                        *has_possibly_undefined = true;
-                    }
-                }
+                handle_possibly_undefined_offset(
+                    statements_analyzer,
+                    pos,
+                    tast_info,
+                    context,
+                    dict,
+                    &dict_key,
+                    in_assignment,
+                    allow_possibly_undefined,
+                    has_possibly_undefined,
+                );
             }
 
             params.1.clone()
@@ -869,6 +929,96 @@ pub(crate) fn handle_array_access_on_dict(
     return get_nothing();
 }
 
+// Every fetch of a key that a vec or dict might not have goes through here, so that
+// isset, unset and assignment contexts are treated the same way for both
+pub(crate) fn handle_possibly_undefined_offset(
+    statements_analyzer: &StatementsAnalyzer,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+    array: &TAtomic,
+    dict_key: &DictKey,
+    in_assignment: bool,
+    allow_possibly_undefined: bool,
+    has_possibly_undefined: &mut bool,
+) {
+    if in_assignment {
+        return;
+    }
+
+    if allow_possibly_undefined {
+        *has_possibly_undefined = true;
+        return;
+    }
+
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            match dict_key {
+                DictKey::Int(_) => IssueKind::PossiblyUndefinedIntArrayOffset,
+                _ => IssueKind::PossiblyUndefinedStringArrayOffset,
+            },
+            format!(
+                "Fetch on {} using possibly-undefined key {}",
+                array.get_id(Some(interner)),
+                dict_key.to_string(Some(interner))
+            ),
+            statements_analyzer.get_hpos(&pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
+// For keys that a vec or dict can never have. Inside isset the fetch isn't an error
+// in itself, but the check can never pass.
+fn report_undefined_offset(
+    statements_analyzer: &StatementsAnalyzer,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+    array: &TAtomic,
+    dict_key: &DictKey,
+) {
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let (issue_kind, description) = if context.inside_isset {
+        (
+            IssueKind::ImpossibleNonnullEntryCheck,
+            format!(
+                "Type {} does not have a nonnull entry for {}",
+                array.get_id(Some(interner)),
+                dict_key.to_string(Some(interner))
+            ),
+        )
+    } else {
+        (
+            match dict_key {
+                DictKey::Int(_) => IssueKind::UndefinedIntArrayOffset,
+                _ => IssueKind::UndefinedStringArrayOffset,
+            },
+            format!(
+                "Invalid fetch on {} using key {}",
+                array.get_id(Some(interner)),
+                dict_key.to_string(Some(interner))
+            ),
+        )
+    };
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            issue_kind,
+            description,
+            statements_analyzer.get_hpos(&pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
 // Handle array access on strings
 pub(crate) fn handle_array_access_on_string(
     statements_analyzer: &StatementsAnalyzer,
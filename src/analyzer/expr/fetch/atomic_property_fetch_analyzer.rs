@@ -180,6 +180,18 @@ pub(crate) fn analyze(
     //     $class_property_type->has_mutations = false;
     // }
 
+    if !in_assignment
+        && codebase
+            .classlike_infos
+            .get(declaring_property_class.unwrap())
+            .and_then(|declaring_classlike_storage| {
+                declaring_classlike_storage.properties.get(&prop_name)
+            })
+            .map_or(false, |property_storage| property_storage.is_readonly)
+    {
+        class_property_type.readonly = true;
+    }
+
     tast_info.set_expr_type(
         &pos,
         add_optional_union_type(class_property_type, tast_info.get_expr_type(pos), codebase),
@@ -16,7 +16,8 @@ use oxidized::{
     aast::{self, ClassId},
     ast_defs::Pos,
 };
-use rustc_hash::{FxHashSet};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::sync::Arc;
 
 pub(crate) fn analyze(
     statements_analyzer: &StatementsAnalyzer,
@@ -115,6 +116,29 @@ pub(crate) fn analyze(
         }
     };
 
+    if !codebase.class_or_interface_or_enum_or_trait_exists(&classlike_name) {
+        if let Some(defining_entities) = statements_analyzer
+            .get_type_resolution_context()
+            .template_type_map
+            .get(&classlike_name)
+        {
+            let stmt_type = analyse_generic_param_class_constant(
+                codebase,
+                tast_info,
+                context,
+                &classlike_name,
+                defining_entities,
+                const_name,
+                statements_analyzer,
+                pos,
+            )
+            .unwrap_or(get_mixed_any());
+            tast_info.set_expr_type(&pos, stmt_type);
+
+            return true;
+        }
+    }
+
     let stmt_type = analyse_known_class_constant(
         codebase,
         tast_info,
@@ -131,6 +155,64 @@ pub(crate) fn analyze(
     return true;
 }
 
+// Handles T::class and T::SOME_CONST where T is a (reified) generic param. T::class is a
+// classname<T>, which callers resolve to the classname of whatever T is at the call site,
+// while other constants are looked up on the class T is constrained to.
+fn analyse_generic_param_class_constant(
+    codebase: &CodebaseInfo,
+    tast_info: &mut TastInfo,
+    context: &mut ScopeContext,
+    param_name: &StrId,
+    defining_entities: &FxHashMap<StrId, Arc<TUnion>>,
+    const_name: &String,
+    statements_analyzer: &StatementsAnalyzer,
+    pos: &Pos,
+) -> Option<TUnion> {
+    // a function's own type params shadow its class's, and the choice mustn't depend on
+    // hash ordering
+    let (defining_entity, as_type) = defining_entities
+        .iter()
+        .min_by_key(|(defining_entity, _)| {
+            let defining_entity_name = codebase.interner.lookup(defining_entity);
+            (
+                !defining_entity_name.starts_with("fn-"),
+                defining_entity_name,
+            )
+        })
+        .unwrap();
+
+    if const_name == "class" {
+        return Some(wrap_atomic(TAtomic::TGenericClassname {
+            param_name: *param_name,
+            defining_entity: *defining_entity,
+            as_type: Box::new(TAtomic::TGenericParam {
+                param_name: *param_name,
+                as_type: (**as_type).clone(),
+                defining_entity: *defining_entity,
+                from_class: false,
+                extra_types: None,
+            }),
+        }));
+    }
+
+    if as_type.is_single() {
+        if let TAtomic::TNamedObject { name, .. } = as_type.get_single() {
+            return analyse_known_class_constant(
+                codebase,
+                tast_info,
+                context,
+                name,
+                const_name,
+                true,
+                statements_analyzer,
+                pos,
+            );
+        }
+    }
+
+    None
+}
+
 fn analyse_known_class_constant(
     codebase: &CodebaseInfo,
     tast_info: &mut TastInfo,
@@ -148,28 +230,33 @@ fn analyse_known_class_constant(
             }));
         }
 
-        tast_info.maybe_add_issue(
-            if const_name == "class" {
-                Issue::new(
-                    IssueKind::NonExistentType,
-                    format!("Unknown class {}", codebase.interner.lookup(classlike_name)),
-                    statements_analyzer.get_hpos(&pos),
-                    &context.function_context.calling_functionlike_id,
-                )
-            } else {
-                Issue::new(
-                    IssueKind::NonExistentClasslike,
-                    format!(
-                        "Unknown classlike {}",
-                        codebase.interner.lookup(classlike_name)
-                    ),
-                    statements_analyzer.get_hpos(&pos),
-                    &context.function_context.calling_functionlike_id,
-                )
-            },
-            statements_analyzer.get_config(),
-            statements_analyzer.get_file_path_actual(),
-        );
+        if !context
+            .asserted_existing_symbols
+            .contains(codebase.interner.lookup(classlike_name))
+        {
+            tast_info.maybe_add_issue(
+                if const_name == "class" {
+                    Issue::new(
+                        IssueKind::NonExistentType,
+                        format!("Unknown class {}", codebase.interner.lookup(classlike_name)),
+                        statements_analyzer.get_hpos(&pos),
+                        &context.function_context.calling_functionlike_id,
+                    )
+                } else {
+                    Issue::new(
+                        IssueKind::NonExistentClasslike,
+                        format!(
+                            "Unknown classlike {}",
+                            codebase.interner.lookup(classlike_name)
+                        ),
+                        statements_analyzer.get_hpos(&pos),
+                        &context.function_context.calling_functionlike_id,
+                    )
+                },
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
 
         return None;
     }
@@ -228,6 +315,20 @@ fn analyse_known_class_constant(
         false,
     );
 
+    // constants can come from parent classes, interfaces and traits, which are then
+    // referenced too
+    if let Some(declaring_classlike) =
+        codebase.get_declaring_class_for_constant(classlike_name, &const_name)
+    {
+        if declaring_classlike != classlike_name {
+            tast_info.symbol_references.add_reference_to_class_member(
+                &context.function_context,
+                (*declaring_classlike, const_name),
+                false,
+            );
+        }
+    }
+
     let classlike_storage = codebase.classlike_infos.get(classlike_name).unwrap();
 
     if !classlike_storage.constants.contains_key(&const_name) {
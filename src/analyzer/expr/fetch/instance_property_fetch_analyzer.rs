@@ -147,7 +147,17 @@ pub(crate) fn analyze(
 
     let mut stmt_type = tast_info.get_rc_expr_type(&pos).cloned();
 
-    if has_nullsafe_null {
+    // a nullsafe fetch on a value that's always null is just null, which still carries
+    // the value's dataflow so that later hops in a chain can be traced back to it
+    if has_nullsafe_null && stmt_type.is_none() {
+        let mut null_type = get_null();
+        null_type.parent_nodes = stmt_var_type.parent_nodes.clone();
+
+        let null_type = Rc::new(null_type);
+
+        tast_info.set_rc_expr_type(pos, null_type.clone());
+        stmt_type = Some(null_type);
+    } else if has_nullsafe_null {
         if let Some(ref mut stmt_type) = stmt_type {
             if !stmt_type.is_nullable_mixed() {
                 let mut stmt_type_inner = (**stmt_type).clone();
@@ -167,6 +177,20 @@ pub(crate) fn analyze(
         // todo emit issue
     }
 
+    // properties of a readonly object can only be read as readonly values
+    if stmt_var_type.readonly && !in_assignment {
+        if let Some(ref mut stmt_type) = stmt_type {
+            if !stmt_type.readonly {
+                let mut stmt_type_inner = (**stmt_type).clone();
+                stmt_type_inner.readonly = true;
+
+                *stmt_type = Rc::new(stmt_type_inner);
+
+                tast_info.set_rc_expr_type(pos, stmt_type.clone());
+            }
+        }
+    }
+
     // TODO $stmt_var_type->isNullable(
 
     // TODO  if ($invalid_fetch_types) {
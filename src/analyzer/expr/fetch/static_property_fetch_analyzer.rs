@@ -66,19 +66,24 @@ pub(crate) fn analyze(
             false,
         );
 
-        tast_info.maybe_add_issue(
-            Issue::new(
-                IssueKind::NonExistentClass,
-                format!(
-                    "Cannot access property on undefined class {}",
-                    codebase.interner.lookup(&classlike_name)
+        if !context
+            .asserted_existing_symbols
+            .contains(codebase.interner.lookup(&classlike_name))
+        {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::NonExistentClass,
+                    format!(
+                        "Cannot access property on undefined class {}",
+                        codebase.interner.lookup(&classlike_name)
+                    ),
+                    statements_analyzer.get_hpos(&pos),
+                    &context.function_context.calling_functionlike_id,
                 ),
-                statements_analyzer.get_hpos(&pos),
-                &context.function_context.calling_functionlike_id,
-            ),
-            statements_analyzer.get_config(),
-            statements_analyzer.get_file_path_actual(),
-        );
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
 
         return false;
     }
@@ -155,13 +160,30 @@ pub(crate) fn analyze(
         false,
     );
 
+    // reads via a subclass see writes via the declaring class, and vice versa
+    let dataflow_property_id = if statements_analyzer
+        .get_config()
+        .security_config
+        .static_properties_as_globals
+    {
+        if let Some(declaring_property_class) =
+            codebase.get_declaring_class_for_property(&property_id.0, &property_id.1)
+        {
+            (*declaring_property_class, property_id.1)
+        } else {
+            property_id
+        }
+    } else {
+        property_id
+    };
+
     // Handle scoped property fetches
     if context.has_variable(&var_id) {
         let mut stmt_type = (**context.vars_in_scope.get(&var_id).unwrap()).clone();
 
         stmt_type = add_unspecialized_property_fetch_dataflow(
             &None,
-            &property_id,
+            &dataflow_property_id,
             statements_analyzer.get_hpos(pos),
             tast_info,
             false,
@@ -233,7 +255,7 @@ pub(crate) fn analyze(
 
         inserted_type = add_unspecialized_property_fetch_dataflow(
             &None,
-            &property_id,
+            &dataflow_property_id,
             statements_analyzer.get_hpos(pos),
             tast_info,
             false,
@@ -0,0 +1,208 @@
+use hakana_reflection_info::analysis_result::Replacement;
+use hakana_reflection_info::issue::{Issue, IssueKind, QuickFixEdit};
+use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
+use hakana_type::type_comparator::type_comparison_result::TypeComparisonResult;
+use hakana_type::type_comparator::union_type_comparator;
+use oxidized::aast;
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// Checks for an Awaitable<T> that's passed somewhere expecting a T.
+// Returns the awaited type if an issue was emitted.
+pub(crate) fn check_argument(
+    statements_analyzer: &StatementsAnalyzer,
+    input_expr: &aast::Expr<(), ()>,
+    input_type: &TUnion,
+    param_type: &TUnion,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+) -> Option<TUnion> {
+    let codebase = statements_analyzer.get_codebase();
+
+    let awaited_type = get_awaited_type(statements_analyzer, input_type)?;
+
+    if !union_type_comparator::is_contained_by(
+        codebase,
+        &awaited_type,
+        param_type,
+        false,
+        false,
+        false,
+        &mut TypeComparisonResult::new(),
+    ) {
+        return None;
+    }
+
+    report(
+        statements_analyzer,
+        input_expr,
+        input_type,
+        &awaited_type,
+        false,
+        context,
+        tast_info,
+    );
+
+    Some(awaited_type)
+}
+
+// Checks for an Awaitable<T> compared with a value that could only ever be a T
+pub(crate) fn check_comparison_operand(
+    statements_analyzer: &StatementsAnalyzer,
+    operand_expr: &aast::Expr<(), ()>,
+    operand_type: &TUnion,
+    other_operand_type: &TUnion,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let awaited_type =
+        if let Some(awaited_type) = get_awaited_type(statements_analyzer, operand_type) {
+            awaited_type
+        } else {
+            return;
+        };
+
+    if other_operand_type.is_mixed()
+        || union_type_comparator::can_expression_types_be_identical(
+            codebase,
+            operand_type,
+            other_operand_type,
+            false,
+        )
+        || !union_type_comparator::can_expression_types_be_identical(
+            codebase,
+            &awaited_type,
+            other_operand_type,
+            false,
+        )
+    {
+        return;
+    }
+
+    report(
+        statements_analyzer,
+        operand_expr,
+        operand_type,
+        &awaited_type,
+        false,
+        context,
+        tast_info,
+    );
+}
+
+// Checks for array access on an Awaitable, which can never succeed.
+// Returns the awaited type if an issue was emitted.
+pub(crate) fn check_array_access(
+    statements_analyzer: &StatementsAnalyzer,
+    array_expr: &aast::Expr<(), ()>,
+    array_type: &TUnion,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+) -> Option<TUnion> {
+    let awaited_type = get_awaited_type(statements_analyzer, array_type)?;
+
+    report(
+        statements_analyzer,
+        array_expr,
+        array_type,
+        &awaited_type,
+        true,
+        context,
+        tast_info,
+    );
+
+    Some(awaited_type)
+}
+
+fn get_awaited_type(statements_analyzer: &StatementsAnalyzer, t: &TUnion) -> Option<TUnion> {
+    if !t.is_single() {
+        return None;
+    }
+
+    if let TAtomic::TNamedObject {
+        name,
+        type_params: Some(type_params),
+        ..
+    } = t.get_single()
+    {
+        if statements_analyzer.get_codebase().interner.lookup(name) == "HH\\Awaitable"
+            && type_params.len() == 1
+        {
+            let mut awaited_type = type_params[0].clone();
+            awaited_type.parent_nodes.extend(t.parent_nodes.clone());
+
+            return Some(awaited_type);
+        }
+    }
+
+    None
+}
+
+fn report(
+    statements_analyzer: &StatementsAnalyzer,
+    expr: &aast::Expr<(), ()>,
+    awaitable_type: &TUnion,
+    awaited_type: &TUnion,
+    needs_parentheses: bool,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+) {
+    let config = statements_analyzer.get_config();
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let pos = expr.pos();
+
+    let edits = if needs_parentheses {
+        vec![
+            QuickFixEdit {
+                start_offset: pos.start_offset(),
+                end_offset: pos.start_offset(),
+                replacement: "(await ".to_string(),
+            },
+            QuickFixEdit {
+                start_offset: pos.end_offset(),
+                end_offset: pos.end_offset(),
+                replacement: ")".to_string(),
+            },
+        ]
+    } else {
+        vec![QuickFixEdit {
+            start_offset: pos.start_offset(),
+            end_offset: pos.start_offset(),
+            replacement: "await ".to_string(),
+        }]
+    };
+
+    let mut issue = Issue::new(
+        IssueKind::MissingAwait,
+        format!(
+            "{} is used where {} is expected, did you forget to await it?",
+            awaitable_type.get_id(Some(interner)),
+            awaited_type.get_id(Some(interner)),
+        ),
+        statements_analyzer.get_hpos(expr.pos()),
+        &context.function_context.calling_functionlike_id,
+    );
+
+    // await is only allowed in async functions
+    if context.inside_async {
+        if config.issues_to_fix.contains(&IssueKind::MissingAwait) && !config.add_fixmes {
+            for edit in &edits {
+                tast_info.replacements.insert(
+                    (edit.start_offset, edit.end_offset),
+                    Replacement::Substitute(edit.replacement.clone()),
+                );
+            }
+        }
+
+        issue = issue.with_quickfix("Add await".to_string(), edits);
+    }
+
+    tast_info.maybe_add_issue(issue, config, statements_analyzer.get_file_path_actual());
+}
@@ -11,10 +11,15 @@ pub(crate) mod const_fetch_analyzer;
 pub(crate) mod echo_analyzer;
 pub(crate) mod exit_analyzer;
 pub mod expression_identifier;
+pub(crate) mod expression_tree_analyzer;
 pub(crate) mod fetch;
 pub(crate) mod isset_analyzer;
+pub(crate) mod missing_await_analyzer;
+pub(crate) mod nullable_return_analyzer;
 pub(crate) mod pipe_analyzer;
 pub(crate) mod prefixed_string_analyzer;
+pub(crate) mod readonly_analyzer;
+pub(crate) mod redundant_computation_analyzer;
 pub(crate) mod shape_analyzer;
 pub(crate) mod ternary_analyzer;
 pub(crate) mod tuple_analyzer;
@@ -0,0 +1,177 @@
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::t_union::TUnion;
+use oxidized::aast;
+use oxidized::ast_defs::Pos;
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// The result of calling one of the config's must_handle_null_functions, which has
+// to be null-checked (or narrowed, or coalesced) before it's used
+#[derive(Clone, Debug)]
+pub struct UncheckedNullableReturn {
+    pub function_name: String,
+    // the variable the result was assigned to, if any
+    pub var_id: Option<String>,
+    // where the result was first used without a check
+    pub unchecked_use: Option<HPos>,
+}
+
+pub(crate) fn track_call(
+    statements_analyzer: &StatementsAnalyzer,
+    functionlike_ids: &[FunctionLikeIdentifier],
+    return_type: &TUnion,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let config = statements_analyzer.get_config();
+
+    // inside isset, which includes the left side of ??, null is handled
+    if config.must_handle_null_functions.is_empty()
+        || !return_type.is_nullable()
+        || context.inside_isset
+    {
+        return;
+    }
+
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let function_name = if let Some(function_name) = functionlike_ids
+        .iter()
+        .map(|functionlike_id| functionlike_id.to_string(interner))
+        .find(|function_name| config.must_handle_null_functions.contains(function_name))
+    {
+        function_name
+    } else {
+        return;
+    };
+
+    // until we know where the result goes, the call itself is the use
+    tast_info
+        .unchecked_nullable_returns
+        .entry((pos.start_offset(), pos.end_offset()))
+        .or_insert_with(|| UncheckedNullableReturn {
+            function_name,
+            var_id: None,
+            unchecked_use: Some(statements_analyzer.get_hpos(pos)),
+        });
+}
+
+pub(crate) fn handle_assignment(
+    var_id: &String,
+    source_expr: Option<&aast::Expr<(), ()>>,
+    tast_info: &mut TastInfo,
+) {
+    if tast_info.unchecked_nullable_returns.is_empty() {
+        return;
+    }
+
+    let source_offsets = source_expr.map(|e| (e.pos().start_offset(), e.pos().end_offset()));
+
+    // the variable no longer holds any earlier result, but unchecked uses of that
+    // result are still reported
+    tast_info
+        .unchecked_nullable_returns
+        .retain(|offsets, unchecked_return| {
+            Some(*offsets) == source_offsets
+                || unchecked_return.var_id.as_ref() != Some(var_id)
+                || unchecked_return.unchecked_use.is_some()
+        });
+
+    for (offsets, unchecked_return) in tast_info.unchecked_nullable_returns.iter_mut() {
+        if Some(*offsets) == source_offsets {
+            unchecked_return.var_id = Some(var_id.clone());
+            unchecked_return.unchecked_use = None;
+        } else if unchecked_return.var_id.as_ref() == Some(var_id) {
+            unchecked_return.var_id = None;
+        }
+    }
+}
+
+pub(crate) fn handle_variable_read(
+    statements_analyzer: &StatementsAnalyzer,
+    var_id: &String,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+) {
+    for unchecked_return in tast_info.unchecked_nullable_returns.values_mut() {
+        if unchecked_return.var_id.as_ref() == Some(var_id)
+            && unchecked_return.unchecked_use.is_none()
+        {
+            unchecked_return.unchecked_use = Some(statements_analyzer.get_hpos(pos));
+        }
+    }
+}
+
+// Called when the variable is reconciled against some assertion, e.g. `if ($x)`,
+// `$x is null` or `$x ?? $y`. Reading the variable inside the check itself isn't a use,
+// but a check that comes after the result was used doesn't excuse that use
+pub(crate) fn mark_var_checked(var_id: &String, check_pos: &Pos, tast_info: &mut TastInfo) {
+    tast_info
+        .unchecked_nullable_returns
+        .retain(|_, unchecked_return| {
+            unchecked_return.var_id.as_ref() != Some(var_id)
+                || unchecked_return
+                    .unchecked_use
+                    .as_ref()
+                    .map_or(false, |use_pos| {
+                        use_pos.start_offset < check_pos.start_offset()
+                            || use_pos.end_offset > check_pos.end_offset()
+                    })
+        });
+}
+
+// Called for expressions where null is handled, or where it becomes someone else's
+// responsibility (e.g. when returned)
+pub(crate) fn mark_checked(expr: &aast::Expr<(), ()>, tast_info: &mut TastInfo) {
+    if tast_info.unchecked_nullable_returns.is_empty() {
+        return;
+    }
+
+    if let aast::Expr_::Lvar(lid) = &expr.2 {
+        mark_var_checked(&lid.1 .1, expr.pos(), tast_info);
+    } else {
+        tast_info
+            .unchecked_nullable_returns
+            .remove(&(expr.pos().start_offset(), expr.pos().end_offset()));
+    }
+}
+
+pub(crate) fn report_unchecked(
+    statements_analyzer: &StatementsAnalyzer,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let mut unchecked_uses = tast_info
+        .unchecked_nullable_returns
+        .drain()
+        .filter_map(|(_, unchecked_return)| {
+            unchecked_return
+                .unchecked_use
+                .map(|pos| (pos, unchecked_return.function_name))
+        })
+        .collect::<Vec<_>>();
+
+    unchecked_uses.sort_by_key(|(pos, _)| pos.start_offset);
+
+    for (pos, function_name) in unchecked_uses {
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::UnhandledNullableReturn,
+                format!(
+                    "The result of {} may be null, and is used here without being checked",
+                    function_name
+                ),
+                pos,
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+}
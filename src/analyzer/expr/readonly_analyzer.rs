@@ -0,0 +1,58 @@
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::{TUnion, TypeNode};
+use oxidized::ast_defs::Pos;
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// Readonly values can't be passed, returned or stored where a mutable value is expected
+pub(crate) fn check_mutable_use(
+    statements_analyzer: &StatementsAnalyzer,
+    value_type: &TUnion,
+    message: String,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    if !value_type.readonly || !can_be_mutated(value_type) {
+        return;
+    }
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            IssueKind::ReadonlyMismatch,
+            message,
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
+// Scalars, and collections holding only scalars, are copied rather than shared, so
+// the readonly-ness of their values doesn't matter
+fn can_be_mutated(value_type: &TUnion) -> bool {
+    value_type
+        .get_all_child_nodes()
+        .into_iter()
+        .any(|type_node| {
+            matches!(
+                type_node,
+                TypeNode::Atomic(
+                    TAtomic::TNamedObject { .. }
+                        | TAtomic::TObject
+                        | TAtomic::TClosure { .. }
+                        | TAtomic::TClosureAlias { .. }
+                        | TAtomic::TMixed
+                        | TAtomic::TMixedWithFlags(..)
+                        | TAtomic::TMixedFromLoopIsset
+                        | TAtomic::TGenericParam { .. }
+                        | TAtomic::TResource
+                )
+            )
+        })
+}
@@ -0,0 +1,179 @@
+use std::hash::{Hash, Hasher};
+
+use hakana_reflection_info::data_flow::graph::GraphKind;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::functionlike_info::{FnEffect, FunctionLikeInfo};
+use hakana_reflection_info::issue::{Issue, IssueKind, IssueSeverity};
+use no_pos_hash::position_insensitive_hash;
+use oxidized::aast;
+use oxidized::ast_defs::{self, Pos};
+use rustc_hash::FxHasher;
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// Suggests reusing the result of a call to a pure function or static method when an
+// earlier call in the same scope had structurally identical arguments. Arguments are
+// only compared when they're built from literals, constants and local variables, and
+// each variable has to still hold the value it held at the earlier call, which the
+// dataflow nodes of its type identify.
+pub(crate) fn check_call(
+    statements_analyzer: &StatementsAnalyzer,
+    functionlike_id: &FunctionLikeIdentifier,
+    functionlike_storage: &FunctionLikeInfo,
+    args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
+    unpacked_arg: &Option<aast::Expr<(), ()>>,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &mut ScopeContext,
+) {
+    if !statements_analyzer.get_config().find_redundant_computations
+        || tast_info.data_flow_graph.kind != GraphKind::FunctionBody
+    {
+        return;
+    }
+
+    // builtins are cheap, and memoized functions already cache their results
+    if !functionlike_storage.user_defined
+        || functionlike_storage.is_memoized
+        || !matches!(functionlike_storage.effects, FnEffect::None)
+        || unpacked_arg.is_some()
+    {
+        return;
+    }
+
+    let mut var_ids = vec![];
+
+    for (param_kind, arg_expr) in args {
+        if matches!(param_kind, ast_defs::ParamKind::Pinout(_))
+            || !is_stable_expr(arg_expr, &mut var_ids)
+        {
+            return;
+        }
+    }
+
+    var_ids.sort();
+    var_ids.dedup();
+
+    let mut hasher = FxHasher::default();
+
+    functionlike_id.hash(&mut hasher);
+
+    for (_, arg_expr) in args {
+        position_insensitive_hash(arg_expr).hash(&mut hasher);
+    }
+
+    for var_id in var_ids {
+        let var_type = if let Some(var_type) = context.vars_in_scope.get(&var_id) {
+            var_type
+        } else {
+            return;
+        };
+
+        // without dataflow nodes, a reassignment can't be told apart
+        if var_type.parent_nodes.is_empty() {
+            return;
+        }
+
+        let mut parent_node_ids = var_type
+            .parent_nodes
+            .iter()
+            .map(|parent_node| &parent_node.id)
+            .collect::<Vec<_>>();
+        parent_node_ids.sort();
+
+        var_id.hash(&mut hasher);
+        parent_node_ids.hash(&mut hasher);
+    }
+
+    let call_hash = hasher.finish();
+
+    let first_call_pos = context
+        .pure_call_positions
+        .entry(call_hash)
+        .or_insert_with(|| pos.clone())
+        .clone();
+
+    // loop bodies are analyzed more than once
+    if &first_call_pos == pos {
+        return;
+    }
+
+    let mut issue = Issue::new(
+        IssueKind::RedundantComputation,
+        format!(
+            "{} was already called with the same arguments on line {}, reuse that result instead",
+            functionlike_id.to_string(&statements_analyzer.get_codebase().interner),
+            first_call_pos.line()
+        ),
+        statements_analyzer.get_hpos(pos),
+        &context.function_context.calling_functionlike_id,
+    );
+    issue.severity = IssueSeverity::Info;
+
+    tast_info.maybe_add_issue(
+        issue,
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
+// Whether the expression evaluates to the same value whenever the local variables it
+// reads are unchanged, collecting those variables
+fn is_stable_expr(expr: &aast::Expr<(), ()>, var_ids: &mut Vec<String>) -> bool {
+    match &expr.2 {
+        aast::Expr_::Null
+        | aast::Expr_::True
+        | aast::Expr_::False
+        | aast::Expr_::Int(_)
+        | aast::Expr_::Float(_)
+        | aast::Expr_::String(_)
+        | aast::Expr_::Id(_)
+        | aast::Expr_::ClassConst(_) => true,
+        aast::Expr_::Lvar(lid) => {
+            if lid.1 .1 == "$this" {
+                return false;
+            }
+
+            var_ids.push(lid.1 .1.clone());
+            true
+        }
+        aast::Expr_::String2(exprs) | aast::Expr_::Tuple(exprs) => {
+            exprs.iter().all(|expr| is_stable_expr(expr, var_ids))
+        }
+        aast::Expr_::ValCollection(boxed) => {
+            boxed.2.iter().all(|expr| is_stable_expr(expr, var_ids))
+        }
+        aast::Expr_::KeyValCollection(boxed) => boxed
+            .2
+            .iter()
+            .all(|field| is_stable_expr(&field.0, var_ids) && is_stable_expr(&field.1, var_ids)),
+        aast::Expr_::Shape(fields) => fields
+            .iter()
+            .all(|(_, field_expr)| is_stable_expr(field_expr, var_ids)),
+        aast::Expr_::ArrayGet(boxed) => {
+            is_stable_expr(&boxed.0, var_ids)
+                && boxed
+                    .1
+                    .as_ref()
+                    .map_or(false, |dim_expr| is_stable_expr(dim_expr, var_ids))
+        }
+        aast::Expr_::Binop(boxed) => {
+            !matches!(boxed.0, ast_defs::Bop::Eq(_))
+                && is_stable_expr(&boxed.1, var_ids)
+                && is_stable_expr(&boxed.2, var_ids)
+        }
+        aast::Expr_::Unop(boxed) => {
+            !matches!(
+                boxed.0,
+                ast_defs::Uop::Uincr
+                    | ast_defs::Uop::Udecr
+                    | ast_defs::Uop::Upincr
+                    | ast_defs::Uop::Updecr
+            ) && is_stable_expr(&boxed.1, var_ids)
+        }
+        _ => false,
+    }
+}
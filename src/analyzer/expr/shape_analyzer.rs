@@ -1,5 +1,6 @@
 use crate::{
-    expression_analyzer, scope_analyzer::ScopeAnalyzer, scope_context::ScopeContext,
+    expr::collection_analyzer::check_duplicate_key, expression_analyzer,
+    scope_analyzer::ScopeAnalyzer, scope_context::ScopeContext,
     statements_analyzer::StatementsAnalyzer, typed_ast::TastInfo,
 };
 use hakana_reflection_info::{
@@ -17,7 +18,7 @@ use oxidized::{
     aast,
     ast_defs::{Pos, ShapeFieldName},
 };
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{collections::BTreeMap, sync::Arc};
 
 pub(crate) fn analyze(
@@ -34,6 +35,8 @@ pub(crate) fn analyze(
     let mut effects = 0;
 
     let mut known_items = BTreeMap::new();
+    let mut seen_keys = FxHashMap::default();
+
     for (name, value_expr) in shape_fields {
         let start_pos = match name {
             ShapeFieldName::SFlitInt(name) => &name.0,
@@ -98,6 +101,17 @@ pub(crate) fn analyze(
             }
         };
 
+        if let Some(name) = &name {
+            check_duplicate_key(
+                statements_analyzer,
+                name.clone(),
+                start_pos,
+                &mut seen_keys,
+                tast_info,
+                context,
+            );
+        }
+
         // Now check types of the values
         if !expression_analyzer::analyze(
             statements_analyzer,
@@ -130,11 +144,14 @@ pub(crate) fn analyze(
                 &match &name {
                     DictKey::Int(i) => i.to_string(),
                     DictKey::String(k) => k.clone(),
-                    DictKey::Enum(class_name, member_name) => {
-                        codebase.interner.lookup(class_name).to_string()
-                            + "::"
-                            + codebase.interner.lookup(member_name)
-                    }
+                    // keyed by value, like assignments to and fetches of the field
+                    DictKey::Enum(class_name, member_name) => codebase
+                        .get_enum_case_key_value(class_name, member_name)
+                        .unwrap_or_else(|| {
+                            codebase.interner.lookup(class_name).to_string()
+                                + "::"
+                                + codebase.interner.lookup(member_name)
+                        }),
                 },
                 value_expr,
             ) {
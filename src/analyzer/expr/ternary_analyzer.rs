@@ -48,6 +48,7 @@ pub(crate) fn analyze(
     let mut if_context = if_conditional_scope.if_body_context;
     let post_if_context = if_conditional_scope.post_if_context;
     *context = if_conditional_scope.outer_context;
+    let was_inside_conditional_operand = context.inside_conditional_operand;
     let mut cond_referenced_var_ids = if_conditional_scope.cond_referenced_var_ids;
 
     let cond_object_id = (expr.0.pos().start_offset(), expr.0.pos().end_offset());
@@ -236,6 +237,8 @@ pub(crate) fn analyze(
     let mut temp_else_context = post_if_context.clone();
     // Check if there is an expression for the true case
     if let Some(if_branch) = expr.1 {
+        if_context.inside_conditional_operand = true;
+
         if !expression_analyzer::analyze(
             statements_analyzer,
             if_branch,
@@ -298,6 +301,8 @@ pub(crate) fn analyze(
         .0;
     }
 
+    temp_else_context.inside_conditional_operand = true;
+
     if !expression_analyzer::analyze(
         statements_analyzer,
         &expr.2,
@@ -415,6 +420,7 @@ pub(crate) fn analyze(
         if let Some(stmt_else_type) = stmt_else_type {
             if stmt_else_type.is_nothing() {
                 *context = if_context;
+                context.inside_conditional_operand = was_inside_conditional_operand;
             }
             Some((lhs_type, stmt_else_type))
         } else {
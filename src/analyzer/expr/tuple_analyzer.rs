@@ -66,6 +66,7 @@ pub(crate) fn analyze(
         },
         type_param: get_nothing(),
         non_empty: true,
+        is_tuple: true,
     });
 
     new_dict.parent_nodes = parent_nodes;
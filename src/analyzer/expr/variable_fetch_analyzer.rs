@@ -1,5 +1,5 @@
 use crate::{
-    scope_analyzer::ScopeAnalyzer, scope_context::ScopeContext,
+    expr::nullable_return_analyzer, scope_analyzer::ScopeAnalyzer, scope_context::ScopeContext,
     statements_analyzer::StatementsAnalyzer, typed_ast::TastInfo,
 };
 use hakana_reflection_info::{
@@ -41,13 +41,25 @@ pub(crate) fn analyze(
                 superglobal_type
             }
             _ => {
-                tast_info.maybe_add_issue(
+                // the variable is assigned on some paths that reach here, but not all
+                let issue = if context.possibly_assigned_var_ids.contains(&lid.1 .1) {
+                    Issue::new(
+                        IssueKind::PossiblyUndefinedVariable,
+                        format!("Variable {} might not be defined", &lid.1 .1),
+                        statements_analyzer.get_hpos(&pos),
+                        &context.function_context.calling_functionlike_id,
+                    )
+                } else {
                     Issue::new(
                         IssueKind::UndefinedVariable,
                         format!("Cannot find referenced variable {}", &lid.1 .1),
                         statements_analyzer.get_hpos(&pos),
                         &context.function_context.calling_functionlike_id,
-                    ),
+                    )
+                };
+
+                tast_info.maybe_add_issue(
+                    issue,
                     statements_analyzer.get_config(),
                     statements_analyzer.get_file_path_actual(),
                 );
@@ -65,6 +77,43 @@ pub(crate) fn analyze(
     } else if let Some(var_type) = context.vars_in_scope.get(&lid.1 .1) {
         let mut var_type = (**var_type).clone();
 
+        // assigned in a try block, but read somewhere an exception could have skipped that
+        if var_type.possibly_undefined_from_try && !context.inside_isset && !context.inside_unset {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::PossiblyUndefinedVariable,
+                    format!("Variable {} might not be defined", &lid.1 .1),
+                    statements_analyzer.get_hpos(&pos),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+
+        if let Some(reassignment_line) = context.reassigned_foreach_value_vars.remove(&lid.1 .1) {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::ForeachValueReassigned,
+                    format!(
+                        "{} is used after the foreach loop that reassigned it on line {}",
+                        &lid.1 .1, reassignment_line
+                    ),
+                    statements_analyzer.get_hpos(&pos),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+
+        nullable_return_analyzer::handle_variable_read(
+            statements_analyzer,
+            &lid.1 .1,
+            pos,
+            tast_info,
+        );
+
         var_type =
             add_dataflow_to_variable(statements_analyzer, lid, pos, var_type, tast_info, context);
 
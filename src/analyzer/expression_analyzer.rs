@@ -8,9 +8,10 @@ use crate::expr::fetch::{
 };
 use crate::expr::{
     as_analyzer, binop_analyzer, call_analyzer, cast_analyzer, closure_analyzer,
-    collection_analyzer, const_fetch_analyzer, expression_identifier, pipe_analyzer,
-    prefixed_string_analyzer, shape_analyzer, ternary_analyzer, tuple_analyzer, unop_analyzer,
-    variable_fetch_analyzer, xml_analyzer, yield_analyzer,
+    collection_analyzer, const_fetch_analyzer, expression_identifier, expression_tree_analyzer,
+    nullable_return_analyzer, pipe_analyzer, prefixed_string_analyzer, shape_analyzer,
+    ternary_analyzer, tuple_analyzer, unop_analyzer, variable_fetch_analyzer, xml_analyzer,
+    yield_analyzer,
 };
 use crate::expression_analyzer;
 use crate::scope_analyzer::ScopeAnalyzer;
@@ -28,6 +29,7 @@ use hakana_reflection_info::method_identifier::MethodIdentifier;
 use hakana_reflection_info::t_atomic::TAtomic;
 use hakana_reflection_info::t_union::TUnion;
 use hakana_reflection_info::taint::SinkType;
+use hakana_reflector::import_resolver::get_import_path;
 use hakana_type::type_expander::get_closure_from_id;
 use hakana_type::{
     get_bool, get_false, get_float, get_int, get_literal_int, get_literal_string, get_mixed_any,
@@ -37,6 +39,7 @@ use oxidized::ast::Field;
 use oxidized::pos::Pos;
 use oxidized::{aast, ast_defs};
 use rustc_hash::FxHashSet;
+use std::path::Path;
 
 pub(crate) fn analyze(
     statements_analyzer: &StatementsAnalyzer,
@@ -251,8 +254,7 @@ pub(crate) fn analyze(
             }
 
             if let ast_defs::OgNullFlavor::OGNullsafe = nullfetch {
-                // handle nullsafe calls
-            } else {
+                nullable_return_analyzer::mark_checked(lhs_expr, tast_info);
             }
         }
         aast::Expr_::New(boxed) => {
@@ -458,6 +460,8 @@ pub(crate) fn analyze(
             );
         }
         aast::Expr_::Await(boxed) => {
+            check_await_position(statements_analyzer, context, tast_info, &expr.1);
+
             if !expression_analyzer::analyze(
                 statements_analyzer,
                 &boxed,
@@ -528,8 +532,18 @@ pub(crate) fn analyze(
         aast::Expr_::List(_) => {
             panic!("should not happen")
         }
-        aast::Expr_::Import(_) => {
-            // do nothing with require/include
+        aast::Expr_::Import(boxed) => {
+            if !expression_analyzer::analyze(
+                statements_analyzer,
+                &boxed.1,
+                tast_info,
+                context,
+                if_body_context,
+            ) {
+                return false;
+            }
+
+            analyze_import(statements_analyzer, &boxed.1, context, tast_info, expr);
         }
         aast::Expr_::EnumClassLabel(boxed) => {
             let class_name = if let Some(id) = &boxed.0 {
@@ -604,17 +618,56 @@ pub(crate) fn analyze(
             }
         }
 
+        aast::Expr_::ExpressionTree(boxed) => {
+            if !expression_tree_analyzer::analyze(
+                statements_analyzer,
+                boxed,
+                &expr.1,
+                tast_info,
+                context,
+            ) {
+                return false;
+            }
+        }
+        aast::Expr_::ETSplice(boxed) => {
+            if !expression_analyzer::analyze(
+                statements_analyzer,
+                boxed,
+                tast_info,
+                context,
+                if_body_context,
+            ) {
+                return false;
+            }
+
+            if let Some(splice_type) = tast_info.get_expr_type(boxed.pos()).cloned() {
+                tast_info.set_expr_type(&expr.1, splice_type);
+            }
+        }
+        aast::Expr_::ReadonlyExpr(boxed) => {
+            if !expression_analyzer::analyze(
+                statements_analyzer,
+                boxed,
+                tast_info,
+                context,
+                if_body_context,
+            ) {
+                return false;
+            }
+
+            if let Some(mut readonly_type) = tast_info.get_expr_type(boxed.pos()).cloned() {
+                readonly_type.readonly = true;
+                tast_info.set_expr_type(&expr.1, readonly_type);
+            }
+        }
         aast::Expr_::Collection(_)
         | aast::Expr_::This
         | aast::Expr_::Omitted
         | aast::Expr_::Dollardollar(_)
-        | aast::Expr_::ReadonlyExpr(_)
         | aast::Expr_::Upcast(_)
-        | aast::Expr_::ExpressionTree(_)
         | aast::Expr_::Lplaceholder(_)
         | aast::Expr_::MethodCaller(_)
         | aast::Expr_::Pair(_)
-        | aast::Expr_::ETSplice(_)
         | aast::Expr_::Hole(_)
         | aast::Expr_::Invalid(_) => {
             tast_info.maybe_add_issue(
@@ -645,6 +698,55 @@ pub(crate) fn analyze(
     true
 }
 
+// Hack rejects await outside async functions (which constructors can never be), in
+// finally blocks and in operands that are only evaluated conditionally
+pub(crate) fn check_await_position(
+    statements_analyzer: &StatementsAnalyzer,
+    context: &ScopeContext,
+    tast_info: &mut TastInfo,
+    pos: &Pos,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let (issue_kind, message) = match &context.function_context.calling_functionlike_id {
+        Some(FunctionLikeIdentifier::Method(_, method_name))
+            if context.calling_closure_id.is_none()
+                && codebase.interner.lookup(method_name) == "__construct" =>
+        {
+            (
+                IssueKind::AwaitInConstructor,
+                "Constructors cannot be async, so cannot use await",
+            )
+        }
+        Some(_) if !context.inside_async => (
+            IssueKind::AwaitInNonAsyncFunction,
+            "Await can only be used inside async functions",
+        ),
+        _ if context.inside_finally => (
+            IssueKind::AwaitInFinally,
+            "Await cannot be used in a finally block",
+        ),
+        _ if context.inside_conditional_operand => (
+            IssueKind::AwaitInConditionalOperand,
+            "Await cannot be used in an operand that is only conditionally evaluated",
+        ),
+        _ => {
+            return;
+        }
+    };
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            issue_kind,
+            message.to_string(),
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
 fn analyze_function_pointer(
     statements_analyzer: &StatementsAnalyzer,
     boxed: &Box<(aast::FunctionPtrId<(), ()>, Vec<aast::Targ<()>>)>,
@@ -694,16 +796,21 @@ fn analyze_function_pointer(
             );
 
             if !codebase.functionlike_infos.contains_key(name) {
-                tast_info.maybe_add_issue(
-                    Issue::new(
-                        IssueKind::NonExistentFunction,
-                        format!("Unknown function {}", codebase.interner.lookup(name)),
-                        statements_analyzer.get_hpos(&expr.pos()),
-                        &context.function_context.calling_functionlike_id,
-                    ),
-                    statements_analyzer.get_config(),
-                    statements_analyzer.get_file_path_actual(),
-                );
+                if !context
+                    .asserted_existing_symbols
+                    .contains(codebase.interner.lookup(name))
+                {
+                    tast_info.maybe_add_issue(
+                        Issue::new(
+                            IssueKind::NonExistentFunction,
+                            format!("Unknown function {}", codebase.interner.lookup(name)),
+                            statements_analyzer.get_hpos(&expr.pos()),
+                            &context.function_context.calling_functionlike_id,
+                        ),
+                        statements_analyzer.get_config(),
+                        statements_analyzer.get_file_path_actual(),
+                    );
+                }
 
                 return;
             }
@@ -735,16 +842,21 @@ fn analyze_function_pointer(
                     }
                 }
             } else {
-                tast_info.maybe_add_issue(
-                    Issue::new(
-                        IssueKind::NonExistentClasslike,
-                        format!("Unknown classlike {}", codebase.interner.lookup(class_name)),
-                        statements_analyzer.get_hpos(&expr.pos()),
-                        &context.function_context.calling_functionlike_id,
-                    ),
-                    statements_analyzer.get_config(),
-                    statements_analyzer.get_file_path_actual(),
-                );
+                if !context
+                    .asserted_existing_symbols
+                    .contains(codebase.interner.lookup(class_name))
+                {
+                    tast_info.maybe_add_issue(
+                        Issue::new(
+                            IssueKind::NonExistentClasslike,
+                            format!("Unknown classlike {}", codebase.interner.lookup(class_name)),
+                            statements_analyzer.get_hpos(&expr.pos()),
+                            &context.function_context.calling_functionlike_id,
+                        ),
+                        statements_analyzer.get_config(),
+                        statements_analyzer.get_file_path_actual(),
+                    );
+                }
 
                 return;
             }
@@ -759,6 +871,47 @@ fn analyze_function_pointer(
     }
 }
 
+fn analyze_import(
+    statements_analyzer: &StatementsAnalyzer,
+    import_expr: &aast::Expr<(), ()>,
+    context: &mut ScopeContext,
+    tast_info: &mut TastInfo,
+    expr: &aast::Expr<(), ()>,
+) {
+    let codebase = statements_analyzer.get_codebase();
+    let file_path = codebase
+        .interner
+        .lookup(statements_analyzer.get_file_path());
+
+    let import_path = if let Some(import_path) = get_import_path(import_expr, file_path) {
+        import_path
+    } else {
+        return;
+    };
+
+    // the file might exist but not be scanned, e.g. if it's not a Hack file
+    let is_resolved = if let Some(import_path_id) = codebase.interner.get(&import_path) {
+        codebase.files.contains_key(&import_path_id)
+    } else {
+        false
+    } || Path::new(&statements_analyzer.get_config().root_dir)
+        .join(&import_path)
+        .exists();
+
+    if !is_resolved {
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::UnresolvedImport,
+                format!("Imported file {} does not exist", import_path),
+                statements_analyzer.get_hpos(&expr.pos()),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+}
+
 pub(crate) fn add_decision_dataflow(
     statements_analyzer: &StatementsAnalyzer,
     tast_info: &mut TastInfo,
@@ -6,12 +6,17 @@ use crate::scope_context::ScopeContext;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::typed_ast::TastInfo;
 use hakana_reflection_info::analysis_result::AnalysisResult;
+use hakana_reflection_info::code_location::HPos;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
 use hakana_reflection_info::data_flow::graph::DataFlowGraph;
 use hakana_reflection_info::function_context::FunctionContext;
+use hakana_reflection_info::issue::{
+    get_issue_from_comment, get_suppression_expiry, suppression_has_expired, Issue, IssueKind,
+};
 use hakana_reflection_info::type_resolution::TypeResolutionContext;
 use hakana_reflection_info::{FileSource, StrId};
 use oxidized::aast;
+use oxidized::prim_defs::Comment;
 use rustc_hash::FxHashMap;
 
 #[derive(Clone)]
@@ -96,17 +101,61 @@ impl<'a> FileAnalyzer<'a> {
             }
         }
 
-        update_analysis_result_with_tast(
-            tast_info,
-            analysis_result,
-            self.get_codebase().interner.lookup(
-                &statements_analyzer
-                    .get_file_analyzer()
-                    .get_file_source()
-                    .file_path,
-            ),
-            false,
-        );
+        if let Some(earliest_expiry) = self.report_expired_suppressions(&mut tast_info) {
+            analysis_result.suppression_expiries.insert(
+                self.codebase
+                    .interner
+                    .lookup(&self.file_source.file_path)
+                    .to_string(),
+                earliest_expiry,
+            );
+        }
+
+        update_analysis_result_with_tast(tast_info, analysis_result, &statements_analyzer, false);
+    }
+
+    // Returns the earliest date on which one of the file's remaining suppressions expires
+    fn report_expired_suppressions(&self, tast_info: &mut TastInfo) -> Option<String> {
+        let config = self.get_config();
+
+        let mut earliest_expiry: Option<&str> = None;
+
+        for (pos, comment) in &self.file_source.comments {
+            if let Comment::CmtBlock(text) = comment {
+                let trimmed_text = if text.starts_with("*") {
+                    text[1..].trim()
+                } else {
+                    text.trim()
+                };
+
+                if let Some(Ok(issue_kind)) =
+                    get_issue_from_comment(trimmed_text, &config.all_custom_issues)
+                {
+                    if let Some(expiry) = get_suppression_expiry(trimmed_text) {
+                        if suppression_has_expired(expiry) {
+                            tast_info.maybe_add_issue(
+                                Issue::new(
+                                    IssueKind::ExpiredSuppression,
+                                    format!(
+                                        "Suppression of {} expired on {}",
+                                        issue_kind.to_string(),
+                                        expiry
+                                    ),
+                                    HPos::new(pos, self.file_source.file_path, None),
+                                    &None,
+                                ),
+                                config,
+                                &self.file_source.file_path_actual,
+                            );
+                        } else if earliest_expiry.map_or(true, |earliest| expiry < earliest) {
+                            earliest_expiry = Some(expiry);
+                        }
+                    }
+                }
+            }
+        }
+
+        earliest_expiry.map(str::to_string)
     }
 
     pub fn get_file_source(&self) -> &FileSource {
@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 
 use hakana_reflection_info::symbol_references::ReferenceSource;
 use indexmap::IndexMap;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHasher};
 
 use hakana_algebra::Clause;
 use hakana_reflection_info::{
@@ -11,13 +12,15 @@ use hakana_reflection_info::{
 use hakana_reflection_info::{FileSource, StrId};
 use oxidized::{
     aast,
+    aast_visitor::{visit, AstParams, Node, Visitor},
     ast::{Bop, Uop},
 };
 
-use crate::{expr::assertion_finder, typed_ast::TastInfo};
+use crate::{config::Config, expr::assertion_finder, typed_ast::TastInfo};
 
 pub(crate) struct AssertionContext<'a> {
     pub file_source: &'a FileSource,
+    pub config: &'a Config,
     pub resolved_names: &'a FxHashMap<usize, StrId>,
     pub codebase: Option<&'a CodebaseInfo>,
     pub this_class_name: Option<&'a StrId>,
@@ -25,6 +28,9 @@ pub(crate) struct AssertionContext<'a> {
     pub reference_source: ReferenceSource,
 }
 
+// Conditionals in long if/elseif chains (and loop bodies) are often seen more than once,
+// so formulae are cached for as long as the types of everything in the conditional stay
+// the same
 pub(crate) fn get_formula(
     conditional_object_id: (usize, usize),
     creating_object_id: (usize, usize),
@@ -33,6 +39,48 @@ pub(crate) fn get_formula(
     tast_info: &mut TastInfo,
     cache: bool,
     inside_negation: bool,
+) -> Result<Vec<Clause>, String> {
+    let cache_key = (
+        (conditional.1.start_offset(), conditional.1.end_offset()),
+        conditional_object_id,
+        creating_object_id,
+        cache,
+        inside_negation,
+    );
+
+    let types_hash = get_conditional_types_hash(conditional, tast_info);
+
+    if let Some((cached_types_hash, clauses)) = tast_info.formula_cache.get(&cache_key) {
+        if *cached_types_hash == types_hash {
+            return clauses.clone();
+        }
+    }
+
+    let clauses = get_uncached_formula(
+        conditional_object_id,
+        creating_object_id,
+        conditional,
+        assertion_context,
+        tast_info,
+        cache,
+        inside_negation,
+    );
+
+    tast_info
+        .formula_cache
+        .insert(cache_key, (types_hash, clauses.clone()));
+
+    clauses
+}
+
+fn get_uncached_formula(
+    conditional_object_id: (usize, usize),
+    creating_object_id: (usize, usize),
+    conditional: &aast::Expr<(), ()>,
+    assertion_context: &AssertionContext,
+    tast_info: &mut TastInfo,
+    cache: bool,
+    inside_negation: bool,
 ) -> Result<Vec<Clause>, String> {
     if let aast::Expr_::Binop(expr) = &conditional.2 {
         if let Some(clauses) = handle_binop(
@@ -333,3 +381,64 @@ fn handle_uop(
 
     None
 }
+
+struct ConditionalTypesHasher {
+    hasher: FxHasher,
+}
+
+impl<'ast> Visitor<'ast> for ConditionalTypesHasher {
+    type Params = AstParams<TastInfo, ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_expr(
+        &mut self,
+        tast_info: &mut TastInfo,
+        expr: &aast::Expr<(), ()>,
+    ) -> Result<(), ()> {
+        let offsets = (expr.1.start_offset(), expr.1.end_offset());
+
+        // synthetic expressions (e.g. negated conditionals) share offsets with the
+        // expressions they wrap, so the kind of expression matters too
+        offsets.hash(&mut self.hasher);
+        std::mem::discriminant(&expr.2).hash(&mut self.hasher);
+
+        if let Some(expr_type) = tast_info.expr_types.get(&offsets) {
+            expr_type.hash(&mut self.hasher);
+        }
+
+        // calls to functions with custom assertions contribute those assertions to the
+        // formula, and they can differ between calls at the same offsets
+        for assertions in [
+            tast_info.if_true_assertions.get(&offsets),
+            tast_info.if_false_assertions.get(&offsets),
+        ] {
+            let assertions = assertions.map(|assertions| {
+                let mut assertions = assertions.iter().collect::<Vec<_>>();
+                assertions.sort_by(|a, b| a.0.cmp(b.0));
+                assertions
+            });
+
+            assertions.hash(&mut self.hasher);
+        }
+
+        tast_info
+            .symbol_existence_checks
+            .get(&offsets)
+            .hash(&mut self.hasher);
+
+        expr.recurse(tast_info, self)
+    }
+}
+
+fn get_conditional_types_hash(conditional: &aast::Expr<(), ()>, tast_info: &mut TastInfo) -> u64 {
+    let mut types_hasher = ConditionalTypesHasher {
+        hasher: FxHasher::default(),
+    };
+
+    visit(&mut types_hasher, tast_info, conditional).unwrap();
+
+    types_hasher.hasher.finish()
+}
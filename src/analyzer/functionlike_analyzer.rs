@@ -4,9 +4,11 @@ use crate::dataflow::unused_variable_analyzer::{
     add_unused_expression_replacements, check_variables_used,
 };
 use crate::expr::fetch::atomic_property_fetch_analyzer;
+use crate::expr::nullable_return_analyzer;
 use crate::expression_analyzer;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope_context::ScopeContext;
+use crate::shape_param_collector::collect_shape_params;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::stmt::return_analyzer::handle_inout_at_return;
 use crate::{file_analyzer::FileAnalyzer, typed_ast::TastInfo};
@@ -21,6 +23,7 @@ use hakana_reflection_info::functionlike_info::{FnEffect, FunctionLikeInfo};
 use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::member_visibility::MemberVisibility;
 use hakana_reflection_info::method_identifier::MethodIdentifier;
+use hakana_reflection_info::symbol_references::SymbolReferences;
 use hakana_reflection_info::t_atomic::TAtomic;
 use hakana_reflection_info::t_union::TUnion;
 use hakana_type::type_comparator::type_comparison_result::TypeComparisonResult;
@@ -168,6 +171,82 @@ impl<'a> FunctionLikeAnalyzer<'a> {
         stmt: &aast::Method_<(), ()>,
         classlike_storage: &ClassLikeInfo,
         analysis_result: &mut AnalysisResult,
+    ) {
+        self.analyze_method_in_class(stmt, classlike_storage, classlike_storage, analysis_result);
+    }
+
+    // Analyzes a trait's method as though it were declared in a class using the trait, so
+    // that self and $this refer to that class. Only the issues that the trait's own
+    // analysis didn't find are kept, and they're attributed to the class.
+    pub fn analyze_trait_method_in_class(
+        &mut self,
+        stmt: &aast::Method_<(), ()>,
+        trait_storage: &ClassLikeInfo,
+        using_class_storage: &ClassLikeInfo,
+        analysis_result: &mut AnalysisResult,
+    ) {
+        let mut class_analysis_result = AnalysisResult::new(
+            analysis_result.program_dataflow_graph.kind,
+            SymbolReferences::new(),
+        );
+
+        self.analyze_method_in_class(
+            stmt,
+            trait_storage,
+            using_class_storage,
+            &mut class_analysis_result,
+        );
+
+        let interner = &self.get_codebase().interner;
+        let method_name = interner.get(&stmt.name.1).unwrap();
+
+        for (file_path, issues) in std::mem::take(&mut class_analysis_result.emitted_issues) {
+            let existing_issues = analysis_result
+                .emitted_issues
+                .entry(file_path)
+                .or_insert_with(Vec::new);
+
+            for mut issue in issues {
+                if existing_issues.iter().any(|existing_issue| {
+                    existing_issue.kind == issue.kind
+                        && existing_issue.pos.start_offset == issue.pos.start_offset
+                        && existing_issue.pos.end_offset == issue.pos.end_offset
+                }) {
+                    continue;
+                }
+
+                issue.description = format!(
+                    "{} (in {}, which uses this trait)",
+                    issue.description,
+                    interner.lookup(&using_class_storage.name)
+                );
+                issue.symbol = (using_class_storage.name, method_name);
+
+                *analysis_result
+                    .issue_counts
+                    .entry(issue.kind.clone())
+                    .or_insert(0) += 1;
+
+                existing_issues.push(issue);
+            }
+        }
+
+        // the trait's own analysis already counted its issues and expressions
+        class_analysis_result.issue_counts.clear();
+        class_analysis_result.type_coverage.clear();
+        class_analysis_result.expr_types.clear();
+
+        analysis_result.extend(class_analysis_result);
+    }
+
+    // classlike_storage declares the method, and calling_class_storage is the class whose
+    // instance $this is, which differ for trait methods analyzed in a using class
+    fn analyze_method_in_class(
+        &mut self,
+        stmt: &aast::Method_<(), ()>,
+        classlike_storage: &ClassLikeInfo,
+        calling_class_storage: &ClassLikeInfo,
+        analysis_result: &mut AnalysisResult,
     ) {
         if stmt.abstract_ {
             return;
@@ -207,19 +286,19 @@ impl<'a> FunctionLikeAnalyzer<'a> {
 
         let mut function_context = FunctionContext::new();
         function_context.calling_functionlike_id = Some(FunctionLikeIdentifier::Method(
-            classlike_storage.name.clone(),
+            calling_class_storage.name.clone(),
             method_name.clone(),
         ));
-        function_context.calling_class = Some(classlike_storage.name.clone());
+        function_context.calling_class = Some(calling_class_storage.name.clone());
 
         let mut context = ScopeContext::new(function_context);
 
         if !stmt.static_ {
             let mut this_type = wrap_atomic(TAtomic::TNamedObject {
-                name: classlike_storage.name.clone(),
-                type_params: if !classlike_storage.template_types.is_empty() {
+                name: calling_class_storage.name.clone(),
+                type_params: if !calling_class_storage.template_types.is_empty() {
                     Some(
-                        classlike_storage
+                        calling_class_storage
                             .template_types
                             .iter()
                             .map(|(param_name, template_map)| {
@@ -244,9 +323,9 @@ impl<'a> FunctionLikeAnalyzer<'a> {
             });
 
             if let GraphKind::WholeProgram(_) = &analysis_result.program_dataflow_graph.kind {
-                if classlike_storage.specialize_instance {
+                if calling_class_storage.specialize_instance {
                     let new_call_node = DataFlowNode::get_for_this_before_method(
-                        &MethodIdentifier(classlike_storage.name.clone(), method_name.clone()),
+                        &MethodIdentifier(calling_class_storage.name.clone(), method_name.clone()),
                         functionlike_storage.return_type_location.clone(),
                         None,
                         &statements_analyzer.get_codebase().interner,
@@ -357,6 +436,8 @@ impl<'a> FunctionLikeAnalyzer<'a> {
         parent_tast_info: Option<&mut TastInfo>,
     ) -> (Option<TUnion>, u8) {
         context.inside_async = functionlike_storage.is_async;
+        context.inside_finally = false;
+        context.inside_conditional_operand = false;
 
         let mut tast_info = TastInfo::new(
             DataFlowGraph::new(statements_analyzer.get_config().graph_kind),
@@ -379,6 +460,22 @@ impl<'a> FunctionLikeAnalyzer<'a> {
             tast_info.issue_filter = Some(issue_filter.clone());
         }
 
+        tast_info.data_flow_graph.recording =
+            statements_analyzer.get_config().needs_function_body_graph();
+
+        if functionlike_storage.generated {
+            tast_info.in_generated_code = true;
+        } else if let Some(calling_class) = &context.function_context.calling_class {
+            if let Some(classlike_storage) = self
+                .file_analyzer
+                .get_codebase()
+                .classlike_infos
+                .get(calling_class)
+            {
+                tast_info.in_generated_code |= classlike_storage.generated;
+            }
+        }
+
         let mut completed_analysis = false;
 
         if self.add_param_types_to_context(
@@ -439,6 +536,8 @@ impl<'a> FunctionLikeAnalyzer<'a> {
             }
         }
 
+        nullable_return_analyzer::report_unchecked(statements_analyzer, &mut tast_info, &context);
+
         if let GraphKind::WholeProgram(_) = &tast_info.data_flow_graph.kind {
             if let Some(method_storage) = &functionlike_storage.method_info {
                 if !method_storage.is_static {
@@ -574,6 +673,22 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                     });
                 }
             }
+
+            if config.refine_nullable_returns
+                && parent_tast_info.is_none()
+                && completed_analysis
+                && never_returns_null(
+                    functionlike_storage,
+                    &expected_return_type,
+                    &tast_info,
+                    &context,
+                    codebase,
+                )
+            {
+                if let Some(functionlike_id) = &context.function_context.calling_functionlike_id {
+                    analysis_result.never_null_returns.insert(*functionlike_id);
+                }
+            }
         } else {
             if !tast_info.inferred_return_types.is_empty() {
                 for callsite_return_type in &tast_info.inferred_return_types {
@@ -606,6 +721,30 @@ impl<'a> FunctionLikeAnalyzer<'a> {
             }
         }
 
+        if parent_tast_info.is_none() {
+            if let Some(functionlike_id) = &context.function_context.calling_functionlike_id {
+                if statements_analyzer.get_config().collect_shape_params {
+                    collect_shape_params(
+                        statements_analyzer,
+                        functionlike_id,
+                        functionlike_storage,
+                        context.function_context.calling_class.as_ref(),
+                        fb_ast,
+                        analysis_result,
+                    );
+                }
+
+                for visitor in &statements_analyzer.get_config().program_visitors {
+                    visitor.visit_functionlike(
+                        codebase,
+                        functionlike_id,
+                        functionlike_storage,
+                        Some(&tast_info),
+                    );
+                }
+            }
+        }
+
         if let Some(parent_tast_info) = parent_tast_info {
             if !tast_info.replacements.is_empty() {
                 parent_tast_info.replacements.extend(tast_info.replacements);
@@ -635,19 +774,30 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                 .matched_ignore_positions
                 .extend(tast_info.matched_ignore_positions);
 
+            if statements_analyzer.get_config().typed_ast_file.is_some()
+                || statements_analyzer.get_config().collect_type_coverage
+            {
+                parent_tast_info.expr_types.extend(tast_info.expr_types);
+            }
+
             for (kind, count) in tast_info.issue_counts {
                 *parent_tast_info.issue_counts.entry(kind).or_insert(0) += count;
             }
         } else {
+            if let (GraphKind::WholeProgram(_), Some(functionlike_id)) = (
+                &tast_info.data_flow_graph.kind,
+                &context.function_context.calling_functionlike_id,
+            ) {
+                tast_info.data_flow_graph.functionlike_locations.push((
+                    functionlike_storage.def_location.clone(),
+                    functionlike_id.to_string(&self.get_codebase().interner),
+                ));
+            }
+
             update_analysis_result_with_tast(
                 tast_info,
                 analysis_result,
-                self.get_codebase().interner.lookup(
-                    &statements_analyzer
-                        .get_file_analyzer()
-                        .get_file_source()
-                        .file_path,
-                ),
+                statements_analyzer,
                 functionlike_storage.ignore_taint_path,
             );
         }
@@ -864,9 +1014,14 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                     type_param: param_type,
                     known_count: None,
                     non_empty: false,
+                    is_tuple: false,
                 });
             }
 
+            if param.is_readonly {
+                param_type.readonly = true;
+            }
+
             let new_parent_node = if let GraphKind::WholeProgram(_) =
                 &tast_info.data_flow_graph.kind
             {
@@ -966,6 +1121,53 @@ impl<'a> FunctionLikeAnalyzer<'a> {
     }
 }
 
+// Whether callers can treat the declared nullable return type as non-null, which needs every
+// return to return a non-null value, and the method to be one that can't be overridden
+fn never_returns_null(
+    functionlike_storage: &FunctionLikeInfo,
+    expected_return_type: &TUnion,
+    tast_info: &TastInfo,
+    context: &ScopeContext,
+    codebase: &CodebaseInfo,
+) -> bool {
+    if !expected_return_type.is_nullable()
+        || functionlike_storage.is_async
+        || functionlike_storage.has_yield
+        || !context.has_returned
+        || tast_info.inferred_return_types.is_empty()
+    {
+        return false;
+    }
+
+    if let Some(method_info) = &functionlike_storage.method_info {
+        let classlike_is_final = context
+            .function_context
+            .calling_class
+            .and_then(|calling_class| codebase.classlike_infos.get(&calling_class))
+            .map_or(false, |classlike_info| classlike_info.is_final);
+
+        if !method_info.is_final
+            && !classlike_is_final
+            && !matches!(method_info.visibility, MemberVisibility::Private)
+        {
+            return false;
+        }
+    }
+
+    tast_info.inferred_return_types.iter().all(|return_type| {
+        return_type.types.iter().all(|atomic| {
+            !atomic.is_mixed()
+                && !matches!(
+                    atomic,
+                    TAtomic::TNull
+                        | TAtomic::TGenericParam { .. }
+                        | TAtomic::TTypeAlias { .. }
+                        | TAtomic::TClassTypeConstant { .. }
+                )
+        })
+    })
+}
+
 fn report_unused_expressions(
     tast_info: &mut TastInfo,
     config: &Config,
@@ -1075,9 +1277,42 @@ fn report_unused_expressions(
 pub(crate) fn update_analysis_result_with_tast(
     tast_info: TastInfo,
     analysis_result: &mut AnalysisResult,
-    file_path: &str,
+    statements_analyzer: &StatementsAnalyzer,
     ignore_taint_path: bool,
 ) {
+    let interner = &statements_analyzer.get_codebase().interner;
+
+    let file_path = interner.lookup(
+        &statements_analyzer
+            .get_file_analyzer()
+            .get_file_source()
+            .file_path,
+    );
+
+    if statements_analyzer.get_config().typed_ast_file.as_deref() == Some(file_path) {
+        analysis_result.expr_types.extend(
+            tast_info
+                .expr_types
+                .iter()
+                .map(|(offsets, expr_type)| (*offsets, expr_type.get_id(Some(interner)))),
+        );
+    }
+
+    if statements_analyzer.get_config().collect_type_coverage {
+        let type_coverage = analysis_result
+            .type_coverage
+            .entry(file_path.to_string())
+            .or_default();
+
+        for expr_type in tast_info.expr_types.values() {
+            if expr_type.is_mixed() {
+                type_coverage.mixed_count += 1;
+            } else {
+                type_coverage.typed_count += 1;
+            }
+        }
+    }
+
     if !tast_info.replacements.is_empty() {
         analysis_result
             .replacements
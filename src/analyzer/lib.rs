@@ -8,9 +8,15 @@ mod expression_analyzer;
 pub mod file_analyzer;
 mod formula_generator;
 mod functionlike_analyzer;
+pub mod linter;
+pub mod program_visitor;
+pub mod progress_reporter;
+mod property_initialization_analyzer;
+pub mod pure_type_api;
 mod reconciler;
 pub mod scope_analyzer;
 pub mod scope_context;
+mod shape_param_collector;
 pub mod statements_analyzer;
 mod stmt;
 mod stmt_analyzer;
@@ -0,0 +1,151 @@
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::t_union::TUnion;
+use oxidized::aast;
+use oxidized::ast_defs::Pos;
+
+use crate::custom_hook::{AfterExprAnalysisData, AfterStmtAnalysisData, CustomHook, InternalHook};
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::scope_context::ScopeContext;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+pub struct LintContext<'a> {
+    pub statements_analyzer: &'a StatementsAnalyzer<'a>,
+    pub context: &'a ScopeContext,
+    tast_info: &'a TastInfo,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn get_codebase(&self) -> &CodebaseInfo {
+        self.statements_analyzer.get_codebase()
+    }
+
+    pub fn get_expr_type(&self, pos: &Pos) -> Option<&TUnion> {
+        self.tast_info.get_expr_type(pos)
+    }
+
+    // Returns the fully-qualified name of the symbol that starts at the given position
+    pub fn get_resolved_name(&self, pos: &Pos) -> Option<&str> {
+        self.statements_analyzer
+            .get_file_analyzer()
+            .resolved_names
+            .get(&pos.start_offset())
+            .map(|id| self.get_codebase().interner.lookup(id))
+    }
+}
+
+pub struct LintError {
+    pub description: String,
+    pub pos: Pos,
+}
+
+impl LintError {
+    pub fn new(description: String, pos: &Pos) -> Self {
+        Self {
+            description,
+            pos: pos.clone(),
+        }
+    }
+}
+
+/// An AST-pattern-based rule in the style of an HHAST linter.
+///
+/// Linters are registered as custom hooks with `LinterHook`, and every error they
+/// produce is emitted as a custom issue with the linter's name.
+pub trait Linter: Send + Sync {
+    fn get_name(&self) -> &str;
+
+    #[allow(unused_variables)]
+    fn lint_expr(&self, expr: &aast::Expr<(), ()>, lint_context: &LintContext) -> Vec<LintError> {
+        vec![]
+    }
+
+    #[allow(unused_variables)]
+    fn lint_stmt(&self, stmt: &aast::Stmt<(), ()>, lint_context: &LintContext) -> Vec<LintError> {
+        vec![]
+    }
+}
+
+pub struct LinterHook<T: Linter> {
+    linter: T,
+}
+
+impl<T: Linter> LinterHook<T> {
+    pub fn new(linter: T) -> Self {
+        Self { linter }
+    }
+
+    fn add_lint_errors(
+        &self,
+        lint_errors: Vec<LintError>,
+        tast_info: &mut TastInfo,
+        statements_analyzer: &StatementsAnalyzer,
+        context: &ScopeContext,
+    ) {
+        for lint_error in lint_errors {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::CustomIssue(self.linter.get_name().to_string()),
+                    lint_error.description,
+                    statements_analyzer.get_hpos(&lint_error.pos),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+    }
+}
+
+impl<T: Linter> InternalHook for LinterHook<T> {
+    fn after_expr_analysis(
+        &self,
+        tast_info: &mut TastInfo,
+        after_expr_analysis_data: AfterExprAnalysisData,
+    ) {
+        let lint_errors = self.linter.lint_expr(
+            after_expr_analysis_data.expr,
+            &LintContext {
+                statements_analyzer: after_expr_analysis_data.statements_analyzer,
+                context: after_expr_analysis_data.context,
+                tast_info,
+            },
+        );
+
+        self.add_lint_errors(
+            lint_errors,
+            tast_info,
+            after_expr_analysis_data.statements_analyzer,
+            after_expr_analysis_data.context,
+        );
+    }
+
+    fn after_stmt_analysis(
+        &self,
+        tast_info: &mut TastInfo,
+        after_stmt_analysis_data: AfterStmtAnalysisData,
+    ) {
+        let lint_errors = self.linter.lint_stmt(
+            after_stmt_analysis_data.stmt,
+            &LintContext {
+                statements_analyzer: after_stmt_analysis_data.statements_analyzer,
+                context: after_stmt_analysis_data.context,
+                tast_info,
+            },
+        );
+
+        self.add_lint_errors(
+            lint_errors,
+            tast_info,
+            after_stmt_analysis_data.statements_analyzer,
+            after_stmt_analysis_data.context,
+        );
+    }
+
+    fn get_custom_issue_names(&self) -> Vec<&str> {
+        vec![self.linter.get_name()]
+    }
+}
+
+impl<T: Linter> CustomHook for LinterHook<T> {}
@@ -0,0 +1,134 @@
+// Lets downstream tools and plugins build passes over the analyzed program (e.g. for
+// architectural rules or metrics) without knowing how the codebase and analysis
+// results are stored
+
+use hakana_reflection_info::{
+    analysis_result::AnalysisResult, classlike_info::ClassLikeInfo, codebase_info::CodebaseInfo,
+    functionlike_identifier::FunctionLikeIdentifier, functionlike_info::FunctionLikeInfo,
+    issue::Issue, Interner,
+};
+
+use crate::typed_ast::TastInfo;
+
+// Visits are spread across threads, so implementations need to be thread-safe
+pub trait ProgramVisitor: Send + Sync {
+    #[allow(unused_variables)]
+    fn visit_classlike(&self, codebase: &CodebaseInfo, classlike_info: &ClassLikeInfo) {}
+
+    // Called with the TastInfo of every function and method as it's analyzed, and then
+    // without one for every function and method in the codebase once analysis is done
+    #[allow(unused_variables)]
+    fn visit_functionlike(
+        &self,
+        codebase: &CodebaseInfo,
+        functionlike_id: &FunctionLikeIdentifier,
+        functionlike_info: &FunctionLikeInfo,
+        tast_info: Option<&TastInfo>,
+    ) {
+    }
+
+    #[allow(unused_variables)]
+    fn visit_issue(&self, interner: &Interner, file_path: &str, issue: &Issue) {}
+}
+
+pub fn visit_classlikes(
+    visitors: &Vec<Box<dyn ProgramVisitor>>,
+    codebase: &CodebaseInfo,
+    threads: u8,
+) {
+    let classlike_infos = codebase.classlike_infos.values().collect::<Vec<_>>();
+
+    visit_in_parallel(
+        visitors,
+        &classlike_infos,
+        threads,
+        |visitor, classlike_info| visitor.visit_classlike(codebase, classlike_info),
+    );
+}
+
+pub fn visit_functionlikes(
+    visitors: &Vec<Box<dyn ProgramVisitor>>,
+    codebase: &CodebaseInfo,
+    threads: u8,
+) {
+    let functionlike_infos = codebase
+        .functionlike_infos
+        .iter()
+        .map(|(function_name, functionlike_info)| {
+            (
+                FunctionLikeIdentifier::Function(*function_name),
+                functionlike_info,
+            )
+        })
+        .chain(
+            codebase
+                .classlike_infos
+                .iter()
+                .flat_map(|(classlike_name, classlike_info)| {
+                    classlike_info
+                        .methods
+                        .iter()
+                        .map(|(method_name, functionlike_info)| {
+                            (
+                                FunctionLikeIdentifier::Method(*classlike_name, *method_name),
+                                functionlike_info,
+                            )
+                        })
+                }),
+        )
+        .collect::<Vec<_>>();
+
+    visit_in_parallel(
+        visitors,
+        &functionlike_infos,
+        threads,
+        |visitor, (functionlike_id, functionlike_info)| {
+            visitor.visit_functionlike(codebase, functionlike_id, functionlike_info, None)
+        },
+    );
+}
+
+pub fn visit_issues(
+    visitors: &Vec<Box<dyn ProgramVisitor>>,
+    analysis_result: &AnalysisResult,
+    interner: &Interner,
+    threads: u8,
+) {
+    let issues = analysis_result
+        .emitted_issues
+        .iter()
+        .flat_map(|(file_path, issues)| issues.iter().map(move |issue| (file_path, issue)))
+        .collect::<Vec<_>>();
+
+    visit_in_parallel(visitors, &issues, threads, |visitor, (file_path, issue)| {
+        visitor.visit_issue(interner, file_path, issue)
+    });
+}
+
+// Every visitor sees every item, with the items split into one chunk per thread
+fn visit_in_parallel<T: Sync>(
+    visitors: &Vec<Box<dyn ProgramVisitor>>,
+    items: &Vec<T>,
+    threads: u8,
+    visit: impl Fn(&dyn ProgramVisitor, &T) + Sync,
+) {
+    if visitors.is_empty() || items.is_empty() {
+        return;
+    }
+
+    let chunk_size = items.len().div_ceil(threads.max(1) as usize);
+
+    std::thread::scope(|scope| {
+        for chunk in items.chunks(chunk_size) {
+            let visit = &visit;
+
+            scope.spawn(move || {
+                for item in chunk {
+                    for visitor in visitors {
+                        visit(&**visitor, item);
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,18 @@
+// Lets embedders (IDEs, build systems etc.) show scanning and analysis progress
+// their own way, rather than through the CLI's progress bar
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnalysisPhase {
+    Scanning,
+    Analyzing,
+}
+
+// Called from the threads doing the work, so implementations need to be thread-safe
+pub trait ProgressReporter: Send + Sync {
+    fn on_phase_start(&self, phase: AnalysisPhase, total_files: u64);
+
+    // files_done counts every file finished so far in the current phase
+    fn on_file_done(&self, files_done: u64, total_files: u64);
+
+    fn on_phase_end(&self, phase: AnalysisPhase);
+}
@@ -0,0 +1,587 @@
+use hakana_reflection_info::classlike_info::ClassLikeInfo;
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::StrId;
+use oxidized::{
+    aast,
+    aast_visitor::{visit, AstParams, Node, Visitor},
+    ast_defs::{self, Pos},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::scope_analyzer::ScopeAnalyzer;
+use crate::statements_analyzer::StatementsAnalyzer;
+use crate::typed_ast::TastInfo;
+
+// Checks that a constructor sets every property that has no default and can't hold null,
+// before the properties are read and before $this is passed anywhere that could read them.
+// Calls to the class's own methods are followed, so properties can be set in helpers.
+// Properties only count as set once every branch leading to a point has set them, and
+// loop bodies may not run at all.
+pub(crate) fn check_constructor(
+    statements_analyzer: &StatementsAnalyzer,
+    stmt: &aast::Class_<(), ()>,
+    classlike_storage: &ClassLikeInfo,
+    tast_info: &mut TastInfo,
+) {
+    let constructor = if let Some(constructor) = stmt
+        .methods
+        .iter()
+        .find(|method| method.name.1 == "__construct" && !method.abstract_)
+    {
+        constructor
+    } else {
+        return;
+    };
+
+    let candidates = get_uninitialized_properties(statements_analyzer, stmt, classlike_storage);
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    let methods = stmt
+        .methods
+        .iter()
+        .filter(|method| !method.static_ && !method.abstract_ && method.name.1 != "__construct")
+        .map(|method| (method.name.1.as_str(), method))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut scanner = Scanner {
+        candidates: &candidates,
+        methods: &methods,
+        followed_methods: vec![],
+        return_states: vec![],
+        assigned: FxHashSet::default(),
+        closure_depth: 0,
+        reported: FxHashSet::default(),
+        uninitialized_uses: vec![],
+    };
+
+    let end_state = scanner.walk_block(
+        &constructor.body.fb_ast.0,
+        Some(FxHashSet::default()),
+        &mut vec![],
+    );
+
+    if let Some(end_state) = end_state {
+        scanner.add_unset_at_exit(&end_state);
+    }
+
+    let codebase = statements_analyzer.get_codebase();
+    let calling_functionlike_id = Some(FunctionLikeIdentifier::Method(
+        classlike_storage.name,
+        StrId::construct(),
+    ));
+
+    for (property_name, pos, reason) in scanner.uninitialized_uses {
+        let issue_pos = if let Some(pos) = pos {
+            statements_analyzer.get_hpos(pos)
+        } else if let Some(property_pos) = &candidates[property_name] {
+            property_pos.clone()
+        } else {
+            statements_analyzer.get_hpos(&constructor.name.0)
+        };
+
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::PropertyNotSetInConstructor,
+                format!(
+                    "Property {}::${} {}",
+                    codebase.interner.lookup(&classlike_storage.name),
+                    property_name,
+                    reason
+                ),
+                issue_pos,
+                &calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+}
+
+// The properties declared in the class that have to be set in its constructor, with the
+// positions of their declarations
+fn get_uninitialized_properties<'a>(
+    statements_analyzer: &StatementsAnalyzer,
+    stmt: &'a aast::Class_<(), ()>,
+    classlike_storage: &ClassLikeInfo,
+) -> FxHashMap<&'a str, Option<HPos>> {
+    let codebase = statements_analyzer.get_codebase();
+    let resolved_names = statements_analyzer.get_file_analyzer().resolved_names;
+
+    let mut candidates = FxHashMap::default();
+
+    for var in &stmt.vars {
+        if var.is_static || var.abstract_ || var.expr.is_some() || var.xhp_attr.is_some() {
+            continue;
+        }
+
+        let mut is_late_init = false;
+
+        for user_attribute in &var.user_attributes {
+            if let Some(name) = resolved_names.get(&user_attribute.name.0.start_offset()) {
+                is_late_init |= codebase.interner.lookup(name) == "__LateInit";
+            }
+        }
+
+        if is_late_init {
+            continue;
+        }
+
+        let property_name = if let Some(property_name) = codebase.interner.get(&var.id.1) {
+            property_name
+        } else {
+            continue;
+        };
+
+        // promoted constructor params
+        if classlike_storage
+            .initialized_properties
+            .contains(&property_name)
+        {
+            continue;
+        }
+
+        if let Some(property_storage) = classlike_storage.properties.get(&property_name) {
+            if property_storage.type_.is_mixed()
+                || property_storage.type_.is_nullable()
+                || property_storage.type_.is_null()
+            {
+                continue;
+            }
+
+            candidates.insert(var.id.1.as_str(), property_storage.pos.clone());
+        }
+    }
+
+    candidates
+}
+
+struct Scanner<'a> {
+    candidates: &'a FxHashMap<&'a str, Option<HPos>>,
+    methods: &'a FxHashMap<&'a str, &'a aast::Method_<(), ()>>,
+    // the methods being followed from the constructor, innermost last
+    followed_methods: Vec<&'a str>,
+    // the properties set where the innermost followed method returns
+    return_states: Vec<FxHashSet<String>>,
+    // the properties set on every path to the expression being scanned
+    assigned: FxHashSet<String>,
+    closure_depth: usize,
+    reported: FxHashSet<&'a str>,
+    uninitialized_uses: Vec<(&'a str, Option<&'a Pos>, String)>,
+}
+
+impl<'a> Scanner<'a> {
+    // Returns the properties set when control reaches the end of the statements, or None
+    // if it never does. The properties set at each break are added to break_states.
+    fn walk_block(
+        &mut self,
+        stmts: &'a [aast::Stmt<(), ()>],
+        mut state: Option<FxHashSet<String>>,
+        break_states: &mut Vec<FxHashSet<String>>,
+    ) -> Option<FxHashSet<String>> {
+        for stmt in stmts {
+            state = if let Some(state) = state {
+                self.walk_stmt(stmt, state, break_states)
+            } else {
+                return None;
+            };
+        }
+
+        state
+    }
+
+    fn walk_stmt(
+        &mut self,
+        stmt: &'a aast::Stmt<(), ()>,
+        state: FxHashSet<String>,
+        break_states: &mut Vec<FxHashSet<String>>,
+    ) -> Option<FxHashSet<String>> {
+        match &stmt.1 {
+            aast::Stmt_::Expr(boxed) => Some(self.scan_expr(boxed, state)),
+            aast::Stmt_::Return(_) => {
+                let state = if let Some(return_expr) = stmt.1.as_return().unwrap() {
+                    self.scan_expr(return_expr, state)
+                } else {
+                    state
+                };
+
+                if self.followed_methods.is_empty() {
+                    self.add_unset_at_exit(&state);
+                } else {
+                    self.return_states.push(state);
+                }
+
+                None
+            }
+            aast::Stmt_::Throw(boxed) => {
+                self.scan_expr(boxed, state);
+
+                None
+            }
+            aast::Stmt_::Break => {
+                break_states.push(state);
+
+                None
+            }
+            aast::Stmt_::Continue => None,
+            aast::Stmt_::If(boxed) => {
+                let state = self.scan_expr(&boxed.0, state);
+
+                let if_state = self.walk_block(&boxed.1 .0, Some(state.clone()), break_states);
+                let else_state = self.walk_block(&boxed.2 .0, Some(state), break_states);
+
+                merge_states(if_state, else_state)
+            }
+            aast::Stmt_::While(boxed) => {
+                let state = self.scan_expr(&boxed.0, state);
+
+                self.walk_block(&boxed.1 .0, Some(state.clone()), &mut vec![]);
+
+                Some(state)
+            }
+            aast::Stmt_::Do(boxed) => {
+                let mut loop_break_states = vec![];
+
+                let body_state =
+                    self.walk_block(&boxed.0 .0, Some(state.clone()), &mut loop_break_states);
+
+                if let Some(body_state) = body_state {
+                    let body_state = self.scan_expr(&boxed.1, body_state);
+
+                    Some(
+                        loop_break_states
+                            .into_iter()
+                            .fold(body_state, |a, b| a.intersection(&b).cloned().collect()),
+                    )
+                } else {
+                    // the body only runs once up to a break
+                    Some(state)
+                }
+            }
+            aast::Stmt_::For(boxed) => {
+                let mut state = state;
+
+                for init_expr in &boxed.0 {
+                    state = self.scan_expr(init_expr, state);
+                }
+
+                if let Some(condition) = &boxed.1 {
+                    state = self.scan_expr(condition, state);
+                }
+
+                if let Some(mut body_state) =
+                    self.walk_block(&boxed.3 .0, Some(state.clone()), &mut vec![])
+                {
+                    for increment_expr in &boxed.2 {
+                        body_state = self.scan_expr(increment_expr, body_state);
+                    }
+                }
+
+                Some(state)
+            }
+            aast::Stmt_::Foreach(boxed) => {
+                let state = self.scan_expr(&boxed.0, state);
+
+                self.walk_block(&boxed.2 .0, Some(state.clone()), &mut vec![]);
+
+                Some(state)
+            }
+            aast::Stmt_::Switch(boxed) => {
+                let mut state = self.scan_expr(&boxed.0, state);
+
+                for case in &boxed.1 {
+                    state = self.scan_expr(&case.0, state);
+                }
+
+                // falling through from one case to the next can't set fewer properties
+                // than jumping to the next case directly
+                let mut switch_break_states = vec![];
+                let mut end_state = None;
+
+                for case in &boxed.1 {
+                    end_state =
+                        self.walk_block(&case.1 .0, Some(state.clone()), &mut switch_break_states);
+                }
+
+                if let Some(default_case) = &boxed.2 {
+                    end_state = self.walk_block(
+                        &default_case.1 .0,
+                        Some(state.clone()),
+                        &mut switch_break_states,
+                    );
+                } else {
+                    end_state = merge_states(end_state, Some(state));
+                }
+
+                switch_break_states
+                    .into_iter()
+                    .fold(end_state, |a, b| merge_states(a, Some(b)))
+            }
+            aast::Stmt_::Try(boxed) => {
+                let mut end_state = self.walk_block(&boxed.0 .0, Some(state.clone()), break_states);
+
+                // an exception can be thrown before anything in the try block runs
+                for catch in &boxed.1 {
+                    end_state = merge_states(
+                        end_state,
+                        self.walk_block(&catch.2 .0, Some(state.clone()), break_states),
+                    );
+                }
+
+                if boxed.2.is_empty() {
+                    end_state
+                } else if let Some(end_state) = end_state {
+                    self.walk_block(&boxed.2 .0, Some(end_state), break_states)
+                } else {
+                    self.walk_block(&boxed.2 .0, Some(state), break_states);
+
+                    None
+                }
+            }
+            aast::Stmt_::Awaitall(boxed) => {
+                let mut state = state;
+
+                for (_, expr) in &boxed.0 {
+                    state = self.scan_expr(expr, state);
+                }
+
+                self.walk_block(&boxed.1 .0, Some(state), break_states)
+            }
+            aast::Stmt_::Using(boxed) => {
+                let mut state = state;
+
+                for using_expr in &boxed.exprs.1 {
+                    state = self.scan_expr(using_expr, state);
+                }
+
+                self.walk_block(&boxed.block, Some(state), break_states)
+            }
+            aast::Stmt_::Block(boxed) => self.walk_block(boxed, Some(state), break_states),
+            _ => Some(state),
+        }
+    }
+
+    fn scan_expr(
+        &mut self,
+        expr: &'a aast::Expr<(), ()>,
+        state: FxHashSet<String>,
+    ) -> FxHashSet<String> {
+        self.assigned = state;
+
+        visit(self, &mut (), expr).unwrap();
+
+        std::mem::take(&mut self.assigned)
+    }
+
+    // Scans the body of a method called on $this as if it were part of the constructor
+    fn follow_method(&mut self, method_name: &'a str, method: &'a aast::Method_<(), ()>) {
+        let state = std::mem::take(&mut self.assigned);
+        let caller_return_states = std::mem::take(&mut self.return_states);
+
+        self.followed_methods.push(method_name);
+
+        let end_state = self.walk_block(&method.body.fb_ast.0, Some(state.clone()), &mut vec![]);
+
+        self.followed_methods.pop();
+
+        let return_states = std::mem::replace(&mut self.return_states, caller_return_states);
+
+        // if the method always throws, the constructor doesn't continue past the call
+        self.assigned = return_states
+            .into_iter()
+            .fold(end_state, |a, b| merge_states(a, Some(b)))
+            .unwrap_or(state);
+    }
+
+    fn add_unset_at_exit(&mut self, state: &FxHashSet<String>) {
+        let mut unset_properties = self
+            .candidates
+            .keys()
+            .filter(|property_name| {
+                !state.contains(**property_name) && !self.reported.contains(*property_name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        unset_properties.sort();
+
+        for property_name in unset_properties {
+            self.reported.insert(property_name);
+            self.uninitialized_uses.push((
+                property_name,
+                None,
+                "isn't set in the constructor".to_string(),
+            ));
+        }
+    }
+
+    fn add_unset_at_use(&mut self, pos: &'a Pos, reason: String) {
+        let mut unset_properties = self
+            .candidates
+            .keys()
+            .filter(|property_name| {
+                !self.assigned.contains(**property_name) && !self.reported.contains(*property_name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        unset_properties.sort();
+
+        for property_name in unset_properties {
+            self.reported.insert(property_name);
+            self.uninitialized_uses
+                .push((property_name, Some(pos), reason.clone()));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Scanner<'ast> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_expr(&mut self, c: &mut (), expr: &'ast aast::Expr<(), ()>) -> Result<(), ()> {
+        if self.closure_depth > 0 {
+            // a closure using $this can be called before the properties are set
+            if is_this(expr) {
+                self.add_unset_at_use(
+                    &expr.1,
+                    "isn't set in the constructor before $this is captured by a closure"
+                        .to_string(),
+                );
+
+                return Ok(());
+            }
+
+            return expr.recurse(c, self);
+        }
+
+        match &expr.2 {
+            aast::Expr_::Binop(boxed) => {
+                if let ast_defs::Bop::Eq(None) = boxed.0 {
+                    if let Some(property_name) = get_this_property_name(&boxed.1) {
+                        self.visit_expr(c, &boxed.2)?;
+
+                        self.assigned.insert(property_name.to_string());
+
+                        return Ok(());
+                    }
+                }
+            }
+            aast::Expr_::ObjGet(boxed) => {
+                if let Some(property_name) = get_this_property_name(expr) {
+                    if self.candidates.contains_key(property_name)
+                        && !self.assigned.contains(property_name)
+                        && !self.reported.contains(property_name)
+                    {
+                        self.reported.insert(property_name);
+                        self.uninitialized_uses.push((
+                            property_name,
+                            Some(&expr.1),
+                            "is read before it's set in the constructor".to_string(),
+                        ));
+                    }
+
+                    return Ok(());
+                }
+
+                // a dynamic property name, e.g. $this->$name
+                if is_this(&boxed.0) {
+                    return self.visit_expr(c, &boxed.1);
+                }
+            }
+            aast::Expr_::Call(boxed) => {
+                if let aast::Expr_::ObjGet(obj_get) = &boxed.0 .2 {
+                    if is_this(&obj_get.0) {
+                        for (_, arg_expr) in &boxed.2 {
+                            self.visit_expr(c, arg_expr)?;
+                        }
+
+                        if let Some(unpacked_arg) = &boxed.3 {
+                            self.visit_expr(c, unpacked_arg)?;
+                        }
+
+                        let method_name = if let aast::Expr_::Id(id) = &obj_get.1 .2 {
+                            id.1.as_str()
+                        } else {
+                            "a method"
+                        };
+
+                        if let Some(method) = self.methods.get(method_name) {
+                            if !self.followed_methods.contains(&method_name) {
+                                self.follow_method(method_name, *method);
+
+                                return Ok(());
+                            }
+                        }
+
+                        self.add_unset_at_use(
+                            &expr.1,
+                            format!(
+                                "isn't set in the constructor before {} is called on $this",
+                                method_name
+                            ),
+                        );
+
+                        return Ok(());
+                    }
+                }
+            }
+            aast::Expr_::Lfun(_) | aast::Expr_::Efun(_) => {
+                self.closure_depth += 1;
+                let result = expr.recurse(c, self);
+                self.closure_depth -= 1;
+
+                return result;
+            }
+            aast::Expr_::Lvar(_) => {
+                if is_this(expr) {
+                    self.add_unset_at_use(
+                        &expr.1,
+                        "isn't set in the constructor before $this is used".to_string(),
+                    );
+                }
+
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        expr.recurse(c, self)
+    }
+}
+
+fn is_this(expr: &aast::Expr<(), ()>) -> bool {
+    if let aast::Expr_::Lvar(lid) = &expr.2 {
+        lid.1 .1 == "$this"
+    } else {
+        false
+    }
+}
+
+fn get_this_property_name(expr: &aast::Expr<(), ()>) -> Option<&str> {
+    if let aast::Expr_::ObjGet(boxed) = &expr.2 {
+        if is_this(&boxed.0) && matches!(boxed.3, ast_defs::PropOrMethod::IsProp) {
+            if let aast::Expr_::Id(id) = &boxed.1 .2 {
+                return Some(id.1.as_str());
+            }
+        }
+    }
+
+    None
+}
+
+fn merge_states(
+    a: Option<FxHashSet<String>>,
+    b: Option<FxHashSet<String>>,
+) -> Option<FxHashSet<String>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+        (Some(state), None) | (None, Some(state)) => Some(state),
+        (None, None) => None,
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use hakana_reflection_info::{
+    assertion::Assertion,
+    codebase_info::{symbols::SymbolKind, CodebaseInfo},
+    data_flow::graph::DataFlowGraph,
+    data_flow::graph::GraphKind,
+    t_atomic::{DictKey, TAtomic},
+    t_union::{TUnion, TypeNode},
+    type_resolution::TypeResolutionContext,
+    FileSource, StrId,
+};
+use hakana_type::type_combiner;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    config::Config, file_analyzer::FileAnalyzer, reconciler::assertion_reconciler,
+    statements_analyzer::StatementsAnalyzer, typed_ast::TastInfo,
+};
+
+// Entry points into the type combiner and reconciler that only need a codebase,
+// and that take JSON-serialized types and assertions. These exist so fuzzers and
+// property-based tests can exercise those components directly.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeApiError {
+    InvalidTypes(String),
+    InvalidAssertion(String),
+    EmptyType,
+}
+
+impl fmt::Display for TypeApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeApiError::InvalidTypes(message) => write!(f, "Invalid types: {}", message),
+            TypeApiError::InvalidAssertion(message) => {
+                write!(f, "Invalid assertion: {}", message)
+            }
+            TypeApiError::EmptyType => write!(f, "Types must contain at least one atomic type"),
+        }
+    }
+}
+
+// Combines a JSON list of atomic types into a single union
+pub fn combine(types_json: &str, codebase: &CodebaseInfo) -> Result<TUnion, TypeApiError> {
+    let types = parse_atomics(types_json, codebase)?;
+
+    Ok(TUnion::new(type_combiner::combine(types, codebase, false)))
+}
+
+// Narrows a JSON list of atomic types (the existing type of a variable) with a JSON assertion,
+// as if that assertion had been made in a conditional
+pub fn reconcile(
+    existing_types_json: &str,
+    assertion_json: &str,
+    codebase: &CodebaseInfo,
+) -> Result<TUnion, TypeApiError> {
+    let existing_types = parse_atomics(existing_types_json, codebase)?;
+
+    if existing_types.is_empty() {
+        return Err(TypeApiError::EmptyType);
+    }
+
+    let assertion = serde_json::from_str::<Assertion>(assertion_json)
+        .map_err(|e| TypeApiError::InvalidAssertion(e.to_string()))?;
+
+    validate_assertion(&assertion, codebase).map_err(TypeApiError::InvalidAssertion)?;
+
+    let config = Config::new("".to_string(), FxHashSet::default());
+    let resolved_names = FxHashMap::default();
+    let file_source = FileSource {
+        file_path: StrId::empty(),
+        file_path_actual: "".to_string(),
+        file_contents: "".to_string(),
+        is_production_code: true,
+        is_generated: false,
+        issue_profile: None,
+        regeneration_command: None,
+        literal_collection_thresholds: config.literal_collection_thresholds,
+        hh_fixmes: Default::default(),
+        comments: vec![],
+    };
+
+    let mut tast_info = TastInfo::new(
+        DataFlowGraph::new(GraphKind::FunctionBody),
+        &file_source,
+        &vec![],
+        &config.all_custom_issues,
+        None,
+        None,
+    );
+
+    let file_analyzer = FileAnalyzer::new(file_source, &resolved_names, codebase, &config);
+    let type_resolution_context = TypeResolutionContext::new();
+    let statements_analyzer =
+        StatementsAnalyzer::new(&file_analyzer, &type_resolution_context, vec![]);
+
+    Ok(assertion_reconciler::reconcile(
+        &assertion,
+        Some(&TUnion::new(existing_types)),
+        false,
+        None,
+        &statements_analyzer,
+        &mut tast_info,
+        false,
+        None,
+        &None,
+        false,
+        false,
+        &FxHashMap::default(),
+    ))
+}
+
+fn parse_atomics(types_json: &str, codebase: &CodebaseInfo) -> Result<Vec<TAtomic>, TypeApiError> {
+    let types = serde_json::from_str::<Vec<TAtomic>>(types_json)
+        .map_err(|e| TypeApiError::InvalidTypes(e.to_string()))?;
+
+    for atomic in &types {
+        validate_atomic(atomic, codebase).map_err(TypeApiError::InvalidTypes)?;
+    }
+
+    Ok(types)
+}
+
+fn validate_assertion(assertion: &Assertion, codebase: &CodebaseInfo) -> Result<(), String> {
+    match assertion {
+        Assertion::IsType(atomic)
+        | Assertion::IsNotType(atomic)
+        | Assertion::IsEqual(atomic)
+        | Assertion::IsNotEqual(atomic) => validate_atomic(atomic, codebase),
+        Assertion::InArray(union) | Assertion::NotInArray(union) => {
+            for atomic in &union.types {
+                validate_atomic(atomic, codebase)?;
+            }
+
+            Ok(())
+        }
+        Assertion::HasArrayKey(key)
+        | Assertion::DoesNotHaveArrayKey(key)
+        | Assertion::HasNonnullEntryForKey(key)
+        | Assertion::DoesNotHaveNonnullEntryForKey(key) => validate_dict_key(key, codebase),
+        _ => Ok(()),
+    }
+}
+
+// The combiner and reconciler assume every symbol a type refers to exists in the codebase,
+// and that types have already been resolved in the context of some function
+fn validate_atomic(atomic: &TAtomic, codebase: &CodebaseInfo) -> Result<(), String> {
+    let union = TUnion::new(vec![atomic.clone()]);
+
+    for node in union.get_all_child_nodes() {
+        let atomic = if let TypeNode::Atomic(atomic) = node {
+            atomic
+        } else {
+            continue;
+        };
+
+        match atomic {
+            TAtomic::TNamedObject { name, .. } | TAtomic::TLiteralClassname { name } => {
+                if !codebase.classlike_infos.contains_key(name) {
+                    return Err(format!("unknown class {}", name.0));
+                }
+            }
+            TAtomic::TEnum { name, .. } => validate_enum(name, None, codebase)?,
+            TAtomic::TEnumLiteralCase {
+                enum_name,
+                member_name,
+                ..
+            } => validate_enum(enum_name, Some(member_name), codebase)?,
+            TAtomic::TTypeAlias { name, .. } => {
+                if !codebase.type_definitions.contains_key(name) {
+                    return Err(format!("unknown type alias {}", name.0));
+                }
+            }
+            TAtomic::TDict {
+                known_items: Some(known_items),
+                ..
+            } => {
+                for key in known_items.keys() {
+                    validate_dict_key(key, codebase)?;
+                }
+            }
+            TAtomic::TReference { .. }
+            | TAtomic::TGenericParam { .. }
+            | TAtomic::TGenericClassname { .. }
+            | TAtomic::TGenericTypename { .. }
+            | TAtomic::TClassTypeConstant { .. }
+            | TAtomic::TEnumClassLabel { .. } => {
+                return Err(format!(
+                    "{} needs a function context to be resolved",
+                    atomic.get_id(None)
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_dict_key(key: &DictKey, codebase: &CodebaseInfo) -> Result<(), String> {
+    if let DictKey::Enum(enum_name, member_name) = key {
+        validate_enum(enum_name, Some(member_name), codebase)?;
+    }
+
+    Ok(())
+}
+
+fn validate_enum(
+    enum_name: &StrId,
+    member_name: Option<&StrId>,
+    codebase: &CodebaseInfo,
+) -> Result<(), String> {
+    let enum_storage = if let Some(classlike_storage) = codebase.classlike_infos.get(enum_name) {
+        classlike_storage
+    } else {
+        return Err(format!("unknown enum {}", enum_name.0));
+    };
+
+    if !matches!(enum_storage.kind, SymbolKind::Enum) {
+        return Err(format!("{} is not an enum", enum_name.0));
+    }
+
+    if let Some(member_name) = member_name {
+        if !enum_storage.constants.contains_key(member_name) {
+            return Err(format!(
+                "unknown enum case {}::{}",
+                enum_name.0, member_name.0
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
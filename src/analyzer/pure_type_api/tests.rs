@@ -0,0 +1,104 @@
+use super::*;
+
+fn to_json(types: Vec<TAtomic>) -> String {
+    serde_json::to_string(&types).unwrap()
+}
+
+#[test]
+fn combine_scalars() {
+    let codebase = CodebaseInfo::new();
+
+    let combined = combine(&to_json(vec![TAtomic::TInt, TAtomic::TNull]), &codebase).unwrap();
+
+    assert_eq!(2, combined.types.len());
+    assert!(combined.is_nullable());
+}
+
+#[test]
+fn combine_rejects_malformed_json() {
+    let codebase = CodebaseInfo::new();
+
+    assert!(matches!(
+        combine("[{\"TNotAType\": 1}]", &codebase),
+        Err(TypeApiError::InvalidTypes(_))
+    ));
+}
+
+#[test]
+fn combine_rejects_unknown_class() {
+    let codebase = CodebaseInfo::new();
+
+    let types = to_json(vec![TAtomic::TNamedObject {
+        name: StrId(1_000_000),
+        type_params: None,
+        is_this: false,
+        extra_types: None,
+        remapped_params: false,
+    }]);
+
+    assert!(matches!(
+        combine(&types, &codebase),
+        Err(TypeApiError::InvalidTypes(_))
+    ));
+}
+
+#[test]
+fn reconcile_removes_null() {
+    let codebase = CodebaseInfo::new();
+
+    let assertion = serde_json::to_string(&Assertion::IsNotType(TAtomic::TNull)).unwrap();
+
+    let reconciled = reconcile(
+        &to_json(vec![TAtomic::TInt, TAtomic::TNull]),
+        &assertion,
+        &codebase,
+    )
+    .unwrap();
+
+    assert_eq!(vec![TAtomic::TInt], reconciled.types);
+}
+
+#[test]
+fn reconcile_rejects_empty_type() {
+    let codebase = CodebaseInfo::new();
+
+    assert_eq!(
+        Err(TypeApiError::EmptyType),
+        reconcile("[]", "\"Truthy\"", &codebase)
+    );
+}
+
+#[test]
+fn reconcile_rejects_unknown_enum() {
+    let codebase = CodebaseInfo::new();
+
+    let existing_types = to_json(vec![TAtomic::TEnum {
+        name: StrId(1_000_000),
+        base_type: None,
+    }]);
+
+    let assertion =
+        serde_json::to_string(&Assertion::IsNotEqual(TAtomic::TLiteralInt { value: 1 })).unwrap();
+
+    assert!(matches!(
+        reconcile(&existing_types, &assertion, &codebase),
+        Err(TypeApiError::InvalidTypes(_))
+    ));
+}
+
+#[test]
+fn reconcile_rejects_unknown_enum_case_in_assertion() {
+    let codebase = CodebaseInfo::new();
+
+    let assertion = serde_json::to_string(&Assertion::IsNotType(TAtomic::TEnumLiteralCase {
+        enum_name: StrId(1_000_000),
+        member_name: StrId(1_000_001),
+        constraint_type: None,
+    }))
+    .unwrap();
+
+    assert!(matches!(
+        reconcile(&to_json(vec![TAtomic::TInt]), &assertion, &codebase),
+        Err(TypeApiError::InvalidAssertion(_))
+    ));
+}
@@ -45,6 +45,18 @@ pub fn reconcile(
 
     let old_var_type_string = existing_var_type.get_id(Some(&codebase.interner));
 
+    let resolved_var_type = resolve_class_type_constants(existing_var_type, codebase, tast_info);
+    let existing_var_type = resolved_var_type.as_ref().unwrap_or(existing_var_type);
+
+    // nothing is known about projections that couldn't be resolved, so assertions
+    // about them can be neither redundant nor impossible
+    let has_unresolved_projections = existing_var_type
+        .types
+        .iter()
+        .any(|atomic| matches!(atomic, TAtomic::TClassTypeConstant { .. }));
+
+    let can_report_issues = can_report_issues && !has_unresolved_projections;
+
     if is_negation {
         return negated_assertion_reconciler::reconcile(
             assertion,
@@ -61,6 +73,36 @@ pub fn reconcile(
         );
     }
 
+    // positive assertions narrow unresolved projections as if they were mixed, apart from
+    // `is nonnull`, which only has to remove null
+    let mixed_projection_var_type;
+
+    let existing_var_type = if has_unresolved_projections
+        && !matches!(
+            assertion.get_type(),
+            Some(TAtomic::TMixedWithFlags(_, _, _, true))
+        ) {
+        let mut var_type = existing_var_type.clone();
+        var_type.types = type_combiner::combine(
+            existing_var_type
+                .types
+                .iter()
+                .map(|atomic| match atomic {
+                    TAtomic::TClassTypeConstant { .. } => {
+                        TAtomic::TMixedWithFlags(true, false, false, false)
+                    }
+                    _ => atomic.clone(),
+                })
+                .collect(),
+            codebase,
+            false,
+        );
+        mixed_projection_var_type = var_type;
+        &mixed_projection_var_type
+    } else {
+        existing_var_type
+    };
+
     let simple_asserted_type = simple_assertion_reconciler::reconcile(
         assertion,
         &existing_var_type,
@@ -136,6 +178,62 @@ pub fn reconcile(
     get_mixed_any()
 }
 
+// Resolves type constant projections like this::TData or SomeClass::TShape through the
+// codebase, where the class is known and gives the type constant a value. Abstract type
+// constants, and projections on anything other than a known class, are kept as-is.
+fn resolve_class_type_constants(
+    existing_var_type: &TUnion,
+    codebase: &CodebaseInfo,
+    tast_info: &mut TastInfo,
+) -> Option<TUnion> {
+    if !existing_var_type
+        .types
+        .iter()
+        .any(|atomic| matches!(atomic, TAtomic::TClassTypeConstant { .. }))
+    {
+        return None;
+    }
+
+    let mut resolved_types = vec![];
+
+    for atomic in &existing_var_type.types {
+        if let TAtomic::TClassTypeConstant {
+            class_type,
+            member_name,
+        } = atomic
+        {
+            if let TAtomic::TNamedObject { name, is_this, .. } = class_type.as_ref() {
+                if let Some(Some(_)) = codebase
+                    .classlike_infos
+                    .get(name)
+                    // for this::, a subclass may provide a different type
+                    .filter(|classlike_info| !is_this || classlike_info.is_final)
+                    .and_then(|classlike_info| classlike_info.type_constants.get(member_name))
+                {
+                    let mut resolved_type = wrap_atomic(atomic.clone());
+
+                    type_expander::expand_union(
+                        codebase,
+                        &mut resolved_type,
+                        &TypeExpansionOptions::default(),
+                        &mut tast_info.data_flow_graph,
+                    );
+
+                    resolved_types.extend(resolved_type.types);
+                    continue;
+                }
+            }
+        }
+
+        resolved_types.push(atomic.clone());
+    }
+
+    let mut resolved_var_type = existing_var_type.clone();
+    resolved_var_type.types = type_combiner::combine(resolved_types, codebase, false);
+
+    Some(resolved_var_type)
+}
+
 pub(crate) fn refine_atomic_with_union(
     statements_analyzer: &StatementsAnalyzer,
     new_type: &TAtomic,
@@ -578,6 +676,62 @@ pub(crate) fn intersect_atomic_with_atomic(
                 return Some(type_2_atomic);
             }
         }
+        (TAtomic::TStringWithFlags(..), TAtomic::TStringWithPrefix { .. }) => {
+            return Some(type_2_atomic.clone());
+        }
+        // a pattern can't carry the string flags, so a string already known to be
+        // non-empty keeps them rather than widening to the bare pattern
+        (
+            TAtomic::TStringWithFlags(is_truthy, is_non_empty, is_nonspecific_literal),
+            TAtomic::TStringWithPattern { .. },
+        ) if *is_truthy || *is_non_empty || *is_nonspecific_literal => {
+            return Some(type_1_atomic.clone());
+        }
+        // a string can't carry both a prefix and a pattern, so the pattern wins, as it's
+        // what makes the string safe to use
+        (
+            TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. },
+            TAtomic::TStringWithPattern { .. },
+        ) => {
+            return Some(type_2_atomic.clone());
+        }
+        (TAtomic::TStringWithPattern { .. }, TAtomic::TStringWithPrefix { .. }) => {
+            return Some(type_1_atomic.clone());
+        }
+        (TAtomic::TLiteralClassname { name }, TAtomic::TStringWithPrefix { prefix }) => {
+            if codebase.interner.lookup(name).starts_with(prefix.as_str()) {
+                return Some(type_1_atomic.clone());
+            }
+        }
+        // we don't know the exact string value of these, so keep them as-is
+        (
+            TAtomic::TClassname { .. }
+            | TAtomic::TTypename { .. }
+            | TAtomic::TGenericClassname { .. }
+            | TAtomic::TGenericTypename { .. }
+            | TAtomic::TGenericParam { .. }
+            | TAtomic::TEnum { .. }
+            | TAtomic::TEnumLiteralCase { .. }
+            | TAtomic::TTypeAlias { .. },
+            TAtomic::TStringWithPrefix { .. },
+        )
+        | (
+            TAtomic::TLiteralClassname { .. }
+            | TAtomic::TLiteralString { .. }
+            | TAtomic::TClassname { .. }
+            | TAtomic::TTypename { .. }
+            | TAtomic::TGenericClassname { .. }
+            | TAtomic::TGenericTypename { .. }
+            | TAtomic::TGenericParam { .. }
+            | TAtomic::TEnum { .. }
+            | TAtomic::TEnumLiteralCase { .. }
+            | TAtomic::TTypeAlias { .. },
+            TAtomic::TStringWithPattern { .. },
+        ) => {
+            return Some(type_1_atomic.clone());
+        }
         _ => (),
     }
 
@@ -5,7 +5,7 @@ use super::{
 use crate::typed_ast::TastInfo;
 use crate::{scope_analyzer::ScopeAnalyzer, statements_analyzer::StatementsAnalyzer};
 use hakana_reflection_info::{
-    assertion::Assertion, codebase_info::CodebaseInfo,
+    assertion::Assertion, class_constant_info::ConstantInfo, codebase_info::CodebaseInfo,
     functionlike_identifier::FunctionLikeIdentifier, t_atomic::TAtomic, t_union::TUnion, StrId,
 };
 use hakana_type::{get_nothing, get_placeholder, wrap_atomic};
@@ -354,6 +354,8 @@ fn handle_literal_negated_equality(
                     } else {
                         acceptable_types.push(existing_atomic_type);
                     }
+                } else {
+                    acceptable_types.push(existing_atomic_type);
                 }
             }
             TAtomic::TString => {
@@ -410,9 +412,9 @@ fn handle_literal_negated_equality(
                     constraint_type,
                 } = assertion_type
                 {
-                    did_remove_type = true;
-
                     if enum_name == &existing_name {
+                        did_remove_type = true;
+
                         let enum_storage = codebase.classlike_infos.get(enum_name).unwrap();
 
                         for (cname, _) in &enum_storage.constants {
@@ -427,38 +429,28 @@ fn handle_literal_negated_equality(
                     } else {
                         acceptable_types.push(existing_atomic_type);
                     }
-                } else if let TAtomic::TLiteralString {
-                    value: assertion_value,
-                    ..
-                } = assertion_type
+                } else if let TAtomic::TLiteralString { .. } | TAtomic::TLiteralInt { .. } =
+                    assertion_type
                 {
                     let enum_storage = codebase.classlike_infos.get(&existing_name).unwrap();
-                    let mut matched_string = false;
+                    let mut matched_literal = false;
 
                     let mut member_enum_literals = vec![];
                     for (cname, const_info) in &enum_storage.constants {
-                        if let Some(inferred_type) = &const_info.inferred_type {
-                            if let Some(const_inferred_value) =
-                                inferred_type.get_single_literal_string_value(&codebase.interner)
-                            {
-                                if &const_inferred_value != assertion_value {
-                                    if let Some(constant_type) = codebase.get_class_constant_type(
-                                        &existing_name,
-                                        cname,
-                                        FxHashSet::default(),
-                                    ) {
-                                        member_enum_literals.push(constant_type.get_single_owned());
-                                    } else {
-                                        panic!("unrecognised constant type");
-                                    }
-                                } else {
-                                    matched_string = true;
-                                }
-                            }
+                        if enum_member_matches_literal(codebase, const_info, assertion_type) {
+                            matched_literal = true;
+                        } else if let Some(constant_type) = codebase.get_class_constant_type(
+                            &existing_name,
+                            cname,
+                            FxHashSet::default(),
+                        ) {
+                            member_enum_literals.push(constant_type.get_single_owned());
+                        } else {
+                            panic!("unrecognised constant type");
                         }
                     }
 
-                    if !matched_string {
+                    if !matched_literal {
                         acceptable_types.push(existing_atomic_type);
                     } else {
                         acceptable_types.extend(member_enum_literals);
@@ -479,30 +471,25 @@ fn handle_literal_negated_equality(
                     ..
                 } = assertion_type
                 {
-
                     if enum_name == &existing_name && member_name == &existing_member_name {
                         did_remove_type = true;
                     } else {
                         acceptable_types.push(existing_atomic_type);
                     }
-                } else if let TAtomic::TLiteralString { value, .. } = &assertion_type {
+                } else if let TAtomic::TLiteralString { .. } | TAtomic::TLiteralInt { .. } =
+                    assertion_type
+                {
                     let enum_storage = codebase.classlike_infos.get(&existing_name).unwrap();
 
-                    let mut matched_string = false;
-
-                    if let Some(const_info) = enum_storage.constants.get(&existing_member_name) {
-                        if let Some(const_inferred_type) = &const_info.inferred_type {
-                            if let Some(const_inferred_value) = const_inferred_type
-                                .get_single_literal_string_value(&codebase.interner)
-                            {
-                                if &const_inferred_value == value {
-                                    matched_string = true;
-                                }
-                            }
-                        }
-                    }
+                    let matched_literal = if let Some(const_info) =
+                        enum_storage.constants.get(&existing_member_name)
+                    {
+                        enum_member_matches_literal(codebase, const_info, assertion_type)
+                    } else {
+                        false
+                    };
 
-                    if !matched_string {
+                    if !matched_literal {
                         acceptable_types.push(existing_atomic_type);
                     } else {
                         did_remove_type = true;
@@ -540,3 +527,25 @@ fn handle_literal_negated_equality(
 
     new_var_type
 }
+
+// Whether the value of an enum member is the same as the given literal string or int
+fn enum_member_matches_literal(
+    codebase: &CodebaseInfo,
+    const_info: &ConstantInfo,
+    literal: &TAtomic,
+) -> bool {
+    if let Some(inferred_type) = &const_info.inferred_type {
+        match literal {
+            TAtomic::TLiteralString { value } => {
+                inferred_type.get_single_literal_string_value(&codebase.interner)
+                    == Some(value.clone())
+            }
+            TAtomic::TLiteralInt { value } => {
+                inferred_type.get_single_literal_int_value() == Some(*value)
+            }
+            _ => false,
+        }
+    } else {
+        false
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    expr::nullable_return_analyzer,
     scope_analyzer::ScopeAnalyzer,
     scope_context::{var_has_root, ScopeContext},
     statements_analyzer::StatementsAnalyzer,
@@ -43,6 +44,10 @@ pub(crate) fn reconcile_keyed_types(
         return;
     }
 
+    for var_id in new_types.keys() {
+        nullable_return_analyzer::mark_var_checked(var_id, pos, tast_info);
+    }
+
     let inside_loop = context.inside_loop;
 
     let old_new_types = new_types.clone();
@@ -51,6 +56,8 @@ pub(crate) fn reconcile_keyed_types(
 
     add_nested_assertions(&mut new_types, &mut active_new_types, context);
 
+    add_alias_assertions(&mut new_types, context);
+
     let codebase = statements_analyzer.get_codebase();
 
     // we want to remove any
@@ -61,6 +68,31 @@ pub(crate) fn reconcile_keyed_types(
             continue;
         }
 
+        // existence checks are keyed by the symbol they check, so there's no variable to narrow
+        if new_type_parts.iter().flatten().all(|assertion| {
+            matches!(
+                assertion,
+                Assertion::SymbolExists(_) | Assertion::SymbolDoesNotExist(_)
+            )
+        }) {
+            for new_type_part_parts in new_type_parts {
+                // a || b doesn't tell us that either exists
+                if let [assertion] = &new_type_part_parts[..] {
+                    match assertion {
+                        Assertion::SymbolExists(name) => {
+                            context.asserted_existing_symbols.insert(name.clone());
+                        }
+                        Assertion::SymbolDoesNotExist(name) => {
+                            context.asserted_existing_symbols.remove(name);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
+            continue;
+        }
+
         let mut has_negation = false;
         let mut has_isset = false;
         let mut has_inverted_isset = false;
@@ -217,6 +249,11 @@ pub(crate) fn reconcile_keyed_types(
 
         let mut result_type = result_type.unwrap();
 
+        // narrowing a readonly value doesn't make it mutable
+        if let Some(before_adjustment) = &before_adjustment {
+            result_type.readonly |= before_adjustment.readonly;
+        }
+
         if !did_type_exist && result_type.is_nothing() {
             continue;
         }
@@ -463,6 +500,31 @@ fn adjust_array_type(
         .insert(base_key, Rc::new(existing_type));
 }
 
+// Assertions about a variable also hold for the variables that are pure copies of it
+// (and vice versa), though those aren't reported as redundant or impossible
+fn add_alias_assertions(
+    new_types: &mut BTreeMap<String, Vec<Vec<Assertion>>>,
+    context: &ScopeContext,
+) {
+    if context.var_aliases.is_empty() {
+        return;
+    }
+
+    let mut alias_types = vec![];
+
+    for (var_id, new_type) in new_types.iter() {
+        for alias_var_id in context.get_var_aliases(var_id) {
+            if !new_types.contains_key(&alias_var_id)
+                && context.vars_in_scope.contains_key(&alias_var_id)
+            {
+                alias_types.push((alias_var_id, new_type.clone()));
+            }
+        }
+    }
+
+    new_types.extend(alias_types);
+}
+
 fn add_nested_assertions(
     new_types: &mut BTreeMap<String, Vec<Vec<Assertion>>>,
     active_new_types: &mut BTreeMap<String, FxHashSet<usize>>,
@@ -794,10 +856,18 @@ fn get_value_for_key(
                     let mut new_base_type_candidate;
 
                     if let TAtomic::TDict { known_items, .. } = &existing_key_type_part {
+                        // int keys are looked up the same way as they are for vecs
                         let known_item = if !array_key.starts_with("$") {
                             if let Some(known_items) = known_items {
-                                let key_parts_key = array_key.replace("'", "");
-                                known_items.get(&DictKey::String(key_parts_key))
+                                if INTEGER_REGEX.is_match(&array_key) {
+                                    array_key
+                                        .parse::<u32>()
+                                        .ok()
+                                        .and_then(|key| known_items.get(&DictKey::Int(key)))
+                                } else {
+                                    let key_parts_key = array_key.replace("'", "");
+                                    known_items.get(&DictKey::String(key_parts_key))
+                                }
                             } else {
                                 None
                             }
@@ -881,6 +951,8 @@ fn get_value_for_key(
                         TAtomic::TString
                             | TAtomic::TLiteralString { .. }
                             | TAtomic::TStringWithFlags(..)
+                            | TAtomic::TStringWithPrefix { .. }
+                            | TAtomic::TStringWithPattern { .. }
                     ) {
                         return Some(hakana_type::get_string());
                     } else if matches!(
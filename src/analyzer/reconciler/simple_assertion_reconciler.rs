@@ -58,6 +58,8 @@ pub(crate) fn reconcile(
                         | TAtomic::TFloat
                         | TAtomic::TInt { .. }
                         | TAtomic::TStringWithFlags(..)
+                        | TAtomic::TStringWithPrefix { .. }
+                        | TAtomic::TStringWithPattern { .. }
                         | TAtomic::TNum
                         | TAtomic::TString
                         | TAtomic::TTrue,
@@ -644,33 +646,37 @@ fn intersect_vec(
             acceptable_types.push(atomic.clone());
         } else {
             if let TAtomic::TNamedObject {
-                name,
-                type_params: Some(typed_params),
-                ..
+                name, type_params, ..
             } = atomic
             {
-                match statements_analyzer.get_codebase().interner.lookup(name) {
-                    "HH\\Container" => {
+                match (
+                    statements_analyzer.get_codebase().interner.lookup(name),
+                    type_params,
+                ) {
+                    ("HH\\Container", Some(typed_params)) => {
                         acceptable_types.push(TAtomic::TVec {
                             type_param: typed_params.get(0).unwrap().clone(),
                             known_items: None,
                             non_empty: false,
+                            is_tuple: false,
                             known_count: None,
                         });
                     }
-                    "HH\\KeyedContainer" | "HH\\AnyArray" => {
+                    ("HH\\KeyedContainer" | "HH\\AnyArray", Some(typed_params)) => {
                         acceptable_types.push(TAtomic::TVec {
                             type_param: typed_params.get(1).unwrap().clone(),
                             known_items: None,
                             non_empty: false,
+                            is_tuple: false,
                             known_count: None,
                         });
                     }
-                    "XHPChild" => {
+                    ("XHPChild", _) => {
                         acceptable_types.push(TAtomic::TVec {
                             type_param: wrap_atomic(atomic.clone()),
                             known_items: None,
                             non_empty: false,
+                            is_tuple: false,
                             known_count: None,
                         });
                     }
@@ -732,25 +738,27 @@ fn intersect_keyset(
             acceptable_types.push(atomic.clone());
         } else {
             if let TAtomic::TNamedObject {
-                name,
-                type_params: Some(typed_params),
-                ..
+                name, type_params, ..
             } = atomic
             {
-                match statements_analyzer.get_codebase().interner.lookup(name) {
-                    "HH\\Container" => {
+                match (
+                    statements_analyzer.get_codebase().interner.lookup(name),
+                    type_params,
+                ) {
+                    ("HH\\Container", Some(_)) => {
                         acceptable_types.push(TAtomic::TKeyset {
                             type_param: get_arraykey(true),
                         });
                     }
-                    "HH\\KeyedContainer" | "HH\\AnyArray" => {
+                    ("HH\\KeyedContainer" | "HH\\AnyArray", Some(typed_params)) => {
                         acceptable_types.push(TAtomic::TKeyset {
                             type_param: typed_params.get(0).unwrap().clone(),
                         });
                     }
-                    "XHPChild" => {
+                    // keyset values are always arraykeys
+                    ("XHPChild", _) => {
                         acceptable_types.push(TAtomic::TKeyset {
-                            type_param: wrap_atomic(atomic.clone()),
+                            type_param: get_arraykey(false),
                         });
                     }
                     _ => {}
@@ -1044,6 +1052,8 @@ fn intersect_string(
             | TAtomic::TClassname { .. }
             | TAtomic::TTypename { .. }
             | TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. }
             | TAtomic::TString { .. } => {
                 acceptable_types.push(atomic.clone());
             }
@@ -1514,9 +1524,17 @@ fn reconcile_non_empty_countable(
                     }
                 }
 
+                acceptable_types.push(atomic);
+            }
+        } else if let TAtomic::TKeyset { type_param } = &atomic {
+            did_remove_type = true;
+
+            if !type_param.is_nothing() {
                 acceptable_types.push(atomic);
             }
         } else {
+            // we can't tell whether other containers are empty
+            did_remove_type = true;
             acceptable_types.push(atomic);
         }
     }
@@ -1590,9 +1608,9 @@ fn reconcile_exactly_countable(
                 false
             };
             if !non_empty || min_under_count {
-                if type_param.is_nothing() {
-                    existing_var_type.remove_type(atomic);
-                } else {
+                existing_var_type.remove_type(atomic);
+
+                if !type_param.is_nothing() {
                     let non_empty_vec = atomic.get_non_empty_vec(Some(*count));
 
                     existing_var_type.types.push(non_empty_vec);
@@ -1608,9 +1626,9 @@ fn reconcile_exactly_countable(
         } = atomic
         {
             if !non_empty {
-                if params.is_none() {
-                    existing_var_type.remove_type(atomic);
-                } else {
+                existing_var_type.remove_type(atomic);
+
+                if !params.is_none() {
                     let non_empty_dict = atomic.clone().make_non_empty_dict();
 
                     existing_var_type.types.push(non_empty_dict);
@@ -1624,6 +1642,9 @@ fn reconcile_exactly_countable(
                     }
                 }
             }
+        } else {
+            // we can't tell how many items other containers have
+            did_remove_type = true;
         }
     }
 
@@ -2156,7 +2177,10 @@ fn reconcile_has_nonnull_entry_for_key(
                 did_remove_type = true;
                 acceptable_types.push(atomic);
             }
-            TAtomic::TString | TAtomic::TStringWithFlags(..) => {
+            TAtomic::TString
+            | TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. } => {
                 if let DictKey::Int(_) = key_name {
                     acceptable_types.push(atomic);
                 }
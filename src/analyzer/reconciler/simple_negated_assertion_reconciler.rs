@@ -118,6 +118,12 @@ pub(crate) fn reconcile(
                     suppressed_issues,
                 ));
             }
+            TAtomic::TStringWithPrefix { prefix } => {
+                return Some(subtract_string_with_prefix(existing_var_type, prefix));
+            }
+            TAtomic::TStringWithPattern { pattern } => {
+                return Some(subtract_string_with_pattern(existing_var_type, pattern));
+            }
             TAtomic::TArraykey { .. } => {
                 return Some(subtract_arraykey(
                     assertion,
@@ -390,7 +396,6 @@ fn subtract_object(
     )
 }
 
-// TODO: in the future subtract from Container and KeyedContainer
 fn subtract_vec(
     assertion: &Assertion,
     existing_var_type: &TUnion,
@@ -444,6 +449,10 @@ fn subtract_vec(
                 acceptable_types.push(atomic);
             }
         } else {
+            if can_contain_collections(&atomic, statements_analyzer) {
+                did_remove_type = true;
+            }
+
             acceptable_types.push(atomic);
         }
     }
@@ -517,6 +526,10 @@ fn subtract_keyset(
                 acceptable_types.push(atomic);
             }
         } else {
+            if can_contain_collections(&atomic, statements_analyzer) {
+                did_remove_type = true;
+            }
+
             acceptable_types.push(atomic);
         }
     }
@@ -590,6 +603,10 @@ fn subtract_dict(
                 acceptable_types.push(atomic);
             }
         } else {
+            if can_contain_collections(&atomic, statements_analyzer) {
+                did_remove_type = true;
+            }
+
             acceptable_types.push(atomic);
         }
     }
@@ -610,6 +627,19 @@ fn subtract_dict(
     )
 }
 
+// XHPChild and the Container-like pseudo-types may or may not hold a vec, dict or keyset,
+// so they're kept when one of those is subtracted, but the check isn't redundant
+fn can_contain_collections(atomic: &TAtomic, statements_analyzer: &StatementsAnalyzer) -> bool {
+    if let TAtomic::TNamedObject { name, .. } = atomic {
+        matches!(
+            statements_analyzer.get_codebase().interner.lookup(name),
+            "HH\\Container" | "HH\\KeyedContainer" | "HH\\AnyArray" | "XHPChild"
+        )
+    } else {
+        false
+    }
+}
+
 fn subtract_string(
     assertion: &Assertion,
     existing_var_type: &TUnion,
@@ -714,6 +744,44 @@ fn subtract_string(
     )
 }
 
+// Only removes strings we know start with the prefix. A string not starting with some
+// prefix is a normal thing to check for, so this never reports redundant checks
+fn subtract_string_with_prefix(existing_var_type: &TUnion, prefix: &String) -> TUnion {
+    let mut new_var_type = existing_var_type.clone();
+
+    new_var_type.types.retain(|atomic| match atomic {
+        TAtomic::TLiteralString { value } => !value.starts_with(prefix.as_str()),
+        TAtomic::TStringWithPrefix {
+            prefix: existing_prefix,
+        } => !existing_prefix.starts_with(prefix.as_str()),
+        _ => true,
+    });
+
+    if new_var_type.types.is_empty() {
+        return get_nothing();
+    }
+
+    new_var_type
+}
+
+// Only removes strings already known to match the same pattern
+fn subtract_string_with_pattern(existing_var_type: &TUnion, pattern: &String) -> TUnion {
+    let mut new_var_type = existing_var_type.clone();
+
+    new_var_type.types.retain(|atomic| match atomic {
+        TAtomic::TStringWithPattern {
+            pattern: existing_pattern,
+        } => existing_pattern != pattern,
+        _ => true,
+    });
+
+    if new_var_type.types.is_empty() {
+        return get_nothing();
+    }
+
+    new_var_type
+}
+
 fn subtract_int(
     assertion: &Assertion,
     existing_var_type: &TUnion,
@@ -1504,6 +1572,7 @@ fn reconcile_falsy(
                     type_param: get_nothing(),
                     known_items: None,
                     non_empty: false,
+                    is_tuple: false,
                     known_count: None,
                 };
                 acceptable_types.push(new_atomic);
@@ -1622,6 +1691,7 @@ fn reconcile_empty_countable(
                     type_param: get_nothing(),
                     known_items: None,
                     non_empty: false,
+                    is_tuple: false,
                     known_count: None,
                 };
                 acceptable_types.push(new_atomic);
@@ -1640,7 +1710,15 @@ fn reconcile_empty_countable(
                 };
                 acceptable_types.push(new_atomic);
             }
+        } else if let TAtomic::TKeyset { .. } = atomic {
+            did_remove_type = true;
+
+            acceptable_types.push(TAtomic::TKeyset {
+                type_param: get_nothing(),
+            });
         } else {
+            // we can't tell whether other containers are empty
+            did_remove_type = true;
             acceptable_types.push(atomic);
         }
     }
@@ -1693,10 +1771,12 @@ fn reconcile_not_exactly_countable(
             } else if !atomic.is_falsy() {
                 did_remove_type = true;
             }
-        } else if let TAtomic::TDict { .. } = atomic {
+        } else if let TAtomic::TDict { .. } | TAtomic::TKeyset { .. } = atomic {
             if !atomic.is_falsy() {
                 did_remove_type = true;
             }
+        } else {
+            did_remove_type = true;
         }
 
         acceptable_types.push(atomic);
@@ -148,9 +148,49 @@ pub struct ScopeContext {
 
     pub allow_taints: bool,
 
+    /**
+     * Functions, classes and constants that function_exists() and friends have
+     * asserted exist, even though Hakana can't see them. Only these existence checks
+     * are modelled: Hack definitions are always top-level, so can't be conditional
+     * themselves, and branches gated on platform constants (e.g. `if (IS_LINUX)`)
+     * still report unknown symbols.
+     */
+    pub asserted_existing_symbols: FxHashSet<String>,
+
     pub inside_async: bool,
 
+    /**
+     * Whether or not we're inside a finally block
+     */
+    pub inside_finally: bool,
+
+    /**
+     * Whether or not we're inside an operand that's only evaluated conditionally, e.g.
+     * the right side of && or a ternary branch
+     */
+    pub inside_conditional_operand: bool,
+
     pub for_loop_init_bounds: Option<(usize, usize)>,
+
+    /**
+     * foreach value variables that were reassigned inside the loop body,
+     * mapped to the line of that reassignment
+     */
+    pub reassigned_foreach_value_vars: FxHashMap<String, usize>,
+
+    /**
+     * Variables that are pure copies of other variables, mapped to the variable at the
+     * root of their set of aliases. Every alias points directly at its root, so this is
+     * a union-find whose paths are always compressed.
+     */
+    pub var_aliases: FxHashMap<String, String>,
+
+    /**
+     * Calls to pure functions and static methods made so far, keyed by a hash of the
+     * callee, its arguments and the assignments of the variables they read, mapped to
+     * the position of the first such call
+     */
+    pub pure_call_positions: FxHashMap<u64, Pos>,
 }
 
 impl ScopeContext {
@@ -187,8 +227,14 @@ impl ScopeContext {
             calling_closure_id: None,
             parent_conflicting_clause_vars: FxHashSet::default(),
             allow_taints: true,
+            asserted_existing_symbols: FxHashSet::default(),
             inside_async: false,
+            inside_finally: false,
+            inside_conditional_operand: false,
             for_loop_init_bounds: None,
+            reassigned_foreach_value_vars: FxHashMap::default(),
+            var_aliases: FxHashMap::default(),
+            pure_call_positions: FxHashMap::default(),
         }
     }
 
@@ -388,6 +434,115 @@ impl ScopeContext {
         );
         self.parent_conflicting_clause_vars
             .insert(remove_var_id.clone());
+        self.remove_var_alias(remove_var_id);
+    }
+
+    pub(crate) fn add_var_alias(&mut self, var_id: &String, source_var_id: &String) {
+        let root_var_id = self
+            .var_aliases
+            .get(source_var_id)
+            .unwrap_or(source_var_id)
+            .clone();
+
+        if &root_var_id != var_id {
+            self.var_aliases.insert(var_id.clone(), root_var_id);
+        }
+    }
+
+    // Called whenever a variable is written to, after which it no longer aliases anything
+    pub(crate) fn remove_var_alias(&mut self, var_id: &String) {
+        if self.var_aliases.is_empty() {
+            return;
+        }
+
+        self.var_aliases.remove(var_id);
+
+        // the variable's other aliases still alias each other, so one of them becomes the root
+        let mut orphaned_var_ids = self
+            .var_aliases
+            .iter()
+            .filter(|(_, root_var_id)| *root_var_id == var_id)
+            .map(|(alias_var_id, _)| alias_var_id.clone())
+            .collect::<Vec<_>>();
+
+        if orphaned_var_ids.is_empty() {
+            return;
+        }
+
+        orphaned_var_ids.sort();
+
+        let new_root_var_id = orphaned_var_ids.remove(0);
+
+        self.var_aliases.remove(&new_root_var_id);
+
+        for orphaned_var_id in orphaned_var_ids {
+            self.var_aliases
+                .insert(orphaned_var_id, new_root_var_id.clone());
+        }
+    }
+
+    // Variables that may have been written in a branch or loop body no longer reliably
+    // alias anything once control reaches the code after it
+    pub(crate) fn remove_var_aliases_for_assignments<'a>(
+        &mut self,
+        assigned_var_ids: impl IntoIterator<Item = &'a String>,
+    ) {
+        if self.var_aliases.is_empty() {
+            return;
+        }
+
+        for assigned_var_id in assigned_var_ids {
+            // e.g. $a['b'] or $a->b
+            let root_var_id = assigned_var_id
+                .split(|c| c == '[' || c == '-')
+                .next()
+                .unwrap();
+
+            self.remove_var_alias(&root_var_id.to_string());
+        }
+    }
+
+    // Keeps only the aliases that still hold at the end of a branch that started from this
+    // context
+    pub(crate) fn intersect_var_aliases(&mut self, branch_context: &ScopeContext) {
+        if self.var_aliases.is_empty() {
+            return;
+        }
+
+        let get_branch_root_var_id = |var_id: &String| {
+            branch_context
+                .var_aliases
+                .get(var_id)
+                .cloned()
+                .unwrap_or_else(|| var_id.clone())
+        };
+
+        self.var_aliases.retain(|alias_var_id, root_var_id| {
+            get_branch_root_var_id(alias_var_id) == get_branch_root_var_id(root_var_id)
+        });
+    }
+
+    pub(crate) fn get_var_aliases(&self, var_id: &String) -> Vec<String> {
+        if self.var_aliases.is_empty() {
+            return vec![];
+        }
+
+        let root_var_id = self.var_aliases.get(var_id).unwrap_or(var_id);
+
+        let mut aliases = self
+            .var_aliases
+            .iter()
+            .filter(|(alias_var_id, alias_root_var_id)| {
+                *alias_root_var_id == root_var_id && *alias_var_id != var_id
+            })
+            .map(|(alias_var_id, _)| alias_var_id.clone())
+            .collect::<Vec<_>>();
+
+        if root_var_id != var_id {
+            aliases.push(root_var_id.clone());
+        }
+
+        aliases
     }
 
     pub(crate) fn remove_descendants(
@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use hakana_reflection_info::{
+    analysis_result::{AnalysisResult, ShapeParam},
+    codebase_info::CodebaseInfo,
+    data_flow::graph::{DataFlowGraph, GraphKind},
+    functionlike_identifier::FunctionLikeIdentifier,
+    functionlike_info::FunctionLikeInfo,
+    t_atomic::{DictKey, TAtomic},
+    StrId,
+};
+use hakana_type::type_expander::{self, StaticClassType, TypeExpansionOptions};
+use oxidized::{
+    aast,
+    aast_visitor::{visit, AstParams, Node, Visitor},
+};
+use rustc_hash::FxHashMap;
+
+use crate::{scope_analyzer::ScopeAnalyzer, statements_analyzer::StatementsAnalyzer};
+
+// Records the parameters of a function or method that are typed as closed shapes with
+// string keys, along with the fields its body reads from them, so that shapes used like
+// records can be suggested for migration to value classes
+pub(crate) fn collect_shape_params(
+    statements_analyzer: &StatementsAnalyzer,
+    functionlike_id: &FunctionLikeIdentifier,
+    functionlike_storage: &FunctionLikeInfo,
+    calling_class: Option<&StrId>,
+    fb_ast: &Vec<aast::Stmt<(), ()>>,
+    analysis_result: &mut AnalysisResult,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let mut shape_params = vec![];
+
+    for param in &functionlike_storage.params {
+        let mut param_type = if let Some(signature_type) = &param.signature_type {
+            signature_type.clone()
+        } else {
+            continue;
+        };
+
+        type_expander::expand_union(
+            codebase,
+            &mut param_type,
+            &TypeExpansionOptions {
+                self_class: calling_class,
+                static_class_type: if let Some(calling_class) = calling_class {
+                    StaticClassType::Name(calling_class)
+                } else {
+                    StaticClassType::None
+                },
+                file_path: Some(statements_analyzer.get_file_path()),
+                ..Default::default()
+            },
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+        );
+
+        if !param_type.is_single() {
+            continue;
+        }
+
+        if let TAtomic::TDict {
+            known_items: Some(known_items),
+            params: None,
+            ..
+        } = param_type.get_single()
+        {
+            let mut fields = BTreeMap::new();
+
+            for (key, (possibly_undefined, field_type)) in known_items {
+                if let DictKey::String(key) = key {
+                    fields.insert(
+                        key.clone(),
+                        (
+                            *possibly_undefined,
+                            field_type.get_id(Some(&codebase.interner)),
+                        ),
+                    );
+                }
+            }
+
+            if fields.len() == known_items.len() {
+                shape_params.push((param, fields));
+            }
+        }
+    }
+
+    if shape_params.is_empty() {
+        return;
+    }
+
+    let mut scanner = Scanner {
+        codebase,
+        resolved_names: statements_analyzer.get_file_analyzer().resolved_names,
+        accessed_fields: FxHashMap::default(),
+    };
+
+    for stmt in fb_ast {
+        visit(&mut scanner, &mut (), stmt).unwrap();
+    }
+
+    let file_path = statements_analyzer.get_file_path_actual();
+
+    for (param, fields) in shape_params {
+        analysis_result.shape_params.push(ShapeParam {
+            functionlike_id: *functionlike_id,
+            functionlike_name: functionlike_id.to_string(&codebase.interner),
+            param_name: param.name.clone(),
+            file_path: file_path.to_string(),
+            line: param.location.start_line,
+            fields,
+            accessed_fields: scanner
+                .accessed_fields
+                .remove(&param.name)
+                .unwrap_or_default(),
+        });
+    }
+}
+
+struct Scanner<'a> {
+    codebase: &'a CodebaseInfo,
+    resolved_names: &'a FxHashMap<usize, StrId>,
+    // string keys read from each variable, via $var['key'] or Shapes::idx($var, 'key')
+    accessed_fields: FxHashMap<String, BTreeSet<String>>,
+}
+
+impl Scanner<'_> {
+    fn add_accessed_field(&mut self, var_expr: &aast::Expr<(), ()>, key_expr: &aast::Expr<(), ()>) {
+        if let (aast::Expr_::Lvar(lid), aast::Expr_::String(key)) = (&var_expr.2, &key_expr.2) {
+            self.accessed_fields
+                .entry(lid.1 .1.clone())
+                .or_default()
+                .insert(key.to_string());
+        }
+    }
+
+    fn is_shapes_idx_call(&self, function_expr: &aast::Expr<(), ()>) -> bool {
+        if let aast::Expr_::ClassConst(boxed) = &function_expr.2 {
+            if let aast::ClassId_::CIexpr(aast::Expr(_, _, aast::Expr_::Id(id))) = &boxed.0 .2 {
+                return boxed.1 .1 == "idx"
+                    && self
+                        .resolved_names
+                        .get(&id.0.start_offset())
+                        .map_or(false, |class_name| {
+                            self.codebase.interner.lookup(class_name) == "HH\\Shapes"
+                        });
+            }
+        }
+
+        false
+    }
+}
+
+impl<'ast> Visitor<'ast> for Scanner<'_> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_expr(&mut self, c: &mut (), expr: &aast::Expr<(), ()>) -> Result<(), ()> {
+        match &expr.2 {
+            aast::Expr_::ArrayGet(boxed) => {
+                if let Some(key_expr) = &boxed.1 {
+                    self.add_accessed_field(&boxed.0, key_expr);
+                }
+            }
+            aast::Expr_::Call(boxed) => {
+                if boxed.2.len() >= 2 && self.is_shapes_idx_call(&boxed.0) {
+                    self.add_accessed_field(&boxed.2[0].1, &boxed.2[1].1);
+                }
+            }
+            _ => {}
+        }
+
+        expr.recurse(c, self)
+    }
+}
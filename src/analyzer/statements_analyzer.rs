@@ -102,6 +102,7 @@ impl<'a> StatementsAnalyzer<'a> {
     ) -> AssertionContext {
         AssertionContext {
             file_source: self.get_file_analyzer().get_file_source(),
+            config: self.get_config(),
             resolved_names: self.get_file_analyzer().resolved_names,
             codebase: Some(self.get_codebase()),
             this_class_name,
@@ -1,4 +1,4 @@
-use super::{control_analyzer::BreakContext, loop_analyzer};
+use super::{control_analyzer::BreakContext, loop_::foreach_mutation_visitor, loop_analyzer};
 use crate::{
     expr::{
         binop::assignment_analyzer, expression_identifier,
@@ -86,6 +86,21 @@ pub(crate) fn analyze(
         None
     };
 
+    let is_mutable_collection = if let Some(iterator_type) = &iterator_type {
+        iterator_type.types.iter().all(|atomic| {
+            if let TAtomic::TNamedObject { name, .. } = atomic {
+                matches!(
+                    statements_analyzer.get_codebase().interner.lookup(name),
+                    "HH\\Vector" | "HH\\Map" | "HH\\Set"
+                )
+            } else {
+                false
+            }
+        })
+    } else {
+        false
+    };
+
     if let Some(iterator_type) = iterator_type {
         let result = check_iterator_type(
             statements_analyzer,
@@ -146,6 +161,39 @@ pub(crate) fn analyze(
 
     foreach_context.for_loop_init_bounds = None;
 
+    let key_var_id = match stmt.1 {
+        aast::AsExpr::AsKv(key_expr, _) | aast::AsExpr::AwaitAsKv(_, key_expr, _) => {
+            if let aast::Expr_::Lvar(lid) = &key_expr.2 {
+                Some(lid.1 .1.clone())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let value_var_id = if let aast::Expr_::Lvar(lid) = &value_expr.2 {
+        if lid.1 .1 != "$_" {
+            Some(lid.1 .1.clone())
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let foreach_mutations = foreach_mutation_visitor::get_foreach_mutations(
+        &stmt.2 .0,
+        value_var_id.as_ref(),
+        key_var_id.as_ref(),
+        var_id.as_ref(),
+        is_mutable_collection,
+        context.function_context.calling_class.as_ref(),
+        statements_analyzer.get_file_analyzer().get_file_source(),
+        statements_analyzer.get_file_analyzer().resolved_names,
+        statements_analyzer.get_codebase(),
+    );
+
     let (analysis_result, _) = loop_analyzer::analyze(
         statements_analyzer,
         &stmt.2.0,
@@ -163,6 +211,33 @@ pub(crate) fn analyze(
         return false;
     }
 
+    for mutation_pos in foreach_mutations.collection_mutation_positions {
+        tast_info.maybe_add_issue(
+            Issue::new(
+                IssueKind::IteratedCollectionMutation,
+                format!(
+                    "{} is modified while it's being iterated over",
+                    var_id.as_ref().unwrap()
+                ),
+                statements_analyzer.get_hpos(&mutation_pos),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+
+    if let (Some(value_var_id), Some(value_reassignment_pos)) =
+        (value_var_id, foreach_mutations.value_reassignment_pos)
+    {
+        context.reassigned_foreach_value_vars.insert(
+            value_var_id,
+            statements_analyzer
+                .get_hpos(&value_reassignment_pos)
+                .start_line,
+        );
+    }
+
     // todo do we need to remove the loop scope from tast_info here? unsure
 
     return true;
@@ -277,6 +277,7 @@ pub(crate) fn analyze(
             context.vars_in_scope.insert(var_id, var_type);
         }
         context.allow_taints = else_context.allow_taints;
+        context.asserted_existing_symbols = else_context.asserted_existing_symbols;
 
         // TODO handle removal of mixed issues when followed by quick assertion
     }
@@ -293,6 +294,8 @@ pub(crate) fn analyze(
         loop_scope.final_actions.extend(if_scope.final_actions);
     }
 
+    context.remove_var_aliases_for_assignments(&if_scope.possibly_assigned_var_ids);
+
     context
         .possibly_assigned_var_ids
         .extend(if_scope.possibly_assigned_var_ids);
@@ -0,0 +1,219 @@
+use hakana_reflection_info::{codebase_info::CodebaseInfo, FileSource, StrId};
+use oxidized::{
+    aast,
+    aast_visitor::{visit, AstParams, Node, Visitor},
+    ast_defs::{self, ParamKind, Pos},
+};
+use rustc_hash::FxHashMap;
+
+use crate::expr::expression_identifier;
+
+const COLLECTION_MUTATION_METHODS: [&str; 10] = [
+    "add",
+    "addAll",
+    "addAllKeysOf",
+    "append",
+    "clear",
+    "pop",
+    "remove",
+    "removeKey",
+    "set",
+    "setAll",
+];
+
+pub(crate) struct ForeachMutations {
+    pub value_reassignment_pos: Option<Pos>,
+    pub collection_mutation_positions: Vec<Pos>,
+}
+
+struct Scanner<'a> {
+    value_var_id: Option<&'a String>,
+    key_var_id: Option<&'a String>,
+    collection_var_id: Option<&'a String>,
+    is_mutable_collection: bool,
+    this_class_name: Option<&'a StrId>,
+    file_source: &'a FileSource,
+    resolved_names: &'a FxHashMap<usize, StrId>,
+    codebase: &'a CodebaseInfo,
+    mutations: ForeachMutations,
+}
+
+impl<'a> Scanner<'a> {
+    fn get_var_id(&self, expr: &aast::Expr<(), ()>) -> Option<String> {
+        expression_identifier::get_var_id(
+            expr,
+            self.this_class_name,
+            self.file_source,
+            self.resolved_names,
+            Some(self.codebase),
+        )
+    }
+
+    fn handle_assigned_expr(&mut self, expr: &aast::Expr<(), ()>) {
+        if let aast::Expr_::List(contents) = &expr.2 {
+            for list_expr in contents {
+                self.handle_assigned_expr(list_expr);
+            }
+
+            return;
+        }
+
+        if let Some(value_var_id) = self.value_var_id {
+            if let aast::Expr_::Lvar(lid) = &expr.2 {
+                if &lid.1 .1 == value_var_id && self.mutations.value_reassignment_pos.is_none() {
+                    self.mutations.value_reassignment_pos = Some(expr.1.clone());
+                }
+            }
+        }
+
+        // reassigning the collection variable itself is fine, since the loop
+        // keeps iterating over the original value
+        self.handle_collection_entry_change(expr, false);
+    }
+
+    fn handle_collection_entry_change(&mut self, expr: &aast::Expr<(), ()>, is_unset: bool) {
+        if !matches!(expr.2, aast::Expr_::ArrayGet(..)) {
+            return;
+        }
+
+        let collection_var_id = if let Some(collection_var_id) = self.collection_var_id {
+            collection_var_id
+        } else {
+            return;
+        };
+
+        let mut root_expr = expr;
+        let mut is_key_update = false;
+
+        while let aast::Expr_::ArrayGet(boxed) = &root_expr.2 {
+            // updating the entry for the current key doesn't change what's being iterated over,
+            // but unsetting it does
+            is_key_update = !is_unset
+                && if let (Some(aast::Expr(_, _, aast::Expr_::Lvar(lid))), Some(key_var_id)) =
+                    (&boxed.1, self.key_var_id)
+                {
+                    &lid.1 .1 == key_var_id
+                } else {
+                    false
+                };
+            root_expr = &boxed.0;
+        }
+
+        if is_key_update {
+            return;
+        }
+
+        if let Some(root_var_id) = self.get_var_id(root_expr) {
+            if &root_var_id == collection_var_id {
+                self.mutations
+                    .collection_mutation_positions
+                    .push(expr.1.clone());
+            }
+        }
+    }
+}
+
+impl<'ast, 'a> Visitor<'ast> for Scanner<'a> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_expr(&mut self, c: &mut (), expr: &aast::Expr<(), ()>) -> Result<(), ()> {
+        match &expr.2 {
+            aast::Expr_::Binop(boxed) => {
+                if let ast_defs::Bop::Eq(_) = boxed.0 {
+                    self.handle_assigned_expr(&boxed.1);
+                }
+            }
+            aast::Expr_::Unop(boxed) => match boxed.0 {
+                ast_defs::Uop::Udecr
+                | ast_defs::Uop::Uincr
+                | ast_defs::Uop::Updecr
+                | ast_defs::Uop::Upincr => {
+                    self.handle_assigned_expr(&boxed.1);
+                }
+                _ => {}
+            },
+            aast::Expr_::Call(boxed) => {
+                for arg_expr in &boxed.2 {
+                    if let ParamKind::Pinout(..) = arg_expr.0 {
+                        self.handle_assigned_expr(&arg_expr.1);
+                    }
+                }
+
+                match &boxed.0 .2 {
+                    aast::Expr_::Id(id) => {
+                        if id.1 == "unset" {
+                            for arg_expr in &boxed.2 {
+                                self.handle_collection_entry_change(&arg_expr.1, true);
+                            }
+                        }
+                    }
+                    aast::Expr_::ObjGet(obj_get) => {
+                        if let (ast_defs::PropOrMethod::IsMethod, aast::Expr_::Id(method_id)) =
+                            (&obj_get.3, &obj_get.1 .2)
+                        {
+                            if self.is_mutable_collection
+                                && COLLECTION_MUTATION_METHODS.contains(&method_id.1.as_str())
+                            {
+                                if let (Some(lhs_var_id), Some(collection_var_id)) =
+                                    (self.get_var_id(&obj_get.0), self.collection_var_id)
+                                {
+                                    if &lhs_var_id == collection_var_id {
+                                        self.mutations
+                                            .collection_mutation_positions
+                                            .push(expr.1.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            aast::Expr_::Lfun(_) | aast::Expr_::Efun(_) => {
+                return Result::Ok(());
+            }
+            _ => {}
+        }
+
+        expr.recurse(c, self)
+    }
+}
+
+// Finds places in a foreach body that reassign the value variable,
+// or that change the collection being iterated over
+pub(crate) fn get_foreach_mutations(
+    stmts: &Vec<aast::Stmt<(), ()>>,
+    value_var_id: Option<&String>,
+    key_var_id: Option<&String>,
+    collection_var_id: Option<&String>,
+    is_mutable_collection: bool,
+    this_class_name: Option<&StrId>,
+    file_source: &FileSource,
+    resolved_names: &FxHashMap<usize, StrId>,
+    codebase: &CodebaseInfo,
+) -> ForeachMutations {
+    let mut scanner = Scanner {
+        value_var_id,
+        key_var_id,
+        collection_var_id,
+        is_mutable_collection,
+        this_class_name,
+        file_source,
+        resolved_names,
+        codebase,
+        mutations: ForeachMutations {
+            value_reassignment_pos: None,
+            collection_mutation_positions: vec![],
+        },
+    };
+
+    for stmt in stmts {
+        visit(&mut scanner, &mut (), stmt).unwrap();
+    }
+
+    scanner.mutations
+}
@@ -1,2 +1,3 @@
 pub(crate) mod assignment_map_visitor;
+pub(crate) mod foreach_mutation_visitor;
 pub(crate) mod tast_cleaner;
@@ -41,6 +41,11 @@ pub(crate) fn analyze<'a>(
         loop_context.function_context.calling_class.clone(),
     );
 
+    // the body may run any number of times, so aliases of anything it writes can't be relied
+    // on inside or after the loop
+    loop_context.remove_var_aliases_for_assignments(assignment_map.keys());
+    loop_parent_context.remove_var_aliases_for_assignments(assignment_map.keys());
+
     let assignment_depth = if let Some(first_var_id) = first_var_id {
         get_assignment_map_depth(&first_var_id, &mut assignment_map.clone())
     } else {
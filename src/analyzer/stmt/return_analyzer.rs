@@ -23,8 +23,8 @@ use oxidized::{aast, aast::Pos};
 use rustc_hash::FxHashSet;
 
 use crate::{
-    expression_analyzer, scope_analyzer::ScopeAnalyzer, statements_analyzer::StatementsAnalyzer,
-    typed_ast::TastInfo,
+    expr::nullable_return_analyzer, expr::readonly_analyzer, expression_analyzer,
+    scope_analyzer::ScopeAnalyzer, statements_analyzer::StatementsAnalyzer, typed_ast::TastInfo,
 };
 
 pub(crate) fn analyze(
@@ -48,6 +48,9 @@ pub(crate) fn analyze(
         );
         context.inside_return = false;
 
+        // whoever calls this function is now responsible for checking the value
+        nullable_return_analyzer::mark_checked(return_expr, tast_info);
+
         if let Some(mut inferred_return_type) = tast_info.get_expr_type(&return_expr.1).cloned() {
             if inferred_return_type.is_nothing() {
                 tast_info.maybe_add_issue(
@@ -87,6 +90,20 @@ pub(crate) fn analyze(
         return;
     };
 
+    if let Some(return_expr) = return_expr {
+        if !functionlike_storage.readonly_return {
+            readonly_analyzer::check_mutable_use(
+                statements_analyzer,
+                &inferred_return_type,
+                "The return type isn't declared readonly, so a readonly value can't be returned"
+                    .to_string(),
+                &return_expr.1,
+                tast_info,
+                context,
+            );
+        }
+    }
+
     handle_inout_at_return(
         functionlike_storage,
         statements_analyzer,
@@ -1,6 +1,11 @@
-use hakana_reflection_info::{codebase_info::CodebaseInfo, StrId};
+use hakana_reflection_info::{
+    codebase_info::{symbols::SymbolKind, CodebaseInfo},
+    issue::{Issue, IssueKind},
+    t_atomic::TAtomic,
+    StrId,
+};
 use hakana_type::{combine_union_types, get_mixed_any};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use oxidized::{
     aast,
     aast::Pos,
@@ -242,6 +247,10 @@ pub(crate) fn analyze(
         }
     }
 
+    if stmt.2.is_none() {
+        check_enum_exhaustiveness(statements_analyzer, stmt.0, stmt.1, pos, tast_info, context);
+    }
+
     tast_info
         .fully_matched_switch_offsets
         .insert(pos.start_offset());
@@ -252,6 +261,106 @@ pub(crate) fn analyze(
     context.has_returned = all_options_returned && has_default;
 }
 
+// Switches over enums without a default arm should handle every enum case
+fn check_enum_exhaustiveness(
+    statements_analyzer: &StatementsAnalyzer,
+    switch_condition: &aast::Expr<(), ()>,
+    cases: &Vec<aast::Case<(), ()>>,
+    pos: &Pos,
+    tast_info: &mut TastInfo,
+    context: &ScopeContext,
+) {
+    let codebase = statements_analyzer.get_codebase();
+
+    let condition_type = if let Some(condition_type) = tast_info.get_expr_type(&switch_condition.1)
+    {
+        condition_type
+    } else {
+        return;
+    };
+
+    let mut unhandled_cases = IndexSet::new();
+
+    for atomic in &condition_type.types {
+        match atomic {
+            TAtomic::TEnum { name, .. } => {
+                if let Some(enum_storage) = codebase.classlike_infos.get(name) {
+                    if !matches!(enum_storage.kind, SymbolKind::Enum) {
+                        return;
+                    }
+
+                    for (member_name, _) in &enum_storage.constants {
+                        unhandled_cases.insert((*name, *member_name));
+                    }
+                } else {
+                    return;
+                }
+            }
+            TAtomic::TEnumLiteralCase {
+                enum_name,
+                member_name,
+                ..
+            } => {
+                unhandled_cases.insert((*enum_name, *member_name));
+            }
+            _ => {
+                return;
+            }
+        }
+    }
+
+    for case in cases {
+        let case_type = if let Some(case_type) = tast_info.get_expr_type(&case.0 .1) {
+            case_type
+        } else {
+            return;
+        };
+
+        for atomic in &case_type.types {
+            if let TAtomic::TEnumLiteralCase {
+                enum_name,
+                member_name,
+                ..
+            } = atomic
+            {
+                unhandled_cases.remove(&(*enum_name, *member_name));
+            } else {
+                // we can't tell which case a non-literal value matches
+                return;
+            }
+        }
+    }
+
+    if unhandled_cases.is_empty() {
+        return;
+    }
+
+    let description = format!(
+        "Switch over {} does not handle {}",
+        condition_type.get_id(Some(&codebase.interner)),
+        unhandled_cases
+            .iter()
+            .map(|(enum_name, member_name)| format!(
+                "{}::{}",
+                codebase.interner.lookup(enum_name),
+                codebase.interner.lookup(member_name)
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    tast_info.maybe_add_issue(
+        Issue::new(
+            IssueKind::NonExhaustiveSwitch,
+            description,
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
 fn update_case_exit_map(
     codebase: &CodebaseInfo,
     case_stmts: &Vec<aast::Stmt<(), ()>>,
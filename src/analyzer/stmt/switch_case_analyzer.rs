@@ -433,6 +433,14 @@ pub(crate) fn analyze_case(
     tast_info.expr_types = old_node_data;
 
     if !matches!(case_exit_type, ControlAction::Return) {
+        // so that reads after the switch of vars only some cases assign can be told apart
+        // from reads of vars that are never assigned
+        context
+            .possibly_assigned_var_ids
+            .extend(case_context.possibly_assigned_var_ids.iter().cloned());
+
+        context.intersect_var_aliases(&case_context);
+
         if !handle_non_returning_case(
             statements_analyzer,
             switch_var_id,
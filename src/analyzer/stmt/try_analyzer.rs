@@ -146,6 +146,9 @@ pub(crate) fn analyze(
         let mut catch_context = original_context.clone();
         catch_context.has_returned = false;
 
+        // the try block may have been interrupted after any of its writes
+        catch_context.remove_var_aliases_for_assignments(newly_assigned_var_ids.keys());
+
         for (var_id, after_try_type) in catch_context.vars_in_scope.clone() {
             if let Some(before_try_type) = old_context.vars_in_scope.get(&var_id) {
                 catch_context.vars_in_scope.insert(
@@ -224,6 +227,8 @@ pub(crate) fn analyze(
             definitely_newly_assigned_var_ids
                 .retain(|var_id, _| new_catch_assigned_var_ids.contains_key(var_id));
 
+            context.intersect_var_aliases(&catch_context);
+
             for (var_id, var_type) in &catch_context.vars_in_scope {
                 if try_block_control_actions.len() == 1
                     && matches!(
@@ -288,8 +293,13 @@ pub(crate) fn analyze(
 
             finally_context.vars_in_scope = finally_scope.vars_in_scope;
 
+            let was_inside_finally = context.inside_finally;
+            context.inside_finally = true;
+
             statements_analyzer.analyze(&stmt.2.0, tast_info, context, loop_scope);
 
+            context.inside_finally = was_inside_finally;
+
             finally_has_returned = finally_context.has_returned;
 
             for (var_id, _) in &finally_context.assigned_var_ids {
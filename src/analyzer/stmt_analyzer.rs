@@ -3,6 +3,7 @@ use rustc_hash::FxHashMap;
 
 use crate::custom_hook::AfterStmtAnalysisData;
 use crate::expr::binop::assignment_analyzer;
+use crate::expr::nullable_return_analyzer;
 use crate::expression_analyzer::{self};
 
 use crate::scope_analyzer::ScopeAnalyzer;
@@ -16,6 +17,8 @@ use crate::stmt::{
 use crate::typed_ast::TastInfo;
 use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
+use hakana_type::{get_mixed_any, type_combiner};
 use oxidized::{aast, ast_defs};
 
 pub(crate) fn analyze(
@@ -56,6 +59,9 @@ pub(crate) fn analyze(
             ) {
                 return false;
             }
+
+            // a discarded result isn't used
+            nullable_return_analyzer::mark_checked(&boxed, tast_info);
         }
         aast::Stmt_::Return(_) => {
             return_analyzer::analyze(stmt, statements_analyzer, tast_info, context);
@@ -244,6 +250,9 @@ pub(crate) fn analyze(
     true
 }
 
+// Concurrent blocks start every awaitable in them before any of their results are
+// assigned, so all the awaited expressions are analyzed before any of the assignments.
+// Awaiting lets other code run, which can have any effect.
 fn analyze_awaitall(
     boxed: (
         &Vec<(Option<oxidized::tast::Lid>, aast::Expr<(), ()>)>,
@@ -255,33 +264,23 @@ fn analyze_awaitall(
     stmt: &aast::Stmt<(), ()>,
     loop_scope: &mut Option<LoopScope>,
 ) {
-    for (assignment_id, expr) in boxed.0 {
+    expression_analyzer::check_await_position(statements_analyzer, context, tast_info, &stmt.0);
+
+    for (_, expr) in boxed.0 {
         expression_analyzer::analyze(statements_analyzer, expr, tast_info, context, &mut None);
+    }
 
-        if let Some(assignment_id) = assignment_id {
-            let mut assignment_type = None;
+    tast_info
+        .expr_effects
+        .insert((stmt.0.start_offset(), stmt.0.end_offset()), 7);
 
-            if let Some(t) = tast_info.get_expr_type(expr.pos()) {
-                let parent_nodes = t.parent_nodes.clone();
-                if t.is_single() {
-                    let inner = t.get_single();
-                    if let TAtomic::TNamedObject {
-                        name,
-                        type_params: Some(type_params),
-                        ..
-                    } = inner
-                    {
-                        if statements_analyzer.get_codebase().interner.lookup(name)
-                            == "HH\\Awaitable"
-                        {
-                            let mut new = type_params.get(0).unwrap().clone();
+    context.remove_mutable_object_vars();
 
-                            new.parent_nodes = parent_nodes;
-                            assignment_type = Some(new)
-                        }
-                    }
-                }
-            }
+    for (assignment_id, expr) in boxed.0 {
+        if let Some(assignment_id) = assignment_id {
+            let assignment_type = tast_info
+                .get_expr_type(expr.pos())
+                .map(|awaitable_type| get_awaited_type(statements_analyzer, awaitable_type));
 
             assignment_analyzer::analyze(
                 statements_analyzer,
@@ -308,3 +307,37 @@ fn analyze_awaitall(
         analyze(statements_analyzer, &stmt, tast_info, context, loop_scope);
     }
 }
+
+// The type each member of a (possibly nullable) Awaitable union resolves to when awaited
+fn get_awaited_type(statements_analyzer: &StatementsAnalyzer, awaitable_type: &TUnion) -> TUnion {
+    let codebase = statements_analyzer.get_codebase();
+
+    let mut awaited_types = vec![];
+
+    for atomic_type in &awaitable_type.types {
+        match atomic_type {
+            TAtomic::TNamedObject {
+                name,
+                type_params: Some(type_params),
+                ..
+            } if type_params.len() == 1 && codebase.interner.lookup(name) == "HH\\Awaitable" => {
+                awaited_types.extend(type_params[0].types.clone());
+            }
+            TAtomic::TNull => {
+                awaited_types.push(TAtomic::TNull);
+            }
+            _ => {
+                return get_mixed_any();
+            }
+        }
+    }
+
+    if awaited_types.is_empty() {
+        return get_mixed_any();
+    }
+
+    let mut awaited_type = TUnion::new(type_combiner::combine(awaited_types, codebase, false));
+    awaited_type.parent_nodes = awaitable_type.parent_nodes.clone();
+
+    awaited_type
+}
@@ -1,4 +1,9 @@
-use crate::{config::Config, scope_context::CaseScope};
+use crate::{
+    config::{Config, GeneratedIssuePolicy},
+    expr::nullable_return_analyzer::UncheckedNullableReturn,
+    scope_context::CaseScope,
+};
+use hakana_algebra::Clause;
 use hakana_reflection_info::analysis_result::Replacement;
 use hakana_reflection_info::code_location::StmtStart;
 use hakana_reflection_info::FileSource;
@@ -6,7 +11,10 @@ use hakana_reflection_info::{
     assertion::Assertion,
     data_flow::graph::{DataFlowGraph, GraphKind, WholeProgramKind},
     functionlike_info::FunctionLikeInfo,
-    issue::{get_issue_from_comment, Issue, IssueKind},
+    issue::{
+        get_issue_from_comment, get_suppression_expiry, suppression_has_expired, Issue, IssueKind,
+        IssueSeverity,
+    },
     symbol_references::SymbolReferences,
     t_union::TUnion,
 };
@@ -14,17 +22,19 @@ use oxidized::{ast_defs::Pos, prim_defs::Comment};
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{collections::BTreeMap, rc::Rc};
 
-pub(crate) const PURE: u8 = 0b00000000;
-pub(crate) const READ_PROPS: u8 = 0b00000001;
-pub(crate) const READ_GLOBALS: u8 = 0b00000010;
-pub(crate) const WRITE_PROPS: u8 = 0b00000100;
-pub(crate) const WRITE_GLOBALS: u8 = 0b0001000;
-pub(crate) const IMPURE: u8 = READ_PROPS | READ_GLOBALS | WRITE_PROPS | WRITE_GLOBALS;
+pub const PURE: u8 = 0b00000000;
+pub const READ_PROPS: u8 = 0b00000001;
+pub const READ_GLOBALS: u8 = 0b00000010;
+pub const WRITE_PROPS: u8 = 0b00000100;
+pub const WRITE_GLOBALS: u8 = 0b0001000;
+pub const IMPURE: u8 = READ_PROPS | READ_GLOBALS | WRITE_PROPS | WRITE_GLOBALS;
 
 pub struct TastInfo {
     pub expr_types: FxHashMap<(usize, usize), Rc<TUnion>>,
     pub if_true_assertions: FxHashMap<(usize, usize), FxHashMap<String, Vec<Assertion>>>,
     pub if_false_assertions: FxHashMap<(usize, usize), FxHashMap<String, Vec<Assertion>>>,
+    // the symbol that a function_exists() or class_exists() call checks for, by the call's offsets
+    pub symbol_existence_checks: FxHashMap<(usize, usize), String>,
     pub data_flow_graph: DataFlowGraph,
     pub case_scopes: Vec<CaseScope>,
     pub issues_to_emit: Vec<Issue>,
@@ -44,8 +54,21 @@ pub struct TastInfo {
     hh_fixmes: BTreeMap<isize, BTreeMap<isize, Pos>>,
     pub hakana_fixme_or_ignores: BTreeMap<usize, Vec<(IssueKind, (usize, usize, u64))>>,
     pub matched_ignore_positions: FxHashSet<(usize, usize)>,
+    // whether issues are in generated code, which can have its own issue policy
+    pub in_generated_code: bool,
+    // the config's issue profile for the file, which decides how issues are reported
+    pub issue_profile: Option<String>,
+    // the command that regenerates the file, which issues point to instead of the file
+    pub regeneration_command: Option<String>,
+    // keyed by the offsets of the call that produced the result
+    pub unchecked_nullable_returns: FxHashMap<(usize, usize), UncheckedNullableReturn>,
+    // generated formulae, keyed by the conditional's offsets and the ids passed to
+    // formula_generator::get_formula, along with a hash of the types they were generated from
+    pub(crate) formula_cache: FxHashMap<FormulaCacheKey, (u64, Result<Vec<Clause>, String>)>,
 }
 
+pub(crate) type FormulaCacheKey = ((usize, usize), (usize, usize), (usize, usize), bool, bool);
+
 impl TastInfo {
     pub(crate) fn new(
         data_flow_graph: DataFlowGraph,
@@ -68,6 +91,7 @@ impl TastInfo {
             closure_spans: vec![],
             if_true_assertions: FxHashMap::default(),
             if_false_assertions: FxHashMap::default(),
+            symbol_existence_checks: FxHashMap::default(),
             replacements: BTreeMap::new(),
             current_stmt_offset,
             hh_fixmes: file_source.hh_fixmes.clone(),
@@ -79,6 +103,11 @@ impl TastInfo {
             expr_fixme_positions: FxHashMap::default(),
             matched_ignore_positions: FxHashSet::default(),
             issue_counts: FxHashMap::default(),
+            in_generated_code: file_source.is_generated,
+            issue_profile: file_source.issue_profile.clone(),
+            regeneration_command: file_source.regeneration_command.clone(),
+            unchecked_nullable_returns: FxHashMap::default(),
+            formula_cache: FxHashMap::default(),
         }
     }
 
@@ -97,6 +126,26 @@ impl TastInfo {
             return;
         }
 
+        if self.in_generated_code {
+            match config.get_generated_issue_policy(&issue.kind) {
+                Some(GeneratedIssuePolicy::Drop) => return,
+                Some(GeneratedIssuePolicy::Downgrade) => issue.severity = IssueSeverity::Info,
+                None => {}
+            }
+        }
+
+        if let Some(issue_profile) = self
+            .issue_profile
+            .as_ref()
+            .and_then(|profile_name| config.issue_profiles.get(profile_name))
+        {
+            if let Some(severity) = issue_profile.get_issue_severity(&issue) {
+                issue.severity = severity;
+            } else {
+                return;
+            }
+        }
+
         issue.pos.insertion_start = if let Some(expr_fixme_position) = self
             .expr_fixme_positions
             .get(&(issue.pos.start_offset, issue.pos.end_offset))
@@ -116,6 +165,17 @@ impl TastInfo {
             issue.fixme_added = self.add_issue_fixme(&issue);
         }
 
+        if issue.help_uri.is_none() {
+            issue.help_uri = config.get_issue_help_uri(&issue.kind);
+        }
+
+        if let Some(regeneration_command) = &self.regeneration_command {
+            issue.regeneration_hint = Some(format!(
+                "This file is generated, so fix the template or generator and re-run {}",
+                regeneration_command
+            ));
+        }
+
         self.add_issue(issue);
     }
 
@@ -491,6 +551,10 @@ fn get_hakana_fixmes_and_ignores(
                 if let Some(Ok(issue_kind)) =
                     get_issue_from_comment(trimmed_text, all_custom_issues)
                 {
+                    if get_suppression_expiry(trimmed_text).map_or(false, suppression_has_expired) {
+                        continue;
+                    }
+
                     hakana_fixme_or_ignores
                         .entry(pos.line())
                         .or_insert_with(Vec::new)
@@ -0,0 +1,106 @@
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::analysis_result::AnalysisResult;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// Lifts the issue filters from config so that an analysis with it reports everything
+// either config would, and returns the current and other configs to apply afterwards
+pub(crate) fn prepare_config_comparison(
+    config: &mut Config,
+    other_config: Config,
+) -> (Config, Config) {
+    let mut current_config = Config::new(config.root_dir.clone(), config.all_custom_issues.clone());
+    current_config.allowed_issues = config.allowed_issues.take();
+    current_config.ignore_mixed_issues = config.ignore_mixed_issues;
+    current_config.ignore_issue_files = std::mem::take(&mut config.ignore_issue_files);
+    current_config.ignore_all_issues_in_files =
+        std::mem::take(&mut config.ignore_all_issues_in_files);
+    current_config.ignore_files = config.ignore_files.clone();
+    current_config.issue_profiles = config.issue_profiles.clone();
+    current_config.issue_profile_files = std::mem::take(&mut config.issue_profile_files);
+
+    config.ignore_mixed_issues = false;
+    config
+        .ignore_files
+        .retain(|ignore_file| other_config.ignore_files.contains(ignore_file));
+
+    (current_config, other_config)
+}
+
+pub(crate) fn print_config_diff(
+    analysis_result: &AnalysisResult,
+    current_config: &Config,
+    other_config: &Config,
+) {
+    println!(
+        "\n{}",
+        get_config_diff_text(analysis_result, current_config, other_config)
+    );
+}
+
+// How the number of issues reported for each issue kind, and in each directory, would
+// change when switching from current_config to other_config. Expects a result from
+// analyzing with neither config's issue filters applied.
+pub(crate) fn get_config_diff_text(
+    analysis_result: &AnalysisResult,
+    current_config: &Config,
+    other_config: &Config,
+) -> String {
+    let mut counts_by_kind = BTreeMap::new();
+    let mut counts_by_dir = BTreeMap::new();
+    let mut total_counts = (0, 0);
+
+    for (file_path, issues) in &analysis_result.emitted_issues {
+        let dir = Path::new(file_path)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or(".".to_string());
+
+        for issue in issues {
+            let counts = (
+                current_config.reports_issue(issue, file_path) as usize,
+                other_config.reports_issue(issue, file_path) as usize,
+            );
+
+            for (current_count, other_count) in [
+                counts_by_kind
+                    .entry(issue.kind.to_string())
+                    .or_insert((0, 0)),
+                counts_by_dir.entry(dir.clone()).or_insert((0, 0)),
+                &mut total_counts,
+            ] {
+                *current_count += counts.0;
+                *other_count += counts.1;
+            }
+        }
+    }
+
+    format!(
+        "{}\n\n{}\n\nTotal issues: {} now, {} with the other config ({:+})",
+        get_count_changes_text("Issue kind", &counts_by_kind),
+        get_count_changes_text("Directory", &counts_by_dir),
+        total_counts.0,
+        total_counts.1,
+        total_counts.1 as isize - total_counts.0 as isize
+    )
+}
+
+// Only rows whose count changes are included
+fn get_count_changes_text(heading: &str, counts: &BTreeMap<String, (usize, usize)>) -> String {
+    let mut lines = vec![format!("{}\tcurrent\tother\tchange", heading)];
+
+    for (name, (current_count, other_count)) in counts {
+        if current_count != other_count {
+            lines.push(format!(
+                "{}\t{}\t{}\t{:+}",
+                name,
+                current_count,
+                other_count,
+                *other_count as isize - *current_count as isize
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,270 @@
+use hakana_reflection_info::analysis_result::TypedAstNode;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+const TYPE_AT_POS_BATCH_SIZE: usize = 1000;
+
+#[derive(Serialize)]
+pub(crate) struct ConformanceReport {
+    pub file: String,
+    pub checked_count: usize,
+    // expressions the typechecker had no type for
+    pub unchecked_count: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Divergence {
+    pub kind: &'static str,
+    pub line: usize,
+    pub column: usize,
+    pub hakana_type: String,
+    pub hh_type: String,
+    // hakana's type is narrower than the typechecker's (e.g. a literal int where it
+    // infers int), which is expected, rather than a real mismatch
+    pub is_narrower: bool,
+}
+
+// Compares the types Hakana infers for the expressions in a file with the types the Hack
+// typechecker reports for them via `hh_client --json --type-at-pos-batch`. hh_client
+// returns the type of the innermost expression at a position, so only the innermost
+// expression starting at each position is checked.
+pub(crate) fn get_conformance_report(
+    root_dir: &str,
+    file_path: &str,
+    typed_ast: &Vec<TypedAstNode>,
+    hh_client_path: &str,
+) -> Result<ConformanceReport, String> {
+    let mut innermost_nodes = BTreeMap::new();
+
+    for node in typed_ast {
+        if node.inferred_type.is_none() {
+            continue;
+        }
+
+        let existing_node = innermost_nodes
+            .entry((node.start_line, node.start_column))
+            .or_insert(node);
+
+        if node.end_offset < existing_node.end_offset {
+            *existing_node = node;
+        }
+    }
+
+    let hh_types = get_hh_types_at_positions(
+        root_dir,
+        file_path,
+        innermost_nodes.keys().copied().collect(),
+        hh_client_path,
+    )?;
+
+    let mut report = ConformanceReport {
+        file: file_path.to_string(),
+        checked_count: 0,
+        unchecked_count: 0,
+        divergences: vec![],
+    };
+
+    for ((line, column), node) in innermost_nodes {
+        let hh_type = if let Some(hh_type) = hh_types.get(&(line, column)) {
+            hh_type.clone()
+        } else {
+            report.unchecked_count += 1;
+            continue;
+        };
+
+        report.checked_count += 1;
+
+        let hakana_type = node.inferred_type.clone().unwrap();
+
+        let hakana_type_parts = get_normalized_type_parts(&hakana_type);
+        let hh_type_parts = get_normalized_type_parts(&hh_type);
+
+        if hakana_type_parts != hh_type_parts {
+            report.divergences.push(Divergence {
+                kind: node.kind,
+                line,
+                column,
+                is_narrower: hakana_type_parts.is_subset(&hh_type_parts),
+                hakana_type,
+                hh_type,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+// hh_client is run once per chunk of positions rather than once per position, with
+// chunks small enough to keep the command line a reasonable length
+fn get_hh_types_at_positions(
+    root_dir: &str,
+    file_path: &str,
+    positions: Vec<(usize, usize)>,
+    hh_client_path: &str,
+) -> Result<BTreeMap<(usize, usize), String>, String> {
+    let mut hh_types = BTreeMap::new();
+
+    for chunk in positions.chunks(TYPE_AT_POS_BATCH_SIZE) {
+        let output = Command::new(hh_client_path)
+            .arg("--json")
+            .arg("--type-at-pos-batch")
+            .args(
+                chunk
+                    .iter()
+                    .map(|(line, column)| format!("{}:{}:{}", file_path, line, column)),
+            )
+            .current_dir(root_dir)
+            .output()
+            .map_err(|e| format!("Could not run {}: {}", hh_client_path, e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                hh_client_path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        hh_types.extend(
+            parse_type_at_pos_batch_output(&String::from_utf8_lossy(&output.stdout))
+                .map_err(|e| format!("Could not parse {} output: {}", hh_client_path, e))?,
+        );
+    }
+
+    Ok(hh_types)
+}
+
+// Each line of output is a JSON object with the position that was queried and the type
+// found there, which is null when the typechecker has no type for it
+fn parse_type_at_pos_batch_output(
+    output: &str,
+) -> Result<BTreeMap<(usize, usize), String>, serde_json::Error> {
+    let mut hh_types = BTreeMap::new();
+
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let response: serde_json::Value = serde_json::from_str(line)?;
+
+        let position = response.get("position");
+
+        let (line, column) = if let (Some(line), Some(column)) = (
+            position
+                .and_then(|position| position.get("line"))
+                .and_then(|line| line.as_u64()),
+            position
+                .and_then(|position| position.get("character"))
+                .and_then(|column| column.as_u64()),
+        ) {
+            (line as usize, column as usize)
+        } else {
+            continue;
+        };
+
+        if let Some(hh_type) = response.get("type").and_then(|hh_type| hh_type.as_str()) {
+            hh_types.insert((line, column), hh_type.to_string());
+        }
+    }
+
+    Ok(hh_types)
+}
+
+// The two tools print the same types differently, so types are compared as sets of
+// top-level union members, with namespaces, whitespace and literal values removed
+fn get_normalized_type_parts(type_string: &str) -> BTreeSet<String> {
+    let mut normalized = String::new();
+    let mut chars = type_string.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let at_identifier_start = !normalized
+            .chars()
+            .last()
+            .map_or(false, |last| last.is_alphanumeric() || last == '_');
+
+        if c == '\\' && at_identifier_start {
+            continue;
+        }
+
+        normalized.push(c);
+
+        // int(5) and string(foo) are literals of int and string
+        if c == '('
+            && (ends_with_word(&normalized, "int(") || ends_with_word(&normalized, "string("))
+        {
+            normalized.pop();
+
+            let mut depth = 1;
+
+            for c in chars.by_ref() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    let normalized = normalized.replace("HH\\", "");
+
+    let normalized = if normalized.starts_with('(')
+        && normalized.ends_with(')')
+        && !normalized.starts_with("(function")
+    {
+        &normalized[1..normalized.len() - 1]
+    } else {
+        &normalized
+    };
+
+    let mut parts = BTreeSet::new();
+    let mut depth = 0;
+    let mut part = String::new();
+
+    for c in normalized.chars().chain(std::iter::once('|')) {
+        match c {
+            '<' | '(' | '{' => depth += 1,
+            // not the arrow in shape fields
+            '>' if part.ends_with('=') => {}
+            '>' | ')' | '}' => depth -= 1,
+            '|' if depth == 0 => {
+                add_type_part(&mut parts, std::mem::take(&mut part));
+                continue;
+            }
+            _ => {}
+        }
+
+        part.push(c);
+    }
+
+    parts
+}
+
+fn ends_with_word(text: &str, word: &str) -> bool {
+    text.strip_suffix(word).map_or(false, |rest| {
+        !rest.chars().last().map_or(false, |last| {
+            last.is_alphanumeric() || last == '_' || last == '\\'
+        })
+    })
+}
+
+fn add_type_part(parts: &mut BTreeSet<String>, part: String) {
+    if let Some(part) = part.strip_prefix('?') {
+        parts.insert("null".to_string());
+        add_type_part(parts, part.to_string());
+    } else if part == "true" || part == "false" {
+        parts.insert("bool".to_string());
+    } else if !part.is_empty() {
+        parts.insert(part);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,62 @@
+use super::*;
+
+fn parts(types: &[&str]) -> BTreeSet<String> {
+    types.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn normalizes_literals() {
+    assert_eq!(parts(&["int"]), get_normalized_type_parts("int(5)"));
+    assert_eq!(parts(&["string"]), get_normalized_type_parts("string(foo)"));
+    assert_eq!(parts(&["bool"]), get_normalized_type_parts("true"));
+}
+
+#[test]
+fn normalizes_namespaces_and_whitespace() {
+    assert_eq!(
+        get_normalized_type_parts("vec<\\Foo\\Bar>"),
+        get_normalized_type_parts("HH\\vec<Foo\\Bar>")
+    );
+    assert_eq!(
+        get_normalized_type_parts("dict<string, int>"),
+        get_normalized_type_parts("dict<string,int>")
+    );
+}
+
+#[test]
+fn splits_top_level_unions() {
+    assert_eq!(
+        parts(&["int", "null", "vec<int|string>"]),
+        get_normalized_type_parts("(int | null | vec<int | string>)")
+    );
+    assert_eq!(
+        parts(&["null", "string"]),
+        get_normalized_type_parts("?string")
+    );
+}
+
+#[test]
+fn keeps_shape_arrows_intact() {
+    assert_eq!(
+        parts(&["shape('a'=>int)"]),
+        get_normalized_type_parts("shape('a' => int)")
+    );
+}
+
+#[test]
+fn parses_type_at_pos_batch_output() {
+    let output = concat!(
+        "{\"position\":{\"file\":\"a.hack\",\"line\":3,\"character\":5},\"type\":\"int\"}\n",
+        "{\"position\":{\"file\":\"a.hack\",\"line\":4,\"character\":9},\"type\":null}\n",
+    );
+
+    let hh_types = parse_type_at_pos_batch_output(output).unwrap();
+
+    assert_eq!(Some(&"int".to_string()), hh_types.get(&(3, 5)));
+    assert_eq!(None, hh_types.get(&(4, 9)));
+}
+
+#[test]
+fn rejects_malformed_type_at_pos_batch_output() {
+    assert!(parse_type_at_pos_batch_output("not json").is_err());
+}
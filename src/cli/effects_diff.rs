@@ -0,0 +1,264 @@
+use hakana_analyzer::typed_ast::{READ_GLOBALS, READ_PROPS, WRITE_GLOBALS, WRITE_PROPS};
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::functionlike_info::{FnEffect, FunctionLikeInfo};
+use hakana_reflection_info::method_identifier::MethodIdentifier;
+use hakana_reflection_info::Interner;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::BTreeMap;
+
+// these aren't effects the analyzer tracks, but they're diffed like them
+const NON_DETERMINISTIC: u8 = 0b00010000;
+// the function has the effects of a function passed to it
+const ARG_EFFECTS: u8 = 0b00100000;
+// closures without contexts, whose effects are only known after analysis
+const UNKNOWN_EFFECTS: u8 = 0b01000000;
+
+const EFFECT_NAMES: [(u8, &str); 7] = [
+    (READ_PROPS, "reads properties"),
+    (READ_GLOBALS, "reads globals"),
+    (WRITE_PROPS, "writes properties"),
+    (WRITE_GLOBALS, "writes globals"),
+    (NON_DETERMINISTIC, "non-deterministic"),
+    (ARG_EFFECTS, "effects of an argument"),
+    (UNKNOWN_EFFECTS, "unknown effects"),
+];
+
+struct FunctionEffects {
+    user_defined: bool,
+    // the effects from the function's own contexts
+    own_effects: u8,
+    // including the effects of everything it calls
+    effects: u8,
+    called_functions: Vec<String>,
+}
+
+// Prints the functions and methods whose effects grew between the previous codebase
+// and the current one (e.g. a function that was pure now writes globals), along with
+// the chain of calls leading to the function where the new effects were introduced
+pub(crate) fn print_effects_diff(
+    previous_codebase: &(CodebaseInfo, Interner),
+    current_codebase: &(CodebaseInfo, Interner),
+) {
+    let changes = get_effects_changes(previous_codebase, current_codebase);
+
+    if changes.is_empty() {
+        println!("\nNo functions have gained effects since the previous run");
+        return;
+    }
+
+    println!("\nFunctions that have gained effects since the previous run:");
+
+    for (name, (previous, current, chain)) in changes {
+        println!(
+            "\n{}: {} -> {}",
+            name,
+            get_effects_string(previous),
+            get_effects_string(current)
+        );
+
+        if chain.len() > 1 {
+            println!("  via {}", chain.join(" -> "));
+        }
+    }
+}
+
+// the previous and current effects of every function that gained effects, with the
+// call chain to the function that introduced them
+fn get_effects_changes(
+    previous_codebase: &(CodebaseInfo, Interner),
+    current_codebase: &(CodebaseInfo, Interner),
+) -> BTreeMap<String, (u8, u8, Vec<String>)> {
+    let previous_effects = get_function_effects(&previous_codebase.0, &previous_codebase.1);
+    let current_effects = get_function_effects(&current_codebase.0, &current_codebase.1);
+
+    let mut changes = BTreeMap::new();
+
+    for (name, current) in &current_effects {
+        if !current.user_defined {
+            continue;
+        }
+
+        if let Some(previous) = previous_effects.get(name) {
+            if current.effects & !previous.effects != 0 {
+                changes.insert(
+                    name.clone(),
+                    (
+                        previous.effects,
+                        current.effects,
+                        get_introducing_call_chain(name, &previous_effects, &current_effects),
+                    ),
+                );
+            }
+        }
+    }
+
+    changes
+}
+
+fn get_function_effects(
+    codebase: &CodebaseInfo,
+    interner: &Interner,
+) -> FxHashMap<String, FunctionEffects> {
+    let mut function_effects = FxHashMap::default();
+
+    let mut add_function = |name: String, functionlike_info: &FunctionLikeInfo| {
+        let mut own_effects = match functionlike_info.effects {
+            FnEffect::Some(effects) => effects,
+            FnEffect::None => 0,
+            FnEffect::Arg(_) => ARG_EFFECTS,
+            FnEffect::Unknown => UNKNOWN_EFFECTS,
+        };
+
+        if functionlike_info.is_non_deterministic {
+            own_effects |= NON_DETERMINISTIC;
+        }
+
+        let mut called_functions = functionlike_info
+            .called_functions
+            .iter()
+            .map(|called_function| interner.lookup(called_function).to_string())
+            .collect::<Vec<_>>();
+
+        // methods are keyed by the class that declares them, which may be a parent of
+        // the class named at the call site
+        for (classlike_name, method_name) in &functionlike_info.called_methods {
+            let declaring_method_id =
+                codebase.get_declaring_method_id(&MethodIdentifier(*classlike_name, *method_name));
+
+            called_functions.push(declaring_method_id.to_string(interner));
+        }
+
+        called_functions.sort();
+        called_functions.dedup();
+
+        function_effects.insert(
+            name,
+            FunctionEffects {
+                user_defined: functionlike_info.user_defined,
+                own_effects,
+                effects: own_effects,
+                called_functions,
+            },
+        );
+    };
+
+    for (function_name, functionlike_info) in &codebase.functionlike_infos {
+        add_function(
+            interner.lookup(function_name).to_string(),
+            functionlike_info,
+        );
+    }
+
+    for (classlike_name, classlike_info) in &codebase.classlike_infos {
+        for (method_name, functionlike_info) in &classlike_info.methods {
+            add_function(
+                format!(
+                    "{}::{}",
+                    interner.lookup(classlike_name),
+                    interner.lookup(method_name)
+                ),
+                functionlike_info,
+            );
+        }
+    }
+
+    // a function has the effects of everything it calls, so spread them until nothing
+    // changes. Effects only ever get added, so this terminates.
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        let names = function_effects.keys().cloned().collect::<Vec<_>>();
+
+        for name in names {
+            let called_effects = function_effects[&name]
+                .called_functions
+                .iter()
+                .filter_map(|called_function| function_effects.get(called_function))
+                .fold(0, |effects, called| effects | called.effects);
+
+            let function = function_effects.get_mut(&name).unwrap();
+
+            if called_effects & !function.effects != 0 {
+                function.effects |= called_effects;
+                changed = true;
+            }
+        }
+    }
+
+    function_effects
+}
+
+// Follows calls to functions that gained the same effects, until reaching the function
+// whose own contexts gained them
+fn get_introducing_call_chain(
+    name: &String,
+    previous_effects: &FxHashMap<String, FunctionEffects>,
+    current_effects: &FxHashMap<String, FunctionEffects>,
+) -> Vec<String> {
+    let mut chain = vec![name.clone()];
+    let mut seen_functions = FxHashSet::default();
+    let mut current_name = name.clone();
+
+    seen_functions.insert(current_name.clone());
+
+    loop {
+        let current = &current_effects[&current_name];
+        let previous = previous_effects.get(&current_name);
+
+        let new_effects = current.effects & !previous.map_or(0, |previous| previous.effects);
+
+        if current.own_effects & !previous.map_or(0, |previous| previous.own_effects) != 0 {
+            break;
+        }
+
+        let next_name = current.called_functions.iter().find(|called_function| {
+            if seen_functions.contains(*called_function) {
+                return false;
+            }
+
+            if let Some(called) = current_effects.get(*called_function) {
+                let previous_called_effects = previous_effects
+                    .get(*called_function)
+                    .map_or(0, |previous_called| previous_called.effects);
+
+                // either the callee gained the effects, or it's newly called
+                let was_called = previous.map_or(false, |previous| {
+                    previous.called_functions.contains(*called_function)
+                });
+
+                called.effects & new_effects != 0
+                    && (!was_called || called.effects & !previous_called_effects & new_effects != 0)
+            } else {
+                false
+            }
+        });
+
+        if let Some(next_name) = next_name {
+            current_name = next_name.clone();
+            seen_functions.insert(current_name.clone());
+            chain.push(current_name.clone());
+        } else {
+            break;
+        }
+    }
+
+    chain
+}
+
+fn get_effects_string(effects: u8) -> String {
+    if effects == 0 {
+        return "pure".to_string();
+    }
+
+    EFFECT_NAMES
+        .iter()
+        .filter(|(effect, _)| effects & effect != 0)
+        .map(|(_, effect_name)| *effect_name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests;
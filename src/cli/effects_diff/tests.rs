@@ -0,0 +1,184 @@
+use hakana_reflection_info::classlike_info::ClassLikeInfo;
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::StrId;
+
+use super::*;
+
+fn get_pos() -> HPos {
+    HPos {
+        file_path: StrId(0),
+        start_offset: 0,
+        end_offset: 0,
+        start_line: 1,
+        end_line: 1,
+        start_column: 1,
+        end_column: 1,
+        insertion_start: None,
+    }
+}
+
+fn get_functionlike_info(
+    codebase: &mut (CodebaseInfo, Interner),
+    name: &str,
+    effects: FnEffect,
+    called_functions: &[&str],
+    called_methods: &[(&str, &str)],
+) -> FunctionLikeInfo {
+    let mut functionlike_info =
+        FunctionLikeInfo::new(codebase.1.intern(name.to_string()), get_pos());
+    functionlike_info.user_defined = true;
+    functionlike_info.effects = effects;
+
+    for called_function in called_functions {
+        let called_function = codebase.1.intern(called_function.to_string());
+        functionlike_info.called_functions.insert(called_function);
+    }
+
+    for (classlike_name, method_name) in called_methods {
+        let classlike_name = codebase.1.intern(classlike_name.to_string());
+        let method_name = codebase.1.intern(method_name.to_string());
+        functionlike_info
+            .called_methods
+            .insert((classlike_name, method_name));
+    }
+
+    functionlike_info
+}
+
+fn add_function(
+    codebase: &mut (CodebaseInfo, Interner),
+    name: &str,
+    effects: FnEffect,
+    called_functions: &[&str],
+    called_methods: &[(&str, &str)],
+) {
+    let functionlike_info =
+        get_functionlike_info(codebase, name, effects, called_functions, called_methods);
+
+    codebase
+        .0
+        .functionlike_infos
+        .insert(functionlike_info.name, functionlike_info);
+}
+
+fn add_method(
+    codebase: &mut (CodebaseInfo, Interner),
+    classlike_name: &str,
+    declaring_classlike_name: &str,
+    method_name: &str,
+    effects: FnEffect,
+) {
+    let functionlike_info = get_functionlike_info(codebase, method_name, effects, &[], &[]);
+
+    let declaring_classlike_name = codebase.1.intern(declaring_classlike_name.to_string());
+
+    for classlike_name in [
+        codebase.1.intern(classlike_name.to_string()),
+        declaring_classlike_name,
+    ] {
+        let classlike_info = codebase
+            .0
+            .classlike_infos
+            .entry(classlike_name)
+            .or_insert_with(|| ClassLikeInfo::new(classlike_name, get_pos(), get_pos()));
+
+        classlike_info
+            .declaring_method_ids
+            .insert(functionlike_info.name, declaring_classlike_name);
+
+        if classlike_name == declaring_classlike_name {
+            classlike_info
+                .methods
+                .insert(functionlike_info.name, functionlike_info.clone());
+        }
+    }
+}
+
+#[test]
+fn follows_method_calls_to_the_declaring_class() {
+    let mut previous_codebase = (CodebaseInfo::new(), Interner::new());
+    add_method(
+        &mut previous_codebase,
+        "Child",
+        "Base",
+        "save",
+        FnEffect::None,
+    );
+    add_function(
+        &mut previous_codebase,
+        "caller",
+        FnEffect::None,
+        &[],
+        &[("Child", "save")],
+    );
+
+    let mut current_codebase = (CodebaseInfo::new(), Interner::new());
+    add_method(
+        &mut current_codebase,
+        "Child",
+        "Base",
+        "save",
+        FnEffect::Some(WRITE_GLOBALS),
+    );
+    add_function(
+        &mut current_codebase,
+        "caller",
+        FnEffect::None,
+        &[],
+        &[("Child", "save")],
+    );
+
+    let changes = get_effects_changes(&previous_codebase, &current_codebase);
+
+    assert_eq!(
+        Some(&(
+            0,
+            WRITE_GLOBALS,
+            vec!["caller".to_string(), "Base::save".to_string()]
+        )),
+        changes.get("caller")
+    );
+    assert_eq!(
+        Some(&(0, WRITE_GLOBALS, vec!["Base::save".to_string()])),
+        changes.get("Base::save")
+    );
+}
+
+#[test]
+fn reports_argument_and_unknown_effects() {
+    let mut previous_codebase = (CodebaseInfo::new(), Interner::new());
+    add_function(&mut previous_codebase, "run", FnEffect::None, &[], &[]);
+    add_function(&mut previous_codebase, "closure", FnEffect::None, &[], &[]);
+
+    let mut current_codebase = (CodebaseInfo::new(), Interner::new());
+    add_function(&mut current_codebase, "run", FnEffect::Arg(0), &[], &[]);
+    add_function(
+        &mut current_codebase,
+        "closure",
+        FnEffect::Unknown,
+        &[],
+        &[],
+    );
+
+    let changes = get_effects_changes(&previous_codebase, &current_codebase);
+
+    assert_eq!(
+        vec!["closure".to_string(), "run".to_string()],
+        changes.keys().cloned().collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        "effects of an argument",
+        get_effects_string(changes["run"].1)
+    );
+    assert_eq!("unknown effects", get_effects_string(changes["closure"].1));
+}
+
+#[test]
+fn names_effects_from_the_analyzer_constants() {
+    assert_eq!("pure", get_effects_string(0));
+    assert_eq!(
+        "reads properties, writes globals, non-deterministic",
+        get_effects_string(READ_PROPS | WRITE_GLOBALS | NON_DETERMINISTIC)
+    );
+}
@@ -0,0 +1,73 @@
+use hakana_reflection_info::issue::Issue;
+use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
+
+// Sorts the issues into those that are new since the previous run, those that have been
+// fixed since and those that are unchanged. Issues are matched by fingerprint, so moving
+// code around doesn't make its issues new, and repeated issues by how often they occur.
+pub(crate) fn print_issue_changes(
+    previous_issues: &BTreeMap<String, Vec<Issue>>,
+    current_issues: &BTreeMap<String, Vec<Issue>>,
+) {
+    let mut unmatched_counts = FxHashMap::default();
+
+    for (file_path, issues) in previous_issues {
+        for issue in issues {
+            *unmatched_counts
+                .entry(issue.get_fingerprint(file_path))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let mut new_issues = vec![];
+    let mut unchanged_count = 0;
+
+    for (file_path, issues) in current_issues {
+        for issue in issues {
+            match unmatched_counts.get_mut(&issue.get_fingerprint(file_path)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    unchanged_count += 1;
+                }
+                _ => new_issues.push(issue.format(file_path)),
+            }
+        }
+    }
+
+    // the previous issues that no current issue matched
+    let mut fixed_issues = vec![];
+
+    for (file_path, issues) in previous_issues {
+        for issue in issues {
+            if let Some(count) = unmatched_counts.get_mut(&issue.get_fingerprint(file_path)) {
+                if *count > 0 {
+                    *count -= 1;
+                    fixed_issues.push(issue.format(file_path));
+                }
+            }
+        }
+    }
+
+    println!(
+        "\nSince the previous run: {} new, {} fixed, {} unchanged",
+        new_issues.len(),
+        fixed_issues.len(),
+        unchanged_count
+    );
+
+    if !new_issues.is_empty() {
+        println!("\nNew issues:");
+
+        for issue in new_issues {
+            print!("{}", issue);
+        }
+    }
+
+    if !fixed_issues.is_empty() {
+        println!("\nFixed issues:");
+
+        for issue in fixed_issues {
+            print!("{}", issue);
+        }
+    }
+}
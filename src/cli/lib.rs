@@ -1,20 +1,32 @@
 use clap::{arg, Command};
 use hakana_analyzer::config::{self, Verbosity};
 use hakana_analyzer::custom_hook::CustomHook;
-use hakana_reflection_info::analysis_result::{AnalysisResult, CheckPointEntry, Replacement};
+use hakana_reflection_info::analysis_result::{
+    AnalysisResult, CheckPointEntry, LivenessExplanation, LivenessRule, ReferenceKind, Replacement,
+    TaintTraceEntry, TypeCoverage,
+};
 use hakana_reflection_info::data_flow::graph::{GraphKind, WholeProgramKind};
-use hakana_reflection_info::issue::IssueKind;
+use hakana_reflection_info::issue::{IssueKind, IssueSeverity};
 use indexmap::IndexMap;
 use rustc_hash::FxHashSet;
-use std::collections::BTreeMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
 use std::process::exit;
 use std::sync::Arc;
+use std::time::Instant;
 use test_runners::test_runner::TestRunner;
+mod config_diff;
+mod conformance;
+mod effects_diff;
+mod issue_changes;
+mod shape_report;
 pub mod test_runners;
+mod trend_database;
+mod watch;
 
 pub fn init(
     analysis_hooks: Vec<Box<dyn CustomHook>>,
@@ -22,24 +34,1346 @@ pub fn init(
     header: &str,
     test_runner: Box<dyn TestRunner>,
 ) {
-    println!("{}\n", header);
+    let mut all_custom_issues = vec![];
+
+    for analysis_hook in &analysis_hooks {
+        all_custom_issues.extend(analysis_hook.get_custom_issue_names());
+    }
+
+    for migration_hook in &migration_hooks {
+        all_custom_issues.extend(migration_hook.get_custom_issue_names());
+    }
+
+    let all_custom_issues = all_custom_issues
+        .into_iter()
+        .map(|i| i.to_string())
+        .collect::<FxHashSet<_>>();
+
+    let matches = get_command().get_matches();
+
+    // the language server's stdout is reserved for talking to the editor, and
+    // typed-ast's is reserved for JSON
+    if !matches!(
+        matches.subcommand(),
+        Some(("language-server", _)) | Some(("typed-ast", _))
+    ) {
+        println!("{}\n", header);
+    }
+
+    let cwd = (env::current_dir()).unwrap().to_str().unwrap().to_string();
+
+    let threads = match matches.subcommand() {
+        Some(("test", _)) => 1,
+        Some((_, sub_matches)) => {
+            if let Some(val) = sub_matches.value_of("threads").map(|f| f.to_string()) {
+                val.parse::<u8>().unwrap()
+            } else {
+                8
+            }
+        }
+        _ => 8,
+    };
+
+    let verbosity = match matches.subcommand() {
+        Some(("test", sub_matches)) => {
+            if sub_matches.is_present("debug") {
+                Verbosity::Debugging
+            } else {
+                Verbosity::Quiet
+            }
+        }
+        // keep stdout machine-readable
+        Some(("analyze", sub_matches)) if sub_matches.is_present("list-unused-symbols") => {
+            Verbosity::Quiet
+        }
+        Some(("language-server", _)) | Some(("typed-ast", _)) => Verbosity::Quiet,
+        Some(("explain-liveness", sub_matches)) if sub_matches.is_present("json") => {
+            Verbosity::Quiet
+        }
+        Some((_, sub_matches)) => {
+            if sub_matches.is_present("debug") {
+                Verbosity::Debugging
+            } else {
+                Verbosity::Simple
+            }
+        }
+        _ => Verbosity::Simple,
+    };
+
+    let root_dir = match matches.subcommand() {
+        Some(("test", _)) => cwd.as_str().to_string(),
+        Some((_, sub_matches)) => sub_matches
+            .value_of("root")
+            .unwrap_or(cwd.as_str())
+            .to_string(),
+        _ => panic!(),
+    };
+
+    let config_path = match matches.subcommand() {
+        Some(("test", _)) => None,
+        Some((_, sub_matches)) => Some(
+            sub_matches
+                .value_of("config")
+                .unwrap_or(format!("{}/hakana.json", root_dir).as_str())
+                .to_string(),
+        ),
+        _ => panic!(),
+    };
+
+    let config_path = if let Some(config_path) = &config_path {
+        Some(Path::new(config_path))
+    } else {
+        None
+    };
+
+    let cache_dir = format!("{}/.hakana_cache", root_dir);
+
+    if !Path::new(&cache_dir).is_dir() && fs::create_dir(&cache_dir).is_err() {
+        panic!("could not create aast cache directory");
+    }
+
+    let mut had_error = false;
+
+    match matches.subcommand() {
+        Some(("analyze", sub_matches)) => {
+            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
+
+            let output_file = sub_matches.value_of("output").map(|f| f.to_string());
+            let dependency_manifest_file = sub_matches
+                .value_of("dependency-manifest")
+                .map(|f| f.to_string());
+            let trend_database_file = sub_matches
+                .value_of("trend-database")
+                .map(|f| f.to_string());
+
+            let ignored = sub_matches
+                .values_of("ignore")
+                .map(|values| values.map(|f| f.to_string()).collect::<FxHashSet<_>>());
+            let find_unused_expressions = sub_matches.is_present("find-unused-expressions");
+            let list_unused_symbols = sub_matches.is_present("list-unused-symbols");
+            let find_unused_definitions =
+                sub_matches.is_present("find-unused-definitions") || list_unused_symbols;
+            let show_mixed_function_counts = sub_matches.is_present("show-mixed-function-counts");
+            let type_coverage_format = sub_matches.value_of("type-coverage").map(|f| f.to_string());
+            let show_shape_report = sub_matches.is_present("shape-report");
+            let shape_report_min_callers = sub_matches
+                .get_one::<usize>("shape-report-min-callers")
+                .copied()
+                .unwrap_or(3);
+            let show_issue_profiles = sub_matches.is_present("show-issue-profiles");
+            let show_symbol_map = sub_matches.is_present("show-symbol-map");
+            let ignore_mixed_issues = sub_matches.is_present("ignore-mixed-issues");
+            let show_issue_stats = sub_matches.is_present("show-issue-stats");
+            let do_ast_diff = sub_matches.is_present("diff");
+            let apply_fixes = sub_matches.is_present("fix");
+            let show_effects_diff = sub_matches.is_present("effects-diff");
+            let show_issue_changes = sub_matches.is_present("issue-changes");
+            let compare_config_path = sub_matches
+                .value_of("compare-config")
+                .map(|f| get_absolute_path(f.to_string(), &cwd));
+
+            let mut issue_kinds_filter = FxHashSet::default();
+
+            let filter_issue_strings = sub_matches
+                .values_of("show-issue")
+                .map(|values| values.collect::<FxHashSet<_>>());
+
+            if let Some(filter_issue_strings) = filter_issue_strings {
+                for filter_issue_string in filter_issue_strings {
+                    if let Ok(issue_kind) =
+                        IssueKind::from_str_custom(filter_issue_string, &all_custom_issues)
+                    {
+                        issue_kinds_filter.insert(issue_kind);
+                    } else {
+                        println!("Invalid issue type {}", filter_issue_string);
+                        exit(1);
+                    }
+                }
+            }
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.find_unused_expressions = find_unused_expressions;
+            config.find_unused_definitions = find_unused_definitions;
+            config.list_unused_symbols = list_unused_symbols;
+            config.ignore_mixed_issues = ignore_mixed_issues;
+            config.ast_diff = do_ast_diff;
+            config.compute_dependency_digests = dependency_manifest_file.is_some();
+            config.topological_analysis_order = sub_matches.is_present("topological-order");
+            config.collect_type_coverage = type_coverage_format.is_some();
+            config.collect_shape_params = show_shape_report;
+            config.collect_mixed_source_counts = show_mixed_function_counts;
+            config.collect_issue_profile_files = show_issue_profiles;
+            config.publish_remote_cache = sub_matches.is_present("publish-remote-cache");
+
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+
+            // do this after we've loaded from file, as they can be overridden
+            if !issue_kinds_filter.is_empty() {
+                // the selected kinds may need analysis that's otherwise opt-in
+                config.find_unused_expressions |= issue_kinds_filter
+                    .iter()
+                    .any(|issue_kind| issue_kind.is_unused_expression());
+                config.find_unused_definitions |= issue_kinds_filter
+                    .iter()
+                    .any(|issue_kind| issue_kind.is_unused_definition());
+
+                config.allowed_issues = Some(issue_kinds_filter);
+            }
+
+            if apply_fixes {
+                // fixing an issue may need analysis that's otherwise opt-in
+                config.find_unused_expressions |= config
+                    .safe_fix_issues
+                    .iter()
+                    .any(|issue_kind| issue_kind.is_unused_expression());
+                config.find_unused_definitions |= config
+                    .safe_fix_issues
+                    .iter()
+                    .any(|issue_kind| issue_kind.is_unused_definition());
+
+                config.issues_to_fix = config.safe_fix_issues.clone();
+            }
+
+            // analyzes with both configs' issue filters lifted, applying each afterwards
+            let config_comparison = compare_config_path.map(|compare_config_path| {
+                // the output describes the comparison, not either config's issues
+                if output_file.is_some() || trend_database_file.is_some() || apply_fixes {
+                    println!(
+                        "--compare-config can't be used with --output, --trend-database or --fix"
+                    );
+                    exit(1);
+                }
+
+                let mut other_config =
+                    config::Config::new(root_dir.clone(), config.all_custom_issues.clone());
+                update_config_from_file(&mut other_config, &cwd, Path::new(&compare_config_path));
+
+                config_diff::prepare_config_comparison(&mut config, other_config)
+            });
+
+            if let Some(max_issue_count) = sub_matches.get_one::<usize>("max-issues") {
+                config.max_issue_count = *max_issue_count;
+            }
+
+            let max_issue_count = config.max_issue_count;
+
+            if sub_matches.is_present("watch") {
+                // only re-analyze the symbols affected by each change
+                config.ast_diff = true;
+
+                if let Err(error) = watch::watch(
+                    Arc::new(config),
+                    filter,
+                    ignored,
+                    &cache_dir,
+                    threads,
+                    verbosity,
+                    &header,
+                ) {
+                    println!("{}", error);
+                    exit(1);
+                }
+
+                return;
+            }
+
+            // the dependency codebase is only read, and already contains the core libs
+            let starter_data = sub_matches
+                .value_of("dependency-root")
+                .map(|dependency_root| {
+                    let dependency_root = get_absolute_path(dependency_root.to_string(), &cwd);
+
+                    match hakana_workhorse::load_dependency_codebase(
+                        &dependency_root,
+                        &get_absolute_path(root_dir.clone(), &cwd),
+                        verbosity,
+                    ) {
+                        Some(starter_data) => starter_data,
+                        None => {
+                            println!("Could not find a codebase cache in {}", dependency_root);
+                            exit(1);
+                        }
+                    }
+                });
+
+            // the run replaces the cached codebase, so the previous one is loaded first
+            let previous_codebase = if show_effects_diff {
+                if sub_matches.is_present("no-cache") || starter_data.is_some() {
+                    println!("--effects-diff needs the codebase cache in {}", cache_dir);
+                    exit(1);
+                }
+
+                match hakana_workhorse::load_codebase_cache(&cache_dir, verbosity) {
+                    Some(previous_codebase) => Some(previous_codebase),
+                    None => {
+                        println!("Could not find a codebase cache in {}", cache_dir);
+                        exit(1);
+                    }
+                }
+            } else {
+                None
+            };
+
+            let previous_issues = if show_issue_changes {
+                if sub_matches.is_present("no-cache") || starter_data.is_some() {
+                    println!("--issue-changes needs the issues cache in {}", cache_dir);
+                    exit(1);
+                }
+
+                match hakana_workhorse::load_issues_cache(&cache_dir, verbosity) {
+                    Ok(Some(previous_issues)) => Some(previous_issues),
+                    Ok(None) => {
+                        println!("Could not find an issues cache in {}", cache_dir);
+                        exit(1);
+                    }
+                    Err(error) => {
+                        println!("{}", error);
+                        exit(1);
+                    }
+                }
+            } else {
+                None
+            };
+
+            let start_time = Instant::now();
+
+            let result = hakana_workhorse::scan_and_analyze(
+                starter_data.is_none(),
+                Vec::new(),
+                filter,
+                ignored,
+                Arc::new(config),
+                // the comparison run's unfiltered issues mustn't be cached as this config's
+                if sub_matches.is_present("no-cache")
+                    || starter_data.is_some()
+                    || config_comparison.is_some()
+                {
+                    None
+                } else {
+                    Some(&cache_dir)
+                },
+                threads,
+                verbosity,
+                &header,
+                starter_data,
+            );
+
+            if let Err(error) = &result {
+                println!("{}", error);
+                had_error = true;
+            }
+
+            if let Ok(mut analysis_result) = result {
+                if let Some((current_config, other_config)) = &config_comparison {
+                    config_diff::print_config_diff(&analysis_result, current_config, other_config);
+                } else if list_unused_symbols {
+                    println!("{}", get_unused_symbols_json(&mut analysis_result));
+                } else if type_coverage_format.as_deref() == Some("json") {
+                    println!(
+                        "{}",
+                        get_report_json(
+                            &analysis_result,
+                            "type_coverage",
+                            &get_type_coverage_json(&analysis_result.type_coverage)
+                        )
+                    );
+                } else {
+                    for (file_path, issues) in &analysis_result.emitted_issues {
+                        for issue in issues {
+                            had_error |= issue.severity == IssueSeverity::Error;
+                            println!("{}", issue.format(&file_path));
+                        }
+                    }
+
+                    // warnings and infos are still issues, even though they don't fail the run
+                    if analysis_result
+                        .emitted_issues
+                        .values()
+                        .all(|issues| issues.is_empty())
+                    {
+                        println!("\nNo issues reported!\n");
+                    }
+                }
+
+                if analysis_result.unanalyzed_file_count > 0 {
+                    had_error = true;
+                    println!(
+                        "\nStopped after finding {} or more issues, so {} files were not analyzed. \
+                        This usually means Hakana is misconfigured (e.g. it has the wrong root directory).\n",
+                        max_issue_count, analysis_result.unanalyzed_file_count
+                    );
+                }
+
+                if apply_fixes {
+                    let file_edit_counts = update_files(&analysis_result, &root_dir);
+
+                    println!("\n{}", get_fix_summary(&file_edit_counts));
+                }
+
+                if let Some(output_file) = output_file {
+                    write_output_files(output_file, &cwd, &analysis_result);
+                }
+
+                if let Some(dependency_manifest_file) = dependency_manifest_file {
+                    let manifest_path = get_absolute_path(dependency_manifest_file, &cwd);
+                    let mut manifest_file = fs::File::create(Path::new(&manifest_path)).unwrap();
+                    let manifest_json = get_report_json(
+                        &analysis_result,
+                        "files",
+                        &analysis_result.dependency_digests,
+                    );
+
+                    write!(manifest_file, "{}", manifest_json).unwrap();
+                }
+
+                if let Some(trend_database_file) = trend_database_file {
+                    let database_path = get_absolute_path(trend_database_file, &cwd);
+
+                    if let Err(error) = trend_database::record_run(
+                        &database_path,
+                        header,
+                        &analysis_result,
+                        start_time.elapsed(),
+                    ) {
+                        println!("Could not write to trend database: {}", error);
+                    }
+                }
+
+                if show_issue_stats {
+                    let mut issues_by_kind = analysis_result
+                        .issue_counts
+                        .into_iter()
+                        .collect::<IndexMap<_, _>>();
+                    issues_by_kind.sort_by(|_, a, _, b| b.cmp(a));
+
+                    for (issue, count) in issues_by_kind {
+                        println!("{}\t{}", issue.to_string(), count);
+                    }
+                }
+
+                if show_symbol_map {
+                    println!("{:#?}", analysis_result.symbol_references);
+                }
+
+                if show_mixed_function_counts {
+                    let mut mixed_sources = analysis_result
+                        .mixed_source_counts
+                        .iter()
+                        .map(|(k, v)| format!("{}\t{}", k, v.len()))
+                        .collect::<Vec<_>>();
+
+                    mixed_sources.sort();
+
+                    println!("{}", mixed_sources.join("\n"));
+                }
+
+                if type_coverage_format.as_deref() == Some("text") {
+                    println!(
+                        "{}\n",
+                        get_type_coverage_text(&analysis_result.type_coverage)
+                    );
+                }
+
+                if show_shape_report {
+                    shape_report::print_shape_report(&analysis_result, shape_report_min_callers);
+                }
+
+                if show_issue_profiles {
+                    print_issue_profile_files(&analysis_result.issue_profile_files);
+                }
+
+                if let Some(previous_issues) = &previous_issues {
+                    issue_changes::print_issue_changes(
+                        previous_issues,
+                        &analysis_result.emitted_issues,
+                    );
+                }
+
+                if let Some(previous_codebase) = &previous_codebase {
+                    match hakana_workhorse::load_codebase_cache(&cache_dir, verbosity) {
+                        Some(current_codebase) => {
+                            effects_diff::print_effects_diff(previous_codebase, &current_codebase)
+                        }
+                        None => println!("Could not find a codebase cache in {}", cache_dir),
+                    }
+                }
+            }
+        }
+        Some(("security-check", sub_matches)) => {
+            let mut config = config::Config::new(cwd.clone(), all_custom_issues);
+            config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Taint);
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            let output_file = sub_matches.value_of("output").map(|f| f.to_string());
+            let save_baseline_file = sub_matches.value_of("save-baseline").map(|f| f.to_string());
+            let baseline_file = sub_matches.value_of("baseline").map(|f| f.to_string());
+            let trace_output_file = sub_matches.value_of("trace-output").map(|f| f.to_string());
+
+            config.security_config.max_depth =
+                if let Some(val) = sub_matches.value_of("max-depth").map(|f| f.to_string()) {
+                    val.parse::<u8>().unwrap()
+                } else {
+                    20
+                };
+
+            config.hooks = analysis_hooks;
+
+            config.dataflow_graph_path = sub_matches
+                .value_of("graph-output")
+                .map(|f| get_absolute_path(f.to_string(), &cwd));
+            config.dataflow_graph_sources = sub_matches
+                .values_of("graph-source")
+                .map(|values| values.map(|f| f.to_string()).collect())
+                .unwrap_or_default();
+
+            // saved before looking for paths, as that's when memory usage peaks
+            let checkpoint_path = format!("{}/taint_checkpoint", cache_dir);
+
+            let result = if sub_matches.is_present("resume") {
+                hakana_workhorse::resume_whole_program_analysis(
+                    Arc::new(config),
+                    &checkpoint_path,
+                    threads,
+                    verbosity,
+                    &header,
+                )
+            } else {
+                if sub_matches.is_present("checkpoint") {
+                    config.whole_program_checkpoint_path = Some(checkpoint_path);
+                }
+
+                hakana_workhorse::scan_and_analyze(
+                    true,
+                    Vec::new(),
+                    None,
+                    None,
+                    Arc::new(config),
+                    None,
+                    threads,
+                    verbosity,
+                    &header,
+                    None,
+                )
+            };
+
+            if let Err(error) = &result {
+                println!("{}", error);
+                had_error = true;
+            }
+
+            if let Ok(mut analysis_result) = result {
+                if let Some(save_baseline_file) = save_baseline_file {
+                    write_taint_baseline(save_baseline_file, &cwd, &analysis_result);
+                }
+
+                if let Some(baseline_file) = baseline_file {
+                    let hidden_count =
+                        remove_baseline_taint_flows(baseline_file, &cwd, &mut analysis_result);
+
+                    if hidden_count > 0 {
+                        println!("Ignoring {} pre-existing taint flows\n", hidden_count);
+                    }
+                }
+
+                for (file_path, issues) in &analysis_result.emitted_issues {
+                    for issue in issues {
+                        had_error |= issue.severity == IssueSeverity::Error;
+                        println!("{}", issue.format(&file_path));
+                    }
+                }
+
+                if !had_error {
+                    println!("\nNo security issues found!\n");
+                }
+
+                if let Some(output_file) = output_file {
+                    write_output_files(output_file, &cwd, &analysis_result);
+                }
+
+                if let Some(trace_output_file) = trace_output_file {
+                    write_taint_traces(trace_output_file, &cwd, &analysis_result);
+                }
+            }
+        }
+        Some(("find-paths", sub_matches)) => {
+            let mut config = config::Config::new(cwd.clone(), all_custom_issues);
+            config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Query);
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            config.security_config.max_depth =
+                if let Some(val) = sub_matches.value_of("max-depth").map(|f| f.to_string()) {
+                    val.parse::<u8>().unwrap()
+                } else {
+                    20
+                };
+
+            config.hooks = analysis_hooks;
+
+            config.dataflow_graph_path = sub_matches
+                .value_of("graph-output")
+                .map(|f| get_absolute_path(f.to_string(), &cwd));
+            config.dataflow_graph_sources = sub_matches
+                .values_of("graph-source")
+                .map(|values| values.map(|f| f.to_string()).collect())
+                .unwrap_or_default();
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                None,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Err(error) = &result {
+                println!("{}", error);
+                had_error = true;
+            }
+
+            if let Ok(analysis_result) = result {
+                for (file_path, issues) in analysis_result.emitted_issues {
+                    for issue in issues {
+                        had_error |= issue.severity == IssueSeverity::Error;
+                        println!("{}", issue.format(&file_path));
+                    }
+                }
+
+                if !had_error {
+                    println!("\nNo security issues found!\n");
+                }
+            }
+        }
+        Some(("migrate", sub_matches)) => {
+            let migration_name = sub_matches.value_of("migration").unwrap().to_string();
+            let migration_source = sub_matches.value_of("symbols").unwrap().to_string();
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.hooks = migration_hooks
+                .into_iter()
+                .filter(|m| {
+                    if let Some(name) = m.get_migration_name() {
+                        migration_name == name
+                    } else {
+                        false
+                    }
+                })
+                .collect();
+
+            if config.hooks.is_empty() {
+                println!("Migration {} not recognised", migration_name);
+                exit(1);
+            }
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            let file_path = format!("{}/{}", cwd, migration_source);
+
+            let buf = fs::read_to_string(file_path.clone());
+
+            if let Ok(contents) = buf {
+                config.migration_symbols = contents
+                    .lines()
+                    .map(|v| (migration_name.clone(), v.to_string()))
+                    .collect();
+            } else {
+                println!(
+                    "\nERROR: File {} does not exist or could not be read\n",
+                    file_path
+                );
+                exit(1);
+            }
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                None,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Ok(analysis_result) = result {
+                update_files(&analysis_result, &root_dir);
+            }
+        }
+        Some(("add-fixmes", sub_matches)) => {
+            let filter_issue_strings = sub_matches
+                .values_of("issue")
+                .map(|values| values.collect::<FxHashSet<_>>());
+
+            let mut issue_kinds_filter = FxHashSet::default();
+
+            if let Some(filter_issue_strings) = filter_issue_strings {
+                for filter_issue_string in filter_issue_strings {
+                    if let Ok(issue_kind) =
+                        IssueKind::from_str_custom(filter_issue_string, &all_custom_issues)
+                    {
+                        issue_kinds_filter.insert(issue_kind);
+                    } else {
+                        println!("Invalid issue type {}", filter_issue_string);
+                        exit(1);
+                    }
+                }
+            }
+
+            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+
+            config.issues_to_fix.extend(issue_kinds_filter);
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            config.add_fixmes = true;
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                filter,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Ok(analysis_result) = result {
+                update_files(&analysis_result, &root_dir);
+            }
+        }
+        Some(("remove-unused-fixmes", sub_matches)) => {
+            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            config.remove_fixmes = true;
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                filter,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Ok(analysis_result) = result {
+                update_files(&analysis_result, &root_dir);
+            }
+        }
+        Some(("remove-unused-files", _)) => {
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.find_unused_definitions = true;
+
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+            config.allowed_issues = None;
+
+            config.remove_unused_files = true;
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                None,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Ok(analysis_result) = result {
+                update_files(&analysis_result, &root_dir);
+            }
+        }
+        Some(("explain-liveness", sub_matches)) => {
+            let symbol_name = sub_matches.value_of("symbol").unwrap().to_string();
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.find_unused_definitions = true;
+
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+
+            config.explain_liveness_of = Some(symbol_name.clone());
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                None,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            match result {
+                Ok(AnalysisResult {
+                    liveness_explanation: Some(explanation),
+                    ..
+                }) => {
+                    if sub_matches.is_present("json") {
+                        println!("{}", serde_json::to_string_pretty(&explanation).unwrap());
+                    } else {
+                        println!("{}", get_liveness_explanation_text(&explanation));
+                    }
+                }
+                Ok(_) => {
+                    println!("Could not find a symbol named {}", symbol_name);
+                    had_error = true;
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    had_error = true;
+                }
+            }
+        }
+        Some(("typed-ast", sub_matches)) => {
+            let file_path =
+                get_absolute_path(sub_matches.value_of("file").unwrap().to_string(), &cwd);
+
+            let relative_path = if let Some(relative_path) = file_path
+                .strip_prefix(&root_dir)
+                .and_then(|path| path.strip_prefix('/'))
+            {
+                relative_path.to_string()
+            } else {
+                eprintln!("{} is not inside {}", file_path, root_dir);
+                exit(1);
+            };
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            // not update_config_from_file, which writes to stdout
+            if config_path.exists() {
+                if let Err(error) = config.update_from_file(&cwd, config_path) {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+
+            config.typed_ast_file = Some(relative_path.clone());
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                Some(file_path),
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            match result {
+                Ok(AnalysisResult {
+                    typed_ast: Some(typed_ast),
+                    ..
+                }) => {
+                    if sub_matches.is_present("conformance") {
+                        let hh_client_path =
+                            sub_matches.value_of("hh-client").unwrap_or("hh_client");
+
+                        match conformance::get_conformance_report(
+                            &root_dir,
+                            &relative_path,
+                            &typed_ast,
+                            hh_client_path,
+                        ) {
+                            Ok(report) => {
+                                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+                            }
+                            Err(error) => {
+                                eprintln!("{}", error);
+                                had_error = true;
+                            }
+                        }
+                    } else {
+                        println!("{}", serde_json::to_string_pretty(&typed_ast).unwrap());
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    eprintln!("{}", error);
+                    had_error = true;
+                }
+            }
+        }
+        Some(("fix", sub_matches)) => {
+            let issue_name = sub_matches.value_of("issue").unwrap().to_string();
+            let issue_kind = IssueKind::from_str_custom(&issue_name, &all_custom_issues).unwrap();
+
+            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
+
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.find_unused_expressions = issue_kind.is_unused_expression();
+            config.find_unused_definitions = issue_kind.is_unused_definition();
+            config.issues_to_fix.insert(issue_kind);
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+
+            config.allowed_issues = None;
+
+            let result = hakana_workhorse::scan_and_analyze(
+                true,
+                Vec::new(),
+                filter,
+                None,
+                Arc::new(config),
+                None,
+                threads,
+                verbosity,
+                &header,
+                None,
+            );
+
+            if let Ok(analysis_result) = result {
+                update_files(&analysis_result, &root_dir);
+            }
+        }
+        Some(("test", sub_matches)) => {
+            let repeat = if let Some(val) = sub_matches.value_of("repeat").map(|f| f.to_string()) {
+                val.parse::<u16>().unwrap()
+            } else {
+                0
+            };
+            test_runner.run_test(
+                sub_matches.value_of("TEST").expect("required").to_string(),
+                verbosity,
+                !sub_matches.is_present("no-cache"),
+                &mut had_error,
+                header,
+                repeat,
+            );
+        }
+        Some(("stubs", sub_matches)) => {
+            let vendor_dir = get_absolute_path(
+                sub_matches
+                    .value_of("vendor-dir")
+                    .unwrap()
+                    .trim_end_matches('/')
+                    .to_string(),
+                &cwd,
+            );
+            let output_dir =
+                get_absolute_path(sub_matches.value_of("output").unwrap().to_string(), &cwd);
+
+            // paths in the scanned codebase are relative to the vendor directory,
+            // which is how they're laid out in the output directory
+            let mut config = config::Config::new(vendor_dir, all_custom_issues);
+
+            let config_path = config_path.unwrap();
+
+            if config_path.exists() {
+                update_config_from_file(&mut config, &cwd, config_path);
+            }
+
+            match hakana_workhorse::stubs::generate_stubs(
+                Arc::new(config),
+                &output_dir,
+                threads,
+                verbosity,
+            ) {
+                Ok(stub_count) => {
+                    println!("Wrote {} stubs to {}", stub_count, output_dir);
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    had_error = true;
+                }
+            }
+        }
+        Some(("language-server", _)) => {
+            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
+            config.hooks = analysis_hooks;
+
+            let config_path = config_path.unwrap();
+
+            // not update_config_from_file, which writes to stdout
+            if config_path.exists() {
+                if let Err(error) = config.update_from_file(&cwd, config_path) {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+
+            if let Err(error) = hakana_language_server::run(config, threads) {
+                eprintln!("{}", error);
+                had_error = true;
+            }
+        }
+        _ => unreachable!(), // If all subcommands are defined above, anything else is unreachabe!()
+    }
+
+    if had_error {
+        exit(1);
+    }
+}
+
+fn write_output_files(output_file: String, cwd: &String, analysis_result: &AnalysisResult) {
+    if output_file.ends_with("checkpoint_results.json") {
+        let output_path = if output_file.starts_with("/") {
+            output_file
+        } else {
+            format!("{}/{}", cwd, output_file)
+        };
+        let mut output_path = fs::File::create(Path::new(&output_path)).unwrap();
+        let mut checkpoint_entries = vec![];
 
-    let mut all_custom_issues = vec![];
+        for (file_path, issues) in &analysis_result.emitted_issues {
+            for issue in issues {
+                checkpoint_entries.push(CheckPointEntry::from_issue(issue, &file_path));
+            }
+        }
 
-    for analysis_hook in &analysis_hooks {
-        all_custom_issues.extend(analysis_hook.get_custom_issue_names());
+        let checkpoint_json = get_report_json(analysis_result, "issues", &checkpoint_entries);
+
+        write!(output_path, "{}", checkpoint_json).unwrap();
     }
+}
 
-    for migration_hook in &migration_hooks {
-        all_custom_issues.extend(migration_hook.get_custom_issue_names());
+// JSON reports record how they were produced alongside their contents
+fn get_report_json<T: Serialize>(
+    analysis_result: &AnalysisResult,
+    key: &str,
+    contents: &T,
+) -> String {
+    serde_json::to_string_pretty(&serde_json::json!({
+        "metadata": analysis_result.run_metadata,
+        key: contents,
+    }))
+    .unwrap()
+}
+
+fn get_unused_symbols_json(analysis_result: &mut AnalysisResult) -> String {
+    analysis_result
+        .unused_symbols
+        .sort_by(|a, b| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)));
+
+    get_report_json(
+        analysis_result,
+        "unused_symbols",
+        &analysis_result.unused_symbols,
+    )
+}
+
+fn get_type_coverage_json(type_coverage: &BTreeMap<String, TypeCoverage>) -> serde_json::Value {
+    let get_entry = |coverage: &TypeCoverage| {
+        serde_json::json!({
+            "mixed_count": coverage.mixed_count,
+            "typed_count": coverage.typed_count,
+            "typed_percentage": coverage.get_typed_percentage(),
+        })
+    };
+
+    serde_json::json!({
+        "total": get_entry(&get_total_type_coverage(type_coverage)),
+        "files": type_coverage
+            .iter()
+            .map(|(file_path, coverage)| (file_path.clone(), get_entry(coverage)))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+// Files without a profile aren't listed
+fn print_issue_profile_files(issue_profile_files: &BTreeMap<String, String>) {
+    let mut files_by_profile = BTreeMap::new();
+
+    for (file_path, profile_name) in issue_profile_files {
+        files_by_profile
+            .entry(profile_name)
+            .or_insert_with(Vec::new)
+            .push(file_path);
     }
 
-    let all_custom_issues = all_custom_issues
-        .into_iter()
-        .map(|i| i.to_string())
-        .collect::<FxHashSet<_>>();
+    for (profile_name, file_paths) in files_by_profile {
+        println!("\n{} ({} files)", profile_name, file_paths.len());
+
+        for file_path in file_paths {
+            println!("  {}", file_path);
+        }
+    }
+}
+
+fn get_type_coverage_text(type_coverage: &BTreeMap<String, TypeCoverage>) -> String {
+    let mut lines = type_coverage
+        .iter()
+        .map(|(file_path, coverage)| {
+            format!(
+                "{}\t{}/{}\t{:.2}%",
+                file_path,
+                coverage.typed_count,
+                coverage.typed_count + coverage.mixed_count,
+                coverage.get_typed_percentage()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let total_coverage = get_total_type_coverage(type_coverage);
+
+    lines.push(format!(
+        "\nHakana inferred a non-mixed type for {:.2}% of expressions ({} of {})",
+        total_coverage.get_typed_percentage(),
+        total_coverage.typed_count,
+        total_coverage.typed_count + total_coverage.mixed_count
+    ));
+
+    lines.join("\n")
+}
+
+fn get_total_type_coverage(type_coverage: &BTreeMap<String, TypeCoverage>) -> TypeCoverage {
+    let mut total_coverage = TypeCoverage::default();
+
+    for coverage in type_coverage.values() {
+        total_coverage.add(coverage);
+    }
+
+    total_coverage
+}
+
+fn update_config_from_file(config: &mut config::Config, cwd: &String, config_path: &Path) {
+    println!("Loading config from {:?}", config_path);
+
+    if let Err(error) = config.update_from_file(cwd, config_path) {
+        println!("{}", error);
+        exit(1);
+    }
+}
+
+fn get_absolute_path(path: String, cwd: &String) -> String {
+    if path.starts_with("/") {
+        path
+    } else {
+        format!("{}/{}", cwd, path)
+    }
+}
+
+fn get_taint_flow_ids(analysis_result: &AnalysisResult) -> BTreeSet<String> {
+    analysis_result
+        .emitted_issues
+        .values()
+        .flatten()
+        .filter_map(|issue| issue.taint_flow_id.clone())
+        .collect()
+}
+
+fn write_taint_baseline(baseline_file: String, cwd: &String, analysis_result: &AnalysisResult) {
+    let baseline_path = get_absolute_path(baseline_file, cwd);
+
+    let mut baseline_file = fs::File::create(Path::new(&baseline_path)).unwrap();
+    let baseline_json = serde_json::to_string_pretty(&get_taint_flow_ids(analysis_result)).unwrap();
+
+    write!(baseline_file, "{}", baseline_json).unwrap();
+}
+
+fn write_taint_traces(trace_file: String, cwd: &String, analysis_result: &AnalysisResult) {
+    let trace_path = get_absolute_path(trace_file, cwd);
+
+    let mut trace_entries = vec![];
+
+    for (file_path, issues) in &analysis_result.emitted_issues {
+        for issue in issues {
+            if let Some(trace_entry) = TaintTraceEntry::from_issue(issue, file_path) {
+                trace_entries.push(trace_entry);
+            }
+        }
+    }
+
+    let mut trace_file = fs::File::create(Path::new(&trace_path)).unwrap();
+    let trace_json = get_report_json(analysis_result, "traces", &trace_entries);
+
+    write!(trace_file, "{}", trace_json).unwrap();
+}
+
+// Removes any taint issues whose flows were already present in the baseline revision,
+// returning the number of issues removed.
+fn remove_baseline_taint_flows(
+    baseline_file: String,
+    cwd: &String,
+    analysis_result: &mut AnalysisResult,
+) -> usize {
+    let baseline_path = get_absolute_path(baseline_file, cwd);
+
+    let baseline_contents = fs::read_to_string(&baseline_path).unwrap_or_else(|_| {
+        println!(
+            "\nERROR: File {} does not exist or could not be read\n",
+            baseline_path
+        );
+        exit(1);
+    });
+
+    let existing_flow_ids = serde_json::from_str::<FxHashSet<String>>(&baseline_contents)
+        .unwrap_or_else(|e| {
+            println!("\nERROR: Invalid baseline {}: {}\n", baseline_path, e);
+            exit(1);
+        });
+
+    remove_taint_flows(&existing_flow_ids, analysis_result)
+}
+
+// A flow is identified by its source and sink, along with the functions (or files) they're
+// in and the sink type, so an existing flow doesn't hide a new one between the same kinds
+// of source and sink elsewhere
+fn remove_taint_flows(
+    existing_flow_ids: &FxHashSet<String>,
+    analysis_result: &mut AnalysisResult,
+) -> usize {
+    let mut hidden_count = 0;
+
+    for issues in analysis_result.emitted_issues.values_mut() {
+        issues.retain(|issue| {
+            if let Some(taint_flow_id) = &issue.taint_flow_id {
+                if existing_flow_ids.contains(taint_flow_id) {
+                    hidden_count += 1;
+                    return false;
+                }
+            }
+
+            true
+        });
+    }
+
+    analysis_result
+        .emitted_issues
+        .retain(|_, issues| !issues.is_empty());
+
+    hidden_count
+}
+
+fn get_liveness_explanation_text(explanation: &LivenessExplanation) -> String {
+    if explanation.chain.is_empty() {
+        return if let Some(kept_by) = &explanation.kept_by {
+            format!(
+                "{} is always kept: {}",
+                explanation.symbol,
+                get_liveness_rule_description(kept_by)
+            )
+        } else {
+            format!("{} is not referenced anywhere", explanation.symbol)
+        };
+    }
+
+    let mut lines = vec![format!("{} is kept alive by:", explanation.symbol)];
+
+    for link in &explanation.chain {
+        lines.push(format!(
+            "  {} ({})",
+            link.referencing_symbol,
+            match link.reference_kind {
+                ReferenceKind::Body => "referenced in its body",
+                ReferenceKind::Signature => "referenced in its signature",
+                ReferenceKind::Override => "calls a method that overrides it",
+                ReferenceKind::Dynamic => "calls to it can dispatch here",
+            }
+        ));
+    }
+
+    if let Some(kept_by) = &explanation.kept_by {
+        lines.push(format!(
+            "which is always kept: {}",
+            get_liveness_rule_description(kept_by)
+        ));
+    } else {
+        lines.push("which is itself unused, so is reported instead".to_string());
+    }
 
-    let matches = Command::new("hakana")
+    lines.join("\n")
+}
+
+fn get_liveness_rule_description(rule: &LivenessRule) -> &'static str {
+    match rule {
+        LivenessRule::DynamicallyCallable => "it can be called dynamically",
+        LivenessRule::Generated => "it is generated",
+        LivenessRule::IgnoredPath => "its file is ignored",
+        LivenessRule::NotUserDefined => "it is not defined in this project",
+        LivenessRule::Suppressed => "unused issues are suppressed for it",
+        LivenessRule::ExtendsExternalClass => "it extends a class defined outside this project",
+        LivenessRule::MagicMethod => "it is a magic method",
+        LivenessRule::NotChecked => "symbols of its kind are not checked for usage",
+    }
+}
+
+// Kept out of init so that tests can parse arguments with the same definitions
+fn get_command() -> Command<'static> {
+    Command::new("hakana")
         .about("Another static analysis tool for Hack")
         .subcommand_required(true)
         .arg_required_else_help(true)
@@ -81,11 +1415,20 @@ pub fn init(
                         .required(false)
                         .help("Find unused definitions — classes, functions, methods etc."),
                 )
+                .arg(
+                    arg!(--"list-unused-symbols" <FORMAT>)
+                        .required(false)
+                        .possible_values(["json"])
+                        .help("Output unused definitions and whether they are safe to delete"),
+                )
                 .arg(
                     arg!(--"show-issue" <PATH>)
                         .required(false)
                         .multiple(true)
-                        .help("Only output issues of this/these type(s)"),
+                        .help(
+                            "Only output issues of this/these type(s), skipping analysis that \
+                            can't find them",
+                        ),
                 )
                 .arg(
                     arg!(--"ignore-mixed-issues")
@@ -97,6 +1440,28 @@ pub fn init(
                         .required(false)
                         .help("Show which functions we lead to mixed types"),
                 )
+                .arg(
+                    arg!(--"type-coverage" <FORMAT>)
+                        .required(false)
+                        .possible_values(["text", "json"])
+                        .help("Report how many inferred expression types are mixed, per file and overall"),
+                )
+                .arg(
+                    arg!(--"shape-report")
+                        .required(false)
+                        .help("Suggest value classes for shapes that are passed around like records"),
+                )
+                .arg(
+                    arg!(--"shape-report-min-callers" <COUNT>)
+                        .required(false)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("How many functions need to call the functions taking a shape for it to be suggested (default 3)"),
+                )
+                .arg(
+                    arg!(--"show-issue-profiles")
+                        .required(false)
+                        .help("List the files that use each issue profile"),
+                )
                 .arg(
                     arg!(--"show-symbol-map")
                         .required(false)
@@ -122,10 +1487,60 @@ pub fn init(
                         .required(false)
                         .help("Output a summary of issue counts"),
                 )
+                .arg(
+                    arg!(--"fix")
+                        .required(false)
+                        .help("Apply fixes for the issue kinds listed in safe_fix_issues"),
+                )
+                .arg(
+                    arg!(--"effects-diff")
+                        .required(false)
+                        .help("Report functions that have gained effects since the cached codebase from the previous run"),
+                )
+                .arg(
+                    arg!(--"issue-changes")
+                        .required(false)
+                        .help("Report which issues are new, fixed or unchanged since the issues cached by the previous run"),
+                )
+                .arg(
+                    arg!(--"compare-config" <PATH>)
+                        .required(false)
+                        .help("Report how issue counts would change under another hakana.json"),
+                )
                 .arg(
                     arg!(--"output" <PATH>)
                         .required(false)
                         .help("File to save output to"),
+                )
+                .arg(
+                    arg!(--"dependency-manifest" <PATH>)
+                        .required(false)
+                        .help("File to save per-file dependency digests to"),
+                )
+                .arg(arg!(--"topological-order").required(false).help(
+                    "Analyze files after the files they depended on in the previous run",
+                ))
+                .arg(arg!(--"publish-remote-cache").required(false).help(
+                    "Upload the codebase cache to the remote_cache_url in hakana.json",
+                ))
+                .arg(
+                    arg!(--"trend-database" <PATH>)
+                        .required(false)
+                        .help("SQLite database to append issue counts and timings from this run to"),
+                )
+                .arg(
+                    arg!(--"max-issues" <COUNT>)
+                        .required(false)
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Stop analyzing files once this many issues have been found"),
+                )
+                .arg(arg!(--"dependency-root" <PATH>).required(false).help(
+                    "Root of a previous full run whose cached codebase resolves symbols outside --root",
+                ))
+                .arg(
+                    arg!(--"watch")
+                        .required(false)
+                        .help("Re-analyze whenever files change, printing new and fixed issues"),
                 ),
         )
         .subcommand(
@@ -215,6 +1630,90 @@ pub fn init(
                         .help("Add output for debugging"),
                 ),
         )
+        .subcommand(
+            Command::new("remove-unused-files")
+                .about("Removes files where every definition is unused")
+                .arg(arg!(--"root" <PATH>).required(false).help(
+                    "The root directory that Hakana runs in. Defaults to the current directory",
+                ))
+                .arg(
+                    arg!(--"config" <PATH>)
+                        .required(false)
+                        .help("Hakana config path — defaults to ./hakana.json"),
+                )
+                .arg(
+                    arg!(--"threads" <PATH>)
+                        .required(false)
+                        .help("How many threads to use"),
+                )
+                .arg(
+                    arg!(--"debug")
+                        .required(false)
+                        .help("Add output for debugging"),
+                ),
+        )
+        .subcommand(
+            Command::new("explain-liveness")
+                .about("Explains why a function, class or method isn't reported as unused")
+                .arg(arg!(--"root" <PATH>).required(false).help(
+                    "The root directory that Hakana runs in. Defaults to the current directory",
+                ))
+                .arg(
+                    arg!(--"config" <PATH>)
+                        .required(false)
+                        .help("Hakana config path — defaults to ./hakana.json"),
+                )
+                .arg(
+                    arg!(--"symbol" <NAME>)
+                        .required(true)
+                        .help("The symbol to explain, e.g. Foo\\bar or Foo\\Bar::baz"),
+                )
+                .arg(
+                    arg!(--"json")
+                        .required(false)
+                        .help("Output the explanation as JSON"),
+                )
+                .arg(
+                    arg!(--"threads" <PATH>)
+                        .required(false)
+                        .help("How many threads to use"),
+                )
+                .arg(
+                    arg!(--"debug")
+                        .required(false)
+                        .help("Add output for debugging"),
+                ),
+        )
+        .subcommand(
+            Command::new("typed-ast")
+                .about("Outputs every expression in a file with its inferred type, as JSON")
+                .arg(arg!(--"root" <PATH>).required(false).help(
+                    "The root directory that Hakana runs in. Defaults to the current directory",
+                ))
+                .arg(
+                    arg!(--"config" <PATH>)
+                        .required(false)
+                        .help("Hakana config path — defaults to ./hakana.json"),
+                )
+                .arg(
+                    arg!(--"file" <PATH>)
+                        .required(true)
+                        .help("The file to output the typed AST of"),
+                )
+                .arg(arg!(--"conformance").required(false).help(
+                    "Instead of the typed AST, output where the inferred types differ from those the Hack typechecker reports",
+                ))
+                .arg(
+                    arg!(--"hh-client" <PATH>)
+                        .required(false)
+                        .help("The hh_client binary to use with --conformance — defaults to hh_client"),
+                )
+                .arg(
+                    arg!(--"threads" <PATH>)
+                        .required(false)
+                        .help("How many threads to use"),
+                ),
+        )
         .subcommand(
             Command::new("fix")
                 .about("Fixes issues in the codebase")
@@ -277,6 +1776,42 @@ pub fn init(
                     arg!(--"output" <PATH>)
                         .required(false)
                         .help("File to save output to"),
+                )
+                .arg(
+                    arg!(--"save-baseline" <PATH>)
+                        .required(false)
+                        .help("File to save the taint flows found in this revision to"),
+                )
+                .arg(
+                    arg!(--"baseline" <PATH>)
+                        .required(false)
+                        .help("Only report taint flows not found in this saved baseline"),
+                )
+                .arg(
+                    arg!(--"checkpoint")
+                        .required(false)
+                        .help("Save the dataflow graph before looking for taint paths, so a later run can --resume from it"),
+                )
+                .arg(
+                    arg!(--"resume")
+                        .required(false)
+                        .help("Only look for taint paths in the graph saved by the last --checkpoint run"),
+                )
+                .arg(
+                    arg!(--"trace-output" <PATH>)
+                        .required(false)
+                        .help("File to save each taint issue's source-to-sink trace to, as JSON"),
+                )
+                .arg(
+                    arg!(--"graph-output" <PATH>)
+                        .required(false)
+                        .help("File to save the dataflow graph to, in GraphViz DOT format"),
+                )
+                .arg(
+                    arg!(--"graph-source" <PATH>)
+                        .required(false)
+                        .multiple(true)
+                        .help("Only include the graph reachable from sources with this in their name"),
                 ),
         )
         .subcommand(
@@ -298,7 +1833,47 @@ pub fn init(
                 .arg(
                     arg!(--"max-depth" <PATH>)
                         .required(false)
-                        .help("Length of the longest allowable path"),
+                        .help("Length of the longest allowable path"),
+                )
+                .arg(
+                    arg!(--"debug")
+                        .required(false)
+                        .help("Add output for debugging"),
+                )
+                .arg(
+                    arg!(--"graph-output" <PATH>)
+                        .required(false)
+                        .help("File to save the dataflow graph to, in GraphViz DOT format"),
+                )
+                .arg(
+                    arg!(--"graph-source" <PATH>)
+                        .required(false)
+                        .multiple(true)
+                        .help("Only include the graph reachable from sources with this in their name"),
+                ),
+        )
+        .subcommand(
+            Command::new("stubs")
+                .about("Generates .hhi stubs for third-party code, without function bodies")
+                .arg(
+                    arg!(--"vendor-dir" <PATH>)
+                        .required(true)
+                        .help("The directory containing the code to generate stubs for"),
+                )
+                .arg(
+                    arg!(--"output" <PATH>)
+                        .required(true)
+                        .help("The directory to write stubs to, mirroring the vendor directory"),
+                )
+                .arg(
+                    arg!(--"config" <PATH>)
+                        .required(false)
+                        .help("Hakana config path — defaults to ./hakana.json"),
+                )
+                .arg(
+                    arg!(--"threads" <PATH>)
+                        .required(false)
+                        .help("How many threads to use"),
                 )
                 .arg(
                     arg!(--"debug")
@@ -306,6 +1881,18 @@ pub fn init(
                         .help("Add output for debugging"),
                 ),
         )
+        .subcommand(
+            Command::new("language-server")
+                .about("Runs a language server, talking to an editor over stdio")
+                .arg(arg!(--"root" <PATH>).required(false).help(
+                    "The root directory that Hakana runs in. Defaults to the current directory",
+                ))
+                .arg(
+                    arg!(--"config" <PATH>)
+                        .required(false)
+                        .help("Hakana config path — defaults to ./hakana.json"),
+                ),
+        )
         .subcommand(
             Command::new("test")
                 .about("Runs one or more Hakana tests")
@@ -320,534 +1907,80 @@ pub fn init(
                         .help("Whether to show debug output"),
                 )
                 .arg(
-                    arg!(--"repeat" <COUNT>)
-                        .required(false)
-                        .help("How many times to repeat the test (useful for profiling)"),
-                )
-                .arg(arg!(<TEST> "The test to run"))
-                .arg_required_else_help(true),
-        )
-        .get_matches();
-
-    let cwd = (env::current_dir()).unwrap().to_str().unwrap().to_string();
-
-    let threads = match matches.subcommand() {
-        Some(("test", _)) => 1,
-        Some((_, sub_matches)) => {
-            if let Some(val) = sub_matches.value_of("threads").map(|f| f.to_string()) {
-                val.parse::<u8>().unwrap()
-            } else {
-                8
-            }
-        }
-        _ => 8,
-    };
-
-    let verbosity = match matches.subcommand() {
-        Some(("test", sub_matches)) => {
-            if sub_matches.is_present("debug") {
-                Verbosity::Debugging
-            } else {
-                Verbosity::Quiet
-            }
-        }
-        Some((_, sub_matches)) => {
-            if sub_matches.is_present("debug") {
-                Verbosity::Debugging
-            } else {
-                Verbosity::Simple
-            }
-        }
-        _ => Verbosity::Simple,
-    };
-
-    let root_dir = match matches.subcommand() {
-        Some(("test", _)) => cwd.as_str().to_string(),
-        Some((_, sub_matches)) => sub_matches
-            .value_of("root")
-            .unwrap_or(cwd.as_str())
-            .to_string(),
-        _ => panic!(),
-    };
-
-    let config_path = match matches.subcommand() {
-        Some(("test", _)) => None,
-        Some((_, sub_matches)) => Some(
-            sub_matches
-                .value_of("config")
-                .unwrap_or(format!("{}/hakana.json", root_dir).as_str())
-                .to_string(),
-        ),
-        _ => panic!(),
-    };
-
-    let config_path = if let Some(config_path) = &config_path {
-        Some(Path::new(config_path))
-    } else {
-        None
-    };
-
-    let cache_dir = format!("{}/.hakana_cache", root_dir);
-
-    if !Path::new(&cache_dir).is_dir() && fs::create_dir(&cache_dir).is_err() {
-        panic!("could not create aast cache directory");
-    }
-
-    let mut had_error = false;
-
-    match matches.subcommand() {
-        Some(("analyze", sub_matches)) => {
-            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
-
-            let output_file = sub_matches.value_of("output").map(|f| f.to_string());
-
-            let ignored = sub_matches
-                .values_of("ignore")
-                .map(|values| values.map(|f| f.to_string()).collect::<FxHashSet<_>>());
-            let find_unused_expressions = sub_matches.is_present("find-unused-expressions");
-            let find_unused_definitions = sub_matches.is_present("find-unused-definitions");
-            let show_mixed_function_counts = sub_matches.is_present("show-mixed-function-counts");
-            let show_symbol_map = sub_matches.is_present("show-symbol-map");
-            let ignore_mixed_issues = sub_matches.is_present("ignore-mixed-issues");
-            let show_issue_stats = sub_matches.is_present("show-issue-stats");
-            let do_ast_diff = sub_matches.is_present("diff");
-
-            let mut issue_kinds_filter = FxHashSet::default();
-
-            let filter_issue_strings = sub_matches
-                .values_of("show-issue")
-                .map(|values| values.collect::<FxHashSet<_>>());
-
-            if let Some(filter_issue_strings) = filter_issue_strings {
-                for filter_issue_string in filter_issue_strings {
-                    if let Ok(issue_kind) =
-                        IssueKind::from_str_custom(filter_issue_string, &all_custom_issues)
-                    {
-                        issue_kinds_filter.insert(issue_kind);
-                    } else {
-                        println!("Invalid issue type {}", filter_issue_string);
-                        exit(1);
-                    }
-                }
-            }
-
-            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
-            config.find_unused_expressions = find_unused_expressions;
-            config.find_unused_definitions = find_unused_definitions;
-            config.ignore_mixed_issues = ignore_mixed_issues;
-            config.ast_diff = do_ast_diff;
-
-            config.hooks = analysis_hooks;
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-
-            // do this after we've loaded from file, as they can be overridden
-            if !issue_kinds_filter.is_empty() {
-                config.allowed_issues = Some(issue_kinds_filter);
-            }
-
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                filter,
-                ignored,
-                Arc::new(config),
-                if sub_matches.is_present("no-cache") {
-                    None
-                } else {
-                    Some(&cache_dir)
-                },
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-
-            if let Ok(analysis_result) = result {
-                for (file_path, issues) in &analysis_result.emitted_issues {
-                    for issue in issues {
-                        had_error = true;
-                        println!("{}", issue.format(&file_path));
-                    }
-                }
-
-                if !had_error {
-                    println!("\nNo issues reported!\n");
-                }
-
-                if let Some(output_file) = output_file {
-                    write_output_files(output_file, &cwd, &analysis_result);
-                }
-
-                if show_issue_stats {
-                    let mut issues_by_kind = analysis_result
-                        .issue_counts
-                        .into_iter()
-                        .collect::<IndexMap<_, _>>();
-                    issues_by_kind.sort_by(|_, a, _, b| b.cmp(a));
-
-                    for (issue, count) in issues_by_kind {
-                        println!("{}\t{}", issue.to_string(), count);
-                    }
-                }
-
-                if show_symbol_map {
-                    println!("{:#?}", analysis_result.symbol_references);
-                }
-
-                if show_mixed_function_counts {
-                    let mut mixed_sources = analysis_result
-                        .mixed_source_counts
-                        .iter()
-                        .map(|(k, v)| format!("{}\t{}", k, v.len()))
-                        .collect::<Vec<_>>();
-
-                    mixed_sources.sort();
-
-                    println!("{}", mixed_sources.join("\n"));
-                }
-            }
-        }
-        Some(("security-check", sub_matches)) => {
-            let mut config = config::Config::new(cwd.clone(), all_custom_issues);
-            config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Taint);
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-            config.allowed_issues = None;
-
-            let output_file = sub_matches.value_of("output").map(|f| f.to_string());
-
-            config.security_config.max_depth =
-                if let Some(val) = sub_matches.value_of("max-depth").map(|f| f.to_string()) {
-                    val.parse::<u8>().unwrap()
-                } else {
-                    20
-                };
-
-            config.hooks = analysis_hooks;
-
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                None,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-            if let Ok(analysis_result) = result {
-                for (file_path, issues) in &analysis_result.emitted_issues {
-                    for issue in issues {
-                        had_error = true;
-                        println!("{}", issue.format(&file_path));
-                    }
-                }
-
-                if !had_error {
-                    println!("\nNo security issues found!\n");
-                }
-
-                if let Some(output_file) = output_file {
-                    write_output_files(output_file, &cwd, &analysis_result);
-                }
-            }
-        }
-        Some(("find-paths", sub_matches)) => {
-            let mut config = config::Config::new(cwd.clone(), all_custom_issues);
-            config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Query);
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-            config.allowed_issues = None;
+                    arg!(--"repeat" <COUNT>)
+                        .required(false)
+                        .help("How many times to repeat the test (useful for profiling)"),
+                )
+                .arg(arg!(<TEST> "The test to run"))
+                .arg_required_else_help(true),
+        )
+}
 
-            config.security_config.max_depth =
-                if let Some(val) = sub_matches.value_of("max-depth").map(|f| f.to_string()) {
-                    val.parse::<u8>().unwrap()
-                } else {
-                    20
-                };
+// Returns the number of edits made to each file, with deleted files counting as one edit
+fn update_files(analysis_result: &AnalysisResult, root_dir: &String) -> BTreeMap<String, usize> {
+    let mut file_edit_counts = BTreeMap::new();
 
-            config.hooks = analysis_hooks;
+    for (filename, replacements) in &analysis_result.replacements {
+        let file_path = format!("{}/{}", root_dir, filename);
 
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                None,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-            if let Ok(analysis_result) = result {
-                for (file_path, issues) in analysis_result.emitted_issues {
-                    for issue in issues {
-                        had_error = true;
-                        println!("{}", issue.format(&file_path));
-                    }
-                }
+        if replacements
+            .values()
+            .any(|replacement| matches!(replacement, Replacement::DeleteFile))
+        {
+            println!("deleting {}", filename);
 
-                if !had_error {
-                    println!("\nNo security issues found!\n");
+            match fs::remove_file(&file_path) {
+                Ok(()) => {
+                    file_edit_counts.insert(filename.clone(), replacements.len());
                 }
-            }
-        }
-        Some(("migrate", sub_matches)) => {
-            let migration_name = sub_matches.value_of("migration").unwrap().to_string();
-            let migration_source = sub_matches.value_of("symbols").unwrap().to_string();
-
-            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
-            config.hooks = migration_hooks
-                .into_iter()
-                .filter(|m| {
-                    if let Some(name) = m.get_migration_name() {
-                        migration_name == name
-                    } else {
-                        false
-                    }
-                })
-                .collect();
-
-            if config.hooks.is_empty() {
-                println!("Migration {} not recognised", migration_name);
-                exit(1);
-            }
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-            config.allowed_issues = None;
-
-            let file_path = format!("{}/{}", cwd, migration_source);
-
-            let buf = fs::read_to_string(file_path.clone());
-
-            if let Ok(contents) = buf {
-                config.migration_symbols = contents
-                    .lines()
-                    .map(|v| (migration_name.clone(), v.to_string()))
-                    .collect();
-            } else {
-                println!(
-                    "\nERROR: File {} does not exist or could not be read\n",
-                    file_path
-                );
-                exit(1);
-            }
-
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                None,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-
-            if let Ok(analysis_result) = result {
-                update_files(analysis_result, &root_dir);
-            }
-        }
-        Some(("add-fixmes", sub_matches)) => {
-            let filter_issue_strings = sub_matches
-                .values_of("issue")
-                .map(|values| values.collect::<FxHashSet<_>>());
-
-            let mut issue_kinds_filter = FxHashSet::default();
-
-            if let Some(filter_issue_strings) = filter_issue_strings {
-                for filter_issue_string in filter_issue_strings {
-                    if let Ok(issue_kind) =
-                        IssueKind::from_str_custom(filter_issue_string, &all_custom_issues)
-                    {
-                        issue_kinds_filter.insert(issue_kind);
-                    } else {
-                        println!("Invalid issue type {}", filter_issue_string);
-                        exit(1);
-                    }
+                Err(error) => {
+                    println!("Could not delete file {}: {}", &file_path, error);
                 }
             }
 
-            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
-
-            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
-
-            config.issues_to_fix.extend(issue_kinds_filter);
-            config.hooks = analysis_hooks;
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-            config.allowed_issues = None;
-
-            config.add_fixmes = true;
-
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                filter,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-
-            if let Ok(analysis_result) = result {
-                update_files(analysis_result, &root_dir);
-            }
-        }
-        Some(("remove-unused-fixmes", sub_matches)) => {
-            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
-
-            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
-
-            config.hooks = analysis_hooks;
-
-            let config_path = config_path.unwrap();
-
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
-            config.allowed_issues = None;
-
-            config.remove_fixmes = true;
-
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                filter,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
-
-            if let Ok(analysis_result) = result {
-                update_files(analysis_result, &root_dir);
-            }
+            continue;
         }
-        Some(("fix", sub_matches)) => {
-            let issue_name = sub_matches.value_of("issue").unwrap().to_string();
-            let issue_kind = IssueKind::from_str_custom(&issue_name, &all_custom_issues).unwrap();
-
-            let filter = sub_matches.value_of("filter").map(|f| f.to_string());
-
-            let mut config = config::Config::new(root_dir.clone(), all_custom_issues);
-            config.find_unused_expressions = issue_kind.is_unused_expression();
-            config.find_unused_definitions = issue_kind.is_unused_definition();
-            config.issues_to_fix.insert(issue_kind);
-
-            let config_path = config_path.unwrap();
 
-            if config_path.exists() {
-                config.update_from_file(&cwd, config_path);
-            }
+        file_edit_counts.insert(filename.clone(), replacements.len());
 
-            config.allowed_issues = None;
+        println!("updating {}", filename);
 
-            let result = hakana_workhorse::scan_and_analyze(
-                true,
-                Vec::new(),
-                filter,
-                None,
-                Arc::new(config),
-                None,
-                threads,
-                verbosity,
-                &header,
-                None,
-            );
+        let file_contents = fs::read_to_string(&file_path).unwrap();
 
-            if let Ok(analysis_result) = result {
-                update_files(analysis_result, &root_dir);
-            }
-        }
-        Some(("test", sub_matches)) => {
-            let repeat = if let Some(val) = sub_matches.value_of("repeat").map(|f| f.to_string()) {
-                val.parse::<u16>().unwrap()
-            } else {
-                0
-            };
-            test_runner.run_test(
-                sub_matches.value_of("TEST").expect("required").to_string(),
-                verbosity,
-                !sub_matches.is_present("no-cache"),
-                &mut had_error,
-                header,
-                repeat,
-            );
-        }
-        _ => unreachable!(), // If all subcommands are defined above, anything else is unreachabe!()
+        write_file_atomically(&file_path, &replace_contents(file_contents, replacements))
+            .unwrap_or_else(|_| panic!("Could not write file {}", &file_path));
     }
 
-    if had_error {
-        exit(1);
-    }
+    file_edit_counts
 }
 
-fn write_output_files(output_file: String, cwd: &String, analysis_result: &AnalysisResult) {
-    if output_file.ends_with("checkpoint_results.json") {
-        let output_path = if output_file.starts_with("/") {
-            output_file
-        } else {
-            format!("{}/{}", cwd, output_file)
-        };
-        let mut output_path = fs::File::create(Path::new(&output_path)).unwrap();
-        let mut checkpoint_entries = vec![];
-
-        for (file_path, issues) in &analysis_result.emitted_issues {
-            for issue in issues {
-                checkpoint_entries.push(CheckPointEntry::from_issue(issue, &file_path));
-            }
-        }
+fn get_fix_summary(file_edit_counts: &BTreeMap<String, usize>) -> String {
+    if file_edit_counts.is_empty() {
+        return "No fixes applied".to_string();
+    }
 
-        let checkpoint_json = serde_json::to_string_pretty(&checkpoint_entries).unwrap();
+    let mut summary = format!("Applied fixes to {} files:", file_edit_counts.len());
 
-        write!(output_path, "{}", checkpoint_json).unwrap();
+    for (filename, edit_count) in file_edit_counts {
+        summary += &format!(
+            "\n  {} ({} edit{})",
+            filename,
+            edit_count,
+            if *edit_count == 1 { "" } else { "s" }
+        );
     }
-}
 
-fn update_files(analysis_result: AnalysisResult, root_dir: &String) {
-    for (filename, replacements) in &analysis_result.replacements {
-        println!("updating {}", filename);
+    summary
+}
 
-        let file_path = format!("{}/{}", root_dir, filename);
+// Writes to a temporary file that's then moved into place, so that an interrupted
+// run never leaves a file half-written
+fn write_file_atomically(file_path: &String, contents: &str) -> io::Result<()> {
+    let temp_path = format!("{}.hakana_tmp", file_path);
 
-        let file_contents = fs::read_to_string(&file_path).unwrap();
-        let mut file = File::create(&file_path).unwrap();
-        file.write_all(replace_contents(file_contents, replacements).as_bytes())
-            .unwrap_or_else(|_| panic!("Could not write file {}", &file_path));
-    }
+    fs::write(&temp_path, contents)?;
+    fs::set_permissions(&temp_path, fs::metadata(file_path)?.permissions())?;
+    fs::rename(&temp_path, file_path)
 }
 
 fn replace_contents(
@@ -881,8 +2014,14 @@ fn replace_contents(
                     + string
                     + &*file_contents[*end..].to_string();
             }
+            Replacement::DeleteFile => {
+                return String::new();
+            }
         }
     }
 
     file_contents
 }
+
+#[cfg(test)]
+mod tests;
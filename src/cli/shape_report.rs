@@ -0,0 +1,193 @@
+use hakana_reflection_info::analysis_result::{AnalysisResult, ShapeParam};
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::StrId;
+use rustc_hash::FxHashSet;
+use std::collections::BTreeMap;
+
+// Suggests shapes that are passed around like records for migration to value classes.
+// A shape is suggested when the functions taking it are called from at least
+// min_callers other functions, and every one of them that reads fields from the shape
+// reads the same ones.
+pub(crate) fn print_shape_report(analysis_result: &AnalysisResult, min_callers: usize) {
+    let mut shape_params_by_fields = BTreeMap::new();
+
+    for shape_param in &analysis_result.shape_params {
+        shape_params_by_fields
+            .entry(&shape_param.fields)
+            .or_insert_with(Vec::new)
+            .push(shape_param);
+    }
+
+    let mut candidates = vec![];
+
+    for (fields, shape_params) in shape_params_by_fields {
+        let accessed_field_sets = shape_params
+            .iter()
+            .map(|shape_param| &shape_param.accessed_fields)
+            .filter(|accessed_fields| !accessed_fields.is_empty())
+            .collect::<FxHashSet<_>>();
+
+        if accessed_field_sets.len() != 1 {
+            continue;
+        }
+
+        let caller_count = get_caller_count(analysis_result, &shape_params);
+
+        if caller_count >= min_callers {
+            candidates.push((
+                fields,
+                shape_params,
+                accessed_field_sets.into_iter().next().unwrap(),
+                caller_count,
+            ));
+        }
+    }
+
+    if candidates.is_empty() {
+        println!("\nNo shapes were found that are used like records");
+        return;
+    }
+
+    candidates.sort_by(|a, b| b.3.cmp(&a.3));
+
+    for (fields, shape_params, accessed_fields, caller_count) in candidates {
+        let name = get_suggested_name(&shape_params);
+        let shape = get_shape_string(fields);
+
+        println!("\n{}: {}", name, shape);
+        println!(
+            "  taken by {} signatures, called from {} functions, which read: {}",
+            shape_params.len(),
+            caller_count,
+            accessed_fields
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        println!("  suggested type alias:");
+        println!("    type {} = {};", name, shape);
+
+        println!("  suggested value class:");
+        println!("    final class {} {{", name);
+        println!("      public function __construct(");
+
+        // optional fields become parameters with defaults, which have to come last
+        let mut sorted_fields = fields.iter().collect::<Vec<_>>();
+        sorted_fields.sort_by_key(|(_, (possibly_undefined, _))| *possibly_undefined);
+
+        for (field_name, (possibly_undefined, field_type)) in sorted_fields {
+            let property_name = field_name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>();
+
+            if *possibly_undefined {
+                let nullable_type =
+                    if field_type.starts_with('?') || field_type == "mixed" || field_type == "null"
+                    {
+                        field_type.clone()
+                    } else {
+                        format!("?{}", field_type)
+                    };
+
+                println!(
+                    "        public {} ${} = null,",
+                    nullable_type, property_name
+                );
+            } else {
+                println!("        public {} ${},", field_type, property_name);
+            }
+        }
+
+        println!("      ) {{}}");
+        println!("    }}");
+
+        println!("  affected signatures:");
+
+        for shape_param in shape_params {
+            println!(
+                "    {}({}) - {}:{}",
+                shape_param.functionlike_name,
+                shape_param.param_name,
+                shape_param.file_path,
+                shape_param.line
+            );
+        }
+    }
+}
+
+// The number of functions, other than the ones taking the shape, that call them
+fn get_caller_count(analysis_result: &AnalysisResult, shape_params: &Vec<&ShapeParam>) -> usize {
+    let callees = shape_params
+        .iter()
+        .map(|shape_param| match shape_param.functionlike_id {
+            FunctionLikeIdentifier::Function(function_name) => (function_name, StrId::empty()),
+            FunctionLikeIdentifier::Method(classlike_name, method_name) => {
+                (classlike_name, method_name)
+            }
+        })
+        .collect::<FxHashSet<_>>();
+
+    analysis_result
+        .symbol_references
+        .symbol_references_to_symbols
+        .iter()
+        .filter(|(referencing_symbol, referenced_symbols)| {
+            !callees.contains(referencing_symbol)
+                && referenced_symbols
+                    .iter()
+                    .any(|referenced_symbol| callees.contains(referenced_symbol))
+        })
+        .count()
+}
+
+// Named after the parameter most signatures use, e.g. $user_info becomes UserInfo
+fn get_suggested_name(shape_params: &Vec<&ShapeParam>) -> String {
+    let mut param_name_counts = BTreeMap::new();
+
+    for shape_param in shape_params {
+        *param_name_counts
+            .entry(shape_param.param_name.trim_start_matches('$'))
+            .or_insert(0) += 1;
+    }
+
+    let param_name = param_name_counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(param_name, _)| param_name)
+        .unwrap_or("");
+
+    let mut name = String::new();
+    let mut capitalize_next = true;
+
+    for c in param_name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            name.extend(c.to_uppercase());
+            capitalize_next = false;
+        } else {
+            name.push(c);
+        }
+    }
+
+    name
+}
+
+fn get_shape_string(fields: &BTreeMap<String, (bool, String)>) -> String {
+    format!(
+        "shape({})",
+        fields
+            .iter()
+            .map(|(field_name, (possibly_undefined, field_type))| format!(
+                "{}'{}' => {}",
+                if *possibly_undefined { "?" } else { "" },
+                field_name,
+                field_type
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
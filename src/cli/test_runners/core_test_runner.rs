@@ -1,5 +1,23 @@
+use hakana_analyzer::custom_hook::CustomHook;
+use hakana_analyzer::linter::LinterHook;
+
+use super::test_hooks::ReturnsParamHook;
+use super::test_linters::{NoIntConcatLinter, NoVarDumpLinter};
 use super::test_runner::TestRunner;
 
 pub struct CoreTestRunner {}
 
-impl TestRunner for CoreTestRunner {}
+impl TestRunner for CoreTestRunner {
+    fn get_hooks_for_test(&self, dir: &String) -> Vec<Box<dyn CustomHook>> {
+        if dir.contains("/linter/") {
+            vec![
+                Box::new(LinterHook::new(NoVarDumpLinter {})),
+                Box::new(LinterHook::new(NoIntConcatLinter {})),
+            ]
+        } else if dir.contains("/hooks/") {
+            vec![Box::new(ReturnsParamHook {})]
+        } else {
+            vec![]
+        }
+    }
+}
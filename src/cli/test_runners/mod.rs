@@ -1,2 +1,4 @@
 pub mod core_test_runner;
+mod test_hooks;
+mod test_linters;
 pub mod test_runner;
@@ -0,0 +1,75 @@
+use hakana_analyzer::custom_hook::{AfterStmtAnalysisData, CustomHook, InternalHook};
+use hakana_analyzer::typed_ast::TastInfo;
+use hakana_reflection_info::data_flow::node::{DataFlowNodeKind, VariableSourceKind};
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use oxidized::aast;
+
+// Hooks that exercise custom hooks in tests/hooks fixtures
+
+// Reads the function body graph to find returned data that comes from a parameter
+pub(crate) struct ReturnsParamHook {}
+
+impl InternalHook for ReturnsParamHook {
+    fn after_stmt_analysis(
+        &self,
+        tast_info: &mut TastInfo,
+        after_stmt_analysis_data: AfterStmtAnalysisData,
+    ) {
+        let return_expr = if let aast::Stmt_::Return(boxed) = &after_stmt_analysis_data.stmt.1 {
+            if let Some(return_expr) = &**boxed {
+                return_expr
+            } else {
+                return;
+            }
+        } else {
+            return;
+        };
+
+        let mut param_names = vec![];
+
+        if let Some(return_type) = tast_info.get_expr_type(return_expr.pos()) {
+            for parent_node in &return_type.parent_nodes {
+                for origin_node in tast_info.data_flow_graph.get_origin_nodes(parent_node) {
+                    if let DataFlowNodeKind::VariableUseSource {
+                        kind:
+                            VariableSourceKind::PrivateParam
+                            | VariableSourceKind::NonPrivateParam
+                            | VariableSourceKind::InoutParam,
+                        label,
+                        ..
+                    } = origin_node.kind
+                    {
+                        param_names.push(label);
+                    }
+                }
+            }
+        }
+
+        param_names.sort();
+        param_names.dedup();
+
+        let statements_analyzer = after_stmt_analysis_data.statements_analyzer;
+
+        for param_name in param_names {
+            tast_info.maybe_add_issue(
+                Issue::new(
+                    IssueKind::CustomIssue("ReturnsParam".to_string()),
+                    format!("Returned data comes from parameter {}", param_name),
+                    statements_analyzer.get_hpos(return_expr.pos()),
+                    &after_stmt_analysis_data
+                        .context
+                        .function_context
+                        .calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+    }
+
+    fn get_custom_issue_names(&self) -> Vec<&str> {
+        vec!["ReturnsParam"]
+    }
+}
+
+impl CustomHook for ReturnsParamHook {}
@@ -0,0 +1,61 @@
+use hakana_analyzer::linter::{LintContext, LintError, Linter};
+use oxidized::aast;
+use oxidized::ast_defs::Bop;
+
+// Linters that exercise LinterHook in tests/linter fixtures
+
+// Uses the name the call resolves to, not the name as written
+pub(crate) struct NoVarDumpLinter {}
+
+impl Linter for NoVarDumpLinter {
+    fn get_name(&self) -> &str {
+        "NoVarDump"
+    }
+
+    fn lint_expr(&self, expr: &aast::Expr<(), ()>, lint_context: &LintContext) -> Vec<LintError> {
+        if let aast::Expr_::Call(boxed) = &expr.2 {
+            if let aast::Expr_::Id(id) = &boxed.0 .2 {
+                if lint_context.get_resolved_name(&id.0) == Some("var_dump") {
+                    return vec![LintError::new(
+                        "Calls to var_dump shouldn't be committed".to_string(),
+                        &expr.1,
+                    )];
+                }
+            }
+        }
+
+        vec![]
+    }
+}
+
+// Uses the inferred types of each operand
+pub(crate) struct NoIntConcatLinter {}
+
+impl Linter for NoIntConcatLinter {
+    fn get_name(&self) -> &str {
+        "NoIntConcat"
+    }
+
+    fn lint_expr(&self, expr: &aast::Expr<(), ()>, lint_context: &LintContext) -> Vec<LintError> {
+        if let aast::Expr_::Binop(boxed) = &expr.2 {
+            if let (Bop::Dot, left, right) = (&boxed.0, &boxed.1, &boxed.2) {
+                return [left, right]
+                    .into_iter()
+                    .filter(|operand| {
+                        lint_context
+                            .get_expr_type(operand.pos())
+                            .map_or(false, |operand_type| operand_type.is_int())
+                    })
+                    .map(|operand| {
+                        LintError::new(
+                            "Ints should be formatted before they're concatenated".to_string(),
+                            operand.pos(),
+                        )
+                    })
+                    .collect();
+            }
+        }
+
+        vec![]
+    }
+}
@@ -1,13 +1,18 @@
 use hakana_analyzer::config;
 use hakana_analyzer::config::Verbosity;
 use hakana_analyzer::custom_hook::CustomHook;
+use hakana_reflection_info::analysis_result::{AnalysisResult, CheckPointEntry, TaintTraceEntry};
 use hakana_reflection_info::codebase_info::CodebaseInfo;
 use hakana_reflection_info::data_flow::graph::GraphKind;
 use hakana_reflection_info::data_flow::graph::WholeProgramKind;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_reflection_info::issue::IssueKind;
 use hakana_reflection_info::Interner;
-use hakana_workhorse::wasm::get_single_file_codebase;
+use hakana_type::signature_printer::{get_printed_signature, SignaturePrintOptions};
+use hakana_workhorse::wasm::{get_single_file_codebase, scan_and_analyze_single_file};
+use rusqlite::Connection;
 use rustc_hash::FxHashSet;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io;
@@ -47,8 +52,9 @@ pub trait TestRunner {
                     panic!("could not create aast cache directory");
                 }
 
-                let needs_fresh_codebase =
-                    test_folder.contains("xhp") || test_folder.contains("/diff/");
+                let needs_fresh_codebase = test_folder.contains("xhp")
+                    || test_folder.contains("/diff/")
+                    || test_folder.contains("/cache/");
 
                 let test_result = self.run_test_in_dir(
                     test_folder,
@@ -87,12 +93,25 @@ pub trait TestRunner {
     }
 
     fn get_config_for_test(&self, dir: &String) -> config::Config {
-        let mut analysis_config = config::Config::new(dir.clone(), FxHashSet::default());
+        let hooks = self.get_hooks_for_test(dir);
+
+        let all_custom_issues = hooks
+            .iter()
+            .flat_map(|hook| hook.get_custom_issue_names())
+            .map(|i| i.to_string())
+            .collect::<FxHashSet<_>>();
+
+        let mut analysis_config = config::Config::new(dir.clone(), all_custom_issues);
         analysis_config.find_unused_expressions =
             dir.contains("/unused/") || dir.contains("/fix/UnusedAssignment/");
-        analysis_config.find_unused_definitions =
-            dir.contains("/unused/UnusedCode/") || dir.contains("/migrations/unused_symbol/");
-        analysis_config.graph_kind = if dir.contains("/security/") {
+        analysis_config.find_unused_definitions = dir.contains("/unused/UnusedCode/")
+            || dir.contains("/migrations/unused_symbol/")
+            || dir.contains("/remove-unused-files/");
+        analysis_config.graph_kind = if dir.contains("/security/")
+            || dir.contains("/taint-traces/")
+            || dir.contains("/taint-baseline/")
+            || dir.contains("/dataflow-graph/")
+        {
             GraphKind::WholeProgram(WholeProgramKind::Taint)
         } else if dir.contains("/find-paths/") {
             GraphKind::WholeProgram(WholeProgramKind::Query)
@@ -100,7 +119,7 @@ pub trait TestRunner {
             GraphKind::FunctionBody
         };
 
-        analysis_config.hooks = self.get_hooks_for_test(dir);
+        analysis_config.hooks = hooks;
 
         let mut dir_parts = dir.split("/").collect::<Vec<_>>();
 
@@ -133,7 +152,43 @@ pub trait TestRunner {
             analysis_config.add_fixmes = true;
         } else if dir.contains("/remove-unused-fixmes/") {
             analysis_config.remove_fixmes = true;
+        } else if dir.contains("/remove-unused-files/") {
+            analysis_config.remove_unused_files = true;
+        } else if dir.contains("/dependency-digests/") {
+            analysis_config.compute_dependency_digests = true;
+        } else if dir.contains("/type-coverage/") {
+            analysis_config.collect_type_coverage = true;
+        } else if dir.contains("/typed-ast/") {
+            analysis_config.typed_ast_file = Some("input.hack".to_string());
+        } else if dir.contains("/dataflow-graph/") {
+            analysis_config.dataflow_graph_path = Some(get_dataflow_graph_path(dir));
+
+            let sources_path = dir.clone() + "/sources.txt";
+
+            if Path::new(&sources_path).exists() {
+                analysis_config.dataflow_graph_sources = fs::read_to_string(sources_path)
+                    .unwrap()
+                    .lines()
+                    .map(|source| source.to_string())
+                    .collect();
+            }
+        } else if dir.contains("/explain-liveness/") {
+            let symbol_path = dir.clone() + "/symbol.txt";
+
+            analysis_config.find_unused_definitions = true;
+            analysis_config.explain_liveness_of =
+                Some(fs::read_to_string(symbol_path).unwrap().trim().to_string());
         }
+
+        // tests that need extra config can supply it in their own hakana.json
+        let config_path = format!("{}/hakana.json", dir);
+
+        if Path::new(&config_path).exists() {
+            analysis_config
+                .update_from_file(dir, Path::new(&config_path))
+                .unwrap_or_else(|e| panic!("{}", e));
+        }
+
         analysis_config
     }
 
@@ -156,7 +211,170 @@ pub trait TestRunner {
             return "S".to_string();
         }
 
-        if dir.contains("/diff/") {
+        // these check the error an invalid hakana.json produces, so there's nothing to analyze
+        if dir.contains("/config-errors/") {
+            let config_path = format!("{}/hakana.json", dir);
+            let expected_output = fs::read_to_string(format!("{}/output.txt", dir)).unwrap();
+
+            let output = match config::Config::from_file(
+                dir.clone(),
+                FxHashSet::default(),
+                Path::new(&config_path),
+            ) {
+                Ok(_) => "".to_string(),
+                Err(error) => error.to_string(),
+            };
+
+            // each expected line only has to appear somewhere in the error
+            return if !output.is_empty()
+                && expected_output
+                    .lines()
+                    .all(|expected_line| output.contains(expected_line))
+            {
+                ".".to_string()
+            } else {
+                test_diagnostics.push((dir, format!("- {}\n+ {}", expected_output, output)));
+                "F".to_string()
+            };
+        }
+
+        // these print signatures from a codebase built the way editor integrations build theirs
+        if dir.contains("/signatures/") {
+            let (mut codebase, interner) =
+                starter_data.unwrap_or_else(|| get_single_file_codebase(vec![]));
+            codebase.interner = interner;
+
+            scan_and_analyze_single_file(
+                &mut codebase,
+                "input.hack".to_string(),
+                fs::read_to_string(format!("{}/input.hack", dir)).unwrap(),
+                config::Config::new("".to_string(), FxHashSet::default()),
+            )
+            .unwrap();
+
+            let expected_output = fs::read_to_string(format!("{}/output.txt", dir)).unwrap();
+            let symbols = fs::read_to_string(format!("{}/symbol.txt", dir)).unwrap();
+
+            let output = symbols
+                .lines()
+                .map(|symbol| get_signature_output(symbol, &codebase))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            return if output == expected_output.trim() {
+                ".".to_string()
+            } else {
+                test_diagnostics.push((dir, format!("- {}\n+ {}", expected_output, output)));
+                "F".to_string()
+            };
+        }
+
+        // these only report the taint flows in b/ that weren't already in a/
+        if dir.contains("/taint-baseline/") {
+            let cwd = env::current_dir().unwrap().to_str().unwrap().to_string();
+
+            let analyze_revision = |revision: &str| {
+                hakana_workhorse::scan_and_analyze(
+                    starter_data.is_none(),
+                    vec![cwd.clone() + "/test/stubs"],
+                    None,
+                    Some(FxHashSet::from_iter(["tests/stubs/stubs.hack".to_string()])),
+                    Arc::new(self.get_config_for_test(&format!("{}/{}", dir, revision))),
+                    None,
+                    1,
+                    verbosity,
+                    build_checksum,
+                    starter_data.clone(),
+                )
+                .unwrap()
+            };
+
+            let base_result = analyze_revision("a");
+            let mut result = analyze_revision("b");
+
+            crate::remove_taint_flows(
+                &crate::get_taint_flow_ids(&base_result)
+                    .into_iter()
+                    .collect(),
+                &mut result,
+            );
+
+            let test_output = result
+                .emitted_issues
+                .iter()
+                .flat_map(|(file_path, issues)| issues.iter().map(|issue| issue.format(file_path)))
+                .collect::<Vec<_>>();
+
+            let expected_output_path = dir.clone() + "/output.txt";
+            let expected_output = if Path::new(&expected_output_path).exists() {
+                fs::read_to_string(expected_output_path)
+                    .unwrap()
+                    .trim()
+                    .to_string()
+            } else {
+                "".to_string()
+            };
+
+            // like other issue tests, a single expected line can match part of a single issue
+            return if expected_output == test_output.join("").trim()
+                || (expected_output != ""
+                    && test_output.len() == 1
+                    && test_output[0].contains(&expected_output))
+            {
+                ".".to_string()
+            } else {
+                test_diagnostics.push((
+                    dir,
+                    format!("- {}\n+ {}", expected_output, test_output.join("+ ")),
+                ));
+                "F".to_string()
+            };
+        }
+
+        // these report how issue counts would change when switching to other.json
+        if dir.contains("/config-diff/") {
+            let cwd = env::current_dir().unwrap().to_str().unwrap().to_string();
+
+            let mut analysis_config = self.get_config_for_test(&dir);
+
+            let mut other_config =
+                config::Config::new(dir.clone(), analysis_config.all_custom_issues.clone());
+            other_config
+                .update_from_file(&dir, Path::new(&format!("{}/other.json", dir)))
+                .unwrap_or_else(|e| panic!("{}", e));
+
+            let (current_config, other_config) =
+                crate::config_diff::prepare_config_comparison(&mut analysis_config, other_config);
+
+            let result = hakana_workhorse::scan_and_analyze(
+                starter_data.is_none(),
+                vec![cwd.clone() + "/test/stubs"],
+                None,
+                Some(FxHashSet::from_iter(["tests/stubs/stubs.hack".to_string()])),
+                Arc::new(analysis_config),
+                None,
+                1,
+                verbosity,
+                build_checksum,
+                starter_data,
+            )
+            .unwrap();
+
+            *total_time_in_analysis += result.time_in_analysis;
+
+            let output =
+                crate::config_diff::get_config_diff_text(&result, &current_config, &other_config);
+            let expected_output = fs::read_to_string(format!("{}/output.txt", dir)).unwrap();
+
+            return if output == expected_output.trim() {
+                ".".to_string()
+            } else {
+                test_diagnostics.push((dir, format!("- {}\n+ {}", expected_output, output)));
+                "F".to_string()
+            };
+        }
+
+        if dir.contains("/diff/") || dir.contains("/cache/") {
             return self.run_diff_test(
                 dir,
                 verbosity,
@@ -208,6 +426,7 @@ pub trait TestRunner {
             || dir.contains("/fix/")
             || dir.contains("/add-fixmes/")
             || dir.contains("/remove-unused-fixmes/")
+            || dir.contains("/remove-unused-files/")
         {
             let input_file = format!("{}/input.hack", dir);
             let output_file = format!("{}/output.txt", dir);
@@ -235,6 +454,135 @@ pub trait TestRunner {
                 ));
                 "F".to_string()
             };
+        } else if dir.contains("/explain-liveness/")
+            || dir.contains("/dependency-digests/")
+            || dir.contains("/trend-database/")
+            || dir.contains("/issue-metadata/")
+            || dir.contains("/taint-traces/")
+            || dir.contains("/typed-ast/")
+            || dir.contains("/type-coverage/")
+            || dir.contains("/dataflow-graph/")
+        {
+            let output_file = format!("{}/output.txt", dir);
+            let expected_output_contents = fs::read_to_string(&output_file).unwrap();
+
+            let result = result.unwrap();
+
+            *total_time_in_analysis += result.time_in_analysis;
+
+            let output_contents = if dir.contains("/dependency-digests/") {
+                // digests change whenever a file does, so only the dependencies are compared
+                serde_json::to_string_pretty(
+                    &result
+                        .dependency_digests
+                        .iter()
+                        .map(|(file_path, digest)| (file_path, &digest.dependencies))
+                        .collect::<BTreeMap<_, _>>(),
+                )
+                .unwrap()
+            } else if dir.contains("/issue-metadata/") {
+                // the same entries that checkpoint results contain
+                serde_json::to_string_pretty(
+                    &result
+                        .emitted_issues
+                        .iter()
+                        .flat_map(|(file_path, issues)| {
+                            issues
+                                .iter()
+                                .map(|issue| CheckPointEntry::from_issue(issue, file_path))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .unwrap()
+            } else if dir.contains("/taint-traces/") {
+                // labels in the middle of a trace depend on how each expression is modelled,
+                // so only where each trace starts and ends is compared
+                let mut traces = result
+                    .emitted_issues
+                    .iter()
+                    .flat_map(|(file_path, issues)| {
+                        issues
+                            .iter()
+                            .filter_map(|issue| TaintTraceEntry::from_issue(issue, file_path))
+                    })
+                    .map(|trace_entry| {
+                        format!(
+                            "{} -> {}",
+                            trace_entry.trace.first().unwrap().label,
+                            trace_entry.trace.last().unwrap().label
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                traces.sort();
+
+                traces.join("\n")
+            } else if let Some(typed_ast) = &result.typed_ast {
+                typed_ast
+                    .iter()
+                    .map(|node| {
+                        format!(
+                            "{} {}:{} {}",
+                            node.kind,
+                            node.start_line,
+                            node.start_column,
+                            node.inferred_type.as_deref().unwrap_or("-")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else if dir.contains("/type-coverage/") {
+                crate::get_type_coverage_text(&result.type_coverage)
+            } else if dir.contains("/dataflow-graph/") {
+                let dataflow_graph_path = get_dataflow_graph_path(&dir);
+                let dot = fs::read_to_string(&dataflow_graph_path).unwrap();
+                fs::remove_file(dataflow_graph_path).unwrap();
+
+                dot
+            } else if dir.contains("/trend-database/") {
+                get_trend_database_output(&dir, &result)
+            } else if let Some(explanation) = &result.liveness_explanation {
+                crate::get_liveness_explanation_text(explanation)
+            } else {
+                "".to_string()
+            };
+
+            // typed ASTs list every expression, so those tests only list the ones they check
+            let matches_expected_output = if dir.contains("/typed-ast/") {
+                expected_output_contents.lines().all(|expected_line| {
+                    output_contents
+                        .lines()
+                        .any(|output_line| output_line == expected_line)
+                })
+            } else if dir.contains("/dataflow-graph/") {
+                // node ids depend on offsets, so these tests check for parts of the graph,
+                // and optionally for parts that mustn't be there
+                let excluded_path = dir.clone() + "/excluded.txt";
+                let excluded_contents = if Path::new(&excluded_path).exists() {
+                    fs::read_to_string(excluded_path).unwrap()
+                } else {
+                    "".to_string()
+                };
+
+                expected_output_contents
+                    .lines()
+                    .all(|expected_part| output_contents.contains(expected_part))
+                    && !excluded_contents
+                        .lines()
+                        .any(|excluded_part| output_contents.contains(excluded_part))
+            } else {
+                output_contents == expected_output_contents.trim()
+            };
+
+            return if matches_expected_output {
+                ".".to_string()
+            } else {
+                test_diagnostics.push((
+                    dir,
+                    format!("- {}\n+ {}", expected_output_contents, output_contents),
+                ));
+                "F".to_string()
+            };
         } else {
             let test_output = match result {
                 Ok(analysis_result) => {
@@ -324,7 +672,8 @@ pub trait TestRunner {
         copy_recursively(dir.clone() + "/a", workdir_base.clone()).unwrap();
 
         let mut config = self.get_config_for_test(&workdir_base);
-        config.ast_diff = true;
+        // cache tests check the per-file analysis cache, which AST-diff mode doesn't use
+        config.ast_diff = !dir.contains("/cache/");
         config.find_unused_definitions = true;
         let config = Arc::new(config);
 
@@ -435,6 +784,123 @@ pub trait TestRunner {
     }
 }
 
+// Where the analysis writes the graph, in the cache directory the runner creates for each test
+fn get_dataflow_graph_path(dir: &String) -> String {
+    format!("{}/.hakana_cache/dataflow.dot", dir)
+}
+
+// Records the same run twice, so that fixtures also cover appending to an existing database
+fn get_trend_database_output(dir: &String, analysis_result: &AnalysisResult) -> String {
+    let database_path = format!("{}/.hakana_cache/trends.sqlite", dir);
+
+    for _ in 0..2 {
+        crate::trend_database::record_run(&database_path, "", analysis_result, Duration::default())
+            .unwrap();
+    }
+
+    let connection = Connection::open(&database_path).unwrap();
+
+    let run_count: i64 = connection
+        .query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))
+        .unwrap();
+
+    let mut output = vec![format!("runs: {}", run_count)];
+
+    for (table, column) in [
+        ("issue_kind_counts", "issue_kind"),
+        ("directory_counts", "directory"),
+    ] {
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT {}, count FROM {} WHERE run_id = (SELECT MAX(id) FROM runs) ORDER BY {}",
+                column, table, column
+            ))
+            .unwrap();
+
+        let rows = statement
+            .query_map([], |row| {
+                Ok(format!(
+                    "{} {:?}: {}",
+                    table,
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?
+                ))
+            })
+            .unwrap();
+
+        for row in rows {
+            output.push(row.unwrap());
+        }
+    }
+
+    fs::remove_file(&database_path).unwrap();
+
+    output.join("\n")
+}
+
+// Prints the symbol's signature with the default options, and then again with
+// short names and type aliases left unexpanded
+fn get_signature_output(symbol: &str, codebase: &CodebaseInfo) -> String {
+    let functionlike_id = if let Some((class_name, method_name)) = symbol.split_once("::") {
+        FunctionLikeIdentifier::Method(
+            codebase.interner.get(class_name).unwrap(),
+            codebase.interner.get(method_name).unwrap(),
+        )
+    } else {
+        FunctionLikeIdentifier::Function(codebase.interner.get(symbol).unwrap())
+    };
+
+    [
+        SignaturePrintOptions::default(),
+        SignaturePrintOptions {
+            short_names: true,
+            expand_type_aliases: false,
+        },
+    ]
+    .iter()
+    .map(|options| {
+        let signature = get_printed_signature(&functionlike_id, codebase, options).unwrap();
+
+        let params = signature
+            .params
+            .iter()
+            .map(|param| {
+                let mut param_text = String::new();
+
+                if param.is_inout {
+                    param_text += "inout ";
+                }
+
+                if let Some(param_type) = &param.param_type {
+                    param_text += param_type;
+                    param_text += " ";
+                }
+
+                if param.is_variadic {
+                    param_text += "...";
+                }
+
+                param_text += &param.name;
+
+                if param.is_optional {
+                    param_text += " = _";
+                }
+
+                param_text
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{}({}): {}",
+            symbol,
+            params.join(", "),
+            signature.return_type.unwrap_or("_".to_string())
+        )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
 fn copy_recursively(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> io::Result<()> {
     fs::create_dir_all(&destination)?;
     for entry in fs::read_dir(source)? {
@@ -469,7 +935,9 @@ fn get_all_test_folders(test_or_test_dir: String) -> Vec<String> {
             if metadata.is_dir() {
                 if let Some(path) = path.to_str() {
                     if (Path::new(&(path.to_owned() + "/input.hack")).exists()
-                        && !path.contains("/diff/"))
+                        && !path.contains("/diff/")
+                        && !path.contains("/cache/")
+                        && !path.contains("/taint-baseline/"))
                         || Path::new(&(path.to_owned() + "/output.txt")).exists()
                     {
                         test_folders.push(path.to_owned().to_string());
@@ -0,0 +1,234 @@
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::symbol_references::SymbolReferences;
+
+use super::*;
+
+#[test]
+fn lists_unused_symbols_as_json() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-unused-json-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    fs::write(
+        root_dir.join("a.hack"),
+        r#"function unused_function(): void {}
+
+final class Foo {
+    public static function unusedMethod(): void {}
+
+    <<SomeAttribute>>
+    public static function unusedAttributedMethod(): void {}
+}
+
+<<__EntryPoint>>
+function main(): void {
+    new Foo();
+}
+"#,
+    )
+    .unwrap();
+
+    let mut config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+    config.find_unused_definitions = true;
+    config.list_unused_symbols = true;
+
+    let analysis_result = hakana_workhorse::scan_and_analyze(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        None,
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    );
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&get_unused_symbols_json(&mut analysis_result.unwrap())).unwrap();
+
+    let deletion_safeties = json["unused_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|unused_symbol| {
+            (
+                unused_symbol["name"].as_str().unwrap().to_string(),
+                unused_symbol["deletion_safety"]
+                    .as_str()
+                    .unwrap()
+                    .to_string(),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    assert_eq!(
+        BTreeMap::from([
+            (
+                "Foo::unusedAttributedMethod".to_string(),
+                "needs_review".to_string()
+            ),
+            ("Foo::unusedMethod".to_string(), "safe".to_string()),
+            ("unused_function".to_string(), "safe".to_string()),
+        ]),
+        deletion_safeties
+    );
+}
+
+#[test]
+fn writes_checkpoint_results_with_run_metadata() {
+    let root_dir =
+        std::env::temp_dir().join(format!("hakana-checkpoint-results-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    fs::write(
+        root_dir.join("a.hack"),
+        "function foo(): void {\n    $a = 1;\n}\n",
+    )
+    .unwrap();
+
+    let root_dir_path = root_dir.to_string_lossy().to_string();
+
+    let mut config = Config::new(root_dir_path.clone(), FxHashSet::default());
+    config.find_unused_expressions = true;
+
+    let analysis_result = hakana_workhorse::scan_and_analyze(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        None,
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    )
+    .unwrap();
+
+    write_output_files(
+        "checkpoint_results.json".to_string(),
+        &root_dir_path,
+        &analysis_result,
+    );
+
+    let checkpoint_json = fs::read_to_string(root_dir.join("checkpoint_results.json"));
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&checkpoint_json.unwrap()).unwrap();
+
+    assert!(json["metadata"]["hakana_version"].is_string());
+    assert!(json["metadata"]["codebase_checksum"].is_string());
+
+    let issues = json["issues"].as_array().unwrap();
+
+    assert_eq!(1, issues.len());
+    assert_eq!("UnusedAssignment", issues[0]["case"].as_str().unwrap());
+    assert_eq!("a.hack", issues[0]["filename"].as_str().unwrap());
+}
+
+#[test]
+fn applies_fixes_and_summarizes_them() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-fix-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    let a_path = root_dir.join("a.hack");
+    fs::write(&a_path, "function foo(): void {\n    $a = 1;\n}\n").unwrap();
+
+    let root_dir_path = root_dir.to_string_lossy().to_string();
+
+    let mut config = Config::new(root_dir_path.clone(), FxHashSet::default());
+    config.find_unused_expressions = true;
+    config.issues_to_fix.insert(IssueKind::UnusedAssignment);
+
+    let analysis_result = hakana_workhorse::scan_and_analyze(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        None,
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    )
+    .unwrap();
+
+    let file_edit_counts = update_files(&analysis_result, &root_dir_path);
+    let fixed_contents = fs::read_to_string(&a_path);
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    assert_eq!("function foo(): void {\n}\n", fixed_contents.unwrap());
+    assert_eq!(vec!["a.hack"], file_edit_counts.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn skips_files_that_cannot_be_deleted() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-delete-files-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    fs::write(root_dir.join("a.hack"), "function foo(): void {}\n").unwrap();
+
+    let mut analysis_result = AnalysisResult::new(GraphKind::FunctionBody, SymbolReferences::new());
+
+    for filename in ["a.hack", "missing.hack"] {
+        analysis_result.replacements.insert(
+            filename.to_string(),
+            BTreeMap::from([((0, 23), Replacement::DeleteFile)]),
+        );
+    }
+
+    let file_edit_counts = update_files(&analysis_result, &root_dir.to_string_lossy().to_string());
+    let a_exists = root_dir.join("a.hack").exists();
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    assert!(!a_exists);
+    assert_eq!(vec!["a.hack"], file_edit_counts.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn summarizes_fixes_by_file() {
+    assert_eq!("No fixes applied", get_fix_summary(&BTreeMap::new()));
+
+    assert_eq!(
+        "Applied fixes to 2 files:\n  a.hack (1 edit)\n  b.hack (3 edits)",
+        get_fix_summary(&BTreeMap::from([
+            ("a.hack".to_string(), 1),
+            ("b.hack".to_string(), 3)
+        ]))
+    );
+}
+
+#[test]
+fn parses_shape_report_min_callers() {
+    let matches = get_command()
+        .try_get_matches_from(["hakana", "analyze", "--shape-report-min-callers", "5"])
+        .unwrap();
+
+    let (_, sub_matches) = matches.subcommand().unwrap();
+
+    assert_eq!(
+        Some(&5),
+        sub_matches.get_one::<usize>("shape-report-min-callers")
+    );
+}
+
+#[test]
+fn rejects_invalid_shape_report_min_callers() {
+    assert!(get_command()
+        .try_get_matches_from(["hakana", "analyze", "--shape-report-min-callers", "many"])
+        .is_err());
+}
+
+#[test]
+fn rejects_invalid_max_issues() {
+    assert!(get_command()
+        .try_get_matches_from(["hakana", "analyze", "--max-issues", "lots"])
+        .is_err());
+}
@@ -0,0 +1,93 @@
+use hakana_reflection_info::analysis_result::AnalysisResult;
+use rusqlite::{params, Connection};
+use rustc_hash::FxHashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const SCHEMA_VERSION: i64 = 1;
+
+// Appends the results of a single run to a SQLite database, so that issue counts
+// can be charted over time.
+pub(crate) fn record_run(
+    database_path: &String,
+    build_header: &str,
+    analysis_result: &AnalysisResult,
+    total_time: Duration,
+) -> rusqlite::Result<()> {
+    let mut connection = Connection::open(Path::new(database_path))?;
+
+    migrate_schema(&connection)?;
+
+    let transaction = connection.transaction()?;
+
+    transaction.execute(
+        "INSERT INTO runs (started_at, build, total_time_ms, analysis_time_ms) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64,
+            build_header,
+            total_time.as_millis() as i64,
+            analysis_result.time_in_analysis.as_millis() as i64,
+        ],
+    )?;
+
+    let run_id = transaction.last_insert_rowid();
+
+    for (issue_kind, count) in &analysis_result.issue_counts {
+        transaction.execute(
+            "INSERT INTO issue_kind_counts (run_id, issue_kind, count) VALUES (?1, ?2, ?3)",
+            params![run_id, issue_kind.to_string(), *count as i64],
+        )?;
+    }
+
+    let mut directory_counts = FxHashMap::default();
+
+    for (file_path, issues) in &analysis_result.emitted_issues {
+        let directory = if let Some(parent) = Path::new(file_path).parent() {
+            parent.to_str().unwrap_or("").to_string()
+        } else {
+            "".to_string()
+        };
+
+        *directory_counts.entry(directory).or_insert(0) += issues.len();
+    }
+
+    for (directory, count) in directory_counts {
+        transaction.execute(
+            "INSERT INTO directory_counts (run_id, directory, count) VALUES (?1, ?2, ?3)",
+            params![run_id, directory, count as i64],
+        )?;
+    }
+
+    transaction.commit()
+}
+
+fn migrate_schema(connection: &Connection) -> rusqlite::Result<()> {
+    let user_version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    if user_version < 1 {
+        connection.execute_batch(
+            "CREATE TABLE runs (
+                id INTEGER PRIMARY KEY,
+                started_at INTEGER NOT NULL,
+                build TEXT NOT NULL,
+                total_time_ms INTEGER NOT NULL,
+                analysis_time_ms INTEGER NOT NULL
+            );
+            CREATE TABLE issue_kind_counts (
+                run_id INTEGER NOT NULL REFERENCES runs(id),
+                issue_kind TEXT NOT NULL,
+                count INTEGER NOT NULL
+            );
+            CREATE TABLE directory_counts (
+                run_id INTEGER NOT NULL REFERENCES runs(id),
+                directory TEXT NOT NULL,
+                count INTEGER NOT NULL
+            );",
+        )?;
+    }
+
+    connection.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+}
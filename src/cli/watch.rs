@@ -0,0 +1,120 @@
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_reflection_info::analysis_result::AnalysisResult;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use rustc_hash::FxHashSet;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+// Re-analyzes the codebase whenever a Hack file under the root changes, printing the
+// issues that appeared or disappeared since the last run. Each run after the first
+// uses the cache from the one before, so only changed code is re-analyzed.
+pub(crate) fn watch(
+    config: Arc<Config>,
+    filter: Option<String>,
+    ignored: Option<FxHashSet<String>>,
+    cache_dir: &String,
+    threads: u8,
+    verbosity: Verbosity,
+    header: &str,
+) -> notify::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(Path::new(&config.root_dir), RecursiveMode::Recursive)?;
+
+    let mut previous_issues = BTreeSet::new();
+
+    loop {
+        let result = hakana_workhorse::scan_and_analyze(
+            true,
+            Vec::new(),
+            filter.clone(),
+            ignored.clone(),
+            config.clone(),
+            Some(cache_dir),
+            threads,
+            verbosity,
+            header,
+            None,
+        );
+
+        match result {
+            Ok(analysis_result) => {
+                let issues = get_formatted_issues(&analysis_result);
+
+                for issue_change in get_issue_changes(&previous_issues, &issues) {
+                    println!("{}", issue_change);
+                }
+
+                println!("\n{} issues, watching for changes\n", issues.len());
+
+                previous_issues = issues;
+            }
+            Err(error) => {
+                println!("{}\n\nwatching for changes\n", error);
+            }
+        }
+
+        wait_for_change(&receiver, cache_dir)?;
+    }
+}
+
+fn get_formatted_issues(analysis_result: &AnalysisResult) -> BTreeSet<String> {
+    analysis_result
+        .emitted_issues
+        .iter()
+        .flat_map(|(file_path, issues)| issues.iter().map(|issue| issue.format(file_path)))
+        .collect()
+}
+
+// The fixed issues prefixed with "- ", followed by the new ones prefixed with "+ "
+fn get_issue_changes(previous_issues: &BTreeSet<String>, issues: &BTreeSet<String>) -> Vec<String> {
+    previous_issues
+        .difference(issues)
+        .map(|fixed_issue| format!("- {}", fixed_issue))
+        .chain(
+            issues
+                .difference(previous_issues)
+                .map(|new_issue| format!("+ {}", new_issue)),
+        )
+        .collect()
+}
+
+fn wait_for_change(
+    receiver: &mpsc::Receiver<notify::Result<Event>>,
+    cache_dir: &String,
+) -> notify::Result<()> {
+    loop {
+        if is_relevant_change(&receiver.recv().unwrap()?, cache_dir) {
+            break;
+        }
+    }
+
+    // editors often write a file more than once when saving, and branch switches
+    // touch lots of files, so wait for things to settle before analyzing
+    while receiver.recv_timeout(Duration::from_millis(100)).is_ok() {}
+
+    Ok(())
+}
+
+fn is_relevant_change(event: &Event, cache_dir: &String) -> bool {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return false;
+    }
+
+    event.paths.iter().any(|path| {
+        !path.starts_with(cache_dir)
+            && matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some("hack" | "php" | "hhi")
+            )
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,40 @@
+use super::*;
+
+fn get_issues(issues: &[&str]) -> BTreeSet<String> {
+    issues.iter().map(|issue| issue.to_string()).collect()
+}
+
+#[test]
+fn lists_fixed_issues_before_new_ones() {
+    assert_eq!(
+        get_issue_changes(
+            &get_issues(&["ERROR: A - a.hack:1:1 - a", "ERROR: B - a.hack:2:1 - b"]),
+            &get_issues(&["ERROR: B - a.hack:2:1 - b", "ERROR: C - b.hack:1:1 - c"]),
+        ),
+        vec![
+            "- ERROR: A - a.hack:1:1 - a".to_string(),
+            "+ ERROR: C - b.hack:1:1 - c".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn lists_every_issue_as_new_on_the_first_run() {
+    assert_eq!(
+        get_issue_changes(
+            &BTreeSet::new(),
+            &get_issues(&["ERROR: B - a.hack:2:1 - b", "ERROR: A - a.hack:1:1 - a"]),
+        ),
+        vec![
+            "+ ERROR: A - a.hack:1:1 - a".to_string(),
+            "+ ERROR: B - a.hack:2:1 - b".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn lists_nothing_when_the_issues_are_unchanged() {
+    let issues = get_issues(&["ERROR: A - a.hack:1:1 - a"]);
+
+    assert!(get_issue_changes(&issues, &issues).is_empty());
+}
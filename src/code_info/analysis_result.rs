@@ -1,11 +1,15 @@
-use std::{collections::BTreeMap, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
 
 use rustc_hash::{FxHashMap, FxHashSet};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     data_flow::graph::{DataFlowGraph, GraphKind},
-    issue::{Issue, IssueKind},
+    functionlike_identifier::FunctionLikeIdentifier,
+    issue::{Issue, IssueKind, IssueSeverity, QuickFix, TaintTraceStep},
     symbol_references::SymbolReferences,
 };
 
@@ -14,6 +18,8 @@ pub enum Replacement {
     Remove,
     TrimPrecedingWhitespace(u64),
     Substitute(String),
+    // the whole file is removed, so no other replacement applies
+    DeleteFile,
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +31,165 @@ pub struct AnalysisResult {
     pub symbol_references: SymbolReferences,
     pub issue_counts: FxHashMap<IssueKind, usize>,
     pub time_in_analysis: Duration,
+    pub dependency_digests: BTreeMap<String, FileDependencyDigest>,
+    pub unanalyzed_file_count: usize,
+    pub unused_symbols: Vec<UnusedSymbol>,
+    pub liveness_explanation: Option<LivenessExplanation>,
+    // the inferred types of expressions in the file being dumped, keyed by offsets
+    pub expr_types: BTreeMap<(usize, usize), String>,
+    pub typed_ast: Option<Vec<TypedAstNode>>,
+    pub run_metadata: Option<RunMetadata>,
+    // how many inferred expression types in each file are mixed
+    pub type_coverage: BTreeMap<String, TypeCoverage>,
+    pub shape_params: Vec<ShapeParam>,
+    // functions and methods declared to return a nullable type that never return null
+    pub never_null_returns: FxHashSet<FunctionLikeIdentifier>,
+    // the issue profile used by each analyzed file that has one
+    pub issue_profile_files: BTreeMap<String, String>,
+    // the earliest date on which a suppression in each analyzed file expires
+    pub suppression_expiries: BTreeMap<String, String>,
+}
+
+// What a run analyzed and how, so that reported issues can be reproduced later
+#[derive(Clone, Debug, Serialize)]
+pub struct RunMetadata {
+    pub hakana_version: String,
+    // the build header (and core libs digest) that cached data is keyed on
+    pub build_checksum: String,
+    pub config_hash: String,
+    // a digest of every analyzed file's contents, unknown when resuming from a checkpoint
+    pub codebase_checksum: Option<String>,
+    // the commit checked out in the root directory, if it's a git repository
+    pub revision: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TypeCoverage {
+    pub mixed_count: usize,
+    pub typed_count: usize,
+}
+
+impl TypeCoverage {
+    pub fn add(&mut self, other: &TypeCoverage) {
+        self.mixed_count += other.mixed_count;
+        self.typed_count += other.typed_count;
+    }
+
+    // files without any expressions count as fully typed
+    pub fn get_typed_percentage(&self) -> f64 {
+        let total_count = self.mixed_count + self.typed_count;
+
+        if total_count == 0 {
+            100.0
+        } else {
+            (self.typed_count as f64 * 100.0) / total_count as f64
+        }
+    }
+}
+
+// The files whose symbols a given file's analysis consulted, along with a digest
+// of the contents of that file and all its dependencies
+#[derive(Clone, Debug, Serialize)]
+pub struct FileDependencyDigest {
+    pub digest: String,
+    pub dependencies: BTreeSet<String>,
+}
+
+// An unused definition, with an indication of whether automation can delete it
+#[derive(Clone, Debug, Serialize)]
+pub struct UnusedSymbol {
+    pub name: String,
+    pub kind: IssueKind,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub deletion_safety: DeletionSafety,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionSafety {
+    // nothing outside the symbol's own file can reference it
+    Safe,
+    // nothing references it statically, but it could still be called dynamically
+    PartiallySafe,
+    // it overrides or is overridden by something, or is otherwise exposed
+    NeedsReview,
+}
+
+// Why a symbol isn't reported as unused. The chain starts with whatever references the
+// symbol directly, and ends with something that's kept by one of the rules below. When
+// nothing keeps the chain alive, kept_by is None and the chain just shows a referencer.
+#[derive(Clone, Debug, Serialize)]
+pub struct LivenessExplanation {
+    pub symbol: String,
+    pub chain: Vec<LivenessChainLink>,
+    pub kept_by: Option<LivenessRule>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LivenessChainLink {
+    pub referencing_symbol: String,
+    pub reference_kind: ReferenceKind,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    Body,
+    Signature,
+    // the referencer calls a method that overrides the referenced one
+    Override,
+    // a call to a parent method can dispatch to this one
+    Dynamic,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LivenessRule {
+    // e.g. entry points and anything marked __DynamicallyCallable
+    DynamicallyCallable,
+    Generated,
+    IgnoredPath,
+    // it comes from a library or stub, not the project
+    NotUserDefined,
+    // the unused-symbol issue is suppressed
+    Suppressed,
+    // the framework that defines its parent class could instantiate it
+    ExtendsExternalClass,
+    // methods like __toString are called implicitly
+    MagicMethod,
+    // constants, type aliases and properties aren't checked for usage
+    NotChecked,
+}
+
+// A function or method parameter typed as a closed shape with string keys
+#[derive(Clone, Debug)]
+pub struct ShapeParam {
+    pub functionlike_id: FunctionLikeIdentifier,
+    pub functionlike_name: String,
+    pub param_name: String,
+    pub file_path: String,
+    pub line: usize,
+    // each field's type, and whether it's optional
+    pub fields: BTreeMap<String, (bool, String)>,
+    // the fields the function's body reads from the parameter
+    pub accessed_fields: BTreeSet<String>,
+}
+
+// An expression in a typed AST dump. Expressions are listed in the order they appear,
+// with each one before any expressions it contains.
+#[derive(Clone, Debug, Serialize)]
+pub struct TypedAstNode {
+    pub kind: &'static str,
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    // None for expressions Hakana doesn't infer a type for, e.g. assignment targets
+    pub inferred_type: Option<String>,
 }
 
 impl AnalysisResult {
@@ -40,6 +205,18 @@ impl AnalysisResult {
             issue_counts: FxHashMap::default(),
             symbol_references,
             time_in_analysis: Duration::default(),
+            dependency_digests: BTreeMap::new(),
+            unanalyzed_file_count: 0,
+            unused_symbols: vec![],
+            liveness_explanation: None,
+            expr_types: BTreeMap::new(),
+            typed_ast: None,
+            run_metadata: None,
+            type_coverage: BTreeMap::new(),
+            shape_params: vec![],
+            never_null_returns: FxHashSet::default(),
+            issue_profile_files: BTreeMap::new(),
+            suppression_expiries: BTreeMap::new(),
         }
     }
 
@@ -63,6 +240,19 @@ impl AnalysisResult {
         for (kind, count) in other.issue_counts {
             *self.issue_counts.entry(kind).or_insert(0) += count;
         }
+        self.unanalyzed_file_count += other.unanalyzed_file_count;
+        self.unused_symbols.extend(other.unused_symbols);
+        self.expr_types.extend(other.expr_types);
+        for (file_path, type_coverage) in other.type_coverage {
+            self.type_coverage
+                .entry(file_path)
+                .or_default()
+                .add(&type_coverage);
+        }
+        self.shape_params.extend(other.shape_params);
+        self.never_null_returns.extend(other.never_null_returns);
+        self.issue_profile_files.extend(other.issue_profile_files);
+        self.suppression_expiries.extend(other.suppression_expiries);
     }
 }
 
@@ -73,16 +263,58 @@ pub struct CheckPointEntry {
     pub filename: String,
     pub line: usize,
     pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quickfixes: Vec<QuickFix>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 impl CheckPointEntry {
     pub fn from_issue(issue: &Issue, path: &String) -> Self {
         Self {
             output: issue.description.clone(),
-            level: "failure".to_string(),
+            level: match issue.severity {
+                IssueSeverity::Error => "failure",
+                IssueSeverity::Warning => "warning",
+                IssueSeverity::Info => "notice",
+            }
+            .to_string(),
             filename: path.clone(),
             line: issue.pos.start_line,
             case: issue.kind.to_string(),
+            help_uri: issue.help_uri.clone(),
+            rule_id: issue.rule_id.clone(),
+            quickfixes: issue.quickfixes.clone(),
+            hint: issue.regeneration_hint.clone(),
         }
     }
 }
+
+// A taint issue along with the full path its data took from source to sink
+#[derive(Serialize)]
+pub struct TaintTraceEntry {
+    pub case: String,
+    pub filename: String,
+    pub line: usize,
+    pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taint_flow_id: Option<String>,
+    pub trace: Vec<TaintTraceStep>,
+}
+
+impl TaintTraceEntry {
+    pub fn from_issue(issue: &Issue, path: &String) -> Option<Self> {
+        Some(Self {
+            case: issue.kind.to_string(),
+            filename: path.clone(),
+            line: issue.pos.start_line,
+            output: issue.description.clone(),
+            taint_flow_id: issue.taint_flow_id.clone(),
+            trace: issue.taint_trace.clone()?,
+        })
+    }
+}
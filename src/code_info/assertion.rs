@@ -43,6 +43,9 @@ pub enum Assertion {
     DontIgnoreTaints,
     RemoveTaints(String, #[derivative(Hash = "ignore")] FxHashSet<SinkType>),
     DontRemoveTaints(String, #[derivative(Hash = "ignore")] FxHashSet<SinkType>),
+    // from e.g. function_exists('foo') or class_exists(Foo::class)
+    SymbolExists(String),
+    SymbolDoesNotExist(String),
 }
 
 impl Assertion {
@@ -92,6 +95,8 @@ impl Assertion {
             Assertion::DontIgnoreTaints => "dont-ignore-taints".to_string(),
             Assertion::RemoveTaints(key, _) => "remove-some-taints-".to_string() + key,
             Assertion::DontRemoveTaints(key, _) => "!remove-some-taints-".to_string() + key,
+            Assertion::SymbolExists(name) => "symbol-exists-".to_string() + name,
+            Assertion::SymbolDoesNotExist(name) => "!symbol-exists-".to_string() + name,
         }
     }
 
@@ -112,7 +117,8 @@ impl Assertion {
             | Assertion::DoesNotHaveArrayKey(_)
             | Assertion::DoesNotHaveExactCount(_)
             | Assertion::DoesNotHaveNonnullEntryForKey(_)
-            | Assertion::EmptyCountable => true,
+            | Assertion::EmptyCountable
+            | Assertion::SymbolDoesNotExist(_) => true,
 
             _ => false,
         }
@@ -264,6 +270,14 @@ impl Assertion {
                 }
                 _ => false,
             },
+            Assertion::SymbolExists(name) => match other {
+                Assertion::SymbolDoesNotExist(other_name) => other_name == name,
+                _ => false,
+            },
+            Assertion::SymbolDoesNotExist(name) => match other {
+                Assertion::SymbolExists(other_name) => other_name == name,
+                _ => false,
+            },
         }
     }
 
@@ -314,6 +328,8 @@ impl Assertion {
             Assertion::DontRemoveTaints(key, taints) => {
                 Assertion::RemoveTaints(key.clone(), taints.clone())
             }
+            Assertion::SymbolExists(name) => Assertion::SymbolDoesNotExist(name.clone()),
+            Assertion::SymbolDoesNotExist(name) => Assertion::SymbolExists(name.clone()),
         }
     }
 }
@@ -25,6 +25,9 @@ pub enum Variance {
 pub struct ClassLikeInfo {
     pub constants: IndexMap<StrId, ConstantInfo>,
 
+    // the class, interface or trait each constant (including inherited ones) is declared in
+    pub declaring_constant_ids: FxHashMap<StrId, StrId>,
+
     /**
      * Aliases to help Hakana understand constant refs
      */
@@ -204,6 +207,7 @@ impl ClassLikeInfo {
     pub fn new(name: StrId, def_location: HPos, name_location: HPos) -> ClassLikeInfo {
         ClassLikeInfo {
             constants: IndexMap::default(),
+            declaring_constant_ids: FxHashMap::default(),
             is_populated: false,
             is_stubbed: false,
             is_deprecated: false,
@@ -33,6 +33,8 @@ pub struct CodebaseInfo {
     pub safe_symbols: FxHashSet<StrId>,
     /* Symbol members that have already been checked on a previous Hakana run */
     pub safe_symbol_members: FxHashSet<(StrId, StrId)>,
+    /* Files that failed to parse, whose symbols are carried over from a previous Hakana run */
+    pub stale_files: FxHashSet<StrId>,
 }
 
 impl CodebaseInfo {
@@ -52,6 +54,7 @@ impl CodebaseInfo {
             files: FxHashMap::default(),
             safe_symbols: FxHashSet::default(),
             safe_symbol_members: FxHashSet::default(),
+            stale_files: FxHashSet::default(),
         }
     }
 
@@ -262,6 +265,23 @@ impl CodebaseInfo {
         }
     }
 
+    // Enum cases used as dict and shape keys are stored under their values
+    pub fn get_enum_case_key_value(
+        &self,
+        enum_name: &StrId,
+        member_name: &StrId,
+    ) -> Option<String> {
+        let literal_value = self.get_classconst_literal_value(enum_name, member_name)?;
+
+        literal_value
+            .get_literal_string_value(&self.interner)
+            .or_else(|| {
+                literal_value
+                    .get_literal_int_value()
+                    .map(|value| value.to_string())
+            })
+    }
+
     pub fn property_exists(&self, classlike_name: &StrId, property_name: &StrId) -> bool {
         if let Some(classlike_info) = self.classlike_infos.get(classlike_name) {
             classlike_info
@@ -306,6 +326,18 @@ impl CodebaseInfo {
         return None;
     }
 
+    pub fn get_declaring_class_for_constant(
+        &self,
+        fq_class_name: &StrId,
+        const_name: &StrId,
+    ) -> Option<&StrId> {
+        if let Some(classlike_storage) = self.classlike_infos.get(fq_class_name) {
+            return classlike_storage.declaring_constant_ids.get(const_name);
+        }
+
+        return None;
+    }
+
     pub fn get_property_type(
         &self,
         fq_class_name: &StrId,
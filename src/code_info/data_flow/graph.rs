@@ -2,23 +2,25 @@ use super::{
     node::{DataFlowNode, DataFlowNodeKind},
     path::{DataFlowPath, ArrayDataKind, PathKind},
 };
+use crate::code_location::HPos;
 use crate::taint::SinkType;
 use oxidized::ast_defs::Pos;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WholeProgramKind {
     Taint,
     Query,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GraphKind {
     FunctionBody,
     WholeProgram(WholeProgramKind),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataFlowGraph {
     pub kind: GraphKind,
     pub vertices: FxHashMap<String, DataFlowNode>,
@@ -29,6 +31,11 @@ pub struct DataFlowGraph {
     pub mixed_source_counts: FxHashMap<String, FxHashSet<String>>,
     pub specializations: FxHashMap<String, FxHashSet<String>>,
     specialized_calls: FxHashMap<String, FxHashSet<String>>,
+    // where each function and method whose body was added to a whole-program graph is
+    // defined, so that flows through the graph can be attributed to them
+    pub functionlike_locations: Vec<(HPos, String)>,
+    // false when nothing will read the graph, so nodes and paths needn't be stored
+    pub recording: bool,
 }
 
 impl DataFlowGraph {
@@ -43,10 +50,16 @@ impl DataFlowGraph {
             mixed_source_counts: FxHashMap::default(),
             specializations: FxHashMap::default(),
             specialized_calls: FxHashMap::default(),
+            functionlike_locations: Vec::new(),
+            recording: true,
         }
     }
 
     pub fn add_node(&mut self, node: DataFlowNode) {
+        if !self.recording {
+            return;
+        }
+
         match &node.kind {
             DataFlowNodeKind::Vertex {
                 unspecialized_id,
@@ -91,6 +104,10 @@ impl DataFlowGraph {
         added_taints: Option<FxHashSet<SinkType>>,
         removed_taints: Option<FxHashSet<SinkType>>,
     ) {
+        if !self.recording {
+            return;
+        }
+
         if matches!(
             path_kind,
             PathKind::UnknownArrayFetch(ArrayDataKind::ArrayKey)
@@ -163,6 +180,21 @@ impl DataFlowGraph {
         self.vertices.extend(graph.vertices);
         self.sources.extend(graph.sources);
         self.sinks.extend(graph.sinks);
+        self.functionlike_locations
+            .extend(graph.functionlike_locations);
+    }
+
+    // The innermost function or method whose definition contains the given position
+    pub fn get_enclosing_functionlike(&self, pos: &HPos) -> Option<&String> {
+        self.functionlike_locations
+            .iter()
+            .filter(|(def_location, _)| {
+                def_location.file_path == pos.file_path
+                    && def_location.start_offset <= pos.start_offset
+                    && def_location.end_offset >= pos.end_offset
+            })
+            .min_by_key(|(def_location, _)| def_location.end_offset - def_location.start_offset)
+            .map(|(_, functionlike_name)| functionlike_name)
     }
 
     pub fn get_origin_nodes(&self, assignment_node: &DataFlowNode) -> Vec<DataFlowNode> {
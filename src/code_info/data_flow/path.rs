@@ -24,6 +24,8 @@ pub enum PathKind {
     RemoveDictKey(String),
     RefineSymbol(StrId),
     ScalarTypeGuard,
+    Serialize,
+    Unserialize,
 }
 
 impl std::fmt::Display for PathKind {
@@ -45,11 +47,13 @@ impl std::fmt::Display for PathKind {
             PathKind::RemoveDictKey(_) => write!(f, "remove-dict-key"),
             PathKind::RefineSymbol(_) => write!(f, "refine-symbol"),
             PathKind::ScalarTypeGuard => write!(f, "scalar-type-guard"),
+            PathKind::Serialize => write!(f, "serialize"),
+            PathKind::Unserialize => write!(f, "unserialize"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataFlowPath {
     pub kind: PathKind,
     pub added_taints: Option<FxHashSet<SinkType>>,
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     code_location::HPos,
+    issue::{TaintTraceLocation, TaintTraceStep},
     taint::{self, SinkType, SourceType},
     Interner,
 };
@@ -63,6 +64,56 @@ impl TaintedNode {
         source_descriptor
     }
 
+    // The same path as get_trace, source first, in a form that can be serialized
+    pub fn get_trace_steps(&self, interner: &Interner) -> Vec<TaintTraceStep> {
+        let mut steps = vec![];
+        let mut current_node = Some(self);
+
+        while let Some(node) = current_node {
+            steps.push(TaintTraceStep {
+                label: node.label.clone(),
+                location: node.pos.as_ref().map(|pos| TaintTraceLocation {
+                    file_path: interner.lookup(&pos.file_path).to_string(),
+                    start_line: pos.start_line,
+                    start_column: pos.start_column,
+                    end_line: pos.end_line,
+                    end_column: pos.end_column,
+                }),
+                path_kind: if node.previous.is_some() {
+                    node.path_types.last().map(|path| path.to_string())
+                } else {
+                    None
+                },
+            });
+
+            current_node = node.previous.as_deref();
+        }
+
+        steps.reverse();
+
+        steps
+    }
+
+    pub fn get_source_label(&self) -> &String {
+        if let Some(previous_source) = &self.previous {
+            previous_source.get_source_label()
+        } else {
+            &self.label
+        }
+    }
+
+    // The position of the earliest node in the path that has one. Sources such as
+    // superglobals don't have a position of their own.
+    pub fn get_source_pos(&self) -> Option<&HPos> {
+        if let Some(previous_source) = &self.previous {
+            if let Some(pos) = previous_source.get_source_pos() {
+                return Some(pos);
+            }
+        }
+
+        self.pos.as_deref()
+    }
+
     pub fn get_taint_sources(&self) -> &FxHashSet<SourceType> {
         if let Some(previous_source) = &self.previous {
             return previous_source.get_taint_sources();
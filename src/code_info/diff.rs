@@ -7,6 +7,9 @@ pub struct CodebaseDiff {
     pub keep: Vec<(StrId, StrId)>,
     pub keep_signature: Vec<(StrId, StrId)>,
     pub add_or_delete: Vec<(StrId, StrId)>,
+    // classlikes whose own signatures are unchanged, but which have changed members.
+    // They're re-analyzed, but their unchanged members are not.
+    pub member_changes_only: Vec<StrId>,
     pub diff_map: FxHashMap<StrId, Vec<(usize, usize, isize, isize)>>,
     pub deletion_ranges_map: FxHashMap<StrId, Vec<(usize, usize)>>,
 }
@@ -16,6 +19,7 @@ impl CodebaseDiff {
         self.keep.extend(other.keep);
         self.keep_signature.extend(other.keep_signature);
         self.add_or_delete.extend(other.add_or_delete);
+        self.member_changes_only.extend(other.member_changes_only);
         self.diff_map.extend(other.diff_map);
         self.deletion_ranges_map.extend(other.deletion_ranges_map);
     }
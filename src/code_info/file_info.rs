@@ -1,10 +1,27 @@
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
-use crate::{ast_signature::DefSignatureNode, functionlike_info::FunctionLikeInfo};
+use crate::{ast_signature::DefSignatureNode, functionlike_info::FunctionLikeInfo, StrId};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct FileInfo {
     pub ast_nodes: Vec<DefSignatureNode>,
     pub closure_infos: FxHashMap<usize, FunctionLikeInfo>,
+
+    // files (relative to the project root) that this file requires or includes
+    pub imported_files: Vec<StrId>,
+
+    // `use` statements that import a single class or function
+    pub symbol_uses: Vec<SymbolUseStatement>,
+
+    // the command that regenerates the file, from a @codegen-command marker in its header
+    pub regeneration_command: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SymbolUseStatement {
+    pub name: StrId,
+    // the start of the line the statement is on
+    pub start_offset: usize,
+    pub end_offset: usize,
 }
@@ -9,7 +9,7 @@ use crate::{
     attribute_info::AttributeInfo,
     code_location::HPos,
     functionlike_parameter::FunctionLikeParameter,
-    issue::IssueKind,
+    issue::{suppression_has_expired, IssueKind},
     method_info::MethodInfo,
     t_union::TUnion,
     taint::{SinkType, SourceType},
@@ -35,6 +35,11 @@ impl FnEffect {
         }
     }
 
+    // whether the function's contexts allow it to read or write state beyond its arguments
+    pub fn is_impure(&self) -> bool {
+        matches!(self, FnEffect::Some(effects) if *effects != 0)
+    }
+
     pub fn from_u8(effects: &Option<u8>) -> Self {
         if let Some(effects) = effects {
             if effects == &0 {
@@ -60,9 +65,14 @@ pub struct FunctionLikeInfo {
 
     pub return_type_location: Option<HPos>,
 
+    // set between analysis passes when the return type is nullable but the function was
+    // found to never return null, so that callers can use the non-null type
+    pub never_returns_null: bool,
+
     pub name: StrId,
 
-    pub suppressed_issues: Option<FxHashMap<IssueKind, HPos>>,
+    // suppressions in the definition's comments, along with when they expire
+    pub suppressed_issues: Option<FxHashMap<IssueKind, (HPos, Option<String>)>>,
 
     pub deprecated: bool,
 
@@ -91,10 +101,31 @@ pub struct FunctionLikeInfo {
 
     pub is_async: bool,
 
+    // whether the function has a __Memoize or __MemoizeLSB attribute
+    pub is_memoized: bool,
+
+    // whether the result can change between calls with the same arguments (e.g. it reads
+    // the clock, or is random, or does IO). Known builtins are marked when they're scanned.
+    pub is_non_deterministic: bool,
+
+    // whether the return type is declared readonly, so the result can't be mutated
+    pub readonly_return: bool,
+
     pub mutation_free: bool,
 
     pub effects: FnEffect,
 
+    // functions called directly in the body, used to infer effects across the call graph
+    pub called_functions: FxHashSet<StrId>,
+
+    // methods called directly in the body where the class is known without type inference
+    // (static calls on a named class, self::, static::, parent:: and $this->), as
+    // (class, method) pairs with the class named at the call site
+    pub called_methods: FxHashSet<(StrId, StrId)>,
+
+    // set when populating the codebase if the function, or anything it calls, is impure
+    pub inferred_impure: bool,
+
     /**
      * Whether or not the function output is dependent solely on input - a function can be
      * impure but still have this property (e.g. var_export). Useful for taint analysis.
@@ -137,6 +168,10 @@ pub struct FunctionLikeInfo {
 
     pub where_constraints: Vec<(StrId, TUnion)>,
 
+    // `where T as Foo` constraints, as (template name, defining entity, upper bound),
+    // which are checked at call sites once the template has been inferred
+    pub where_as_constraints: Vec<(StrId, StrId, TUnion)>,
+
     /*
      * Stores a reference to the Asynchronous version of this function.
      * If a function body is just a one-line
@@ -145,6 +180,14 @@ pub struct FunctionLikeInfo {
      */
     pub async_version: Option<FunctionLikeIdentifier>,
 
+    /*
+     * If a function body is just a one-line
+     *   return some_other_function($first_param, ...);
+     * then the id for the other function is stored here, so that wrappers around
+     * sanitizers can be treated as sanitizers
+     */
+    pub wrapped_function: Option<StrId>,
+
     pub is_production_code: bool,
 }
 
@@ -156,6 +199,7 @@ impl FunctionLikeInfo {
             params: Vec::new(),
             return_type: None,
             return_type_location: None,
+            never_returns_null: false,
             name,
             suppressed_issues: None,
             deprecated: false,
@@ -168,6 +212,9 @@ impl FunctionLikeInfo {
             has_yield: false,
             mutation_free: false,
             effects: FnEffect::Unknown,
+            called_functions: FxHashSet::default(),
+            called_methods: FxHashSet::default(),
+            inferred_impure: false,
             specialize_call: false,
             taint_source_types: FxHashSet::default(),
             added_taints: None,
@@ -176,6 +223,9 @@ impl FunctionLikeInfo {
             attributes: Vec::new(),
             method_info: None,
             is_async: false,
+            is_memoized: false,
+            is_non_deterministic: false,
+            readonly_return: false,
             ignore_taint_path: false,
             user_defined: false,
             dynamically_callable: false,
@@ -183,8 +233,22 @@ impl FunctionLikeInfo {
             ignore_taints_if_true: false,
             type_resolution_context: None,
             where_constraints: vec![],
+            where_as_constraints: vec![],
             async_version: None,
+            wrapped_function: None,
             is_production_code: true,
         }
     }
+
+    pub fn is_issue_suppressed(&self, issue_kind: &IssueKind) -> bool {
+        if let Some(suppressed_issues) = &self.suppressed_issues {
+            if let Some((_, expiry)) = suppressed_issues.get(issue_kind) {
+                return !expiry
+                    .as_ref()
+                    .map_or(false, |expiry| suppression_has_expired(expiry));
+            }
+        }
+
+        false
+    }
 }
@@ -19,6 +19,8 @@ pub struct FunctionLikeParameter {
 
     pub is_inout: bool,
 
+    pub is_readonly: bool,
+
     pub signature_type: Option<TUnion>,
 
     pub is_optional: bool,
@@ -37,6 +39,10 @@ pub struct FunctionLikeParameter {
 
     pub removed_taints_when_returning_true: Option<FxHashSet<SinkType>>,
 
+    // set when populating the codebase for the first param of a function that just wraps
+    // a sanitizer, as values passed here only flow through that sanitizer
+    pub inferred_removed_taints: Option<FxHashSet<SinkType>>,
+
     pub assert_untainted: bool,
 
     /**
@@ -59,6 +65,7 @@ impl FunctionLikeParameter {
         Self {
             name,
             is_inout: false,
+            is_readonly: false,
             signature_type: None,
             is_optional: false,
             is_nullable: false,
@@ -73,6 +80,7 @@ impl FunctionLikeParameter {
             promoted_property: false,
             attributes: Vec::new(),
             removed_taints_when_returning_true: None,
+            inferred_removed_taints: None,
         }
     }
 }
@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
@@ -10,17 +11,25 @@ use crate::{
 
 #[derive(Clone, PartialEq, Eq, Hash, Display, Debug, Serialize, Deserialize, EnumString)]
 pub enum IssueKind {
+    AwaitInConditionalOperand,
+    AwaitInConstructor,
+    AwaitInFinally,
+    AwaitInNonAsyncFunction,
     CannotInferGenericParam,
     CustomIssue(String),
+    DuplicateArrayKey,
     EmptyBlock,
+    ExpiredSuppression,
     FalsableReturnStatement,
     FalseArgument,
+    FloatEquality,
     ImpossibleAssignment,
     ImpossibleKeyCheck,
     ImpossibleNonnullEntryCheck,
     ImpossibleNullTypeComparison,
     ImpossibleTruthinessCheck,
     ImpossibleTypeComparison,
+    ImpureFunctionInPureContext,
     InternalError,
     InvalidArgument,
     InvalidArrayOffset,
@@ -32,6 +41,10 @@ pub enum IssueKind {
     InvalidReturnStatement,
     InvalidReturnType,
     InvalidReturnValue,
+    InvalidTupleAppend,
+    InvalidTupleOffset,
+    InvalidVariance,
+    IteratedCollectionMutation,
     LessSpecificArgument,
     LessSpecificNestedAnyArgumentType,
     LessSpecificNestedAnyReturnStatement,
@@ -39,7 +52,9 @@ pub enum IssueKind {
     LessSpecificNestedReturnStatement,
     LessSpecificReturnStatement,
     ForLoopInvalidation,
+    ForeachValueReassigned,
     MethodCallOnNull,
+    MissingAwait,
     MissingRequiredXhpAttribute,
     MixedAnyArgument,
     MixedAnyArrayAccess,
@@ -59,7 +74,11 @@ pub enum IssueKind {
     MixedPropertyAssignment,
     MixedPropertyTypeCoercion,
     MixedReturnStatement,
+    NanComparison,
     NoValue,
+    NonExhaustiveSwitch,
+    NondeterministicCallInMemoizedFunction,
+    NondeterministicCallInPureFunction,
     NonExistentClass,
     NonExistentClasslike,
     NonExistentClassConstant,
@@ -77,6 +96,7 @@ pub enum IssueKind {
     NullableReturnStatement,
     NullableReturnValue,
     ParadoxicalCondition,
+    ParseError,
     PossibleMethodCallOnNull,
     PossiblyFalseArgument,
     PossiblyInvalidArgument,
@@ -89,8 +109,12 @@ pub enum IssueKind {
     PossiblyNullPropertyFetch,
     PossiblyUndefinedIntArrayOffset,
     PossiblyUndefinedStringArrayOffset,
+    PossiblyUndefinedVariable,
     PossiblyUnusedProperty,
+    PropertyNotSetInConstructor,
     PropertyTypeCoercion,
+    ReadonlyMismatch,
+    RedundantComputation,
     RedundantIssetCheck,
     RedundantKeyCheck,
     RedundantNonnullEntryCheck,
@@ -98,6 +122,8 @@ pub enum IssueKind {
     RedundantTruthinessCheck,
     RedundantTypeComparison,
     TaintedData(SinkType),
+    TemplateConstraintViolation,
+    UnhandledNullableReturn,
     UndefinedIntArrayOffset,
     UndefinedStringArrayOffset,
     UndefinedVariable,
@@ -108,6 +134,7 @@ pub enum IssueKind {
     UnrecognizedStatement,
     UnrecognizedType,
     UnrecognizedUnaryOp,
+    UnresolvedImport,
     UnusedAssignment,
     UnusedAssignmentInClosure,
     UnusedClass,
@@ -176,6 +203,7 @@ impl IssueKind {
             | Self::UnusedFunction
             | Self::UnusedInterface
             | Self::UnusedProperty
+            | Self::UnusedPrivateMethod
             | Self::UnusedPublicOrProtectedMethod
             | Self::UnusedTrait => true,
             _ => false,
@@ -184,20 +212,74 @@ impl IssueKind {
 
     pub fn is_unused_expression(&self) -> bool {
         match &self {
-            Self::UnusedAssignment => true,
+            Self::UnusedAssignment
+            | Self::UnusedAssignmentInClosure
+            | Self::UnusedParameter
+            | Self::UnusedPipeVariable => true,
             _ => false,
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    Error,
+    // neither of these cause analysis to fail
+    Warning,
+    Info,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Issue {
     pub kind: IssueKind,
+    pub severity: IssueSeverity,
     pub description: String,
     pub pos: HPos,
     pub can_fix: bool,
     pub fixme_added: bool,
     pub symbol: (StrId, StrId),
+    pub taint_flow_id: Option<String>,
+    // every step from the taint source to the sink, for tools that render traces
+    pub taint_trace: Option<Vec<TaintTraceStep>>,
+    // a page explaining the issue, for IDEs and other structured output
+    pub help_uri: Option<String>,
+    // a short identifier for the rule that produced the issue, e.g. from a plugin
+    pub rule_id: Option<String>,
+    pub quickfixes: Vec<QuickFix>,
+    // for issues in generated files, how to fix them at the source
+    pub regeneration_hint: Option<String>,
+}
+
+// A suggested fix, made up of edits to the file the issue was reported in
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<QuickFixEdit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuickFixEdit {
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub replacement: String,
+}
+
+// One node on the path from a taint source to a sink
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaintTraceStep {
+    pub label: String,
+    pub location: Option<TaintTraceLocation>,
+    // the kind of path that led to this node, None for the source itself
+    pub path_kind: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaintTraceLocation {
+    pub file_path: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
 }
 
 impl Issue {
@@ -209,6 +291,7 @@ impl Issue {
     ) -> Self {
         Self {
             kind,
+            severity: IssueSeverity::Error,
             description,
             symbol: match calling_functionlike_id {
                 Some(FunctionLikeIdentifier::Function(id)) => (*id, StrId::empty()),
@@ -218,19 +301,56 @@ impl Issue {
             pos,
             can_fix: false,
             fixme_added: false,
+            taint_flow_id: None,
+            taint_trace: None,
+            help_uri: None,
+            rule_id: None,
+            quickfixes: vec![],
+            regeneration_hint: None,
         }
     }
 
+    pub fn with_help_uri(mut self, help_uri: String) -> Self {
+        self.help_uri = Some(help_uri);
+        self
+    }
+
+    pub fn with_rule_id(mut self, rule_id: String) -> Self {
+        self.rule_id = Some(rule_id);
+        self
+    }
+
+    pub fn with_quickfix(mut self, title: String, edits: Vec<QuickFixEdit>) -> Self {
+        self.quickfixes.push(QuickFix { title, edits });
+        self
+    }
+
     pub fn format(&self, path: &String) -> String {
         format!(
-            "ERROR: {} - {}:{}:{} - {}\n",
+            "{}: {} - {}:{}:{} - {}\n{}",
+            match self.severity {
+                IssueSeverity::Error => "ERROR",
+                IssueSeverity::Warning => "WARNING",
+                IssueSeverity::Info => "INFO",
+            },
             self.kind.to_string(),
             path,
             self.pos.start_line,
             self.pos.start_column,
-            self.description
+            self.description,
+            if let Some(regeneration_hint) = &self.regeneration_hint {
+                format!("  {}\n", regeneration_hint)
+            } else {
+                "".to_string()
+            }
         )
     }
+
+    // Identifies the issue across runs. The position is left out, as it changes whenever
+    // the code above the issue is edited.
+    pub fn get_fingerprint(&self, path: &String) -> String {
+        format!("{}:{}:{}", path, self.kind.to_string(), self.description)
+    }
 }
 
 pub fn get_issue_from_comment(
@@ -255,3 +375,50 @@ pub fn get_issue_from_comment(
 
     return None;
 }
+
+// Suppressions can be given an expiry date, after which they're ignored, e.g.
+// HAKANA_FIXME[UnusedFunction] until=2024-12-31
+pub fn get_suppression_expiry(trimmed_text: &str) -> Option<&str> {
+    let end_bracket_pos = trimmed_text.find("]")?;
+
+    trimmed_text[(end_bracket_pos + 1)..]
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix("until="))
+        .filter(|date| {
+            date.len() == 10
+                && date.bytes().enumerate().all(|(i, c)| match i {
+                    4 | 7 => c == b'-',
+                    _ => c.is_ascii_digit(),
+                })
+        })
+}
+
+// Expiry dates are in YYYY-MM-DD format, so they can be compared as strings
+pub fn suppression_has_expired(expiry: &str) -> bool {
+    expiry < get_current_date().as_str()
+}
+
+// The current UTC date in YYYY-MM-DD format
+fn get_current_date() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86400) as i64;
+
+    // converts days since the epoch to a proleptic Gregorian date, with years starting in March
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
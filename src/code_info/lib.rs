@@ -46,10 +46,39 @@ pub struct FileSource {
     pub file_path_actual: String,
     pub file_contents: String,
     pub is_production_code: bool,
+    // whether the config's rules mark the file as generated
+    pub is_generated: bool,
+    // the name of the config's issue profile used for the file, if any
+    pub issue_profile: Option<String>,
+    // for generated files, the command that regenerates them
+    pub regeneration_command: Option<String>,
+    // shared by the scanner and the analyzer, so that the types of constants in the
+    // codebase cache match the types the analyzer gives the same literals
+    pub literal_collection_thresholds: LiteralCollectionThresholds,
     pub hh_fixmes: BTreeMap<isize, BTreeMap<isize, Pos>>,
     pub comments: Vec<(Pos, Comment)>,
 }
 
+// Literal vecs and dicts with more entries than these are summarized: instead of a type
+// for every entry they get the union of their key and value types (and, for vecs, their
+// count), which keeps files with huge lookup tables from bloating memory and the cache.
+// Vecs are only summarized when a threshold is configured; the dict default matches the
+// size above which constant dicts previously got no inferred type at all.
+#[derive(Clone, Copy, Debug)]
+pub struct LiteralCollectionThresholds {
+    pub vec: usize,
+    pub dict: usize,
+}
+
+impl Default for LiteralCollectionThresholds {
+    fn default() -> Self {
+        Self {
+            vec: usize::MAX,
+            dict: 100,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 pub struct StrId(pub u32);
 
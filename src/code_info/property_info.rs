@@ -30,6 +30,9 @@ pub struct PropertyInfo {
     // different runtime handling
     pub soft_readonly: bool,
 
+    // declared with the readonly modifier, so it can hold readonly values
+    pub is_readonly: bool,
+
     pub is_promoted: bool,
 
     pub is_internal: bool,
@@ -338,11 +338,17 @@ impl SymbolReferences {
 
         invalid_symbols.extend(invalid_symbol_bodies);
 
-        let partially_invalid_symbols = invalid_symbol_members
+        let mut partially_invalid_symbols = invalid_symbol_members
             .iter()
+            .chain(invalid_symbols.iter())
+            .filter(|(_, member_name)| !member_name.is_empty())
             .map(|(a, _)| *a)
             .collect::<FxHashSet<_>>();
 
+        // changes to a member only affect things that reference that member, which were
+        // handled above, so the classlike's other members are still valid
+        partially_invalid_symbols.extend(&codebase_diff.member_changes_only);
+
         for keep_signature in &codebase_diff.keep_signature {
             if !keep_signature.1.is_empty() {
                 invalid_symbol_members.insert((keep_signature.0, keep_signature.1));
@@ -111,6 +111,14 @@ pub enum TAtomic {
     // .1 => TNonEmptyString
     // .2 => TNonspecificLiteralString
     TStringWithFlags(bool, bool, bool),
+    // a string known to start with the given (non-empty) literal prefix
+    TStringWithPrefix {
+        prefix: String,
+    },
+    // a string known to match the given regex pattern (without delimiters)
+    TStringWithPattern {
+        pattern: String,
+    },
     TGenericParam {
         param_name: StrId,
         as_type: TUnion,
@@ -142,6 +150,8 @@ pub enum TAtomic {
         type_param: TUnion,
         known_count: Option<usize>,
         non_empty: bool,
+        // declared as a tuple, so has a fixed arity and can't be appended to
+        is_tuple: bool,
     },
     TVoid,
     TPlaceholder,
@@ -468,6 +478,12 @@ impl TAtomic {
 
                 return str + "string";
             }
+            TAtomic::TStringWithPrefix { prefix } => {
+                format!("string-with-prefix<'{}'>", prefix)
+            }
+            TAtomic::TStringWithPattern { pattern } => {
+                format!("string-with-pattern<'{}'>", pattern)
+            }
             TAtomic::TGenericParam {
                 param_name,
                 defining_entity,
@@ -661,7 +677,9 @@ impl TAtomic {
             | TAtomic::TEnumClassLabel { .. }
             | TAtomic::TMixedWithFlags(..) => self.get_id(None),
 
-            TAtomic::TStringWithFlags(..) => "string".to_string(),
+            TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. } => "string".to_string(),
 
             TAtomic::TNamedObject {
                 name,
@@ -877,6 +895,8 @@ impl TAtomic {
             | TAtomic::TNum { .. }
             | TAtomic::TString { .. }
             | TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. }
             | TAtomic::TTrue { .. }
             | TAtomic::TEnum { .. }
             | TAtomic::TEnumLiteralCase { .. } => true,
@@ -927,7 +947,9 @@ impl TAtomic {
             | TAtomic::TTypename { .. }
             | TAtomic::TGenericClassname { .. }
             | TAtomic::TGenericTypename { .. }
-            | TAtomic::TStringWithFlags { .. } => true,
+            | TAtomic::TStringWithFlags { .. }
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. } => true,
 
             _ => false,
         }
@@ -942,7 +964,9 @@ impl TAtomic {
             | TAtomic::TTypename { .. }
             | TAtomic::TGenericClassname { .. }
             | TAtomic::TGenericTypename { .. }
-            | TAtomic::TStringWithFlags { .. } => true,
+            | TAtomic::TStringWithFlags { .. }
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. } => true,
 
             _ => false,
         }
@@ -1005,6 +1029,7 @@ impl TAtomic {
         if let TAtomic::TVec {
             known_items,
             type_param,
+            is_tuple,
             ..
         } = self
         {
@@ -1013,6 +1038,7 @@ impl TAtomic {
                 type_param: type_param.clone(),
                 known_count: known_count,
                 non_empty: true,
+                is_tuple: *is_tuple,
             };
         }
 
@@ -1058,6 +1084,7 @@ impl TAtomic {
                 }
                 false
             }
+            &TAtomic::TStringWithPrefix { prefix } => prefix != "0",
             &TAtomic::TDict {
                 known_items,
                 non_empty,
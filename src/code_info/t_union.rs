@@ -35,6 +35,10 @@ pub struct TUnion {
     pub has_mutations: bool,
 
     pub populated: bool,
+
+    // Whether the value was reached through a readonly reference (e.g. a readonly
+    // parameter, or a property of a readonly object), so it can't be mutated
+    pub readonly: bool,
 }
 
 impl Hash for TUnion {
@@ -77,6 +81,7 @@ impl TUnion {
             from_template_default: false,
             has_mutations: true,
             populated: false,
+            readonly: false,
         }
     }
 
@@ -143,7 +148,9 @@ impl TUnion {
             match atomic {
                 TAtomic::TString { .. }
                 | TAtomic::TLiteralString { .. }
-                | TAtomic::TStringWithFlags { .. } => {
+                | TAtomic::TStringWithFlags { .. }
+                | TAtomic::TStringWithPrefix { .. }
+                | TAtomic::TStringWithPattern { .. } => {
                     return true;
                 }
                 _ => {}
@@ -569,6 +576,30 @@ impl TUnion {
         }
     }
 
+    // The literal prefix every string in this union starts with, if there is one
+    pub fn get_string_prefix(&self) -> Option<String> {
+        let mut common_prefix: Option<&str> = None;
+
+        for atomic in &self.types {
+            let prefix = match atomic {
+                TAtomic::TLiteralString { value } => value.as_str(),
+                TAtomic::TStringWithPrefix { prefix } => prefix.as_str(),
+                _ => return None,
+            };
+
+            common_prefix = Some(if let Some(common_prefix) = common_prefix {
+                get_common_prefix(common_prefix, prefix)
+            } else {
+                prefix
+            });
+        }
+
+        match common_prefix {
+            Some(common_prefix) if !common_prefix.is_empty() => Some(common_prefix.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn get_single_dict_key(&self) -> Option<DictKey> {
         if self.is_single() {
             match self.get_single() {
@@ -747,3 +778,14 @@ pub fn populate_union_type(
         }
     }
 }
+
+pub fn get_common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, a_char), b_char)| a_char != b_char)
+        .map(|((offset, _), _)| offset)
+        .unwrap_or_else(|| a.len().min(b.len()));
+
+    &a[..len]
+}
@@ -1,9 +1,9 @@
 use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
-use std::{hash::Hash, str::FromStr};
-use strum_macros::{Display, EnumString};
+use std::{fmt, hash::Hash, str::FromStr};
+use strum_macros::EnumString;
 
-#[derive(Clone, PartialEq, Eq, Hash, Display, Debug, Serialize, Deserialize, EnumString)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize, EnumString)]
 pub enum SourceType {
     UriRequestHeader,
     NonUriRequestHeader,
@@ -11,22 +11,26 @@ pub enum SourceType {
     UserPII,
     UserPassword,
     SystemSecret,
+    // A source defined in a taint category from config, written as category:Name
+    #[strum(disabled)]
+    Category(String, String),
 }
 
 impl SourceType {
-    pub fn get_error_message(&self) -> &str {
+    pub fn get_error_message(&self) -> String {
         match self {
-            SourceType::UriRequestHeader => "a URL query string",
-            SourceType::NonUriRequestHeader => "a server request",
-            SourceType::RawUserData => "raw user-controllable data",
-            SourceType::UserPassword => "a user secret",
-            SourceType::UserPII => "PII user data",
-            SourceType::SystemSecret => "a system secret",
+            SourceType::UriRequestHeader => "a URL query string".to_string(),
+            SourceType::NonUriRequestHeader => "a server request".to_string(),
+            SourceType::RawUserData => "raw user-controllable data".to_string(),
+            SourceType::UserPassword => "a user secret".to_string(),
+            SourceType::UserPII => "PII user data".to_string(),
+            SourceType::SystemSecret => "a system secret".to_string(),
+            SourceType::Category(category, name) => format!("{} source {}", category, name),
         }
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Display, Debug, Serialize, Deserialize, EnumString)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize, EnumString)]
 pub enum SinkType {
     HtmlTag,
     Sql,
@@ -42,6 +46,29 @@ pub enum SinkType {
     Logging,
     Output,
     Custom(String),
+    // A sink defined in a taint category from config, written as category:Name
+    #[strum(disabled)]
+    Category(String, String),
+}
+
+// Category taints are written the way they're referred to in config
+impl fmt::Display for SourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceType::Category(category, name) => write!(f, "{}:{}", category, name),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl fmt::Display for SinkType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkType::Custom(_) => write!(f, "Custom"),
+            SinkType::Category(category, name) => write!(f, "{}:{}", category, name),
+            _ => write!(f, "{:?}", self),
+        }
+    }
 }
 
 impl Default for SinkType {
@@ -120,9 +147,25 @@ impl SinkType {
             SinkType::Logging => "a logging method".to_string(),
             SinkType::Output => "generic output".to_string(),
             SinkType::Custom(str) => format!("Detected data passed to {}", str),
+            SinkType::Category(category, name) => format!("{} sink {}", category, name),
         }
     }
 
+    // The name used to refer to this sink in config and annotations
+    pub fn get_config_name(&self) -> String {
+        self.to_string()
+    }
+
+    // Taints that only matter for data embedded directly in HTML, which serialized data
+    // (e.g. the output of json_encode) can't break out of
+    pub fn html_taints() -> FxHashSet<SinkType> {
+        FxHashSet::from_iter([
+            SinkType::HtmlTag,
+            SinkType::HtmlAttribute,
+            SinkType::HtmlAttributeUri,
+        ])
+    }
+
     pub fn user_controllable_taints() -> FxHashSet<SinkType> {
         FxHashSet::from_iter([
             SinkType::HtmlTag,
@@ -141,8 +184,24 @@ impl SinkType {
     }
 }
 
+// The taints that builtin sanitizers remove from their first argument
+pub fn get_builtin_sanitizer_removed_taints(function_name: &str) -> Option<FxHashSet<SinkType>> {
+    match function_name {
+        "htmlentities" | "htmlspecialchars" | "strip_tags" => {
+            Some(FxHashSet::from_iter([SinkType::HtmlTag]))
+        }
+        _ => None,
+    }
+}
+
 pub fn string_to_source_types(str: String) -> Option<SourceType> {
-    SourceType::from_str(&str).ok()
+    if let Ok(source_type) = SourceType::from_str(&str) {
+        Some(source_type)
+    } else if let Some((category, name)) = str.split_once(':') {
+        Some(SourceType::Category(category.to_string(), name.to_string()))
+    } else {
+        None
+    }
 }
 
 pub fn string_to_sink_types(str: String) -> FxHashSet<SinkType> {
@@ -165,6 +224,8 @@ pub fn string_to_sink_types(str: String) -> FxHashSet<SinkType> {
                 FxHashSet::from_iter([sink_type])
             } else if str.starts_with("Custom:") {
                 FxHashSet::from_iter([SinkType::Custom(str.get(7..).unwrap().to_string())])
+            } else if let Some((category, name)) = str.split_once(':') {
+                FxHashSet::from_iter([SinkType::Category(category.to_string(), name.to_string())])
             } else {
                 FxHashSet::default()
             }
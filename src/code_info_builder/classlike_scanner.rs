@@ -492,6 +492,7 @@ pub(crate) fn scan(
     }
 
     storage.specialize_instance = true;
+    storage.generated = file_source.is_generated;
 
     let codegen_id = interner.intern_str("Codegen");
     let sealed_id = interner.intern_str("__Sealed");
@@ -515,6 +516,7 @@ pub(crate) fn scan(
                     &mut FxHashMap::default(),
                     attribute_param_expr,
                     resolved_names,
+                    &file_source.literal_collection_thresholds,
                 );
 
                 if let Some(attribute_param_type) = attribute_param_type {
@@ -664,6 +666,7 @@ fn visit_xhp_attribute(
         type_: attribute_type,
         has_default: xhp_attribute.1.expr.is_some(),
         soft_readonly: false,
+        is_readonly: false,
         is_promoted: false,
         is_internal: false,
     };
@@ -752,6 +755,7 @@ fn visit_class_const_declaration(
                 &mut FxHashMap::default(),
                 const_expr,
                 resolved_names,
+                &file_source.literal_collection_thresholds,
             )
         } else {
             None
@@ -761,6 +765,9 @@ fn visit_class_const_declaration(
     };
 
     classlike_storage.constants.insert(name, const_storage);
+    classlike_storage
+        .declaring_constant_ids
+        .insert(name, classlike_storage.name);
 }
 
 fn visit_class_typeconst_declaration(
@@ -896,6 +903,7 @@ fn visit_property_declaration(
         type_: property_type.unwrap_or(get_mixed_any()),
         has_default: property_node.expr.is_some(),
         soft_readonly: false,
+        is_readonly: property_node.readonly,
         is_promoted: false,
         is_internal: matches!(property_node.visibility, ast_defs::Visibility::Internal),
     };
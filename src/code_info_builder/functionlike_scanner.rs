@@ -4,6 +4,7 @@ use super::Context;
 use crate::simple_type_inferer;
 use crate::typehint_resolver::get_type_from_hint;
 use crate::typehint_resolver::get_type_from_optional_hint;
+use hakana_reflection_info::assertion::Assertion;
 use hakana_reflection_info::classlike_info::ClassLikeInfo;
 use hakana_reflection_info::code_location::HPos;
 use hakana_reflection_info::codebase_info::symbols::SymbolKind;
@@ -12,12 +13,13 @@ use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_reflection_info::functionlike_info::FnEffect;
 use hakana_reflection_info::functionlike_info::FunctionLikeInfo;
 use hakana_reflection_info::functionlike_parameter::FunctionLikeParameter;
-use hakana_reflection_info::issue::get_issue_from_comment;
 use hakana_reflection_info::issue::IssueKind;
+use hakana_reflection_info::issue::{get_issue_from_comment, get_suppression_expiry};
 use hakana_reflection_info::member_visibility::MemberVisibility;
 use hakana_reflection_info::method_info::MethodInfo;
 use hakana_reflection_info::property_info::PropertyInfo;
 use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
 use hakana_reflection_info::taint::string_to_sink_types;
 use hakana_reflection_info::taint::string_to_source_types;
 use hakana_reflection_info::type_resolution::TypeResolutionContext;
@@ -86,6 +88,7 @@ pub(crate) fn scan_method(
     );
 
     functionlike_info.is_production_code = file_source.is_production_code;
+    functionlike_info.readonly_return = m.readonly_ret.is_some();
 
     let mut classlike_storage = codebase.classlike_infos.get_mut(&classlike_name).unwrap();
 
@@ -176,6 +179,7 @@ fn add_promoted_param_property(
         .unwrap_or(get_mixed_any()),
         has_default: param_node.expr.is_some(),
         soft_readonly: false,
+        is_readonly: param_node.readonly.is_some(),
         is_promoted: false,
         is_internal: matches!(param_visibility, ast_defs::Visibility::Internal),
     };
@@ -239,6 +243,8 @@ pub(crate) fn get_functionlike(
 
     let mut functionlike_info = FunctionLikeInfo::new(name.clone(), definition_location);
 
+    functionlike_info.generated = file_source.is_generated;
+
     let mut template_supers = FxHashMap::default();
 
     if !tparams.is_empty() {
@@ -320,12 +326,23 @@ pub(crate) fn get_functionlike(
                     .unwrap();
 
             match where_first {
-                TAtomic::TGenericParam { param_name, .. } => match where_hint.1 {
+                TAtomic::TGenericParam {
+                    param_name,
+                    defining_entity,
+                    ..
+                } => match where_hint.1 {
                     ast_defs::ConstraintKind::ConstraintEq => {
                         functionlike_info
                             .where_constraints
                             .push((param_name, where_second));
                     }
+                    ast_defs::ConstraintKind::ConstraintAs => {
+                        functionlike_info.where_as_constraints.push((
+                            param_name,
+                            defining_entity,
+                            where_second,
+                        ));
+                    }
                     _ => {}
                 },
                 _ => {}
@@ -349,6 +366,15 @@ pub(crate) fn get_functionlike(
     functionlike_info.return_type =
         get_type_from_optional_hint(ret.get_hint(), None, &type_context, resolved_names);
 
+    add_param_assertions(
+        codebase,
+        interner,
+        params,
+        resolved_names,
+        file_source,
+        &mut functionlike_info,
+    );
+
     for user_attribute in user_attributes {
         let name = resolved_names
             .get(&user_attribute.name.0.start_offset())
@@ -365,6 +391,7 @@ pub(crate) fn get_functionlike(
                         &mut FxHashMap::default(),
                         attribute_param_expr,
                         resolved_names,
+                        &file_source.literal_collection_thresholds,
                     );
 
                     if let Some(attribute_param_type) = attribute_param_type {
@@ -398,6 +425,7 @@ pub(crate) fn get_functionlike(
                         &mut FxHashMap::default(),
                         attribute_param_expr,
                         resolved_names,
+                        &file_source.literal_collection_thresholds,
                     );
                     if let Some(attribute_param_type) = attribute_param_type {
                         attribute_param_type
@@ -420,6 +448,9 @@ pub(crate) fn get_functionlike(
             "__DynamicallyCallable" => {
                 functionlike_info.dynamically_callable = true;
             }
+            "__Memoize" | "__MemoizeLSB" => {
+                functionlike_info.is_memoized = true;
+            }
             "Codegen" => {
                 functionlike_info.generated = true;
             }
@@ -472,6 +503,8 @@ pub(crate) fn get_functionlike(
         }
     };
 
+    functionlike_info.is_non_deterministic = is_non_deterministic_function(functionlike_id);
+
     if matches!(functionlike_info.effects, FnEffect::None) || !functionlike_id.contains("::") {
         functionlike_info.specialize_call = true;
     }
@@ -486,6 +519,9 @@ pub(crate) fn get_functionlike(
                 {
                     functionlike_info.async_version = Some(function_id);
                 }
+
+                functionlike_info.wrapped_function =
+                    get_wrapped_function(expr, resolved_names, &functionlike_info.params);
             }
         }
     }
@@ -546,6 +582,26 @@ fn get_async_version(
     return None;
 }
 
+fn get_wrapped_function(
+    expr: &oxidized::ast::Expr,
+    resolved_names: &FxHashMap<usize, StrId>,
+    params: &Vec<FunctionLikeParameter>,
+) -> Option<StrId> {
+    if let aast::Expr_::Call(call) = &expr.2 {
+        if let (aast::Expr_::Id(boxed_id), Some((_, first_arg_expr)), Some(first_param)) =
+            (&call.0 .2, call.2.first(), params.first())
+        {
+            if let aast::Expr_::Lvar(id) = &first_arg_expr.2 {
+                if id.1 .1 == first_param.name {
+                    return resolved_names.get(&boxed_id.0.start_offset()).copied();
+                }
+            }
+        }
+    }
+
+    None
+}
+
 fn is_async_call_is_same_as_sync(
     call_args: &Vec<(ast_defs::ParamKind, aast::Expr<(), ()>)>,
     params: &Vec<FunctionLikeParameter>,
@@ -571,7 +627,7 @@ pub(crate) fn adjust_location_from_comments(
     comments: &Vec<(Pos, Comment)>,
     definition_location: &mut HPos,
     file_source: &FileSource,
-    suppressed_issues: &mut FxHashMap<IssueKind, HPos>,
+    suppressed_issues: &mut FxHashMap<IssueKind, (HPos, Option<String>)>,
     all_custom_issues: &FxHashSet<String>,
 ) {
     for (comment_pos, comment) in comments.iter().rev() {
@@ -597,7 +653,13 @@ pub(crate) fn adjust_location_from_comments(
                     {
                         if let Ok(issue_kind) = issue_kind {
                             let comment_pos = HPos::new(comment_pos, file_source.file_path, None);
-                            suppressed_issues.insert(issue_kind, comment_pos);
+                            suppressed_issues.insert(
+                                issue_kind,
+                                (
+                                    comment_pos,
+                                    get_suppression_expiry(trimmed_text).map(str::to_string),
+                                ),
+                            );
                         }
                     }
 
@@ -632,6 +694,7 @@ fn convert_param_nodes(
                 None
             };
             param.is_inout = matches!(param_node.callconv, ast_defs::ParamKind::Pinout(_));
+            param.is_readonly = param_node.readonly.is_some();
             param.signature_type_location = if let Some(param_type) = &param_node.type_hint.1 {
                 Some(HPos::new(&param_type.0, file_source.file_path, None))
             } else {
@@ -652,6 +715,7 @@ fn convert_param_nodes(
                                 &mut FxHashMap::default(),
                                 attribute_param_expr,
                                 resolved_names,
+                                &file_source.literal_collection_thresholds,
                             );
 
                             if let Some(attribute_param_type) = attribute_param_type {
@@ -674,6 +738,7 @@ fn convert_param_nodes(
                                 &mut FxHashMap::default(),
                                 attribute_param_expr,
                                 resolved_names,
+                                &file_source.literal_collection_thresholds,
                             );
 
                             if let Some(attribute_param_type) = attribute_param_type {
@@ -696,3 +761,122 @@ fn convert_param_nodes(
         })
         .collect()
 }
+
+// Type guard functions mark their params with <<\Hakana\AssertIfTrue(...)>> or
+// <<\Hakana\AssertIfFalse(...)>>, naming the type the param has when the function returns
+// true (or false), either as Foo::class or as a primitive type like 'string' or 'nonnull'
+fn add_param_assertions(
+    codebase: &CodebaseInfo,
+    interner: &mut ThreadedInterner,
+    param_nodes: &Vec<aast::FunParam<(), ()>>,
+    resolved_names: &FxHashMap<usize, StrId>,
+    file_source: &FileSource,
+    functionlike_info: &mut FunctionLikeInfo,
+) {
+    for (offset, param_node) in param_nodes.iter().enumerate() {
+        for user_attribute in &param_node.user_attributes {
+            let name = if let Some(name) = resolved_names.get(&user_attribute.name.0.start_offset())
+            {
+                name
+            } else {
+                continue;
+            };
+
+            let assertions = match interner.lookup(*name) {
+                "Hakana\\AssertIfTrue" => &mut functionlike_info.if_true_assertions,
+                "Hakana\\AssertIfFalse" => &mut functionlike_info.if_false_assertions,
+                _ => continue,
+            };
+
+            let assertion = if let Some(attribute_param_expr) = user_attribute.params.first() {
+                simple_type_inferer::infer(
+                    codebase,
+                    &mut FxHashMap::default(),
+                    attribute_param_expr,
+                    resolved_names,
+                    &file_source.literal_collection_thresholds,
+                )
+                .and_then(|attribute_param_type| get_param_assertion(&attribute_param_type))
+            } else {
+                None
+            };
+
+            if let Some(assertion) = assertion {
+                assertions
+                    .get_or_insert_with(FxHashMap::default)
+                    .insert(offset, assertion);
+            }
+        }
+    }
+}
+
+fn get_param_assertion(attribute_param_type: &TUnion) -> Option<Assertion> {
+    if !attribute_param_type.is_single() {
+        return None;
+    }
+
+    Some(match attribute_param_type.get_single() {
+        TAtomic::TLiteralClassname { name } => Assertion::IsType(TAtomic::TNamedObject {
+            name: *name,
+            type_params: None,
+            is_this: false,
+            extra_types: None,
+            remapped_params: false,
+        }),
+        TAtomic::TLiteralString { value } => match value.as_str() {
+            "nonnull" => Assertion::IsNotType(TAtomic::TNull),
+            "null" => Assertion::IsType(TAtomic::TNull),
+            "string" => Assertion::IsType(TAtomic::TString),
+            "int" => Assertion::IsType(TAtomic::TInt),
+            "float" => Assertion::IsType(TAtomic::TFloat),
+            "bool" => Assertion::IsType(TAtomic::TBool),
+            "num" => Assertion::IsType(TAtomic::TNum),
+            "arraykey" => Assertion::IsType(TAtomic::TArraykey { from_any: false }),
+            _ => {
+                return None;
+            }
+        },
+        _ => {
+            return None;
+        }
+    })
+}
+
+// Builtins whose results can change between calls with the same arguments
+fn is_non_deterministic_function(functionlike_id: &str) -> bool {
+    matches!(
+        functionlike_id,
+        "time"
+            | "microtime"
+            | "hrtime"
+            | "HH\\clock_gettime_ns"
+            | "rand"
+            | "mt_rand"
+            | "random_int"
+            | "random_bytes"
+            | "lcg_value"
+            | "uniqid"
+            | "shuffle"
+            | "str_shuffle"
+            | "array_rand"
+            | "openssl_random_pseudo_bytes"
+            | "getenv"
+            | "getmypid"
+            | "gethostname"
+            | "memory_get_usage"
+            | "memory_get_peak_usage"
+            | "file_get_contents"
+            | "file_exists"
+            | "file"
+            | "fgets"
+            | "fread"
+            | "readline"
+            | "HH\\Lib\\PseudoRandom\\float"
+            | "HH\\Lib\\PseudoRandom\\int"
+            | "HH\\Lib\\PseudoRandom\\string"
+            | "HH\\Lib\\SecureRandom\\float"
+            | "HH\\Lib\\SecureRandom\\int"
+            | "HH\\Lib\\SecureRandom\\string"
+            | "HH\\Lib\\Vec\\shuffle"
+    )
+}
@@ -0,0 +1,57 @@
+use oxidized::{aast, ast_defs};
+
+// Resolves the path of a require/include expression, relative to the project root.
+// Only literal paths (optionally prefixed with `__DIR__ .`) can be resolved statically.
+pub fn get_import_path(expr: &aast::Expr<(), ()>, file_path: &str) -> Option<String> {
+    let import_path = get_literal_path(expr, file_path)?;
+
+    if import_path.starts_with('/') {
+        return None;
+    }
+
+    normalize_path(&import_path)
+}
+
+fn get_literal_path(expr: &aast::Expr<(), ()>, file_path: &str) -> Option<String> {
+    match &expr.2 {
+        aast::Expr_::String(value) => Some(value.to_string()),
+        aast::Expr_::Id(id) if id.1 == "__DIR__" => {
+            // paths are relative to the project root, so this becomes a relative path too
+            Some(match file_path.rfind('/') {
+                Some(pos) => format!("./{}", &file_path[..pos]),
+                None => ".".to_string(),
+            })
+        }
+        aast::Expr_::Binop(boxed) => {
+            if let ast_defs::Bop::Dot = boxed.0 {
+                Some(
+                    get_literal_path(&boxed.1, file_path)?
+                        + &get_literal_path(&boxed.2, file_path)?,
+                )
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn normalize_path(path: &str) -> Option<String> {
+    let mut segments = vec![];
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("/"))
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use crate::typehint_resolver::get_type_from_hint;
 use hakana_aast_helper::Uses;
-use hakana_reflection_info::file_info::FileInfo;
+use hakana_reflection_info::file_info::{FileInfo, SymbolUseStatement};
 use hakana_reflection_info::functionlike_info::FunctionLikeInfo;
 use hakana_reflection_info::{
     ast_signature::DefSignatureNode, class_constant_info::ConstantInfo, classlike_info::Variance,
@@ -16,7 +16,8 @@ use hakana_type::get_mixed_any;
 use indexmap::IndexMap;
 use no_pos_hash::{position_insensitive_hash, Hasher};
 use oxidized::ast::{FunParam, Tparam, TypeHint};
-use oxidized::ast_defs::Id;
+use oxidized::ast_defs::{Id, Pos};
+use oxidized::prim_defs::Comment;
 use oxidized::{
     aast,
     aast_visitor::{visit, AstParams, Node, Visitor},
@@ -26,6 +27,7 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 mod classlike_scanner;
 mod functionlike_scanner;
+pub mod import_resolver;
 pub mod simple_type_inferer;
 pub mod typehint_resolver;
 
@@ -48,6 +50,8 @@ struct Scanner<'a> {
     user_defined: bool,
     closures: FxHashMap<usize, FunctionLikeInfo>,
     ast_nodes: Vec<DefSignatureNode>,
+    imported_files: Vec<StrId>,
+    symbol_uses: Vec<SymbolUseStatement>,
     uses: Uses,
 }
 
@@ -67,6 +71,31 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                 }
             }
             aast::Def::NamespaceUse(uses) => {
+                // only statements with a single clause can be removed wholesale later
+                if let [(ns_kind, name, alias_name)] = &uses[..] {
+                    if !matches!(ns_kind, aast::NsKind::NSNamespace | aast::NsKind::NSConst) {
+                        let start_offset = name.0.to_raw_span().start.beg_of_line() as usize;
+                        let mut end_offset = alias_name.0.end_offset() + 1;
+
+                        // when the statement sits between blank lines, one of them goes too
+                        if self.file_source.file_contents[..start_offset].ends_with("\n\n")
+                            && self.file_source.file_contents[end_offset..].starts_with("\n\n")
+                        {
+                            end_offset += 1;
+                        }
+
+                        self.symbol_uses.push(SymbolUseStatement {
+                            name: self.interner.intern_str(if name.1.starts_with("\\") {
+                                &name.1[1..]
+                            } else {
+                                &name.1
+                            }),
+                            start_offset,
+                            end_offset,
+                        });
+                    }
+                }
+
                 for (_, name, alias_name) in uses {
                     let adjusted_start = name.0.to_raw_span().start.beg_of_line() as usize;
                     if let Some(ref mut uses_position) = c.uses_position {
@@ -178,6 +207,7 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                     &mut FxHashMap::default(),
                     &gc.value,
                     &self.resolved_names,
+                    &self.file_source.literal_collection_thresholds,
                 ),
                 unresolved_value: None,
                 is_abstract: false,
@@ -348,6 +378,7 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                 &mut FxHashMap::default(),
                 attribute_param_expr,
                 &self.resolved_names,
+                &self.file_source.literal_collection_thresholds,
             );
 
             if let Some(attribute_param_type) = attribute_param_type {
@@ -571,6 +602,55 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
             aast::Expr_::Efun(f) => {
                 fun = Some(&f.fun);
             }
+            aast::Expr_::Call(call) => match &call.0 .2 {
+                aast::Expr_::Id(id) => {
+                    if let Some(function_name) =
+                        self.resolved_names.get(&id.0.start_offset()).copied()
+                    {
+                        if let Some(functionlike_storage) = self.get_current_functionlike(c) {
+                            functionlike_storage.called_functions.insert(function_name);
+                        }
+                    }
+                }
+                aast::Expr_::ClassConst(boxed) => {
+                    if let Some(classlike_name) = self.get_static_call_class(c, &boxed.0) {
+                        let method_name = self.interner.intern(boxed.1 .1.clone());
+
+                        if let Some(functionlike_storage) = self.get_current_functionlike(c) {
+                            functionlike_storage
+                                .called_methods
+                                .insert((classlike_name, method_name));
+                        }
+                    }
+                }
+                aast::Expr_::ObjGet(boxed) => {
+                    if let (aast::Expr_::Lvar(lid), aast::Expr_::Id(id), Some(classlike_name)) =
+                        (&boxed.0 .2, &boxed.1 .2, c.classlike_name)
+                    {
+                        if lid.1 .1 == "$this" {
+                            let method_name = self.interner.intern(id.1.clone());
+
+                            if let Some(functionlike_storage) = self.get_current_functionlike(c) {
+                                functionlike_storage
+                                    .called_methods
+                                    .insert((classlike_name, method_name));
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            },
+            aast::Expr_::Import(boxed) => {
+                let file_path = self.interner.lookup(self.file_source.file_path).to_string();
+
+                if let Some(import_path) = import_resolver::get_import_path(&boxed.1, &file_path) {
+                    let import_path_id = self.interner.intern(import_path);
+
+                    if !self.imported_files.contains(&import_path_id) {
+                        self.imported_files.push(import_path_id);
+                    }
+                }
+            }
             _ => (),
         }
 
@@ -593,6 +673,47 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
 }
 
 impl<'a> Scanner<'a> {
+    // the class a static call resolves to, where that's known without type inference
+    fn get_static_call_class(
+        &self,
+        c: &Context,
+        class_id: &aast::ClassId<(), ()>,
+    ) -> Option<StrId> {
+        if let aast::ClassId_::CIexpr(lhs_expr) = &class_id.2 {
+            if let aast::Expr_::Id(id) = &lhs_expr.2 {
+                return match id.1.as_str() {
+                    "self" | "static" => c.classlike_name,
+                    "parent" => {
+                        self.codebase
+                            .classlike_infos
+                            .get(c.classlike_name.as_ref()?)?
+                            .direct_parent_class
+                    }
+                    _ => self.resolved_names.get(&id.0.start_offset()).copied(),
+                };
+            }
+        }
+
+        None
+    }
+
+    // closures are attributed to the function or method they're defined in
+    fn get_current_functionlike(&mut self, c: &Context) -> Option<&mut FunctionLikeInfo> {
+        if let Some(function_name) = &c.function_name {
+            self.codebase.functionlike_infos.get_mut(function_name)
+        } else if let (Some(classlike_name), Some(member_name)) =
+            (&c.classlike_name, &c.member_name)
+        {
+            self.codebase
+                .classlike_infos
+                .get_mut(classlike_name)?
+                .methods
+                .get_mut(member_name)
+        } else {
+            None
+        }
+    }
+
     fn visit_function(
         &mut self,
         is_anonymous: bool,
@@ -667,6 +788,7 @@ impl<'a> Scanner<'a> {
         );
 
         functionlike_storage.is_production_code = self.file_source.is_production_code;
+        functionlike_storage.readonly_return = fun.readonly_ret.is_some();
 
         functionlike_storage.user_defined = self.user_defined && !is_anonymous;
         functionlike_storage.type_resolution_context = Some(type_resolution_context);
@@ -738,6 +860,8 @@ pub fn collect_info_for_aast(
 ) {
     let file_path_id = file_source.file_path;
 
+    let regeneration_command = get_regeneration_command(&file_source.comments);
+
     let mut checker = Scanner {
         codebase,
         interner,
@@ -747,6 +871,8 @@ pub fn collect_info_for_aast(
         all_custom_issues,
         closures: FxHashMap::default(),
         ast_nodes: Vec::new(),
+        imported_files: Vec::new(),
+        symbol_uses: Vec::new(),
         uses,
     };
 
@@ -766,7 +892,34 @@ pub fn collect_info_for_aast(
             FileInfo {
                 closure_infos: checker.closures,
                 ast_nodes: checker.ast_nodes,
+                imported_files: checker.imported_files,
+                symbol_uses: checker.symbol_uses,
+                regeneration_command,
             },
         );
     }
 }
+
+// Generated files can document how they're regenerated with a header comment like
+// `@codegen-command : scripts/codegen.sh Foo`. Only the first ten lines count.
+fn get_regeneration_command(comments: &Vec<(Pos, Comment)>) -> Option<String> {
+    comments
+        .iter()
+        .take_while(|(pos, _)| pos.line() <= 10)
+        .find_map(|(_, comment)| match comment {
+            Comment::CmtLine(text) | Comment::CmtBlock(text) => text.lines().find_map(|line| {
+                let (_, command) = line.split_once("@codegen-command")?;
+                let command = command
+                    .trim()
+                    .trim_start_matches(':')
+                    .trim_end_matches("*/")
+                    .trim();
+
+                if command.is_empty() {
+                    None
+                } else {
+                    Some(command.to_string())
+                }
+            }),
+        })
+}
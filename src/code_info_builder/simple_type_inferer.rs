@@ -2,11 +2,11 @@ use hakana_reflection_info::{
     codebase_info::CodebaseInfo,
     t_atomic::{DictKey, TAtomic},
     t_union::TUnion,
-    StrId,
+    LiteralCollectionThresholds, StrId,
 };
 use hakana_type::{
     get_false, get_float, get_int, get_literal_int, get_literal_string, get_nothing, get_null,
-    get_true, wrap_atomic,
+    get_true, type_combiner, wrap_atomic,
 };
 use oxidized::{
     aast,
@@ -20,6 +20,7 @@ pub fn infer(
     expr_types: &mut FxHashMap<Pos, TUnion>,
     expr: &aast::Expr<(), ()>,
     resolved_names: &FxHashMap<usize, StrId>,
+    thresholds: &LiteralCollectionThresholds,
 ) -> Option<TUnion> {
     return match &expr.2 {
         aast::Expr_::ArrayGet(_) => None,
@@ -57,7 +58,13 @@ pub fn infer(
 
             for (shape_field_name, field_expr) in shape_fields {
                 if let ast_defs::ShapeFieldName::SFlitStr((_, str)) = shape_field_name {
-                    let field_type = infer(codebase, expr_types, &field_expr, resolved_names);
+                    let field_type = infer(
+                        codebase,
+                        expr_types,
+                        &field_expr,
+                        resolved_names,
+                        thresholds,
+                    );
 
                     if let Some(field_type) = field_type {
                         known_items.insert(
@@ -83,7 +90,13 @@ pub fn infer(
             let mut entries = BTreeMap::new();
 
             for (i, entry_expr) in boxed.2.iter().enumerate() {
-                let entry_type = infer(codebase, expr_types, &entry_expr, resolved_names);
+                let entry_type = infer(
+                    codebase,
+                    expr_types,
+                    &entry_expr,
+                    resolved_names,
+                    thresholds,
+                );
 
                 if let Some(entry_type) = entry_type {
                     entries.insert(i, (false, entry_type));
@@ -93,12 +106,7 @@ pub fn infer(
             }
 
             match boxed.0.1 {
-                oxidized::tast::VcKind::Vec => Some(wrap_atomic(TAtomic::TVec {
-                    known_count: Some(entries.len()),
-                    known_items: Some(entries),
-                    type_param: get_nothing(),
-                    non_empty: true,
-                })),
+                oxidized::tast::VcKind::Vec => Some(get_vec_type(entries, codebase, thresholds)),
                 oxidized::tast::VcKind::Keyset => None,
                 _ => panic!(),
             }
@@ -108,7 +116,13 @@ pub fn infer(
 
             for entry_field in &boxed.2 {
                 if let aast::Expr_::String(key_value) = &entry_field.0 .2 {
-                    let value_type = infer(codebase, expr_types, &entry_field.1, resolved_names);
+                    let value_type = infer(
+                        codebase,
+                        expr_types,
+                        &entry_field.1,
+                        resolved_names,
+                        thresholds,
+                    );
 
                     if let Some(value_type) = value_type {
                         known_items.insert(
@@ -123,18 +137,11 @@ pub fn infer(
                 }
             }
 
-            if known_items.len() < 100 {
-                match boxed.0.1 {
-                    oxidized::tast::KvcKind::Dict => Some(wrap_atomic(TAtomic::TDict {
-                        non_empty: !known_items.is_empty(),
-                        known_items: Some(known_items),
-                        params: None,
-                        shape_name: None,
-                    })),
-                    _ => panic!(),
+            match boxed.0.1 {
+                oxidized::tast::KvcKind::Dict => {
+                    Some(get_dict_type(known_items, codebase, thresholds))
                 }
-            } else {
-                None
+                _ => panic!(),
             }
         }
         aast::Expr_::Null => Some(get_null()),
@@ -160,7 +167,13 @@ pub fn infer(
             let mut entries = BTreeMap::new();
 
             for (i, entry_expr) in values.iter().enumerate() {
-                let entry_type = infer(codebase, expr_types, &entry_expr, resolved_names);
+                let entry_type = infer(
+                    codebase,
+                    expr_types,
+                    &entry_expr,
+                    resolved_names,
+                    thresholds,
+                );
 
                 if let Some(entry_type) = entry_type {
                     entries.insert(i, (false, entry_type));
@@ -174,6 +187,7 @@ pub fn infer(
                 known_items: Some(entries),
                 type_param: get_nothing(),
                 non_empty: true,
+                is_tuple: true,
             }))
         }
         aast::Expr_::Binop(boxed) => {
@@ -185,7 +199,7 @@ pub fn infer(
         }
         aast::Expr_::Unop(boxed) => {
             if let ast_defs::Uop::Uminus = boxed.0 {
-                let number_type = infer(codebase, expr_types, &boxed.1, resolved_names);
+                let number_type = infer(codebase, expr_types, &boxed.1, resolved_names, thresholds);
 
                 if let Some(number_type) = number_type {
                     if number_type.is_single() {
@@ -215,7 +229,13 @@ pub fn infer(
 
             for (key_expr, value_expr) in &boxed.1 {
                 if let aast::Expr_::String(key_value) = &key_expr.2 {
-                    let value_type = infer(codebase, expr_types, &value_expr, resolved_names);
+                    let value_type = infer(
+                        codebase,
+                        expr_types,
+                        &value_expr,
+                        resolved_names,
+                        thresholds,
+                    );
 
                     if let Some(value_type) = value_type {
                         known_items.insert(
@@ -230,18 +250,19 @@ pub fn infer(
                 }
             }
 
-            Some(wrap_atomic(TAtomic::TDict {
-                non_empty: !known_items.is_empty(),
-                known_items: Some(known_items),
-                params: None,
-                shape_name: None,
-            }))
+            Some(get_dict_type(known_items, codebase, thresholds))
         }
         aast::Expr_::Varray(boxed) => {
             let mut entries = BTreeMap::new();
 
             for (i, entry_expr) in boxed.1.iter().enumerate() {
-                let entry_type = infer(codebase, expr_types, &entry_expr, resolved_names);
+                let entry_type = infer(
+                    codebase,
+                    expr_types,
+                    &entry_expr,
+                    resolved_names,
+                    thresholds,
+                );
 
                 if let Some(entry_type) = entry_type {
                     entries.insert(i, (false, entry_type));
@@ -250,12 +271,7 @@ pub fn infer(
                 }
             }
 
-            Some(wrap_atomic(TAtomic::TVec {
-                known_count: Some(entries.len()),
-                known_items: Some(entries),
-                type_param: get_nothing(),
-                non_empty: true,
-            }))
+            Some(get_vec_type(entries, codebase, thresholds))
         }
         aast::Expr_::New(..) => None,
         aast::Expr_::Omitted => None,
@@ -265,3 +281,72 @@ pub fn infer(
         }
     };
 }
+
+// Above the threshold, the entries' types are combined rather than kept individually,
+// as in collection_analyzer
+fn get_vec_type(
+    entries: BTreeMap<usize, (bool, TUnion)>,
+    codebase: &CodebaseInfo,
+    thresholds: &LiteralCollectionThresholds,
+) -> TUnion {
+    if entries.len() <= thresholds.vec {
+        return wrap_atomic(TAtomic::TVec {
+            known_count: Some(entries.len()),
+            known_items: Some(entries),
+            type_param: get_nothing(),
+            non_empty: true,
+            is_tuple: false,
+        });
+    }
+
+    wrap_atomic(TAtomic::TVec {
+        known_count: Some(entries.len()),
+        known_items: None,
+        type_param: TUnion::new(type_combiner::combine(
+            entries
+                .into_values()
+                .flat_map(|(_, entry_type)| entry_type.types)
+                .collect(),
+            codebase,
+            false,
+        )),
+        non_empty: true,
+        is_tuple: false,
+    })
+}
+
+fn get_dict_type(
+    known_items: BTreeMap<DictKey, (bool, Arc<TUnion>)>,
+    codebase: &CodebaseInfo,
+    thresholds: &LiteralCollectionThresholds,
+) -> TUnion {
+    if known_items.len() <= thresholds.dict {
+        return wrap_atomic(TAtomic::TDict {
+            non_empty: !known_items.is_empty(),
+            known_items: Some(known_items),
+            params: None,
+            shape_name: None,
+        });
+    }
+
+    let mut key_types = vec![];
+    let mut value_types = vec![];
+
+    for (key, (_, value_type)) in known_items {
+        if let DictKey::String(value) = key {
+            key_types.push(TAtomic::TLiteralString { value });
+        }
+
+        value_types.extend(value_type.types.clone());
+    }
+
+    wrap_atomic(TAtomic::TDict {
+        non_empty: true,
+        known_items: None,
+        params: Some((
+            TUnion::new(type_combiner::combine(key_types, codebase, false)),
+            TUnion::new(type_combiner::combine(value_types, codebase, false)),
+        )),
+        shape_name: None,
+    })
+}
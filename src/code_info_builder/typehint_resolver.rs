@@ -27,6 +27,7 @@ fn get_vec_type_from_hint(
             .unwrap(),
         known_count: None,
         non_empty: false,
+        is_tuple: false,
         known_items: None,
     }
 }
@@ -41,6 +42,7 @@ fn get_tuple_type_from_hints(
         type_param: get_nothing(),
         known_count: Some(hints.len()),
         non_empty: true,
+        is_tuple: true,
         known_items: Some({
             let mut map = BTreeMap::new();
             let mut i = 0;
@@ -414,6 +416,7 @@ pub fn get_type_from_hint(
                             known_items: None,
                             known_count: None,
                             non_empty: false,
+                            is_tuple: false,
                         }
                     }
                 }
@@ -481,6 +484,7 @@ pub fn get_type_from_hint(
                             true, false, false, false,
                         )),
                         non_empty: false,
+                        is_tuple: false,
                         known_count: None,
                     });
                     TAtomic::TDict {
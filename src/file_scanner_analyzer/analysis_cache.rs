@@ -0,0 +1,419 @@
+use crate::error::HakanaError;
+use crate::file_cache_provider::FileStatus;
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_reflection_info::analysis_result::{AnalysisResult, TypeCoverage};
+use hakana_reflection_info::ast_signature::DefSignatureNode;
+use hakana_reflection_info::codebase_info::symbols::SymbolKind;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::data_flow::graph::DataFlowGraph;
+use hakana_reflection_info::issue::{suppression_has_expired, Issue, IssueKind};
+use hakana_reflection_info::symbol_references::SymbolReferences;
+use hakana_reflection_info::StrId;
+use indexmap::IndexMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use std::sync::Mutex;
+
+// Everything a file's analysis contributed to the analysis result, along with what it
+// was computed from. Like AST-diff mode, this assumes that a file's analysis only
+// depends on the signatures (and not the bodies) of the symbols it references.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct CachedFileAnalysis {
+    file_id: StrId,
+    contents_hash: u64,
+    // files defining symbols this file references
+    dependencies: Vec<StrId>,
+    dependency_digest: u64,
+    // set when the file references unknown symbols, which may since have been added
+    symbols_digest: Option<u64>,
+    // the classes using the file's traits, when trait methods are analyzed in them
+    trait_users_digest: Option<u64>,
+    // source-mapped issues can be emitted for other files
+    emitted_issues: BTreeMap<String, Vec<Issue>>,
+    symbol_references: SymbolReferences,
+    program_dataflow_graph: DataFlowGraph,
+    mixed_source_counts: FxHashMap<String, FxHashSet<String>>,
+    issue_counts: FxHashMap<IssueKind, usize>,
+    type_coverage: Option<TypeCoverage>,
+    // once a suppression in the file expires, its issues have to be reported again
+    suppression_expiry: Option<String>,
+}
+
+// Per-file analysis results from previous runs, keyed by file path
+pub(crate) struct AnalysisCache {
+    config_fingerprint: u64,
+    file_analyses: Mutex<FxHashMap<String, CachedFileAnalysis>>,
+    // the contents hash of each file, and whether it's unchanged since the last run
+    contents_hashes: FxHashMap<String, (u64, bool)>,
+    symbol_files: FxHashMap<StrId, StrId>,
+    file_signature_hashes: FxHashMap<StrId, u64>,
+    symbols_digest: u64,
+    trait_users_digests: FxHashMap<StrId, u64>,
+}
+
+impl AnalysisCache {
+    pub(crate) fn load(
+        cache_path: &String,
+        config: &Config,
+        codebase: &CodebaseInfo,
+        file_statuses: &IndexMap<String, FileStatus>,
+        verbosity: Verbosity,
+    ) -> Self {
+        let config_fingerprint = get_config_fingerprint(config);
+
+        let mut file_analyses = FxHashMap::default();
+
+        if Path::new(cache_path).exists() {
+            if !matches!(verbosity, Verbosity::Quiet) {
+                println!("Deserializing file analysis cache");
+            }
+
+            if let Ok(cache_file) = fs::File::open(cache_path) {
+                if let Ok((cached_fingerprint, cached_analyses)) =
+                    bincode::deserialize_from::<_, (u64, FxHashMap<String, CachedFileAnalysis>)>(
+                        BufReader::new(cache_file),
+                    )
+                {
+                    // results depend on which issues are reported, how, and what's tracked
+                    if cached_fingerprint == config_fingerprint {
+                        file_analyses = cached_analyses;
+                    }
+                }
+            }
+        }
+
+        let mut symbol_files = FxHashMap::default();
+        let mut file_signature_hashes = FxHashMap::default();
+        let mut symbols_digest = 0;
+
+        for (file_id, file_info) in &codebase.files {
+            let mut signature_input = vec![];
+
+            for ast_node in &file_info.ast_nodes {
+                symbol_files.insert(ast_node.name, *file_id);
+                symbols_digest ^=
+                    xxhash_rust::xxh3::xxh3_64(codebase.interner.lookup(&ast_node.name).as_bytes());
+                add_signature_hashes(ast_node, &mut signature_input);
+            }
+
+            file_signature_hashes.insert(*file_id, xxhash_rust::xxh3::xxh3_64(&signature_input));
+        }
+
+        let trait_users_digests = if config.analyze_traits_in_using_classes {
+            get_trait_users_digests(codebase, &symbol_files, &file_signature_hashes)
+        } else {
+            FxHashMap::default()
+        };
+
+        let contents_hashes = file_statuses
+            .iter()
+            .filter_map(|(file_path, status)| match status {
+                FileStatus::Unchanged(contents_hash, _) => {
+                    Some((file_path.clone(), (*contents_hash, true)))
+                }
+                FileStatus::Added(contents_hash, _) | FileStatus::Modified(contents_hash, _) => {
+                    Some((file_path.clone(), (*contents_hash, false)))
+                }
+                FileStatus::Deleted => None,
+            })
+            .collect();
+
+        Self {
+            config_fingerprint,
+            file_analyses: Mutex::new(file_analyses),
+            contents_hashes,
+            symbol_files,
+            file_signature_hashes,
+            symbols_digest,
+            trait_users_digests,
+        }
+    }
+
+    // Only files that are unchanged since the last run are looked up, as otherwise
+    // the cache might have been rebuilt, and interned ids might not line up
+    pub(crate) fn get(&self, file_path: &String) -> Option<AnalysisResult> {
+        let (contents_hash, is_unchanged) = self.contents_hashes.get(file_path)?;
+
+        if !is_unchanged {
+            return None;
+        }
+
+        let file_analyses = self.file_analyses.lock().unwrap();
+
+        let cached_analysis = file_analyses.get(file_path)?;
+
+        if cached_analysis.contents_hash != *contents_hash
+            || cached_analysis.dependency_digest
+                != self.get_dependency_digest(&cached_analysis.dependencies)
+            || cached_analysis
+                .symbols_digest
+                .map_or(false, |symbols_digest| {
+                    symbols_digest != self.symbols_digest
+                })
+            || cached_analysis.trait_users_digest
+                != self
+                    .trait_users_digests
+                    .get(&cached_analysis.file_id)
+                    .copied()
+            || cached_analysis
+                .suppression_expiry
+                .as_ref()
+                .map_or(false, |expiry| suppression_has_expired(expiry))
+        {
+            return None;
+        }
+
+        let cached_analysis = cached_analysis.clone();
+
+        let mut analysis_result = AnalysisResult::new(
+            cached_analysis.program_dataflow_graph.kind,
+            cached_analysis.symbol_references,
+        );
+
+        analysis_result.emitted_issues = cached_analysis.emitted_issues;
+        analysis_result.program_dataflow_graph = cached_analysis.program_dataflow_graph;
+        analysis_result.mixed_source_counts = cached_analysis.mixed_source_counts;
+        analysis_result.issue_counts = cached_analysis.issue_counts;
+
+        if let Some(type_coverage) = cached_analysis.type_coverage {
+            analysis_result
+                .type_coverage
+                .insert(file_path.clone(), type_coverage);
+        }
+
+        Some(analysis_result)
+    }
+
+    pub(crate) fn insert(
+        &self,
+        file_path: &String,
+        file_id: StrId,
+        resolved_names: &FxHashMap<usize, StrId>,
+        analysis_result: &AnalysisResult,
+    ) {
+        let contents_hash = if let Some((contents_hash, _)) = self.contents_hashes.get(file_path) {
+            *contents_hash
+        } else {
+            return;
+        };
+
+        // fixes aren't cached
+        if !analysis_result.replacements.is_empty() {
+            return;
+        }
+
+        let references = &analysis_result.symbol_references;
+
+        let dependencies = references
+            .symbol_references_to_symbols
+            .values()
+            .chain(
+                references
+                    .symbol_references_to_symbols_in_signature
+                    .values(),
+            )
+            .chain(references.symbol_references_to_overridden_members.values())
+            .flatten()
+            .filter_map(|(symbol, _)| self.symbol_files.get(symbol))
+            .filter(|dependency_file_id| **dependency_file_id != file_id)
+            .copied()
+            .collect::<FxHashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        // names that resolve to nothing may be defined later, whether or not looking them
+        // up produced an issue (it might have been suppressed, or guarded by class_exists)
+        let references_unknown_symbols = resolved_names
+            .values()
+            .any(|name| !self.symbol_files.contains_key(name))
+            || analysis_result
+                .emitted_issues
+                .values()
+                .flatten()
+                .any(|issue| {
+                    matches!(
+                        issue.kind,
+                        IssueKind::NonExistentClass
+                            | IssueKind::NonExistentClasslike
+                            | IssueKind::NonExistentClassConstant
+                            | IssueKind::NonExistentFunction
+                            | IssueKind::NonExistentMethod
+                            | IssueKind::NonExistentProperty
+                            | IssueKind::NonExistentType
+                    )
+                });
+
+        self.file_analyses.lock().unwrap().insert(
+            file_path.clone(),
+            CachedFileAnalysis {
+                file_id,
+                contents_hash,
+                dependency_digest: self.get_dependency_digest(&dependencies),
+                dependencies,
+                symbols_digest: if references_unknown_symbols {
+                    Some(self.symbols_digest)
+                } else {
+                    None
+                },
+                trait_users_digest: self.trait_users_digests.get(&file_id).copied(),
+                emitted_issues: analysis_result.emitted_issues.clone(),
+                symbol_references: analysis_result.symbol_references.clone(),
+                program_dataflow_graph: analysis_result.program_dataflow_graph.clone(),
+                mixed_source_counts: analysis_result.mixed_source_counts.clone(),
+                issue_counts: analysis_result.issue_counts.clone(),
+                type_coverage: analysis_result.type_coverage.get(file_path).copied(),
+                suppression_expiry: analysis_result.suppression_expiries.values().min().cloned(),
+            },
+        );
+    }
+
+    pub(crate) fn save(self, cache_path: &String) -> Result<(), HakanaError> {
+        let mut file_analyses = self.file_analyses.into_inner().unwrap();
+
+        // drop results for files that no longer exist
+        file_analyses.retain(|file_path, _| self.contents_hashes.contains_key(file_path));
+
+        let cache_file = BufWriter::new(fs::File::create(cache_path)?);
+
+        bincode::serialize_into(cache_file, &(self.config_fingerprint, file_analyses))
+            .map_err(|error| HakanaError::Io(io::Error::new(io::ErrorKind::Other, error)))
+    }
+
+    fn get_dependency_digest(&self, dependencies: &Vec<StrId>) -> u64 {
+        let mut digest_input = vec![];
+
+        for dependency_file_id in dependencies {
+            // a deleted file gets a different digest than one with no signatures
+            let signature_hash = self
+                .file_signature_hashes
+                .get(dependency_file_id)
+                .map_or(u64::MAX, |signature_hash| *signature_hash);
+
+            digest_input.extend(signature_hash.to_le_bytes());
+        }
+
+        xxhash_rust::xxh3::xxh3_64(&digest_input)
+    }
+}
+
+// Fixes, migrations, typed AST dumps, shape reports, nullable return refinement, issue
+// profile reports and program visitors need every file to be analyzed
+pub(crate) fn can_use_analysis_cache(config: &Config) -> bool {
+    config.issues_to_fix.is_empty()
+        && config.migration_symbols.is_empty()
+        && !config.add_fixmes
+        && !config.remove_fixmes
+        && !config.ast_diff
+        && config.typed_ast_file.is_none()
+        && !config.collect_shape_params
+        && !config.refine_nullable_returns
+        && !config.collect_issue_profile_files
+        && config.program_visitors.is_empty()
+}
+
+// Trait methods can be analyzed in the classes that use them, so the results for a file
+// defining traits depend on which classes use them, and on those classes' signatures
+fn get_trait_users_digests(
+    codebase: &CodebaseInfo,
+    symbol_files: &FxHashMap<StrId, StrId>,
+    file_signature_hashes: &FxHashMap<StrId, u64>,
+) -> FxHashMap<StrId, u64> {
+    let mut trait_users = FxHashMap::default();
+
+    for classlike_info in codebase.classlike_infos.values() {
+        if matches!(classlike_info.kind, SymbolKind::Trait) {
+            continue;
+        }
+
+        for used_trait in &classlike_info.used_traits {
+            trait_users
+                .entry(*used_trait)
+                .or_insert_with(Vec::new)
+                .push(classlike_info.name);
+        }
+    }
+
+    let mut trait_users_digests = FxHashMap::default();
+
+    for (trait_name, mut user_names) in trait_users {
+        let trait_file_id = if let Some(trait_file_id) = symbol_files.get(&trait_name) {
+            *trait_file_id
+        } else {
+            continue;
+        };
+
+        user_names.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut digest_input = trait_name.0.to_le_bytes().to_vec();
+
+        for user_name in user_names {
+            digest_input.extend(user_name.0.to_le_bytes());
+
+            let signature_hash = symbol_files
+                .get(&user_name)
+                .and_then(|user_file_id| file_signature_hashes.get(user_file_id))
+                .map_or(u64::MAX, |signature_hash| *signature_hash);
+
+            digest_input.extend(signature_hash.to_le_bytes());
+        }
+
+        // a file can define several traits
+        *trait_users_digests.entry(trait_file_id).or_insert(0) ^=
+            xxhash_rust::xxh3::xxh3_64(&digest_input);
+    }
+
+    trait_users_digests
+}
+
+fn add_signature_hashes(ast_node: &DefSignatureNode, signature_input: &mut Vec<u8>) {
+    signature_input.extend(ast_node.name.0.to_le_bytes());
+    signature_input.extend(ast_node.signature_hash.to_le_bytes());
+
+    for child in &ast_node.children {
+        add_signature_hashes(child, signature_input);
+    }
+}
+
+// The settings that change what analyzing a file produces
+pub(crate) fn get_config_fingerprint(config: &Config) -> u64 {
+    let settings = format!(
+        "{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{:?}\n{}",
+        config.graph_kind,
+        config.find_unused_expressions,
+        config.find_unused_definitions,
+        config.allowed_issues,
+        config.ignore_files,
+        config.test_files,
+        config.ignore_issue_files,
+        config.ignore_all_issues_in_files,
+        config.security_config,
+        config.ignore_mixed_issues,
+        config.all_custom_issues,
+        config.function_aliases,
+        config.report_generated_issues_at_origin,
+        config.issue_help_uri,
+        config.generated_issue_policies,
+        config.default_generated_issue_policy,
+        config.type_predicates,
+        config.must_handle_null_functions,
+        config.collect_type_coverage,
+        config.collect_mixed_source_counts,
+        config.generated_file_header_patterns,
+        config.generated_file_paths,
+        config.issue_profiles,
+        config.issue_profile_files,
+        config.find_redundant_computations,
+        config.analyze_traits_in_using_classes,
+        config.max_trait_using_classes,
+        config.hooks.len(),
+    );
+
+    xxhash_rust::xxh3::xxh3_64(settings.as_bytes())
+}
+
+#[cfg(test)]
+mod tests;
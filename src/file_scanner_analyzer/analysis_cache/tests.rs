@@ -0,0 +1,22 @@
+use hakana_analyzer::program_visitor::ProgramVisitor;
+
+use super::*;
+
+struct NoopVisitor;
+
+impl ProgramVisitor for NoopVisitor {}
+
+#[test]
+fn uses_analysis_cache_by_default() {
+    let config = Config::new("/".to_string(), FxHashSet::default());
+
+    assert!(can_use_analysis_cache(&config));
+}
+
+#[test]
+fn skips_analysis_cache_with_program_visitors() {
+    let mut config = Config::new("/".to_string(), FxHashSet::default());
+    config.program_visitors.push(Box::new(NoopVisitor));
+
+    assert!(!can_use_analysis_cache(&config));
+}
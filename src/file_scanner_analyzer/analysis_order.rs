@@ -0,0 +1,85 @@
+use crate::dependency_digests::get_file_dependencies;
+use crate::get_relative_path;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::symbol_references::SymbolReferences;
+use hakana_reflection_info::StrId;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+// Orders files so that, where possible, a file is analyzed after the files it depends on.
+// Dependencies come from a previous run's references, so cycles and files that didn't
+// exist last time are tolerated and just keep their existing relative order.
+pub(crate) fn sort_by_dependencies(
+    paths: Vec<String>,
+    codebase: &CodebaseInfo,
+    previous_references: &SymbolReferences,
+    root_dir: &String,
+) -> Vec<String> {
+    let file_dependencies = get_file_dependencies(codebase, previous_references);
+
+    let mut path_ids = FxHashMap::default();
+
+    for path in &paths {
+        if let Some(file_id) = codebase.interner.get(&get_relative_path(path, root_dir)) {
+            path_ids.insert(file_id, path.clone());
+        }
+    }
+
+    let mut sorted_paths = Vec::with_capacity(paths.len());
+    let mut visited = FxHashSet::default();
+
+    for path in paths {
+        if let Some(file_id) = codebase.interner.get(&get_relative_path(&path, root_dir)) {
+            visit(
+                file_id,
+                &file_dependencies,
+                &path_ids,
+                &mut visited,
+                &mut sorted_paths,
+            );
+        } else {
+            sorted_paths.push(path);
+        }
+    }
+
+    sorted_paths
+}
+
+fn visit(
+    file_id: StrId,
+    file_dependencies: &FxHashMap<StrId, FxHashSet<StrId>>,
+    path_ids: &FxHashMap<StrId, String>,
+    visited: &mut FxHashSet<StrId>,
+    sorted_paths: &mut Vec<String>,
+) {
+    let mut stack = vec![(file_id, false)];
+
+    while let Some((file_id, dependencies_visited)) = stack.pop() {
+        if dependencies_visited {
+            if let Some(path) = path_ids.get(&file_id) {
+                sorted_paths.push(path.clone());
+            }
+
+            continue;
+        }
+
+        if !visited.insert(file_id) {
+            continue;
+        }
+
+        stack.push((file_id, true));
+
+        if let Some(dependencies) = file_dependencies.get(&file_id) {
+            let mut dependencies = dependencies
+                .iter()
+                .filter(|dependency| !visited.contains(dependency))
+                .collect::<Vec<_>>();
+
+            // keep the order stable between runs
+            dependencies.sort_by_key(|dependency| dependency.0);
+
+            for dependency in dependencies.into_iter().rev() {
+                stack.push((*dependency, false));
+            }
+        }
+    }
+}
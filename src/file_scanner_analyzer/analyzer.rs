@@ -1,19 +1,22 @@
-use crate::file_cache_provider::FileStatus;
-use crate::{get_aast_for_path, get_relative_path, update_progressbar};
+use crate::analysis_cache::AnalysisCache;
+use crate::error::HakanaError;
+use crate::file_provider::FileProvider;
+use crate::progress_bar::get_progress_reporter;
+use crate::source_map::SourceMap;
+use crate::{get_aast_for_path, get_relative_path};
 use hakana_aast_helper::ParserError;
 use hakana_analyzer::config::{Config, Verbosity};
 use hakana_analyzer::file_analyzer;
+use hakana_analyzer::progress_reporter::AnalysisPhase;
 use hakana_reflection_info::analysis_result::AnalysisResult;
 use hakana_reflection_info::code_location::HPos;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
 use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::symbol_references::SymbolReferences;
 use hakana_reflection_info::{FileSource, StrId};
-use indexmap::IndexMap;
-use indicatif::{ProgressBar, ProgressStyle};
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use std::io;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
 
 pub fn analyze_files(
@@ -25,10 +28,11 @@ pub fn analyze_files(
     filter: Option<String>,
     ignored_paths: &Option<FxHashSet<String>>,
     cache_dir: Option<&String>,
-    _file_statuses: &IndexMap<String, FileStatus>,
+    analysis_cache: &Option<Arc<AnalysisCache>>,
     threads: u8,
     verbosity: Verbosity,
-) -> io::Result<()> {
+    file_provider: &Arc<dyn FileProvider>,
+) -> Result<(), HakanaError> {
     let mut group_size = threads as usize;
 
     let mut path_groups = FxHashMap::default();
@@ -65,22 +69,35 @@ pub fn analyze_files(
             .push(str_path);
     }
 
-    let bar = if matches!(verbosity, Verbosity::Simple) {
-        let pb = ProgressBar::new(total_file_count);
-        let sty = ProgressStyle::with_template("{bar:40.green/yellow} {pos:>7}/{len:7}").unwrap();
-        pb.set_style(sty);
-        Some(Arc::new(pb))
-    } else {
-        None
-    };
+    // issues from a cached previous run count towards the limit too
+    let issue_count = Arc::new(Mutex::new(
+        analysis_result
+            .lock()
+            .unwrap()
+            .emitted_issues
+            .values()
+            .map(|issues| issues.len())
+            .sum::<usize>(),
+    ));
+
+    let progress_reporter = get_progress_reporter(&config, verbosity);
+
+    if let Some(progress_reporter) = &progress_reporter {
+        progress_reporter.on_phase_start(AnalysisPhase::Analyzing, total_file_count);
+    }
 
     if path_groups.len() == 1 {
         let mut new_analysis_result =
             AnalysisResult::new(config.graph_kind, SymbolReferences::new());
 
         for (i, str_path) in path_groups[&0].iter().enumerate() {
+            if *issue_count.lock().unwrap() >= config.max_issue_count {
+                new_analysis_result.unanalyzed_file_count = path_groups[&0].len() - i;
+                break;
+            }
+
             if let Some(resolved_names) = resolved_names.get(*str_path) {
-                analyze_file(
+                let file_issue_count = analyze_file_using_cache(
                     str_path,
                     cache_dir,
                     &codebase,
@@ -88,10 +105,16 @@ pub fn analyze_files(
                     &mut new_analysis_result,
                     resolved_names,
                     verbosity,
-                );
+                    &**file_provider,
+                    analysis_cache.as_deref(),
+                )?;
+
+                *issue_count.lock().unwrap() += file_issue_count;
             }
 
-            update_progressbar(i as u64, bar.clone());
+            if let Some(progress_reporter) = &progress_reporter {
+                progress_reporter.on_file_done(i as u64 + 1, total_file_count);
+            }
         }
 
         analysis_result.lock().unwrap().extend(new_analysis_result);
@@ -115,17 +138,25 @@ pub fn analyze_files(
             let analysis_config = config.clone();
 
             let files_processed = files_processed.clone();
-            let bar = bar.clone();
+            let issue_count = issue_count.clone();
+            let progress_reporter = progress_reporter.clone();
 
             let resolved_names = resolved_names.clone();
+            let file_provider = file_provider.clone();
+            let analysis_cache = analysis_cache.clone();
 
-            let handle = std::thread::spawn(move || {
+            let handle = std::thread::spawn(move || -> Result<(), HakanaError> {
                 let mut new_analysis_result =
                     AnalysisResult::new(analysis_config.graph_kind, SymbolReferences::new());
 
-                for str_path in &pgc {
+                for (i, str_path) in pgc.iter().enumerate() {
+                    if *issue_count.lock().unwrap() >= analysis_config.max_issue_count {
+                        new_analysis_result.unanalyzed_file_count = pgc.len() - i;
+                        break;
+                    }
+
                     if let Some(resolved_names) = resolved_names.get(str_path) {
-                        analyze_file(
+                        let file_issue_count = analyze_file_using_cache(
                             str_path,
                             cache_dir_c.as_ref(),
                             &codebase,
@@ -133,42 +164,144 @@ pub fn analyze_files(
                             &mut new_analysis_result,
                             resolved_names,
                             verbosity,
-                        );
+                            &*file_provider,
+                            analysis_cache.as_deref(),
+                        )?;
+
+                        *issue_count.lock().unwrap() += file_issue_count;
                     }
 
                     let mut tally = files_processed.lock().unwrap();
                     *tally += 1;
 
-                    update_progressbar(*tally, bar.clone());
+                    if let Some(progress_reporter) = &progress_reporter {
+                        progress_reporter.on_file_done(*tally, total_file_count);
+                    }
                 }
 
                 analysis_result.lock().unwrap().extend(new_analysis_result);
+
+                Ok(())
             });
 
             handles.push(handle);
         }
 
         for handle in handles {
-            handle.join().unwrap();
+            handle.join().unwrap()?;
         }
     }
 
-    if let Some(bar) = &bar {
-        bar.finish_and_clear();
+    if let Some(progress_reporter) = &progress_reporter {
+        progress_reporter.on_phase_end(AnalysisPhase::Analyzing);
     }
 
     Ok(())
 }
 
-fn analyze_file(
+// Reuses the file's results from the last run when neither it nor anything it depends
+// on has changed, and otherwise analyzes it and saves its results for next time
+fn analyze_file_using_cache(
+    str_path: &String,
+    cache_dir: Option<&String>,
+    codebase: &CodebaseInfo,
+    config: &Config,
+    analysis_result: &mut AnalysisResult,
+    resolved_names: &FxHashMap<usize, StrId>,
+    verbosity: Verbosity,
+    file_provider: &dyn FileProvider,
+    analysis_cache: Option<&AnalysisCache>,
+) -> Result<usize, HakanaError> {
+    let analysis_cache = if let Some(analysis_cache) = analysis_cache {
+        analysis_cache
+    } else {
+        return analyze_file_catching_panics(
+            str_path,
+            cache_dir,
+            codebase,
+            config,
+            analysis_result,
+            resolved_names,
+            verbosity,
+            file_provider,
+        );
+    };
+
+    if let Some(cached_analysis_result) = analysis_cache.get(str_path) {
+        let file_issue_count = cached_analysis_result
+            .emitted_issues
+            .values()
+            .map(|issues| issues.len())
+            .sum();
+
+        analysis_result.extend(cached_analysis_result);
+
+        return Ok(file_issue_count);
+    }
+
+    let mut file_analysis_result = AnalysisResult::new(config.graph_kind, SymbolReferences::new());
+
+    let file_issue_count = analyze_file_catching_panics(
+        str_path,
+        cache_dir,
+        codebase,
+        config,
+        &mut file_analysis_result,
+        resolved_names,
+        verbosity,
+        file_provider,
+    )?;
+
+    if let Some(file_id) = codebase
+        .interner
+        .get(&get_relative_path(str_path, &config.root_dir))
+    {
+        analysis_cache.insert(str_path, file_id, resolved_names, &file_analysis_result);
+    }
+
+    analysis_result.extend(file_analysis_result);
+
+    Ok(file_issue_count)
+}
+
+// A crash in one file shouldn't take down the whole process without saying where it happened
+pub(crate) fn analyze_file_catching_panics(
     str_path: &String,
     cache_dir: Option<&String>,
-    codebase: &Arc<CodebaseInfo>,
-    config: &Arc<Config>,
+    codebase: &CodebaseInfo,
+    config: &Config,
     analysis_result: &mut AnalysisResult,
     resolved_names: &FxHashMap<usize, StrId>,
     verbosity: Verbosity,
-) {
+    file_provider: &dyn FileProvider,
+) -> Result<usize, HakanaError> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        analyze_file(
+            str_path,
+            cache_dir,
+            codebase,
+            config,
+            analysis_result,
+            resolved_names,
+            verbosity,
+            file_provider,
+        )
+    }))
+    .map_err(|_| HakanaError::InternalPanic {
+        file: get_relative_path(str_path, &config.root_dir),
+    })
+}
+
+pub(crate) fn analyze_file(
+    str_path: &String,
+    cache_dir: Option<&String>,
+    codebase: &CodebaseInfo,
+    config: &Config,
+    analysis_result: &mut AnalysisResult,
+    resolved_names: &FxHashMap<usize, StrId>,
+    verbosity: Verbosity,
+    file_provider: &dyn FileProvider,
+) -> usize {
     if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
         println!("Analyzing {}", &str_path);
     }
@@ -177,7 +310,7 @@ fn analyze_file(
 
     let file_path = codebase.interner.get(target_name.as_str()).unwrap();
 
-    let aast_result = get_aast_for_path(str_path, &config.root_dir, cache_dir);
+    let aast_result = get_aast_for_path(str_path, &config.root_dir, cache_dir, file_provider);
     let aast = match aast_result {
         Ok(aast) => aast,
         Err(err) => {
@@ -201,18 +334,46 @@ fn analyze_file(
                     ),
                     ParserError::SyntaxError { message, mut pos } => {
                         pos.file_path = file_path;
-                        Issue::new(IssueKind::InvalidHackFile, message, pos, &None)
+                        Issue::new(IssueKind::ParseError, message, pos, &None)
                     }
                 }],
             );
 
-            return;
+            return 1;
         }
     };
 
+    let source_map = if config.report_generated_issues_at_origin {
+        SourceMap::from_comments(&aast.1.comments)
+    } else {
+        None
+    };
+
+    let existing_issue_count = analysis_result
+        .emitted_issues
+        .get(&target_name)
+        .map_or(0, |issues| issues.len());
+
+    let issue_profile = config.get_issue_profile_name(&target_name).cloned();
+
+    if config.collect_issue_profile_files {
+        if let Some(issue_profile) = &issue_profile {
+            analysis_result
+                .issue_profile_files
+                .insert(target_name.clone(), issue_profile.clone());
+        }
+    }
+
     let file_source = FileSource {
         is_production_code: true,
-        file_path_actual: target_name,
+        is_generated: config.is_generated_file(&target_name, &aast.1.comments),
+        issue_profile,
+        regeneration_command: codebase
+            .files
+            .get(&file_path)
+            .and_then(|file_info| file_info.regeneration_command.clone()),
+        literal_collection_thresholds: config.literal_collection_thresholds,
+        file_path_actual: target_name.clone(),
         file_path,
         hh_fixmes: aast.1.fixmes,
         comments: aast.1.comments,
@@ -221,4 +382,16 @@ fn analyze_file(
     let mut file_analyzer =
         file_analyzer::FileAnalyzer::new(file_source, &resolved_names, codebase, config);
     file_analyzer.analyze(&aast.0, analysis_result);
+
+    let file_issue_count = analysis_result
+        .emitted_issues
+        .get(&target_name)
+        .map_or(0, |issues| issues.len())
+        - existing_issue_count;
+
+    if let Some(source_map) = source_map {
+        source_map.remap_issues(&target_name, &codebase.interner, analysis_result);
+    }
+
+    file_issue_count
 }
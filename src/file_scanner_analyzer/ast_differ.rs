@@ -12,6 +12,7 @@ pub(crate) fn get_diff(
     let mut keep = vec![];
     let mut keep_signature = vec![];
     let mut add_or_delete = vec![];
+    let mut member_changes_only = vec![];
     let mut diff_map = FxHashMap::default();
     let mut deletion_ranges_map = FxHashMap::default();
 
@@ -87,7 +88,7 @@ pub(crate) fn get_diff(
                         }
 
                         if has_change {
-                            keep_signature.push((a.name, StrId::empty()));
+                            member_changes_only.push(a.name);
                         } else {
                             keep.push((a.name, StrId::empty()));
 
@@ -129,6 +130,7 @@ pub(crate) fn get_diff(
         keep,
         keep_signature,
         add_or_delete,
+        member_changes_only,
         diff_map,
         deletion_ranges_map,
     }
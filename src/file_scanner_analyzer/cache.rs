@@ -1,109 +1,204 @@
+use crate::error::HakanaError;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use hakana_analyzer::config::Verbosity;
 use hakana_reflection_info::codebase_info::symbols::SymbolKind;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::data_flow::graph::DataFlowGraph;
+use hakana_reflection_info::file_info::FileInfo;
 use hakana_reflection_info::issue::Issue;
 use hakana_reflection_info::symbol_references::SymbolReferences;
 use hakana_reflection_info::Interner;
 use hakana_reflection_info::StrId;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::de::DeserializeOwned;
 use std::collections::BTreeMap;
 use std::fs;
+use std::io::{self, BufReader, BufWriter, Write};
 use std::path::Path;
 
 pub(crate) fn load_cached_codebase(
     codebase_path: &String,
-    use_codebase_cache: bool,
-    codebase: &mut CodebaseInfo,
-    interner: &Interner,
-    changed_files: &FxHashSet<String>,
     verbosity: Verbosity,
-) {
-    if Path::new(codebase_path).exists() && use_codebase_cache {
+) -> Option<CodebaseInfo> {
+    if Path::new(codebase_path).exists() {
         if !matches!(verbosity, Verbosity::Quiet) {
             println!("Deserializing stored codebase cache");
         }
-        let serialized = fs::read(&codebase_path)
-            .unwrap_or_else(|_| panic!("Could not read file {}", &codebase_path));
-        if let Ok(d) = bincode::deserialize::<CodebaseInfo>(&serialized) {
-            *codebase = d;
-
-            for (_, file_storage) in codebase
-                .files
-                .iter()
-                .filter(|f| changed_files.contains(interner.lookup(f.0)))
-            {
-                for ast_node in &file_storage.ast_nodes {
-                    match codebase.symbols.all.get(&ast_node.name) {
-                        Some(kind) => {
-                            if let SymbolKind::TypeDefinition = kind {
-                                codebase.type_definitions.remove(&ast_node.name);
-                            } else {
-                                codebase.classlike_infos.remove(&ast_node.name);
-                            }
-                            codebase.symbols.all.remove(&ast_node.name);
+
+        return read_cache_file::<CodebaseInfo>(codebase_path, verbosity);
+    }
+
+    None
+}
+
+// Removes the symbols defined in the given files, keeping them aside in previous_codebase
+pub(crate) fn remove_file_symbols(
+    codebase: &mut CodebaseInfo,
+    previous_codebase: &mut CodebaseInfo,
+    interner: &Interner,
+    changed_files: &FxHashSet<String>,
+) {
+    // symbols from changed files are kept aside, so they can be restored
+    // if the changed file can no longer be parsed
+    for (_, file_storage) in codebase
+        .files
+        .iter()
+        .filter(|f| changed_files.contains(interner.lookup(f.0)))
+    {
+        for ast_node in &file_storage.ast_nodes {
+            match codebase.symbols.all.remove(&ast_node.name) {
+                Some(kind) => {
+                    if let SymbolKind::TypeDefinition = kind {
+                        if let Some(info) = codebase.type_definitions.remove(&ast_node.name) {
+                            previous_codebase
+                                .type_definitions
+                                .insert(ast_node.name, info);
                         }
-                        None => {
-                            if ast_node.is_function {
-                                codebase.functionlike_infos.remove(&ast_node.name);
-                            } else if ast_node.is_constant {
-                                codebase.constant_infos.remove(&ast_node.name);
-                            }
+                    } else if let Some(info) = codebase.classlike_infos.remove(&ast_node.name) {
+                        previous_codebase
+                            .classlike_infos
+                            .insert(ast_node.name, info);
+                    }
+                    previous_codebase.symbols.all.insert(ast_node.name, kind);
+                }
+                None => {
+                    if ast_node.is_function {
+                        if let Some(info) = codebase.functionlike_infos.remove(&ast_node.name) {
+                            previous_codebase
+                                .functionlike_infos
+                                .insert(ast_node.name, info);
+                        }
+                    } else if ast_node.is_constant {
+                        if let Some(info) = codebase.constant_infos.remove(&ast_node.name) {
+                            previous_codebase.constant_infos.insert(ast_node.name, info);
                         }
                     }
                 }
             }
+        }
+    }
 
-            // we need to check for anonymous functions here
-            let closures_to_remove = codebase
-                .closures_in_files
-                .iter()
-                .filter(|(k, _)| changed_files.contains(*k))
-                .map(|(_, v)| v.clone().into_iter().collect::<Vec<_>>())
-                .flatten()
-                .collect::<FxHashSet<_>>();
+    codebase
+        .stale_files
+        .retain(|f| !changed_files.contains(interner.lookup(f)));
 
-            codebase
+    // we need to check for anonymous functions here
+    let closures_to_remove = codebase
+        .closures_in_files
+        .iter()
+        .filter(|(k, _)| changed_files.contains(*k))
+        .map(|(_, v)| v.clone().into_iter().collect::<Vec<_>>())
+        .flatten()
+        .collect::<FxHashSet<_>>();
+
+    for closure_id in closures_to_remove {
+        if let Some(info) = codebase.functionlike_infos.remove(&closure_id) {
+            previous_codebase
                 .functionlike_infos
-                .retain(|k, _| !closures_to_remove.contains(k));
+                .insert(closure_id, info);
         }
     }
 }
 
-pub(crate) fn load_cached_symbols(
-    symbols_path: &String,
-    use_codebase_cache: bool,
-    interner: &mut Interner,
-    verbosity: Verbosity,
+// Restores the symbols a file defined in the previous run, for use when the
+// file has been modified and can no longer be parsed.
+pub(crate) fn restore_stale_file_symbols(
+    codebase: &mut CodebaseInfo,
+    previous_codebase: &mut CodebaseInfo,
+    file_id: StrId,
+    file_storage: FileInfo,
+    file_path: &String,
 ) {
-    if Path::new(symbols_path).exists() && use_codebase_cache {
+    for ast_node in &file_storage.ast_nodes {
+        if let Some(kind) = previous_codebase.symbols.all.remove(&ast_node.name) {
+            if let SymbolKind::TypeDefinition = kind {
+                if let Some(info) = previous_codebase.type_definitions.remove(&ast_node.name) {
+                    codebase.type_definitions.insert(ast_node.name, info);
+                }
+            } else if let Some(info) = previous_codebase.classlike_infos.remove(&ast_node.name) {
+                codebase.classlike_infos.insert(ast_node.name, info);
+            }
+
+            codebase.symbols.all.insert(ast_node.name, kind);
+        } else if ast_node.is_function {
+            if let Some(info) = previous_codebase.functionlike_infos.remove(&ast_node.name) {
+                codebase.functionlike_infos.insert(ast_node.name, info);
+            }
+        } else if ast_node.is_constant {
+            if let Some(info) = previous_codebase.constant_infos.remove(&ast_node.name) {
+                codebase.constant_infos.insert(ast_node.name, info);
+            }
+        }
+    }
+
+    if let Some(closure_ids) = codebase.closures_in_files.get(file_path) {
+        for closure_id in closure_ids {
+            if let Some(info) = previous_codebase.functionlike_infos.remove(closure_id) {
+                codebase.functionlike_infos.insert(*closure_id, info);
+            }
+        }
+    }
+
+    codebase.files.insert(file_id, file_storage);
+    codebase.stale_files.insert(file_id);
+}
+
+pub(crate) fn load_cached_symbols(symbols_path: &String, verbosity: Verbosity) -> Option<Interner> {
+    if Path::new(symbols_path).exists() {
         if !matches!(verbosity, Verbosity::Quiet) {
             println!("Deserializing stored symbol cache");
         }
-        let serialized = fs::read(&symbols_path)
-            .unwrap_or_else(|_| panic!("Could not read file {}", &symbols_path));
-        if let Ok(d) = bincode::deserialize::<Interner>(&serialized) {
-            *interner = d;
-        }
+
+        return read_cache_file::<Interner>(symbols_path, verbosity);
     }
+
+    None
 }
 
 pub(crate) fn load_cached_aast_names(
     aast_names_path: &String,
-    use_codebase_cache: bool,
-    resolved_names: &mut FxHashMap<String, FxHashMap<usize, StrId>>,
     verbosity: Verbosity,
-) {
-    if Path::new(aast_names_path).exists() && use_codebase_cache {
+) -> Option<FxHashMap<String, FxHashMap<usize, StrId>>> {
+    if Path::new(aast_names_path).exists() {
         if !matches!(verbosity, Verbosity::Quiet) {
             println!("Deserializing aast names cache");
         }
-        let serialized = fs::read(&aast_names_path)
-            .unwrap_or_else(|_| panic!("Could not read file {}", &aast_names_path));
-        if let Ok(d) =
-            bincode::deserialize::<FxHashMap<String, FxHashMap<usize, StrId>>>(&serialized)
-        {
-            *resolved_names = d;
-        }
+
+        return read_cache_file::<FxHashMap<String, FxHashMap<usize, StrId>>>(
+            aast_names_path,
+            verbosity,
+        );
+    }
+
+    None
+}
+
+// A cache file that can't be read is treated as missing, so that it gets rebuilt
+fn read_cache_file<T: DeserializeOwned>(cache_path: &String, verbosity: Verbosity) -> Option<T> {
+    let deserialized = fs::read(cache_path)
+        .ok()
+        .and_then(|serialized| bincode::deserialize::<T>(&serialized).ok());
+
+    if deserialized.is_none() {
+        warn(
+            verbosity,
+            &format!(
+                "could not read cache file {}, which may be corrupted. Discarding it.",
+                cache_path
+            ),
+        );
+    }
+
+    deserialized
+}
+
+// Cache problems never fail a run, as everything in a cache can be rebuilt, so they're
+// reported as warnings, on stderr to keep them out of any output on stdout
+pub(crate) fn warn(verbosity: Verbosity, message: &str) {
+    if !matches!(verbosity, Verbosity::Quiet) {
+        eprintln!("Warning: {}", message);
     }
 }
 
@@ -144,3 +239,77 @@ pub(crate) fn load_cached_existing_issues(
 
     None
 }
+
+// Saves everything the whole-program phase needs. The graph can be huge, so it's
+// streamed to disk rather than serialized in memory first.
+pub(crate) fn save_whole_program_checkpoint(
+    checkpoint_path: &String,
+    interner: &Interner,
+    program_dataflow_graph: &DataFlowGraph,
+    emitted_issues: &BTreeMap<String, Vec<Issue>>,
+) -> Result<(), HakanaError> {
+    let checkpoint_file = BufWriter::new(fs::File::create(&checkpoint_path)?);
+
+    bincode::serialize_into(
+        checkpoint_file,
+        &(interner, program_dataflow_graph, emitted_issues),
+    )
+    .map_err(|error| HakanaError::Io(io::Error::new(io::ErrorKind::Other, error)))
+}
+
+pub(crate) fn load_whole_program_checkpoint(
+    checkpoint_path: &String,
+    verbosity: Verbosity,
+) -> Result<(Interner, DataFlowGraph, BTreeMap<String, Vec<Issue>>), HakanaError> {
+    if !matches!(verbosity, Verbosity::Quiet) {
+        println!("Deserializing whole-program checkpoint");
+    }
+
+    let checkpoint_file = BufReader::new(fs::File::open(&checkpoint_path)?);
+
+    bincode::deserialize_from(checkpoint_file)
+        .map_err(|_| HakanaError::CacheCorruption(checkpoint_path.clone()))
+}
+
+// Saves the issues of the last complete run, which later runs compare theirs against.
+// Only the issues are kept from the AnalysisResult, as nothing else is compared, and
+// they're compressed because every unchanged issue is repeated in every run.
+pub(crate) fn save_previous_issues(
+    previous_issues_path: &String,
+    emitted_issues: &BTreeMap<String, Vec<Issue>>,
+) -> Result<(), HakanaError> {
+    let mut previous_issues_file = GzEncoder::new(
+        BufWriter::new(fs::File::create(&previous_issues_path)?),
+        Compression::default(),
+    );
+
+    bincode::serialize_into(&mut previous_issues_file, emitted_issues)
+        .map_err(|error| HakanaError::Io(io::Error::new(io::ErrorKind::Other, error)))?;
+
+    previous_issues_file.finish()?.flush()?;
+
+    Ok(())
+}
+
+pub(crate) fn load_previous_issues(
+    previous_issues_path: &String,
+    verbosity: Verbosity,
+) -> Result<Option<BTreeMap<String, Vec<Issue>>>, HakanaError> {
+    if !Path::new(previous_issues_path).exists() {
+        return Ok(None);
+    }
+
+    if !matches!(verbosity, Verbosity::Quiet) {
+        println!("Deserializing previous issues");
+    }
+
+    let previous_issues_file =
+        GzDecoder::new(BufReader::new(fs::File::open(&previous_issues_path)?));
+
+    bincode::deserialize_from(previous_issues_file)
+        .map(Some)
+        .map_err(|_| HakanaError::CacheCorruption(previous_issues_path.clone()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::issue::IssueKind;
+
+use super::*;
+use crate::file_provider::{DiskFileProvider, FileProvider};
+use crate::scanner::scan_files;
+
+#[test]
+fn discards_corrupt_cache_files() {
+    let cache_dir = std::env::temp_dir().join(format!("hakana-cache-{}", std::process::id()));
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    let codebase_path = cache_dir.join("codebase").to_string_lossy().to_string();
+    fs::write(&codebase_path, "not a codebase").unwrap();
+
+    let codebase = load_cached_codebase(&codebase_path, Verbosity::Quiet);
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(codebase.is_none());
+}
+
+#[test]
+fn rescans_everything_when_the_cache_is_corrupt() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-cold-scan-{}", std::process::id()));
+    let cache_dir = root_dir.join(".hakana_cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    fs::write(root_dir.join("a.hack"), "function foo(): void {}\n").unwrap();
+
+    fs::write(cache_dir.join("buildinfo"), "checksum").unwrap();
+
+    for cache_file in ["manifest", "symbols", "codebase", "aast_names"] {
+        fs::write(cache_dir.join(cache_file), "garbage").unwrap();
+    }
+
+    let root_dir_path = root_dir.to_string_lossy().to_string();
+    let cache_dir_path = cache_dir.to_string_lossy().to_string();
+
+    let config = Arc::new(Config::new(root_dir_path.clone(), FxHashSet::default()));
+    let file_provider: Arc<dyn FileProvider> = Arc::new(DiskFileProvider);
+
+    let scan_result = scan_files(
+        &vec![root_dir_path],
+        false,
+        Some(&cache_dir_path),
+        &mut vec![],
+        &config,
+        1,
+        Verbosity::Quiet,
+        "checksum",
+        None,
+        &file_provider,
+    );
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let scan_result = scan_result.unwrap();
+
+    let foo_id = scan_result.interner.get("foo").unwrap();
+
+    assert!(scan_result
+        .codebase
+        .functionlike_infos
+        .contains_key(&foo_id));
+}
+
+#[test]
+fn round_trips_previous_issues() {
+    let cache_dir =
+        std::env::temp_dir().join(format!("hakana-previous-issues-{}", std::process::id()));
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    let previous_issues_path = cache_dir
+        .join("previous_issues")
+        .to_string_lossy()
+        .to_string();
+
+    let issue = Issue::new(
+        IssueKind::UnusedFunction,
+        "Unused function foo".to_string(),
+        HPos {
+            file_path: StrId(0),
+            start_offset: 0,
+            end_offset: 3,
+            start_line: 1,
+            end_line: 1,
+            start_column: 1,
+            end_column: 4,
+            insertion_start: None,
+        },
+        &None,
+    );
+
+    let emitted_issues = BTreeMap::from([("a.hack".to_string(), vec![issue])]);
+
+    save_previous_issues(&previous_issues_path, &emitted_issues).unwrap();
+    let missing_issues = load_previous_issues(
+        &cache_dir.join("missing").to_string_lossy().to_string(),
+        Verbosity::Quiet,
+    );
+    let loaded_issues = load_previous_issues(&previous_issues_path, Verbosity::Quiet);
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(matches!(missing_issues, Ok(None)));
+
+    let loaded_issues = loaded_issues.unwrap().unwrap();
+
+    assert_eq!(
+        emitted_issues["a.hack"][0].get_fingerprint(&"a.hack".to_string()),
+        loaded_issues["a.hack"][0].get_fingerprint(&"a.hack".to_string())
+    );
+}
+
+#[test]
+fn reports_corrupt_previous_issues() {
+    let cache_dir = std::env::temp_dir().join(format!(
+        "hakana-corrupt-previous-issues-{}",
+        std::process::id()
+    ));
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    let previous_issues_path = cache_dir
+        .join("previous_issues")
+        .to_string_lossy()
+        .to_string();
+    fs::write(&previous_issues_path, "garbage").unwrap();
+
+    let loaded_issues = load_previous_issues(&previous_issues_path, Verbosity::Quiet);
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(matches!(
+        loaded_issues,
+        Err(HakanaError::CacheCorruption(_))
+    ));
+}
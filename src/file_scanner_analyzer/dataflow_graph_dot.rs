@@ -0,0 +1,261 @@
+use std::collections::BTreeSet;
+
+use hakana_reflection_info::data_flow::graph::DataFlowGraph;
+use hakana_reflection_info::data_flow::node::{DataFlowNode, DataFlowNodeKind};
+use hakana_reflection_info::data_flow::path::{DataFlowPath, PathKind};
+use hakana_reflection_info::taint::{SinkType, SourceType};
+use hakana_reflection_info::Interner;
+use rustc_hash::FxHashSet;
+
+// Renders a whole-program dataflow graph in GraphViz's DOT format, so it's possible to see
+// why data does or doesn't reach a sink. With source filters, only the part of the graph
+// reachable from sources whose label or id contains one of the filters is included.
+// Dashed edges link a node to its specializations, which data can flow along both ways.
+pub(crate) fn get_dataflow_graph_dot(
+    graph: &DataFlowGraph,
+    interner: &Interner,
+    source_filters: &Vec<String>,
+) -> String {
+    let node_ids = if source_filters.is_empty() {
+        get_all_node_ids(graph)
+    } else {
+        get_reachable_node_ids(graph, source_filters)
+    };
+
+    let mut dot = "digraph dataflow {\n    node [shape=box];\n".to_string();
+
+    for node_id in &node_ids {
+        dot += &format!("    \"{}\"", escape(node_id));
+
+        if let Some(node) = get_node(graph, node_id) {
+            dot += &format!(" [label=\"{}\"", escape(&get_node_label(node, interner)));
+
+            match &node.kind {
+                DataFlowNodeKind::TaintSource { .. }
+                | DataFlowNodeKind::DataSource { .. }
+                | DataFlowNodeKind::VariableUseSource { .. } => {
+                    dot += ", shape=ellipse, style=filled, fillcolor=lightblue"
+                }
+                DataFlowNodeKind::TaintSink { .. } | DataFlowNodeKind::VariableUseSink { .. } => {
+                    dot += ", shape=ellipse, style=filled, fillcolor=salmon"
+                }
+                DataFlowNodeKind::Vertex { .. } | DataFlowNodeKind::ForLoopInit { .. } => {}
+            }
+
+            dot += "]";
+        }
+
+        dot += ";\n";
+    }
+
+    for from_id in &node_ids {
+        if let Some(edges) = graph.forward_edges.get(from_id) {
+            // sorted so that the output is stable between runs
+            let mut edges = edges.iter().collect::<Vec<_>>();
+            edges.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (to_id, path) in edges {
+                if !node_ids.contains(to_id) {
+                    continue;
+                }
+
+                dot += &format!("    \"{}\" -> \"{}\"", escape(from_id), escape(to_id));
+
+                let path_label = get_path_label(path, interner);
+
+                if !path_label.is_empty() {
+                    dot += &format!(" [label=\"{}\"]", escape(&path_label));
+                }
+
+                dot += ";\n";
+            }
+        }
+
+        for specialized_id in get_specialized_ids(graph, from_id) {
+            if node_ids.contains(&specialized_id) {
+                dot += &format!(
+                    "    \"{}\" -> \"{}\" [style=dashed, dir=both];\n",
+                    escape(from_id),
+                    escape(&specialized_id)
+                );
+            }
+        }
+    }
+
+    dot += "}\n";
+
+    dot
+}
+
+fn get_all_node_ids(graph: &DataFlowGraph) -> BTreeSet<String> {
+    let mut node_ids = BTreeSet::new();
+
+    node_ids.extend(graph.vertices.keys().cloned());
+    node_ids.extend(graph.sources.keys().cloned());
+    node_ids.extend(graph.sinks.keys().cloned());
+
+    for (from_id, edges) in &graph.forward_edges {
+        node_ids.insert(from_id.clone());
+        node_ids.extend(edges.keys().cloned());
+    }
+
+    node_ids
+}
+
+fn get_reachable_node_ids(graph: &DataFlowGraph, source_filters: &Vec<String>) -> BTreeSet<String> {
+    let mut node_ids = BTreeSet::new();
+
+    let mut queue = graph
+        .sources
+        .values()
+        .filter(|source| {
+            source_filters.iter().any(|source_filter| {
+                source.get_label().contains(source_filter) || source.id.contains(source_filter)
+            })
+        })
+        .map(|source| source.id.clone())
+        .collect::<Vec<_>>();
+
+    while let Some(node_id) = queue.pop() {
+        if node_ids.contains(&node_id) {
+            continue;
+        }
+
+        if let Some(edges) = graph.forward_edges.get(&node_id) {
+            queue.extend(edges.keys().cloned());
+        }
+
+        queue.extend(get_specialized_ids(graph, &node_id));
+
+        if let Some(DataFlowNodeKind::Vertex {
+            unspecialized_id: Some(unspecialized_id),
+            ..
+        }) = get_node(graph, &node_id).map(|node| &node.kind)
+        {
+            queue.push(unspecialized_id.clone());
+        }
+
+        node_ids.insert(node_id);
+    }
+
+    node_ids
+}
+
+fn get_specialized_ids(graph: &DataFlowGraph, node_id: &String) -> Vec<String> {
+    graph
+        .specializations
+        .get(node_id)
+        .map(|specialization_keys| {
+            specialization_keys
+                .iter()
+                .map(|specialization_key| format!("{}-{}", node_id, specialization_key))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get_node<'a>(graph: &'a DataFlowGraph, node_id: &String) -> Option<&'a DataFlowNode> {
+    graph
+        .vertices
+        .get(node_id)
+        .or_else(|| graph.sources.get(node_id))
+        .or_else(|| graph.sinks.get(node_id))
+}
+
+fn get_node_label(node: &DataFlowNode, interner: &Interner) -> String {
+    let mut label = node.get_label().clone();
+
+    let pos = match &node.kind {
+        DataFlowNodeKind::Vertex { pos, .. }
+        | DataFlowNodeKind::TaintSource { pos, .. }
+        | DataFlowNodeKind::TaintSink { pos, .. } => pos.as_ref(),
+        DataFlowNodeKind::VariableUseSource { pos, .. }
+        | DataFlowNodeKind::VariableUseSink { pos }
+        | DataFlowNodeKind::DataSource { pos, .. } => Some(pos),
+        DataFlowNodeKind::ForLoopInit { .. } => None,
+    };
+
+    if let Some(pos) = pos {
+        label += &format!(
+            "\n{}:{}:{}",
+            interner.lookup(&pos.file_path),
+            pos.start_line,
+            pos.start_column
+        );
+    }
+
+    match &node.kind {
+        DataFlowNodeKind::TaintSource { types, .. } => {
+            label += &format!("\nsources: {}", get_source_names(types));
+        }
+        DataFlowNodeKind::TaintSink { types, .. } => {
+            label += &format!("\nsinks: {}", get_sink_names(types));
+        }
+        _ => {}
+    }
+
+    label
+}
+
+fn get_path_label(path: &DataFlowPath, interner: &Interner) -> String {
+    let mut label = match &path.kind {
+        PathKind::ArrayFetch(_, key) | PathKind::ArrayAssignment(_, key) => {
+            format!("{} {}", path.kind, key)
+        }
+        PathKind::PropertyFetch(classlike_name, property_name)
+        | PathKind::PropertyAssignment(classlike_name, property_name) => format!(
+            "{} {}::{}",
+            path.kind,
+            interner.lookup(classlike_name),
+            interner.lookup(property_name)
+        ),
+        PathKind::RemoveDictKey(key) => format!("{} {}", path.kind, key),
+        PathKind::RefineSymbol(symbol) => format!("{} {}", path.kind, interner.lookup(symbol)),
+        _ => path.kind.to_string(),
+    };
+
+    if let Some(added_taints) = &path.added_taints {
+        if !added_taints.is_empty() {
+            label += &format!("\n+{}", get_sink_names(added_taints));
+        }
+    }
+
+    if let Some(removed_taints) = &path.removed_taints {
+        if !removed_taints.is_empty() {
+            label += &format!("\n-{}", get_sink_names(removed_taints));
+        }
+    }
+
+    label.trim().to_string()
+}
+
+fn get_source_names(source_types: &FxHashSet<SourceType>) -> String {
+    let mut names = source_types
+        .iter()
+        .map(|source_type| match source_type {
+            SourceType::Category(category, name) => format!("{}:{}", category, name),
+            _ => source_type.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    names.join(", ")
+}
+
+fn get_sink_names(sink_types: &FxHashSet<SinkType>) -> String {
+    let mut names = sink_types
+        .iter()
+        .map(|sink_type| sink_type.get_config_name())
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    names.join(", ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,168 @@
+use crate::file_cache_provider::FileStatus;
+use crate::get_relative_path;
+use hakana_reflection_info::analysis_result::FileDependencyDigest;
+use hakana_reflection_info::codebase_info::symbols::SymbolKind;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::symbol_references::SymbolReferences;
+use hakana_reflection_info::StrId;
+use indexmap::IndexMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub(crate) fn get_dependency_digests(
+    codebase: &CodebaseInfo,
+    symbol_references: &SymbolReferences,
+    file_statuses: &IndexMap<String, FileStatus>,
+    analyzed_files: &Vec<String>,
+    root_dir: &String,
+    analyze_traits_in_using_classes: bool,
+) -> BTreeMap<String, FileDependencyDigest> {
+    let mut content_hashes = FxHashMap::default();
+
+    for (file_path, status) in file_statuses {
+        match status {
+            FileStatus::Unchanged(hash, _)
+            | FileStatus::Added(hash, _)
+            | FileStatus::Modified(hash, _) => {
+                content_hashes.insert(get_relative_path(file_path, root_dir), *hash);
+            }
+            FileStatus::Deleted => {}
+        }
+    }
+
+    let mut file_dependencies = get_file_dependencies(codebase, symbol_references);
+
+    if analyze_traits_in_using_classes {
+        add_trait_user_dependencies(codebase, &mut file_dependencies);
+    }
+
+    let mut digests = BTreeMap::new();
+
+    for analyzed_file in analyzed_files {
+        let file_path = get_relative_path(analyzed_file, root_dir);
+
+        let file_id = if let Some(file_id) = codebase.interner.get(&file_path) {
+            file_id
+        } else {
+            continue;
+        };
+
+        let dependencies = if let Some(dependency_file_ids) = file_dependencies.get(&file_id) {
+            dependency_file_ids
+                .iter()
+                .map(|dependency_file_id| codebase.interner.lookup(dependency_file_id).to_string())
+                .collect::<BTreeSet<_>>()
+        } else {
+            BTreeSet::new()
+        };
+
+        let mut digest_input = format!(
+            "{}:{}\n",
+            file_path,
+            content_hashes.get(&file_path).unwrap_or(&0)
+        );
+
+        for dependency in &dependencies {
+            digest_input += &format!(
+                "{}:{}\n",
+                dependency,
+                content_hashes.get(dependency).unwrap_or(&0)
+            );
+        }
+
+        digests.insert(
+            file_path,
+            FileDependencyDigest {
+                digest: format!("{:016x}", xxhash_rust::xxh3::xxh3_64(digest_input.as_bytes())),
+                dependencies,
+            },
+        );
+    }
+
+    digests
+}
+
+// Maps each file to the other files that define symbols it references
+pub(crate) fn get_file_dependencies(
+    codebase: &CodebaseInfo,
+    symbol_references: &SymbolReferences,
+) -> FxHashMap<StrId, FxHashSet<StrId>> {
+    let mut symbol_files = FxHashMap::default();
+    let mut file_symbols = FxHashMap::default();
+
+    for (file_id, file_info) in &codebase.files {
+        for ast_node in &file_info.ast_nodes {
+            symbol_files.insert(ast_node.name, *file_id);
+            file_symbols
+                .entry(*file_id)
+                .or_insert_with(FxHashSet::default)
+                .insert(ast_node.name);
+        }
+    }
+
+    let mut file_dependencies = FxHashMap::default();
+
+    for (file_id, defined_symbols) in &file_symbols {
+        let mut dependencies = FxHashSet::default();
+
+        for referenced_symbol in get_symbols_referenced_by(symbol_references, defined_symbols) {
+            if let Some(dependency_file_id) = symbol_files.get(&referenced_symbol) {
+                if dependency_file_id != file_id {
+                    dependencies.insert(*dependency_file_id);
+                }
+            }
+        }
+
+        file_dependencies.insert(*file_id, dependencies);
+    }
+
+    file_dependencies
+}
+
+// Trait methods are also analyzed in the classes that use them, so a trait's file depends
+// on the files of those classes
+fn add_trait_user_dependencies(
+    codebase: &CodebaseInfo,
+    file_dependencies: &mut FxHashMap<StrId, FxHashSet<StrId>>,
+) {
+    for classlike_info in codebase.classlike_infos.values() {
+        if matches!(classlike_info.kind, SymbolKind::Trait) {
+            continue;
+        }
+
+        let user_file_id = classlike_info.def_location.file_path;
+
+        for used_trait in &classlike_info.used_traits {
+            if let Some(trait_info) = codebase.classlike_infos.get(used_trait) {
+                let trait_file_id = trait_info.def_location.file_path;
+
+                if trait_file_id != user_file_id {
+                    file_dependencies
+                        .entry(trait_file_id)
+                        .or_insert_with(FxHashSet::default)
+                        .insert(user_file_id);
+                }
+            }
+        }
+    }
+}
+
+fn get_symbols_referenced_by(
+    symbol_references: &SymbolReferences,
+    referencing_symbols: &FxHashSet<StrId>,
+) -> FxHashSet<StrId> {
+    let mut referenced_symbols = FxHashSet::default();
+
+    for reference_map in [
+        &symbol_references.symbol_references_to_symbols,
+        &symbol_references.symbol_references_to_symbols_in_signature,
+    ] {
+        for (referencing_member, referenced_members) in reference_map {
+            if referencing_symbols.contains(&referencing_member.0) {
+                referenced_symbols.extend(referenced_members.iter().map(|m| m.0));
+            }
+        }
+    }
+
+    referenced_symbols
+}
@@ -5,11 +5,13 @@ use hakana_reflection_info::issue::Issue;
 use hakana_reflection_info::symbol_references::SymbolReferences;
 use hakana_reflection_info::Interner;
 use hakana_reflection_info::StrId;
+use indexmap::IndexMap;
 use rustc_hash::FxHashSet;
 use std::collections::BTreeMap;
 
 use crate::cache::load_cached_existing_issues;
 use crate::cache::load_cached_existing_references;
+use crate::file_cache_provider::FileStatus;
 use crate::get_relative_path;
 
 #[derive(Default)]
@@ -27,15 +29,31 @@ pub(crate) fn mark_safe_symbols_from_diff(
     codebase: &CodebaseInfo,
     interner: &mut Interner,
     files_to_analyze: &mut Vec<String>,
+    file_statuses: &IndexMap<String, FileStatus>,
     config: &Config,
     issues_path: &Option<String>,
 ) -> Option<CachedAnalysis> {
     if let Some(existing_references) =
         load_cached_existing_references(references_path.as_ref().unwrap(), true, verbosity)
     {
-        let (invalid_symbols_and_members, partially_invalid_symbols) =
+        let (mut invalid_symbols_and_members, partially_invalid_symbols) =
             existing_references.get_invalid_symbols(&codebase_diff);
 
+        // files that require or include a changed file need to be re-analyzed too
+        let changed_files = file_statuses
+            .iter()
+            .filter(|(_, v)| !matches!(v, FileStatus::Unchanged(..)))
+            .map(|(k, _)| get_relative_path(k, &config.root_dir))
+            .collect::<FxHashSet<_>>();
+
+        for importing_file_id in get_importing_files(codebase, interner, &changed_files) {
+            if let Some(file_info) = codebase.files.get(&importing_file_id) {
+                for node in &file_info.ast_nodes {
+                    invalid_symbols_and_members.insert((node.name, StrId::empty()));
+                }
+            }
+        }
+
         let mut cached_analysis = CachedAnalysis::default();
 
         cached_analysis.symbol_references = existing_references;
@@ -50,7 +68,11 @@ pub(crate) fn mark_safe_symbols_from_diff(
                         .insert((keep_symbol.0, keep_symbol.1));
                 }
             } else {
-                if !invalid_symbols_and_members.contains(&keep_symbol) {
+                // a classlike with invalid members has to be analyzed, even though
+                // its valid members are skipped
+                if !invalid_symbols_and_members.contains(&keep_symbol)
+                    && !partially_invalid_symbols.contains(&keep_symbol.0)
+                {
                     cached_analysis.safe_symbols.insert(keep_symbol.0);
                 }
             }
@@ -60,7 +82,7 @@ pub(crate) fn mark_safe_symbols_from_diff(
             .symbol_references
             .remove_references_from_invalid_symbols(&invalid_symbols_and_members);
 
-        let invalid_files = codebase
+        let mut invalid_files = codebase
             .files
             .iter()
             .filter(|(_, file_info)| {
@@ -72,6 +94,10 @@ pub(crate) fn mark_safe_symbols_from_diff(
             .map(|(file_id, _)| interner.lookup(file_id).to_string())
             .collect::<FxHashSet<_>>();
 
+        // changed files are always re-analyzed, since code outside of any symbol may
+        // have changed (their unchanged symbols are still skipped)
+        invalid_files.extend(changed_files);
+
         files_to_analyze.retain(|full_path| {
             invalid_files.contains(&get_relative_path(full_path, &config.root_dir))
         });
@@ -85,6 +111,7 @@ pub(crate) fn mark_safe_symbols_from_diff(
                     interner,
                     codebase_diff,
                     &invalid_symbols_and_members,
+                    &invalid_files,
                 );
                 cached_analysis.existing_issues = existing_issues;
             }
@@ -96,17 +123,48 @@ pub(crate) fn mark_safe_symbols_from_diff(
     }
 }
 
+// Finds the files that (directly or transitively) import any of the given files
+fn get_importing_files(
+    codebase: &CodebaseInfo,
+    interner: &Interner,
+    imported_files: &FxHashSet<String>,
+) -> FxHashSet<StrId> {
+    let mut importing_files = FxHashSet::default();
+
+    let mut new_imported_files = imported_files
+        .iter()
+        .filter_map(|file_path| interner.get(file_path))
+        .collect::<Vec<_>>();
+
+    while let Some(imported_file_id) = new_imported_files.pop() {
+        for (file_id, file_info) in &codebase.files {
+            if file_info.imported_files.contains(&imported_file_id)
+                && importing_files.insert(*file_id)
+            {
+                new_imported_files.push(*file_id);
+            }
+        }
+    }
+
+    importing_files
+}
+
 fn update_issues_from_diff(
     existing_issues: &mut BTreeMap<String, Vec<Issue>>,
     interner: &mut Interner,
     codebase_diff: CodebaseDiff,
     invalid_symbols_and_members: &FxHashSet<(StrId, StrId)>,
+    invalid_files: &FxHashSet<String>,
 ) {
     for (existing_file, file_issues) in existing_issues.iter_mut() {
         let file_id = &interner.intern(existing_file.clone());
 
+        // issues outside of any symbol are only regenerated if the file is re-analyzed
+        let file_is_invalid = invalid_files.contains(existing_file);
+
         file_issues.retain(|issue| {
-            !invalid_symbols_and_members.contains(&issue.symbol) && &issue.symbol.0 != file_id
+            !invalid_symbols_and_members.contains(&issue.symbol)
+                && !(file_is_invalid && &issue.symbol.0 == file_id)
         });
 
         if file_issues.is_empty() {
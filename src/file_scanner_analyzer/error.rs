@@ -0,0 +1,72 @@
+use std::{error::Error, fmt, io};
+
+use hakana_aast_helper::ParserError;
+use hakana_reflection_info::code_location::HPos;
+
+#[derive(Debug)]
+pub enum HakanaError {
+    // pos is None when the file isn't Hack at all
+    ParseError { pos: Option<HPos>, message: String },
+    Io(io::Error),
+    CacheCorruption(String),
+    InternalPanic { file: String },
+}
+
+impl fmt::Display for HakanaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HakanaError::ParseError { pos, message } => {
+                if let Some(pos) = pos {
+                    write!(
+                        f,
+                        "Parse error at {}:{}: {}",
+                        pos.start_line, pos.start_column, message
+                    )
+                } else {
+                    write!(f, "Parse error: {}", message)
+                }
+            }
+            HakanaError::Io(error) => write!(f, "{}", error),
+            HakanaError::CacheCorruption(path) => {
+                write!(
+                    f,
+                    "Could not read cache file {}, which may be corrupted",
+                    path
+                )
+            }
+            HakanaError::InternalPanic { file } => {
+                write!(f, "Hakana crashed while analyzing {}", file)
+            }
+        }
+    }
+}
+
+impl Error for HakanaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HakanaError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HakanaError {
+    fn from(error: io::Error) -> Self {
+        HakanaError::Io(error)
+    }
+}
+
+impl From<ParserError> for HakanaError {
+    fn from(error: ParserError) -> Self {
+        match error {
+            ParserError::NotAHackFile => HakanaError::ParseError {
+                pos: None,
+                message: "Invalid Hack file".to_string(),
+            },
+            ParserError::SyntaxError { message, pos } => HakanaError::ParseError {
+                pos: Some(pos),
+                message,
+            },
+        }
+    }
+}
@@ -1,7 +1,11 @@
-use std::{fs, path::Path};
+use std::{fs, io::Read, path::Path, time::Duration};
 
+use hakana_analyzer::config::Verbosity;
 use indexmap::IndexMap;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::cache::warn;
+use crate::file_provider::FileProvider;
 
 #[derive(Debug)]
 pub enum FileStatus {
@@ -25,19 +29,24 @@ pub(crate) fn get_file_manifest(cache_dir: &String) -> Option<FxHashMap<String,
     None
 }
 
-fn get_contents_hash(file_path: &String) -> Result<u64, std::io::Error> {
-    match fs::read_to_string(&file_path) {
-        Ok(file_contents) => Ok(xxhash_rust::xxh3::xxh3_64(file_contents.as_bytes())),
-        Err(error) => Err(error),
-    }
+fn get_contents_hash(file_path: &String, file_provider: &dyn FileProvider) -> Option<u64> {
+    file_provider
+        .get_contents(file_path)
+        .map(|file_contents| xxhash_rust::xxh3::xxh3_64(file_contents.as_bytes()))
 }
 
 pub(crate) fn get_file_diff(
     target_files: &IndexMap<String, u64>,
     file_update_hashes: FxHashMap<String, (u64, u64)>,
+    file_provider: &dyn FileProvider,
 ) -> IndexMap<String, FileStatus> {
     let mut file_statuses = IndexMap::new();
 
+    let overlaid_paths = file_provider
+        .get_overlaid_paths()
+        .into_iter()
+        .collect::<FxHashSet<_>>();
+
     for (file_path, new_update_time) in target_files {
         if let Some((old_contents_hash, old_update_time)) = file_update_hashes.get(file_path) {
             if file_path.starts_with("hhi_embedded_") || file_path.starts_with("hsl_embedded_") {
@@ -48,8 +57,8 @@ pub(crate) fn get_file_diff(
                 continue;
             }
 
-            if new_update_time != old_update_time {
-                if let Ok(new_contents_hash) = get_contents_hash(&file_path) {
+            if new_update_time != old_update_time || overlaid_paths.contains(file_path) {
+                if let Some(new_contents_hash) = get_contents_hash(&file_path, file_provider) {
                     if new_contents_hash != *old_contents_hash {
                         file_statuses.insert(
                             file_path.clone(),
@@ -76,7 +85,7 @@ pub(crate) fn get_file_diff(
                 continue;
             }
 
-            if let Ok(contents_hash) = get_contents_hash(&file_path) {
+            if let Some(contents_hash) = get_contents_hash(&file_path, file_provider) {
                 file_statuses.insert(
                     file_path.clone(),
                     FileStatus::Added(contents_hash, *new_update_time),
@@ -95,3 +104,186 @@ pub(crate) fn get_file_diff(
 
     file_statuses
 }
+
+// The cache files that together describe a scanned codebase
+const CODEBASE_CACHE_FILES: [&str; 4] = ["manifest", "symbols", "codebase", "aast_names"];
+
+// Published alongside the cache files, with a hash of each, so that a truncated or
+// corrupted download is caught before it replaces anything in the local cache
+const CODEBASE_CACHE_DIGESTS: &str = "digests";
+
+// Somewhere besides the local cache directory that codebase caches can be shared through,
+// so that e.g. CI workers can start from a codebase another worker has already scanned.
+// Files are stored under keys derived from the build checksum, as that decides whether
+// a cache can be used at all.
+pub trait RemoteCacheProvider: Send + Sync {
+    // Ok(None) when nothing has been published under the key
+    fn fetch(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+
+    fn publish(&self, key: &str, contents: &[u8]) -> Result<(), String>;
+}
+
+// Fetches with GET and publishes with PUT to {base_url}/{key}, which works with plain
+// HTTP file servers and S3-compatible buckets alike
+pub struct HttpCacheProvider {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpCacheProvider {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            // an unreachable cache server shouldn't hold up the run for long, as
+            // scanning locally is always an option
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(5))
+                .timeout(Duration::from_secs(300))
+                .build(),
+        }
+    }
+
+    fn get_url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+}
+
+impl RemoteCacheProvider for HttpCacheProvider {
+    fn fetch(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = match self.agent.get(&self.get_url(key)).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(error) => return Err(error.to_string()),
+        };
+
+        let mut contents = vec![];
+        response
+            .into_reader()
+            .read_to_end(&mut contents)
+            .map_err(|error| error.to_string())?;
+
+        Ok(Some(contents))
+    }
+
+    fn publish(&self, key: &str, contents: &[u8]) -> Result<(), String> {
+        self.agent
+            .put(&self.get_url(key))
+            .send_bytes(contents)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+}
+
+fn get_remote_cache_key(build_checksum: &str, file_name: &str) -> String {
+    format!(
+        "{:x}/{}",
+        xxhash_rust::xxh3::xxh3_64(build_checksum.as_bytes()),
+        file_name
+    )
+}
+
+fn get_digest(contents: &[u8]) -> String {
+    format!("{:x}", xxhash_rust::xxh3::xxh3_64(contents))
+}
+
+// Copies the remote codebase cache for this build into cache_dir, returning whether there
+// was a complete one. Manifest paths are absolute, so the cache is only useful to machines
+// that check the repository out to the same place. Anything wrong with the remote cache
+// is reported as a warning, and leaves the local cache to be rebuilt by scanning.
+pub(crate) fn fetch_remote_codebase_cache(
+    remote_cache_provider: &dyn RemoteCacheProvider,
+    cache_dir: &String,
+    build_checksum: &str,
+    verbosity: Verbosity,
+) -> bool {
+    match get_remote_codebase_cache(remote_cache_provider, build_checksum) {
+        Ok(Some(fetched_files)) => {
+            for (file_name, contents) in fetched_files {
+                if let Err(error) = fs::write(format!("{}/{}", cache_dir, file_name), contents) {
+                    warn(
+                        verbosity,
+                        &format!("could not write fetched {} cache: {}", file_name, error),
+                    );
+                    return false;
+                }
+            }
+
+            fs::write(format!("{}/buildinfo", cache_dir), build_checksum).is_ok()
+        }
+        Ok(None) => false,
+        Err(error) => {
+            warn(
+                verbosity,
+                &format!("could not fetch remote codebase cache: {}", error),
+            );
+            false
+        }
+    }
+}
+
+fn get_remote_codebase_cache(
+    remote_cache_provider: &dyn RemoteCacheProvider,
+    build_checksum: &str,
+) -> Result<Option<Vec<(&'static str, Vec<u8>)>>, String> {
+    let digests = if let Some(digests) = remote_cache_provider.fetch(&get_remote_cache_key(
+        build_checksum,
+        CODEBASE_CACHE_DIGESTS,
+    ))? {
+        String::from_utf8(digests).map_err(|_| "its digests are corrupted".to_string())?
+    } else {
+        return Ok(None);
+    };
+
+    let digests = digests
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut fetched_files = vec![];
+
+    for file_name in CODEBASE_CACHE_FILES {
+        let contents = if let Some(contents) =
+            remote_cache_provider.fetch(&get_remote_cache_key(build_checksum, file_name))?
+        {
+            contents
+        } else {
+            return Ok(None);
+        };
+
+        if digests.get(file_name) != Some(&get_digest(&contents).as_str()) {
+            return Err(format!("its {} file is corrupted", file_name));
+        }
+
+        fetched_files.push((file_name, contents));
+    }
+
+    Ok(Some(fetched_files))
+}
+
+// The digests are published last, so that a cache is never fetched while it's only
+// partly uploaded
+pub(crate) fn publish_codebase_cache(
+    remote_cache_provider: &dyn RemoteCacheProvider,
+    cache_dir: &String,
+    build_checksum: &str,
+) -> Result<(), String> {
+    let mut digests = String::new();
+
+    for file_name in CODEBASE_CACHE_FILES {
+        let contents = fs::read(format!("{}/{}", cache_dir, file_name))
+            .map_err(|error| format!("Could not read {} cache: {}", file_name, error))?;
+
+        remote_cache_provider
+            .publish(&get_remote_cache_key(build_checksum, file_name), &contents)?;
+
+        digests += &format!("{} {}\n", file_name, get_digest(&contents));
+    }
+
+    remote_cache_provider.publish(
+        &get_remote_cache_key(build_checksum, CODEBASE_CACHE_DIGESTS),
+        digests.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use super::*;
+
+#[derive(Default)]
+struct InMemoryCacheProvider {
+    files: Mutex<FxHashMap<String, Vec<u8>>>,
+}
+
+impl RemoteCacheProvider for InMemoryCacheProvider {
+    fn fetch(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.files.lock().unwrap().get(key).cloned())
+    }
+
+    fn publish(&self, key: &str, contents: &[u8]) -> Result<(), String> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), contents.to_vec());
+        Ok(())
+    }
+}
+
+fn create_cache_dir(name: &str) -> String {
+    let cache_dir = std::env::temp_dir().join(format!("hakana-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&cache_dir).unwrap();
+    cache_dir.to_string_lossy().to_string()
+}
+
+fn publish_test_cache(remote_cache_provider: &InMemoryCacheProvider) {
+    let cache_dir = create_cache_dir("remote-cache-source");
+
+    for file_name in CODEBASE_CACHE_FILES {
+        fs::write(format!("{}/{}", cache_dir, file_name), file_name).unwrap();
+    }
+
+    let published = publish_codebase_cache(remote_cache_provider, &cache_dir, "checksum");
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    published.unwrap();
+}
+
+#[test]
+fn fetches_published_codebase_cache() {
+    let remote_cache_provider = InMemoryCacheProvider::default();
+    publish_test_cache(&remote_cache_provider);
+
+    let cache_dir = create_cache_dir("remote-cache-fetch");
+
+    let fetched = fetch_remote_codebase_cache(
+        &remote_cache_provider,
+        &cache_dir,
+        "checksum",
+        Verbosity::Quiet,
+    );
+    let codebase = fs::read_to_string(format!("{}/codebase", cache_dir));
+    let buildinfo = fs::read_to_string(format!("{}/buildinfo", cache_dir));
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(fetched);
+    assert_eq!("codebase", codebase.unwrap());
+    assert_eq!("checksum", buildinfo.unwrap());
+}
+
+#[test]
+fn ignores_corrupt_remote_codebase_cache() {
+    let remote_cache_provider = InMemoryCacheProvider::default();
+    publish_test_cache(&remote_cache_provider);
+
+    remote_cache_provider
+        .publish(&get_remote_cache_key("checksum", "codebase"), b"garbage")
+        .unwrap();
+
+    let cache_dir = create_cache_dir("remote-cache-corrupt");
+
+    let fetched = fetch_remote_codebase_cache(
+        &remote_cache_provider,
+        &cache_dir,
+        "checksum",
+        Verbosity::Quiet,
+    );
+    let manifest_written = Path::new(&format!("{}/manifest", cache_dir)).exists();
+    let buildinfo_written = Path::new(&format!("{}/buildinfo", cache_dir)).exists();
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(!fetched);
+    assert!(!manifest_written);
+    assert!(!buildinfo_written);
+}
+
+#[test]
+fn ignores_incomplete_remote_codebase_cache() {
+    let remote_cache_provider = InMemoryCacheProvider::default();
+    publish_test_cache(&remote_cache_provider);
+
+    remote_cache_provider
+        .files
+        .lock()
+        .unwrap()
+        .remove(&get_remote_cache_key("checksum", "symbols"));
+
+    let cache_dir = create_cache_dir("remote-cache-incomplete");
+
+    let fetched = fetch_remote_codebase_cache(
+        &remote_cache_provider,
+        &cache_dir,
+        "checksum",
+        Verbosity::Quiet,
+    );
+    let buildinfo_written = Path::new(&format!("{}/buildinfo", cache_dir)).exists();
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+
+    assert!(!fetched);
+    assert!(!buildinfo_written);
+}
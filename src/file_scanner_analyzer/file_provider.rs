@@ -0,0 +1,101 @@
+use std::fs;
+use std::sync::Arc;
+
+use rustc_hash::FxHashMap;
+
+use crate::{HhiAsset, HslAsset};
+
+// Where the contents of scanned and analyzed files come from
+pub trait FileProvider: Send + Sync {
+    fn get_contents(&self, path: &str) -> Option<String>;
+
+    // Files whose contents don't match what's on disk, so can't be checked for changes
+    // using their modification times
+    fn get_overlaid_paths(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+// Reads files from disk, along with the HHI and HSL files embedded at build time
+pub struct DiskFileProvider;
+
+impl FileProvider for DiskFileProvider {
+    fn get_contents(&self, path: &str) -> Option<String> {
+        if path.starts_with("hsl_embedded_") {
+            Some(
+                std::str::from_utf8(
+                    &HslAsset::get(path)
+                        .unwrap_or_else(|| panic!("Could not read HSL file {}", path))
+                        .data,
+                )
+                .unwrap_or_else(|_| panic!("Could not convert HSL file {}", path))
+                .to_string(),
+            )
+        } else if path.starts_with("hhi_embedded_") {
+            Some(
+                std::str::from_utf8(
+                    &HhiAsset::get(path)
+                        .unwrap_or_else(|| panic!("Could not read HSL file {}", path))
+                        .data,
+                )
+                .unwrap_or_else(|_| panic!("Could not convert HHI file {}", path))
+                .to_string(),
+            )
+        } else if path.ends_with("tests/stubs/stubs.hack") {
+            Some("function hakana_expect_type<T>(T $id): void {}".to_string())
+        } else {
+            fs::read_to_string(path).ok()
+        }
+    }
+}
+
+// Serves files that only exist in memory, e.g. from an editor buffer or a browser
+pub struct InMemoryFileProvider {
+    files: FxHashMap<String, String>,
+}
+
+impl InMemoryFileProvider {
+    pub fn new(files: FxHashMap<String, String>) -> Self {
+        Self { files }
+    }
+}
+
+impl FileProvider for InMemoryFileProvider {
+    fn get_contents(&self, path: &str) -> Option<String> {
+        self.files.get(path).cloned()
+    }
+}
+
+// Layers in-memory contents (e.g. unsaved editor buffers) over another provider, usually
+// the disk. Overlaid files are always checked for changes, and runs that use them
+// never write to the on-disk caches.
+pub struct OverlayFileProvider {
+    overlaid_files: FxHashMap<String, String>,
+    base_provider: Arc<dyn FileProvider>,
+}
+
+impl OverlayFileProvider {
+    pub fn new(
+        overlaid_files: FxHashMap<String, String>,
+        base_provider: Arc<dyn FileProvider>,
+    ) -> Self {
+        Self {
+            overlaid_files,
+            base_provider,
+        }
+    }
+}
+
+impl FileProvider for OverlayFileProvider {
+    fn get_contents(&self, path: &str) -> Option<String> {
+        if let Some(contents) = self.overlaid_files.get(path) {
+            Some(contents.clone())
+        } else {
+            self.base_provider.get_contents(path)
+        }
+    }
+
+    fn get_overlaid_paths(&self) -> Vec<String> {
+        self.overlaid_files.keys().cloned().collect()
+    }
+}
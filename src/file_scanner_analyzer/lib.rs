@@ -1,37 +1,66 @@
 pub(crate) mod populator;
 
-use crate::file_cache_provider::FileStatus;
+use crate::file_cache_provider::{FileStatus, HttpCacheProvider};
+use analysis_cache::{can_use_analysis_cache, AnalysisCache};
+use analysis_order::sort_by_dependencies;
 use analyzer::analyze_files;
+use cache::{
+    load_cached_codebase, load_cached_existing_references, load_cached_symbols,
+    load_previous_issues, load_whole_program_checkpoint, remove_file_symbols, save_previous_issues,
+    save_whole_program_checkpoint,
+};
+use dataflow_graph_dot::get_dataflow_graph_dot;
+use dependency_digests::get_dependency_digests;
 use diff::mark_safe_symbols_from_diff;
+use error::HakanaError;
+use file_provider::{DiskFileProvider, FileProvider};
 use hakana_aast_helper::{get_aast_for_path_and_contents, ParserError};
 use hakana_analyzer::config::{Config, Verbosity};
-use hakana_analyzer::dataflow::program_analyzer::{find_connections, find_tainted_data};
+use hakana_analyzer::program_visitor::{visit_classlikes, visit_functionlikes, visit_issues};
 use hakana_reflection_info::analysis_result::AnalysisResult;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
-use hakana_reflection_info::data_flow::graph::{GraphKind, WholeProgramKind};
+use hakana_reflection_info::data_flow::graph::GraphKind;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::issue::Issue;
 use hakana_reflection_info::symbol_references::SymbolReferences;
 use hakana_reflection_info::Interner;
 use indexmap::IndexMap;
-use indicatif::ProgressBar;
+use orchestrator::Orchestrator;
 use oxidized::aast;
 use oxidized::scoured_comments::ScouredComments;
 use populator::populate_codebase;
+use run_metadata::get_run_metadata;
 use rust_embed::RustEmbed;
 use rustc_hash::{FxHashMap, FxHashSet};
 use scanner::{scan_files, ScanFilesResult};
 use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, Write};
+use std::io::Write;
 use std::sync::{Arc, Mutex};
 use std::time::{Instant, SystemTime};
+use symbol_liveness::explain_symbol_liveness;
+use typed_ast_dump::get_typed_ast;
 use unused_symbols::find_unused_definitions;
 
+mod analysis_cache;
+mod analysis_order;
 mod analyzer;
 mod ast_differ;
 mod cache;
+mod dataflow_graph_dot;
+mod dependency_digests;
 mod diff;
-mod file_cache_provider;
+pub mod error;
+pub mod file_cache_provider;
+pub mod file_provider;
+pub mod orchestrator;
+pub mod progress_bar;
+mod run_metadata;
 mod scanner;
+mod source_map;
+pub mod stubs;
+mod symbol_liveness;
+mod typed_ast_dump;
 mod unused_symbols;
 pub mod wasm;
 
@@ -62,12 +91,50 @@ pub fn scan_and_analyze(
     verbosity: Verbosity,
     header: &str,
     starter_data: Option<(CodebaseInfo, Interner)>,
-) -> io::Result<AnalysisResult> {
+) -> Result<AnalysisResult, HakanaError> {
+    scan_and_analyze_with_file_provider(
+        include_core_libs,
+        stubs_dirs,
+        filter,
+        ignored_paths,
+        config,
+        cache_dir,
+        threads,
+        verbosity,
+        header,
+        starter_data,
+        Arc::new(DiskFileProvider),
+    )
+}
+
+// Like scan_and_analyze, but with file contents coming from the given provider. With an
+// OverlayFileProvider, only the overlaid files are rescanned, and the caches in cache_dir
+// are read but never written.
+pub fn scan_and_analyze_with_file_provider(
+    include_core_libs: bool,
+    stubs_dirs: Vec<String>,
+    filter: Option<String>,
+    ignored_paths: Option<FxHashSet<String>>,
+    config: Arc<Config>,
+    cache_dir: Option<&String>,
+    threads: u8,
+    verbosity: Verbosity,
+    header: &str,
+    starter_data: Option<(CodebaseInfo, Interner)>,
+    file_provider: Arc<dyn FileProvider>,
+) -> Result<AnalysisResult, HakanaError> {
+    // the caches must only ever reflect what's on disk
+    let can_write_cache = file_provider.get_overlaid_paths().is_empty();
+
+    let orchestrator = Orchestrator::new(config.clone(), file_provider, verbosity);
+
     let mut all_scanned_dirs = stubs_dirs.clone();
     all_scanned_dirs.push(config.root_dir.clone());
 
     let now = Instant::now();
 
+    let build_checksum = get_build_checksum(header, &config);
+
     let mut files_to_analyze = vec![];
 
     let ScanFilesResult {
@@ -84,14 +151,15 @@ pub fn scan_and_analyze(
         &config,
         threads,
         verbosity,
-        header,
+        &build_checksum,
         starter_data,
+        orchestrator.file_provider(),
     )?;
 
-    if let Some(cache_dir) = cache_dir {
+    if let Some(cache_dir) = cache_dir.filter(|_| can_write_cache) {
         let timestamp_path = format!("{}/buildinfo", cache_dir);
         let mut timestamp_file = fs::File::create(&timestamp_path).unwrap();
-        write!(timestamp_file, "{}", header).unwrap();
+        write!(timestamp_file, "{}", build_checksum).unwrap();
 
         let aast_manifest_path = format!("{}/manifest", cache_dir);
         let mut manifest_file = fs::File::create(&aast_manifest_path).unwrap();
@@ -117,6 +185,24 @@ pub fn scan_and_analyze(
         manifest_file
             .write_all(&serialized_hashes)
             .unwrap_or_else(|_| panic!("Could not write aast manifest {}", &aast_manifest_path));
+
+        if config.publish_remote_cache {
+            if let Some(remote_cache_url) = &config.remote_cache_url {
+                // a failed upload shouldn't fail the run, as the local cache is still fine
+                if let Err(error) = file_cache_provider::publish_codebase_cache(
+                    &HttpCacheProvider::new(remote_cache_url.clone()),
+                    cache_dir,
+                    &build_checksum,
+                ) {
+                    cache::warn(
+                        verbosity,
+                        &format!("could not publish codebase cache: {}", error),
+                    );
+                } else if !matches!(verbosity, Verbosity::Quiet) {
+                    println!("Published codebase cache to {}", remote_cache_url);
+                }
+            }
+        }
     }
 
     let references_path = if let Some(cache_dir) = cache_dir {
@@ -131,6 +217,23 @@ pub fn scan_and_analyze(
         None
     };
 
+    let file_analyses_path = if let Some(cache_dir) = cache_dir {
+        Some(format!("{}/file_analyses", cache_dir))
+    } else {
+        None
+    };
+
+    // diff-based analysis loads this itself, below
+    let previous_references = if config.topological_analysis_order && !config.ast_diff {
+        if let Some(references_path) = &references_path {
+            load_cached_existing_references(references_path, true, verbosity)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
     let mut safe_symbols = FxHashSet::default();
     let mut safe_symbol_members = FxHashSet::default();
     let mut existing_issues = BTreeMap::new();
@@ -144,6 +247,7 @@ pub fn scan_and_analyze(
             &codebase,
             &mut interner,
             &mut files_to_analyze,
+            &file_statuses,
             &config,
             &issues_path,
         ) {
@@ -164,7 +268,13 @@ pub fn scan_and_analyze(
         println!("Calculating symbol inheritance");
     }
 
-    populate_codebase(&mut codebase, &interner, &mut symbol_references);
+    let now = Instant::now();
+
+    populate_codebase(&mut codebase, &interner, &mut symbol_references, threads);
+
+    if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
+        println!("Calculating symbol inheritance took {:.2?}", now.elapsed());
+    }
 
     codebase.interner = interner;
     codebase.safe_symbols = safe_symbols;
@@ -176,24 +286,112 @@ pub fn scan_and_analyze(
 
     analysis_result.emitted_issues = existing_issues;
 
+    // refining nullable returns needs a second pass, which starts from the same state
+    let initial_analysis_result = if config.refine_nullable_returns {
+        Some(analysis_result.clone())
+    } else {
+        None
+    };
+
     let analysis_result = Arc::new(Mutex::new(analysis_result));
 
+    if config.topological_analysis_order {
+        let analysis_result = analysis_result.lock().unwrap();
+
+        let previous_references = if let Some(previous_references) = &previous_references {
+            Some(previous_references)
+        } else if config.ast_diff {
+            Some(&analysis_result.symbol_references)
+        } else {
+            None
+        };
+
+        if let Some(previous_references) = previous_references {
+            files_to_analyze = sort_by_dependencies(
+                files_to_analyze,
+                &codebase,
+                previous_references,
+                &config.root_dir,
+            );
+        }
+    }
+
     let arc_codebase = Arc::new(codebase);
 
+    let analysis_cache = match &file_analyses_path {
+        Some(file_analyses_path) if can_use_analysis_cache(&config) => {
+            Some(Arc::new(AnalysisCache::load(
+                file_analyses_path,
+                &config,
+                &arc_codebase,
+                &file_statuses,
+                verbosity,
+            )))
+        }
+        _ => None,
+    };
+
+    let analyzed_files = if config.compute_dependency_digests {
+        files_to_analyze.clone()
+    } else {
+        vec![]
+    };
+
+    let files_to_reanalyze = if config.refine_nullable_returns {
+        files_to_analyze.clone()
+    } else {
+        vec![]
+    };
+
     analyze_files(
         files_to_analyze,
         arc_codebase.clone(),
         &resolved_names,
         config.clone(),
         &analysis_result,
-        filter,
+        filter.clone(),
         &ignored_paths,
         None,
-        &file_statuses,
+        &analysis_cache,
         threads,
         verbosity,
+        orchestrator.file_provider(),
     )?;
 
+    let never_null_returns =
+        std::mem::take(&mut analysis_result.lock().unwrap().never_null_returns);
+
+    // once we know which nullable returns are never null, everything is analyzed again so
+    // that their callers see the non-null type
+    let (arc_codebase, analysis_result) = match initial_analysis_result {
+        Some(initial_analysis_result) if !never_null_returns.is_empty() => {
+            let mut codebase = Arc::try_unwrap(arc_codebase).unwrap();
+
+            mark_never_null_returns(&mut codebase, &never_null_returns);
+
+            let arc_codebase = Arc::new(codebase);
+            let analysis_result = Arc::new(Mutex::new(initial_analysis_result));
+
+            analyze_files(
+                files_to_reanalyze,
+                arc_codebase.clone(),
+                &resolved_names,
+                config.clone(),
+                &analysis_result,
+                filter,
+                &ignored_paths,
+                None,
+                &analysis_cache,
+                threads,
+                verbosity,
+                orchestrator.file_provider(),
+            )?;
+
+            (arc_codebase, analysis_result)
+        }
+        _ => (arc_codebase, analysis_result),
+    };
+
     let elapsed = now.elapsed();
 
     if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
@@ -203,6 +401,27 @@ pub fn scan_and_analyze(
     let mut analysis_result = (*analysis_result.lock().unwrap()).clone();
 
     analysis_result.time_in_analysis = elapsed;
+    analysis_result.run_metadata = Some(get_run_metadata(
+        &config,
+        &build_checksum,
+        Some(&file_statuses),
+    ));
+
+    // every cached result is checked before it's used, so even a truncated run can save
+    if let (Some(analysis_cache), Some(file_analyses_path)) = (
+        analysis_cache,
+        file_analyses_path.as_ref().filter(|_| can_write_cache),
+    ) {
+        if let Ok(analysis_cache) = Arc::try_unwrap(analysis_cache) {
+            analysis_cache.save(file_analyses_path)?;
+        }
+    }
+
+    // a truncated run has incomplete references and issues, so shouldn't be cached
+    let references_path =
+        references_path.filter(|_| analysis_result.unanalyzed_file_count == 0 && can_write_cache);
+    let issues_path =
+        issues_path.filter(|_| analysis_result.unanalyzed_file_count == 0 && can_write_cache);
 
     if let Some(references_path) = references_path {
         let mut symbols_file = fs::File::create(&references_path).unwrap();
@@ -219,10 +438,43 @@ pub fn scan_and_analyze(
 
     let mut codebase = Arc::try_unwrap(arc_codebase).unwrap();
 
-    if config.find_unused_definitions {
+    if config.compute_dependency_digests {
+        analysis_result.dependency_digests = get_dependency_digests(
+            &codebase,
+            &analysis_result.symbol_references,
+            &file_statuses,
+            &analyzed_files,
+            &config.root_dir,
+            config.analyze_traits_in_using_classes,
+        );
+    }
+
+    if config.needs_unused_definitions() {
         find_unused_definitions(&mut analysis_result, &config, &codebase, &ignored_paths);
     }
 
+    if let Some(symbol_name) = &config.explain_liveness_of {
+        analysis_result.liveness_explanation = explain_symbol_liveness(
+            &analysis_result,
+            &config,
+            &codebase,
+            &ignored_paths,
+            symbol_name,
+        );
+    }
+
+    if let Some(file_path) = &config.typed_ast_file {
+        analysis_result.typed_ast = Some(get_typed_ast(
+            file_path,
+            &analysis_result,
+            &config,
+            &**orchestrator.file_provider(),
+        )?);
+    }
+
+    visit_classlikes(&config.program_visitors, &codebase, threads);
+    visit_functionlikes(&config.program_visitors, &codebase, threads);
+
     let interner = codebase.interner;
 
     std::thread::spawn(move || {
@@ -232,32 +484,220 @@ pub fn scan_and_analyze(
         codebase.type_definitions.clear();
     });
 
-    if let GraphKind::WholeProgram(whole_program_kind) = config.graph_kind {
-        let issues = match whole_program_kind {
-            WholeProgramKind::Taint => find_tainted_data(
+    // the checkpoint is resumed from later, so must only ever reflect what's on disk
+    if let (GraphKind::WholeProgram(_), Some(checkpoint_path)) = (
+        config.graph_kind,
+        config
+            .whole_program_checkpoint_path
+            .as_ref()
+            .filter(|_| can_write_cache),
+    ) {
+        save_whole_program_checkpoint(
+            checkpoint_path,
+            &interner,
+            &analysis_result.program_dataflow_graph,
+            &analysis_result.emitted_issues,
+        )?;
+    }
+
+    if let Some(dataflow_graph_path) = &config.dataflow_graph_path {
+        fs::write(
+            dataflow_graph_path,
+            get_dataflow_graph_dot(
                 &analysis_result.program_dataflow_graph,
-                &config,
-                verbosity,
                 &interner,
+                &config.dataflow_graph_sources,
             ),
-            WholeProgramKind::Query => find_connections(
+        )?;
+    }
+
+    orchestrator.add_whole_program_issues(&mut analysis_result, &interner, threads);
+
+    // unlike the issues cache, this has the unused symbol and whole-program issues too
+    if let Some(cache_dir) =
+        cache_dir.filter(|_| analysis_result.unanalyzed_file_count == 0 && can_write_cache)
+    {
+        save_previous_issues(
+            &format!("{}/previous_issues", cache_dir),
+            &analysis_result.emitted_issues,
+        )?;
+    }
+
+    visit_issues(
+        &config.program_visitors,
+        &analysis_result,
+        &interner,
+        threads,
+    );
+
+    Ok(analysis_result)
+}
+
+// Runs just the whole-program phase of an earlier run, using the checkpoint it saved
+// before that phase started. The config can differ from the earlier run's, e.g. to
+// use a lower max depth.
+pub fn resume_whole_program_analysis(
+    config: Arc<Config>,
+    checkpoint_path: &String,
+    threads: u8,
+    verbosity: Verbosity,
+    header: &str,
+) -> Result<AnalysisResult, HakanaError> {
+    let (interner, program_dataflow_graph, emitted_issues) =
+        load_whole_program_checkpoint(checkpoint_path, verbosity)?;
+
+    let orchestrator = Orchestrator::new(config.clone(), Arc::new(DiskFileProvider), verbosity);
+
+    let mut analysis_result = AnalysisResult::new(config.graph_kind, SymbolReferences::new());
+    analysis_result.program_dataflow_graph = program_dataflow_graph;
+    analysis_result.emitted_issues = emitted_issues;
+    analysis_result.run_metadata = Some(get_run_metadata(
+        &config,
+        &get_build_checksum(header, &config),
+        None,
+    ));
+
+    if let Some(dataflow_graph_path) = &config.dataflow_graph_path {
+        fs::write(
+            dataflow_graph_path,
+            get_dataflow_graph_dot(
                 &analysis_result.program_dataflow_graph,
-                &config,
-                verbosity,
                 &interner,
+                &config.dataflow_graph_sources,
             ),
+        )?;
+    }
+
+    orchestrator.add_whole_program_issues(&mut analysis_result, &interner, threads);
+
+    visit_issues(
+        &config.program_visitors,
+        &analysis_result,
+        &interner,
+        threads,
+    );
+
+    Ok(analysis_result)
+}
+
+// Loads every issue reported by the last complete run that used cache_dir
+pub fn load_issues_cache(
+    cache_dir: &String,
+    verbosity: Verbosity,
+) -> Result<Option<BTreeMap<String, Vec<Issue>>>, HakanaError> {
+    load_previous_issues(&format!("{}/previous_issues", cache_dir), verbosity)
+}
+
+// Loads the scanned (but not populated) codebase cached in cache_dir by the last run,
+// along with the interner its ids refer to
+pub fn load_codebase_cache(
+    cache_dir: &String,
+    verbosity: Verbosity,
+) -> Option<(CodebaseInfo, Interner)> {
+    let interner = load_cached_symbols(&format!("{}/symbols", cache_dir), verbosity)?;
+    let codebase = load_cached_codebase(&format!("{}/codebase", cache_dir), verbosity)?;
+
+    Some((codebase, interner))
+}
+
+// Loads the codebase cached by a full run in dependency_root_dir, so that a subtree of
+// that root can be analyzed without rescanning everything else. Symbols from files in
+// the subtree are dropped, since the subtree is rescanned and its current symbols
+// take their place.
+pub fn load_dependency_codebase(
+    dependency_root_dir: &String,
+    subtree_dir: &String,
+    verbosity: Verbosity,
+) -> Option<(CodebaseInfo, Interner)> {
+    let (mut codebase, interner) =
+        load_codebase_cache(&format!("{}/.hakana_cache", dependency_root_dir), verbosity)?;
+
+    if let Some(subtree_path) = subtree_dir.strip_prefix(&format!("{}/", dependency_root_dir)) {
+        let subtree_prefix = format!("{}/", subtree_path);
+
+        let subtree_files = codebase
+            .files
+            .keys()
+            .map(|file_id| interner.lookup(file_id))
+            .filter(|file_path| file_path.starts_with(&subtree_prefix))
+            .map(|file_path| file_path.to_string())
+            .collect::<FxHashSet<_>>();
+
+        remove_file_symbols(
+            &mut codebase,
+            &mut CodebaseInfo::new(),
+            &interner,
+            &subtree_files,
+        );
+
+        codebase
+            .files
+            .retain(|file_id, _| !subtree_files.contains(interner.lookup(file_id)));
+    }
+
+    Some((codebase, interner))
+}
+
+// Marks functions whose nullable return is never null, so that the second analysis pass
+// gives their callers the non-null type
+fn mark_never_null_returns(
+    codebase: &mut CodebaseInfo,
+    never_null_returns: &FxHashSet<FunctionLikeIdentifier>,
+) {
+    for functionlike_id in never_null_returns {
+        let functionlike_info = match functionlike_id {
+            FunctionLikeIdentifier::Function(function_name) => {
+                codebase.functionlike_infos.get_mut(function_name)
+            }
+            FunctionLikeIdentifier::Method(classlike_name, method_name) => codebase
+                .classlike_infos
+                .get_mut(classlike_name)
+                .and_then(|classlike_info| classlike_info.methods.get_mut(method_name)),
         };
 
-        for issue in issues {
-            analysis_result
-                .emitted_issues
-                .entry(interner.lookup(&issue.pos.file_path).to_string())
-                .or_insert_with(Vec::new)
-                .push(issue);
+        if let Some(functionlike_info) = functionlike_info {
+            functionlike_info.never_returns_null = true;
         }
     }
+}
 
-    Ok(analysis_result)
+// Core libs loaded from disk are part of what the cached codebase was built from,
+// so switching them (e.g. to another HHVM release's HHI files) invalidates the cache.
+// So do the rules for which files are generated, and whether generated code's origins
+// are interned, as they're applied at scan time.
+fn get_build_checksum(header: &str, config: &Config) -> String {
+    // the thresholds change the constant types stored in the codebase cache
+    let mut build_checksum = format!(
+        "{}\nGenerated files: {:?} {:?} {}\nLiteral collection thresholds: {:?}",
+        header,
+        config.generated_file_header_patterns,
+        config.generated_file_paths,
+        config.report_generated_issues_at_origin,
+        config.literal_collection_thresholds
+    );
+
+    if config.hhi_dir.is_none() && config.hsl_dir.is_none() {
+        return build_checksum;
+    }
+
+    let mut core_lib_contents = String::new();
+
+    for core_lib_dir in [&config.hhi_dir, &config.hsl_dir].into_iter().flatten() {
+        for path in find_files_in_dir(core_lib_dir, config, &mut vec![]).keys() {
+            core_lib_contents += path;
+
+            if let Ok(contents) = fs::read_to_string(path) {
+                core_lib_contents += &contents;
+            }
+        }
+    }
+
+    build_checksum += &format!(
+        "\nCore libs: {:016x}",
+        xxhash_rust::xxh3::xxh3_64(core_lib_contents.as_bytes())
+    );
+
+    build_checksum
 }
 
 fn find_files_in_dir(
@@ -346,35 +786,16 @@ pub fn get_aast_for_path(
     path: &String,
     root_dir: &String,
     cache_dir: Option<&String>,
+    file_provider: &dyn FileProvider,
 ) -> Result<(aast::Program<(), ()>, ScouredComments, String), ParserError> {
-    let file_contents = if path.starts_with("hsl_embedded_") {
-        std::str::from_utf8(
-            &HslAsset::get(path)
-                .unwrap_or_else(|| panic!("Could not read HSL file {}", path))
-                .data,
-        )
-        .unwrap_or_else(|_| panic!("Could not convert HSL file {}", path))
-        .to_string()
-    } else if path.starts_with("hhi_embedded_") {
-        std::str::from_utf8(
-            &HhiAsset::get(path)
-                .unwrap_or_else(|| panic!("Could not read HSL file {}", path))
-                .data,
-        )
-        .unwrap_or_else(|_| panic!("Could not convert HHI file {}", path))
-        .to_string()
-    } else if path.ends_with("tests/stubs/stubs.hack") {
-        "function hakana_expect_type<T>(T $id): void {}".to_string()
-    } else {
-        match fs::read_to_string(path) {
-            Ok(str_file) => str_file,
-            Err(_) => return Err(ParserError::NotAHackFile),
-        }
+    let file_contents = match file_provider.get_contents(path) {
+        Some(file_contents) => file_contents,
+        None => return Err(ParserError::NotAHackFile),
     };
 
     let mut local_path = path.clone();
 
-    if local_path.starts_with(root_dir) {
+    if !root_dir.is_empty() && local_path.starts_with(root_dir) {
         local_path = local_path.replace(root_dir, "");
         local_path = local_path[1..].to_string();
     }
@@ -388,16 +809,13 @@ pub fn get_aast_for_path(
     get_aast_for_path_and_contents(local_path, file_contents, aast_cache_dir)
 }
 
-fn update_progressbar(percentage: u64, bar: Option<Arc<ProgressBar>>) {
-    if let Some(bar) = bar {
-        bar.set_position(percentage);
-    }
-}
-
 fn get_relative_path(str_path: &String, root_dir: &String) -> String {
-    if str_path.contains(root_dir) {
+    if !root_dir.is_empty() && str_path.contains(root_dir) {
         str_path[(root_dir.len() + 1)..].to_string()
     } else {
         str_path.clone()
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,213 @@
+use hakana_aast_helper::name_context::NameContext;
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_analyzer::dataflow::program_analyzer::{find_connections, find_tainted_data};
+use hakana_reflection_info::analysis_result::AnalysisResult;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::data_flow::graph::{GraphKind, WholeProgramKind};
+use hakana_reflection_info::symbol_references::SymbolReferences;
+use hakana_reflection_info::{Interner, ThreadedInterner};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::analyzer::analyze_file_catching_panics;
+use crate::cache::remove_file_symbols;
+use crate::error::HakanaError;
+use crate::file_provider::FileProvider;
+use crate::get_relative_path;
+use crate::populator::populate_codebase;
+use crate::scanner::{scan_file, scan_files, ScanFilesResult};
+
+// Runs the steps shared by every way of invoking Hakana (CLI, language server, wasm),
+// so that they all produce the same issues for the same config. Where file contents
+// come from is left to the file provider.
+pub struct Orchestrator {
+    config: Arc<Config>,
+    file_provider: Arc<dyn FileProvider>,
+    verbosity: Verbosity,
+}
+
+impl Orchestrator {
+    pub fn new(
+        config: Arc<Config>,
+        file_provider: Arc<dyn FileProvider>,
+        verbosity: Verbosity,
+    ) -> Self {
+        Self {
+            config,
+            file_provider,
+            verbosity,
+        }
+    }
+
+    pub fn config(&self) -> &Arc<Config> {
+        &self.config
+    }
+
+    pub fn file_provider(&self) -> &Arc<dyn FileProvider> {
+        &self.file_provider
+    }
+
+    // Scans the core libraries and every file under the root dir, without analyzing
+    // anything, so that individual files can later be analyzed against the whole project
+    pub fn scan_workspace(&self, threads: u8) -> Result<CodebaseInfo, HakanaError> {
+        let ScanFilesResult {
+            mut codebase,
+            interner,
+            ..
+        } = scan_files(
+            &vec![self.config.root_dir.clone()],
+            true,
+            None,
+            &mut vec![],
+            &self.config,
+            threads,
+            self.verbosity,
+            "",
+            None,
+            &self.file_provider,
+        )?;
+
+        populate_codebase(
+            &mut codebase,
+            &interner,
+            &mut SymbolReferences::new(),
+            threads,
+        );
+
+        codebase.interner = interner;
+
+        Ok(codebase)
+    }
+
+    // Scans the given files into an existing codebase (e.g. one containing just the
+    // core libraries) and then analyzes them. Used when there are only a handful of
+    // files to look at, so everything happens on the current thread.
+    pub fn scan_and_analyze_in_codebase(
+        &self,
+        codebase: &mut CodebaseInfo,
+        paths: &Vec<String>,
+    ) -> Result<AnalysisResult, HakanaError> {
+        let interner = std::mem::take(&mut codebase.interner);
+
+        // the files may have been scanned into this codebase before (e.g. an earlier
+        // version of an editor buffer), and symbols they no longer define shouldn't linger
+        let relative_paths = paths
+            .iter()
+            .map(|str_path| get_relative_path(str_path, &self.config.root_dir))
+            .collect::<FxHashSet<_>>();
+
+        remove_file_symbols(
+            codebase,
+            &mut CodebaseInfo::new(),
+            &interner,
+            &relative_paths,
+        );
+
+        let interner = Arc::new(Mutex::new(interner));
+        let mut threaded_interner = ThreadedInterner::new(interner.clone());
+        let empty_name_context = NameContext::new(&mut threaded_interner);
+
+        let mut resolved_names = FxHashMap::default();
+
+        for str_path in paths {
+            let file_resolved_names = if let Ok(file_resolved_names) = scan_file(
+                str_path,
+                &self.config.root_dir,
+                &self.config,
+                codebase,
+                &mut threaded_interner,
+                empty_name_context.clone(),
+                true,
+                true,
+                self.verbosity,
+                &*self.file_provider,
+            ) {
+                file_resolved_names
+            } else {
+                // the analyzer reports the parse error, but needs the path to be interned
+                threaded_interner.intern(get_relative_path(str_path, &self.config.root_dir));
+                FxHashMap::default()
+            };
+
+            resolved_names.insert(str_path.clone(), file_resolved_names);
+        }
+
+        drop(threaded_interner);
+
+        let interner = Arc::try_unwrap(interner).unwrap().into_inner().unwrap();
+
+        let mut symbol_references = SymbolReferences::new();
+
+        populate_codebase(codebase, &interner, &mut symbol_references, 1);
+
+        codebase.interner = interner;
+
+        let mut analysis_result = AnalysisResult::new(self.config.graph_kind, symbol_references);
+
+        for str_path in paths {
+            analyze_file_catching_panics(
+                str_path,
+                None,
+                codebase,
+                &self.config,
+                &mut analysis_result,
+                &resolved_names[str_path],
+                self.verbosity,
+                &*self.file_provider,
+            )?;
+        }
+
+        self.add_whole_program_issues(&mut analysis_result, &codebase.interner, 1);
+
+        Ok(analysis_result)
+    }
+
+    // Whole-program analyses only produce issues once every file has contributed to
+    // the dataflow graph
+    pub(crate) fn add_whole_program_issues(
+        &self,
+        analysis_result: &mut AnalysisResult,
+        interner: &Interner,
+        threads: u8,
+    ) {
+        let whole_program_kind =
+            if let GraphKind::WholeProgram(whole_program_kind) = self.config.graph_kind {
+                whole_program_kind
+            } else {
+                return;
+            };
+
+        let issues = match whole_program_kind {
+            WholeProgramKind::Taint => find_tainted_data(
+                &analysis_result.program_dataflow_graph,
+                &self.config,
+                self.verbosity,
+                interner,
+                threads,
+            ),
+            WholeProgramKind::Query => find_connections(
+                &analysis_result.program_dataflow_graph,
+                &self.config,
+                self.verbosity,
+                interner,
+                threads,
+            ),
+        };
+
+        for mut issue in issues {
+            let file_path = interner.lookup(&issue.pos.file_path);
+
+            // these are found after every file has been analyzed, so the file's issue
+            // profile is applied here rather than in TastInfo
+            if let Some(severity) = self.config.get_reported_issue_severity(&issue, file_path) {
+                issue.severity = severity;
+
+                analysis_result
+                    .emitted_issues
+                    .entry(file_path.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(issue);
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ use hakana_reflection_info::member_visibility::MemberVisibility;
 use hakana_reflection_info::symbol_references::{ReferenceSource, SymbolReferences};
 use hakana_reflection_info::t_atomic::{populate_atomic_type, TAtomic};
 use hakana_reflection_info::t_union::{populate_union_type, TUnion};
+use hakana_reflection_info::taint::{get_builtin_sanitizer_removed_taints, SinkType};
 use hakana_reflection_info::{Interner, StrId};
 use indexmap::IndexMap;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -17,14 +18,28 @@ pub fn populate_codebase(
     codebase: &mut CodebaseInfo,
     interner: &Interner,
     symbol_references: &mut SymbolReferences,
+    threads: u8,
 ) {
     let mut all_classlike_descendants = FxHashMap::default();
 
-    let classlike_names = codebase
+    for classlike_layer in get_classlike_layers(codebase) {
+        populate_classlike_layer(
+            classlike_layer,
+            &mut all_classlike_descendants,
+            codebase,
+            symbol_references,
+            threads,
+        );
+    }
+
+    // classlikes in (or inheriting from) inheritance cycles aren't in any layer
+    let mut classlike_names = codebase
         .classlike_infos
         .iter()
+        .filter(|(_, storage)| !storage.is_populated)
         .map(|(k, _)| k.clone())
         .collect::<Vec<_>>();
+    classlike_names.sort();
 
     for k in &classlike_names {
         populate_classlike_storage(
@@ -196,6 +211,140 @@ pub fn populate_codebase(
     all_classlike_descendants.retain(|k, _| !interner.lookup(k).starts_with("HH\\"));
 
     codebase.classlike_descendants = all_classlike_descendants;
+
+    infer_function_effects(codebase);
+
+    infer_sanitizer_wrappers(codebase, interner);
+}
+
+// Functions whose bodies just return a call to a sanitizer, passing their first param
+// through, remove the same taints as that sanitizer from values passed to that param
+fn infer_sanitizer_wrappers(codebase: &mut CodebaseInfo, interner: &Interner) {
+    let mut wrappers = vec![];
+
+    for (name, functionlike_info) in codebase.functionlike_infos.iter_mut() {
+        if let Some(first_param) = functionlike_info.params.first_mut() {
+            // inferred afresh, as the wrapped function may have changed since the last run
+            first_param.inferred_removed_taints = None;
+        } else {
+            continue;
+        }
+
+        if !functionlike_info.user_defined || functionlike_info.removed_taints.is_some() {
+            continue;
+        }
+
+        if let Some(wrapped_function) = functionlike_info.wrapped_function {
+            wrappers.push((*name, wrapped_function));
+        }
+    }
+
+    // wrappers can wrap other wrappers, so this repeats until no more are found
+    let mut found_sanitizer = true;
+
+    while found_sanitizer {
+        found_sanitizer = false;
+
+        for (name, wrapped_function) in &wrappers {
+            if codebase.functionlike_infos[name].params[0]
+                .inferred_removed_taints
+                .is_some()
+            {
+                continue;
+            }
+
+            let removed_taints = get_sanitizer_removed_taints(codebase, interner, wrapped_function);
+
+            if let Some(removed_taints) = removed_taints {
+                if let Some(functionlike_info) = codebase.functionlike_infos.get_mut(name) {
+                    functionlike_info.params[0].inferred_removed_taints = Some(removed_taints);
+                    found_sanitizer = true;
+                }
+            }
+        }
+    }
+}
+
+// The taints a call to the given function removes from its first argument
+fn get_sanitizer_removed_taints(
+    codebase: &CodebaseInfo,
+    interner: &Interner,
+    function_name: &StrId,
+) -> Option<FxHashSet<SinkType>> {
+    if let Some(removed_taints) =
+        get_builtin_sanitizer_removed_taints(interner.lookup(function_name))
+    {
+        return Some(removed_taints);
+    }
+
+    let functionlike_info = codebase.functionlike_infos.get(function_name)?;
+
+    functionlike_info.removed_taints.clone().or_else(|| {
+        functionlike_info
+            .params
+            .first()
+            .and_then(|param| param.inferred_removed_taints.clone())
+    })
+}
+
+// Marks every user-defined function or method that's impure, or that calls (directly or
+// via other functions) an impure function, as impure. Builtins are left alone, as their
+// contexts are already checked by the typechecker.
+//
+// Only direct calls to named functions are edges in the call graph. Calls made inside a
+// closure count as calls made by the function that defines it, whether or not the closure
+// is ever invoked, while calls through function pointers, closures and method calls
+// aren't followed.
+fn infer_function_effects(codebase: &mut CodebaseInfo) {
+    let mut callers: FxHashMap<StrId, Vec<StrId>> = FxHashMap::default();
+    let mut impure_functions = vec![];
+
+    for (name, functionlike_info) in codebase.functionlike_infos.iter_mut() {
+        functionlike_info.inferred_impure = false;
+
+        if !functionlike_info.user_defined {
+            continue;
+        }
+
+        if functionlike_info.effects.is_impure() {
+            impure_functions.push(*name);
+        }
+
+        for called_function in &functionlike_info.called_functions {
+            callers
+                .entry(*called_function)
+                .or_insert_with(Vec::new)
+                .push(*name);
+        }
+    }
+
+    let mut seen_functions = impure_functions.iter().copied().collect::<FxHashSet<_>>();
+
+    while let Some(impure_function) = impure_functions.pop() {
+        if let Some(functionlike_info) = codebase.functionlike_infos.get_mut(&impure_function) {
+            functionlike_info.inferred_impure = true;
+        }
+
+        if let Some(function_callers) = callers.get(&impure_function) {
+            for caller in function_callers {
+                if seen_functions.insert(*caller) {
+                    impure_functions.push(*caller);
+                }
+            }
+        }
+    }
+
+    // nothing tracks calls to methods, so they can be settled once every function has been
+    for classlike_info in codebase.classlike_infos.values_mut() {
+        for method_info in classlike_info.methods.values_mut() {
+            method_info.inferred_impure = method_info.user_defined
+                && (method_info.effects.is_impure()
+                    || method_info
+                        .called_functions
+                        .iter()
+                        .any(|called_function| seen_functions.contains(called_function)));
+        }
+    }
 }
 
 fn populate_functionlike_storage(
@@ -245,6 +394,149 @@ fn populate_functionlike_storage(
             symbol_references,
         );
     }
+
+    for (_, _, where_type) in storage.where_as_constraints.iter_mut() {
+        populate_union_type(
+            where_type,
+            &codebase_symbols,
+            reference_source,
+            symbol_references,
+        );
+    }
+}
+
+// Groups the classlikes that need populating so that the parents, interfaces and traits
+// of every classlike are populated in an earlier layer than it. Each layer can then be
+// populated in parallel.
+fn get_classlike_layers(codebase: &CodebaseInfo) -> Vec<Vec<StrId>> {
+    let mut pending_classlikes = codebase
+        .classlike_infos
+        .iter()
+        .filter(|(_, storage)| !storage.is_populated)
+        .map(|(name, storage)| {
+            let dependencies = get_classlike_dependencies(storage)
+                .into_iter()
+                .filter(|dependency| {
+                    codebase
+                        .classlike_infos
+                        .get(dependency)
+                        .map_or(false, |dependency_storage| !dependency_storage.is_populated)
+                })
+                .collect::<Vec<_>>();
+
+            (*name, dependencies)
+        })
+        .collect::<Vec<_>>();
+
+    pending_classlikes.sort_by_key(|(name, _)| *name);
+
+    let mut layered_classlikes = FxHashSet::default();
+    let mut layers = vec![];
+
+    loop {
+        let mut layer = vec![];
+
+        pending_classlikes.retain(|(name, dependencies)| {
+            if dependencies
+                .iter()
+                .all(|dependency| layered_classlikes.contains(dependency))
+            {
+                layer.push(*name);
+                false
+            } else {
+                true
+            }
+        });
+
+        if layer.is_empty() {
+            break;
+        }
+
+        layered_classlikes.extend(layer.iter().copied());
+        layers.push(layer);
+    }
+
+    layers
+}
+
+fn get_classlike_dependencies(storage: &ClassLikeInfo) -> Vec<StrId> {
+    let mut dependencies = storage.used_traits.iter().copied().collect::<Vec<_>>();
+
+    if let Some(parent_classname) = storage.direct_parent_class {
+        dependencies.push(parent_classname);
+    }
+
+    dependencies.extend(storage.direct_parent_interfaces.iter().copied());
+    dependencies.extend(storage.direct_class_interfaces.iter().copied());
+
+    dependencies
+}
+
+// Populates classlikes whose dependencies have all been populated, split into one chunk
+// per thread. Each thread collects its own symbol references, which are merged in chunk
+// order afterwards so the result doesn't depend on scheduling.
+fn populate_classlike_layer(
+    classlike_layer: Vec<StrId>,
+    all_classlike_descendants: &mut FxHashMap<StrId, FxHashSet<StrId>>,
+    codebase: &mut CodebaseInfo,
+    symbol_references: &mut SymbolReferences,
+    threads: u8,
+) {
+    let mut storages = classlike_layer
+        .iter()
+        .filter_map(|classlike_name| codebase.classlike_infos.remove(classlike_name))
+        .collect::<Vec<_>>();
+
+    if storages.is_empty() {
+        return;
+    }
+
+    let chunk_size = storages.len().div_ceil(threads.max(1) as usize);
+
+    let codebase_ref = &*codebase;
+
+    let chunk_results = std::thread::scope(|scope| {
+        let handles = storages
+            .chunks_mut(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut chunk_symbol_references = SymbolReferences::new();
+                    let mut trait_users = vec![];
+
+                    for storage in chunk {
+                        populate_classlike_storage_from_dependencies(
+                            storage,
+                            codebase_ref,
+                            &mut chunk_symbol_references,
+                            &mut trait_users,
+                        );
+                    }
+
+                    (chunk_symbol_references, trait_users)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for (chunk_symbol_references, trait_users) in chunk_results {
+        symbol_references.extend(chunk_symbol_references);
+
+        for (trait_name, classlike_name) in trait_users {
+            all_classlike_descendants
+                .entry(trait_name)
+                .or_insert_with(FxHashSet::default)
+                .insert(classlike_name);
+        }
+    }
+
+    for storage in storages {
+        codebase.classlike_infos.insert(storage.name, storage);
+    }
 }
 
 fn populate_classlike_storage(
@@ -276,30 +568,61 @@ fn populate_classlike_storage(
         }
     }
 
-    for trait_name in &storage.used_traits.clone() {
-        populate_data_from_trait(
-            &mut storage,
+    for dependency in get_classlike_dependencies(&storage) {
+        populate_classlike_storage(
+            &dependency,
             all_classlike_descendants,
             codebase,
-            trait_name,
             symbol_references,
         );
     }
 
-    if let Some(parent_classname) = &storage.direct_parent_class.clone() {
-        populate_data_from_parent_classlike(
-            &mut storage,
-            all_classlike_descendants,
+    let mut trait_users = vec![];
+
+    populate_classlike_storage_from_dependencies(
+        &mut storage,
+        codebase,
+        symbol_references,
+        &mut trait_users,
+    );
+
+    for (trait_name, user_name) in trait_users {
+        all_classlike_descendants
+            .entry(trait_name)
+            .or_insert_with(FxHashSet::default)
+            .insert(user_name);
+    }
+
+    codebase
+        .classlike_infos
+        .insert(classlike_name.clone(), storage);
+}
+
+// Expects the classlike's parents, interfaces and traits to have been populated already.
+// The traits it uses are added to trait_users, as (trait, user) pairs.
+fn populate_classlike_storage_from_dependencies(
+    storage: &mut ClassLikeInfo,
+    codebase: &CodebaseInfo,
+    symbol_references: &mut SymbolReferences,
+    trait_users: &mut Vec<(StrId, StrId)>,
+) {
+    for trait_name in &storage.used_traits.clone() {
+        populate_data_from_trait(
+            storage,
             codebase,
-            parent_classname,
+            trait_name,
             symbol_references,
+            trait_users,
         );
     }
 
+    if let Some(parent_classname) = &storage.direct_parent_class.clone() {
+        populate_data_from_parent_classlike(storage, codebase, parent_classname, symbol_references);
+    }
+
     for direct_parent_interface in &storage.direct_parent_interfaces.clone() {
         populate_interface_data_from_parent_interface(
-            &mut storage,
-            all_classlike_descendants,
+            storage,
             codebase,
             direct_parent_interface,
             symbol_references,
@@ -308,8 +631,7 @@ fn populate_classlike_storage(
 
     for direct_class_interface in &storage.direct_class_interfaces.clone() {
         populate_data_from_implemented_interface(
-            &mut storage,
-            all_classlike_descendants,
+            storage,
             codebase,
             direct_class_interface,
             symbol_references,
@@ -345,24 +667,13 @@ fn populate_classlike_storage(
     }
 
     storage.is_populated = true;
-
-    codebase
-        .classlike_infos
-        .insert(classlike_name.clone(), storage);
 }
 
 fn populate_interface_data_from_parent_or_implemented_interface(
     storage: &mut ClassLikeInfo,
     interface_storage: &ClassLikeInfo,
 ) {
-    storage.constants.extend(
-        interface_storage
-            .constants
-            .iter()
-            .filter(|(k, _)| !storage.constants.contains_key(*k))
-            .map(|v| (v.0.clone(), v.1.clone()))
-            .collect::<FxHashMap<_, _>>(),
-    );
+    inherit_constants_from_parent(storage, interface_storage);
 
     storage
         .invalid_dependencies
@@ -375,18 +686,10 @@ fn populate_interface_data_from_parent_or_implemented_interface(
 
 fn populate_interface_data_from_parent_interface(
     storage: &mut ClassLikeInfo,
-    all_classlike_descendants: &mut FxHashMap<StrId, FxHashSet<StrId>>,
-    codebase: &mut CodebaseInfo,
+    codebase: &CodebaseInfo,
     parent_storage_interface: &StrId,
     symbol_references: &mut SymbolReferences,
 ) {
-    populate_classlike_storage(
-        parent_storage_interface,
-        all_classlike_descendants,
-        codebase,
-        symbol_references,
-    );
-
     symbol_references.add_symbol_reference_to_symbol(storage.name, *parent_storage_interface, true);
 
     let parent_interface_storage = if let Some(parent_interface_storage) =
@@ -411,19 +714,11 @@ fn populate_interface_data_from_parent_interface(
 
 fn populate_data_from_implemented_interface(
     storage: &mut ClassLikeInfo,
-    all_classlike_descendants: &mut FxHashMap<StrId, FxHashSet<StrId>>,
-    codebase: &mut CodebaseInfo,
+    codebase: &CodebaseInfo,
     parent_storage_interface: &StrId,
 
     symbol_references: &mut SymbolReferences,
 ) {
-    populate_classlike_storage(
-        parent_storage_interface,
-        all_classlike_descendants,
-        codebase,
-        symbol_references,
-    );
-
     symbol_references.add_symbol_reference_to_symbol(storage.name, *parent_storage_interface, true);
 
     let implemented_interface_storage = if let Some(implemented_interface_storage) =
@@ -451,18 +746,10 @@ fn populate_data_from_implemented_interface(
 
 fn populate_data_from_parent_classlike(
     storage: &mut ClassLikeInfo,
-    all_classlike_descendants: &mut FxHashMap<StrId, FxHashSet<StrId>>,
-    codebase: &mut CodebaseInfo,
+    codebase: &CodebaseInfo,
     parent_storage_class: &StrId,
     symbol_references: &mut SymbolReferences,
 ) {
-    populate_classlike_storage(
-        parent_storage_class,
-        all_classlike_descendants,
-        codebase,
-        symbol_references,
-    );
-
     symbol_references.add_symbol_reference_to_symbol(storage.name, *parent_storage_class, true);
 
     let parent_storage = codebase.classlike_infos.get(parent_storage_class);
@@ -500,14 +787,7 @@ fn populate_data_from_parent_classlike(
         .used_traits
         .extend(parent_storage.used_traits.clone());
 
-    storage.constants.extend(
-        parent_storage
-            .constants
-            .iter()
-            .filter(|(k, _)| !storage.constants.contains_key(*k))
-            .map(|v| (v.0.clone(), v.1.clone()))
-            .collect::<FxHashMap<_, _>>(),
-    );
+    inherit_constants_from_parent(storage, parent_storage);
 
     storage
         .type_constants
@@ -522,18 +802,11 @@ fn populate_data_from_parent_classlike(
 
 fn populate_data_from_trait(
     storage: &mut ClassLikeInfo,
-    all_classlike_descendants: &mut FxHashMap<StrId, FxHashSet<StrId>>,
-    codebase: &mut CodebaseInfo,
+    codebase: &CodebaseInfo,
     trait_name: &StrId,
     symbol_references: &mut SymbolReferences,
+    trait_users: &mut Vec<(StrId, StrId)>,
 ) {
-    populate_classlike_storage(
-        trait_name,
-        all_classlike_descendants,
-        codebase,
-        symbol_references,
-    );
-
     symbol_references.add_symbol_reference_to_symbol(storage.name, *trait_name, true);
 
     let trait_storage = codebase.classlike_infos.get(trait_name);
@@ -545,19 +818,36 @@ fn populate_data_from_trait(
         return;
     };
 
-    all_classlike_descendants
-        .entry(trait_name.clone())
-        .or_insert_with(FxHashSet::default)
-        .insert(storage.name.clone());
+    trait_users.push((*trait_name, storage.name));
 
     storage
         .all_class_interfaces
         .extend(trait_storage.direct_class_interfaces.clone());
 
+    inherit_constants_from_parent(storage, trait_storage);
     inherit_methods_from_parent(storage, trait_storage, codebase);
     inherit_properties_from_parent(storage, trait_storage);
 }
 
+fn inherit_constants_from_parent(storage: &mut ClassLikeInfo, parent_storage: &ClassLikeInfo) {
+    for (constant_name, constant_info) in &parent_storage.constants {
+        if storage.constants.contains_key(constant_name) {
+            continue;
+        }
+
+        storage
+            .constants
+            .insert(*constant_name, constant_info.clone());
+
+        if let Some(declaring_classlike) = parent_storage.declaring_constant_ids.get(constant_name)
+        {
+            storage
+                .declaring_constant_ids
+                .insert(*constant_name, *declaring_classlike);
+        }
+    }
+}
+
 fn inherit_methods_from_parent(
     storage: &mut ClassLikeInfo,
     parent_storage: &ClassLikeInfo,
@@ -843,3 +1133,6 @@ fn extend_type(
 
     Arc::new(TUnion::new(extended_types))
 }
+
+#[cfg(test)]
+mod tests;
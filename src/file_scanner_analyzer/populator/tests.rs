@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use hakana_aast_helper::name_context::NameContext;
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_reflection_info::ThreadedInterner;
+
+use super::*;
+use crate::file_provider::InMemoryFileProvider;
+use crate::scanner::scan_file;
+
+// enough classlikes per layer for them to be split between threads, plus a trait, an
+// interface hierarchy and an inheritance cycle (D and E) with a class (F) inheriting from it
+const INHERITANCE_FILE: &str = r#"
+interface I {
+    public function i(): void;
+}
+
+interface J extends I {}
+
+trait T {
+    public int $t = 0;
+
+    public function t(): void {}
+}
+
+abstract class A implements J {
+    public function i(): void {}
+
+    public function a(): string {
+        return "";
+    }
+}
+
+class B extends A {
+    use T;
+
+    public function a(): string {
+        return "b";
+    }
+}
+
+class B2 extends A {
+    use T;
+}
+
+final class C extends B {}
+
+final class C2 extends B2 {
+    public function t(): void {}
+}
+
+final class C3 extends B implements I {}
+
+class D extends E {}
+
+class E extends D {}
+
+class F extends D {}
+"#;
+
+fn populate_with_threads(threads: u8) -> (CodebaseInfo, SymbolReferences) {
+    let mut codebase = CodebaseInfo::new();
+    let interner = Arc::new(Mutex::new(Interner::new()));
+
+    let mut threaded_interner = ThreadedInterner::new(interner.clone());
+    let empty_name_context = NameContext::new(&mut threaded_interner);
+
+    scan_file(
+        &"input.hack".to_string(),
+        &"".to_string(),
+        &Config::new("".to_string(), FxHashSet::default()),
+        &mut codebase,
+        &mut threaded_interner,
+        empty_name_context,
+        true,
+        true,
+        Verbosity::Quiet,
+        &InMemoryFileProvider::new(FxHashMap::from_iter([(
+            "input.hack".to_string(),
+            INHERITANCE_FILE.to_string(),
+        )])),
+    )
+    .unwrap();
+
+    drop(threaded_interner);
+
+    let interner = match Arc::try_unwrap(interner) {
+        Ok(mutex) => mutex.into_inner().unwrap(),
+        Err(_) => panic!("There's a lock somewhere"),
+    };
+
+    let mut symbol_references = SymbolReferences::new();
+
+    populate_codebase(&mut codebase, &interner, &mut symbol_references, threads);
+
+    (codebase, symbol_references)
+}
+
+// the populated classlike infos, in a form that doesn't depend on hash iteration order
+fn get_classlike_summaries(codebase: &CodebaseInfo) -> BTreeMap<StrId, Vec<String>> {
+    fn sorted<T: Ord>(items: impl IntoIterator<Item = T>) -> BTreeSet<T> {
+        items.into_iter().collect()
+    }
+
+    codebase
+        .classlike_infos
+        .iter()
+        .map(|(name, storage)| {
+            (
+                *name,
+                vec![
+                    format!("{:?}", storage.is_populated),
+                    format!("{:?}", sorted(storage.all_parent_classes.iter())),
+                    format!("{:?}", sorted(storage.all_class_interfaces.iter())),
+                    format!("{:?}", sorted(storage.all_parent_interfaces.iter())),
+                    format!("{:?}", sorted(storage.used_traits.iter())),
+                    format!("{:?}", sorted(storage.declaring_method_ids.iter())),
+                    format!("{:?}", sorted(storage.appearing_method_ids.iter())),
+                    format!("{:?}", sorted(storage.inheritable_method_ids.iter())),
+                    format!(
+                        "{:?}",
+                        sorted(
+                            storage
+                                .overridden_method_ids
+                                .iter()
+                                .map(|(k, v)| (k, sorted(v.iter())))
+                        )
+                    ),
+                    format!(
+                        "{:?}",
+                        sorted(
+                            storage
+                                .potential_declaring_method_ids
+                                .iter()
+                                .map(|(k, v)| (k, sorted(v.iter())))
+                        )
+                    ),
+                    format!("{:?}", sorted(storage.declaring_property_ids.iter())),
+                    format!("{:?}", sorted(storage.appearing_property_ids.iter())),
+                    format!("{:?}", sorted(storage.inheritable_property_ids.iter())),
+                    format!("{:?}", sorted(storage.declaring_constant_ids.iter())),
+                    format!("{:?}", sorted(storage.invalid_dependencies.iter())),
+                    format!(
+                        "{:?}",
+                        storage
+                            .child_classlikes
+                            .as_ref()
+                            .map(|child_classlikes| sorted(child_classlikes.iter()))
+                    ),
+                ],
+            )
+        })
+        .collect()
+}
+
+fn get_symbol_reference_summaries(symbol_references: &SymbolReferences) -> Vec<BTreeSet<String>> {
+    let mut summaries = [
+        &symbol_references.symbol_references_to_symbols,
+        &symbol_references.symbol_references_to_symbols_in_signature,
+        &symbol_references.symbol_references_to_overridden_members,
+    ]
+    .into_iter()
+    .map(|references| {
+        references
+            .iter()
+            .flat_map(|(k, v)| v.iter().map(move |v| format!("{:?} -> {:?}", k, v)))
+            .collect::<BTreeSet<_>>()
+    })
+    .collect::<Vec<_>>();
+
+    summaries.push(
+        symbol_references
+            .functionlike_references_to_functionlike_returns
+            .iter()
+            .flat_map(|(k, v)| v.iter().map(move |v| format!("{:?} -> {:?}", k, v)))
+            .collect(),
+    );
+
+    summaries
+}
+
+#[test]
+fn populates_the_same_with_any_thread_count() {
+    let (single_threaded_codebase, single_threaded_references) = populate_with_threads(1);
+    let (multi_threaded_codebase, multi_threaded_references) = populate_with_threads(4);
+
+    assert!(single_threaded_codebase
+        .classlike_infos
+        .values()
+        .all(|storage| storage.is_populated));
+
+    assert_eq!(
+        get_classlike_summaries(&single_threaded_codebase),
+        get_classlike_summaries(&multi_threaded_codebase)
+    );
+
+    assert_eq!(
+        get_symbol_reference_summaries(&single_threaded_references),
+        get_symbol_reference_summaries(&multi_threaded_references)
+    );
+}
@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_analyzer::progress_reporter::{AnalysisPhase, ProgressReporter};
+use indicatif::{ProgressBar, ProgressStyle};
+
+// The terminal progress bar the CLI shows for each phase
+#[derive(Default)]
+pub struct ProgressBarReporter {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressReporter for ProgressBarReporter {
+    fn on_phase_start(&self, _phase: AnalysisPhase, total_files: u64) {
+        let pb = ProgressBar::new(total_files);
+        let sty = ProgressStyle::with_template("{bar:40.green/yellow} {pos:>7}/{len:7}").unwrap();
+        pb.set_style(sty);
+
+        *self.bar.lock().unwrap() = Some(pb);
+    }
+
+    fn on_file_done(&self, files_done: u64, _total_files: u64) {
+        if let Some(bar) = &*self.bar.lock().unwrap() {
+            bar.set_position(files_done);
+        }
+    }
+
+    fn on_phase_end(&self, _phase: AnalysisPhase) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+pub(crate) fn get_progress_reporter(
+    config: &Config,
+    verbosity: Verbosity,
+) -> Option<Arc<dyn ProgressReporter>> {
+    if let Some(progress_reporter) = &config.progress_reporter {
+        Some(progress_reporter.clone())
+    } else if matches!(verbosity, Verbosity::Simple) {
+        Some(Arc::new(ProgressBarReporter::default()))
+    } else {
+        None
+    }
+}
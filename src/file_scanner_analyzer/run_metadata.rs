@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::Path;
+
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::analysis_result::RunMetadata;
+use indexmap::IndexMap;
+
+use crate::analysis_cache::get_config_fingerprint;
+use crate::file_cache_provider::FileStatus;
+
+pub(crate) fn get_run_metadata(
+    config: &Config,
+    build_checksum: &str,
+    file_statuses: Option<&IndexMap<String, FileStatus>>,
+) -> RunMetadata {
+    RunMetadata {
+        hakana_version: env!("CARGO_PKG_VERSION").to_string(),
+        build_checksum: build_checksum.to_string(),
+        config_hash: format!("{:016x}", get_config_fingerprint(config)),
+        codebase_checksum: file_statuses.map(get_codebase_checksum),
+        revision: get_revision(&config.root_dir),
+    }
+}
+
+fn get_codebase_checksum(file_statuses: &IndexMap<String, FileStatus>) -> String {
+    let mut file_hashes = file_statuses
+        .iter()
+        .filter_map(|(file_path, status)| match status {
+            FileStatus::Unchanged(contents_hash, _)
+            | FileStatus::Added(contents_hash, _)
+            | FileStatus::Modified(contents_hash, _) => Some((file_path, contents_hash)),
+            FileStatus::Deleted => None,
+        })
+        .collect::<Vec<_>>();
+
+    file_hashes.sort();
+
+    let mut checksum_input = vec![];
+
+    for (file_path, contents_hash) in file_hashes {
+        checksum_input.extend(file_path.as_bytes());
+        checksum_input.extend(contents_hash.to_le_bytes());
+    }
+
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&checksum_input))
+}
+
+// Read from .git directly, so that git doesn't need to be installed
+fn get_revision(root_dir: &String) -> Option<String> {
+    let git_dir = Path::new(root_dir).join(".git");
+
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    let ref_name = if let Some(ref_name) = head.strip_prefix("ref: ") {
+        ref_name
+    } else {
+        // a detached HEAD has the commit itself
+        return Some(head.to_string());
+    };
+
+    if let Ok(revision) = fs::read_to_string(git_dir.join(ref_name)) {
+        return Some(revision.trim().to_string());
+    }
+
+    // refs that haven't been updated since the last gc only exist here
+    fs::read_to_string(git_dir.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| match line.split_once(' ') {
+            Some((revision, name)) if name == ref_name => Some(revision.to_string()),
+            _ => None,
+        })
+}
@@ -1,5 +1,4 @@
 use std::fs;
-use std::io;
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
@@ -7,22 +6,29 @@ use std::sync::Mutex;
 use std::time::Instant;
 
 use super::find_files_in_dir;
-use super::update_progressbar;
 use super::HhiAsset;
 use super::HslAsset;
 use crate::ast_differ;
 use crate::cache::load_cached_aast_names;
 use crate::cache::load_cached_codebase;
 use crate::cache::load_cached_symbols;
+use crate::cache::remove_file_symbols;
+use crate::cache::restore_stale_file_symbols;
+use crate::error::HakanaError;
 use crate::file_cache_provider;
 use crate::file_cache_provider::FileStatus;
+use crate::file_cache_provider::HttpCacheProvider;
+use crate::file_provider::FileProvider;
 use crate::get_aast_for_path;
 use crate::get_relative_path;
+use crate::progress_bar::get_progress_reporter;
+use crate::source_map::SourceMap;
 use ast_differ::get_diff;
 use hakana_aast_helper::name_context::NameContext;
 use hakana_aast_helper::ParserError;
 use hakana_analyzer::config::Config;
 use hakana_analyzer::config::Verbosity;
+use hakana_analyzer::progress_reporter::AnalysisPhase;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
 use hakana_reflection_info::diff::CodebaseDiff;
 use hakana_reflection_info::FileSource;
@@ -30,8 +36,6 @@ use hakana_reflection_info::Interner;
 use hakana_reflection_info::StrId;
 use hakana_reflection_info::ThreadedInterner;
 use indexmap::IndexMap;
-use indicatif::ProgressBar;
-use indicatif::ProgressStyle;
 use rustc_hash::FxHashMap;
 use rustc_hash::FxHashSet;
 
@@ -53,7 +57,8 @@ pub(crate) fn scan_files(
     verbosity: Verbosity,
     build_checksum: &str,
     starter_data: Option<(CodebaseInfo, Interner)>,
-) -> io::Result<ScanFilesResult> {
+    file_provider: &Arc<dyn FileProvider>,
+) -> Result<ScanFilesResult, HakanaError> {
     if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
         println!("{:#?}", scan_dirs);
     }
@@ -83,13 +88,21 @@ pub(crate) fn scan_files(
 
     if include_core_libs {
         // add HHVM libs
-        for file in HhiAsset::iter() {
-            files_to_scan.insert(file.to_string(), 0);
+        if let Some(hhi_dir) = &config.hhi_dir {
+            files_to_scan.extend(find_files_in_dir(hhi_dir, config, &mut vec![]));
+        } else {
+            for file in HhiAsset::iter() {
+                files_to_scan.insert(file.to_string(), 0);
+            }
         }
 
         // add HSL
-        for file in HslAsset::iter() {
-            files_to_scan.insert(file.to_string(), 0);
+        if let Some(hsl_dir) = &config.hsl_dir {
+            files_to_scan.extend(find_files_in_dir(hsl_dir, config, &mut vec![]));
+        } else {
+            for file in HslAsset::iter() {
+                files_to_scan.insert(file.to_string(), 0);
+            }
         }
     }
 
@@ -107,6 +120,24 @@ pub(crate) fn scan_files(
         files_to_scan.extend(find_files_in_dir(scan_dir, config, files_to_analyze));
     }
 
+    let overlaid_paths = file_provider.get_overlaid_paths();
+
+    // unsaved editor buffers might not exist on disk yet
+    for overlaid_path in &overlaid_paths {
+        if !files_to_scan.contains_key(overlaid_path)
+            && (overlaid_path.ends_with(".hack") || overlaid_path.ends_with(".php"))
+            && scan_dirs
+                .iter()
+                .any(|scan_dir| overlaid_path.starts_with(scan_dir))
+        {
+            files_to_scan.insert(overlaid_path.clone(), 0);
+            files_to_analyze.push(overlaid_path.clone());
+        }
+    }
+
+    // the caches must only ever reflect what's on disk
+    let can_write_cache = overlaid_paths.is_empty();
+
     let elapsed = now.elapsed();
 
     if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
@@ -131,13 +162,57 @@ pub(crate) fn scan_files(
             use_codebase_cache = false;
         }
 
-        if !use_codebase_cache {
+        // another machine may already have scanned this build
+        if !use_codebase_cache && can_write_cache {
+            if let Some(remote_cache_url) = &config.remote_cache_url {
+                use_codebase_cache = file_cache_provider::fetch_remote_codebase_cache(
+                    &HttpCacheProvider::new(remote_cache_url.clone()),
+                    cache_dir,
+                    build_checksum,
+                    verbosity,
+                );
+
+                if use_codebase_cache && !matches!(verbosity, Verbosity::Quiet) {
+                    println!("Fetched codebase cache from {}", remote_cache_url);
+                }
+            }
+        }
+
+        if !use_codebase_cache && can_write_cache {
             if Path::new(&codebase_path_unwrapped).exists() {
                 fs::remove_file(&codebase_path_unwrapped).unwrap();
             }
         }
     }
 
+    let now = Instant::now();
+
+    let mut resolved_names = FxHashMap::default();
+    let mut loaded_codebase_cache = false;
+
+    if let (Some(symbols_path), Some(codebase_path), Some(aast_names_path)) =
+        (&symbols_path, &codebase_path, &aast_names_path)
+    {
+        if use_codebase_cache {
+            // the codebase refers to interned strings, so the caches are only usable together
+            let cached_data = load_cached_symbols(symbols_path, verbosity).and_then(|i| {
+                load_cached_codebase(codebase_path, verbosity).and_then(|c| {
+                    load_cached_aast_names(aast_names_path, verbosity).map(|r| (i, c, r))
+                })
+            });
+
+            if let Some((cached_interner, cached_codebase, cached_resolved_names)) = cached_data {
+                interner = cached_interner;
+                codebase = cached_codebase;
+                resolved_names = cached_resolved_names;
+                loaded_codebase_cache = true;
+            } else {
+                // fall back to scanning everything from scratch
+                use_codebase_cache = false;
+            }
+        }
+    }
+
     let file_update_hashes = if let Some(cache_dir) = cache_dir {
         if use_codebase_cache {
             file_cache_provider::get_file_manifest(cache_dir).unwrap_or(FxHashMap::default())
@@ -148,13 +223,8 @@ pub(crate) fn scan_files(
         FxHashMap::default()
     };
 
-    let file_statuses = file_cache_provider::get_file_diff(&files_to_scan, file_update_hashes);
-
-    let now = Instant::now();
-
-    if let Some(symbols_path) = &symbols_path {
-        load_cached_symbols(symbols_path, use_codebase_cache, &mut interner, verbosity);
-    }
+    let file_statuses =
+        file_cache_provider::get_file_diff(&files_to_scan, file_update_hashes, &**file_provider);
 
     let changed_files = file_statuses
         .iter()
@@ -168,26 +238,14 @@ pub(crate) fn scan_files(
         })
         .collect::<FxHashSet<_>>();
 
-    // this needs to come after we've loaded interned strings
-    if let Some(codebase_path) = &codebase_path {
-        load_cached_codebase(
-            codebase_path,
-            use_codebase_cache,
+    let mut previous_codebase = CodebaseInfo::new();
+
+    if loaded_codebase_cache {
+        remove_file_symbols(
             &mut codebase,
+            &mut previous_codebase,
             &interner,
             &changed_files,
-            verbosity,
-        );
-    }
-
-    let mut resolved_names = FxHashMap::default();
-
-    if let Some(aast_names_path) = &aast_names_path {
-        load_cached_aast_names(
-            aast_names_path,
-            use_codebase_cache,
-            &mut resolved_names,
-            verbosity,
         );
     }
 
@@ -238,21 +296,20 @@ pub(crate) fn scan_files(
 
     let interner = Arc::new(Mutex::new(interner));
     let resolved_names = Arc::new(Mutex::new(resolved_names));
+    let unparseable_files = Arc::new(Mutex::new(vec![]));
 
     let has_new_files = files_to_scan.len() > 0;
 
     if files_to_scan.len() > 0 {
         let now = Instant::now();
 
-        let bar = if matches!(verbosity, Verbosity::Simple) {
-            let pb = ProgressBar::new(files_to_scan.len() as u64);
-            let sty =
-                ProgressStyle::with_template("{bar:40.green/yellow} {pos:>7}/{len:7}").unwrap();
-            pb.set_style(sty);
-            Some(Arc::new(pb))
-        } else {
-            None
-        };
+        let total_file_count = files_to_scan.len() as u64;
+
+        let progress_reporter = get_progress_reporter(&config, verbosity);
+
+        if let Some(progress_reporter) = &progress_reporter {
+            progress_reporter.on_phase_start(AnalysisPhase::Scanning, total_file_count);
+        }
 
         let files_processed: Arc<Mutex<u64>> = Arc::new(Mutex::new(0));
 
@@ -293,18 +350,20 @@ pub(crate) fn scan_files(
                 let file_resolved_names = if let Ok(file_resolved_names) = scan_file(
                     str_path,
                     &config.root_dir,
-                    &config.all_custom_issues,
+                    &config,
                     &mut new_codebase,
                     &mut new_interner,
                     empty_name_context.clone(),
                     analyze_map.contains(*str_path),
                     !test_patterns.iter().any(|p| p.matches(&str_path)),
                     verbosity,
+                    &**file_provider,
                 ) {
                     file_resolved_names
                 } else {
                     let str_path = get_relative_path(str_path, &config.root_dir);
                     new_interner.intern(str_path.clone());
+                    unparseable_files.lock().unwrap().push(str_path);
                     FxHashMap::default()
                 };
 
@@ -313,7 +372,9 @@ pub(crate) fn scan_files(
                     .unwrap()
                     .insert((**str_path).clone(), file_resolved_names);
 
-                update_progressbar(i as u64, bar.clone());
+                if let Some(progress_reporter) = &progress_reporter {
+                    progress_reporter.on_file_done(i as u64 + 1, total_file_count);
+                }
             }
 
             if config.ast_diff {
@@ -336,7 +397,7 @@ pub(crate) fn scan_files(
 
                 let codebases = thread_codebases.clone();
 
-                let bar = bar.clone();
+                let progress_reporter = progress_reporter.clone();
                 let files_processed = files_processed.clone();
 
                 let analyze_map = files_to_analyze
@@ -347,9 +408,11 @@ pub(crate) fn scan_files(
                 let interner = interner.clone();
 
                 let resolved_names = resolved_names.clone();
+                let unparseable_files = unparseable_files.clone();
 
                 let config = config.clone();
                 let test_patterns = test_patterns.clone();
+                let file_provider = file_provider.clone();
 
                 let handle = std::thread::spawn(move || {
                     let mut new_codebase = CodebaseInfo::new();
@@ -361,25 +424,29 @@ pub(crate) fn scan_files(
                         if let Ok(file_resolved_names) = scan_file(
                             str_path,
                             &root_dir_c,
-                            &config.all_custom_issues,
+                            &config,
                             &mut new_codebase,
                             &mut new_interner,
                             empty_name_context.clone(),
                             analyze_map.contains(str_path),
                             !test_patterns.iter().any(|p| p.matches(&str_path)),
                             verbosity,
+                            &*file_provider,
                         ) {
                             local_resolved_names.insert((*str_path).clone(), file_resolved_names);
                         } else {
                             local_resolved_names.insert((*str_path).clone(), FxHashMap::default());
                             let str_path = get_relative_path(str_path, &root_dir_c);
                             new_interner.intern(str_path.clone());
+                            unparseable_files.lock().unwrap().push(str_path);
                         };
 
                         let mut tally = files_processed.lock().unwrap();
                         *tally += 1;
 
-                        update_progressbar(*tally, bar.clone());
+                        if let Some(progress_reporter) = &progress_reporter {
+                            progress_reporter.on_file_done(*tally, total_file_count);
+                        }
                     }
 
                     resolved_names.lock().unwrap().extend(local_resolved_names);
@@ -406,8 +473,8 @@ pub(crate) fn scan_files(
             }
         }
 
-        if let Some(bar) = &bar {
-            bar.finish_and_clear();
+        if let Some(progress_reporter) = &progress_reporter {
+            progress_reporter.on_phase_end(AnalysisPhase::Scanning);
         }
 
         let elapsed = now.elapsed();
@@ -419,12 +486,31 @@ pub(crate) fn scan_files(
 
     let interner = Arc::try_unwrap(interner).unwrap().into_inner().unwrap();
 
+    let unparseable_files = Arc::try_unwrap(unparseable_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+
+    for str_path in unparseable_files {
+        if let Some(file_id) = interner.get(&str_path) {
+            if let Some(file_storage) = updated_files.remove(&file_id) {
+                restore_stale_file_symbols(
+                    &mut codebase,
+                    &mut previous_codebase,
+                    file_id,
+                    file_storage,
+                    &str_path,
+                );
+            }
+        }
+    }
+
     let resolved_names = Arc::try_unwrap(resolved_names)
         .unwrap()
         .into_inner()
         .unwrap();
 
-    if has_new_files {
+    if has_new_files && can_write_cache {
         if let Some(codebase_path) = codebase_path {
             let mut codebase_file = fs::File::create(&codebase_path).unwrap();
             let serialized_codebase = bincode::serialize(&codebase).unwrap();
@@ -456,19 +542,20 @@ pub(crate) fn scan_files(
 pub(crate) fn scan_file(
     target_file: &String,
     root_dir: &String,
-    all_custom_issues: &FxHashSet<String>,
+    config: &Config,
     codebase: &mut CodebaseInfo,
     interner: &mut ThreadedInterner,
     empty_name_context: NameContext,
     user_defined: bool,
     is_production_code: bool,
     verbosity: Verbosity,
+    file_provider: &dyn FileProvider,
 ) -> Result<FxHashMap<usize, StrId>, ParserError> {
     if matches!(verbosity, Verbosity::Debugging | Verbosity::DebuggingByLine) {
         println!("scanning {}", &target_file);
     }
 
-    let aast = get_aast_for_path(&target_file, root_dir, None);
+    let aast = get_aast_for_path(&target_file, root_dir, None, file_provider);
 
     let aast = match aast {
         Ok(aast) => aast,
@@ -481,6 +568,15 @@ pub(crate) fn scan_file(
 
     let interned_file_path = interner.intern(target_name.clone());
 
+    // so that issues can be reported at the files generated code came from
+    if config.report_generated_issues_at_origin {
+        if let Some(source_map) = SourceMap::from_comments(&aast.1.comments) {
+            for origin_path in source_map.get_origin_paths() {
+                interner.intern(origin_path.clone());
+            }
+        }
+    }
+
     let (resolved_names, uses) =
         hakana_aast_helper::scope_names(&aast.0, interner, empty_name_context);
 
@@ -489,9 +585,13 @@ pub(crate) fn scan_file(
         &resolved_names,
         interner,
         codebase,
-        all_custom_issues,
+        &config.all_custom_issues,
         FileSource {
             is_production_code,
+            is_generated: config.is_generated_file(&target_name, &aast.1.comments),
+            issue_profile: None,
+            regeneration_command: None,
+            literal_collection_thresholds: config.literal_collection_thresholds,
             file_path_actual: target_name.clone(),
             file_path: interned_file_path,
             hh_fixmes: aast.1.fixmes,
@@ -0,0 +1,105 @@
+use hakana_reflection_info::{analysis_result::AnalysisResult, Interner};
+use oxidized::{prim_defs::Comment, tast::Pos};
+
+// Maps lines in a generated file back to the template lines they came from.
+// Codegen emits `// #line <line> "<path>"` comments, and each one applies to
+// the lines that follow it, up until the next such comment.
+pub(crate) struct SourceMap {
+    // (line of the comment, origin path, origin line of the following line)
+    entries: Vec<(usize, String, usize)>,
+}
+
+impl SourceMap {
+    pub(crate) fn from_comments(comments: &Vec<(Pos, Comment)>) -> Option<Self> {
+        let mut entries = vec![];
+
+        for (pos, comment) in comments {
+            if let Comment::CmtLine(text) = comment {
+                if let Some((origin_path, origin_line)) = parse_line_directive(text) {
+                    entries.push((pos.line(), origin_path, origin_line));
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(Self { entries })
+        }
+    }
+
+    pub(crate) fn get_origin_paths(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(_, origin_path, _)| origin_path)
+    }
+
+    pub(crate) fn get_origin(&self, line: usize) -> Option<(&String, usize)> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(comment_line, _, _)| *comment_line < line)
+            .map(|(comment_line, origin_path, origin_line)| {
+                (origin_path, origin_line + (line - comment_line - 1))
+            })
+    }
+
+    // Moves issues emitted for the generated file onto the lines they originated from.
+    // Origin paths are interned when the generated file is scanned.
+    pub(crate) fn remap_issues(
+        &self,
+        file_path: &String,
+        interner: &Interner,
+        analysis_result: &mut AnalysisResult,
+    ) {
+        let issues = if let Some(issues) = analysis_result.emitted_issues.remove(file_path) {
+            issues
+        } else {
+            return;
+        };
+
+        for mut issue in issues {
+            let origin =
+                self.get_origin(issue.pos.start_line)
+                    .and_then(|(origin_path, origin_line)| {
+                        interner
+                            .get(origin_path)
+                            .map(|origin_path_id| (origin_path, origin_path_id, origin_line))
+                    });
+
+            let target_path = if let Some((origin_path, origin_path_id, origin_line)) = origin {
+                issue.pos.file_path = origin_path_id;
+                issue.pos.end_line = origin_line + (issue.pos.end_line - issue.pos.start_line);
+                issue.pos.start_line = origin_line;
+                // the edits are in terms of the generated file
+                issue.quickfixes.clear();
+                origin_path.clone()
+            } else {
+                file_path.clone()
+            };
+
+            analysis_result
+                .emitted_issues
+                .entry(target_path)
+                .or_insert_with(Vec::new)
+                .push(issue);
+        }
+    }
+}
+
+fn parse_line_directive(text: &str) -> Option<(String, usize)> {
+    let text = text.trim().trim_start_matches('/').trim();
+
+    let rest = text.strip_prefix("#line ")?.trim();
+    let (origin_line, origin_path) = rest.split_once(' ')?;
+
+    let origin_line = origin_line.parse::<usize>().ok()?;
+    let origin_path = origin_path.trim().trim_matches('"');
+
+    if origin_path.is_empty() {
+        return None;
+    }
+
+    Some((origin_path.to_string(), origin_line))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,69 @@
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::data_flow::graph::GraphKind;
+use hakana_reflection_info::issue::{Issue, IssueKind};
+use hakana_reflection_info::symbol_references::SymbolReferences;
+use hakana_reflection_info::StrId;
+
+use super::*;
+
+fn get_issue(file_path: StrId, line: usize) -> Issue {
+    Issue::new(
+        IssueKind::InvalidReturnStatement,
+        "Invalid return".to_string(),
+        HPos {
+            file_path,
+            start_offset: 0,
+            end_offset: 1,
+            start_line: line,
+            end_line: line,
+            start_column: 1,
+            end_column: 2,
+            insertion_start: None,
+        },
+        &None,
+    )
+}
+
+fn remap_issue(interner: &Interner, generated_path: StrId, line: usize) -> (String, Issue) {
+    let source_map = SourceMap {
+        entries: vec![(3, "templates/a.hack.tpl".to_string(), 10)],
+    };
+
+    let mut analysis_result = AnalysisResult::new(GraphKind::FunctionBody, SymbolReferences::new());
+    analysis_result.emitted_issues.insert(
+        "gen.hack".to_string(),
+        vec![get_issue(generated_path, line)],
+    );
+
+    source_map.remap_issues(&"gen.hack".to_string(), interner, &mut analysis_result);
+
+    let (path, mut issues) = analysis_result.emitted_issues.into_iter().next().unwrap();
+
+    (path, issues.pop().unwrap())
+}
+
+#[test]
+fn remaps_issue_path_and_line() {
+    let mut interner = Interner::default();
+    let generated_path = interner.intern("gen.hack".to_string());
+    let origin_path = interner.intern("templates/a.hack.tpl".to_string());
+
+    let (path, issue) = remap_issue(&interner, generated_path, 5);
+
+    assert_eq!("templates/a.hack.tpl", path);
+    assert_eq!(origin_path, issue.pos.file_path);
+    assert_eq!(11, issue.pos.start_line);
+}
+
+#[test]
+fn keeps_issues_before_first_directive() {
+    let mut interner = Interner::default();
+    let generated_path = interner.intern("gen.hack".to_string());
+    interner.intern("templates/a.hack.tpl".to_string());
+
+    let (path, issue) = remap_issue(&interner, generated_path, 2);
+
+    assert_eq!("gen.hack", path);
+    assert_eq!(generated_path, issue.pos.file_path);
+    assert_eq!(2, issue.pos.start_line);
+}
@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use hakana_analyzer::config::{Config, Verbosity};
+use oxidized::aast;
+use oxidized::aast_visitor::{visit, AstParams, Visitor};
+use oxidized::ast_defs::{Id, Pos};
+
+use crate::error::HakanaError;
+use crate::file_provider::{DiskFileProvider, FileProvider};
+use crate::get_aast_for_path;
+use crate::scanner::{scan_files, ScanFilesResult};
+
+// Writes a .hhi stub for every Hack file in config.root_dir (e.g. a vendor directory)
+// to the same relative path in output_dir. Stubs keep every declaration as written,
+// but functions and methods lose their bodies, so projects can include third-party
+// types without analyzing their implementations. Returns the number of stubs written.
+pub fn generate_stubs(
+    config: Arc<Config>,
+    output_dir: &String,
+    threads: u8,
+    verbosity: Verbosity,
+) -> Result<usize, HakanaError> {
+    let file_provider: Arc<dyn FileProvider> = Arc::new(DiskFileProvider);
+
+    // only declarations are scanned, and nothing is populated or analyzed
+    let ScanFilesResult {
+        codebase, interner, ..
+    } = scan_files(
+        &vec![config.root_dir.clone()],
+        false,
+        None,
+        &mut vec![],
+        &config,
+        threads,
+        verbosity,
+        "",
+        None,
+        &file_provider,
+    )?;
+
+    let mut file_paths = codebase
+        .files
+        .keys()
+        .map(|file_id| interner.lookup(file_id))
+        .collect::<Vec<_>>();
+
+    file_paths.sort();
+
+    let mut stub_count = 0;
+
+    for file_path in file_paths {
+        // builtin files aren't in the root, and files that don't parse can't be stubbed
+        let (program, _, file_contents) = if let Ok(aast) = get_aast_for_path(
+            &format!("{}/{}", config.root_dir, file_path),
+            &config.root_dir,
+            None,
+            &*file_provider,
+        ) {
+            aast
+        } else {
+            continue;
+        };
+
+        let mut scanner = Scanner {
+            file_contents: &file_contents,
+            body_offsets: vec![],
+        };
+
+        for def in &program {
+            visit(&mut scanner, &mut (), def).unwrap();
+        }
+
+        let mut body_offsets = scanner.body_offsets;
+
+        body_offsets.sort();
+
+        let stub_path = Path::new(output_dir).join(Path::new(file_path).with_extension("hhi"));
+
+        if let Some(stub_dir) = stub_path.parent() {
+            fs::create_dir_all(stub_dir)?;
+        }
+
+        fs::write(&stub_path, get_stub_contents(&file_contents, &body_offsets))?;
+
+        stub_count += 1;
+    }
+
+    Ok(stub_count)
+}
+
+struct Scanner<'a> {
+    file_contents: &'a str,
+    body_offsets: Vec<(usize, usize)>,
+}
+
+impl<'ast> Visitor<'ast> for Scanner<'_> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    // closures only exist inside bodies, so there's no need to recurse into either
+    fn visit_fun_def(&mut self, _: &mut (), f: &aast::FunDef<(), ()>) -> Result<(), ()> {
+        let signature_end = get_signature_end(
+            &f.name,
+            &f.fun.tparams,
+            &f.fun.params,
+            &f.fun.ret,
+            &f.fun.ctxs,
+            &f.fun.where_constraints,
+        );
+
+        if let Some(body_offsets) = get_body_offsets(self.file_contents, &f.fun.span, signature_end)
+        {
+            self.body_offsets.push(body_offsets);
+        }
+
+        Ok(())
+    }
+
+    fn visit_method_(&mut self, _: &mut (), m: &aast::Method_<(), ()>) -> Result<(), ()> {
+        let signature_end = get_signature_end(
+            &m.name,
+            &m.tparams,
+            &m.params,
+            &m.ret,
+            &m.ctxs,
+            &m.where_constraints,
+        );
+
+        if let Some(body_offsets) = get_body_offsets(self.file_contents, &m.span, signature_end) {
+            self.body_offsets.push(body_offsets);
+        }
+
+        Ok(())
+    }
+}
+
+// The end of the last part of a function's signature that the AST knows about
+fn get_signature_end(
+    name: &Id,
+    tparams: &Vec<aast::Tparam<(), ()>>,
+    params: &Vec<aast::FunParam<(), ()>>,
+    ret: &aast::TypeHint<()>,
+    ctxs: &Option<aast::Contexts>,
+    where_constraints: &Vec<aast::WhereConstraintHint>,
+) -> usize {
+    let mut signature_end = name.0.end_offset();
+
+    for tparam in tparams {
+        signature_end = signature_end.max(tparam.name.0.end_offset());
+
+        for (_, constraint) in &tparam.constraints {
+            signature_end = signature_end.max(constraint.0.end_offset());
+        }
+    }
+
+    for param in params {
+        signature_end = signature_end.max(param.pos.end_offset());
+
+        if let Some(expr) = &param.expr {
+            signature_end = signature_end.max(expr.1.end_offset());
+        }
+
+        if let Some(param_hint) = &param.type_hint.1 {
+            signature_end = signature_end.max(param_hint.0.end_offset());
+        }
+    }
+
+    if let Some(ret_hint) = &ret.1 {
+        signature_end = signature_end.max(ret_hint.0.end_offset());
+    }
+
+    if let Some(ctxs) = ctxs {
+        signature_end = signature_end.max(ctxs.0.end_offset());
+    }
+
+    for where_constraint in where_constraints {
+        signature_end = signature_end.max(where_constraint.2 .0.end_offset());
+    }
+
+    signature_end
+}
+
+// A body runs from the first brace after the signature to the end of the definition.
+// Only whitespace, comments and punctuation can come between the signature's end and
+// that brace. Abstract and interface methods, which end in a semicolon, don't have a body.
+fn get_body_offsets(
+    file_contents: &str,
+    span: &Pos,
+    signature_end: usize,
+) -> Option<(usize, usize)> {
+    let contents = file_contents.as_bytes();
+    let body_end = span.end_offset();
+
+    if body_end > contents.len() || signature_end >= body_end || contents[body_end - 1] != b'}' {
+        return None;
+    }
+
+    let mut offset = signature_end;
+
+    while offset < body_end {
+        match (contents[offset], contents.get(offset + 1)) {
+            (b'{', _) => return Some((offset, body_end)),
+            (b';', _) => return None,
+            (b'/', Some(b'*')) => {
+                offset = file_contents[offset + 2..]
+                    .find("*/")
+                    .map_or(body_end, |comment_end| offset + 2 + comment_end + 2);
+            }
+            (b'#', _) | (b'/', Some(b'/')) => {
+                offset = file_contents[offset..]
+                    .find('\n')
+                    .map_or(body_end, |line_end| offset + line_end + 1);
+            }
+            _ => offset += 1,
+        }
+    }
+
+    None
+}
+
+fn get_stub_contents(file_contents: &str, body_offsets: &Vec<(usize, usize)>) -> String {
+    let mut stub_contents = String::new();
+    let mut last_offset = 0;
+
+    for (start_offset, end_offset) in body_offsets {
+        stub_contents += &file_contents[last_offset..*start_offset];
+        stub_contents += "{}";
+        last_offset = *end_offset;
+    }
+
+    stub_contents += &file_contents[last_offset..];
+
+    stub_contents
+}
+
+#[cfg(test)]
+mod tests;
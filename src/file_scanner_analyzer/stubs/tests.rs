@@ -0,0 +1,73 @@
+use rustc_hash::FxHashSet;
+
+use super::*;
+
+#[test]
+fn strips_function_and_method_bodies() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-stubs-{}", std::process::id()));
+    let vendor_dir = root_dir.join("vendor");
+    let output_dir = root_dir.join("stubs");
+    fs::create_dir_all(vendor_dir.join("lib")).unwrap();
+
+    fs::write(
+        vendor_dir.join("a.hack"),
+        r#"function foo(string $s = "{"): string {
+    $f = () ==> { return $s; };
+    return $f();
+}
+
+abstract class A {
+    // a { in a comment
+    public function bar<T>(T $t): T where T as arraykey /* { */ {
+        return $t;
+    }
+
+    abstract public function baz(): void;
+}
+"#,
+    )
+    .unwrap();
+
+    fs::write(
+        vendor_dir.join("lib/b.hack"),
+        "interface B {\n    public function qux(): void;\n}\n",
+    )
+    .unwrap();
+
+    let config = Config::new(
+        vendor_dir.to_string_lossy().to_string(),
+        FxHashSet::default(),
+    );
+
+    let stub_count = generate_stubs(
+        Arc::new(config),
+        &output_dir.to_string_lossy().to_string(),
+        1,
+        Verbosity::Quiet,
+    );
+
+    let a_stub = fs::read_to_string(output_dir.join("a.hhi"));
+    let b_stub = fs::read_to_string(output_dir.join("lib/b.hhi"));
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    assert_eq!(2, stub_count.unwrap());
+
+    assert_eq!(
+        r#"function foo(string $s = "{"): string {}
+
+abstract class A {
+    // a { in a comment
+    public function bar<T>(T $t): T where T as arraykey /* { */ {}
+
+    abstract public function baz(): void;
+}
+"#,
+        a_stub.unwrap()
+    );
+
+    assert_eq!(
+        "interface B {\n    public function qux(): void;\n}\n",
+        b_stub.unwrap()
+    );
+}
@@ -0,0 +1,282 @@
+use std::collections::VecDeque;
+
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::analysis_result::{
+    AnalysisResult, LivenessChainLink, LivenessExplanation, LivenessRule, ReferenceKind,
+};
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::issue::IssueKind;
+use hakana_reflection_info::StrId;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+// Explains why a function, class or method isn't reported as unused, by finding the
+// shortest chain of references to it from something that's always kept
+pub(crate) fn explain_symbol_liveness(
+    analysis_result: &AnalysisResult,
+    config: &Config,
+    codebase: &CodebaseInfo,
+    ignored_paths: &Option<FxHashSet<String>>,
+    symbol_name: &str,
+) -> Option<LivenessExplanation> {
+    let symbol = get_symbol_id(symbol_name, codebase)?;
+
+    let referencers = get_referencers(analysis_result, codebase);
+
+    let mut explanation = LivenessExplanation {
+        symbol: symbol_name.to_string(),
+        chain: vec![],
+        kept_by: get_liveness_rule(&symbol, config, codebase, ignored_paths),
+    };
+
+    if explanation.kept_by.is_some() {
+        return Some(explanation);
+    }
+
+    let mut previous_links = FxHashMap::default();
+    let mut queue = VecDeque::from([symbol]);
+
+    while let Some(current) = queue.pop_front() {
+        for (referencing_symbol, reference_kind) in referencers
+            .get(&current)
+            .map(|r| r.as_slice())
+            .unwrap_or(&[])
+        {
+            if *referencing_symbol == symbol || previous_links.contains_key(referencing_symbol) {
+                continue;
+            }
+
+            previous_links.insert(*referencing_symbol, (current, *reference_kind));
+
+            if let Some(rule) =
+                get_liveness_rule(referencing_symbol, config, codebase, ignored_paths)
+            {
+                let mut link = *referencing_symbol;
+
+                while let Some((referenced_symbol, reference_kind)) = previous_links.get(&link) {
+                    explanation.chain.push(LivenessChainLink {
+                        referencing_symbol: get_symbol_name(&link, codebase),
+                        reference_kind: *reference_kind,
+                    });
+                    link = *referenced_symbol;
+                }
+
+                explanation.chain.reverse();
+                explanation.kept_by = Some(rule);
+
+                return Some(explanation);
+            }
+
+            queue.push_back(*referencing_symbol);
+        }
+    }
+
+    // nothing that's always kept leads here, but a direct reference is still enough to
+    // stop it being reported
+    if let Some((referencing_symbol, reference_kind)) =
+        referencers.get(&symbol).and_then(|r| r.first())
+    {
+        explanation.chain.push(LivenessChainLink {
+            referencing_symbol: get_symbol_name(referencing_symbol, codebase),
+            reference_kind: *reference_kind,
+        });
+    }
+
+    Some(explanation)
+}
+
+fn get_symbol_id(symbol_name: &str, codebase: &CodebaseInfo) -> Option<(StrId, StrId)> {
+    if let Some((classlike_name, member_name)) = symbol_name.split_once("::") {
+        Some((
+            codebase.interner.get(classlike_name)?,
+            codebase.interner.get(member_name)?,
+        ))
+    } else {
+        Some((codebase.interner.get(symbol_name)?, StrId::empty()))
+    }
+}
+
+fn get_symbol_name(symbol: &(StrId, StrId), codebase: &CodebaseInfo) -> String {
+    if symbol.1.is_empty() {
+        codebase.interner.lookup(&symbol.0).to_string()
+    } else {
+        format!(
+            "{}::{}",
+            codebase.interner.lookup(&symbol.0),
+            codebase.interner.lookup(&symbol.1)
+        )
+    }
+}
+
+// Everything that references each symbol, sorted by name so explanations are stable
+fn get_referencers(
+    analysis_result: &AnalysisResult,
+    codebase: &CodebaseInfo,
+) -> FxHashMap<(StrId, StrId), Vec<((StrId, StrId), ReferenceKind)>> {
+    let symbol_references = &analysis_result.symbol_references;
+
+    let mut referencers = FxHashMap::default();
+
+    for (references, reference_kind) in [
+        (
+            &symbol_references.symbol_references_to_symbols,
+            ReferenceKind::Body,
+        ),
+        (
+            &symbol_references.symbol_references_to_symbols_in_signature,
+            ReferenceKind::Signature,
+        ),
+        (
+            &symbol_references.symbol_references_to_overridden_members,
+            ReferenceKind::Override,
+        ),
+    ] {
+        for (referencing_symbol, referenced_symbols) in references {
+            for referenced_symbol in referenced_symbols {
+                referencers
+                    .entry(*referenced_symbol)
+                    .or_insert_with(Vec::new)
+                    .push((*referencing_symbol, reference_kind));
+            }
+        }
+    }
+
+    // a call to a parent method can end up calling any method that overrides it
+    for (classlike_name, classlike_info) in &codebase.classlike_infos {
+        for (method_name, parent_classlikes) in &classlike_info.overridden_method_ids {
+            for parent_classlike in parent_classlikes {
+                referencers
+                    .entry((*classlike_name, *method_name))
+                    .or_insert_with(Vec::new)
+                    .push(((*parent_classlike, *method_name), ReferenceKind::Dynamic));
+            }
+        }
+    }
+
+    for symbol_referencers in referencers.values_mut() {
+        symbol_referencers.sort_by_cached_key(|(referencing_symbol, _)| {
+            get_symbol_name(referencing_symbol, codebase)
+        });
+    }
+
+    referencers
+}
+
+// Mirrors the checks find_unused_definitions makes before it reports a symbol
+fn get_liveness_rule(
+    symbol: &(StrId, StrId),
+    config: &Config,
+    codebase: &CodebaseInfo,
+    ignored_paths: &Option<FxHashSet<String>>,
+) -> Option<LivenessRule> {
+    if symbol.1.is_empty() {
+        if let Some(functionlike_info) = codebase.functionlike_infos.get(&symbol.0) {
+            if !functionlike_info.user_defined {
+                return Some(LivenessRule::NotUserDefined);
+            }
+
+            if let Some(pos) = &functionlike_info.name_location {
+                if is_ignored_path(
+                    codebase.interner.lookup(&pos.file_path),
+                    &IssueKind::UnusedFunction,
+                    config,
+                    ignored_paths,
+                ) {
+                    return Some(LivenessRule::IgnoredPath);
+                }
+            }
+
+            if functionlike_info.dynamically_callable {
+                return Some(LivenessRule::DynamicallyCallable);
+            }
+
+            if functionlike_info.generated {
+                return Some(LivenessRule::Generated);
+            }
+
+            if functionlike_info.is_issue_suppressed(&IssueKind::UnusedFunction) {
+                return Some(LivenessRule::Suppressed);
+            }
+
+            return None;
+        }
+    }
+
+    let classlike_info = if let Some(classlike_info) = codebase.classlike_infos.get(&symbol.0) {
+        classlike_info
+    } else {
+        return Some(LivenessRule::NotChecked);
+    };
+
+    if !classlike_info.user_defined {
+        return Some(LivenessRule::NotUserDefined);
+    }
+
+    if is_ignored_path(
+        codebase
+            .interner
+            .lookup(&classlike_info.name_location.file_path),
+        &IssueKind::UnusedClass,
+        config,
+        ignored_paths,
+    ) {
+        return Some(LivenessRule::IgnoredPath);
+    }
+
+    if classlike_info.generated {
+        return Some(LivenessRule::Generated);
+    }
+
+    if symbol.1.is_empty() {
+        for parent_class in &classlike_info.all_parent_classes {
+            if let Some(parent_classlike_info) = codebase.classlike_infos.get(parent_class) {
+                if !parent_classlike_info.user_defined {
+                    return Some(LivenessRule::ExtendsExternalClass);
+                }
+            }
+        }
+
+        return None;
+    }
+
+    let functionlike_info = if let Some(functionlike_info) = classlike_info.methods.get(&symbol.1) {
+        functionlike_info
+    } else {
+        return Some(LivenessRule::NotChecked);
+    };
+
+    if symbol.1 != StrId::construct() && codebase.interner.lookup(&symbol.1).starts_with("__") {
+        return Some(LivenessRule::MagicMethod);
+    }
+
+    if functionlike_info.dynamically_callable {
+        return Some(LivenessRule::DynamicallyCallable);
+    }
+
+    if functionlike_info.generated {
+        return Some(LivenessRule::Generated);
+    }
+
+    if functionlike_info.is_issue_suppressed(&IssueKind::UnusedPrivateMethod) {
+        return Some(LivenessRule::Suppressed);
+    }
+
+    None
+}
+
+fn is_ignored_path(
+    file_path: &str,
+    issue_kind: &IssueKind,
+    config: &Config,
+    ignored_paths: &Option<FxHashSet<String>>,
+) -> bool {
+    if let Some(ignored_paths) = ignored_paths {
+        if ignored_paths
+            .iter()
+            .any(|ignored_path| file_path.contains(ignored_path.as_str()))
+        {
+            return true;
+        }
+    }
+
+    !config.allow_issue_kind_in_file(issue_kind, file_path)
+}
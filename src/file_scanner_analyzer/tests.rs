@@ -0,0 +1,404 @@
+use std::path::Path;
+
+use hakana_analyzer::custom_hook::{AfterFunctionCallAnalysisData, CustomHook, InternalHook};
+use hakana_analyzer::scope_analyzer::ScopeAnalyzer;
+use hakana_analyzer::typed_ast::TastInfo;
+use hakana_reflection_info::data_flow::graph::WholeProgramKind;
+use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::issue::IssueKind;
+use hakana_reflection_info::t_atomic::TAtomic;
+use hakana_reflection_info::t_union::TUnion;
+
+use super::*;
+use crate::file_provider::OverlayFileProvider;
+
+// Runs a taint analysis that checkpoints before the whole-program phase, and returns
+// whether the checkpoint was written
+fn writes_taint_checkpoint(test_name: &str, overlay_contents: Option<&str>) -> bool {
+    let root_dir =
+        std::env::temp_dir().join(format!("hakana-{}-{}", test_name, std::process::id()));
+    let cache_dir = root_dir.join(".hakana_cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    let a_path = root_dir.join("a.hack").to_string_lossy().to_string();
+    fs::write(&a_path, "function foo(): void {}\n").unwrap();
+
+    let checkpoint_path = cache_dir
+        .join("taint_checkpoint")
+        .to_string_lossy()
+        .to_string();
+
+    let mut config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+    config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Taint);
+    config.whole_program_checkpoint_path = Some(checkpoint_path.clone());
+
+    let file_provider: Arc<dyn FileProvider> = if let Some(overlay_contents) = overlay_contents {
+        Arc::new(OverlayFileProvider::new(
+            FxHashMap::from_iter([(a_path, overlay_contents.to_string())]),
+            Arc::new(DiskFileProvider),
+        ))
+    } else {
+        Arc::new(DiskFileProvider)
+    };
+
+    let analysis_result = scan_and_analyze_with_file_provider(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        Some(&cache_dir.to_string_lossy().to_string()),
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+        file_provider,
+    );
+
+    let checkpoint_written = Path::new(&checkpoint_path).exists();
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    analysis_result.unwrap();
+
+    checkpoint_written
+}
+
+#[test]
+fn writes_taint_checkpoint_from_disk() {
+    assert!(writes_taint_checkpoint("checkpoint-disk", None));
+}
+
+#[test]
+fn skips_taint_checkpoint_with_overlays() {
+    assert!(!writes_taint_checkpoint(
+        "checkpoint-overlay",
+        Some("function foo(): void { echo 1; }\n")
+    ));
+}
+
+fn get_formatted_issues(emitted_issues: &BTreeMap<String, Vec<Issue>>) -> Vec<String> {
+    emitted_issues
+        .iter()
+        .flat_map(|(file_path, issues)| issues.iter().map(|issue| issue.format(file_path)))
+        .collect()
+}
+
+#[test]
+fn resumed_taint_checkpoint_reports_the_same_issues() {
+    let root_dir =
+        std::env::temp_dir().join(format!("hakana-checkpoint-resume-{}", std::process::id()));
+    let cache_dir = root_dir.join(".hakana_cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    fs::write(
+        root_dir.join("a.hack"),
+        r#"function get_name(): string {
+    return (string)$_GET["name"];
+}
+
+function foo(): void {
+    echo get_name();
+}
+"#,
+    )
+    .unwrap();
+
+    let checkpoint_path = cache_dir
+        .join("taint_checkpoint")
+        .to_string_lossy()
+        .to_string();
+
+    let mut config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+    config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Taint);
+    config.whole_program_checkpoint_path = Some(checkpoint_path.clone());
+
+    let config = Arc::new(config);
+
+    let analysis_result = scan_and_analyze(
+        true,
+        vec![],
+        None,
+        None,
+        config.clone(),
+        Some(&cache_dir.to_string_lossy().to_string()),
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    );
+
+    let resumed_analysis_result =
+        resume_whole_program_analysis(config, &checkpoint_path, 1, Verbosity::Quiet, "");
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let emitted_issues = analysis_result.unwrap().emitted_issues;
+
+    assert!(
+        emitted_issues
+            .values()
+            .flatten()
+            .any(|issue| matches!(issue.kind, IssueKind::TaintedData(_))),
+        "{:?}",
+        emitted_issues
+    );
+    assert_eq!(
+        get_formatted_issues(&emitted_issues),
+        get_formatted_issues(&resumed_analysis_result.unwrap().emitted_issues)
+    );
+}
+
+// enough separate source-to-sink paths for them to be split between threads
+const TAINTED_FILE: &str = r#"function get_name(): string {
+    return (string)$_GET["name"];
+}
+
+function get_id(): string {
+    return (string)$_POST["id"];
+}
+
+function get_cookie(): string {
+    return (string)$_COOKIE["c"];
+}
+
+function echo_name(): void {
+    echo get_name();
+}
+
+function echo_id(): void {
+    echo get_id();
+}
+
+function echo_cookie(): void {
+    echo get_cookie();
+}
+
+function echo_all(): void {
+    echo get_name().get_id().get_cookie();
+}
+
+function safe(): void {
+    echo "hello";
+}
+"#;
+
+fn find_tainted_data_with_threads(threads: u8) -> Vec<String> {
+    let root_dir = std::env::temp_dir().join(format!(
+        "hakana-taint-threads-{}-{}",
+        threads,
+        std::process::id()
+    ));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    fs::write(root_dir.join("a.hack"), TAINTED_FILE).unwrap();
+
+    let mut config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+    config.graph_kind = GraphKind::WholeProgram(WholeProgramKind::Taint);
+
+    let analysis_result = scan_and_analyze(
+        true,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        None,
+        threads,
+        Verbosity::Quiet,
+        "",
+        None,
+    );
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let mut issues = get_formatted_issues(&analysis_result.unwrap().emitted_issues);
+    issues.sort();
+    issues
+}
+
+#[test]
+fn finds_the_same_tainted_data_with_any_thread_count() {
+    let single_threaded_issues = find_tainted_data_with_threads(1);
+
+    assert!(
+        single_threaded_issues
+            .iter()
+            .filter(|issue| issue.contains("TaintedData"))
+            .count()
+            > 1,
+        "{:?}",
+        single_threaded_issues
+    );
+    assert_eq!(single_threaded_issues, find_tainted_data_with_threads(4));
+}
+
+struct NonNullFindUserHook;
+
+impl InternalHook for NonNullFindUserHook {
+    fn after_function_call_analysis(
+        &self,
+        _tast_info: &mut TastInfo,
+        after_function_call_analysis_data: AfterFunctionCallAnalysisData,
+        return_type: &mut TUnion,
+    ) {
+        if let FunctionLikeIdentifier::Function(function_name) =
+            after_function_call_analysis_data.functionlike_id
+        {
+            let interner = &after_function_call_analysis_data
+                .statements_analyzer
+                .get_codebase()
+                .interner;
+
+            if interner.lookup(function_name) == "find_user" {
+                return_type.remove_type(&TAtomic::TNull);
+            }
+        }
+    }
+}
+
+impl CustomHook for NonNullFindUserHook {}
+
+#[test]
+fn function_call_hook_refines_type_before_null_tracking() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-call-hook-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    fs::write(
+        root_dir.join("a.hack"),
+        r#"final class User {}
+
+function find_user(int $id): ?User {
+    return $id > 0 ? new User() : null;
+}
+
+function takes_mixed(mixed $_): void {}
+
+function foo(): void {
+    takes_mixed(find_user(1));
+}
+"#,
+    )
+    .unwrap();
+
+    let mut config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+    config.must_handle_null_functions = FxHashSet::from_iter(["find_user".to_string()]);
+    config.hooks = vec![Box::new(NonNullFindUserHook)];
+
+    let analysis_result = scan_and_analyze(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(config),
+        None,
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    );
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    let emitted_issues = analysis_result.unwrap().emitted_issues;
+
+    assert!(
+        emitted_issues.values().all(|issues| issues.is_empty()),
+        "{:?}",
+        emitted_issues
+    );
+}
+
+#[test]
+fn dependency_codebase_drops_symbols_deleted_from_the_subtree() {
+    let root_dir =
+        std::env::temp_dir().join(format!("hakana-dependency-root-{}", std::process::id()));
+    let subtree_dir = root_dir.join("sub");
+    fs::create_dir_all(&subtree_dir).unwrap();
+    fs::create_dir_all(root_dir.join(".hakana_cache")).unwrap();
+
+    fs::write(root_dir.join("a.hack"), "function in_root(): void {}\n").unwrap();
+    fs::write(
+        subtree_dir.join("b.hack"),
+        "function kept_in_subtree(): void {}\n\nfunction deleted_from_subtree(): void {}\n",
+    )
+    .unwrap();
+
+    let root_path = root_dir.to_string_lossy().to_string();
+    let subtree_path = subtree_dir.to_string_lossy().to_string();
+
+    let full_analysis_result = scan_and_analyze(
+        false,
+        vec![],
+        None,
+        None,
+        Arc::new(Config::new(root_path.clone(), FxHashSet::default())),
+        Some(&format!("{}/.hakana_cache", root_path)),
+        1,
+        Verbosity::Quiet,
+        "",
+        None,
+    );
+
+    fs::write(
+        subtree_dir.join("b.hack"),
+        "function kept_in_subtree(): void {}\n",
+    )
+    .unwrap();
+    fs::write(
+        subtree_dir.join("c.hack"),
+        r#"function foo(): void {
+    in_root();
+    kept_in_subtree();
+    deleted_from_subtree();
+}
+"#,
+    )
+    .unwrap();
+
+    let dependency_codebase = load_dependency_codebase(&root_path, &subtree_path, Verbosity::Quiet);
+
+    let subtree_analysis_result = dependency_codebase.clone().map(|starter_data| {
+        scan_and_analyze(
+            false,
+            vec![],
+            None,
+            None,
+            Arc::new(Config::new(subtree_path.clone(), FxHashSet::default())),
+            None,
+            1,
+            Verbosity::Quiet,
+            "",
+            Some(starter_data),
+        )
+    });
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    full_analysis_result.unwrap();
+
+    let (codebase, interner) = dependency_codebase.unwrap();
+
+    let has_function = |function_name: &str| {
+        interner.get(function_name).map_or(false, |function_id| {
+            codebase.functionlike_infos.contains_key(&function_id)
+        })
+    };
+
+    // the subtree is rescanned, so none of its old symbols are kept
+    assert!(has_function("in_root"));
+    assert!(!has_function("kept_in_subtree"));
+    assert!(!has_function("deleted_from_subtree"));
+
+    let emitted_issues = subtree_analysis_result.unwrap().unwrap().emitted_issues;
+
+    assert_eq!(
+        vec!["NonExistentFunction".to_string()],
+        emitted_issues
+            .values()
+            .flatten()
+            .map(|issue| issue.kind.to_string())
+            .collect::<Vec<_>>(),
+        "{:?}",
+        emitted_issues
+    );
+}
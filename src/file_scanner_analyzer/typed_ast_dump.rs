@@ -0,0 +1,126 @@
+use hakana_analyzer::config::Config;
+use hakana_reflection_info::analysis_result::{AnalysisResult, TypedAstNode};
+use oxidized::aast;
+use oxidized::aast_visitor::{visit, AstParams, Node, Visitor};
+use std::collections::BTreeMap;
+
+use crate::error::HakanaError;
+use crate::file_provider::FileProvider;
+use crate::get_aast_for_path;
+
+// Lists every expression in a file along with the type inferred for it, so that tools
+// can use Hakana's inference results without linking against it. Expects the file to
+// have been analyzed with config.typed_ast_file set.
+pub(crate) fn get_typed_ast(
+    file_path: &String,
+    analysis_result: &AnalysisResult,
+    config: &Config,
+    file_provider: &dyn FileProvider,
+) -> Result<Vec<TypedAstNode>, HakanaError> {
+    let (program, _, _) = get_aast_for_path(
+        &format!("{}/{}", config.root_dir, file_path),
+        &config.root_dir,
+        None,
+        file_provider,
+    )?;
+
+    let mut scanner = Scanner {
+        expr_types: &analysis_result.expr_types,
+        nodes: vec![],
+    };
+
+    for def in &program {
+        visit(&mut scanner, &mut (), def).unwrap();
+    }
+
+    Ok(scanner.nodes)
+}
+
+struct Scanner<'a> {
+    expr_types: &'a BTreeMap<(usize, usize), String>,
+    nodes: Vec<TypedAstNode>,
+}
+
+impl<'ast> Visitor<'ast> for Scanner<'_> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_expr(&mut self, c: &mut (), expr: &aast::Expr<(), ()>) -> Result<(), ()> {
+        let (start, end) = expr.1.to_start_and_end_lnum_bol_offset();
+        let (start_line, start_line_offset, start_offset) = start;
+        let (end_line, end_line_offset, end_offset) = end;
+
+        self.nodes.push(TypedAstNode {
+            kind: get_expr_kind(&expr.2),
+            start_offset,
+            end_offset,
+            start_line,
+            start_column: start_offset - start_line_offset + 1,
+            end_line,
+            end_column: end_offset - end_line_offset + 1,
+            inferred_type: self.expr_types.get(&(start_offset, end_offset)).cloned(),
+        });
+
+        expr.recurse(c, self)
+    }
+}
+
+fn get_expr_kind(expr: &aast::Expr_<(), ()>) -> &'static str {
+    match expr {
+        aast::Expr_::Darray(..) => "Darray",
+        aast::Expr_::Varray(..) => "Varray",
+        aast::Expr_::Shape(..) => "Shape",
+        aast::Expr_::ValCollection(..) => "ValCollection",
+        aast::Expr_::KeyValCollection(..) => "KeyValCollection",
+        aast::Expr_::Null => "Null",
+        aast::Expr_::This => "This",
+        aast::Expr_::True => "True",
+        aast::Expr_::False => "False",
+        aast::Expr_::Omitted => "Omitted",
+        aast::Expr_::Id(..) => "Id",
+        aast::Expr_::Lvar(..) => "Lvar",
+        aast::Expr_::Dollardollar(..) => "Dollardollar",
+        aast::Expr_::Clone(..) => "Clone",
+        aast::Expr_::ArrayGet(..) => "ArrayGet",
+        aast::Expr_::ObjGet(..) => "ObjGet",
+        aast::Expr_::ClassGet(..) => "ClassGet",
+        aast::Expr_::ClassConst(..) => "ClassConst",
+        aast::Expr_::Call(..) => "Call",
+        aast::Expr_::FunctionPointer(..) => "FunctionPointer",
+        aast::Expr_::Int(..) => "Int",
+        aast::Expr_::Float(..) => "Float",
+        aast::Expr_::String(..) => "String",
+        aast::Expr_::String2(..) => "String2",
+        aast::Expr_::PrefixedString(..) => "PrefixedString",
+        aast::Expr_::Yield(..) => "Yield",
+        aast::Expr_::Await(..) => "Await",
+        aast::Expr_::ReadonlyExpr(..) => "ReadonlyExpr",
+        aast::Expr_::Tuple(..) => "Tuple",
+        aast::Expr_::List(..) => "List",
+        aast::Expr_::Cast(..) => "Cast",
+        aast::Expr_::Unop(..) => "Unop",
+        aast::Expr_::Binop(..) => "Binop",
+        aast::Expr_::Pipe(..) => "Pipe",
+        aast::Expr_::Eif(..) => "Eif",
+        aast::Expr_::Is(..) => "Is",
+        aast::Expr_::As(..) => "As",
+        aast::Expr_::Upcast(..) => "Upcast",
+        aast::Expr_::New(..) => "New",
+        aast::Expr_::Efun(..) => "Efun",
+        aast::Expr_::Lfun(..) => "Lfun",
+        aast::Expr_::Xml(..) => "Xml",
+        aast::Expr_::Import(..) => "Import",
+        aast::Expr_::Collection(..) => "Collection",
+        aast::Expr_::ExpressionTree(..) => "ExpressionTree",
+        aast::Expr_::Lplaceholder(..) => "Lplaceholder",
+        aast::Expr_::MethodCaller(..) => "MethodCaller",
+        aast::Expr_::Pair(..) => "Pair",
+        aast::Expr_::ETSplice(..) => "ETSplice",
+        aast::Expr_::EnumClassLabel(..) => "EnumClassLabel",
+        aast::Expr_::Hole(..) => "Hole",
+        aast::Expr_::Invalid(..) => "Invalid",
+    }
+}
@@ -1,9 +1,13 @@
 use hakana_analyzer::config::Config;
-use hakana_reflection_info::analysis_result::{AnalysisResult, Replacement};
+use hakana_reflection_info::analysis_result::{
+    AnalysisResult, DeletionSafety, Replacement, UnusedSymbol,
+};
 use hakana_reflection_info::classlike_info::ClassLikeInfo;
+use hakana_reflection_info::code_location::HPos;
 use hakana_reflection_info::codebase_info::symbols::SymbolKind;
 use hakana_reflection_info::codebase_info::{CodebaseInfo, Symbols};
 use hakana_reflection_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_reflection_info::functionlike_info::FunctionLikeInfo;
 use hakana_reflection_info::issue::{Issue, IssueKind};
 use hakana_reflection_info::member_visibility::MemberVisibility;
 use hakana_reflection_info::StrId;
@@ -24,6 +28,10 @@ pub(crate) fn find_unused_definitions(
         .symbol_references
         .get_referenced_overridden_class_members();
 
+    // functions and classes that could be deleted if nothing outside their file used them
+    let mut removable_top_level_symbols = FxHashSet::default();
+    let mut removed_symbols = FxHashSet::default();
+
     'outer1: for (function_name, functionlike_info) in &codebase.functionlike_infos {
         if functionlike_info.user_defined
             && !functionlike_info.dynamically_callable
@@ -40,21 +48,22 @@ pub(crate) fn find_unused_definitions(
                 }
             }
 
-            if !referenced_symbols_and_members.contains(&(*function_name, StrId::empty())) {
-                if let Some(suppressed_issues) = &functionlike_info.suppressed_issues {
-                    if suppressed_issues.contains_key(&IssueKind::UnusedFunction) {
-                        continue;
-                    }
-                }
+            if functionlike_info.is_issue_suppressed(&IssueKind::UnusedFunction) {
+                continue;
+            }
 
-                if !config.allow_issue_kind_in_file(&IssueKind::UnusedFunction, &file_path) {
-                    continue;
-                }
+            if !config.allow_issue_kind_in_file(&IssueKind::UnusedFunction, &file_path) {
+                continue;
+            }
 
+            removable_top_level_symbols.insert(*function_name);
+
+            if !referenced_symbols_and_members.contains(&(*function_name, StrId::empty())) {
                 if config.migration_symbols.contains(&(
                     "unused_symbol".to_string(),
                     codebase.interner.lookup(function_name).to_string(),
                 )) {
+                    removed_symbols.insert(*function_name);
                     let def_pos = &functionlike_info.def_location;
                     analysis_result
                         .replacements
@@ -68,7 +77,7 @@ pub(crate) fn find_unused_definitions(
                         );
                 }
 
-                let issue = Issue::new(
+                let mut issue = Issue::new(
                     IssueKind::UnusedFunction,
                     format!(
                         "Unused function {}",
@@ -78,7 +87,23 @@ pub(crate) fn find_unused_definitions(
                     &Some(FunctionLikeIdentifier::Function(*function_name)),
                 );
 
-                if config.can_add_issue(&issue) {
+                if config.list_unused_symbols {
+                    add_unused_symbol(
+                        analysis_result,
+                        codebase,
+                        &issue,
+                        &functionlike_info.def_location,
+                        if functionlike_info.attributes.is_empty() {
+                            DeletionSafety::Safe
+                        } else {
+                            DeletionSafety::NeedsReview
+                        },
+                    );
+                }
+
+                if let Some(severity) = config.get_reported_issue_severity(&issue, &file_path) {
+                    issue.severity = severity;
+
                     *analysis_result
                         .issue_counts
                         .entry(issue.kind.clone())
@@ -118,8 +143,10 @@ pub(crate) fn find_unused_definitions(
                 }
             }
 
+            removable_top_level_symbols.insert(*classlike_name);
+
             if !referenced_symbols_and_members.contains(&(*classlike_name, StrId::empty())) {
-                let issue = Issue::new(
+                let mut issue = Issue::new(
                     IssueKind::UnusedClass,
                     format!(
                         "Unused class, interface or enum {}",
@@ -133,6 +160,7 @@ pub(crate) fn find_unused_definitions(
                     "unused_symbol".to_string(),
                     codebase.interner.lookup(classlike_name).to_string(),
                 )) {
+                    removed_symbols.insert(*classlike_name);
                     let def_pos = &classlike_info.def_location;
                     analysis_result
                         .replacements
@@ -146,7 +174,19 @@ pub(crate) fn find_unused_definitions(
                         );
                 }
 
-                if config.can_add_issue(&issue) {
+                if config.list_unused_symbols {
+                    add_unused_symbol(
+                        analysis_result,
+                        codebase,
+                        &issue,
+                        &classlike_info.def_location,
+                        get_classlike_deletion_safety(classlike_info),
+                    );
+                }
+
+                if let Some(severity) = config.get_reported_issue_severity(&issue, &file_path) {
+                    issue.severity = severity;
+
                     *analysis_result
                         .issue_counts
                         .entry(issue.kind.clone())
@@ -185,6 +225,16 @@ pub(crate) fn find_unused_definitions(
                             &codebase.symbols,
                             &codebase.classlike_descendants,
                         ) {
+                            // a trait method is used if any class that uses the trait
+                            // has its copy referenced
+                            let trait_user_pair = (trait_user, *method_name_ptr);
+
+                            if referenced_symbols_and_members.contains(&trait_user_pair)
+                                || referenced_overridden_class_members.contains(&trait_user_pair)
+                            {
+                                continue 'inner;
+                            }
+
                             if let Some(classlike_info) = codebase.classlike_infos.get(&trait_user)
                             {
                                 if has_upstream_method_call(
@@ -199,10 +249,9 @@ pub(crate) fn find_unused_definitions(
 
                         let method_storage = functionlike_storage.method_info.as_ref().unwrap();
 
-                        if let Some(suppressed_issues) = &functionlike_storage.suppressed_issues {
-                            if suppressed_issues.contains_key(&IssueKind::UnusedPrivateMethod) {
-                                continue;
-                            }
+                        if functionlike_storage.is_issue_suppressed(&IssueKind::UnusedPrivateMethod)
+                        {
+                            continue;
                         }
 
                         // allow one-liner private construct statements that prevent instantiation
@@ -217,7 +266,7 @@ pub(crate) fn find_unused_definitions(
                             }
                         }
 
-                        let issue =
+                        let mut issue =
                             if matches!(method_storage.visibility, MemberVisibility::Private) {
                                 Issue::new(
                                     IssueKind::UnusedPrivateMethod,
@@ -254,7 +303,27 @@ pub(crate) fn find_unused_definitions(
                             continue;
                         }
 
-                        if config.can_add_issue(&issue) {
+                        if config.list_unused_symbols {
+                            add_unused_symbol(
+                                analysis_result,
+                                codebase,
+                                &issue,
+                                &functionlike_storage.def_location,
+                                get_method_deletion_safety(
+                                    classlike_name,
+                                    classlike_info,
+                                    method_name_ptr,
+                                    functionlike_storage,
+                                    codebase,
+                                ),
+                            );
+                        }
+
+                        if let Some(severity) =
+                            config.get_reported_issue_severity(&issue, &file_path)
+                        {
+                            issue.severity = severity;
+
                             *analysis_result
                                 .issue_counts
                                 .entry(issue.kind.clone())
@@ -270,6 +339,209 @@ pub(crate) fn find_unused_definitions(
             }
         }
     }
+
+    if config.remove_unused_files {
+        remove_unused_files(
+            analysis_result,
+            codebase,
+            &removable_top_level_symbols,
+            removed_symbols,
+        );
+    }
+}
+
+// Deletes every file whose definitions are all removable and unused outside that file,
+// then removes any `use` statements that import a symbol which no longer exists
+fn remove_unused_files(
+    analysis_result: &mut AnalysisResult,
+    codebase: &CodebaseInfo,
+    removable_top_level_symbols: &FxHashSet<StrId>,
+    mut removed_symbols: FxHashSet<StrId>,
+) {
+    let mut symbol_files = FxHashMap::default();
+
+    for (file_path, file_info) in &codebase.files {
+        for node in &file_info.ast_nodes {
+            symbol_files.insert(node.name, *file_path);
+        }
+    }
+
+    let mut live_files = FxHashSet::default();
+
+    let symbol_references = &analysis_result.symbol_references;
+
+    for (referencing_symbol, referenced_symbols) in symbol_references
+        .symbol_references_to_symbols
+        .iter()
+        .chain(
+            symbol_references
+                .symbol_references_to_symbols_in_signature
+                .iter(),
+        )
+        .chain(
+            symbol_references
+                .symbol_references_to_overridden_members
+                .iter(),
+        )
+    {
+        // references from closures and other unknown places are treated as external
+        let referencing_file = symbol_files.get(&referencing_symbol.0);
+
+        for (referenced_symbol, _) in referenced_symbols {
+            if let Some(referenced_file) = symbol_files.get(referenced_symbol) {
+                if referencing_file != Some(referenced_file) {
+                    live_files.insert(*referenced_file);
+                }
+            }
+        }
+    }
+
+    for (classlike_name, descendants) in &codebase.classlike_descendants {
+        if let Some(classlike_file) = symbol_files.get(classlike_name) {
+            if descendants
+                .iter()
+                .any(|descendant| symbol_files.get(descendant) != Some(classlike_file))
+            {
+                live_files.insert(*classlike_file);
+            }
+        }
+    }
+
+    let mut deleted_files = FxHashSet::default();
+
+    for (file_path, file_info) in &codebase.files {
+        // constants and type aliases aren't checked for usage, so they keep a file alive
+        if file_info.ast_nodes.is_empty()
+            || live_files.contains(file_path)
+            || !file_info
+                .ast_nodes
+                .iter()
+                .all(|node| !node.is_constant && removable_top_level_symbols.contains(&node.name))
+        {
+            continue;
+        }
+
+        removed_symbols.extend(file_info.ast_nodes.iter().map(|node| node.name));
+        deleted_files.insert(*file_path);
+
+        let end_offset = file_info
+            .ast_nodes
+            .iter()
+            .map(|node| node.end_offset)
+            .max()
+            .unwrap();
+
+        analysis_result.replacements.insert(
+            codebase.interner.lookup(file_path).to_string(),
+            BTreeMap::from([((0, end_offset), Replacement::DeleteFile)]),
+        );
+    }
+
+    for (file_path, file_info) in &codebase.files {
+        if deleted_files.contains(file_path) {
+            continue;
+        }
+
+        for symbol_use in &file_info.symbol_uses {
+            if removed_symbols.contains(&symbol_use.name) {
+                analysis_result
+                    .replacements
+                    .entry(codebase.interner.lookup(file_path).to_string())
+                    .or_insert_with(BTreeMap::new)
+                    .insert(
+                        (symbol_use.start_offset, symbol_use.end_offset),
+                        Replacement::TrimPrecedingWhitespace(symbol_use.start_offset as u64),
+                    );
+            }
+        }
+    }
+}
+
+fn add_unused_symbol(
+    analysis_result: &mut AnalysisResult,
+    codebase: &CodebaseInfo,
+    issue: &Issue,
+    def_location: &HPos,
+    deletion_safety: DeletionSafety,
+) {
+    let name = if issue.symbol.1.is_empty() {
+        codebase.interner.lookup(&issue.symbol.0).to_string()
+    } else {
+        format!(
+            "{}::{}",
+            codebase.interner.lookup(&issue.symbol.0),
+            codebase.interner.lookup(&issue.symbol.1)
+        )
+    };
+
+    analysis_result.unused_symbols.push(UnusedSymbol {
+        name,
+        kind: issue.kind.clone(),
+        file_path: codebase
+            .interner
+            .lookup(&def_location.file_path)
+            .to_string(),
+        start_line: def_location.start_line,
+        end_line: def_location.end_line,
+        deletion_safety,
+    });
+}
+
+fn get_classlike_deletion_safety(classlike_info: &ClassLikeInfo) -> DeletionSafety {
+    if classlike_info.is_stubbed || !classlike_info.attributes.is_empty() {
+        return DeletionSafety::NeedsReview;
+    }
+
+    // it could still be instantiated via a classname of one of its parents
+    if !classlike_info.all_parent_classes.is_empty()
+        || !classlike_info.all_class_interfaces.is_empty()
+    {
+        return DeletionSafety::PartiallySafe;
+    }
+
+    DeletionSafety::Safe
+}
+
+// Methods follow the same rule as functions, unless they override or are overridden by
+// another method
+fn get_method_deletion_safety(
+    classlike_name: &StrId,
+    classlike_info: &ClassLikeInfo,
+    method_name: &StrId,
+    functionlike_storage: &FunctionLikeInfo,
+    codebase: &CodebaseInfo,
+) -> DeletionSafety {
+    if classlike_info.is_stubbed || !functionlike_storage.attributes.is_empty() {
+        return DeletionSafety::NeedsReview;
+    }
+
+    if matches!(
+        functionlike_storage
+            .method_info
+            .as_ref()
+            .unwrap()
+            .visibility,
+        MemberVisibility::Private
+    ) {
+        return DeletionSafety::Safe;
+    }
+
+    let has_descendants =
+        if let Some(descendants) = codebase.classlike_descendants.get(classlike_name) {
+            !descendants.is_empty()
+        } else {
+            false
+        };
+
+    if has_descendants
+        || classlike_info
+            .overridden_method_ids
+            .contains_key(method_name)
+    {
+        return DeletionSafety::NeedsReview;
+    }
+
+    DeletionSafety::Safe
 }
 
 fn has_upstream_method_call(
@@ -0,0 +1,40 @@
+use hakana_reflection_info::data_flow::graph::{GraphKind, WholeProgramKind};
+use hakana_reflection_info::issue::IssueKind;
+
+use super::*;
+
+const TAINTED_FILE: &str = "// security-check\n$name = $_GET[\"name\"];\necho $name;\n";
+
+fn has_taint_issue(graph_kind: GraphKind) -> bool {
+    let (mut codebase, interner) = get_single_file_codebase(vec![]);
+    codebase.interner = interner;
+
+    let mut analysis_config = Config::new("".to_string(), FxHashSet::default());
+    analysis_config.graph_kind = graph_kind;
+
+    let analysis_result = scan_and_analyze_single_file(
+        &mut codebase,
+        "hello.hack".to_string(),
+        TAINTED_FILE.to_string(),
+        analysis_config,
+    )
+    .unwrap();
+
+    analysis_result
+        .emitted_issues
+        .values()
+        .flatten()
+        .any(|issue| matches!(issue.kind, IssueKind::TaintedData(_)))
+}
+
+#[test]
+fn taint_analysis_follows_graph_kind() {
+    assert!(has_taint_issue(GraphKind::WholeProgram(
+        WholeProgramKind::Taint
+    )));
+}
+
+#[test]
+fn security_check_comment_does_not_enable_taint_analysis() {
+    assert!(!has_taint_issue(GraphKind::FunctionBody));
+}
@@ -1,5 +1,9 @@
+use hakana_analyzer::config::Config;
 use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::data_flow::graph::{GraphKind, WholeProgramKind};
+use hakana_reflection_info::issue::IssueSeverity;
 use hakana_workhorse::wasm::{get_single_file_codebase, scan_and_analyze_single_file};
+use rustc_hash::FxHashSet;
 use serde_json::json;
 use wasm_bindgen::prelude::*;
 extern crate console_error_panic_hook;
@@ -22,12 +26,20 @@ impl ScannerAndAnalyzer {
         Self { codebase }
     }
 
-    pub fn get_results(&mut self, file_contents: String) -> String {
+    pub fn get_results(&mut self, file_contents: String, security_check: bool) -> String {
+        let mut analysis_config = Config::new("".to_string(), FxHashSet::default());
+        analysis_config.find_unused_expressions = true;
+        analysis_config.graph_kind = if security_check {
+            GraphKind::WholeProgram(WholeProgramKind::Taint)
+        } else {
+            GraphKind::FunctionBody
+        };
+
         let result = scan_and_analyze_single_file(
             &mut self.codebase,
             "hello.hack".to_string(),
             file_contents.clone(),
-            true,
+            analysis_config,
         );
         return match result {
             Ok(analysis_result) => {
@@ -35,7 +47,11 @@ impl ScannerAndAnalyzer {
                 for (file_path, issues) in &analysis_result.emitted_issues {
                     for issue in issues {
                         issue_json_objects.push(json!({
-                            "severity": "ERROR",
+                            "severity": match issue.severity {
+                                IssueSeverity::Error => "ERROR",
+                                IssueSeverity::Warning => "WARNING",
+                                IssueSeverity::Info => "INFO",
+                            },
                             "line_from": issue.pos.start_line,
                             "line_to": issue.pos.end_line,
                             "type": format!("{}", issue.kind),
@@ -51,7 +67,7 @@ impl ScannerAndAnalyzer {
                             "column_from": issue.pos.start_column,
                             "column_to": issue.pos.end_column,
                             "shortcode": 0,
-                            "taint_trace": serde_json::Value::Null,
+                            "taint_trace": serde_json::to_value(&issue.taint_trace).unwrap(),
                             "other_references": serde_json::Value::Null,
                         }));
                     }
@@ -64,7 +80,7 @@ impl ScannerAndAnalyzer {
                 json.to_string()
             }
             Err(err) => json!({
-                "error": err,
+                "error": err.to_string(),
             })
             .to_string(),
         };
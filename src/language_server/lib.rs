@@ -0,0 +1,230 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use hakana_analyzer::config::{Config, Verbosity};
+use hakana_reflection_info::code_location::HPos;
+use hakana_reflection_info::codebase_info::CodebaseInfo;
+use hakana_reflection_info::issue::{Issue, IssueSeverity};
+use hakana_workhorse::error::HakanaError;
+use hakana_workhorse::file_provider::{DiskFileProvider, InMemoryFileProvider};
+use hakana_workhorse::orchestrator::Orchestrator;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, NumberOrString, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use rustc_hash::FxHashMap;
+
+type ServerResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+// Speaks the Language Server Protocol over stdio, so editors can show Hakana's issues
+// as files are edited
+pub fn run(config: Config, threads: u8) -> ServerResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let server_capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        ..Default::default()
+    })?;
+
+    connection.initialize(server_capabilities)?;
+
+    LanguageServer::new(config, threads)?.main_loop(connection)?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
+
+pub struct LanguageServer {
+    config: Arc<Config>,
+    // kept between requests so that the workspace is only scanned once, with each open
+    // document rescanned as it changes
+    codebase: CodebaseInfo,
+}
+
+impl LanguageServer {
+    pub fn new(config: Config, threads: u8) -> Result<Self, HakanaError> {
+        let config = Arc::new(config);
+
+        // nothing can be written to stdout, which carries the protocol
+        let orchestrator =
+            Orchestrator::new(config.clone(), Arc::new(DiskFileProvider), Verbosity::Quiet);
+
+        let codebase = orchestrator.scan_workspace(threads)?;
+
+        Ok(Self { config, codebase })
+    }
+
+    fn main_loop(&mut self, connection: Connection) -> ServerResult<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    connection.sender.send(Message::Response(Response::new_err(
+                        request.id,
+                        ErrorCode::MethodNotFound as i32,
+                        format!("Unsupported request {}", request.method),
+                    )))?;
+                }
+                Message::Notification(notification) => {
+                    self.handle_notification(&connection, notification)?;
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_notification(
+        &mut self,
+        connection: &Connection,
+        notification: Notification,
+    ) -> ServerResult<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                let diagnostics =
+                    self.analyze_document(&params.text_document.uri, params.text_document.text);
+
+                publish_diagnostics(
+                    connection,
+                    params.text_document.uri,
+                    diagnostics,
+                    Some(params.text_document.version),
+                )
+            }
+            DidChangeTextDocument::METHOD => {
+                let mut params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                // we only ask for full document syncs, so the last change has everything
+                if let Some(change) = params.content_changes.pop() {
+                    let diagnostics = self.analyze_document(&params.text_document.uri, change.text);
+
+                    publish_diagnostics(
+                        connection,
+                        params.text_document.uri,
+                        diagnostics,
+                        Some(params.text_document.version),
+                    )
+                } else {
+                    Ok(())
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+
+                publish_diagnostics(connection, params.text_document.uri, vec![], None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn analyze_document(&mut self, uri: &Url, file_contents: String) -> Vec<Diagnostic> {
+        let file_path = if let Ok(file_path) = uri.to_file_path() {
+            file_path.to_string_lossy().to_string()
+        } else {
+            return vec![];
+        };
+
+        let mut files = FxHashMap::default();
+        files.insert(file_path.clone(), file_contents);
+
+        let orchestrator = Orchestrator::new(
+            self.config.clone(),
+            Arc::new(InMemoryFileProvider::new(files)),
+            Verbosity::Quiet,
+        );
+
+        match orchestrator.scan_and_analyze_in_codebase(&mut self.codebase, &vec![file_path]) {
+            Ok(analysis_result) => analysis_result
+                .emitted_issues
+                .values()
+                .flatten()
+                .map(get_diagnostic_from_issue)
+                .collect(),
+            Err(error) => vec![get_diagnostic_from_error(error)],
+        }
+    }
+}
+
+fn publish_diagnostics(
+    connection: &Connection,
+    uri: Url,
+    diagnostics: Vec<Diagnostic>,
+    version: Option<i32>,
+) -> ServerResult<()> {
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams::new(uri, diagnostics, version),
+        )))?;
+
+    Ok(())
+}
+
+fn get_diagnostic_from_issue(issue: &Issue) -> Diagnostic {
+    Diagnostic {
+        range: get_range(&issue.pos),
+        severity: Some(match issue.severity {
+            IssueSeverity::Error => DiagnosticSeverity::ERROR,
+            IssueSeverity::Warning => DiagnosticSeverity::WARNING,
+            IssueSeverity::Info => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(issue.kind.to_string())),
+        code_description: issue
+            .help_uri
+            .as_ref()
+            .and_then(|help_uri| Url::parse(help_uri).ok())
+            .map(|href| CodeDescription { href }),
+        source: Some("hakana".to_string()),
+        message: if let Some(regeneration_hint) = &issue.regeneration_hint {
+            format!("{}\n{}", issue.description, regeneration_hint)
+        } else {
+            issue.description.clone()
+        },
+        ..Default::default()
+    }
+}
+
+fn get_diagnostic_from_error(error: HakanaError) -> Diagnostic {
+    let range = if let HakanaError::ParseError { pos: Some(pos), .. } = &error {
+        get_range(pos)
+    } else {
+        Range::default()
+    };
+
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("hakana".to_string()),
+        message: error.to_string(),
+        ..Default::default()
+    }
+}
+
+// Hakana's lines and columns start at 1, LSP's start at 0
+fn get_range(pos: &HPos) -> Range {
+    Range::new(
+        Position::new(pos.start_line as u32 - 1, pos.start_column as u32 - 1),
+        Position::new(pos.end_line as u32 - 1, pos.end_column as u32 - 1),
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,39 @@
+use std::fs;
+
+use rustc_hash::FxHashSet;
+
+use super::*;
+
+#[test]
+fn analyzes_documents_against_the_workspace() {
+    let root_dir = std::env::temp_dir().join(format!("hakana-ls-{}", std::process::id()));
+    fs::create_dir_all(&root_dir).unwrap();
+
+    let a_path = root_dir.join("a.hack");
+    let b_path = root_dir.join("b.hack");
+
+    fs::write(
+        &a_path,
+        "function a(): int {\n    return b();\n}\n\nfunction c(): int {\n    return 1;\n}\n",
+    )
+    .unwrap();
+    fs::write(&b_path, "function b(): int {\n    return c();\n}\n").unwrap();
+
+    let config = Config::new(root_dir.to_string_lossy().to_string(), FxHashSet::default());
+
+    let mut language_server = LanguageServer::new(config, 1).unwrap();
+
+    let a_diagnostics = language_server.analyze_document(
+        &Url::from_file_path(&a_path).unwrap(),
+        fs::read_to_string(&a_path).unwrap(),
+    );
+    let b_diagnostics = language_server.analyze_document(
+        &Url::from_file_path(&b_path).unwrap(),
+        fs::read_to_string(&b_path).unwrap(),
+    );
+
+    fs::remove_dir_all(&root_dir).unwrap();
+
+    assert!(a_diagnostics.is_empty(), "{:?}", a_diagnostics);
+    assert!(b_diagnostics.is_empty(), "{:?}", b_diagnostics);
+}
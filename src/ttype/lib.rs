@@ -9,6 +9,7 @@ use hakana_reflection_info::{
 use itertools::Itertools;
 use type_combiner::combine;
 
+pub mod signature_printer;
 pub mod template;
 mod type_combination;
 pub mod type_combiner;
@@ -135,6 +136,7 @@ pub fn get_vec(type_param: TUnion) -> TUnion {
         type_param,
         known_count: None,
         non_empty: false,
+        is_tuple: false,
     })
 }
 
@@ -194,7 +196,7 @@ pub fn combine_union_types(
     codebase: &CodebaseInfo,
     overwrite_empty_array: bool, // default false
 ) -> TUnion {
-    if type_1 == type_2 {
+    if type_1 == type_2 && type_1.readonly == type_2.readonly {
         return type_1.clone();
     }
 
@@ -229,6 +231,10 @@ pub fn combine_union_types(
         combined_type.ignore_falsable_issues = true;
     }
 
+    if type_1.readonly || type_2.readonly {
+        combined_type.readonly = true;
+    }
+
     if !type_1.parent_nodes.is_empty() || !type_2.parent_nodes.is_empty() {
         let mut parent_nodes = type_1.parent_nodes.clone();
         parent_nodes.extend(type_2.parent_nodes.clone());
@@ -244,7 +250,7 @@ pub fn add_union_type(
     codebase: &CodebaseInfo,
     overwrite_empty_array: bool, // default false
 ) -> TUnion {
-    if &base_type == other_type {
+    if &base_type == other_type && base_type.readonly == other_type.readonly {
         return base_type;
     }
 
@@ -273,6 +279,10 @@ pub fn add_union_type(
         base_type.ignore_falsable_issues = true;
     }
 
+    if other_type.readonly {
+        base_type.readonly = true;
+    }
+
     if !other_type.parent_nodes.is_empty() {
         base_type
             .parent_nodes
@@ -616,7 +626,10 @@ pub fn get_atomic_syntax_type(
             codebase.interner.lookup(enum_name).to_string()
         }
         TAtomic::TLiteralInt { .. } => "int".to_string(),
-        TAtomic::TLiteralString { .. } | TAtomic::TStringWithFlags(..) => "string".to_string(),
+        TAtomic::TLiteralString { .. }
+        | TAtomic::TStringWithFlags(..)
+        | TAtomic::TStringWithPrefix { .. }
+        | TAtomic::TStringWithPattern { .. } => "string".to_string(),
         TAtomic::TMixed | TAtomic::TMixedFromLoopIsset => "mixed".to_string(),
         TAtomic::TNamedObject {
             name, type_params, ..
@@ -0,0 +1,200 @@
+use hakana_reflection_info::{
+    codebase_info::CodebaseInfo,
+    data_flow::graph::{DataFlowGraph, GraphKind},
+    functionlike_identifier::FunctionLikeIdentifier,
+    functionlike_info::FunctionLikeInfo,
+    method_identifier::MethodIdentifier,
+    t_union::TUnion,
+    StrId,
+};
+use indexmap::IndexMap;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    template::{inferred_type_replacer, TemplateResult},
+    type_expander::{self, StaticClassType, TypeExpansionOptions},
+};
+
+// Fully-expanded signatures for documentation generators and other tools that
+// need to show types the way a caller would see them.
+
+#[derive(Debug, Clone)]
+pub struct SignaturePrintOptions {
+    // print Foo\Bar as Bar
+    pub short_names: bool,
+    // print what type aliases refer to, rather than their names
+    pub expand_type_aliases: bool,
+}
+
+impl Default for SignaturePrintOptions {
+    fn default() -> Self {
+        Self {
+            short_names: false,
+            expand_type_aliases: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintedParam {
+    pub name: String,
+    // None when the param has no type hint
+    pub param_type: Option<String>,
+    pub is_inout: bool,
+    pub is_optional: bool,
+    pub is_variadic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintedSignature {
+    pub params: Vec<PrintedParam>,
+    pub return_type: Option<String>,
+}
+
+// Methods are printed as they'd be seen on the given class, so templates from
+// generic parent classes are replaced with whatever the class extends them with
+pub fn get_printed_signature(
+    id: &FunctionLikeIdentifier,
+    codebase: &CodebaseInfo,
+    options: &SignaturePrintOptions,
+) -> Option<PrintedSignature> {
+    match id {
+        FunctionLikeIdentifier::Function(name) => {
+            let functionlike_info = codebase.functionlike_infos.get(name)?;
+
+            Some(print_signature(
+                functionlike_info,
+                None,
+                &IndexMap::new(),
+                codebase,
+                options,
+            ))
+        }
+        FunctionLikeIdentifier::Method(classlike_name, method_name) => {
+            let classlike_info = codebase.classlike_infos.get(classlike_name)?;
+            let declaring_method_id =
+                codebase.get_declaring_method_id(&MethodIdentifier(*classlike_name, *method_name));
+            let functionlike_info = codebase
+                .classlike_infos
+                .get(&declaring_method_id.0)?
+                .methods
+                .get(&declaring_method_id.1)?;
+
+            let mut inherited_template_types = IndexMap::new();
+
+            if let Some(extended_params) = classlike_info
+                .template_extended_params
+                .get(&declaring_method_id.0)
+            {
+                for (template_name, extended_type) in extended_params {
+                    let mut bounds = FxHashMap::default();
+                    bounds.insert(declaring_method_id.0, (**extended_type).clone());
+                    inherited_template_types.insert(*template_name, bounds);
+                }
+            }
+
+            Some(print_signature(
+                functionlike_info,
+                Some((classlike_name, classlike_info.direct_parent_class.as_ref())),
+                &inherited_template_types,
+                codebase,
+                options,
+            ))
+        }
+    }
+}
+
+fn print_signature(
+    functionlike_info: &FunctionLikeInfo,
+    class_context: Option<(&StrId, Option<&StrId>)>,
+    inherited_template_types: &IndexMap<StrId, FxHashMap<StrId, TUnion>>,
+    codebase: &CodebaseInfo,
+    options: &SignaturePrintOptions,
+) -> PrintedSignature {
+    let expansion_options = TypeExpansionOptions {
+        self_class: class_context.map(|(self_class, _)| self_class),
+        static_class_type: if let Some((self_class, _)) = class_context {
+            StaticClassType::Name(self_class)
+        } else {
+            StaticClassType::None
+        },
+        parent_class: class_context.and_then(|(_, parent_class)| parent_class),
+        expand_type_aliases: options.expand_type_aliases,
+        ..Default::default()
+    };
+
+    let print_type = |t: &TUnion| {
+        let mut t = if inherited_template_types.is_empty() {
+            t.clone()
+        } else {
+            inferred_type_replacer::replace(
+                t,
+                &TemplateResult::new(IndexMap::new(), inherited_template_types.clone()),
+                codebase,
+            )
+        };
+
+        type_expander::expand_union(
+            codebase,
+            &mut t,
+            &expansion_options,
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+        );
+
+        let id = t.get_id(Some(&codebase.interner));
+
+        if options.short_names {
+            shorten_names(&id)
+        } else {
+            id
+        }
+    };
+
+    PrintedSignature {
+        params: functionlike_info
+            .params
+            .iter()
+            .map(|param| PrintedParam {
+                name: param.name.clone(),
+                param_type: param.signature_type.as_ref().map(print_type),
+                is_inout: param.is_inout,
+                is_optional: param.is_optional,
+                is_variadic: param.is_variadic,
+            })
+            .collect(),
+        return_type: functionlike_info.return_type.as_ref().map(print_type),
+    }
+}
+
+// Strips namespaces from every name in a printed type, leaving string literals alone
+fn shorten_names(type_id: &str) -> String {
+    let mut shortened = String::new();
+    let mut current_name = String::new();
+    let mut in_string = false;
+
+    for c in type_id.chars() {
+        if in_string {
+            shortened.push(c);
+
+            if c == '\'' {
+                in_string = false;
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            current_name.push(c);
+        } else if c == '\\' {
+            current_name.clear();
+        } else {
+            shortened.push_str(&current_name);
+            current_name.clear();
+            shortened.push(c);
+
+            if c == '\'' {
+                in_string = true;
+            }
+        }
+    }
+
+    shortened.push_str(&current_name);
+
+    shortened
+}
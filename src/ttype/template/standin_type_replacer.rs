@@ -1626,26 +1626,34 @@ fn find_matching_atomic_types_for_template(
                         } = &enum_type
                         {
                             if let Some(classlike_info) = codebase.classlike_infos.get(enum_name) {
-                                if let Some(constant_info) =
-                                    classlike_info.constants.get(member_name)
+                                if let Some(provided_type) = classlike_info
+                                    .constants
+                                    .get(member_name)
+                                    .and_then(|c| c.provided_type.as_ref())
                                 {
-                                    let provided_type =
-                                        constant_info.provided_type.as_ref().unwrap().get_single();
-
-                                    if let TAtomic::TTypeAlias {
+                                    // members are usually declared as HH\MemberOf<TEnum, TType>
+                                    let member_type = if let [TAtomic::TTypeAlias {
                                         type_params: Some(type_params),
                                         ..
-                                    } = provided_type
+                                    }] = &provided_type.types[..]
                                     {
-                                        matching_atomic_types.push(TAtomic::TTypeAlias {
-                                            name: base_name.clone(),
-                                            type_params: Some(vec![
-                                                wrap_atomic(enum_type),
-                                                type_params[1].clone(),
-                                            ]),
-                                            as_type: None,
-                                        });
-                                    }
+                                        type_params.get(1)
+                                    } else {
+                                        None
+                                    };
+
+                                    let member_type = member_type
+                                        .cloned()
+                                        .unwrap_or_else(|| provided_type.clone());
+
+                                    matching_atomic_types.push(TAtomic::TTypeAlias {
+                                        name: base_name.clone(),
+                                        type_params: Some(vec![
+                                            wrap_atomic(enum_type),
+                                            member_type,
+                                        ]),
+                                        as_type: None,
+                                    });
                                 }
                             }
                         }
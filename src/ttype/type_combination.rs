@@ -24,6 +24,7 @@ pub(crate) struct TypeCombination {
 
     pub vec_sometimes_filled: bool,
     pub vec_always_filled: bool,
+    pub vec_always_tuple: bool,
 
     pub dict_sometimes_filled: bool,
     pub dict_always_filled: bool,
@@ -65,6 +66,7 @@ impl TypeCombination {
             vec_counts: Some(FxHashSet::default()),
             vec_sometimes_filled: false,
             vec_always_filled: true,
+            vec_always_tuple: true,
             dict_sometimes_filled: false,
             dict_always_filled: true,
             has_dict: false,
@@ -4,7 +4,7 @@ use hakana_reflection_info::{
     classlike_info::Variance,
     codebase_info::{symbols::SymbolKind, CodebaseInfo},
     t_atomic::{DictKey, TAtomic},
-    t_union::TUnion,
+    t_union::{get_common_prefix, TUnion},
     StrId,
 };
 use itertools::Itertools;
@@ -102,6 +102,14 @@ pub fn combine(
     }
 
     if let Some(vec_type_param) = combination.vec_type_param {
+        // tuples only stay tuples when combined with other tuples of the same arity
+        let tuple_count = match &combination.vec_counts {
+            Some(vec_counts) if combination.vec_always_tuple && vec_counts.len() == 1 => {
+                vec_counts.iter().next().cloned()
+            }
+            _ => None,
+        };
+
         new_types.push(TAtomic::TVec {
             known_items: if combination.vec_entries.is_empty() {
                 None
@@ -110,7 +118,8 @@ pub fn combine(
             },
             type_param: vec_type_param,
             non_empty: combination.vec_always_filled,
-            known_count: None,
+            known_count: tuple_count,
+            is_tuple: tuple_count.is_some(),
         });
     }
 
@@ -173,6 +182,26 @@ pub fn combine(
     }
 
     for (enum_name, values) in combination.enum_value_types {
+        // once every case of an enum is present we can just use the enum type
+        if let Some(enum_storage) = codebase.classlike_infos.get(&enum_name) {
+            if !enum_storage.constants.is_empty()
+                && enum_storage
+                    .constants
+                    .keys()
+                    .all(|member_name| values.contains_key(member_name))
+            {
+                combination.value_types.insert(
+                    enum_name.0.to_string(),
+                    TAtomic::TEnum {
+                        name: enum_name,
+                        // the cases carry the enum's constraint, so the enum has to as well
+                        base_type: enum_storage.enum_constraint.clone(),
+                    },
+                );
+                continue;
+            }
+        }
+
         for value in values {
             combination.value_types.insert(
                 format!("{}::{}", enum_name.0, value.0 .0),
@@ -209,11 +238,8 @@ pub fn combine(
         new_types.push(atomic);
     }
 
+    // combining no types at all also gives nothing
     if new_types.is_empty() {
-        if !has_nothing {
-            panic!();
-        }
-
         return vec![TAtomic::TNothing];
     }
 
@@ -389,11 +415,15 @@ fn scrape_type_properties(
         non_empty,
         known_count,
         ref known_items,
-        ..
+        is_tuple,
     } = atomic
     {
         let had_previous_param = combination.vec_type_param.is_some();
 
+        if !is_tuple {
+            combination.vec_always_tuple = false;
+        }
+
         if non_empty {
             if let Some(ref mut existing_counts) = combination.vec_counts {
                 if let Some(known_count) = known_count {
@@ -1024,6 +1054,8 @@ fn scrape_type_properties(
     if let TAtomic::TString { .. }
     | TAtomic::TLiteralString { .. }
     | TAtomic::TStringWithFlags(..)
+    | TAtomic::TStringWithPrefix { .. }
+    | TAtomic::TStringWithPattern { .. }
     | TAtomic::TInt
     | TAtomic::TLiteralInt { .. } = atomic
     {
@@ -1056,6 +1088,20 @@ fn scrape_type_properties(
                 return;
             }
 
+            if let TAtomic::TStringWithPattern { .. } = existing_string_type {
+                *existing_string_type = TAtomic::TString;
+                return;
+            }
+
+            if let TAtomic::TStringWithPrefix { prefix } = existing_string_type {
+                *existing_string_type = get_prefix_string_fallback(
+                    is_truthy && prefix != "0",
+                    is_truthy || is_nonempty,
+                    false,
+                );
+                return;
+            }
+
             if let TAtomic::TStringWithFlags(
                 existing_is_truthy,
                 existing_is_non_empty,
@@ -1106,10 +1152,99 @@ fn scrape_type_properties(
         return;
     }
 
+    if let TAtomic::TStringWithPrefix { prefix } = atomic {
+        let mut common_prefix = prefix.as_str();
+        let mut has_empty_literal = false;
+
+        if let Some(existing_string_type) = combination.value_types.get_mut("string") {
+            match existing_string_type {
+                TAtomic::TString => {}
+                TAtomic::TStringWithFlags(is_truthy, is_nonempty, _) => {
+                    *existing_string_type = get_prefix_string_fallback(
+                        *is_truthy && prefix != "0",
+                        *is_truthy || *is_nonempty,
+                        false,
+                    );
+                }
+                TAtomic::TStringWithPrefix {
+                    prefix: existing_prefix,
+                } => {
+                    let common_prefix = get_common_prefix(existing_prefix, &prefix).to_string();
+
+                    *existing_string_type = if common_prefix.is_empty() {
+                        get_prefix_string_fallback(false, true, false)
+                    } else {
+                        TAtomic::TStringWithPrefix {
+                            prefix: common_prefix,
+                        }
+                    };
+                }
+                TAtomic::TStringWithPattern { .. } => {
+                    *existing_string_type = TAtomic::TString;
+                }
+                _ => (),
+            }
+
+            return;
+        }
+
+        for (value, _) in &combination.literal_strings {
+            common_prefix = get_common_prefix(common_prefix, value);
+            has_empty_literal = has_empty_literal || value == "";
+        }
+
+        combination.value_types.insert(
+            "string".to_string(),
+            if common_prefix.is_empty() {
+                get_prefix_string_fallback(false, !has_empty_literal, false)
+            } else {
+                TAtomic::TStringWithPrefix {
+                    prefix: common_prefix.to_string(),
+                }
+            },
+        );
+
+        combination.literal_strings = FxHashMap::default();
+
+        return;
+    }
+
+    // strings matching different patterns (or none) have nothing useful in common
+    if let TAtomic::TStringWithPattern { pattern } = &atomic {
+        if let Some(existing_string_type) = combination.value_types.get_mut("string") {
+            match existing_string_type {
+                TAtomic::TStringWithPattern {
+                    pattern: existing_pattern,
+                } if existing_pattern == pattern => {}
+                _ => {
+                    *existing_string_type = TAtomic::TString;
+                }
+            }
+
+            return;
+        }
+
+        if !combination.literal_strings.is_empty() {
+            combination.literal_strings = FxHashMap::default();
+            combination
+                .value_types
+                .insert("string".to_string(), TAtomic::TString);
+
+            return;
+        }
+
+        combination.value_types.insert(atomic.get_key(), atomic);
+        return;
+    }
+
     if let TAtomic::TLiteralString { value, .. } = &atomic {
         if let Some(existing_string_type) = combination.value_types.get_mut("string") {
             match existing_string_type {
                 TAtomic::TString => return,
+                TAtomic::TStringWithPattern { .. } => {
+                    *existing_string_type = TAtomic::TString;
+                    return;
+                }
                 TAtomic::TStringWithFlags(is_truthy, is_nonempty, is_nonspecific_literal) => {
                     if value == "" {
                         *is_truthy = false;
@@ -1124,6 +1259,21 @@ fn scrape_type_properties(
 
                     return;
                 }
+                TAtomic::TStringWithPrefix { prefix } => {
+                    if !value.starts_with(prefix.as_str()) {
+                        let common_prefix = get_common_prefix(prefix, value).to_string();
+
+                        *existing_string_type = if common_prefix.is_empty() {
+                            get_prefix_string_fallback(false, value != "", false)
+                        } else {
+                            TAtomic::TStringWithPrefix {
+                                prefix: common_prefix,
+                            }
+                        };
+                    }
+
+                    return;
+                }
 
                 _ => (),
             }
@@ -1166,6 +1316,15 @@ fn scrape_type_properties(
     combination.value_types.insert(atomic.get_key(), atomic);
 }
 
+// What strings with different literal prefixes combine to
+fn get_prefix_string_fallback(is_truthy: bool, is_nonempty: bool, is_literal: bool) -> TAtomic {
+    if !is_truthy && !is_nonempty && !is_literal {
+        TAtomic::TString
+    } else {
+        TAtomic::TStringWithFlags(is_truthy, is_nonempty && !is_truthy, is_literal)
+    }
+}
+
 fn adjust_key_value_dict_params(
     existing_value_param: &mut TUnion,
     entry_type: &Arc<TUnion>,
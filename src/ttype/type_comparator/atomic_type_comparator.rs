@@ -225,6 +225,7 @@ pub fn is_contained_by(
                             type_param: value_param,
                             known_items: None,
                             non_empty: false,
+                            is_tuple: false,
                             known_count: None,
                         },
                         inside_assertion,
@@ -684,7 +685,9 @@ pub fn is_contained_by(
         if container_name == &codebase.interner.get("HH\\FormatString").unwrap() {
             if let TAtomic::TString { .. }
             | TAtomic::TLiteralString { .. }
-            | TAtomic::TStringWithFlags { .. } = input_type_part
+            | TAtomic::TStringWithFlags { .. }
+            | TAtomic::TStringWithPrefix { .. }
+            | TAtomic::TStringWithPattern { .. } = input_type_part
             {
                 // todo maybe more specific checks for the type of format string
                 return true;
@@ -350,6 +350,54 @@ pub fn is_contained_by(
 
                 return true;
             }
+            TAtomic::TStringWithPrefix { prefix } => {
+                return !container_is_nonspecific_literal
+                    && (prefix != "0" || !container_is_truthy);
+            }
+            TAtomic::TStringWithPattern { .. } => {
+                if !container_is_truthy
+                    && !container_is_nonempty
+                    && !container_is_nonspecific_literal
+                {
+                    return true;
+                }
+
+                atomic_comparison_result.type_coerced = Some(true);
+                return false;
+            }
+            _ => {}
+        },
+        TAtomic::TStringWithPrefix {
+            prefix: container_prefix,
+        } => match input_type_part {
+            TAtomic::TStringWithPrefix {
+                prefix: input_prefix,
+            } => {
+                return input_prefix.starts_with(container_prefix.as_str());
+            }
+            TAtomic::TLiteralString { value } => {
+                return value.starts_with(container_prefix.as_str());
+            }
+            TAtomic::TStringWithFlags(..) | TAtomic::TStringWithPattern { .. } => {
+                atomic_comparison_result.type_coerced = Some(true);
+                return false;
+            }
+            _ => {}
+        },
+        TAtomic::TStringWithPattern {
+            pattern: container_pattern,
+        } => match input_type_part {
+            TAtomic::TStringWithPattern {
+                pattern: input_pattern,
+            } => {
+                return input_pattern == container_pattern;
+            }
+            TAtomic::TLiteralString { .. }
+            | TAtomic::TStringWithFlags(..)
+            | TAtomic::TStringWithPrefix { .. } => {
+                atomic_comparison_result.type_coerced = Some(true);
+                return false;
+            }
             _ => {}
         },
         _ => {}
@@ -16,14 +16,27 @@ pub(crate) fn is_contained_by(
 
     if let TAtomic::TVec {
         known_items: Some(container_known_items),
+        is_tuple: container_is_tuple,
         ..
     } = container_type_part
     {
         if let TAtomic::TVec {
             known_items: Some(input_known_items),
+            type_param: input_type_param,
             ..
         } = input_type_part
         {
+            // tuples have a fixed arity, so can't accept anything with extra items
+            if *container_is_tuple
+                && (!input_type_param.is_nothing()
+                    || input_known_items
+                        .keys()
+                        .any(|key| !container_known_items.contains_key(key)))
+            {
+                all_types_contain = false;
+                obviously_bad = true;
+            }
+
             for (key, (c_u, container_property_type)) in container_known_items {
                 if let Some((i_u, input_property_type)) = input_known_items.get(key) {
                     if *i_u && !c_u {
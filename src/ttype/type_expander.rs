@@ -41,6 +41,8 @@ pub struct TypeExpansionOptions<'a> {
     pub expand_generic: bool,
     pub expand_templates: bool,
     pub expand_hakana_types: bool,
+    // when false, type aliases keep their names (with their type params expanded)
+    pub expand_type_aliases: bool,
 }
 
 impl Default for TypeExpansionOptions<'_> {
@@ -56,6 +58,7 @@ impl Default for TypeExpansionOptions<'_> {
             expand_generic: false,
             expand_templates: true,
             expand_hakana_types: true,
+            expand_type_aliases: true,
         }
     }
 }
@@ -298,7 +301,7 @@ fn expand_atomic(
             return;
         }
 
-        if can_expand_type {
+        if can_expand_type && options.expand_type_aliases {
             *skip_key = true;
 
             let mut untemplated_type = if let Some(type_params) = type_params {